@@ -22,6 +22,20 @@ skip.add('eventfd2')
 # `exit` has a non-trivial wrapper that calls `atexit` hooks, flushes open
 # `FILE*` objects, etc.
 skip.add('exit')
+# the sched_getaffinity syscall returns the size of the cpu mask, but libc
+# returns 0 and clears the rest of the caller's buffer; see libc_impls.c.
+skip.add('sched_getaffinity')
+# libc signalfd() takes different arguments than SYS_signalfd, and calls
+# SYS_signalfd4; see libc_impls.c. libc doesn't have a signalfd4() wrapper.
+skip.add('signalfd')
+skip.add('signalfd4')
+# libc's timer_t is a pointer-sized handle rather than the kernel's int timer
+# id; see libc_impls.c.
+skip.add('timer_create')
+skip.add('timer_delete')
+skip.add('timer_getoverrun')
+skip.add('timer_gettime')
+skip.add('timer_settime')
 
 # syscall wrappers that return errors directly instead of through errno.
 direct_errors = set()
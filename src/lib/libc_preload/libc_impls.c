@@ -13,10 +13,15 @@
 
 #include <assert.h>
 #include <errno.h>
+#include <limits.h>
 #include <netdb.h>
+#include <sched.h>
+#include <signal.h>
 #include <stdarg.h>
+#include <stdint.h>
 #include <stdlib.h>
 #include <string.h>
+#include <sys/signalfd.h>
 #include <sys/socket.h>
 #include <sys/stat.h>
 #include <sys/statfs.h>
@@ -62,6 +67,73 @@ ssize_t recv(int sockfd, void* buf, size_t len, int flags) {
     return recvfrom(sockfd, buf, len, flags, NULL, NULL);
 }
 
+// man 2 signalfd
+int signalfd(int fd, const sigset_t* mask, int flags) {
+    // Like libc, pass the size of the kernel's signal set rather than `sizeof(sigset_t)`.
+    return syscall(SYS_signalfd4, fd, mask, _NSIG / 8, flags);
+}
+
+// man 2 timer_create
+int timer_create(clockid_t clockid, struct sigevent* sevp, timer_t* timerid) {
+    if (sevp != NULL && sevp->sigev_notify == SIGEV_THREAD) {
+        // libc implements this with a helper thread waiting for a realtime signal, which we
+        // don't support.
+        errno = ENOTSUP;
+        return -1;
+    }
+
+    // Unlike libc's `timer_t`, the kernel's timer id is an int.
+    int ktimerid = 0;
+    int rv = syscall(SYS_timer_create, clockid, sevp, &ktimerid);
+    if (rv == 0) {
+        *timerid = (timer_t)(intptr_t)ktimerid;
+    }
+    return rv;
+}
+
+// man 2 timer_settime
+int timer_settime(timer_t timerid, int flags, const struct itimerspec* new_value,
+                  struct itimerspec* old_value) {
+    return syscall(SYS_timer_settime, (int)(intptr_t)timerid, flags, new_value, old_value);
+}
+
+// man 2 timer_gettime
+int timer_gettime(timer_t timerid, struct itimerspec* curr_value) {
+    return syscall(SYS_timer_gettime, (int)(intptr_t)timerid, curr_value);
+}
+
+// man 2 timer_getoverrun
+int timer_getoverrun(timer_t timerid) {
+    return syscall(SYS_timer_getoverrun, (int)(intptr_t)timerid);
+}
+
+// man 2 timer_delete
+int timer_delete(timer_t timerid) { return syscall(SYS_timer_delete, (int)(intptr_t)timerid); }
+
+// man 2 sched_getaffinity
+int sched_getaffinity(pid_t pid, size_t cpusetsize, cpu_set_t* mask) {
+    // The syscall returns the number of bytes it wrote. Like libc, clear the rest of the mask and
+    // return 0 instead.
+    size_t size = cpusetsize < INT_MAX ? cpusetsize : INT_MAX;
+    long rv = syscall(SYS_sched_getaffinity, pid, size, mask);
+    if (rv < 0) {
+        return rv;
+    }
+    memset((char*)mask + rv, 0, cpusetsize - rv);
+    return 0;
+}
+
+// man 3 sched_getcpu
+int sched_getcpu(void) {
+    // libc may use the vdso or the rseq area instead, which wouldn't return the simulated cpu.
+    unsigned int cpu = 0;
+    int rv = syscall(SYS_getcpu, &cpu, NULL, NULL);
+    if (rv < 0) {
+        return rv;
+    }
+    return cpu;
+}
+
 // man 3 usleep
 int usleep(useconds_t usec) {
     struct timespec req, rem;
@@ -749,9 +749,7 @@ INTERPOSE(sched_get_priority_max);
 #ifdef SYS_sched_get_priority_min // kernel entry: num=147 func=sys_sched_get_priority_min
 INTERPOSE(sched_get_priority_min);
 #endif
-#ifdef SYS_sched_getaffinity // kernel entry: num=204 func=sys_sched_getaffinity
-INTERPOSE(sched_getaffinity);
-#endif
+// Skipping SYS_sched_getaffinity
 #ifdef SYS_sched_getattr // kernel entry: num=315 func=sys_sched_getattr
 INTERPOSE(sched_getattr);
 #endif
@@ -899,12 +897,8 @@ INTERPOSE(shutdown);
 #ifdef SYS_sigaltstack // kernel entry: num=131 func=sys_sigaltstack
 INTERPOSE(sigaltstack);
 #endif
-#ifdef SYS_signalfd // kernel entry: num=282 func=sys_signalfd
-INTERPOSE(signalfd);
-#endif
-#ifdef SYS_signalfd4 // kernel entry: num=289 func=sys_signalfd4
-INTERPOSE(signalfd4);
-#endif
+// Skipping SYS_signalfd
+// Skipping SYS_signalfd4
 #ifdef SYS_socket // kernel entry: num=41 func=sys_socket
 INTERPOSE(socket);
 #endif
@@ -962,21 +956,11 @@ INTERPOSE(tgkill);
 #ifdef SYS_time // kernel entry: num=201 func=sys_time
 INTERPOSE(time);
 #endif
-#ifdef SYS_timer_create // kernel entry: num=222 func=sys_timer_create
-INTERPOSE(timer_create);
-#endif
-#ifdef SYS_timer_delete // kernel entry: num=226 func=sys_timer_delete
-INTERPOSE(timer_delete);
-#endif
-#ifdef SYS_timer_getoverrun // kernel entry: num=225 func=sys_timer_getoverrun
-INTERPOSE(timer_getoverrun);
-#endif
-#ifdef SYS_timer_gettime // kernel entry: num=224 func=sys_timer_gettime
-INTERPOSE(timer_gettime);
-#endif
-#ifdef SYS_timer_settime // kernel entry: num=223 func=sys_timer_settime
-INTERPOSE(timer_settime);
-#endif
+// Skipping SYS_timer_create
+// Skipping SYS_timer_delete
+// Skipping SYS_timer_getoverrun
+// Skipping SYS_timer_gettime
+// Skipping SYS_timer_settime
 #ifdef SYS_timerfd_create // kernel entry: num=283 func=sys_timerfd_create
 INTERPOSE(timerfd_create);
 #endif
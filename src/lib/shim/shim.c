@@ -84,6 +84,9 @@ static shadow_spinlock_t _startThreadLock = SHADOW_SPINLOCK_STATICALLY_INITD;
 static struct {
     ShMemBlock childIpcBlk;
     shadow_sem_t childInitd;
+    // Only used when starting a new process.
+    ShMemBlock childProcessShmBlk;
+    ShMemBlock childThreadShmBlk;
 } _startThread;
 
 void shim_newThreadStart(ShMemBlockSerialized* block) {
@@ -119,6 +122,43 @@ void shim_newThreadFinish() {
     }
 }
 
+void shim_newProcessStart(const ShMemBlockSerialized* ipcBlock,
+                          const ShMemBlockSerialized* processShmBlock,
+                          const ShMemBlockSerialized* threadShmBlock) {
+    if (shadow_spin_lock(&_startThreadLock)) {
+        panic("shadow_spin_lock: %s", strerror(errno));
+    };
+    _startThread.childIpcBlk = shmemserializer_globalBlockDeserialize(ipcBlock);
+    _startThread.childProcessShmBlk = shmemserializer_globalBlockDeserialize(processShmBlock);
+    _startThread.childThreadShmBlk = shmemserializer_globalBlockDeserialize(threadShmBlock);
+}
+
+void shim_newProcessFinish() {
+    // Unlike a new thread, the child process initializes its own *copy* of our
+    // state, so there's nothing to wait for.
+    if (shadow_spin_unlock(&_startThreadLock)) {
+        panic("shadow_spin_unlock: %s", strerror(errno));
+    }
+}
+
+void shim_newProcessChildInit() {
+    *_shim_ipcDataBlk() = _startThread.childIpcBlk;
+    *_shim_process_shared_mem_blk() = _startThread.childProcessShmBlk;
+    *_shim_thread_shared_mem_blk() = _startThread.childThreadShmBlk;
+
+    // The parent-death signal isn't inherited across fork.
+    long rv = shim_native_syscall(SYS_prctl, PR_SET_PDEATHSIG, SIGKILL);
+    if (rv < 0) {
+        warning("prctl: %s", strerror(-rv));
+    }
+
+    // Release our copy of the lock, which the parent was holding when it
+    // created us.
+    if (shadow_spin_unlock(&_startThreadLock)) {
+        panic("shadow_spin_unlock: %s", strerror(errno));
+    }
+}
+
 bool shim_swapAllowNativeSyscalls(bool new) {
     bool old = *_shim_allowNativeSyscallsFlag();
     *_shim_allowNativeSyscallsFlag() = new;
@@ -37,6 +37,20 @@ void shim_newThreadFinish();
 // the parent thread that it is now initialized.
 void shim_newThreadChildInitd();
 
+// To be called in parent thread before making a `clone` syscall that creates a
+// new process (e.g. to emulate `fork`). It sets up data for the new process.
+void shim_newProcessStart(const ShMemBlockSerialized* ipcBlock,
+                          const ShMemBlockSerialized* processShmBlock,
+                          const ShMemBlockSerialized* threadShmBlock);
+
+// To be called in parent thread after making a `clone` syscall that creates a
+// new process.
+void shim_newProcessFinish();
+
+// To be called from the new process after a `clone` syscall that created it.
+// Switches to the IPC and shared memory blocks set up by `shim_newProcessStart`.
+void shim_newProcessChildInit();
+
 // Signal stack size parameters defined here because this is a significant
 // portion of the memory that needs to be statically allocated in shim_tls.c.
 //
@@ -9,7 +9,7 @@
 #include "main/host/syscall_types.h"
 
 typedef enum {
-    // Next val: 13
+    // Next val: 14
     SHD_SHIM_EVENT_NULL = 0,
     SHD_SHIM_EVENT_START = 1,
     SHD_SHIM_EVENT_STOP = 2,
@@ -23,6 +23,7 @@ typedef enum {
     SHD_SHIM_EVENT_BLOCK = 10,
     SHD_SHIM_EVENT_ADD_THREAD_REQ = 11,
     SHD_SHIM_EVENT_ADD_THREAD_PARENT_RES = 12,
+    SHD_SHIM_EVENT_ADD_PROCESS_REQ = 13,
 } ShimEventID;
 
 typedef struct _ShimEvent {
@@ -52,6 +53,14 @@ typedef struct _ShimEvent {
         struct {
             ShMemBlockSerialized ipc_block;
         } add_thread_req;
+
+        struct {
+            // Blocks to be used by the child of a `fork`, replacing those
+            // inherited from the parent.
+            ShMemBlockSerialized ipc_block;
+            ShMemBlockSerialized process_shm_block;
+            ShMemBlockSerialized thread_shm_block;
+        } add_process_req;
     } event_data;
 
 } ShimEvent;
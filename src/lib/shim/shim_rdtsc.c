@@ -31,8 +31,9 @@ static uint64_t _shim_rdtsc_nanos(bool allowNative) {
     struct timespec t = {0};
     // *don't* directly call shim_sys_get_simtime_nanos() here.  We need to go
     // through the syscall code to correctly handle the case where
-    // `model_unblocked_syscall_latency` is enabled.
-    long rv = shim_emulated_syscall(SYS_clock_gettime, CLOCK_REALTIME, &t);
+    // `model_unblocked_syscall_latency` is enabled. We use the monotonic clock
+    // since the host's realtime clock can drift and be set.
+    long rv = shim_emulated_syscall(SYS_clock_gettime, CLOCK_MONOTONIC, &t);
     if (rv != 0) {
         panic("emulated SYS_clock_gettime: %s", strerror(-rv));
     }
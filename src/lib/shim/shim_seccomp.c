@@ -8,6 +8,7 @@
 #include <inttypes.h>
 #include <linux/filter.h>
 #include <linux/seccomp.h>
+#include <sched.h>
 #include <signal.h>
 #include <string.h>
 #include <sys/prctl.h>
@@ -63,6 +64,11 @@ static void _shim_seccomp_handle_sigsys(int sig, siginfo_t* info, void* voidUcon
     long rv = shim_syscall(regs[REG_N], regs[REG_ARG1], regs[REG_ARG2], regs[REG_ARG3],
                            regs[REG_ARG4], regs[REG_ARG5], regs[REG_ARG6]);
     trace("Trapped syscall %lld returning %ld", ctx->uc_mcontext.gregs[REG_RAX], rv);
+    if (regs[REG_N] == SYS_clone && rv == 0 && !(regs[REG_ARG1] & CLONE_VM) && regs[REG_ARG2]) {
+        // We're a new child process. Shadow created us on the shim's current
+        // stack; switch to the stack that was requested for the child.
+        regs[REG_RSP] = regs[REG_ARG2];
+    }
     ctx->uc_mcontext.gregs[REG_RAX] = rv;
 }
 
@@ -17,9 +17,28 @@ struct _ShimHostProtectedSharedMem {
     // Modeled CPU latency that hasn't been applied to the clock yet.
     SimulationTime unapplied_cpu_latency;
 
+    // All modeled CPU latency, whether or not it's been applied to the clock.
+    // Never reset; used to measure the CPU time consumed by processes.
+    SimulationTime total_cpu_latency;
+
     // Max simulation time to which sim_time may be incremented.  Moving time
     // beyond this value requires the current thread to be rescheduled.
     EmulatedTime max_runahead_time;
+
+    // The host's realtime clock has value `realtime_base` at emulated time
+    // `realtime_base_time`, and from then on advances `realtime_drift_ppm +
+    // realtime_frequency_ppm` parts per million faster than emulated time. The
+    // drift is fixed, and the frequency is adjusted by the host's processes.
+    EmulatedTime realtime_base_time;
+    EmulatedTime realtime_base;
+    double realtime_drift_ppm;
+    double realtime_frequency_ppm;
+
+    // The clock is also being slewed by `realtime_slew` nanoseconds, as set by
+    // `adjtime`. The slew starts at `realtime_base_time` and is applied at
+    // `REALTIME_SLEW_PPM` parts per million of emulated time, after which the
+    // clock continues at its normal rate.
+    int64_t realtime_slew;
 };
 
 struct _ShimShmemHost {
@@ -121,7 +140,9 @@ size_t shimshmemhost_size() { return sizeof(ShimShmemHost); }
 void shimshmemhost_init(ShimShmemHost* hostMem, Host* host, bool modelUnblockedSyscallLatency,
                         SimulationTime maxUnappliedCpuLatency,
                         SimulationTime unblockedSyscallLatency,
-                        SimulationTime unblockedVdsoLatency) {
+                        SimulationTime unblockedVdsoLatency, EmulatedTime realtimeStart,
+                        double realtimeDriftPpm) {
+    assert(realtimeStart != EMUTIME_INVALID);
     assert(hostMem);
     // We use `memcpy` instead of struct assignment here to allow us to
     // initialize the const members of `hostMem`.
@@ -136,6 +157,9 @@ void shimshmemhost_init(ShimShmemHost* hostMem, Host* host, bool modelUnblockedS
                .protected =
                    {
                        .host_id = host_getID(host),
+                       .realtime_base_time = EMUTIME_SIMULATION_START,
+                       .realtime_base = realtimeStart,
+                       .realtime_drift_ppm = realtimeDriftPpm,
                    },
            },
            sizeof(ShimShmemHost));
@@ -149,6 +173,7 @@ void shimshmemhost_destroy(ShimShmemHost* hostMem) {
 void shimshmem_incrementUnappliedCpuLatency(ShimShmemHostLock* host, SimulationTime dt) {
     assert(host);
     host->unapplied_cpu_latency += dt;
+    host->total_cpu_latency += dt;
 }
 
 SimulationTime shimshmem_getUnappliedCpuLatency(ShimShmemHostLock* host) {
@@ -156,6 +181,132 @@ SimulationTime shimshmem_getUnappliedCpuLatency(ShimShmemHostLock* host) {
     return host->unapplied_cpu_latency;
 }
 
+// The rate at which the realtime clock is slewed, in parts per million of
+// emulated time. Like linux's `MAX_TICKADJ`.
+#define REALTIME_SLEW_PPM 500.0L
+
+// The rate of the host's realtime clock relative to emulated time.
+static long double _shimshmem_realtimeRate(const ShimShmemHostLock* host) {
+    long double ppm = (long double)host->realtime_drift_ppm + host->realtime_frequency_ppm;
+    // the clock must always move forward
+    assert(ppm > -1000000.0L);
+    return 1.0L + ppm / 1000000.0L;
+}
+
+// Round `t` down to an EmulatedTime, saturating at the unix epoch and at
+// EMUTIME_MAX. A long double has enough precision to represent every
+// EmulatedTime exactly, so there is no rounding error when there is no drift.
+static EmulatedTime _shimshmem_toEmulatedTime(long double t) {
+    if (t <= (long double)EMUTIME_UNIX_EPOCH) {
+        return EMUTIME_UNIX_EPOCH;
+    }
+    if (t >= (long double)EMUTIME_MAX) {
+        return EMUTIME_MAX;
+    }
+    return (EmulatedTime)t;
+}
+
+// The emulated time it takes to apply the clock's entire slew.
+static long double _shimshmem_realtimeSlewDuration(const ShimShmemHostLock* host) {
+    long double slew = host->realtime_slew;
+    return (slew < 0 ? -slew : slew) * 1000000.0L / REALTIME_SLEW_PPM;
+}
+
+// The part of the clock's slew that has been applied `elapsed` nanoseconds of
+// emulated time after `realtime_base_time`.
+static long double _shimshmem_appliedRealtimeSlew(const ShimShmemHostLock* host,
+                                                  long double elapsed) {
+    long double duration = _shimshmem_realtimeSlewDuration(host);
+    if (elapsed <= 0) {
+        return 0;
+    }
+    if (elapsed >= duration) {
+        return host->realtime_slew;
+    }
+    return host->realtime_slew * (elapsed / duration);
+}
+
+EmulatedTime shimshmem_getRealtime(const ShimShmemHostLock* host, EmulatedTime t) {
+    assert(host);
+    long double elapsed = (long double)t - (long double)host->realtime_base_time;
+    return _shimshmem_toEmulatedTime((long double)host->realtime_base +
+                                     elapsed * _shimshmem_realtimeRate(host) +
+                                     _shimshmem_appliedRealtimeSlew(host, elapsed));
+}
+
+EmulatedTime shimshmem_getEmulatedTimeForRealtime(const ShimShmemHostLock* host,
+                                                  EmulatedTime realtime) {
+    assert(host);
+    long double rate = _shimshmem_realtimeRate(host);
+    long double duration = _shimshmem_realtimeSlewDuration(host);
+    // the value of the clock when the slew finishes
+    long double slewEnd =
+        (long double)host->realtime_base + duration * rate + host->realtime_slew;
+
+    long double elapsed;
+    if ((long double)realtime < (long double)host->realtime_base) {
+        elapsed = ((long double)realtime - (long double)host->realtime_base) / rate;
+    } else if ((long double)realtime >= slewEnd) {
+        elapsed = duration + ((long double)realtime - slewEnd) / rate;
+    } else {
+        // the clock is still being slewed
+        long double slewRate = REALTIME_SLEW_PPM / 1000000.0L;
+        if (host->realtime_slew < 0) {
+            slewRate = -slewRate;
+        }
+        elapsed = ((long double)realtime - (long double)host->realtime_base) / (rate + slewRate);
+    }
+
+    return _shimshmem_toEmulatedTime((long double)host->realtime_base_time + elapsed);
+}
+
+// Rebase the clock so that it has its current value at emulated time `t`, and
+// so that any remaining slew starts at `t`.
+static void _shimshmem_rebaseRealtime(ShimShmemHostLock* host, EmulatedTime t) {
+    int64_t slew = shimshmem_getRealtimeSlew(host, t);
+    host->realtime_base = shimshmem_getRealtime(host, t);
+    host->realtime_base_time = t;
+    host->realtime_slew = slew;
+}
+
+void shimshmem_setRealtime(ShimShmemHostLock* host, EmulatedTime t, EmulatedTime realtime) {
+    assert(host);
+    host->realtime_base_time = t;
+    host->realtime_base = realtime;
+    // like linux, setting the clock cancels any slew
+    host->realtime_slew = 0;
+}
+
+double shimshmem_getRealtimeFrequencyPpm(const ShimShmemHostLock* host) {
+    assert(host);
+    return host->realtime_frequency_ppm;
+}
+
+void shimshmem_setRealtimeFrequencyPpm(ShimShmemHostLock* host, EmulatedTime t,
+                                       double frequencyPpm) {
+    assert(host);
+    // rebase the clock so that the new rate only applies from `t`
+    _shimshmem_rebaseRealtime(host, t);
+    host->realtime_frequency_ppm = frequencyPpm;
+}
+
+int64_t shimshmem_getRealtimeSlew(const ShimShmemHostLock* host, EmulatedTime t) {
+    assert(host);
+    long double elapsed = (long double)t - (long double)host->realtime_base_time;
+    return host->realtime_slew - (int64_t)_shimshmem_appliedRealtimeSlew(host, elapsed);
+}
+
+void shimshmem_setRealtimeSlew(ShimShmemHostLock* host, EmulatedTime t, int64_t slew) {
+    assert(host);
+    _shimshmem_rebaseRealtime(host, t);
+    host->realtime_slew = slew;
+}
+
+SimulationTime shimshmem_getTotalCpuLatency(ShimShmemHostLock* host) {
+    assert(host);
+    return host->total_cpu_latency;
+}
+
 bool shimshmem_getModelUnblockedSyscallLatency(ShimShmemHost* host) {
     assert(host);
     return host->model_unblocked_syscall_latency;
@@ -370,14 +521,13 @@ void shimshmemhost_unlock(ShimShmemHost* host, ShimShmemHostLock** protected) {
     }
 }
 
-static int _shimshmem_takePendingUnblockedThreadSignal(const ShimShmemHostLock* lock,
-                                                       shd_kernel_sigset_t unblockedSignals,
-                                                       ShimShmemThread* thread, siginfo_t* info) {
+static int _shimshmem_takePendingThreadSignal(const ShimShmemHostLock* lock,
+                                              shd_kernel_sigset_t set, ShimShmemThread* thread,
+                                              siginfo_t* info) {
     shd_kernel_sigset_t thread_pending_signals = shimshmem_getThreadPendingSignals(lock, thread);
-    shd_kernel_sigset_t thread_pending_unblocked_signals =
-        shd_sigandset(&thread_pending_signals, &unblockedSignals);
-    if (!shd_sigisemptyset(&thread_pending_unblocked_signals)) {
-        int signo = shd_siglowest(&thread_pending_signals);
+    shd_kernel_sigset_t thread_pending_set_signals = shd_sigandset(&thread_pending_signals, &set);
+    if (!shd_sigisemptyset(&thread_pending_set_signals)) {
+        int signo = shd_siglowest(&thread_pending_set_signals);
         if (info) {
             *info = shimshmem_getThreadSiginfo(lock, thread, signo);
         }
@@ -388,15 +538,14 @@ static int _shimshmem_takePendingUnblockedThreadSignal(const ShimShmemHostLock*
     return 0;
 }
 
-static int _shimshmem_takePendingUnblockedProcessSignal(const ShimShmemHostLock* lock,
-                                                        shd_kernel_sigset_t unblockedSignals,
-                                                        ShimShmemProcess* process,
-                                                        siginfo_t* info) {
+static int _shimshmem_takePendingProcessSignal(const ShimShmemHostLock* lock,
+                                               shd_kernel_sigset_t set, ShimShmemProcess* process,
+                                               siginfo_t* info) {
     shd_kernel_sigset_t process_pending_signals = shimshmem_getProcessPendingSignals(lock, process);
-    shd_kernel_sigset_t process_pending_unblocked_signals =
-        shd_sigandset(&process_pending_signals, &unblockedSignals);
-    if (!shd_sigisemptyset(&process_pending_unblocked_signals)) {
-        int signo = shd_siglowest(&process_pending_signals);
+    shd_kernel_sigset_t process_pending_set_signals =
+        shd_sigandset(&process_pending_signals, &set);
+    if (!shd_sigisemptyset(&process_pending_set_signals)) {
+        int signo = shd_siglowest(&process_pending_set_signals);
         if (info) {
             *info = shimshmem_getProcessSiginfo(lock, process, signo);
         }
@@ -407,6 +556,17 @@ static int _shimshmem_takePendingUnblockedProcessSignal(const ShimShmemHostLock*
     return 0;
 }
 
+int shimshmem_takePendingSignal(const ShimShmemHostLock* lock, ShimShmemProcess* process,
+                                ShimShmemThread* thread, shd_kernel_sigset_t set,
+                                siginfo_t* info) {
+    int signo = _shimshmem_takePendingThreadSignal(lock, set, thread, info);
+    if (signo != 0) {
+        return signo;
+    }
+
+    return _shimshmem_takePendingProcessSignal(lock, set, process, info);
+}
+
 int shimshmem_takePendingUnblockedSignal(const ShimShmemHostLock* lock, ShimShmemProcess* process,
                                          ShimShmemThread* thread, siginfo_t* info) {
     shd_kernel_sigset_t unblocked_signals;
@@ -415,12 +575,7 @@ int shimshmem_takePendingUnblockedSignal(const ShimShmemHostLock* lock, ShimShme
         unblocked_signals = shd_signotset(&blocked_signals);
     }
 
-    int signo = _shimshmem_takePendingUnblockedThreadSignal(lock, unblocked_signals, thread, info);
-    if (signo != 0) {
-        return signo;
-    }
-
-    return _shimshmem_takePendingUnblockedProcessSignal(lock, unblocked_signals, process, info);
+    return shimshmem_takePendingSignal(lock, process, thread, unblocked_signals, info);
 }
 
 void shim_shmemHandleClone(const ShimEvent* ev) {
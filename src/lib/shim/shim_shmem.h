@@ -40,7 +40,8 @@ size_t shimshmemhost_size();
 void shimshmemhost_init(ShimShmemHost* hostMem, Host* host, bool modelUnblockedSyscallLatency,
                         SimulationTime maxUnappliedCpuLatency,
                         SimulationTime unblockedSyscallLatency,
-                        SimulationTime unblockedVdsoLatency);
+                        SimulationTime unblockedVdsoLatency, EmulatedTime realtimeStart,
+                        double realtimeDriftPpm);
 void shimshmemhost_destroy(ShimShmemHost* hostMem);
 
 ShimShmemHostLock* shimshmemhost_lock(ShimShmemHost* host);
@@ -109,6 +110,14 @@ stack_t shimshmem_getSigAltStack(const ShimShmemHostLock* host, const ShimShmemT
 void shimshmem_setSigAltStack(const ShimShmemHostLock* host, ShimShmemThread* thread,
                               stack_t stack);
 
+// Takes a pending signal in `set` (at the thread or process level) and marks it
+// no longer pending. Sets `info` if non-NULL.
+//
+// Returns 0 if no signal in `set` is pending.
+int shimshmem_takePendingSignal(const ShimShmemHostLock* lock, ShimShmemProcess* process,
+                                ShimShmemThread* thread, shd_kernel_sigset_t set,
+                                siginfo_t* info);
+
 // Takes a pending unblocked signal (at the thread or process level) and marks it
 // no longer pending. Sets `info` if non-NULL.
 //
@@ -121,6 +130,34 @@ void shimshmem_incrementUnappliedCpuLatency(ShimShmemHostLock* host, SimulationT
 SimulationTime shimshmem_getUnappliedCpuLatency(ShimShmemHostLock* host);
 void shimshmem_resetUnappliedCpuLatency(ShimShmemHostLock* host);
 
+// Get the value of the host's realtime clock (CLOCK_REALTIME) at emulated time `t`.
+EmulatedTime shimshmem_getRealtime(const ShimShmemHostLock* host, EmulatedTime t);
+
+// Get the emulated time at which the host's realtime clock has value `realtime`.
+EmulatedTime shimshmem_getEmulatedTimeForRealtime(const ShimShmemHostLock* host,
+                                                  EmulatedTime realtime);
+
+// Set the host's realtime clock to `realtime` at emulated time `t`. This
+// cancels any remaining slew.
+void shimshmem_setRealtime(ShimShmemHostLock* host, EmulatedTime t, EmulatedTime realtime);
+
+// Get and set the frequency adjustment in parts per million of the host's
+// realtime clock, as set by `adjtimex`. This is in addition to the clock's
+// configured drift. The new frequency applies from emulated time `t`.
+double shimshmem_getRealtimeFrequencyPpm(const ShimShmemHostLock* host);
+void shimshmem_setRealtimeFrequencyPpm(ShimShmemHostLock* host, EmulatedTime t,
+                                       double frequencyPpm);
+
+// Get and set the number of nanoseconds by which the host's realtime clock is
+// still to be gradually slewed at emulated time `t`, as set by `adjtime`. A new
+// slew replaces any remaining slew and starts at `t`.
+int64_t shimshmem_getRealtimeSlew(const ShimShmemHostLock* host, EmulatedTime t);
+void shimshmem_setRealtimeSlew(ShimShmemHostLock* host, EmulatedTime t, int64_t slew);
+
+// Get the total CPU latency modeled on the host. Unlike the unapplied CPU
+// latency, this is never reset.
+SimulationTime shimshmem_getTotalCpuLatency(ShimShmemHostLock* host);
+
 // Get whether to model latency of unblocked syscalls.
 bool shimshmem_getModelUnblockedSyscallLatency(ShimShmemHost* host);
 
@@ -28,6 +28,25 @@ static EmulatedTime _shim_sys_get_time() {
     return shimshmem_getEmulatedTime(mem);
 }
 
+// The host's realtime clock may be offset from the emulated time and drift from it.
+static EmulatedTime _shim_sys_get_realtime(EmulatedTime emulated_time) {
+    ShimShmemHost* mem = shim_hostSharedMem();
+    ShimShmemHostLock* host_lock = shimshmemhost_lock(mem);
+    EmulatedTime realtime = shimshmem_getRealtime(host_lock, emulated_time);
+    shimshmemhost_unlock(mem, &host_lock);
+    return realtime;
+}
+
+static bool _shim_sys_is_realtime_clock(clockid_t clk_id) {
+    switch (clk_id) {
+        case CLOCK_REALTIME:
+        case CLOCK_REALTIME_ALARM:
+        case CLOCK_REALTIME_COARSE:
+        case CLOCK_TAI: return true;
+        default: return false;
+    }
+}
+
 uint64_t shim_sys_get_simtime_nanos() { return _shim_sys_get_time() / SIMTIME_ONE_NANOSECOND; }
 
 static SimulationTime _shim_sys_latency_for_syscall(long n) {
@@ -67,6 +86,10 @@ bool shim_sys_handle_syscall_locally(long syscall_num, long* rv, va_list args) {
             clockid_t clk_id = va_arg(args, clockid_t);
             struct timespec* tp = va_arg(args, struct timespec*);
 
+            if (_shim_sys_is_realtime_clock(clk_id)) {
+                emulated_time = _shim_sys_get_realtime(emulated_time);
+            }
+
             if (tp) {
                 *tp = (struct timespec){
                     .tv_sec = emulated_time / SIMTIME_ONE_SECOND,
@@ -88,7 +111,7 @@ bool shim_sys_handle_syscall_locally(long syscall_num, long* rv, va_list args) {
                 // Not initialized yet.
                 return false;
             }
-            time_t now = emulated_time / SIMTIME_ONE_SECOND;
+            time_t now = _shim_sys_get_realtime(emulated_time) / SIMTIME_ONE_SECOND;
 
             trace("servicing syscall %ld:time from the shim", syscall_num);
 
@@ -109,7 +132,7 @@ bool shim_sys_handle_syscall_locally(long syscall_num, long* rv, va_list args) {
                 // Not initialized yet.
                 return false;
             }
-            uint64_t micros = emulated_time / SIMTIME_ONE_MICROSECOND;
+            uint64_t micros = _shim_sys_get_realtime(emulated_time) / SIMTIME_ONE_MICROSECOND;
 
             trace("servicing syscall %ld:gettimeofday from the shim", syscall_num);
 
@@ -1,6 +1,7 @@
 #include <alloca.h>
 #include <assert.h>
 #include <errno.h>
+#include <sched.h>
 #include <stdlib.h>
 #include <sys/syscall.h>
 
@@ -36,8 +37,12 @@ long __attribute__((noinline)) shim_native_syscallv(long n, va_list args) {
     // TODO: it'd be cleaner for this to be a separate, dedicated, function.
     // However right now the actual clone syscall instruction *must* be executed
     // from this function to pass the seccomp filter.
+    //
+    // A clone that creates a new process (i.e. a `fork`) doesn't need this;
+    // the child has a copy of our stack and returns normally.
     void* clone_rip = NULL;
-    if (n == SYS_clone && (clone_rip = shim_seccomp_take_clone_rip()) != NULL) {
+    if (n == SYS_clone && (clone_rip = shim_seccomp_take_clone_rip()) != NULL &&
+        (arg1 & CLONE_VM)) {
         // Make the clone syscall, and then in the child thread initialize the shim's state,
         // and then *jump* to the instruction after the original clone syscall instruction.
         //
@@ -189,6 +194,20 @@ static SysCallReg _shim_emulated_syscall_event(const ShimEvent* syscall_event) {
                 long syscall_rv = shim_native_syscall(
                     res.event_data.syscall.syscall_args.number, regs[0].as_u64, regs[1].as_u64,
                     regs[2].as_u64, regs[3].as_u64, regs[4].as_u64, regs[5].as_u64);
+                if (res.event_data.syscall.syscall_args.number == SYS_clone &&
+                    !(regs[0].as_u64 & CLONE_VM)) {
+                    if (syscall_rv != 0) {
+                        shim_newProcessFinish();
+                    } else {
+                        // We're the child process. Switch to our own IPC block, and wait
+                        // for Shadow to schedule us, which will complete the syscall
+                        // that created us.
+                        shim_newProcessChildInit();
+                        ipc = shim_thisThreadEventIPC();
+                        spin = false;
+                        break;
+                    }
+                }
                 ShimEvent syscall_complete_event = {
                     .event_id = SHD_SHIM_EVENT_SYSCALL_COMPLETE,
                     .event_data.syscall_complete.retval.as_i64 = syscall_rv,
@@ -216,6 +235,16 @@ static SysCallReg _shim_emulated_syscall_event(const ShimEvent* syscall_event) {
                          });
                 break;
             }
+            case SHD_SHIM_EVENT_ADD_PROCESS_REQ: {
+                shim_newProcessStart(&res.event_data.add_process_req.ipc_block,
+                                     &res.event_data.add_process_req.process_shm_block,
+                                     &res.event_data.add_process_req.thread_shm_block);
+                shimevent_sendEventToShadow(
+                    ipc, &(ShimEvent){
+                             .event_id = SHD_SHIM_EVENT_ADD_THREAD_PARENT_RES,
+                         });
+                break;
+            }
             default: {
                 panic("Got unexpected event %d", res.event_id);
                 abort();
@@ -21,11 +21,19 @@ typedef enum StraceFmtMode {
   STRACE_FMT_MODE_DETERMINISTIC,
 } StraceFmtMode;
 
+typedef enum TcpCongestionAlgorithm {
+  TCP_CONGESTION_ALGORITHM_RENO,
+  TCP_CONGESTION_ALGORITHM_CUBIC,
+  TCP_CONGESTION_ALGORITHM_BBR,
+} TcpCongestionAlgorithm;
+
 // Memory allocated by Shadow, in a remote address space.
 typedef struct AllocdMem_u8 AllocdMem_u8;
 
 typedef struct Arc_AtomicRefCell_AbstractUnixNamespace Arc_AtomicRefCell_AbstractUnixNamespace;
 
+typedef struct Arc_AtomicRefCell_PathnameUnixNamespace Arc_AtomicRefCell_PathnameUnixNamespace;
+
 // A queue of bytes that supports reading and writing stream and/or packet data.
 //
 // Both stream and packet data can be pushed onto the buffer and their order will be preserved.
@@ -53,6 +61,21 @@ typedef struct Controller Controller;
 // The main counter object that maps individual keys to count values.
 typedef struct Counter Counter;
 
+// The set of simulated CPUs that a thread is allowed to run on. This backs `sched_getaffinity` and
+// `sched_setaffinity`.
+//
+// The mask uses the same layout as the kernel's cpumask: an array of `unsigned long` where bit `i`
+// of word `j` represents CPU `j * BITS_PER_LONG + i`.
+typedef struct CpuAffinity CpuAffinity;
+
+// A timer that counts down the modeled CPU time consumed by a process, rather than the emulated
+// time. This backs `ITIMER_VIRTUAL` and `ITIMER_PROF`.
+//
+// Unlike [`Timer`](crate::host::timer::Timer), a `CpuTimer` doesn't schedule any events. It only
+// advances when the owner charges it with CPU time using [`CpuTimer::consume`], and the owner is
+// responsible for reporting any expirations.
+typedef struct CpuTimer CpuTimer;
+
 // A file descriptor that reference an open file. Also contains flags that change the behaviour of
 // this file descriptor.
 typedef struct Descriptor Descriptor;
@@ -63,6 +86,8 @@ typedef struct DescriptorTable DescriptorTable;
 // A wrapper for any type of file object.
 typedef struct File File;
 
+typedef struct InetSocket InetSocket;
+
 // Tool for assigning IP addresses to graph nodes.
 typedef struct IpAssignment_u32 IpAssignment_u32;
 
@@ -94,6 +119,9 @@ typedef struct OpenFile OpenFile;
 
 typedef struct PcapWriter_BufWriter_File PcapWriter_BufWriter_File;
 
+// The POSIX timers of a process.
+typedef struct PosixTimerTable PosixTimerTable;
+
 // A mutable reference to a slice of plugin memory. Implements `DerefMut<[T]>`,
 // allowing, e.g.:
 //
@@ -124,6 +152,10 @@ typedef struct Random Random;
 // Routing information for paths between nodes.
 typedef struct RoutingInfo_u32 RoutingInfo_u32;
 
+// A specified event source that passes a state and the changed bits to the function, but only if
+// the monitored bits have changed and if the change the filter is satisfied.
+typedef struct StateEventSource StateEventSource;
+
 typedef struct SyscallHandler SyscallHandler;
 
 // Mostly for interoperability with C APIs.
@@ -131,6 +163,9 @@ typedef struct SyscallHandler SyscallHandler;
 // to directly use a `Fn(&mut Host)` trait object.
 typedef struct TaskRef TaskRef;
 
+// A congestion control object that can be owned by C code.
+typedef struct TcpCongestionControl TcpCongestionControl;
+
 typedef struct Timer Timer;
 
 typedef struct TokenBucket TokenBucket;
@@ -29,6 +29,8 @@ typedef struct AllocdMem_u8 AllocdMem_u8;
 
 typedef struct Arc_AtomicRefCell_AbstractUnixNamespace Arc_AtomicRefCell_AbstractUnixNamespace;
 
+typedef struct Arc_AtomicRefCell_PathnameUnixNamespace Arc_AtomicRefCell_PathnameUnixNamespace;
+
 // A queue of bytes that supports reading and writing stream and/or packet data.
 //
 // Both stream and packet data can be pushed onto the buffer and their order will be preserved.
@@ -56,6 +58,21 @@ typedef struct Controller Controller;
 // The main counter object that maps individual keys to count values.
 typedef struct Counter Counter;
 
+// The set of simulated CPUs that a thread is allowed to run on. This backs `sched_getaffinity` and
+// `sched_setaffinity`.
+//
+// The mask uses the same layout as the kernel's cpumask: an array of `unsigned long` where bit `i`
+// of word `j` represents CPU `j * BITS_PER_LONG + i`.
+typedef struct CpuAffinity CpuAffinity;
+
+// A timer that counts down the modeled CPU time consumed by a process, rather than the emulated
+// time. This backs `ITIMER_VIRTUAL` and `ITIMER_PROF`.
+//
+// Unlike [`Timer`](crate::host::timer::Timer), a `CpuTimer` doesn't schedule any events. It only
+// advances when the owner charges it with CPU time using [`CpuTimer::consume`], and the owner is
+// responsible for reporting any expirations.
+typedef struct CpuTimer CpuTimer;
+
 // A file descriptor that reference an open file. Also contains flags that change the behaviour of
 // this file descriptor.
 typedef struct Descriptor Descriptor;
@@ -66,6 +83,8 @@ typedef struct DescriptorTable DescriptorTable;
 // A wrapper for any type of file object.
 typedef struct File File;
 
+typedef struct InetSocket InetSocket;
+
 // Tool for assigning IP addresses to graph nodes.
 typedef struct IpAssignment_u32 IpAssignment_u32;
 
@@ -97,6 +116,9 @@ typedef struct OpenFile OpenFile;
 
 typedef struct PcapWriter_BufWriter_File PcapWriter_BufWriter_File;
 
+// The POSIX timers of a process.
+typedef struct PosixTimerTable PosixTimerTable;
+
 // A mutable reference to a slice of plugin memory. Implements `DerefMut<[T]>`,
 // allowing, e.g.:
 //
@@ -134,6 +156,9 @@ typedef struct SyscallHandler SyscallHandler;
 // to directly use a `Fn(&mut Host)` trait object.
 typedef struct TaskRef TaskRef;
 
+// A congestion control object that can be owned by C code.
+typedef struct TcpCongestionControl TcpCongestionControl;
+
 typedef struct Timer Timer;
 
 typedef struct TokenBucket TokenBucket;
@@ -203,6 +228,9 @@ int32_t childpidwatcher_forkWatchable(const struct ChildPidWatcher *watcher,
 //
 // Be sure to close the parent's write-end of the pipe.
 //
+// Alternatively `read_fd` may be a pidfd for `pid` (see `pidfd_open(2)`), which
+// becomes readable when `pid` exits.
+//
 // Takes ownership of `read_fd`, and will close it when appropriate.
 void childpidwatcher_registerPid(const struct ChildPidWatcher *watcher,
                                  int32_t pid,
@@ -294,6 +322,11 @@ SimulationTime controller_getLatency(const struct Controller *controller,
                                      in_addr_t src,
                                      in_addr_t dst);
 
+// Get the maximum additional per-packet delay for packets sent from one host to another.
+SimulationTime controller_getJitter(const struct Controller *controller,
+                                    in_addr_t src,
+                                    in_addr_t dst);
+
 float controller_getReliability(const struct Controller *controller, in_addr_t src, in_addr_t dst);
 
 uint64_t controller_getBandwidthDownBytes(const struct Controller *controller, in_addr_t ip);
@@ -566,14 +599,37 @@ void worker_addToGlobalAllocCounters(struct Counter *alloc_counter,
 // Add the counters to their global counterparts, and clear the provided counters.
 void worker_addToGlobalSyscallCounter(struct Counter *syscall_counter);
 
+// Create a new mask containing all `cpu_count` CPUs. Must be freed using `cpuaffinity_free`.
+struct CpuAffinity *cpuaffinity_new(uint32_t cpu_count);
+
+// Create a copy of the mask. Must be freed using `cpuaffinity_free`.
+struct CpuAffinity *cpuaffinity_clone(const struct CpuAffinity *affinity);
+
+void cpuaffinity_free(struct CpuAffinity *affinity);
+
+// Create a new, disarmed CPU timer. Must be freed using `cputimer_free`.
+struct CpuTimer *cputimer_new(void);
+
+void cputimer_free(struct CpuTimer *timer);
+
+// Charge `cpu_time` to the timer. Returns the number of times the timer expired.
+uint64_t cputimer_consume(struct CpuTimer *timer, SimulationTime cpu_time);
+
 // Create an object that can be used to store all descriptors created by a
 // process. When the table is no longer required, use descriptortable_free
 // to release the reference.
 struct DescriptorTable *descriptortable_new(void);
 
+// Set the soft and hard `RLIMIT_NOFILE` limits of the table.
+void descriptortable_setFdLimit(struct DescriptorTable *table, uint64_t limit);
+
 // Free the table.
 void descriptortable_free(struct DescriptorTable *table);
 
+// Create a copy of the table for a child process created by `fork`. When the table is no
+// longer required, use descriptortable_free to release the reference.
+struct DescriptorTable *descriptortable_fork(const struct DescriptorTable *table);
+
 // Store the given descriptor at the given index. Any previous descriptor that was
 // stored there will be returned. This consumes a ref to the given descriptor as in
 // add(), and any returned descriptor must be freed manually.
@@ -591,10 +647,87 @@ void descriptortable_shutdownHelper(struct DescriptorTable *table);
 // Close all descriptors. The `host` option is a legacy option for legacy files.
 void descriptortable_removeAndCloseAll(struct DescriptorTable *table, Host *host);
 
+// Close all descriptors that have the `FD_CLOEXEC` flag set, as when the process calls
+// `execve`. The `host` option is a legacy option for legacy files.
+void descriptortable_removeAndCloseCloexec(struct DescriptorTable *table, Host *host);
+
 struct Arc_AtomicRefCell_AbstractUnixNamespace *abstractunixnamespace_new(void);
 
 void abstractunixnamespace_free(struct Arc_AtomicRefCell_AbstractUnixNamespace *ns);
 
+// Decrement the ref count of the `InetSocket` object. The pointer must not be used after
+// calling this function.
+void inetsocket_drop(const struct InetSocket *socket);
+
+// Increment the ref count of the `InetSocket` object. The returned pointer will not be the
+// same as the given pointer (they are distinct references), and they both must be dropped
+// with `inetsocket_drop` separately later.
+const struct InetSocket *inetsocket_cloneRef(const struct InetSocket *socket);
+
+// Get a handle that is the same for all references to the same socket.
+uintptr_t inetsocket_getCanonicalHandle(const struct InetSocket *socket);
+
+ProtocolType inetsocket_getProtocol(const struct InetSocket *socket);
+
+// Get the socket's peer address in network byte order. Returns false if the socket is not
+// connected. The out-pointers may be null.
+bool inetsocket_getPeerName(const struct InetSocket *socket, in_addr_t *ip, in_port_t *port);
+
+// Get the socket's bound address in network byte order. Returns false if the socket is not
+// bound. The out-pointers may be null.
+bool inetsocket_getSocketName(const struct InetSocket *socket, in_addr_t *ip, in_port_t *port);
+
+// Get a borrowed pointer to the next packet that the socket will send, or null if there are
+// no packets to send. The packet must not be used after the socket is next modified.
+const Packet *inetsocket_peekNextOutPacket(const struct InetSocket *socket);
+
+// Give a packet to the socket. The socket takes its own reference to the packet, so the
+// caller keeps its reference.
+void inetsocket_pushInPacket(const struct InetSocket *socket, Host *host, Packet *packet);
+
+// Remove the next packet that the socket will send, or return null if there are no packets to
+// send. The caller owns the returned packet reference and must unref it.
+Packet *inetsocket_pullOutPacket(const struct InetSocket *socket, Host *host);
+
+// Create a new congestion control object using the given algorithm. The object must be
+// freed with `tcpcongestion_free()`.
+struct TcpCongestionControl *tcpcongestion_new(TcpCongestionAlgorithm algorithm);
+
+void tcpcongestion_free(struct TcpCongestionControl *cong);
+
+TcpCongestionAlgorithm tcpcongestion_getAlgorithm(const struct TcpCongestionControl *cong);
+
+// Get the linux name of the algorithm as a static nul-terminated string.
+const char *tcpcongestion_algorithmName(TcpCongestionAlgorithm algorithm);
+
+// Look up an algorithm by its linux name. Returns false if there is no algorithm with the
+// given name.
+bool tcpcongestion_algorithmFromName(const char *name, TcpCongestionAlgorithm *algorithm);
+
+uint32_t tcpcongestion_getCwnd(const struct TcpCongestionControl *cong);
+
+uint32_t tcpcongestion_getSSThresh(const struct TcpCongestionControl *cong);
+
+bool tcpcongestion_isInFastRecovery(const struct TcpCongestionControl *cong);
+
+void tcpcongestion_onDuplicateAck(struct TcpCongestionControl *cong);
+
+// Notify the algorithm of an acknowledgement for new data. A `srtt` of 0 means that the
+// round-trip time hasn't been measured yet.
+void tcpcongestion_onNewAck(struct TcpCongestionControl *cong,
+                            uint32_t packets_acked,
+                            uint32_t packets_in_flight,
+                            SimulationTime srtt);
+
+void tcpcongestion_onTimeout(struct TcpCongestionControl *cong);
+
+// Create a new pathname unix socket namespace. Relative socket paths will be resolved against
+// `working_dir`, and placeholder files will be created within `data_dir` (if not NULL).
+struct Arc_AtomicRefCell_PathnameUnixNamespace *pathnameunixnamespace_new(const char *working_dir,
+                                                                          const char *data_dir);
+
+void pathnameunixnamespace_free(struct Arc_AtomicRefCell_PathnameUnixNamespace *ns);
+
 // The new descriptor takes ownership of the reference to the legacy file and does not
 // increment its ref count, but will decrement the ref count when this descriptor is
 // freed/dropped with `descriptor_free()`. The descriptor flags must be either 0 or
@@ -663,10 +796,30 @@ void file_removeListener(const struct File *file, StatusListener *listener);
 // underlying data if their handles are equal.
 uintptr_t file_getCanonicalHandle(const struct File *file);
 
+// Create a new `StateEventSource`, which legacy files use to notify rust listeners of their
+// status changes. The source must later be passed to `stateeventsource_free()`.
+struct StateEventSource *stateeventsource_new(void);
+
+// Free the `StateEventSource`. Any listeners are dropped.
+void stateeventsource_free(struct StateEventSource *source);
+
+// Notify the listeners of the `StateEventSource` of a status change. The listeners will have
+// run by the time this function returns.
+void stateeventsource_notifyListeners(struct StateEventSource *source,
+                                      Status status,
+                                      Status changed);
+
 // # Safety
 // * `thread` must point to a valid object.
 struct MemoryManager *memorymanager_new(pid_t pid);
 
+// Create a MemoryManager for the child process `child_pid`, which was just created from
+// `mm`'s process by `fork`. `child_thread` must be the child's thread, and must be
+// running and ready to make native syscalls.
+struct MemoryManager *memorymanager_fork(const struct MemoryManager *mm,
+                                         pid_t child_pid,
+                                         Thread *child_thread);
+
 // # Safety
 // * `mm` must point to a valid object.
 void memorymanager_free(struct MemoryManager *mm);
@@ -769,8 +922,15 @@ SysCallReturn memorymanager_handleMprotect(struct MemoryManager *memory_manager,
                                            uintptr_t size,
                                            int32_t prot);
 
+// Create a new, empty POSIX timer table. Must be freed using `posixtimertable_free`.
+struct PosixTimerTable *posixtimertable_new(void);
+
+// Free the table, disarming all of its timers.
+void posixtimertable_free(struct PosixTimerTable *table);
+
 // Register a `Descriptor`. This takes ownership of the descriptor and you must not access it
-// after.
+// after. If the process' fd limit has been reached, the descriptor is dropped and `-EMFILE` is
+// returned.
 int process_registerDescriptor(Process *proc, struct Descriptor *desc);
 
 // Get a temporary reference to a descriptor.
@@ -782,6 +942,14 @@ struct Descriptor *process_getRegisteredDescriptorMut(Process *proc, int handle)
 // Get a temporary reference to a legacy file.
 LegacyFile *process_getRegisteredLegacyFile(Process *proc, int handle);
 
+// Update the readiness of the signalfds in the process's descriptor table. Should be called
+// whenever a signal becomes pending for the process or one of its threads.
+void process_notifySignalFds(Process *proc);
+
+// Notify the process's timers that the host's realtime clock was set (`was_set`) or its
+// rate changed.
+void process_notifyRealtimeClockChanged(Process *proc, Host *host, bool was_set);
+
 SysCallReturn log_syscall(Process *proc,
                           enum StraceFmtMode logging_mode,
                           pid_t tid,
@@ -17,9 +17,12 @@
 #include "main/host/status.h"
 #include "main/host/status_listener.h"
 #include "main/host/syscall/fcntl.h"
+#include "main/host/syscall/fileat.h"
 #include "main/host/syscall/ioctl.h"
+#include "main/host/syscall/signal.h"
 #include "main/host/syscall/unistd.h"
 #include "main/host/syscall/socket.h"
+#include "main/host/syscall/uio.h"
 #include "main/host/syscall_condition.h"
 #include "main/host/syscall_types.h"
 #include "main/host/thread.h"
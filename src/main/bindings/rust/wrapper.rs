@@ -2,14 +2,19 @@
 use crate::host::descriptor::Descriptor;
 use crate::host::descriptor::OpenFile;
 use crate::host::descriptor::File;
+use crate::host::descriptor::StateEventSource;
 use crate::host::descriptor::descriptor_table::DescriptorTable;
 use crate::host::memory_manager::MemoryManager;
+use crate::host::posix_timer::PosixTimerTable;
+use crate::host::cpu_affinity::CpuAffinity;
+use crate::host::cpu_timer::CpuTimer;
 use crate::host::timer::Timer;
 use crate::host::syscall::handler::SyscallHandler;
 use crate::host::syscall::format::StraceFmtMode;
 use crate::core::controller::Controller;
 use crate::core::support::configuration::ConfigOptions;
 use crate::core::support::configuration::QDiscMode;
+use crate::core::support::configuration::TcpCongestionAlgorithm;
 use crate::core::work::task::TaskRef;
 use crate::utility::childpid_watcher::ChildPidWatcher;
 use crate::utility::counter::Counter;
@@ -20,11 +25,18 @@ use std::collections::HashSet;
 use atomic_refcell::AtomicRefCell;
 use crate::host::descriptor::socket::abstract_unix_ns::AbstractUnixNamespace;
 type Arc_AtomicRefCell_AbstractUnixNamespace = Arc<AtomicRefCell<AbstractUnixNamespace>>;
+use crate::host::descriptor::socket::pathname_unix_ns::PathnameUnixNamespace;
+type Arc_AtomicRefCell_PathnameUnixNamespace = Arc<AtomicRefCell<PathnameUnixNamespace>>;
 type HashSet_String = HashSet<String>;
+use crate::host::descriptor::socket::inet::InetSocket;
 
 pub const SHADOW_SOMAXCONN: u32 = 4096;
+pub const SHADOW_SIMULATED_PROCS: u32 = 100;
 pub const CONFIG_PIPE_BUFFER_SIZE: u32 = 65536;
 pub const SYSCALL_IO_BUFSIZE: u32 = 10485760;
+pub const CONFIG_MTU: u32 = 1500;
+pub const CONFIG_TCP_MAX_SEGMENT_SIZE: u32 = 1460;
+pub const CONFIG_DATAGRAM_MAX_SIZE: u32 = 65507;
 pub type size_t = ::std::os::raw::c_ulong;
 pub type __uint16_t = ::std::os::raw::c_ushort;
 pub type __int32_t = ::std::os::raw::c_int;
@@ -33,8 +45,10 @@ pub type __int64_t = ::std::os::raw::c_long;
 pub type __uint64_t = ::std::os::raw::c_ulong;
 pub type __uid_t = ::std::os::raw::c_uint;
 pub type __pid_t = ::std::os::raw::c_int;
+pub type __off_t = ::std::os::raw::c_long;
 pub type __clock_t = ::std::os::raw::c_long;
 pub type __ssize_t = ::std::os::raw::c_long;
+pub type off_t = __off_t;
 pub type pid_t = __pid_t;
 pub type ssize_t = __ssize_t;
 #[repr(C)]
@@ -109,12 +123,14 @@ extern "C" {
     pub fn runConfigHandlers(config: *const ConfigOptions);
 }
 pub type guint32 = ::std::os::raw::c_uint;
+pub type gint64 = ::std::os::raw::c_long;
 pub type guint64 = ::std::os::raw::c_ulong;
 pub type gssize = ::std::os::raw::c_long;
 pub type gsize = ::std::os::raw::c_ulong;
 pub type gchar = ::std::os::raw::c_char;
 pub type gint = ::std::os::raw::c_int;
 pub type gboolean = gint;
+pub type glong = ::std::os::raw::c_long;
 pub type gulong = ::std::os::raw::c_ulong;
 pub type guint = ::std::os::raw::c_uint;
 pub type gdouble = f64;
@@ -1150,7 +1166,16 @@ pub struct _Event {
     _unused: [u8; 0],
 }
 pub type Event = _Event;
-pub type LegacyFile = [u64; 5usize];
+pub use self::_LegacyFileType as LegacyFileType;
+pub const _LegacyFileType_DT_NONE: _LegacyFileType = 0;
+pub const _LegacyFileType_DT_TCPSOCKET: _LegacyFileType = 1;
+pub const _LegacyFileType_DT_UDPSOCKET: _LegacyFileType = 2;
+pub const _LegacyFileType_DT_EPOLL: _LegacyFileType = 3;
+pub const _LegacyFileType_DT_EVENTD: _LegacyFileType = 4;
+pub const _LegacyFileType_DT_TIMER: _LegacyFileType = 5;
+pub const _LegacyFileType_DT_FILE: _LegacyFileType = 6;
+pub type _LegacyFileType = i32;
+pub type LegacyFile = [u64; 6usize];
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct _Process {
@@ -1163,6 +1188,114 @@ pub struct _Host {
     _unused: [u8; 0],
 }
 pub type Host = _Host;
+pub type HostClockStatus = _HostClockStatus;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _HostClockStatus {
+    pub status: gint,
+    pub maxError: glong,
+    pub estError: glong,
+    pub timeConstant: glong,
+    pub tick: glong,
+}
+#[test]
+fn bindgen_test_layout__HostClockStatus() {
+    assert_eq!(
+        ::std::mem::size_of::<_HostClockStatus>(),
+        40usize,
+        concat!("Size of: ", stringify!(_HostClockStatus))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<_HostClockStatus>(),
+        8usize,
+        concat!("Alignment of ", stringify!(_HostClockStatus))
+    );
+    fn test_field_status() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostClockStatus>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).status) as usize - ptr as usize
+            },
+            0usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostClockStatus),
+                "::",
+                stringify!(status)
+            )
+        );
+    }
+    test_field_status();
+    fn test_field_maxError() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostClockStatus>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).maxError) as usize - ptr as usize
+            },
+            8usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostClockStatus),
+                "::",
+                stringify!(maxError)
+            )
+        );
+    }
+    test_field_maxError();
+    fn test_field_estError() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostClockStatus>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).estError) as usize - ptr as usize
+            },
+            16usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostClockStatus),
+                "::",
+                stringify!(estError)
+            )
+        );
+    }
+    test_field_estError();
+    fn test_field_timeConstant() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostClockStatus>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).timeConstant) as usize - ptr as usize
+            },
+            24usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostClockStatus),
+                "::",
+                stringify!(timeConstant)
+            )
+        );
+    }
+    test_field_timeConstant();
+    fn test_field_tick() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostClockStatus>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).tick) as usize - ptr as usize
+            },
+            32usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostClockStatus),
+                "::",
+                stringify!(tick)
+            )
+        );
+    }
+    test_field_tick();
+}
 #[doc = " Simulation time in nanoseconds. Allows for a consistent representation"]
 #[doc = " of time throughput the simulator."]
 pub type SimulationTime = guint64;
@@ -1179,6 +1312,7 @@ pub const _Status_STATUS_FILE_WRITABLE: _Status = 4;
 pub const _Status_STATUS_FILE_CLOSED: _Status = 8;
 pub const _Status_STATUS_FUTEX_WAKEUP: _Status = 16;
 pub const _Status_STATUS_SOCKET_ALLOWING_CONNECT: _Status = 32;
+pub const _Status_STATUS_CHILD_EVENT: _Status = 64;
 pub type _Status = i32;
 extern "C" {
     pub fn return_code_for_signal(signal: ::std::os::raw::c_int) -> ::std::os::raw::c_int;
@@ -1210,6 +1344,41 @@ pub struct _ShimShmemProcess {
 pub type ShimShmemProcess = _ShimShmemProcess;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct shd_kernel_sigset_t {
+    pub val: u64,
+}
+#[test]
+fn bindgen_test_layout_shd_kernel_sigset_t() {
+    assert_eq!(
+        ::std::mem::size_of::<shd_kernel_sigset_t>(),
+        8usize,
+        concat!("Size of: ", stringify!(shd_kernel_sigset_t))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<shd_kernel_sigset_t>(),
+        8usize,
+        concat!("Alignment of ", stringify!(shd_kernel_sigset_t))
+    );
+    fn test_field_val() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<shd_kernel_sigset_t>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).val) as usize - ptr as usize
+            },
+            0usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(shd_kernel_sigset_t),
+                "::",
+                stringify!(val)
+            )
+        );
+    }
+    test_field_val();
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct _ShimShmemThread {
     _unused: [u8; 0],
 }
@@ -1640,12 +1809,30 @@ extern "C" {
         child: *mut *mut Thread,
     ) -> ::std::os::raw::c_int;
 }
+extern "C" {
+    pub fn thread_fork(
+        thread: *mut Thread,
+        childProcess: *mut Process,
+        flags: ::std::os::raw::c_ulong,
+        newtls: ::std::os::raw::c_ulong,
+        child: *mut *mut Thread,
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn thread_setTidAddress(thread: *mut Thread, addr: PluginVirtualPtr);
 }
 extern "C" {
     pub fn thread_getTidAddress(thread: *mut Thread) -> PluginVirtualPtr;
 }
+extern "C" {
+    pub fn thread_getCpuAffinity(thread: *mut Thread) -> *mut CpuAffinity;
+}
+extern "C" {
+    pub fn thread_setRseqAddress(thread: *mut Thread, addr: PluginVirtualPtr);
+}
+extern "C" {
+    pub fn thread_getRseqAddress(thread: *mut Thread) -> PluginVirtualPtr;
+}
 extern "C" {
     pub fn thread_isLeader(thread: *mut Thread) -> bool;
 }
@@ -1696,6 +1883,15 @@ extern "C" {
 extern "C" {
     pub fn process_unref(proc_: *mut Process);
 }
+extern "C" {
+    pub fn process_fork(
+        parent: *mut Process,
+        parentThread: *mut Thread,
+        flags: ::std::os::raw::c_ulong,
+        newtls: ::std::os::raw::c_ulong,
+        child: *mut *mut Process,
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn process_schedule(proc_: *mut Process, nothing: gpointer);
 }
@@ -1741,6 +1937,30 @@ extern "C" {
 extern "C" {
     pub fn process_getProcessID(proc_: *mut Process) -> guint;
 }
+extern "C" {
+    pub fn process_getParentProcessID(proc_: *mut Process) -> guint;
+}
+extern "C" {
+    pub fn process_getProcessGroupID(proc_: *mut Process) -> guint;
+}
+extern "C" {
+    pub fn process_isWaitable(proc_: *mut Process) -> bool;
+}
+extern "C" {
+    pub fn process_isReaped(proc_: *mut Process) -> bool;
+}
+extern "C" {
+    pub fn process_getWaitStatus(proc_: *mut Process) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn process_reap(proc_: *mut Process);
+}
+extern "C" {
+    pub fn process_addChildEventListener(proc_: *mut Process, listener: *mut StatusListener);
+}
+extern "C" {
+    pub fn process_removeChildEventListener(proc_: *mut Process, listener: *mut StatusListener);
+}
 extern "C" {
     pub fn process_getNativePid(proc_: *const Process) -> pid_t;
 }
@@ -1846,6 +2066,9 @@ extern "C" {
 extern "C" {
     pub fn process_getSharedMem(proc_: *mut Process) -> *mut ShimShmemProcess;
 }
+extern "C" {
+    pub fn process_getSharedMemBlock(proc_: *mut Process) -> *mut ShMemBlock;
+}
 extern "C" {
     pub fn process_signal(
         process: *mut Process,
@@ -1853,9 +2076,47 @@ extern "C" {
         siginfo: *const siginfo_t,
     );
 }
+extern "C" {
+    pub fn process_signalThread(
+        process: *mut Process,
+        currentRunningThread: *mut Thread,
+        thread: *mut Thread,
+        siginfo: *const siginfo_t,
+    );
+}
+extern "C" {
+    pub fn process_signalTimer(
+        process: *mut Process,
+        thread: *mut Thread,
+        siginfo: *const siginfo_t,
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn process_getRealtimeTimer(process: *mut Process) -> *mut Timer;
 }
+extern "C" {
+    pub fn process_getPosixTimers(process: *mut Process) -> *mut PosixTimerTable;
+}
+extern "C" {
+    pub fn process_getVirtualTimer(process: *mut Process) -> *mut CpuTimer;
+}
+extern "C" {
+    pub fn process_getProfTimer(process: *mut Process) -> *mut CpuTimer;
+}
+extern "C" {
+    pub fn process_updateCpuTime(process: *mut Process, currentRunningThread: *mut Thread);
+}
+extern "C" {
+    pub fn process_getPendingSignals(process: *mut Process) -> shd_kernel_sigset_t;
+}
+extern "C" {
+    pub fn process_takePendingSignal(
+        process: *mut Process,
+        thread: *mut Thread,
+        set: shd_kernel_sigset_t,
+        info: *mut siginfo_t,
+    ) -> ::std::os::raw::c_int;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct _Tsc {
@@ -1910,6 +2171,21 @@ extern "C" {
 extern "C" {
     pub fn legacyfile_shutdownHelper(legacyDesc: *mut LegacyFile);
 }
+extern "C" {
+    pub fn legacyfile_getType(descriptor: *mut LegacyFile) -> LegacyFileType;
+}
+extern "C" {
+    pub fn legacyfile_getFlags(descriptor: *mut LegacyFile) -> gint;
+}
+extern "C" {
+    pub fn legacyfile_getStatus(descriptor: *mut LegacyFile) -> Status;
+}
+extern "C" {
+    pub fn legacyfile_getEventSource(descriptor: *mut LegacyFile) -> *mut StateEventSource;
+}
+extern "C" {
+    pub fn legacyfile_supportsSaRestart(legacyDesc: *mut LegacyFile) -> bool;
+}
 pub type Transport = _Transport;
 pub type TransportFunctionTable = _TransportFunctionTable;
 pub type TransportSendFunc = ::std::option::Option<
@@ -1930,6 +2206,7 @@ pub type TransportReceiveFunc = ::std::option::Option<
         nBytes: gsize,
         ip: *mut in_addr_t,
         port: *mut in_port_t,
+        flags: ::std::os::raw::c_int,
     ) -> gssize,
 >;
 #[repr(C)]
@@ -2134,6 +2411,31 @@ pub const _ProtocolType_PLOCAL: _ProtocolType = 1;
 pub const _ProtocolType_PTCP: _ProtocolType = 2;
 pub const _ProtocolType_PUDP: _ProtocolType = 3;
 pub type _ProtocolType = i32;
+pub use self::_PacketDeliveryStatusFlags as PacketDeliveryStatusFlags;
+pub const _PacketDeliveryStatusFlags_PDS_NONE: _PacketDeliveryStatusFlags = 0;
+pub const _PacketDeliveryStatusFlags_PDS_SND_CREATED: _PacketDeliveryStatusFlags = 4;
+pub const _PacketDeliveryStatusFlags_PDS_SND_TCP_ENQUEUE_THROTTLED: _PacketDeliveryStatusFlags = 8;
+pub const _PacketDeliveryStatusFlags_PDS_SND_TCP_ENQUEUE_RETRANSMIT: _PacketDeliveryStatusFlags = 16;
+pub const _PacketDeliveryStatusFlags_PDS_SND_TCP_DEQUEUE_RETRANSMIT: _PacketDeliveryStatusFlags = 32;
+pub const _PacketDeliveryStatusFlags_PDS_SND_TCP_RETRANSMITTED: _PacketDeliveryStatusFlags = 64;
+pub const _PacketDeliveryStatusFlags_PDS_SND_SOCKET_BUFFERED: _PacketDeliveryStatusFlags = 128;
+pub const _PacketDeliveryStatusFlags_PDS_SND_INTERFACE_SENT: _PacketDeliveryStatusFlags = 256;
+pub const _PacketDeliveryStatusFlags_PDS_INET_SENT: _PacketDeliveryStatusFlags = 512;
+pub const _PacketDeliveryStatusFlags_PDS_INET_DROPPED: _PacketDeliveryStatusFlags = 1024;
+pub const _PacketDeliveryStatusFlags_PDS_ROUTER_ENQUEUED: _PacketDeliveryStatusFlags = 2048;
+pub const _PacketDeliveryStatusFlags_PDS_ROUTER_DEQUEUED: _PacketDeliveryStatusFlags = 4096;
+pub const _PacketDeliveryStatusFlags_PDS_ROUTER_DROPPED: _PacketDeliveryStatusFlags = 8192;
+pub const _PacketDeliveryStatusFlags_PDS_RCV_INTERFACE_RECEIVED: _PacketDeliveryStatusFlags = 16384;
+pub const _PacketDeliveryStatusFlags_PDS_RCV_INTERFACE_DROPPED: _PacketDeliveryStatusFlags = 32768;
+pub const _PacketDeliveryStatusFlags_PDS_RCV_SOCKET_PROCESSED: _PacketDeliveryStatusFlags = 65536;
+pub const _PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED: _PacketDeliveryStatusFlags = 131072;
+pub const _PacketDeliveryStatusFlags_PDS_RCV_TCP_ENQUEUE_UNORDERED: _PacketDeliveryStatusFlags = 262144;
+pub const _PacketDeliveryStatusFlags_PDS_RCV_SOCKET_BUFFERED: _PacketDeliveryStatusFlags = 524288;
+pub const _PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DELIVERED: _PacketDeliveryStatusFlags = 1048576;
+pub const _PacketDeliveryStatusFlags_PDS_DESTROYED: _PacketDeliveryStatusFlags = 2097152;
+pub type _PacketDeliveryStatusFlags = ::std::os::raw::c_uint;
+pub const ProtocolUDPFlags_PUDP_NONE: ProtocolUDPFlags = 0;
+pub type ProtocolUDPFlags = ::std::os::raw::c_uint;
 pub const ProtocolTCPFlags_PTCP_NONE: ProtocolTCPFlags = 0;
 pub const ProtocolTCPFlags_PTCP_RST: ProtocolTCPFlags = 2;
 pub const ProtocolTCPFlags_PTCP_SYN: ProtocolTCPFlags = 4;
@@ -2198,12 +2500,24 @@ pub struct _HostParameters {
     pub sendBufSize: guint64,
     pub autotuneSendBuf: gboolean,
     pub interfaceBufSize: guint64,
+    pub tcpCongestionControl: TcpCongestionAlgorithm,
+    pub useNewTcp: gboolean,
+    pub fdLimit: guint64,
+    pub cpuCount: guint32,
+    pub ramTotal: guint64,
+    pub ramFree: guint64,
+    pub swapTotal: guint64,
+    pub swapFree: guint64,
+    pub loadAverage: [gdouble; 3usize],
+    pub clockOffset: gint64,
+    pub clockDriftPpm: gdouble,
+    pub startDatetime: EmulatedTime,
 }
 #[test]
 fn bindgen_test_layout__HostParameters() {
     assert_eq!(
         ::std::mem::size_of::<_HostParameters>(),
-        144usize,
+        248usize,
         concat!("Size of: ", stringify!(_HostParameters))
     );
     assert_eq!(
@@ -2585,93 +2899,297 @@ fn bindgen_test_layout__HostParameters() {
         );
     }
     test_field_interfaceBufSize();
+    fn test_field_tcpCongestionControl() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).tcpCongestionControl) as usize - ptr as usize
+            },
+            144usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(tcpCongestionControl)
+            )
+        );
+    }
+    test_field_tcpCongestionControl();
+    fn test_field_useNewTcp() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).useNewTcp) as usize - ptr as usize
+            },
+            148usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(useNewTcp)
+            )
+        );
+    }
+    test_field_useNewTcp();
+    fn test_field_fdLimit() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).fdLimit) as usize - ptr as usize
+            },
+            152usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(fdLimit)
+            )
+        );
+    }
+    test_field_fdLimit();
+    fn test_field_cpuCount() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).cpuCount) as usize - ptr as usize
+            },
+            160usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(cpuCount)
+            )
+        );
+    }
+    test_field_cpuCount();
+    fn test_field_ramTotal() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).ramTotal) as usize - ptr as usize
+            },
+            168usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(ramTotal)
+            )
+        );
+    }
+    test_field_ramTotal();
+    fn test_field_ramFree() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).ramFree) as usize - ptr as usize
+            },
+            176usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(ramFree)
+            )
+        );
+    }
+    test_field_ramFree();
+    fn test_field_swapTotal() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).swapTotal) as usize - ptr as usize
+            },
+            184usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(swapTotal)
+            )
+        );
+    }
+    test_field_swapTotal();
+    fn test_field_swapFree() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).swapFree) as usize - ptr as usize
+            },
+            192usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(swapFree)
+            )
+        );
+    }
+    test_field_swapFree();
+    fn test_field_loadAverage() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).loadAverage) as usize - ptr as usize
+            },
+            200usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(loadAverage)
+            )
+        );
+    }
+    test_field_loadAverage();
+    fn test_field_clockOffset() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).clockOffset) as usize - ptr as usize
+            },
+            224usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(clockOffset)
+            )
+        );
+    }
+    test_field_clockOffset();
+    fn test_field_clockDriftPpm() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).clockDriftPpm) as usize - ptr as usize
+            },
+            232usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(clockDriftPpm)
+            )
+        );
+    }
+    test_field_clockDriftPpm();
+    fn test_field_startDatetime() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_HostParameters>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).startDatetime) as usize - ptr as usize
+            },
+            240usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_HostParameters),
+                "::",
+                stringify!(startDatetime)
+            )
+        );
+    }
+    test_field_startDatetime();
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _Address {
+    _unused: [u8; 0],
+}
+#[doc = " An Address structure holds information used to identify nodes, allowing for"]
+#[doc = " easy extraction of both integer and string forms of an IP address as well as"]
+#[doc = " the string hostname associated with the IP. Address is an opaque structure and"]
+#[doc = " should only be accessed using the functions in this class."]
+pub type Address = _Address;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _Router {
+    _unused: [u8; 0],
+}
+pub type Router = _Router;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _NetworkInterface {
+    _unused: [u8; 0],
+}
+pub type NetworkInterface = _NetworkInterface;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _DNS {
+    _unused: [u8; 0],
+}
+pub type DNS = _DNS;
+extern "C" {
+    pub fn dns_new() -> *mut DNS;
+}
+extern "C" {
+    pub fn dns_free(dns: *mut DNS);
+}
+extern "C" {
+    pub fn dns_register(
+        dns: *mut DNS,
+        id: GQuark,
+        name: *const gchar,
+        requestedIP: in_addr_t,
+    ) -> *mut Address;
+}
+extern "C" {
+    pub fn dns_deregister(dns: *mut DNS, address: *mut Address);
+}
+extern "C" {
+    pub fn dns_resolveIPToAddress(dns: *mut DNS, ip: in_addr_t) -> *mut Address;
+}
+extern "C" {
+    pub fn dns_resolveNameToAddress(dns: *mut DNS, name: *const gchar) -> *mut Address;
+}
+extern "C" {
+    pub fn dns_getHostsFilePath(dns: *mut DNS) -> *mut gchar;
+}
+extern "C" {
+    pub fn host_new(params: *const HostParameters) -> *mut Host;
+}
+extern "C" {
+    pub fn host_ref(host: *mut Host);
+}
+extern "C" {
+    pub fn host_unref(host: *mut Host);
+}
+extern "C" {
+    pub fn host_lock(host: *mut Host);
+}
+extern "C" {
+    pub fn host_unlock(host: *mut Host);
+}
+extern "C" {
+    pub fn host_continueExecutionTimer(host: *mut Host);
+}
+extern "C" {
+    pub fn host_stopExecutionTimer(host: *mut Host);
+}
+extern "C" {
+    pub fn host_setup(
+        host: *mut Host,
+        dns: *mut DNS,
+        rawCPUFreq: gulong,
+        hostRootPath: *const gchar,
+    );
+}
+extern "C" {
+    pub fn host_boot(host: *mut Host);
 }
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct _Address {
-    _unused: [u8; 0],
-}
-#[doc = " An Address structure holds information used to identify nodes, allowing for"]
-#[doc = " easy extraction of both integer and string forms of an IP address as well as"]
-#[doc = " the string hostname associated with the IP. Address is an opaque structure and"]
-#[doc = " should only be accessed using the functions in this class."]
-pub type Address = _Address;
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct _Router {
-    _unused: [u8; 0],
-}
-pub type Router = _Router;
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct _NetworkInterface {
-    _unused: [u8; 0],
-}
-pub type NetworkInterface = _NetworkInterface;
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct _DNS {
-    _unused: [u8; 0],
-}
-pub type DNS = _DNS;
-extern "C" {
-    pub fn dns_new() -> *mut DNS;
-}
-extern "C" {
-    pub fn dns_free(dns: *mut DNS);
-}
-extern "C" {
-    pub fn dns_register(
-        dns: *mut DNS,
-        id: GQuark,
-        name: *const gchar,
-        requestedIP: in_addr_t,
-    ) -> *mut Address;
-}
-extern "C" {
-    pub fn dns_deregister(dns: *mut DNS, address: *mut Address);
-}
-extern "C" {
-    pub fn dns_resolveIPToAddress(dns: *mut DNS, ip: in_addr_t) -> *mut Address;
-}
-extern "C" {
-    pub fn dns_resolveNameToAddress(dns: *mut DNS, name: *const gchar) -> *mut Address;
-}
-extern "C" {
-    pub fn dns_getHostsFilePath(dns: *mut DNS) -> *mut gchar;
-}
-extern "C" {
-    pub fn host_new(params: *const HostParameters) -> *mut Host;
-}
-extern "C" {
-    pub fn host_ref(host: *mut Host);
-}
-extern "C" {
-    pub fn host_unref(host: *mut Host);
-}
-extern "C" {
-    pub fn host_lock(host: *mut Host);
-}
-extern "C" {
-    pub fn host_unlock(host: *mut Host);
-}
-extern "C" {
-    pub fn host_continueExecutionTimer(host: *mut Host);
-}
-extern "C" {
-    pub fn host_stopExecutionTimer(host: *mut Host);
-}
-extern "C" {
-    pub fn host_setup(
-        host: *mut Host,
-        dns: *mut DNS,
-        rawCPUFreq: gulong,
-        hostRootPath: *const gchar,
-    );
-}
-extern "C" {
-    pub fn host_boot(host: *mut Host);
-}
 extern "C" {
     pub fn host_shutdown(host: *mut Host);
 }
@@ -2699,6 +3217,9 @@ extern "C" {
 extern "C" {
     pub fn host_freeAllApplications(host: *mut Host);
 }
+extern "C" {
+    pub fn host_addChildProcess(host: *mut Host, proc_: *mut Process);
+}
 extern "C" {
     pub fn host_compare(a: gconstpointer, b: gconstpointer, user_data: gpointer) -> gint;
 }
@@ -2741,6 +3262,61 @@ extern "C" {
 extern "C" {
     pub fn host_getConfiguredSendBufSize(host: *mut Host) -> guint64;
 }
+extern "C" {
+    pub fn host_getTcpCongestionAlgorithm(host: *mut Host) -> TcpCongestionAlgorithm;
+}
+extern "C" {
+    pub fn host_useNewTcp(host: *mut Host) -> gboolean;
+}
+extern "C" {
+    pub fn host_getFdLimit(host: *mut Host) -> guint64;
+}
+extern "C" {
+    pub fn host_getCpuCount(host: *mut Host) -> guint32;
+}
+extern "C" {
+    pub fn host_getRamTotal(host: *mut Host) -> guint64;
+}
+extern "C" {
+    pub fn host_getRamFree(host: *mut Host) -> guint64;
+}
+extern "C" {
+    pub fn host_getSwapTotal(host: *mut Host) -> guint64;
+}
+extern "C" {
+    pub fn host_getSwapFree(host: *mut Host) -> guint64;
+}
+extern "C" {
+    pub fn host_getLoadAverage(host: *mut Host) -> *const gdouble;
+}
+extern "C" {
+    pub fn host_getRealtime(host: *mut Host) -> EmulatedTime;
+}
+extern "C" {
+    pub fn host_getEmulatedTimeForRealtime(host: *mut Host, realtime: EmulatedTime)
+        -> EmulatedTime;
+}
+extern "C" {
+    pub fn host_setRealtime(host: *mut Host, realtime: EmulatedTime);
+}
+extern "C" {
+    pub fn host_getRealtimeFrequencyPpm(host: *mut Host) -> gdouble;
+}
+extern "C" {
+    pub fn host_setRealtimeFrequencyPpm(host: *mut Host, frequencyPpm: gdouble);
+}
+extern "C" {
+    pub fn host_getRealtimeSlew(host: *mut Host) -> gint64;
+}
+extern "C" {
+    pub fn host_setRealtimeSlew(host: *mut Host, slew: gint64);
+}
+extern "C" {
+    pub fn host_getClockStatus(host: *mut Host) -> HostClockStatus;
+}
+extern "C" {
+    pub fn host_setClockStatus(host: *mut Host, status: HostClockStatus);
+}
 extern "C" {
     pub fn host_getUpstreamRouter(host: *mut Host, handle: in_addr_t) -> *mut Router;
 }
@@ -2784,11 +3360,29 @@ extern "C" {
         peerPort: in_port_t,
     ) -> in_port_t;
 }
+extern "C" {
+    pub fn host_associateInetSocket(
+        host: *mut Host,
+        socket: *const InetSocket,
+        bindAddress: in_addr_t,
+    );
+}
+extern "C" {
+    pub fn host_disassociateInetSocket(host: *mut Host, socket: *const InetSocket);
+}
+extern "C" {
+    pub fn host_inetSocketWantsSend(host: *mut Host, socket: *const InetSocket, srcIP: in_addr_t);
+}
 extern "C" {
     pub fn host_getAbstractUnixNamespace(
         host: *mut Host,
     ) -> *mut Arc_AtomicRefCell_AbstractUnixNamespace;
 }
+extern "C" {
+    pub fn host_getPathnameUnixNamespace(
+        host: *mut Host,
+    ) -> *mut Arc_AtomicRefCell_PathnameUnixNamespace;
+}
 extern "C" {
     pub fn host_getFutexTable(host: *mut Host) -> *mut FutexTable;
 }
@@ -2798,6 +3392,14 @@ extern "C" {
 extern "C" {
     pub fn host_getProcess(host: *mut Host, virtualPID: pid_t) -> *mut Process;
 }
+extern "C" {
+    pub fn host_getChildProcess(
+        host: *mut Host,
+        parentPID: pid_t,
+        virtualPID: pid_t,
+        waitableOnly: bool,
+    ) -> *mut Process;
+}
 extern "C" {
     pub fn host_getThread(host: *mut Host, virtualTID: pid_t) -> *mut Thread;
 }
@@ -3279,12 +3881,151 @@ extern "C" {
         args: *const SysCallArgs,
     ) -> SysCallReturn;
 }
+extern "C" {
+    pub fn syscallhandler_faccessat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_fchmodat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_fchownat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_futimesat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_linkat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_mkdirat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_mknodat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_newfstatat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_openat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_readlinkat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_renameat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_renameat2(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_statx(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_symlinkat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_unlinkat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_getDirectoryPath(
+        sys: *mut SysCallHandler,
+        dirfd: ::std::os::raw::c_int,
+    ) -> *const ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn syscallhandler_utimensat(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
 extern "C" {
     pub fn syscallhandler_ioctl(
         sys: *mut SysCallHandler,
         args: *const SysCallArgs,
     ) -> SysCallReturn;
 }
+extern "C" {
+    pub fn syscallhandler_kill(sys: *mut SysCallHandler, args: *const SysCallArgs)
+        -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_tgkill(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_tkill(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_rt_sigaction(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_rt_sigprocmask(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_sigaltstack(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_raiseSigpipe(sys: *mut SysCallHandler);
+}
 extern "C" {
     pub fn syscallhandler_exit_group(
         sys: *mut SysCallHandler,
@@ -3303,6 +4044,18 @@ extern "C" {
         args: *const SysCallArgs,
     ) -> SysCallReturn;
 }
+extern "C" {
+    pub fn syscallhandler_getpgid(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_getpgrp(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
 extern "C" {
     pub fn syscallhandler_pread64(
         sys: *mut SysCallHandler,
@@ -3337,6 +4090,33 @@ extern "C" {
         args: *const SysCallArgs,
     ) -> SysCallReturn;
 }
+extern "C" {
+    pub fn syscallhandler_readLegacyFileShadow(
+        sys: *mut SysCallHandler,
+        desc: *mut LegacyFile,
+        buf: *mut ::std::os::raw::c_void,
+        bufSize: size_t,
+        offset: off_t,
+        useOffset: bool,
+    ) -> ssize_t;
+}
+extern "C" {
+    pub fn syscallhandler_writeLegacyFileShadow(
+        sys: *mut SysCallHandler,
+        desc: *mut LegacyFile,
+        buf: *const ::std::os::raw::c_void,
+        bufSize: size_t,
+        offset: off_t,
+        useOffset: bool,
+    ) -> ssize_t;
+}
+extern "C" {
+    pub fn syscallhandler_seekLegacyFile(
+        desc: *mut LegacyFile,
+        offset: off_t,
+        whence: ::std::os::raw::c_int,
+    ) -> off_t;
+}
 extern "C" {
     pub fn syscallhandler_accept(
         sys: *mut SysCallHandler,
@@ -3419,11 +4199,48 @@ extern "C" {
         args: *const SysCallArgs,
     ) -> SysCallReturn;
 }
+extern "C" {
+    pub fn syscallhandler_preadv(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_preadv2(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_pwritev(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_pwritev2(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_readv(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
+extern "C" {
+    pub fn syscallhandler_writev(
+        sys: *mut SysCallHandler,
+        args: *const SysCallArgs,
+    ) -> SysCallReturn;
+}
 pub use self::_TriggerType as TriggerType;
 pub const _TriggerType_TRIGGER_NONE: _TriggerType = 0;
 pub const _TriggerType_TRIGGER_DESCRIPTOR: _TriggerType = 1;
 pub const _TriggerType_TRIGGER_FILE: _TriggerType = 2;
 pub const _TriggerType_TRIGGER_FUTEX: _TriggerType = 3;
+pub const _TriggerType_TRIGGER_CHILD: _TriggerType = 4;
 pub type _TriggerType = i32;
 pub type TriggerObject = _TriggerObject;
 #[repr(C)]
@@ -3433,6 +4250,7 @@ pub union _TriggerObject {
     pub as_legacy_file: *mut LegacyFile,
     pub as_file: *const File,
     pub as_futex: *mut Futex,
+    pub as_process: *mut Process,
 }
 #[test]
 fn bindgen_test_layout__TriggerObject() {
@@ -3514,6 +4332,23 @@ fn bindgen_test_layout__TriggerObject() {
         );
     }
     test_field_as_futex();
+    fn test_field_as_process() {
+        assert_eq!(
+            unsafe {
+                let uninit = ::std::mem::MaybeUninit::<_TriggerObject>::uninit();
+                let ptr = uninit.as_ptr();
+                ::std::ptr::addr_of!((*ptr).as_process) as usize - ptr as usize
+            },
+            0usize,
+            concat!(
+                "Offset of field: ",
+                stringify!(_TriggerObject),
+                "::",
+                stringify!(as_process)
+            )
+        );
+    }
+    test_field_as_process();
 }
 pub type Trigger = _Trigger;
 #[repr(C)]
@@ -3590,12 +4425,18 @@ fn bindgen_test_layout__Trigger() {
 extern "C" {
     pub fn syscallcondition_new(trigger: Trigger) -> *mut SysCallCondition;
 }
+extern "C" {
+    pub fn syscallcondition_setTimeout(cond: *mut SysCallCondition, host: *mut Host, t: EmulatedTime);
+}
 extern "C" {
     pub fn syscallcondition_setActiveFile(cond: *mut SysCallCondition, file: *mut OpenFile);
 }
 extern "C" {
     pub fn syscallcondition_unref(cond: *mut SysCallCondition);
 }
+extern "C" {
+    pub fn syscallcondition_getTimeout(cond: *mut SysCallCondition) -> EmulatedTime;
+}
 extern "C" {
     pub fn syscallcondition_getActiveFile(cond: *mut SysCallCondition) -> *mut OpenFile;
 }
@@ -3814,6 +4655,54 @@ fn bindgen_test_layout__PacketTCPHeader() {
     }
     test_field_timestampEcho();
 }
+extern "C" {
+    pub fn packet_new(host: *mut Host) -> *mut Packet;
+}
+extern "C" {
+    pub fn packet_setPayloadShadow(
+        packet: *mut Packet,
+        host: *mut Host,
+        payload: *const ::std::os::raw::c_void,
+        payloadLength: gsize,
+    );
+}
+extern "C" {
+    pub fn packet_ref(packet: *mut Packet);
+}
+extern "C" {
+    pub fn packet_unref(packet: *mut Packet);
+}
+extern "C" {
+    pub fn packet_setUDP(
+        packet: *mut Packet,
+        flags: ProtocolUDPFlags,
+        sourceIP: in_addr_t,
+        sourcePort: in_port_t,
+        destinationIP: in_addr_t,
+        destinationPort: in_port_t,
+    );
+}
+extern "C" {
+    pub fn packet_setTCP(
+        packet: *mut Packet,
+        flags: ProtocolTCPFlags,
+        sourceIP: in_addr_t,
+        sourcePort: in_port_t,
+        destinationIP: in_addr_t,
+        destinationPort: in_port_t,
+        sequence: guint,
+    );
+}
+extern "C" {
+    pub fn packet_updateTCP(
+        packet: *mut Packet,
+        acknowledgement: guint,
+        selectiveACKs: *mut GList,
+        window: guint,
+        timestampValue: SimulationTime,
+        timestampEcho: SimulationTime,
+    );
+}
 extern "C" {
     pub fn packet_getTotalSize(packet: *const Packet) -> gsize;
 }
@@ -3849,6 +4738,9 @@ extern "C" {
 extern "C" {
     pub fn packet_getTCPHeader(packet: *const Packet) -> *mut PacketTCPHeader;
 }
+extern "C" {
+    pub fn packet_addDeliveryStatus(packet: *mut Packet, status: PacketDeliveryStatusFlags);
+}
 extern "C" {
     pub fn scanRpathForLib(libname: *const gchar) -> *mut gchar;
 }
@@ -98,7 +98,10 @@ impl<'a> Controller<'a> {
         let manager = Manager::new(&self, self.config, manager_hosts, end_time, manager_rand)
             .context("Failed to initialize the manager")?;
 
-        log::info!("Running simulation");
+        log::info!(
+            "Running simulation starting at {}",
+            self.config.general.start_datetime.as_ref().unwrap()
+        );
         manager.run()?;
         log::info!("Finished simulation");
 
@@ -129,6 +132,7 @@ impl std::ops::Drop for Controller<'_> {
 pub trait SimController {
     unsafe fn get_dns(&self) -> *mut c::DNS;
     fn get_latency(&self, src: std::net::IpAddr, dst: std::net::IpAddr) -> Option<SimulationTime>;
+    fn get_jitter(&self, src: std::net::IpAddr, dst: std::net::IpAddr) -> Option<SimulationTime>;
     fn get_reliability(&self, src: std::net::IpAddr, dst: std::net::IpAddr) -> Option<f32>;
     fn get_bandwidth(&self, ip: std::net::IpAddr) -> Option<&Bandwidth>;
     fn increment_packet_count(&self, src: std::net::IpAddr, dst: std::net::IpAddr);
@@ -155,6 +159,15 @@ impl SimController for Controller<'_> {
         ))
     }
 
+    fn get_jitter(&self, src: std::net::IpAddr, dst: std::net::IpAddr) -> Option<SimulationTime> {
+        let src = self.ip_assignment.get_node(src)?;
+        let dst = self.ip_assignment.get_node(dst)?;
+
+        Some(SimulationTime::from_nanos(
+            self.routing_info.path(src, dst)?.jitter_ns,
+        ))
+    }
+
     fn get_reliability(&self, src: std::net::IpAddr, dst: std::net::IpAddr) -> Option<f32> {
         let src = self.ip_assignment.get_node(src)?;
         let dst = self.ip_assignment.get_node(dst)?;
@@ -403,6 +416,20 @@ mod export {
         SimulationTime::to_c_simtime(controller.get_latency(src, dst))
     }
 
+    /// Get the maximum additional per-packet delay for packets sent from one host to another.
+    #[no_mangle]
+    pub extern "C" fn controller_getJitter(
+        controller: *const Controller,
+        src: libc::in_addr_t,
+        dst: libc::in_addr_t,
+    ) -> c::SimulationTime {
+        let controller = unsafe { controller.as_ref() }.unwrap();
+        let src = std::net::IpAddr::V4(u32::from_be(src).into());
+        let dst = std::net::IpAddr::V4(u32::from_be(dst).into());
+
+        SimulationTime::to_c_simtime(controller.get_jitter(src, dst))
+    }
+
     #[no_mangle]
     pub extern "C" fn controller_getReliability(
         controller: *const Controller,
@@ -1,4 +1,5 @@
 use crate::core::support::emulated_time::EmulatedTime;
+use crate::core::support::simulation_time::SimulationTime;
 use crate::core::worker::Worker;
 use crate::host::host::HostInfo;
 use crate::utility::time::TimeParts;
@@ -82,6 +83,11 @@ pub struct ShadowLogger {
     // When false, sends a (still-asynchronous) flush command to the logger
     // thread every time a record is pushed into `records`.
     buffering_enabled: RwLock<bool>,
+
+    // The configured date and time at the start of the simulation. Log
+    // timestamps show the simulated time of day, counted from midnight (UTC)
+    // of this date.
+    start_datetime: RwLock<EmulatedTime>,
 }
 
 thread_local!(static SENDER: RefCell<Option<Sender<LoggerCommand>>> = RefCell::new(None));
@@ -125,6 +131,7 @@ impl ShadowLogger {
             command_sender: Mutex::new(sender),
             command_receiver: Mutex::new(receiver),
             buffering_enabled: RwLock::new(false),
+            start_datetime: RwLock::new(EmulatedTime::SIMULATION_START),
         }
     }
 
@@ -159,6 +166,14 @@ impl ShadowLogger {
         // necessary. Also keeps us from holding the stdout lock indefinitely.
         let mut toflush = self.records.len();
 
+        // log timestamps are relative to midnight of the simulation's start date
+        let start_datetime = *self.start_datetime.read().unwrap();
+        let start_of_day = start_datetime
+            .duration_since(&EmulatedTime::UNIX_EPOCH)
+            .as_nanos()
+            % (24 * 60 * 60 * 1_000_000_000);
+        let start_of_day = SimulationTime::from_nanos(start_of_day.try_into().unwrap());
+
         let stdout_unlocked = std::io::stdout();
         let stdout_locked = stdout_unlocked.lock();
         let mut stdout = std::io::BufWriter::new(stdout_locked);
@@ -186,7 +201,8 @@ impl ShadowLogger {
             }
             write!(stdout, " [{}:{}]", record.thread_id, record.thread_name)?;
             if let Some(emu_time) = record.emu_time {
-                let sim_time = emu_time.duration_since(&EmulatedTime::SIMULATION_START);
+                let sim_time =
+                    emu_time.duration_since(&EmulatedTime::SIMULATION_START) + start_of_day;
                 let parts = TimeParts::from_nanos(sim_time.as_nanos());
                 write!(
                     stdout,
@@ -254,6 +270,13 @@ impl ShadowLogger {
         *writer = buffering_enabled;
     }
 
+    /// Set the configured date and time at the start of the simulation, which
+    /// log timestamps are relative to.
+    pub fn set_start_datetime(&self, start_datetime: EmulatedTime) {
+        let mut writer = self.start_datetime.write().unwrap();
+        *writer = start_datetime;
+    }
+
     // Send a flush command to the logger thread.
     fn flush_impl(&self, notify_done: Option<Sender<()>>) {
         self.send_command(LoggerCommand::Flush(notify_done))
@@ -388,6 +411,10 @@ pub fn set_buffering_enabled(buffering_enabled: bool) {
     SHADOW_LOGGER.set_buffering_enabled(buffering_enabled);
 }
 
+pub fn set_start_datetime(start_datetime: EmulatedTime) {
+    SHADOW_LOGGER.set_start_datetime(start_datetime);
+}
+
 mod export {
     use super::*;
 
@@ -158,6 +158,9 @@ pub fn run_shadow<'a>(args: Vec<&'a OsStr>) -> anyhow::Result<()> {
     let sim_config = SimConfig::new(&shadow_config, &options.debug_hosts.unwrap_or_default())
         .context("Failed to initialize the simulation")?;
 
+    // log timestamps show the time of day on the configured start date
+    shadow_logger::set_start_datetime(sim_config.start_datetime);
+
     // allocate and initialize our main simulation driver
     let controller = Controller::new(sim_config, &shadow_config);
 
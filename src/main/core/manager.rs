@@ -12,6 +12,7 @@ use rand_xoshiro::Xoshiro256PlusPlus;
 use crate::core::controller::{Controller, SimController};
 use crate::core::sim_config::HostInfo;
 use crate::core::support::configuration::{ConfigOptions, Flatten, LogLevel, SchedulerPolicy};
+use crate::core::support::emulated_time::EmulatedTime;
 use crate::core::support::simulation_time::SimulationTime;
 use crate::core::worker;
 use crate::cshadow as c;
@@ -359,6 +360,18 @@ impl<'a> Manager<'a> {
                 sendBufSize: host.send_buf_size,
                 autotuneSendBuf: if host.autotune_send_buf { 1 } else { 0 },
                 interfaceBufSize: host.interface_buf_size,
+                tcpCongestionControl: host.tcp_congestion_control,
+                useNewTcp: if host.use_new_tcp { 1 } else { 0 },
+                fdLimit: host.fd_limit,
+                cpuCount: host.cpu_count,
+                ramTotal: host.ram_total,
+                ramFree: host.ram_free,
+                swapTotal: host.swap_total,
+                swapFree: host.swap_free,
+                loadAverage: host.load_average,
+                clockOffset: host.clock_offset,
+                clockDriftPpm: host.clock_drift_ppm,
+                startDatetime: EmulatedTime::to_c_emutime(Some(host.start_datetime)),
             };
 
             let hosts_path =
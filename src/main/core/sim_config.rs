@@ -12,8 +12,9 @@ use rand_xoshiro::Xoshiro256PlusPlus;
 use crate::core::support::configuration::Flatten;
 use crate::core::support::configuration::{
     parse_string_as_args, ConfigOptions, HostOptions, LogInfoFlag, LogLevel, ProcessArgs,
-    ProcessOptions, QDiscMode,
+    ProcessOptions, QDiscMode, TcpCongestionAlgorithm,
 };
+use crate::core::support::emulated_time::EmulatedTime;
 use crate::core::support::simulation_time::SimulationTime;
 use crate::core::support::units::{self, Unit};
 use crate::network::network_graph::{load_network_graph, IpAssignment, NetworkGraph, RoutingInfo};
@@ -35,6 +36,9 @@ pub struct SimConfig {
 
     // a list of hosts and their processes
     pub hosts: Vec<HostInfo>,
+
+    // the date and time at the start of the simulation
+    pub start_datetime: EmulatedTime,
 }
 
 impl SimConfig {
@@ -47,6 +51,11 @@ impl SimConfig {
         // this should be the same for all hosts
         let randomness_for_seed_calc = random.gen();
 
+        let start_datetime = config.general.start_datetime.as_ref().unwrap();
+        let start_datetime = EmulatedTime::from_rfc3339(start_datetime)
+            .map_err(|e| anyhow::anyhow!(e))
+            .context("Failed to parse the start date and time")?;
+
         // build the host list
         let mut hosts = vec![];
         for (name, host_options) in &config.hosts {
@@ -55,6 +64,7 @@ impl SimConfig {
                 host_options,
                 name,
                 randomness_for_seed_calc,
+                start_datetime,
                 hosts_to_debug,
             )
             .with_context(|| format!("Failed to configure host '{name}'"))?;
@@ -154,6 +164,7 @@ impl SimConfig {
             routing_info,
             host_bandwidths,
             hosts,
+            start_datetime,
         })
     }
 }
@@ -173,6 +184,17 @@ pub struct HostInfo {
     pub log_level: Option<LogLevel>,
     pub pcap_dir: Option<PathBuf>,
     pub pcap_capture_size: u64,
+    pub tcp_congestion_control: TcpCongestionAlgorithm,
+    pub fd_limit: u64,
+    pub cpu_count: u32,
+    pub ram_total: u64,
+    pub ram_free: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+    pub load_average: [f64; 3],
+    pub clock_offset: i64,
+    pub clock_drift_ppm: f64,
+    pub start_datetime: EmulatedTime,
     pub heartbeat_log_level: Option<LogLevel>,
     pub heartbeat_log_info: HashSet<LogInfoFlag>,
     pub heartbeat_interval: Option<SimulationTime>,
@@ -182,6 +204,7 @@ pub struct HostInfo {
     pub autotune_recv_buf: bool,
     pub interface_buf_size: u64,
     pub qdisc: QDiscMode,
+    pub use_new_tcp: bool,
 }
 
 #[derive(Clone)]
@@ -205,6 +228,7 @@ fn build_host(
     host: &HostOptions,
     hostname: &str,
     randomness_for_seed_calc: u64,
+    start_datetime: EmulatedTime,
     hosts_to_debug: &HashSet<String>,
 ) -> anyhow::Result<Vec<HostInfo>> {
     let quantity = *host.quantity;
@@ -217,6 +241,67 @@ fn build_host(
         ));
     }
 
+    let bytes = |x: Option<units::Bytes<units::SiPrefixUpper>>| {
+        x.unwrap()
+            .convert(units::SiPrefixUpper::Base)
+            .unwrap()
+            .value()
+    };
+
+    let ram_total = bytes(host.options.ram_total);
+    let ram_free = bytes(host.options.ram_free);
+    let swap_total = bytes(host.options.swap_total);
+    let swap_free = bytes(host.options.swap_free);
+    let load_average = host.options.load_average.unwrap();
+
+    if ram_free > ram_total {
+        return Err(anyhow::anyhow!(
+            "Free RAM {ram_free} is larger than the total RAM {ram_total}"
+        ));
+    }
+    if swap_free > swap_total {
+        return Err(anyhow::anyhow!(
+            "Free swap {swap_free} is larger than the total swap {swap_total}"
+        ));
+    }
+    if load_average.iter().any(|x| !x.is_finite() || *x < 0.0) {
+        return Err(anyhow::anyhow!(
+            "Load averages {load_average:?} must be non-negative numbers"
+        ));
+    }
+
+    let clock_offset = host
+        .options
+        .clock_offset
+        .unwrap()
+        .convert(units::TimePrefix::Nano)
+        .map_err(|e| anyhow::anyhow!("Invalid clock offset: {e}"))?
+        .value();
+    let clock_drift_ppm = host.options.clock_drift_ppm.unwrap();
+
+    // the host's clock must start at a time that can be represented as an emulated time
+    let start_datetime_nanos = start_datetime
+        .duration_since(&EmulatedTime::UNIX_EPOCH)
+        .as_nanos();
+    let clock_start = i128::try_from(start_datetime_nanos).unwrap() + i128::from(clock_offset);
+    if clock_start < 0 {
+        return Err(anyhow::anyhow!(
+            "The clock offset {clock_offset} ns would start the clock before the unix epoch"
+        ));
+    }
+    if EmulatedTime::from_c_emutime(clock_start.try_into().unwrap_or(u64::MAX)).is_none() {
+        return Err(anyhow::anyhow!(
+            "The clock offset {clock_offset} ns would start the clock too far in the future"
+        ));
+    }
+    // limit the drift to 10% so that the clock always moves forward, even after applying the
+    // frequency adjustment of `adjtimex`
+    if !(-100_000.0..=100_000.0).contains(&clock_drift_ppm) {
+        return Err(anyhow::anyhow!(
+            "The clock drift {clock_drift_ppm} ppm must be between -100000 and 100000"
+        ));
+    }
+
     let mut hosts = Vec::with_capacity(quantity.try_into().unwrap());
 
     for host_index in 0..quantity {
@@ -275,8 +360,19 @@ fn build_host(
                 .convert(units::SiPrefixUpper::Base)
                 .unwrap()
                 .value(),
+            tcp_congestion_control: host.options.tcp_congestion_control.unwrap(),
+            fd_limit: host.options.fd_limit.unwrap(),
+            cpu_count: host.options.cpu_count.unwrap().get(),
+            ram_total,
+            ram_free,
+            swap_total,
+            swap_free,
+            load_average,
+            clock_offset,
+            clock_drift_ppm,
 
             // some options come from the config options and not the host options
+            start_datetime,
             heartbeat_log_level: config.experimental.host_heartbeat_log_level,
             heartbeat_log_info: config
                 .experimental
@@ -312,6 +408,7 @@ fn build_host(
                 .unwrap()
                 .value(),
             qdisc: config.experimental.interface_qdisc.unwrap(),
+            use_new_tcp: config.experimental.use_new_tcp.unwrap(),
         });
     }
 
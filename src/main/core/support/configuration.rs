@@ -146,6 +146,13 @@ pub struct GeneralOptions {
     #[clap(help = GENERAL_HELP.get("stop_time").unwrap().as_str())]
     pub stop_time: Option<units::Time<units::TimePrefixUpper>>,
 
+    /// The date and time at the start of the simulation in RFC 3339 format (for example
+    /// "2000-01-01T00:00:00Z"), used to initialize the hosts' realtime clocks
+    #[clap(long, value_name = "datetime")]
+    #[clap(help = GENERAL_HELP.get("start_datetime").unwrap().as_str())]
+    #[serde(default = "default_start_datetime")]
+    pub start_datetime: Option<String>,
+
     /// Initialize randomness using seed N
     #[clap(long, value_name = "N")]
     #[clap(help = GENERAL_HELP.get("seed").unwrap().as_str())]
@@ -320,6 +327,12 @@ pub struct ExperimentalOptions {
     #[clap(help = EXP_HELP.get("use_memory_manager").unwrap().as_str())]
     pub use_memory_manager: Option<bool>,
 
+    /// Use the rust TCP implementation
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bool")]
+    #[clap(help = EXP_HELP.get("use_new_tcp").unwrap().as_str())]
+    pub use_new_tcp: Option<bool>,
+
     /// Use shim-side syscall handler to force hot-path syscalls to be handled via an inter-process syscall with Shadow
     #[clap(hide_short_help = true)]
     #[clap(long, value_name = "bool")]
@@ -480,6 +493,7 @@ impl Default for ExperimentalOptions {
             // Default to the lower end to minimize effect in simualations without busy loops.
             unblocked_vdso_latency: Some(units::Time::new(10, units::TimePrefix::Nano)),
             use_memory_manager: Some(true),
+            use_new_tcp: Some(false),
             use_shim_syscall_handler: Some(true),
             use_cpu_pinning: Some(true),
             runahead: Some(NullableOption::Value(units::Time::new(
@@ -528,6 +542,62 @@ pub struct HostDefaultOptions {
     #[clap(long, value_name = "bytes")]
     #[clap(help = HOST_HELP.get("pcap_capture_size").unwrap().as_str())]
     pub pcap_capture_size: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The default congestion control algorithm for TCP sockets
+    #[clap(long, value_name = "algorithm")]
+    #[clap(help = HOST_HELP.get("tcp_congestion_control").unwrap().as_str())]
+    pub tcp_congestion_control: Option<TcpCongestionAlgorithm>,
+
+    /// The initial soft and hard limit on the number of open file descriptors
+    /// (`RLIMIT_NOFILE`) for each process
+    #[clap(long, value_name = "fds")]
+    #[clap(help = HOST_HELP.get("fd_limit").unwrap().as_str())]
+    pub fd_limit: Option<u64>,
+
+    /// The number of CPUs that each host's processes will see, for example through
+    /// `sched_getaffinity` and `get_nprocs`
+    #[clap(long, value_name = "cpus")]
+    #[clap(help = HOST_HELP.get("cpu_count").unwrap().as_str())]
+    pub cpu_count: Option<NonZeroU32>,
+
+    /// The total amount of RAM that each host's processes will see, for example through `sysinfo`
+    /// and `/proc/meminfo`
+    #[clap(long, value_name = "bytes")]
+    #[clap(help = HOST_HELP.get("ram_total").unwrap().as_str())]
+    pub ram_total: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The amount of free RAM that each host's processes will see
+    #[clap(long, value_name = "bytes")]
+    #[clap(help = HOST_HELP.get("ram_free").unwrap().as_str())]
+    pub ram_free: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The total amount of swap space that each host's processes will see
+    #[clap(long, value_name = "bytes")]
+    #[clap(help = HOST_HELP.get("swap_total").unwrap().as_str())]
+    pub swap_total: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The amount of free swap space that each host's processes will see
+    #[clap(long, value_name = "bytes")]
+    #[clap(help = HOST_HELP.get("swap_free").unwrap().as_str())]
+    pub swap_free: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The 1, 5, and 15 minute load averages that each host's processes will see, for example
+    /// through `sysinfo` and `/proc/loadavg`
+    #[clap(parse(try_from_str = parse_load_average))]
+    #[clap(long, value_name = "loads")]
+    #[clap(help = HOST_HELP.get("load_average").unwrap().as_str())]
+    pub load_average: Option<[f64; 3]>,
+
+    /// The initial offset of each host's realtime clock from the simulation's realtime clock
+    #[clap(long, value_name = "seconds", allow_hyphen_values = true)]
+    #[clap(help = HOST_HELP.get("clock_offset").unwrap().as_str())]
+    pub clock_offset: Option<units::SignedTime<units::TimePrefix>>,
+
+    /// The rate at which each host's realtime clock drifts from the simulation's realtime clock,
+    /// in parts per million
+    #[clap(long, value_name = "ppm", allow_hyphen_values = true)]
+    #[clap(help = HOST_HELP.get("clock_drift_ppm").unwrap().as_str())]
+    pub clock_drift_ppm: Option<f64>,
 }
 
 impl HostDefaultOptions {
@@ -536,6 +606,16 @@ impl HostDefaultOptions {
             log_level: None,
             pcap_directory: None,
             pcap_capture_size: None,
+            tcp_congestion_control: None,
+            fd_limit: None,
+            cpu_count: None,
+            ram_total: None,
+            ram_free: None,
+            swap_total: None,
+            swap_free: None,
+            load_average: None,
+            clock_offset: None,
+            clock_drift_ppm: None,
         }
     }
 
@@ -555,6 +635,16 @@ impl Default for HostDefaultOptions {
             // capture all the data available from the packet". The maximum length of an IP packet
             // (including the header) is 65535 bytes.
             pcap_capture_size: Some(units::Bytes::new(65535, units::SiPrefixUpper::Base)),
+            tcp_congestion_control: Some(TcpCongestionAlgorithm::Reno),
+            fd_limit: Some(65536),
+            cpu_count: Some(NonZeroU32::new(1).unwrap()),
+            ram_total: Some(units::Bytes::new(32, units::SiPrefixUpper::Gibi)),
+            ram_free: Some(units::Bytes::new(24, units::SiPrefixUpper::Gibi)),
+            swap_total: Some(units::Bytes::new(0, units::SiPrefixUpper::Base)),
+            swap_free: Some(units::Bytes::new(0, units::SiPrefixUpper::Base)),
+            load_average: Some([0.0, 0.0, 0.0]),
+            clock_offset: Some(units::SignedTime::new(0, units::TimePrefix::Sec)),
+            clock_drift_ppm: Some(0.0),
         }
     }
 }
@@ -690,6 +780,10 @@ fn default_data_directory() -> Option<String> {
     Some("shadow.data".into())
 }
 
+fn default_start_datetime() -> Option<String> {
+    Some("2000-01-01T00:00:00Z".into())
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq, ArgEnum, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LogInfoFlag {
@@ -736,6 +830,19 @@ fn parse_set_str(s: &str) -> Result<HashSet<String>, <String as FromStr>::Err> {
     parse_set(s)
 }
 
+/// Parse a string as a comma-delimited list of the 1, 5, and 15 minute load averages.
+fn parse_load_average(s: &str) -> Result<[f64; 3], String> {
+    let loads = s
+        .split(',')
+        .map(|x| x.trim().parse())
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    loads
+        .try_into()
+        .map_err(|_| "Expected three comma-delimited load averages".to_string())
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, ArgEnum, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 #[repr(C)]
@@ -752,6 +859,41 @@ impl FromStr for QDiscMode {
     }
 }
 
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, ArgEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[repr(C)]
+pub enum TcpCongestionAlgorithm {
+    Reno,
+    Cubic,
+    Bbr,
+}
+
+impl TcpCongestionAlgorithm {
+    /// The name that linux uses for this algorithm (for example with `TCP_CONGESTION`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Reno => "reno",
+            Self::Cubic => "cubic",
+            Self::Bbr => "bbr",
+        }
+    }
+
+    /// Get the algorithm with the given linux name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Reno, Self::Cubic, Self::Bbr]
+            .into_iter()
+            .find(|x| x.name() == name)
+    }
+}
+
+impl FromStr for TcpCongestionAlgorithm {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Compression {
@@ -95,6 +95,11 @@ typedef guint64 EmulatedTime;
  */
 #define SHADOW_SOMAXCONN 4096
 
+/**
+ * The number of processes that each host reports in `sysinfo()` and '/proc/loadavg'.
+ */
+#define SHADOW_SIMULATED_PROCS 100
+
 /**
  * We always use TCP_autotuning unless this is set to FALSE
  *
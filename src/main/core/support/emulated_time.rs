@@ -2,6 +2,9 @@
 Deals with instances of time in a Shadow simulation.
 */
 
+use once_cell::sync::Lazy;
+use regex::Regex;
+
 use crate::core::support::simulation_time;
 use crate::core::support::simulation_time::SimulationTime;
 use crate::cshadow as c;
@@ -95,6 +98,95 @@ impl EmulatedTime {
             None => EmulatedTime::SIMULATION_START,
         }
     }
+
+    /// Get the instant corresponding to an RFC 3339 date and time, such as
+    /// "2000-01-01T00:00:00Z" or "2022-06-30T08:30:00.5-04:00". Leap seconds are not supported.
+    pub fn from_rfc3339(s: &str) -> Result<Self, String> {
+        static RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(concat!(
+                r"^([0-9]{4})-([0-9]{2})-([0-9]{2})",
+                r"[Tt ]([0-9]{2}):([0-9]{2}):([0-9]{2})(?:\.([0-9]{1,9}))?",
+                r"(?:[Zz]|([+-])([0-9]{2}):([0-9]{2}))$",
+            ))
+            .unwrap()
+        });
+
+        let captures = RE
+            .captures(s)
+            .ok_or_else(|| format!("'{s}' is not an RFC 3339 date and time"))?;
+        let field = |i: usize| -> i64 {
+            captures
+                .get(i)
+                .map(|x| x.as_str().parse().unwrap())
+                .unwrap_or(0)
+        };
+
+        let (year, month, day) = (field(1), field(2), field(3));
+        let (hour, min, sec) = (field(4), field(5), field(6));
+        // right-pad the fraction to get nanoseconds
+        let nanos: i64 = captures
+            .get(7)
+            .map(|x| format!("{:0<9}", x.as_str()).parse().unwrap())
+            .unwrap_or(0);
+        let (offset_hour, offset_min) = (field(9), field(10));
+
+        if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+            return Err(format!("'{s}' has an invalid date"));
+        }
+        if hour > 23 || min > 59 || sec > 59 || offset_hour > 23 || offset_min > 59 {
+            return Err(format!("'{s}' has an invalid time"));
+        }
+
+        let offset = match captures.get(8).map(|x| x.as_str()) {
+            Some("-") => -(offset_hour * 60 * 60 + offset_min * 60),
+            Some(_) => offset_hour * 60 * 60 + offset_min * 60,
+            None => 0,
+        };
+
+        let secs = days_since_unix_epoch(year, month, day) * 24 * 60 * 60
+            + hour * 60 * 60
+            + min * 60
+            + sec
+            - offset;
+        let nanos = i128::from(secs) * 1_000_000_000 + i128::from(nanos);
+
+        let nanos = u64::try_from(nanos).map_err(|_| {
+            if nanos < 0 {
+                format!("'{s}' is before the unix epoch")
+            } else {
+                format!("'{s}' is too far in the future")
+            }
+        })?;
+        Self::UNIX_EPOCH
+            .checked_add(SimulationTime::from_nanos(nanos))
+            .ok_or_else(|| format!("'{s}' is too far in the future"))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The number of days from 1970-01-01 to the given date in the proleptic Gregorian calendar.
+/// Based on the `days_from_civil` algorithm from
+/// <http://howardhinnant.github.io/date_algorithms.html>.
+fn days_since_unix_epoch(year: i64, month: i64, day: i64) -> i64 {
+    // count years from March so that the leap day is at the end of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
 }
 
 impl std::ops::Add<SimulationTime> for EmulatedTime {
@@ -212,6 +304,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_rfc3339() {
+        assert_eq!(
+            EmulatedTime::from_rfc3339("1970-01-01T00:00:00Z"),
+            Ok(EmulatedTime::UNIX_EPOCH)
+        );
+        assert_eq!(
+            EmulatedTime::from_rfc3339("2000-01-01T00:00:00Z"),
+            Ok(EmulatedTime::SIMULATION_START)
+        );
+        assert_eq!(
+            EmulatedTime::from_rfc3339("2000-01-01 01:30:00.25+01:00"),
+            Ok(EmulatedTime::SIMULATION_START
+                + SimulationTime::SECOND * 60 * 30
+                + SimulationTime::MILLISECOND * 250)
+        );
+        assert_eq!(
+            EmulatedTime::from_rfc3339("1999-12-31t19:00:00-05:00"),
+            Ok(EmulatedTime::SIMULATION_START)
+        );
+        assert_eq!(
+            EmulatedTime::from_rfc3339("2024-02-29T12:00:00z"),
+            Ok(EmulatedTime::UNIX_EPOCH + SimulationTime::SECOND * 1709208000)
+        );
+
+        assert!(EmulatedTime::from_rfc3339("2023-02-29T00:00:00Z").is_err());
+        assert!(EmulatedTime::from_rfc3339("2000-01-01T24:00:00Z").is_err());
+        assert!(EmulatedTime::from_rfc3339("2000-01-01T00:00:00").is_err());
+        assert!(EmulatedTime::from_rfc3339("1969-12-31T23:59:59Z").is_err());
+        assert!(EmulatedTime::from_rfc3339("9999-01-01T00:00:00Z").is_err());
+    }
+
     #[test]
     fn test_to_abs_simtime() {
         assert_eq!(
@@ -557,6 +557,16 @@ impl From<Time<TimePrefixUpper>> for std::time::Duration {
     }
 }
 
+/// An amount of time that may be negative, such as the offset of a clock. Should only use the
+/// time prefix types ([`TimePrefix`] and [`TimePrefixUpper`]) with this type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignedTime<T: Prefix> {
+    value: i64,
+    prefix: T,
+}
+
+unit_impl!(SignedTime, i64, [""]);
+
 /// A number of bytes.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Bytes<T: Prefix> {
@@ -757,6 +767,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_signed_time() {
+        assert_eq!(
+            SignedTime::from_str("-10 min").unwrap(),
+            SignedTime::new(-10, TimePrefix::Min)
+        );
+        assert_eq!(
+            SignedTime::from_str("+10").unwrap(),
+            SignedTime::new(10, TimePrefix::Sec)
+        );
+        assert_eq!(
+            SignedTime::from_str("-2 min")
+                .unwrap()
+                .convert(TimePrefix::Sec)
+                .unwrap(),
+            SignedTime::new(-120, TimePrefix::Sec)
+        );
+        assert!(SignedTime::<TimePrefix>::from_str("-10.5 s").is_err());
+    }
+
     #[test]
     fn test_time_conversion() {
         let time = Time::<TimePrefixUpper>::from_str("70 min").unwrap();
@@ -567,6 +567,14 @@ void worker_sendPacket(Host* srcHost, Packet* packet) {
     if (bootstrapping || chance <= reliability || packet_getPayloadSize(packet) == 0) {
         /* the sender's packet will make it through, find latency */
         SimulationTime delay = worker_getLatencyForAddresses(srcAddress, dstAddress);
+
+        /* jitter only ever adds to the path latency, so that the delay never drops below the
+         * latency used to compute the runahead; the sample is in the range [0, jitter] */
+        SimulationTime jitter = worker_getJitterForAddresses(srcAddress, dstAddress);
+        if (jitter > 0) {
+            delay += (SimulationTime)(random_nextDouble(random) * (gdouble)(jitter + 1));
+        }
+
         worker_updateMinHostRunahead(delay);
         SimulationTime deliverTime = worker_getCurrentSimulationTime() + delay;
 
@@ -645,6 +653,12 @@ SimulationTime worker_getLatencyForAddresses(Address* sourceAddress, Address* de
     return controller_getLatency(_worker_pool()->controller, src, dst);
 }
 
+SimulationTime worker_getJitterForAddresses(Address* sourceAddress, Address* destinationAddress) {
+    in_addr_t src = htonl(address_toHostIP(sourceAddress));
+    in_addr_t dst = htonl(address_toHostIP(destinationAddress));
+    return controller_getJitter(_worker_pool()->controller, src, dst);
+}
+
 gdouble worker_getReliabilityForAddresses(Address* sourceAddress, Address* destinationAddress) {
     in_addr_t src = htonl(address_toHostIP(sourceAddress));
     in_addr_t dst = htonl(address_toHostIP(destinationAddress));
@@ -95,6 +95,7 @@ guint32 worker_getNodeBandwidthDownKiBps(in_addr_t ip);
 
 void workerpool_updateMinHostRunahead(WorkerPool* pool, SimulationTime time);
 SimulationTime worker_getLatencyForAddresses(Address* sourceAddress, Address* destinationAddress);
+SimulationTime worker_getJitterForAddresses(Address* sourceAddress, Address* destinationAddress);
 gdouble worker_getReliabilityForAddresses(Address* sourceAddress, Address* destinationAddress);
 bool worker_isRoutable(Address* sourceAddress, Address* destinationAddress);
 void worker_incrementPacketCount(Address* sourceAddress, Address* destinationAddress);
@@ -0,0 +1,184 @@
+use nix::errno::Errno;
+
+type MaskWord = libc::c_ulong;
+const BITS_PER_WORD: u32 = MaskWord::BITS;
+
+/// The set of simulated CPUs that a thread is allowed to run on. This backs `sched_getaffinity` and
+/// `sched_setaffinity`.
+///
+/// The mask uses the same layout as the kernel's cpumask: an array of `unsigned long` where bit `i`
+/// of word `j` represents CPU `j * BITS_PER_LONG + i`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuAffinity {
+    mask: Vec<MaskWord>,
+    cpu_count: u32,
+}
+
+impl CpuAffinity {
+    /// A mask containing all `cpu_count` CPUs of the host.
+    pub fn new(cpu_count: u32) -> Self {
+        assert!(cpu_count > 0);
+
+        let num_words = (cpu_count + BITS_PER_WORD - 1) / BITS_PER_WORD;
+        let mut affinity = Self {
+            mask: vec![0; num_words.try_into().unwrap()],
+            cpu_count,
+        };
+
+        for cpu in 0..cpu_count {
+            affinity.set(cpu);
+        }
+
+        affinity
+    }
+
+    /// The number of CPUs on the host, which isn't affected by the mask.
+    pub fn cpu_count(&self) -> u32 {
+        self.cpu_count
+    }
+
+    /// The size of the mask in bytes, which is the size of the kernel's cpumask for a system with
+    /// `cpu_count` CPUs.
+    pub fn mask_size(&self) -> usize {
+        self.mask.len() * std::mem::size_of::<MaskWord>()
+    }
+
+    pub fn contains(&self, cpu: u32) -> bool {
+        if cpu >= self.cpu_count {
+            return false;
+        }
+
+        let (word, bit) = Self::position(cpu);
+        self.mask[word] & (1 << bit) != 0
+    }
+
+    /// The CPUs in the mask, in increasing order.
+    pub fn cpus(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.cpu_count).filter(|cpu| self.contains(*cpu))
+    }
+
+    /// The lowest CPU in the mask. We report that a thread is always running on this CPU.
+    pub fn current_cpu(&self) -> u32 {
+        // the mask can never be empty
+        self.cpus().next().unwrap()
+    }
+
+    /// The mask as bytes in the kernel's format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.mask.iter().flat_map(|x| x.to_ne_bytes()).collect()
+    }
+
+    /// Replace the mask with a mask in the kernel's format. Like Linux, bytes past the end of our
+    /// mask and CPUs that don't exist are ignored, and a short mask is extended with zeros. Returns
+    /// `EINVAL` without changing the mask if the new mask doesn't contain any CPUs of the host.
+    pub fn set_from_bytes(&mut self, bytes: &[u8]) -> Result<(), Errno> {
+        let mut new_mask = vec![0; self.mask.len()];
+
+        for (word, chunk) in new_mask
+            .iter_mut()
+            .zip(bytes.chunks(std::mem::size_of::<MaskWord>()))
+        {
+            let mut word_bytes = [0u8; std::mem::size_of::<MaskWord>()];
+            word_bytes[..chunk.len()].copy_from_slice(chunk);
+            *word = MaskWord::from_ne_bytes(word_bytes);
+        }
+
+        // clear any bits past the last CPU
+        let extra_bits = new_mask.len() as u32 * BITS_PER_WORD - self.cpu_count;
+        if extra_bits > 0 {
+            *new_mask.last_mut().unwrap() &= MaskWord::MAX >> extra_bits;
+        }
+
+        if new_mask.iter().all(|x| *x == 0) {
+            return Err(Errno::EINVAL);
+        }
+
+        self.mask = new_mask;
+        Ok(())
+    }
+
+    fn set(&mut self, cpu: u32) {
+        let (word, bit) = Self::position(cpu);
+        self.mask[word] |= 1 << bit;
+    }
+
+    fn position(cpu: u32) -> (usize, u32) {
+        (
+            (cpu / BITS_PER_WORD).try_into().unwrap(),
+            cpu % BITS_PER_WORD,
+        )
+    }
+}
+
+mod export {
+    use super::*;
+
+    /// Create a new mask containing all `cpu_count` CPUs. Must be freed using `cpuaffinity_free`.
+    #[no_mangle]
+    pub extern "C" fn cpuaffinity_new(cpu_count: u32) -> *mut CpuAffinity {
+        Box::into_raw(Box::new(CpuAffinity::new(cpu_count)))
+    }
+
+    /// Create a copy of the mask. Must be freed using `cpuaffinity_free`.
+    #[no_mangle]
+    pub extern "C" fn cpuaffinity_clone(affinity: *const CpuAffinity) -> *mut CpuAffinity {
+        let affinity = unsafe { affinity.as_ref() }.unwrap();
+        Box::into_raw(Box::new(affinity.clone()))
+    }
+
+    #[no_mangle]
+    pub extern "C" fn cpuaffinity_free(affinity: *mut CpuAffinity) {
+        if affinity.is_null() {
+            return;
+        }
+
+        unsafe { Box::from_raw(affinity) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let affinity = CpuAffinity::new(3);
+        assert_eq!(affinity.cpus().collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(affinity.mask_size(), std::mem::size_of::<libc::c_ulong>());
+        assert_eq!(affinity.to_bytes()[0], 0b111);
+        assert_eq!(affinity.current_cpu(), 0);
+
+        let affinity = CpuAffinity::new(65);
+        assert_eq!(
+            affinity.mask_size(),
+            2 * std::mem::size_of::<libc::c_ulong>()
+        );
+        assert!(affinity.contains(64));
+        assert!(!affinity.contains(65));
+    }
+
+    #[test]
+    fn test_set_from_bytes() {
+        let mut affinity = CpuAffinity::new(4);
+
+        // CPUs that don't exist are ignored, and a short mask is extended with zeros
+        affinity.set_from_bytes(&[0b1111_0110]).unwrap();
+        assert_eq!(affinity.cpus().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(affinity.current_cpu(), 1);
+
+        // bytes past the end of the mask are ignored
+        let mut bytes = vec![0u8; 128];
+        bytes[0] = 0b1000;
+        bytes[100] = 0xFF;
+        affinity.set_from_bytes(&bytes).unwrap();
+        assert_eq!(affinity.cpus().collect::<Vec<_>>(), [3]);
+    }
+
+    #[test]
+    fn test_set_empty() {
+        let mut affinity = CpuAffinity::new(4);
+        assert_eq!(affinity.set_from_bytes(&[0b1111_0000]), Err(Errno::EINVAL));
+        assert_eq!(affinity.set_from_bytes(&[]), Err(Errno::EINVAL));
+        assert_eq!(affinity.cpus().collect::<Vec<_>>(), [0, 1, 2, 3]);
+    }
+}
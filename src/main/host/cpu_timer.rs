@@ -0,0 +1,164 @@
+use crate::core::support::simulation_time::SimulationTime;
+
+/// A timer that counts down the modeled CPU time consumed by a process, rather than the emulated
+/// time. This backs `ITIMER_VIRTUAL` and `ITIMER_PROF`.
+///
+/// Unlike [`Timer`](crate::host::timer::Timer), a `CpuTimer` doesn't schedule any events. It only
+/// advances when the owner charges it with CPU time using [`CpuTimer::consume`], and the owner is
+/// responsible for reporting any expirations.
+#[derive(Debug, Clone)]
+pub struct CpuTimer {
+    /// The CPU time remaining until the next expiration, or `None` if disarmed.
+    remaining: Option<SimulationTime>,
+    interval: SimulationTime,
+}
+
+impl CpuTimer {
+    pub fn new() -> Self {
+        Self {
+            remaining: None,
+            interval: SimulationTime::ZERO,
+        }
+    }
+
+    /// The CPU time remaining until the next expiration, or `None` if disarmed.
+    pub fn remaining_time(&self) -> Option<SimulationTime> {
+        self.remaining
+    }
+
+    pub fn expire_interval(&self) -> SimulationTime {
+        self.interval
+    }
+
+    /// Arm the timer to expire after `value` of CPU time, and then every `interval` of CPU time if
+    /// the interval is non-zero. A zero `value` disarms the timer.
+    pub fn arm(&mut self, value: SimulationTime, interval: SimulationTime) {
+        if value.is_zero() {
+            self.disarm();
+            return;
+        }
+
+        self.remaining = Some(value);
+        self.interval = interval;
+    }
+
+    pub fn disarm(&mut self) {
+        self.remaining = None;
+        self.interval = SimulationTime::ZERO;
+    }
+
+    /// Charge `cpu_time` to the timer. Returns the number of times the timer expired.
+    pub fn consume(&mut self, cpu_time: SimulationTime) -> u64 {
+        let remaining = match self.remaining {
+            Some(x) => x,
+            None => return 0,
+        };
+
+        if cpu_time < remaining {
+            self.remaining = Some(remaining - cpu_time);
+            return 0;
+        }
+
+        let past_expiration = cpu_time - remaining;
+
+        if self.interval.is_zero() {
+            self.remaining = None;
+            return 1;
+        }
+
+        let interval_nanos = self.interval.as_nanos();
+        let past_expiration_nanos = past_expiration.as_nanos();
+
+        let extra_expirations = u64::try_from(past_expiration_nanos / interval_nanos).unwrap();
+        let into_interval =
+            SimulationTime::from_nanos((past_expiration_nanos % interval_nanos) as u64);
+
+        self.remaining = Some(self.interval - into_interval);
+        1 + extra_expirations
+    }
+}
+
+impl Default for CpuTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod export {
+    use super::*;
+
+    use crate::cshadow as c;
+
+    /// Create a new, disarmed CPU timer. Must be freed using `cputimer_free`.
+    #[no_mangle]
+    pub extern "C" fn cputimer_new() -> *mut CpuTimer {
+        Box::into_raw(Box::new(CpuTimer::new()))
+    }
+
+    #[no_mangle]
+    pub extern "C" fn cputimer_free(timer: *mut CpuTimer) {
+        if timer.is_null() {
+            return;
+        }
+
+        unsafe { Box::from_raw(timer) };
+    }
+
+    /// Charge `cpu_time` to the timer. Returns the number of times the timer expired.
+    #[no_mangle]
+    pub extern "C" fn cputimer_consume(timer: *mut CpuTimer, cpu_time: c::SimulationTime) -> u64 {
+        let timer = unsafe { timer.as_mut() }.unwrap();
+        timer.consume(SimulationTime::from_c_simtime(cpu_time).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disarmed() {
+        let mut timer = CpuTimer::new();
+        assert_eq!(timer.consume(SimulationTime::SECOND), 0);
+        assert_eq!(timer.remaining_time(), None);
+    }
+
+    #[test]
+    fn test_one_shot() {
+        let mut timer = CpuTimer::new();
+        timer.arm(SimulationTime::from_millis(10), SimulationTime::ZERO);
+
+        assert_eq!(timer.consume(SimulationTime::from_millis(4)), 0);
+        assert_eq!(timer.remaining_time(), Some(SimulationTime::from_millis(6)));
+
+        assert_eq!(timer.consume(SimulationTime::from_millis(20)), 1);
+        assert_eq!(timer.remaining_time(), None);
+
+        assert_eq!(timer.consume(SimulationTime::from_millis(20)), 0);
+    }
+
+    #[test]
+    fn test_interval() {
+        let mut timer = CpuTimer::new();
+        timer.arm(
+            SimulationTime::from_millis(10),
+            SimulationTime::from_millis(3),
+        );
+
+        // exactly reaching the expiration time counts as an expiration
+        assert_eq!(timer.consume(SimulationTime::from_millis(10)), 1);
+        assert_eq!(timer.remaining_time(), Some(SimulationTime::from_millis(3)));
+
+        // 3ms, 6ms, and 9ms
+        assert_eq!(timer.consume(SimulationTime::from_millis(10)), 3);
+        assert_eq!(timer.remaining_time(), Some(SimulationTime::from_millis(2)));
+    }
+
+    #[test]
+    fn test_arm_zero() {
+        let mut timer = CpuTimer::new();
+        timer.arm(SimulationTime::ZERO, SimulationTime::from_millis(3));
+        assert_eq!(timer.remaining_time(), None);
+        assert_eq!(timer.expire_interval(), SimulationTime::ZERO);
+    }
+}
@@ -9,12 +9,14 @@
 
 #include "lib/logger/logger.h"
 #include "main/bindings/c/bindings-opaque.h"
+#include "main/bindings/c/bindings.h"
 #include "main/host/descriptor/socket.h"
 #include "main/utility/tagged_ptr.h"
 
 static void compatsockettypes_assertValid(CompatSocketTypes type) {
     switch (type) {
         case CST_LEGACY_SOCKET:
+        case CST_INET_SOCKET:
         case CST_NONE: return;
     }
     utility_panic("Invalid CompatSocket type");
@@ -28,6 +30,14 @@ CompatSocket compatsocket_fromLegacySocket(LegacySocket* socket) {
     return new_socket;
 }
 
+CompatSocket compatsocket_fromInetSocket(const InetSocket* socket) {
+    CompatSocket new_socket = {
+        .type = CST_INET_SOCKET,
+        .object.as_inet_socket = socket,
+    };
+    return new_socket;
+}
+
 CompatSocket compatsocket_refAs(const CompatSocket* socket) {
     CompatSocket new_socket = {
         .type = socket->type,
@@ -36,6 +46,9 @@ CompatSocket compatsocket_refAs(const CompatSocket* socket) {
 
     switch (new_socket.type) {
         case CST_LEGACY_SOCKET: legacyfile_ref(new_socket.object.as_legacy_socket); break;
+        case CST_INET_SOCKET:
+            new_socket.object.as_inet_socket = inetsocket_cloneRef(socket->object.as_inet_socket);
+            break;
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
@@ -47,6 +60,7 @@ CompatSocket compatsocket_refAs(const CompatSocket* socket) {
 void compatsocket_unref(const CompatSocket* socket) {
     switch (socket->type) {
         case CST_LEGACY_SOCKET: legacyfile_unref(socket->object.as_legacy_socket); break;
+        case CST_INET_SOCKET: inetsocket_drop(socket->object.as_inet_socket); break;
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
@@ -61,6 +75,7 @@ uintptr_t compatsocket_toTagged(const CompatSocket* socket) {
 
     switch (socket->type) {
         case CST_LEGACY_SOCKET: object_ptr = object.as_legacy_socket; break;
+        case CST_INET_SOCKET: object_ptr = object.as_inet_socket; break;
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
@@ -78,6 +93,7 @@ CompatSocket compatsocket_fromTagged(uintptr_t ptr) {
 
     switch (tag) {
         case CST_LEGACY_SOCKET: object.as_legacy_socket = object_ptr; break;
+        case CST_INET_SOCKET: object.as_inet_socket = object_ptr; break;
         case CST_NONE: utility_panic("Unexpected socket pointer tag");
     }
 
@@ -93,9 +109,20 @@ CompatSocket compatsocket_fromTagged(uintptr_t ptr) {
     return socket;
 }
 
+uintptr_t compatsocket_getCanonicalHandle(const CompatSocket* socket) {
+    switch (socket->type) {
+        case CST_LEGACY_SOCKET: return (uintptr_t)socket->object.as_legacy_socket;
+        case CST_INET_SOCKET: return inetsocket_getCanonicalHandle(socket->object.as_inet_socket);
+        case CST_NONE: utility_panic("Unexpected CompatSocket type");
+    }
+
+    utility_panic("Invalid CompatSocket type");
+}
+
 ProtocolType compatsocket_getProtocol(const CompatSocket* socket) {
     switch (socket->type) {
         case CST_LEGACY_SOCKET: return legacysocket_getProtocol(socket->object.as_legacy_socket);
+        case CST_INET_SOCKET: return inetsocket_getProtocol(socket->object.as_inet_socket);
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
@@ -106,6 +133,8 @@ bool compatsocket_getPeerName(const CompatSocket* socket, in_addr_t* ip, in_port
     switch (socket->type) {
         case CST_LEGACY_SOCKET:
             return legacysocket_getPeerName(socket->object.as_legacy_socket, ip, port);
+        case CST_INET_SOCKET:
+            return inetsocket_getPeerName(socket->object.as_inet_socket, ip, port);
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
@@ -116,6 +145,8 @@ bool compatsocket_getSocketName(const CompatSocket* socket, in_addr_t* ip, in_po
     switch (socket->type) {
         case CST_LEGACY_SOCKET:
             return legacysocket_getSocketName(socket->object.as_legacy_socket, ip, port);
+        case CST_INET_SOCKET:
+            return inetsocket_getSocketName(socket->object.as_inet_socket, ip, port);
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
@@ -126,6 +157,7 @@ const Packet* compatsocket_peekNextOutPacket(const CompatSocket* socket) {
     switch (socket->type) {
         case CST_LEGACY_SOCKET:
             return legacysocket_peekNextOutPacket(socket->object.as_legacy_socket);
+        case CST_INET_SOCKET: return inetsocket_peekNextOutPacket(socket->object.as_inet_socket);
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
@@ -136,6 +168,8 @@ void compatsocket_pushInPacket(const CompatSocket* socket, Host* host, Packet* p
     switch (socket->type) {
         case CST_LEGACY_SOCKET:
             return legacysocket_pushInPacket(socket->object.as_legacy_socket, host, packet);
+        case CST_INET_SOCKET:
+            return inetsocket_pushInPacket(socket->object.as_inet_socket, host, packet);
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
@@ -146,6 +180,7 @@ Packet* compatsocket_pullOutPacket(const CompatSocket* socket, Host* host) {
     switch (socket->type) {
         case CST_LEGACY_SOCKET:
             return legacysocket_pullOutPacket(socket->object.as_legacy_socket, host);
+        case CST_INET_SOCKET: return inetsocket_pullOutPacket(socket->object.as_inet_socket, host);
         case CST_NONE: utility_panic("Unexpected CompatSocket type");
     }
 
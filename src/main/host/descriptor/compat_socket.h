@@ -17,10 +17,12 @@ typedef struct _CompatSocket CompatSocket;
 enum _CompatSocketTypes {
     CST_NONE,
     CST_LEGACY_SOCKET,
+    CST_INET_SOCKET,
 };
 
 union _CompatSocketObject {
     LegacySocket* as_legacy_socket;
+    const InetSocket* as_inet_socket;
 };
 
 struct _CompatSocket {
@@ -29,6 +31,7 @@ struct _CompatSocket {
 };
 
 CompatSocket compatsocket_fromLegacySocket(LegacySocket* socket);
+CompatSocket compatsocket_fromInetSocket(const InetSocket* socket);
 
 /* reference counting */
 CompatSocket compatsocket_refAs(const CompatSocket* socket);
@@ -38,6 +41,9 @@ void compatsocket_unref(const CompatSocket* socket);
 uintptr_t compatsocket_toTagged(const CompatSocket* socket);
 CompatSocket compatsocket_fromTagged(uintptr_t ptr);
 
+/* get a handle that is the same for all references to the same socket */
+uintptr_t compatsocket_getCanonicalHandle(const CompatSocket* socket);
+
 /* compatability wrappers */
 ProtocolType compatsocket_getProtocol(const CompatSocket* socket);
 bool compatsocket_getPeerName(const CompatSocket* socket, in_addr_t* ip, in_port_t* port);
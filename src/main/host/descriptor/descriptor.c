@@ -10,6 +10,7 @@
 #include <stddef.h>
 
 #include "lib/logger/logger.h"
+#include "main/bindings/c/bindings.h"
 #include "main/core/worker.h"
 #include "main/host/descriptor/epoll.h"
 #include "main/host/descriptor/tcp.h"
@@ -28,6 +29,7 @@ void legacyfile_init(LegacyFile* descriptor, LegacyFileType type,
     descriptor->type = type;
     descriptor->listeners = g_hash_table_new_full(
         g_direct_hash, g_direct_equal, NULL, (GDestroyNotify)statuslistener_unref);
+    descriptor->eventSource = stateeventsource_new();
     descriptor->refCountStrong = 1;
     descriptor->refCountWeak = 0;
 
@@ -41,6 +43,10 @@ void legacyfile_clear(LegacyFile* descriptor) {
     if (descriptor->listeners) {
         g_hash_table_destroy(descriptor->listeners);
     }
+    if (descriptor->eventSource) {
+        stateeventsource_free(descriptor->eventSource);
+        descriptor->eventSource = NULL;
+    }
     MAGIC_CLEAR(descriptor);
 }
 
@@ -236,6 +242,13 @@ static void _legacyfile_handleStatusChange(LegacyFile* descriptor, Status oldSta
     if (listenerList != NULL) {
         g_list_free(listenerList);
     }
+
+    /* Tell the rust listeners as well, using the latest status. */
+    statusesChanged = descriptor->status ^ oldStatus;
+    if (statusesChanged) {
+        stateeventsource_notifyListeners(
+            descriptor->eventSource, descriptor->status, statusesChanged);
+    }
 }
 
 void legacyfile_adjustStatus(LegacyFile* descriptor, Status status, gboolean doSetBits) {
@@ -261,6 +274,11 @@ Status legacyfile_getStatus(LegacyFile* descriptor) {
     return descriptor->status;
 }
 
+StateEventSource* legacyfile_getEventSource(LegacyFile* descriptor) {
+    MAGIC_ASSERT(descriptor);
+    return descriptor->eventSource;
+}
+
 void legacyfile_addListener(LegacyFile* descriptor, StatusListener* listener) {
     MAGIC_ASSERT(descriptor);
     /* We are storing a listener instance, so count the ref. */
@@ -63,6 +63,10 @@ void legacyfile_adjustStatus(LegacyFile* descriptor, Status status, gboolean doS
 /* Gets the current status of the descriptor. */
 Status legacyfile_getStatus(LegacyFile* descriptor);
 
+/* Gets the event source that rust code can use to listen for status transitions. The source is
+ * owned by the descriptor and must not be used after the descriptor is freed. */
+StateEventSource* legacyfile_getEventSource(LegacyFile* descriptor);
+
 /* Adds a listener that will get notified via descriptorlistener_onStatusChanged
  * on status transitions (bit flips).
  */
@@ -1,5 +1,5 @@
 use crate::cshadow as c;
-use crate::host::descriptor::{CompatFile, Descriptor};
+use crate::host::descriptor::{CompatFile, Descriptor, DescriptorFlags};
 use crate::utility::notnull::*;
 
 use std::collections::{BTreeSet, HashMap};
@@ -16,6 +16,10 @@ pub struct DescriptorTable {
     // Lowest index not in `available_indices` that *might* be available. We still need to verify
     // availability in `descriptors`, though.
     next_index: u32,
+
+    // The process' `RLIMIT_NOFILE` limit. New descriptors can only be added at indices less than
+    // the soft limit.
+    fd_limit: libc::rlimit,
 }
 
 impl DescriptorTable {
@@ -24,14 +28,22 @@ impl DescriptorTable {
             descriptors: HashMap::new(),
             available_indices: BTreeSet::new(),
             next_index: 0,
+            fd_limit: libc::rlimit {
+                rlim_cur: libc::RLIM_INFINITY,
+                rlim_max: libc::RLIM_INFINITY,
+            },
         }
     }
 
-    /// Add the descriptor at an unused index, and return the index.
-    pub fn add(&mut self, descriptor: Descriptor, min_index: u32) -> u32 {
+    /// Add the descriptor at an unused index, and return the index. If the lowest unused index is
+    /// not below the soft fd limit, the descriptor is returned as an error.
+    pub fn add(&mut self, descriptor: Descriptor, min_index: u32) -> Result<u32, Descriptor> {
         let idx = if let Some(idx) = self.available_indices.range(min_index..).next() {
             // Un-borrow from `available_indices`.
             let idx = *idx;
+            if !self.below_fd_limit(idx) {
+                return Err(descriptor);
+            }
             // Take from `available_indices`
             trace!("Reusing available index {}", idx);
             self.available_indices.remove(&idx);
@@ -52,6 +64,10 @@ impl DescriptorTable {
                 idx += 1;
             }
 
+            if !self.below_fd_limit(idx) {
+                return Err(descriptor);
+            }
+
             if should_update_next_index {
                 self.next_index = idx + 1;
             }
@@ -64,7 +80,40 @@ impl DescriptorTable {
         let prev = self.descriptors.insert(idx, descriptor);
         debug_assert!(prev.is_none(), "Already a descriptor at {}", idx);
 
-        idx
+        Ok(idx)
+    }
+
+    /// Get the `RLIMIT_NOFILE` limit.
+    pub fn fd_limit(&self) -> libc::rlimit {
+        self.fd_limit
+    }
+
+    /// Set the `RLIMIT_NOFILE` limit. Existing descriptors at indices above the new soft limit are
+    /// not affected.
+    pub fn set_fd_limit(&mut self, fd_limit: libc::rlimit) {
+        self.fd_limit = fd_limit;
+    }
+
+    /// Returns true if the index is less than the soft fd limit.
+    pub fn below_fd_limit(&self, idx: u32) -> bool {
+        u64::from(idx) < self.fd_limit.rlim_cur
+    }
+
+    /// Returns true if a descriptor can't be added since the lowest unused index is not below the
+    /// soft fd limit.
+    pub fn at_fd_limit(&self) -> bool {
+        let idx = match self.available_indices.iter().next() {
+            Some(idx) => *idx,
+            None => {
+                let mut idx = self.next_index;
+                while self.descriptors.contains_key(&idx) {
+                    idx += 1;
+                }
+                idx
+            }
+        };
+
+        !self.below_fd_limit(idx)
     }
 
     // Call after inserting to `available_indices`, to free any that are contiguous
@@ -100,6 +149,11 @@ impl DescriptorTable {
         self.descriptors.get_mut(&idx)
     }
 
+    /// Iterate over the descriptors and their indices, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&u32, &Descriptor)> {
+        self.descriptors.iter()
+    }
+
     /// Insert a descriptor at `index`. If a descriptor is already present at
     /// that index, it is unregistered from that index and returned.
     pub fn set(&mut self, index: u32, descriptor: Descriptor) -> Option<Descriptor> {
@@ -118,6 +172,22 @@ impl DescriptorTable {
         }
     }
 
+    /// Create a copy of the table for a child process created by `fork`. Each descriptor in
+    /// the new table refers to the same open file as the corresponding descriptor in this table,
+    /// and has the same descriptor flags.
+    pub fn fork(&self) -> Self {
+        DescriptorTable {
+            descriptors: self
+                .descriptors
+                .iter()
+                .map(|(idx, desc)| (*idx, desc.dup(desc.flags())))
+                .collect(),
+            available_indices: self.available_indices.clone(),
+            next_index: self.next_index,
+            fd_limit: self.fd_limit,
+        }
+    }
+
     /// This is a helper function that handles some corner cases where some
     /// descriptors are linked to each other and we must remove that link in
     /// order to ensure that the reference count reaches zero and they are properly
@@ -134,11 +204,31 @@ impl DescriptorTable {
 
     /// Remove and return all descriptors.
     pub fn remove_all<'a>(&mut self) -> impl Iterator<Item = Descriptor> {
-        // reset the descriptor table
-        let old_self = std::mem::replace(self, Self::new());
+        // reset the descriptor table, but keep the fd limit
+        let mut new_self = Self::new();
+        new_self.fd_limit = self.fd_limit;
+        let old_self = std::mem::replace(self, new_self);
         // return the old descriptors
         old_self.descriptors.into_values()
     }
+
+    /// Remove and return all descriptors that have the `FD_CLOEXEC` flag set.
+    pub fn remove_cloexec(&mut self) -> Vec<Descriptor> {
+        let mut indices: Vec<u32> = self
+            .descriptors
+            .iter()
+            .filter(|(_, desc)| desc.flags().contains(DescriptorFlags::CLOEXEC))
+            .map(|(idx, _)| *idx)
+            .collect();
+
+        // remove them in a deterministic order
+        indices.sort_unstable();
+
+        indices
+            .into_iter()
+            .map(|idx| self.remove(idx).unwrap())
+            .collect()
+    }
 }
 
 mod export {
@@ -154,12 +244,32 @@ mod export {
         Box::into_raw(Box::new(DescriptorTable::new()))
     }
 
+    /// Set the soft and hard `RLIMIT_NOFILE` limits of the table.
+    #[no_mangle]
+    pub unsafe extern "C" fn descriptortable_setFdLimit(table: *mut DescriptorTable, limit: u64) {
+        let table = unsafe { table.as_mut().unwrap() };
+        table.set_fd_limit(libc::rlimit {
+            rlim_cur: limit,
+            rlim_max: limit,
+        });
+    }
+
     /// Free the table.
     #[no_mangle]
     pub unsafe extern "C" fn descriptortable_free(table: *mut DescriptorTable) {
         unsafe { Box::from_raw(notnull_mut_debug(table)) };
     }
 
+    /// Create a copy of the table for a child process created by `fork`. When the table is no
+    /// longer required, use descriptortable_free to release the reference.
+    #[no_mangle]
+    pub unsafe extern "C" fn descriptortable_fork(
+        table: *const DescriptorTable,
+    ) -> *mut DescriptorTable {
+        let table = unsafe { table.as_ref().unwrap() };
+        Box::into_raw(Box::new(table.fork()))
+    }
+
     /// Store the given descriptor at the given index. Any previous descriptor that was
     /// stored there will be returned. This consumes a ref to the given descriptor as in
     /// add(), and any returned descriptor must be freed manually.
@@ -203,4 +313,20 @@ mod export {
             }
         });
     }
+
+    /// Close all descriptors that have the `FD_CLOEXEC` flag set, as when the process calls
+    /// `execve`. The `host` option is a legacy option for legacy files.
+    #[no_mangle]
+    pub unsafe extern "C" fn descriptortable_removeAndCloseCloexec(
+        table: *mut DescriptorTable,
+        host: *mut c::Host,
+    ) {
+        let table = unsafe { table.as_mut().unwrap() };
+
+        EventQueue::queue_and_run(|event_queue| {
+            for desc in table.remove_cloexec() {
+                desc.close(host, event_queue);
+            }
+        });
+    }
 }
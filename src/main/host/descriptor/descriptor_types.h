@@ -22,6 +22,7 @@ enum _LegacyFileType {
 typedef struct _LegacyFile LegacyFile;
 typedef struct _LegacyFileFunctionTable LegacyFileFunctionTable;
 
+#include "main/bindings/c/bindings-opaque.h"
 #include "main/core/support/definitions.h"
 #include "main/host/status.h"
 #include "main/utility/utility.h"
@@ -47,6 +48,8 @@ struct _LegacyFile {
     LegacyFileType type;
     Status status;
     GHashTable* listeners;
+    // Notifies rust listeners of status changes, alongside the C `listeners`.
+    StateEventSource* eventSource;
     gint refCountStrong;
     gint refCountWeak;
     gint flags;
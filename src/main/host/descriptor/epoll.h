@@ -13,6 +13,8 @@
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/host.h"
 
+// This legacy epoll is only used internally by the poll and select syscall handlers. The epoll
+// syscalls use the rust `Epoll` file type.
 typedef struct _Epoll Epoll;
 
 /* free this with legacyfile_free() */
@@ -0,0 +1,625 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Weak};
+
+use atomic_refcell::AtomicRefCell;
+use nix::errno::Errno;
+use nix::sys::epoll::EpollFlags;
+
+use crate::cshadow as c;
+use crate::host::descriptor::{
+    CountedLegacyFileRef, File, FileMode, FileState, FileStatus, StateEventSource,
+    StateListenerFilter,
+};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall_types::{PluginPtr, SyscallError, SyscallResult};
+use crate::utility::event_queue::{EventQueue, Handle};
+use crate::utility::HostTreePointer;
+
+/// The maximum depth of nested epoll instances. Linux uses the same limit (`EP_MAX_NESTS`).
+const MAX_NESTING_DEPTH: usize = 4;
+
+/// The events that may be combined with `EPOLLEXCLUSIVE`. From epoll_ctl(2): "EPOLLEXCLUSIVE may
+/// be used only with the following values: EPOLLIN, EPOLLOUT, EPOLLWAKEUP, and EPOLLET. EPOLLHUP
+/// and EPOLLERR can also be specified, but this is not required".
+const EXCLUSIVE_ALLOWED: EpollFlags = EpollFlags::from_bits_truncate(
+    EpollFlags::EPOLLIN.bits()
+        | EpollFlags::EPOLLOUT.bits()
+        | EpollFlags::EPOLLERR.bits()
+        | EpollFlags::EPOLLHUP.bits()
+        | EpollFlags::EPOLLWAKEUP.bits()
+        | EpollFlags::EPOLLET.bits()
+        | EpollFlags::EPOLLEXCLUSIVE.bits(),
+);
+
+/// A file that can be watched by an epoll instance.
+#[derive(Clone, Debug)]
+pub enum EpollTarget {
+    File(File),
+    Legacy(CountedLegacyFileRef),
+}
+
+impl EpollTarget {
+    fn canonical_handle(&self) -> usize {
+        match self {
+            Self::File(file) => file.canonical_handle(),
+            Self::Legacy(file) => (unsafe { file.ptr() }) as usize,
+        }
+    }
+
+    fn state(&self) -> FileState {
+        match self {
+            Self::File(file) => file.borrow().state(),
+            Self::Legacy(file) => unsafe { c::legacyfile_getStatus(file.ptr()) }.into(),
+        }
+    }
+
+    fn add_listener(
+        &self,
+        notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState)> {
+        let monitoring = Entry::MONITORING;
+        let filter = StateListenerFilter::Always;
+
+        match self {
+            Self::File(file) => file
+                .borrow_mut()
+                .add_listener(monitoring, filter, notify_fn),
+            Self::Legacy(file) => {
+                let source = unsafe { c::legacyfile_getEventSource(file.ptr()) };
+                let source = unsafe { source.as_mut() }.unwrap();
+                source.add_listener(monitoring, filter, notify_fn)
+            }
+        }
+    }
+
+    fn add_exclusive_listener(
+        &self,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        let monitoring = Entry::MONITORING;
+        let filter = StateListenerFilter::Always;
+
+        match self {
+            Self::File(file) => file
+                .borrow_mut()
+                .add_exclusive_listener(monitoring, filter, notify_fn),
+            Self::Legacy(file) => {
+                let source = unsafe { c::legacyfile_getEventSource(file.ptr()) };
+                let source = unsafe { source.as_mut() }.unwrap();
+                source.add_exclusive_listener(monitoring, filter, notify_fn)
+            }
+        }
+    }
+}
+
+/// Identifies an entry in the interest list. Like Linux, an entry is keyed by both the file
+/// descriptor and the file it referred to when it was added.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    fd: i32,
+    handle: usize,
+}
+
+struct Entry {
+    target: EpollTarget,
+    /// Uniquely identifies this entry so that stale notifications for a previous entry with the
+    /// same key are ignored.
+    id: u64,
+    /// The events and flags given by the user.
+    interest: EpollFlags,
+    /// The user data returned with each event.
+    data: u64,
+    /// The most recent state of the target.
+    state: FileState,
+    /// Events that have occurred but not been collected. Only used for edge-triggered entries.
+    pending: EpollFlags,
+    /// Set when a one-shot entry has reported an event; cleared by `EPOLL_CTL_MOD`.
+    disabled: bool,
+    /// The entry's position in the ready list, if it's in the ready list.
+    ready_position: Option<u64>,
+    _listener: Option<Handle<(FileState, FileState)>>,
+    _exclusive_listener: Option<Handle<(FileState, FileState, u64)>>,
+}
+
+impl Entry {
+    /// The target states that an entry listens for.
+    const MONITORING: FileState = FileState::from_bits_truncate(
+        FileState::ACTIVE.bits()
+            | FileState::READABLE.bits()
+            | FileState::WRITABLE.bits()
+            | FileState::CLOSED.bits(),
+    );
+
+    /// The events that are available from the target's current state.
+    fn available(&self) -> EpollFlags {
+        let mut available = EpollFlags::empty();
+        available.set(
+            EpollFlags::EPOLLIN,
+            self.state.contains(FileState::READABLE),
+        );
+        available.set(
+            EpollFlags::EPOLLOUT,
+            self.state.contains(FileState::WRITABLE),
+        );
+        available & self.interest
+    }
+
+    /// The events that would be reported for this entry right now.
+    fn ready_events(&self) -> EpollFlags {
+        if self.disabled
+            || !self.state.contains(FileState::ACTIVE)
+            || self.state.contains(FileState::CLOSED)
+        {
+            return EpollFlags::empty();
+        }
+
+        if self.interest.contains(EpollFlags::EPOLLET) {
+            self.available() & self.pending
+        } else {
+            self.available()
+        }
+    }
+
+    fn is_exclusive(&self) -> bool {
+        self.interest.contains(EpollFlags::EPOLLEXCLUSIVE)
+    }
+}
+
+/// An epoll instance. Entries are watched through their target's `StateEventSource`, and
+/// reported in the order that they became ready.
+pub struct Epoll {
+    event_source: StateEventSource,
+    status: FileStatus,
+    state: FileState,
+    // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file
+    has_open_file: bool,
+    /// The interest list.
+    entries: HashMap<Key, Entry>,
+    /// Entries that have events to report, ordered by when they became ready.
+    ready: BTreeMap<u64, Key>,
+    /// The next position in the ready list.
+    ready_counter: u64,
+    /// The id to give the next entry.
+    next_entry_id: u64,
+}
+
+impl Epoll {
+    pub fn new(status: FileStatus) -> Self {
+        Self {
+            event_source: StateEventSource::new(),
+            status,
+            state: FileState::ACTIVE,
+            has_open_file: false,
+            entries: HashMap::new(),
+            ready: BTreeMap::new(),
+            ready_counter: 0,
+            next_entry_id: 0,
+        }
+    }
+
+    pub fn get_status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ | FileMode::WRITE
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        false
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        // drops all of the listeners on the watched files
+        self.entries.clear();
+        self.ready.clear();
+
+        // set the closed flag and remove the active and readable flags
+        self.copy_state(
+            FileState::CLOSED | FileState::ACTIVE | FileState::READABLE,
+            FileState::CLOSED,
+            event_queue,
+        );
+
+        Ok(())
+    }
+
+    pub fn read<W>(
+        &mut self,
+        _bytes: W,
+        _offset: libc::off_t,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        // epoll(7): "the epoll file descriptor [...] can't be read from or written to"
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn write<R>(
+        &mut self,
+        _bytes: R,
+        _offset: libc::off_t,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: u64,
+        _arg_ptr: PluginPtr,
+        _memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        log::warn!("We do not yet handle ioctl request {} on epolls", request);
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState)> {
+        self.event_source
+            .add_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        self.event_source
+            .add_exclusive_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    /// Add, modify, or remove an entry in the interest list of `epoll`. The `events` and `data`
+    /// are ignored for `EPOLL_CTL_DEL`.
+    pub fn ctl(
+        epoll: &Arc<AtomicRefCell<Self>>,
+        op: libc::c_int,
+        fd: i32,
+        target: EpollTarget,
+        events: EpollFlags,
+        data: u64,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let epoll_handle = Arc::as_ptr(epoll) as usize;
+        let key = Key {
+            fd,
+            handle: target.canonical_handle(),
+        };
+
+        // epoll_ctl(2): "EINVAL: epfd is not an epoll file descriptor, or fd is the same as epfd"
+        if key.handle == epoll_handle {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let target_is_epoll = matches!(target, EpollTarget::File(File::Epoll(_)));
+
+        if op == libc::EPOLL_CTL_ADD && target_is_epoll {
+            Self::check_nesting(&target, epoll_handle)?;
+        }
+
+        let mut epoll_ref = epoll.borrow_mut();
+
+        match op {
+            libc::EPOLL_CTL_ADD => {
+                if epoll_ref.entries.contains_key(&key) {
+                    return Err(Errno::EEXIST.into());
+                }
+
+                if events.contains(EpollFlags::EPOLLEXCLUSIVE)
+                    && (target_is_epoll || !EXCLUSIVE_ALLOWED.contains(events))
+                {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let id = epoll_ref.next_entry_id;
+                epoll_ref.next_entry_id += 1;
+
+                let weak = Arc::downgrade(epoll);
+                let (listener, exclusive_listener) = if events.contains(EpollFlags::EPOLLEXCLUSIVE)
+                {
+                    let handle = target.add_exclusive_listener(
+                        move |state, changed, wakeup_available, event_queue| {
+                            Self::notify_weak(
+                                &weak,
+                                key,
+                                id,
+                                state,
+                                changed,
+                                wakeup_available,
+                                event_queue,
+                            )
+                        },
+                    );
+                    (None, Some(handle))
+                } else {
+                    let handle = target.add_listener(move |state, changed, event_queue| {
+                        Self::notify_weak(&weak, key, id, state, changed, true, event_queue);
+                    });
+                    (Some(handle), None)
+                };
+
+                let entry = Entry {
+                    state: target.state(),
+                    target,
+                    id,
+                    interest: events,
+                    data,
+                    // report the current state once, even for edge-triggered entries
+                    pending: EpollFlags::all(),
+                    disabled: false,
+                    ready_position: None,
+                    _listener: listener,
+                    _exclusive_listener: exclusive_listener,
+                };
+
+                epoll_ref.entries.insert(key, entry);
+                epoll_ref.refresh_entry(key, true);
+            }
+            libc::EPOLL_CTL_MOD => {
+                let entry = epoll_ref.entries.get_mut(&key).ok_or(Errno::ENOENT)?;
+
+                // epoll_ctl(2): "EINVAL: An invalid event type was specified along with
+                // EPOLLEXCLUSIVE in events", and "EINVAL: op was EPOLL_CTL_MOD and events included
+                // EPOLLEXCLUSIVE", and "EINVAL: op was EPOLL_CTL_MOD and the EPOLLEXCLUSIVE flag
+                // has previously been applied to this epfd, fd pair"
+                if events.contains(EpollFlags::EPOLLEXCLUSIVE) || entry.is_exclusive() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                entry.interest = events;
+                entry.data = data;
+                entry.state = entry.target.state();
+                entry.pending = EpollFlags::all();
+                entry.disabled = false;
+
+                epoll_ref.refresh_entry(key, true);
+            }
+            libc::EPOLL_CTL_DEL => {
+                let entry = epoll_ref.entries.remove(&key).ok_or(Errno::ENOENT)?;
+                if let Some(position) = entry.ready_position {
+                    epoll_ref.ready.remove(&position);
+                }
+            }
+            _ => return Err(Errno::EINVAL.into()),
+        }
+
+        epoll_ref.update_state(event_queue);
+
+        Ok(())
+    }
+
+    /// Returns an error if adding the epoll `target` to the epoll with canonical handle
+    /// `epoll_handle` would create a cycle, or would nest epoll instances too deeply.
+    fn check_nesting(target: &EpollTarget, epoll_handle: usize) -> Result<(), Errno> {
+        fn helper(file: &File, epoll_handle: usize, depth: usize) -> Result<(), Errno> {
+            let nested = match file {
+                File::Epoll(nested) => nested,
+                _ => return Ok(()),
+            };
+
+            if depth > MAX_NESTING_DEPTH || Arc::as_ptr(nested) as usize == epoll_handle {
+                return Err(Errno::ELOOP);
+            }
+
+            for entry in nested.borrow().entries.values() {
+                if let EpollTarget::File(file) = &entry.target {
+                    helper(file, epoll_handle, depth + 1)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        match target {
+            EpollTarget::File(file) => helper(file, epoll_handle, 1),
+            EpollTarget::Legacy(_) => Ok(()),
+        }
+    }
+
+    /// Collect up to `max_events` ready events in the order that their entries became ready.
+    /// Returns the events and their user data.
+    pub fn collect_ready_events(
+        &mut self,
+        max_events: usize,
+        event_queue: &mut EventQueue,
+    ) -> Vec<(EpollFlags, u64)> {
+        let keys: Vec<Key> = self.ready.values().take(max_events).copied().collect();
+        let mut events = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let entry = self.entries.get_mut(&key).unwrap();
+            let ready = entry.ready_events();
+            debug_assert!(!ready.is_empty());
+
+            events.push((ready, entry.data));
+
+            if entry.interest.contains(EpollFlags::EPOLLET) {
+                entry.pending.remove(ready);
+            }
+            if entry.interest.contains(EpollFlags::EPOLLONESHOT) {
+                entry.disabled = true;
+            }
+
+            // level-triggered entries that are still ready move to the back of the ready list so
+            // that other entries aren't starved
+            self.remove_from_ready(key);
+            self.refresh_entry(key, true);
+        }
+
+        self.update_state(event_queue);
+
+        events
+    }
+
+    /// Notify the epoll at `weak` (if it still exists) that an entry's target changed state.
+    fn notify_weak(
+        weak: &Weak<AtomicRefCell<Self>>,
+        key: Key,
+        id: u64,
+        state: FileState,
+        changed: FileState,
+        wakeup_available: bool,
+        event_queue: &mut EventQueue,
+    ) -> bool {
+        match weak.upgrade() {
+            Some(epoll) => epoll.borrow_mut().notify_entry(
+                key,
+                id,
+                state,
+                changed,
+                wakeup_available,
+                event_queue,
+            ),
+            None => false,
+        }
+    }
+
+    /// Update an entry after its target changed state. If `wakeup_available` is false, the entry
+    /// won't be added to the ready list. Returns true if the entry became ready and there is a
+    /// thread waiting on this epoll.
+    fn notify_entry(
+        &mut self,
+        key: Key,
+        id: u64,
+        state: FileState,
+        changed: FileState,
+        wakeup_available: bool,
+        event_queue: &mut EventQueue,
+    ) -> bool {
+        let entry = match self.entries.get_mut(&key) {
+            Some(entry) if entry.id == id => entry,
+            // the entry was removed
+            _ => return false,
+        };
+
+        // epoll(7): "a file descriptor is removed from all epoll interest lists only after all
+        // the file descriptors referring to the underlying open file description have been closed"
+        if state.contains(FileState::CLOSED) {
+            self.remove_from_ready(key);
+            self.entries.remove(&key);
+            self.update_state(event_queue);
+            return false;
+        }
+
+        entry.state = state;
+
+        // record any new edges
+        let mut edges = EpollFlags::empty();
+        edges.set(
+            EpollFlags::EPOLLIN,
+            (state & changed).contains(FileState::READABLE),
+        );
+        edges.set(
+            EpollFlags::EPOLLOUT,
+            (state & changed).contains(FileState::WRITABLE),
+        );
+        entry.pending.insert(edges);
+
+        let became_ready = self.refresh_entry(key, wakeup_available);
+        self.update_state(event_queue);
+
+        became_ready && self.event_source.has_listeners()
+    }
+
+    /// Add the entry to or remove it from the ready list to match its ready events. If `may_add`
+    /// is false, the entry will not be added. Returns true if the entry was added.
+    fn refresh_entry(&mut self, key: Key, may_add: bool) -> bool {
+        let entry = self.entries.get_mut(&key).unwrap();
+        let is_ready = !entry.ready_events().is_empty();
+
+        match (entry.ready_position, is_ready) {
+            (None, true) if may_add => {
+                let position = self.ready_counter;
+                self.ready_counter += 1;
+                entry.ready_position = Some(position);
+                self.ready.insert(position, key);
+                true
+            }
+            (Some(_), false) => {
+                self.remove_from_ready(key);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn remove_from_ready(&mut self, key: Key) {
+        if let Some(entry) = self.entries.get_mut(&key) {
+            if let Some(position) = entry.ready_position.take() {
+                self.ready.remove(&position);
+            }
+        }
+    }
+
+    fn update_state(&mut self, event_queue: &mut EventQueue) {
+        if self.state.contains(FileState::CLOSED) {
+            return;
+        }
+
+        // an epoll is readable when it has events to report
+        let readable = if self.ready.is_empty() {
+            FileState::empty()
+        } else {
+            FileState::READABLE
+        };
+
+        self.copy_state(FileState::READABLE, readable, event_queue);
+    }
+
+    fn copy_state(&mut self, mask: FileState, state: FileState, event_queue: &mut EventQueue) {
+        let old_state = self.state;
+
+        // remove the masked flags, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, event_queue);
+    }
+
+    fn handle_state_change(&mut self, old_state: FileState, event_queue: &mut EventQueue) {
+        let states_changed = self.state ^ old_state;
+
+        // if nothing changed
+        if states_changed.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, event_queue);
+    }
+}
@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
@@ -12,10 +13,13 @@ use crate::utility::{HostTreePointer, IsSend, IsSync};
 use socket::{Socket, SocketRef, SocketRefMut};
 
 pub mod descriptor_table;
+pub mod epoll;
 pub mod eventfd;
 pub mod pipe;
 pub mod shared_buf;
+pub mod signalfd;
 pub mod socket;
+pub mod timerfd;
 
 bitflags::bitflags! {
     /// These are flags that can potentially be changed from the plugin (analagous to the Linux
@@ -112,6 +116,8 @@ bitflags::bitflags! {
         /// A listening socket is allowing connections. Only applicable to connection-oriented unix
         /// sockets.
         const SOCKET_ALLOWING_CONNECT = c::_Status_STATUS_SOCKET_ALLOWING_CONNECT;
+        /// A child process changed state. Only applicable to processes.
+        const CHILD_EVENT = c::_Status_STATUS_CHILD_EVENT;
     }
 }
 
@@ -136,6 +142,28 @@ pub enum StateListenerFilter {
     Always,
 }
 
+impl StateListenerFilter {
+    /// Returns true if a listener monitoring the `monitoring` bits should be notified of a change
+    /// in the `changed` bits.
+    fn should_notify(&self, monitoring: FileState, state: FileState, changed: FileState) -> bool {
+        // true if any of the bits we're monitoring have changed
+        let flipped = monitoring.intersects(changed);
+
+        // true if any of the bits we're monitoring are set
+        let on = monitoring.intersects(state);
+
+        match self {
+            // at least one monitored bit is on, and at least one has changed
+            Self::OffToOn => flipped && on,
+            // all monitored bits are off, and at least one has changed
+            Self::OnToOff => flipped && !on,
+            // at least one monitored bit has changed
+            Self::Always => flipped,
+            Self::Never => false,
+        }
+    }
+}
+
 /// A wrapper for a `*mut c::StatusListener` that increments its ref count when created,
 /// and decrements when dropped.
 struct LegacyListener(HostTreePointer<c::StatusListener>);
@@ -211,6 +239,13 @@ impl LegacyListenerHelper {
 /// the monitored bits have changed and if the change the filter is satisfied.
 pub struct StateEventSource {
     inner: EventSource<(FileState, FileState)>,
+    /// Listeners which share a single wakeup per notification. The `u64` is the notification's
+    /// generation.
+    exclusive: EventSource<(FileState, FileState, u64)>,
+    /// The generation of the most recent notification sent to the exclusive listeners.
+    exclusive_generation: u64,
+    /// The generation of the most recent notification that an exclusive listener has claimed.
+    exclusive_claimed: Arc<AtomicU64>,
     legacy_helper: LegacyListenerHelper,
 }
 
@@ -218,6 +253,9 @@ impl StateEventSource {
     pub fn new() -> Self {
         Self {
             inner: EventSource::new(),
+            exclusive: EventSource::new(),
+            exclusive_generation: 0,
+            exclusive_claimed: Arc::new(AtomicU64::new(0)),
             legacy_helper: LegacyListenerHelper::new(),
         }
     }
@@ -230,23 +268,7 @@ impl StateEventSource {
     ) -> Handle<(FileState, FileState)> {
         self.inner
             .add_listener(move |(state, changed), event_queue| {
-                // true if any of the bits we're monitoring have changed
-                let flipped = monitoring.intersects(changed);
-
-                // true if any of the bits we're monitoring are set
-                let on = monitoring.intersects(state);
-
-                let notify = match filter {
-                    // at least one monitored bit is on, and at least one has changed
-                    StateListenerFilter::OffToOn => flipped && on,
-                    // all monitored bits are off, and at least one has changed
-                    StateListenerFilter::OnToOff => flipped && !on,
-                    // at least one monitored bit has changed
-                    StateListenerFilter::Always => flipped,
-                    StateListenerFilter::Never => false,
-                };
-
-                if !notify {
+                if !filter.should_notify(monitoring, state, changed) {
                     return;
                 }
 
@@ -254,6 +276,34 @@ impl StateEventSource {
             })
     }
 
+    /// Add a listener that is notified after all non-exclusive listeners. Exclusive listeners
+    /// share a single wakeup per notification: each is told whether the wakeup is still available,
+    /// and the first to return `true` (for example if it woke a waiting thread) claims it. Later
+    /// exclusive listeners are still notified of the state change, but with the wakeup no longer
+    /// available. This is used to implement `EPOLLEXCLUSIVE`.
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        let claimed = Arc::clone(&self.exclusive_claimed);
+
+        self.exclusive
+            .add_listener(move |(state, changed, generation), event_queue| {
+                if !filter.should_notify(monitoring, state, changed) {
+                    return;
+                }
+
+                // has a previous exclusive listener already claimed this notification?
+                let available = claimed.load(Ordering::Relaxed) != generation;
+
+                if (notify_fn)(state, changed, available, event_queue) && available {
+                    claimed.store(generation, Ordering::Relaxed);
+                }
+            })
+    }
+
     pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
         self.legacy_helper.add_listener(ptr, &mut self.inner);
     }
@@ -262,13 +312,24 @@ impl StateEventSource {
         self.legacy_helper.remove_listener(ptr);
     }
 
+    /// Returns true if there are any non-exclusive listeners (including legacy listeners).
+    pub fn has_listeners(&self) -> bool {
+        self.inner.has_listeners()
+    }
+
     pub fn notify_listeners(
         &mut self,
         state: FileState,
         changed: FileState,
         event_queue: &mut EventQueue,
     ) {
-        self.inner.notify_listeners((state, changed), event_queue)
+        self.inner.notify_listeners((state, changed), event_queue);
+
+        if self.exclusive.has_listeners() {
+            self.exclusive_generation += 1;
+            self.exclusive
+                .notify_listeners((state, changed, self.exclusive_generation), event_queue);
+        }
     }
 }
 
@@ -278,6 +339,9 @@ pub enum File {
     Pipe(Arc<AtomicRefCell<pipe::Pipe>>),
     EventFd(Arc<AtomicRefCell<eventfd::EventFd>>),
     Socket(Socket),
+    Epoll(Arc<AtomicRefCell<epoll::Epoll>>),
+    TimerFd(Arc<AtomicRefCell<timerfd::TimerFd>>),
+    SignalFd(Arc<AtomicRefCell<signalfd::SignalFd>>),
 }
 
 // will not compile if `File` is not Send + Sync
@@ -290,6 +354,9 @@ impl File {
             Self::Pipe(ref f) => FileRef::Pipe(f.borrow()),
             Self::EventFd(ref f) => FileRef::EventFd(f.borrow()),
             Self::Socket(ref f) => FileRef::Socket(f.borrow()),
+            Self::Epoll(ref f) => FileRef::Epoll(f.borrow()),
+            Self::TimerFd(ref f) => FileRef::TimerFd(f.borrow()),
+            Self::SignalFd(ref f) => FileRef::SignalFd(f.borrow()),
         }
     }
 
@@ -298,6 +365,9 @@ impl File {
             Self::Pipe(ref f) => FileRef::Pipe(f.try_borrow()?),
             Self::EventFd(ref f) => FileRef::EventFd(f.try_borrow()?),
             Self::Socket(ref f) => FileRef::Socket(f.try_borrow()?),
+            Self::Epoll(ref f) => FileRef::Epoll(f.try_borrow()?),
+            Self::TimerFd(ref f) => FileRef::TimerFd(f.try_borrow()?),
+            Self::SignalFd(ref f) => FileRef::SignalFd(f.try_borrow()?),
         })
     }
 
@@ -306,6 +376,9 @@ impl File {
             Self::Pipe(ref f) => FileRefMut::Pipe(f.borrow_mut()),
             Self::EventFd(ref f) => FileRefMut::EventFd(f.borrow_mut()),
             Self::Socket(ref f) => FileRefMut::Socket(f.borrow_mut()),
+            Self::Epoll(ref f) => FileRefMut::Epoll(f.borrow_mut()),
+            Self::TimerFd(ref f) => FileRefMut::TimerFd(f.borrow_mut()),
+            Self::SignalFd(ref f) => FileRefMut::SignalFd(f.borrow_mut()),
         }
     }
 
@@ -314,6 +387,9 @@ impl File {
             Self::Pipe(ref f) => FileRefMut::Pipe(f.try_borrow_mut()?),
             Self::EventFd(ref f) => FileRefMut::EventFd(f.try_borrow_mut()?),
             Self::Socket(ref f) => FileRefMut::Socket(f.try_borrow_mut()?),
+            Self::Epoll(ref f) => FileRefMut::Epoll(f.try_borrow_mut()?),
+            Self::TimerFd(ref f) => FileRefMut::TimerFd(f.try_borrow_mut()?),
+            Self::SignalFd(ref f) => FileRefMut::SignalFd(f.try_borrow_mut()?),
         })
     }
 
@@ -322,6 +398,9 @@ impl File {
             Self::Pipe(f) => Arc::as_ptr(f) as usize,
             Self::EventFd(f) => Arc::as_ptr(f) as usize,
             Self::Socket(ref f) => f.canonical_handle(),
+            Self::Epoll(f) => Arc::as_ptr(f) as usize,
+            Self::TimerFd(f) => Arc::as_ptr(f) as usize,
+            Self::SignalFd(f) => Arc::as_ptr(f) as usize,
         }
     }
 }
@@ -332,6 +411,9 @@ impl std::fmt::Debug for File {
             Self::Pipe(_) => write!(f, "Pipe")?,
             Self::EventFd(_) => write!(f, "EventFd")?,
             Self::Socket(_) => write!(f, "Socket")?,
+            Self::Epoll(_) => write!(f, "Epoll")?,
+            Self::TimerFd(_) => write!(f, "TimerFd")?,
+            Self::SignalFd(_) => write!(f, "SignalFd")?,
         }
 
         if let Ok(file) = self.try_borrow() {
@@ -351,73 +433,95 @@ pub enum FileRef<'a> {
     Pipe(atomic_refcell::AtomicRef<'a, pipe::Pipe>),
     EventFd(atomic_refcell::AtomicRef<'a, eventfd::EventFd>),
     Socket(SocketRef<'a>),
+    Epoll(atomic_refcell::AtomicRef<'a, epoll::Epoll>),
+    TimerFd(atomic_refcell::AtomicRef<'a, timerfd::TimerFd>),
+    SignalFd(atomic_refcell::AtomicRef<'a, signalfd::SignalFd>),
 }
 
 pub enum FileRefMut<'a> {
     Pipe(atomic_refcell::AtomicRefMut<'a, pipe::Pipe>),
     EventFd(atomic_refcell::AtomicRefMut<'a, eventfd::EventFd>),
     Socket(SocketRefMut<'a>),
+    Epoll(atomic_refcell::AtomicRefMut<'a, epoll::Epoll>),
+    TimerFd(atomic_refcell::AtomicRefMut<'a, timerfd::TimerFd>),
+    SignalFd(atomic_refcell::AtomicRefMut<'a, signalfd::SignalFd>),
 }
 
 impl FileRef<'_> {
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn get_status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn supports_sa_restart(&self) -> bool
     );
 }
 
 impl FileRefMut<'_> {
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn get_status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn supports_sa_restart(&self) -> bool
     );
-    enum_passthrough!(self, (val), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (val), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn set_has_open_file(&mut self, val: bool)
     );
-    enum_passthrough!(self, (event_queue), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (event_queue), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError>
     );
-    enum_passthrough!(self, (status), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (status), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn set_status(&mut self, status: FileStatus)
     );
-    enum_passthrough!(self, (request, arg_ptr, memory_manager), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (request, arg_ptr, memory_manager), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn ioctl(&mut self, request: u64, arg_ptr: PluginPtr, memory_manager: &mut MemoryManager) -> SyscallResult
     );
-    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (monitoring, filter, notify_fn), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
+        pub fn add_listener(
+            &mut self,
+            monitoring: FileState,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+        ) -> Handle<(FileState, FileState)>
+    );
+    enum_passthrough!(self, (monitoring, filter, notify_fn), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
+        pub fn add_exclusive_listener(
+            &mut self,
+            monitoring: FileState,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+        ) -> Handle<(FileState, FileState, u64)>
+    );
+    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>)
     );
-    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket;
+    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener)
     );
 
-    enum_passthrough_generic!(self, (bytes, offset, event_queue), Pipe, EventFd, Socket;
+    enum_passthrough_generic!(self, (bytes, offset, event_queue), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn read<W>(&mut self, bytes: W, offset: libc::off_t, event_queue: &mut EventQueue) -> SyscallResult
         where W: std::io::Write + std::io::Seek
     );
 
-    enum_passthrough_generic!(self, (source, offset, event_queue), Pipe, EventFd, Socket;
+    enum_passthrough_generic!(self, (source, offset, event_queue), Pipe, EventFd, Socket, Epoll, TimerFd, SignalFd;
         pub fn write<R>(&mut self, source: R, offset: libc::off_t, event_queue: &mut EventQueue) -> SyscallResult
         where R: std::io::Read + std::io::Seek
     );
@@ -429,6 +533,9 @@ impl std::fmt::Debug for FileRef<'_> {
             Self::Pipe(_) => write!(f, "Pipe")?,
             Self::EventFd(_) => write!(f, "EventFd")?,
             Self::Socket(_) => write!(f, "Socket")?,
+            Self::Epoll(_) => write!(f, "Epoll")?,
+            Self::TimerFd(_) => write!(f, "TimerFd")?,
+            Self::SignalFd(_) => write!(f, "SignalFd")?,
         }
 
         write!(
@@ -446,6 +553,9 @@ impl std::fmt::Debug for FileRefMut<'_> {
             Self::Pipe(_) => write!(f, "Pipe")?,
             Self::EventFd(_) => write!(f, "EventFd")?,
             Self::Socket(_) => write!(f, "Socket")?,
+            Self::Epoll(_) => write!(f, "Epoll")?,
+            Self::TimerFd(_) => write!(f, "TimerFd")?,
+            Self::SignalFd(_) => write!(f, "SignalFd")?,
         }
 
         write!(
@@ -715,6 +825,12 @@ impl LegacyFileCounter {
         unsafe { self.file.ptr() }
     }
 
+    /// A counted reference to the legacy file. Holding this reference does not keep the legacy
+    /// file open.
+    pub fn file(&self) -> &CountedLegacyFileRef {
+        &self.file
+    }
+
     /// Close the descriptor, and if this is the last descriptor pointing to its legacy file, close
     /// the legacy file as well.
     pub fn close(self, host: *mut c::Host) {
@@ -978,6 +1094,38 @@ mod export {
 
         file.canonical_handle()
     }
+
+    /// Create a new `StateEventSource`, which legacy files use to notify rust listeners of their
+    /// status changes. The source must later be passed to `stateeventsource_free()`.
+    #[no_mangle]
+    pub extern "C" fn stateeventsource_new() -> *mut StateEventSource {
+        Box::into_raw(Box::new(StateEventSource::new()))
+    }
+
+    /// Free the `StateEventSource`. Any listeners are dropped.
+    #[no_mangle]
+    pub extern "C" fn stateeventsource_free(source: *mut StateEventSource) {
+        assert!(!source.is_null());
+
+        unsafe { Box::from_raw(source) };
+    }
+
+    /// Notify the listeners of the `StateEventSource` of a status change. The listeners will have
+    /// run by the time this function returns.
+    #[no_mangle]
+    pub extern "C" fn stateeventsource_notifyListeners(
+        source: *mut StateEventSource,
+        status: c::Status,
+        changed: c::Status,
+    ) {
+        assert!(!source.is_null());
+
+        let source = unsafe { &mut *source };
+
+        EventQueue::queue_and_run(|event_queue| {
+            source.notify_listeners(status.into(), changed.into(), event_queue)
+        });
+    }
 }
 
 #[cfg(test)]
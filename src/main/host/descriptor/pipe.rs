@@ -10,11 +10,18 @@ use crate::host::descriptor::{
     FileMode, FileState, FileStatus, StateEventSource, StateListenerFilter,
 };
 use crate::host::memory_manager::MemoryManager;
-use crate::host::syscall_types::{PluginPtr, SyscallError, SyscallResult};
+use crate::host::syscall_types::{PluginPtr, SyscallError, SyscallResult, TypedPluginPtr};
 use crate::utility::event_queue::{EventQueue, Handle};
 use crate::utility::stream_len::StreamLen;
 use crate::utility::HostTreePointer;
 
+/// The maximum capacity that an unprivileged process can give a pipe using `F_SETPIPE_SZ`. This is
+/// the default value of `/proc/sys/fs/pipe-max-size` in Linux.
+pub const PIPE_MAX_SIZE: usize = 1024 * 1024;
+
+/// Pipe capacities are a power-of-two number of pages.
+const PIPE_PAGE_SIZE: usize = 4096;
+
 pub struct Pipe {
     buffer: Option<Arc<AtomicRefCell<SharedBuf>>>,
     event_source: StateEventSource,
@@ -76,6 +83,43 @@ impl Pipe {
         self.buffer.as_ref().unwrap().borrow().max_len()
     }
 
+    /// Change the capacity of the pipe, following the rules of `fcntl(F_SETPIPE_SZ)`. Returns the
+    /// new capacity, which may be larger than `size`.
+    pub fn set_max_size(
+        &mut self,
+        size: libc::c_uint,
+        event_queue: &mut EventQueue,
+    ) -> Result<usize, SyscallError> {
+        let size = round_pipe_size(size).ok_or(Errno::EINVAL)?;
+
+        let mut buffer = self.buffer.as_ref().unwrap().borrow_mut();
+
+        // fcntl(2): "Attempts to set the pipe capacity above the limit yield the error EPERM";
+        // linux still allows an oversized pipe to keep its current size
+        if size > PIPE_MAX_SIZE && size > buffer.max_len() {
+            return Err(Errno::EPERM.into());
+        }
+
+        // returns EBUSY if the data in the pipe doesn't fit in the new capacity
+        buffer.set_max_len(size, event_queue)?;
+
+        Ok(size)
+    }
+
+    /// The number of bytes that can be written before the pipe is full.
+    pub fn space_available(&self) -> usize {
+        self.buffer.as_ref().unwrap().borrow().space_available()
+    }
+
+    /// Returns true if both pipes use the same buffer, for example the read and write ends of the
+    /// same pipe.
+    pub fn shares_buffer_with(&self, other: &Pipe) -> bool {
+        Arc::ptr_eq(
+            self.buffer.as_ref().unwrap(),
+            other.buffer.as_ref().unwrap(),
+        )
+    }
+
     pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
         if self.state.contains(FileState::CLOSED) {
             log::warn!("Attempting to close an already-closed pipe");
@@ -155,6 +199,27 @@ impl Pipe {
         }
     }
 
+    /// Like [`read()`](Self::read), but the data is not removed from the pipe.
+    pub fn peek<W>(&self, mut bytes: W) -> SyscallResult
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        // if the file is not open for reading, return EBADF
+        if !self.mode.contains(FileMode::READ) {
+            return Err(nix::errno::Errno::EBADF.into());
+        }
+
+        let buffer = self.buffer.as_ref().unwrap().borrow();
+        let (num_copied, _num_in_buf) = buffer.peek(&mut bytes)?;
+
+        // the peek would block under the same conditions as a read
+        if num_copied == 0 && bytes.stream_len_bp()? != 0 && buffer.num_writers() > 0 {
+            Err(Errno::EWOULDBLOCK.into())
+        } else {
+            Ok(num_copied.into())
+        }
+    }
+
     pub fn write<R>(
         &mut self,
         mut bytes: R,
@@ -231,11 +296,25 @@ impl Pipe {
     pub fn ioctl(
         &mut self,
         request: u64,
-        _arg_ptr: PluginPtr,
-        _memory_manager: &mut MemoryManager,
+        arg_ptr: PluginPtr,
+        memory_manager: &mut MemoryManager,
     ) -> SyscallResult {
-        log::warn!("We do not yet handle ioctl request {} on pipes", request);
-        Err(Errno::EINVAL.into())
+        match request {
+            // the number of unread bytes in the pipe
+            libc::FIONREAD => {
+                let num_bytes = self.buffer.as_ref().unwrap().borrow().num_bytes();
+                let num_bytes = libc::c_int::try_from(num_bytes).unwrap();
+
+                let arg_ptr = TypedPluginPtr::new::<libc::c_int>(arg_ptr, 1);
+                memory_manager.copy_to_ptr(arg_ptr, &[num_bytes])?;
+
+                Ok(0.into())
+            }
+            _ => {
+                log::warn!("We do not yet handle ioctl request {} on pipes", request);
+                Err(Errno::EINVAL.into())
+            }
+        }
     }
 
     pub fn connect_to_buffer(
@@ -315,6 +394,16 @@ impl Pipe {
             .add_listener(monitoring, filter, notify_fn)
     }
 
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        self.event_source
+            .add_exclusive_listener(monitoring, filter, notify_fn)
+    }
+
     pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
         self.event_source.add_legacy_listener(ptr);
     }
@@ -389,3 +478,20 @@ enum WriteMode {
     Stream,
     Packet,
 }
+
+/// Round a requested pipe capacity up to a power-of-two number of pages, as Linux does. Returns
+/// `None` if the size is too large to round.
+fn round_pipe_size(size: libc::c_uint) -> Option<usize> {
+    let size = usize::try_from(size).unwrap();
+
+    // linux rejects sizes that can't be rounded without overflowing an unsigned int
+    if size > (1 << 31) {
+        return None;
+    }
+
+    if size < PIPE_PAGE_SIZE {
+        return Some(PIPE_PAGE_SIZE);
+    }
+
+    Some(size.next_power_of_two())
+}
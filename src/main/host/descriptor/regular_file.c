@@ -14,6 +14,7 @@
 #include <string.h>
 #include <sys/file.h>
 #include <sys/ioctl.h>
+#include <sys/mman.h>
 #include <sys/stat.h>
 #include <sys/syscall.h>
 #include <sys/time.h>
@@ -24,6 +25,7 @@
 #include <unistd.h>
 
 #include "lib/logger/logger.h"
+#include "main/core/support/definitions.h"
 #include "main/core/worker.h"
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/host.h"
@@ -42,6 +44,7 @@ enum _FileType {
     FILE_TYPE_RANDOM,    // special handling for /dev/random etc.
     FILE_TYPE_HOSTS,     // special handling for /etc/hosts
     FILE_TYPE_LOCALTIME, // special handling for /etc/localtime
+    FILE_TYPE_EMULATED,  // special handling for files whose contents we generate, e.g. in /sys
 };
 
 struct _RegularFile {
@@ -74,6 +77,11 @@ mode_t regularfile_getModeAtOpen(RegularFile* file) {
     return file->osfile.modeAtOpen;
 }
 
+const char* regularfile_getAbsolutePathAtOpen(RegularFile* file) {
+    MAGIC_ASSERT(file);
+    return file->osfile.absPathAtOpen;
+}
+
 int regularfile_getShadowFlags(RegularFile* file) {
     MAGIC_ASSERT(file);
     return file->shadowFlags;
@@ -222,8 +230,85 @@ static void _regularfile_print_flags(int flags) {
 #undef CHECK_FLAG
 #endif
 
-int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname, int flags,
-                       mode_t mode, const char* workingDir) {
+/* Returns the contents of the file at `abspath` if it's a file whose contents we emulate for the
+ * given host, or NULL otherwise. The caller must free the returned string. */
+static char* _regularfile_getEmulatedContents(Host* host, const char* abspath) {
+    if (!strcmp("/sys/devices/system/cpu/online", abspath) ||
+        !strcmp("/sys/devices/system/cpu/possible", abspath) ||
+        !strcmp("/sys/devices/system/cpu/present", abspath)) {
+        // all of the simulated CPUs are always online
+        guint32 cpuCount = host_getCpuCount(host);
+        if (cpuCount == 1) {
+            return g_strdup("0\n");
+        }
+        return g_strdup_printf("0-%u\n", cpuCount - 1);
+    }
+
+    if (!strcmp("/proc/meminfo", abspath)) {
+        // must match the values returned by sysinfo(); we don't simulate any buffers or caches
+        guint64 ramTotalKiB = host_getRamTotal(host) / 1024;
+        guint64 ramFreeKiB = host_getRamFree(host) / 1024;
+        return g_strdup_printf("MemTotal:       %8" G_GUINT64_FORMAT " kB\n"
+                               "MemFree:        %8" G_GUINT64_FORMAT " kB\n"
+                               "MemAvailable:   %8" G_GUINT64_FORMAT " kB\n"
+                               "Buffers:        %8u kB\n"
+                               "Cached:         %8u kB\n"
+                               "SwapTotal:      %8" G_GUINT64_FORMAT " kB\n"
+                               "SwapFree:       %8" G_GUINT64_FORMAT " kB\n",
+                               ramTotalKiB, ramFreeKiB, ramFreeKiB, 0, 0,
+                               host_getSwapTotal(host) / 1024, host_getSwapFree(host) / 1024);
+    }
+
+    if (!strcmp("/proc/loadavg", abspath)) {
+        // must match the values returned by sysinfo()
+        const gdouble* loads = host_getLoadAverage(host);
+        return g_strdup_printf("%.2f %.2f %.2f 1/%d %u\n", loads[0], loads[1], loads[2],
+                               SHADOW_SIMULATED_PROCS, host_getLastProcessID(host));
+    }
+
+    return NULL;
+}
+
+/* Create an in-memory file containing `contents` to use as the os-backed file for an emulated
+ * file. Returns -1 and sets errno on error, like open(). */
+static int _regularfile_openEmulated(const char* abspath, const char* contents, int flags) {
+    // the emulated files are read-only
+    if ((flags & O_ACCMODE) != O_RDONLY) {
+        errno = EACCES;
+        return -1;
+    }
+
+    int memfd = memfd_create(abspath, MFD_CLOEXEC);
+    if (memfd < 0) {
+        return -1;
+    }
+
+    size_t len = strlen(contents);
+    size_t written = 0;
+    while (written < len) {
+        ssize_t rv = write(memfd, contents + written, len - written);
+        if (rv < 0) {
+            int errcode = errno;
+            close(memfd);
+            errno = errcode;
+            return -1;
+        }
+        written += rv;
+    }
+
+    // reopen the file with the plugin's flags so that it's read-only and starts at offset 0
+    char* memfdPath = g_strdup_printf("/proc/self/fd/%d", memfd);
+    int osfd = open(memfdPath, flags);
+    int errcode = errno;
+    g_free(memfdPath);
+    close(memfd);
+
+    errno = errcode;
+    return osfd;
+}
+
+int regularfile_openat(RegularFile* file, Host* host, RegularFile* dir, const char* pathname,
+                       int flags, mode_t mode, const char* workingDir) {
     MAGIC_ASSERT(file);
     utility_assert(file->osfile.fd == OSFILE_INVALID);
 
@@ -240,6 +325,7 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
     char* abspath = _regularfile_getAbsolutePath(dir, pathname, workingDir);
 
     /* Handle special files. */
+    char* emulatedContents = NULL;
     if (utility_isRandomPath(abspath)) {
         file->type = FILE_TYPE_RANDOM;
     } else if (!strcmp("/etc/hosts", abspath)) {
@@ -251,6 +337,8 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
         }
     } else if (!strcmp("/etc/localtime", abspath)) {
         file->type = FILE_TYPE_LOCALTIME;
+    } else if ((emulatedContents = _regularfile_getEmulatedContents(host, abspath)) != NULL) {
+        file->type = FILE_TYPE_EMULATED;
     } else {
         file->type = FILE_TYPE_REGULAR;
     }
@@ -271,6 +359,10 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
         // timezone info in the correct format for UTC.
         osfd = -1;
         errcode = ENOENT;
+    } else if (file->type == FILE_TYPE_EMULATED) {
+        osfd = _regularfile_openEmulated(abspath, emulatedContents, flags);
+        errcode = errno;
+        g_free(emulatedContents);
     } else {
         // TODO: we should open the os-backed file in non-blocking mode even if a
         // non-block is not requested, and then properly handle the io by, e.g.,
@@ -304,9 +396,9 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
     return 0;
 }
 
-int regularfile_open(RegularFile* file, const char* pathname, int flags, mode_t mode,
+int regularfile_open(RegularFile* file, Host* host, const char* pathname, int flags, mode_t mode,
                      const char* workingDir) {
-    return regularfile_openat(file, NULL, pathname, flags, mode, workingDir);
+    return regularfile_openat(file, host, NULL, pathname, flags, mode, workingDir);
 }
 
 static void _regularfile_readRandomBytes(RegularFile* file, Host* host, void* buf,
@@ -45,10 +45,10 @@ typedef struct _RegularFile RegularFile;
 // ************************
 
 RegularFile* regularfile_new(); // Close the file with legacyfile_close()
-int regularfile_open(RegularFile* file, const char* pathname, int flags, mode_t mode,
+int regularfile_open(RegularFile* file, Host* host, const char* pathname, int flags, mode_t mode,
                      const char* workingDir);
-int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname, int flags,
-                       mode_t mode, const char* workingDir);
+int regularfile_openat(RegularFile* file, Host* host, RegularFile* dir, const char* pathname,
+                       int flags, mode_t mode, const char* workingDir);
 
 // ************************
 // Accessors
@@ -67,6 +67,9 @@ int regularfile_getShadowFlags(RegularFile* file);
 /* Returns the linux-backed fd that shadow uses to perform the file operations.  */
 int regularfile_getOSBackedFD(RegularFile* file);
 
+/* Returns the absolute path of the file when it was opened, or NULL if unknown. */
+const char* regularfile_getAbsolutePathAtOpen(RegularFile* file);
+
 // ****************************************
 // Operations that require a non-null RegularFile*
 // ****************************************
@@ -38,6 +38,30 @@ impl SharedBuf {
         self.max_len - self.queue.num_bytes()
     }
 
+    /// The number of bytes in the buffer.
+    pub fn num_bytes(&self) -> usize {
+        self.queue.num_bytes()
+    }
+
+    /// Change the maximum number of bytes that the buffer can hold. Returns `EBUSY` if the buffer
+    /// currently holds more than `max_len` bytes.
+    pub fn set_max_len(
+        &mut self,
+        max_len: usize,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        assert_ne!(max_len, 0);
+
+        if self.queue.num_bytes() > max_len {
+            return Err(Errno::EBUSY.into());
+        }
+
+        self.max_len = max_len;
+        self.refresh_state(event_queue);
+
+        Ok(())
+    }
+
     /// Register as a reader. The [`ReaderHandle`] must be returned to the buffer later with
     /// [`remove_reader()`](Self::remove_reader).
     pub fn add_reader(&mut self, event_queue: &mut EventQueue) -> ReaderHandle {
@@ -87,6 +111,14 @@ impl SharedBuf {
         Ok((num_copied, num_removed_from_buf))
     }
 
+    /// Copy data from the buffer without removing it. Returns the number of bytes copied and the
+    /// number of bytes that [`read()`](Self::read) would have removed from the buffer.
+    pub fn peek<W: std::io::Write>(&self, bytes: W) -> Result<(usize, usize), SyscallError> {
+        let (num_copied, num_in_buf, _chunk_type) = self.queue.peek(bytes)?;
+
+        Ok((num_copied, num_in_buf))
+    }
+
     pub fn write_stream<R: std::io::Read>(
         &mut self,
         bytes: R,
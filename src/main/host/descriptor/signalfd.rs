@@ -0,0 +1,287 @@
+use nix::errno::Errno;
+
+use crate::cshadow as c;
+use crate::host::descriptor::{
+    FileMode, FileState, FileStatus, StateEventSource, StateListenerFilter,
+};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall_types::{PluginPtr, SyscallError, SyscallResult};
+use crate::utility::event_queue::{EventQueue, Handle};
+use crate::utility::pod;
+use crate::utility::stream_len::StreamLen;
+use crate::utility::HostTreePointer;
+
+pub struct SignalFd {
+    /// The signals that can be read from this signalfd.
+    mask: c::shd_kernel_sigset_t,
+    /// The signals that were pending for the process the last time they were updated.
+    pending: c::shd_kernel_sigset_t,
+    event_source: StateEventSource,
+    state: FileState,
+    status: FileStatus,
+    // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file
+    has_open_file: bool,
+}
+
+impl SignalFd {
+    pub fn new(mask: c::shd_kernel_sigset_t, status: FileStatus) -> Self {
+        Self {
+            mask,
+            pending: c::shd_kernel_sigset_t { val: 0 },
+            event_source: StateEventSource::new(),
+            state: FileState::ACTIVE,
+            status,
+            has_open_file: false,
+        }
+    }
+
+    pub fn get_status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        false
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn mask(&self) -> c::shd_kernel_sigset_t {
+        self.mask
+    }
+
+    /// Replace the set of signals that can be read from this signalfd.
+    pub fn set_mask(&mut self, mask: c::shd_kernel_sigset_t, event_queue: &mut EventQueue) {
+        self.mask = mask;
+        self.update_state(event_queue);
+    }
+
+    /// Should be called whenever the set of signals pending for the process changes.
+    pub fn set_pending_signals(
+        &mut self,
+        pending: c::shd_kernel_sigset_t,
+        event_queue: &mut EventQueue,
+    ) {
+        self.pending = pending;
+        self.update_state(event_queue);
+    }
+
+    pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        // set the closed flag and remove the active and readable flags
+        self.copy_state(
+            FileState::CLOSED | FileState::ACTIVE | FileState::READABLE,
+            FileState::CLOSED,
+            event_queue,
+        );
+
+        Ok(())
+    }
+
+    pub fn read<W>(
+        &mut self,
+        _bytes: W,
+        _offset: libc::off_t,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        // reading requires access to the reading thread's pending signals, so the syscall handler
+        // should have used `read_signals()` instead
+        log::warn!("Reading from a signalfd without access to the reading thread");
+        Err(Errno::EINVAL.into())
+    }
+
+    /// Read as many `signalfd_siginfo` records as will fit in `bytes`. The `take_signal` function
+    /// is given this signalfd's mask, and should dequeue and return a pending signal in that mask
+    /// (or `None` if there are none).
+    pub fn read_signals<W>(
+        &mut self,
+        mut bytes: W,
+        offset: libc::off_t,
+        mut take_signal: impl FnMut(c::shd_kernel_sigset_t) -> Option<c::siginfo_t>,
+    ) -> SyscallResult
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        // signalfds don't support seeking
+        if offset != 0 {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        const RECORD_SIZE: usize = std::mem::size_of::<libc::signalfd_siginfo>();
+
+        // signalfd(2): "The buffer given to read(2) must be large enough to hold at least one
+        // signalfd_siginfo structure"
+        let max_records = usize::try_from(bytes.stream_len_bp()?).unwrap() / RECORD_SIZE;
+        if max_records == 0 {
+            log::trace!(
+                "Reading from signalfd requires a buffer of at least {} bytes",
+                RECORD_SIZE
+            );
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut num_records = 0;
+        while num_records < max_records {
+            let info = match take_signal(self.mask) {
+                Some(x) => x,
+                None => break,
+            };
+
+            let record = signalfd_siginfo_from(&info);
+            bytes.write_all(pod::to_u8_slice(&[record]))?;
+            num_records += 1;
+        }
+
+        if num_records == 0 {
+            log::trace!("No signals in the signalfd mask are pending");
+            return Err(Errno::EWOULDBLOCK.into());
+        }
+
+        Ok((num_records * RECORD_SIZE).into())
+    }
+
+    pub fn write<R>(
+        &mut self,
+        _bytes: R,
+        _offset: libc::off_t,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: u64,
+        _arg_ptr: PluginPtr,
+        _memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        log::warn!(
+            "We do not yet handle ioctl request {} on signalfds",
+            request
+        );
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState)> {
+        self.event_source
+            .add_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        self.event_source
+            .add_exclusive_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    fn update_state(&mut self, event_queue: &mut EventQueue) {
+        if self.state.contains(FileState::CLOSED) {
+            return;
+        }
+
+        // readable if any of the signals in the mask are pending
+        let readable = if self.pending.val & self.mask.val != 0 {
+            FileState::READABLE
+        } else {
+            FileState::empty()
+        };
+
+        self.copy_state(FileState::READABLE, readable, event_queue);
+    }
+
+    fn copy_state(&mut self, mask: FileState, state: FileState, event_queue: &mut EventQueue) {
+        let old_state = self.state;
+
+        // remove the masked flags, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, event_queue);
+    }
+
+    fn handle_state_change(&mut self, old_state: FileState, event_queue: &mut EventQueue) {
+        let states_changed = self.state ^ old_state;
+
+        // if nothing changed
+        if states_changed.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, event_queue);
+    }
+}
+
+/// Convert a `siginfo_t` to the record returned when reading a signalfd.
+fn signalfd_siginfo_from(info: &c::siginfo_t) -> libc::signalfd_siginfo {
+    let mut record: libc::signalfd_siginfo = pod::zeroed();
+
+    record.ssi_signo = info.si_signo.try_into().unwrap();
+    record.ssi_errno = info.si_errno;
+    record.ssi_code = info.si_code;
+
+    // which union field is valid depends on the signal and how it was sent
+    if info.si_code == libc::SI_TIMER {
+        let timer = unsafe { info._sifields._timer };
+        record.ssi_tid = timer.si_tid as u32;
+        record.ssi_overrun = timer.si_overrun as u32;
+        record.ssi_int = unsafe { timer.si_sigval.sival_int };
+        record.ssi_ptr = unsafe { timer.si_sigval.sival_ptr } as u64;
+    } else if info.si_signo == libc::SIGCHLD && info.si_code > 0 {
+        let sigchld = unsafe { info._sifields._sigchld };
+        record.ssi_pid = sigchld.si_pid as u32;
+        record.ssi_uid = sigchld.si_uid;
+        record.ssi_status = sigchld.si_status;
+        record.ssi_utime = sigchld.si_utime as u64;
+        record.ssi_stime = sigchld.si_stime as u64;
+    } else {
+        let rt = unsafe { info._sifields._rt };
+        record.ssi_pid = rt.si_pid as u32;
+        record.ssi_uid = rt.si_uid;
+        if info.si_code == libc::SI_QUEUE {
+            record.ssi_int = unsafe { rt.si_sigval.sival_int };
+            record.ssi_ptr = unsafe { rt.si_sigval.sival_ptr } as u64;
+        }
+    }
+
+    record
+}
@@ -101,11 +101,11 @@ static gssize _legacysocket_sendUserData(Transport* transport, Thread* thread,
 
 static gssize _legacysocket_receiveUserData(Transport* transport, Thread* thread,
                                             PluginVirtualPtr buffer, gsize nBytes, in_addr_t* ip,
-                                            in_port_t* port) {
+                                            in_port_t* port, int flags) {
     LegacySocket* socket = _legacysocket_fromLegacyFile((LegacyFile*)transport);
     MAGIC_ASSERT(socket);
     MAGIC_ASSERT(socket->vtable);
-    return socket->vtable->receive((Transport*)socket, thread, buffer, nBytes, ip, port);
+    return socket->vtable->receive((Transport*)socket, thread, buffer, nBytes, ip, port, flags);
 }
 
 TransportFunctionTable socket_functions = {
@@ -196,6 +196,11 @@ Packet* legacysocket_peekNextInPacket(const LegacySocket* socket) {
     return g_queue_peek_head(socket->inputBuffer);
 }
 
+Packet* legacysocket_peekNthInPacket(const LegacySocket* socket, guint n) {
+    MAGIC_ASSERT(socket);
+    return g_queue_peek_nth(socket->inputBuffer, n);
+}
+
 gboolean legacysocket_getPeerName(LegacySocket* socket, in_addr_t* ip, in_port_t* port) {
     MAGIC_ASSERT(socket);
 
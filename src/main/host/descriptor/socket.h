@@ -87,6 +87,7 @@ void legacysocket_dropPacket(LegacySocket* socket, Host* host, Packet* packet);
 Packet* legacysocket_pullOutPacket(LegacySocket* socket, Host* host);
 Packet* legacysocket_peekNextOutPacket(const LegacySocket* socket);
 Packet* legacysocket_peekNextInPacket(const LegacySocket* socket);
+Packet* legacysocket_peekNthInPacket(const LegacySocket* socket, guint n);
 
 gsize legacysocket_getInputBufferSize(LegacySocket* socket);
 void legacysocket_setInputBufferSize(LegacySocket* socket, gsize newSize);
@@ -0,0 +1,396 @@
+use std::collections::VecDeque;
+
+use super::{AckInfo, CongestionControl, INFINITE_SSTHRESH};
+use crate::core::support::configuration::TcpCongestionAlgorithm;
+use crate::core::support::emulated_time::EmulatedTime;
+use crate::core::support::simulation_time::SimulationTime;
+
+/// The gain used in startup to double the sending rate each round (2/ln(2)).
+const HIGH_GAIN: f64 = 2.885;
+/// The gains used in each phase of the `ProbeBw` cycle.
+const PROBE_BW_GAINS: [f64; 8] = [1.25, 0.75, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+/// The number of rounds over which the maximum bandwidth is tracked.
+const BTL_BW_FILTER_ROUNDS: u64 = 10;
+/// Startup ends once the bandwidth hasn't grown by this factor for `FULL_BW_ROUNDS` rounds.
+const FULL_BW_THRESHOLD: f64 = 1.25;
+const FULL_BW_ROUNDS: u32 = 3;
+const INITIAL_CWND: u32 = 10;
+const MIN_CWND: u32 = 4;
+
+fn min_rtt_window() -> SimulationTime {
+    SimulationTime::from_secs(10)
+}
+
+fn probe_rtt_duration() -> SimulationTime {
+    SimulationTime::from_millis(200)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum BbrMode {
+    /// Grow the window exponentially until the bottleneck bandwidth is found.
+    Startup,
+    /// Drain the queue that was created during startup.
+    Drain,
+    /// Cycle the window around the bandwidth-delay product to probe for more bandwidth.
+    ProbeBw {
+        cycle_index: usize,
+        cycle_start: EmulatedTime,
+    },
+    /// Shrink the window to measure the round-trip time without queueing delay.
+    ProbeRtt { end: Option<EmulatedTime> },
+}
+
+/// TCP BBR congestion control (version 1). Shadow's TCP implementation doesn't pace packets, so
+/// the pacing gain is applied to the congestion window instead of the sending rate.
+#[derive(Debug)]
+pub struct Bbr {
+    mode: BbrMode,
+    cwnd: u32,
+    /// The congestion window from before entering `ProbeRtt`.
+    prior_cwnd: u32,
+    in_fast_recovery: bool,
+    duplicate_ack_count: usize,
+    /// The total number of packets delivered to the peer.
+    delivered: u64,
+    round_count: u64,
+    /// The current round ends once `delivered` reaches this value.
+    next_round_delivered: u64,
+    round_start: Option<(EmulatedTime, u64)>,
+    /// Bandwidth samples (in packets per second) for recent rounds, ordered by round.
+    bw_samples: VecDeque<(u64, f64)>,
+    min_rtt: Option<SimulationTime>,
+    min_rtt_stamp: Option<EmulatedTime>,
+    full_bw: f64,
+    full_bw_count: u32,
+    filled_pipe: bool,
+}
+
+impl Bbr {
+    pub fn new() -> Self {
+        Self {
+            mode: BbrMode::Startup,
+            cwnd: INITIAL_CWND,
+            prior_cwnd: INITIAL_CWND,
+            in_fast_recovery: false,
+            duplicate_ack_count: 0,
+            delivered: 0,
+            round_count: 0,
+            next_round_delivered: 0,
+            round_start: None,
+            bw_samples: VecDeque::new(),
+            min_rtt: None,
+            min_rtt_stamp: None,
+            full_bw: 0.0,
+            full_bw_count: 0,
+            filled_pipe: false,
+        }
+    }
+
+    /// The maximum recently-measured delivery rate, in packets per second.
+    fn btl_bw(&self) -> Option<f64> {
+        self.bw_samples.iter().map(|(_, bw)| *bw).reduce(f64::max)
+    }
+
+    /// The estimated bandwidth-delay product, in packets.
+    fn bdp(&self) -> Option<f64> {
+        let bw = self.btl_bw()?;
+        let min_rtt = self.min_rtt?;
+        Some(bw * min_rtt.as_nanos_f64() / 1e9)
+    }
+
+    /// Returns true if a new round started.
+    fn update_round(&mut self, ack: &AckInfo) -> bool {
+        if self.delivered < self.next_round_delivered {
+            return false;
+        }
+
+        // take a bandwidth sample over the previous round
+        if let Some((start_time, start_delivered)) = self.round_start {
+            let interval = ack.now.saturating_duration_since(&start_time);
+            if !interval.is_zero() {
+                let bw =
+                    (self.delivered - start_delivered) as f64 / (interval.as_nanos_f64() / 1e9);
+                self.bw_samples.push_back((self.round_count, bw));
+            }
+        }
+
+        self.round_count += 1;
+        self.round_start = Some((ack.now, self.delivered));
+        self.next_round_delivered = self.delivered + std::cmp::max(ack.packets_in_flight, 1) as u64;
+
+        while let Some((round, _)) = self.bw_samples.front() {
+            if round + BTL_BW_FILTER_ROUNDS > self.round_count {
+                break;
+            }
+            self.bw_samples.pop_front();
+        }
+
+        true
+    }
+
+    /// Returns true if the minimum rtt estimate had expired before this update.
+    fn update_min_rtt(&mut self, ack: &AckInfo) -> bool {
+        let expired = match self.min_rtt_stamp {
+            Some(stamp) => ack.now > stamp + min_rtt_window(),
+            None => false,
+        };
+
+        if let Some(srtt) = ack.srtt {
+            if expired || self.min_rtt.map_or(true, |x| srtt <= x) {
+                self.min_rtt = Some(srtt);
+                self.min_rtt_stamp = Some(ack.now);
+            }
+        }
+
+        expired
+    }
+
+    fn check_full_pipe(&mut self) {
+        let bw = match self.btl_bw() {
+            Some(x) => x,
+            None => return,
+        };
+
+        if bw >= self.full_bw * FULL_BW_THRESHOLD {
+            self.full_bw = bw;
+            self.full_bw_count = 0;
+            return;
+        }
+
+        self.full_bw_count += 1;
+        if self.full_bw_count >= FULL_BW_ROUNDS {
+            log::debug!("[CONG] bbr found the bottleneck bandwidth ({bw} packets/s)");
+            self.filled_pipe = true;
+        }
+    }
+
+    fn enter_probe_bw(&mut self, now: EmulatedTime) {
+        // start in a phase with a gain of 1 rather than choosing one randomly like linux, so that
+        // the simulation is deterministic
+        self.mode = BbrMode::ProbeBw {
+            cycle_index: 2,
+            cycle_start: now,
+        };
+    }
+
+    fn update_mode(&mut self, ack: &AckInfo, round_start: bool, min_rtt_expired: bool) {
+        if self.mode == BbrMode::Startup && round_start {
+            self.check_full_pipe();
+            if self.filled_pipe {
+                self.mode = BbrMode::Drain;
+            }
+        }
+
+        if self.mode == BbrMode::Drain {
+            if let Some(bdp) = self.bdp() {
+                if ack.packets_in_flight as f64 <= bdp {
+                    self.enter_probe_bw(ack.now);
+                }
+            }
+        }
+
+        if let BbrMode::ProbeBw {
+            cycle_index,
+            cycle_start,
+        } = self.mode
+        {
+            let min_rtt = self.min_rtt.unwrap_or(SimulationTime::ZERO);
+            if ack.now.saturating_duration_since(&cycle_start) >= min_rtt {
+                self.mode = BbrMode::ProbeBw {
+                    cycle_index: (cycle_index + 1) % PROBE_BW_GAINS.len(),
+                    cycle_start: ack.now,
+                };
+            }
+        }
+
+        if min_rtt_expired && !matches!(self.mode, BbrMode::ProbeRtt { .. }) {
+            self.prior_cwnd = self.cwnd;
+            self.mode = BbrMode::ProbeRtt { end: None };
+        }
+
+        if let BbrMode::ProbeRtt { end } = self.mode {
+            match end {
+                None if ack.packets_in_flight <= MIN_CWND => {
+                    self.mode = BbrMode::ProbeRtt {
+                        end: Some(ack.now + probe_rtt_duration()),
+                    };
+                }
+                Some(end) if ack.now >= end => {
+                    self.min_rtt_stamp = Some(ack.now);
+                    self.cwnd = std::cmp::max(self.cwnd, self.prior_cwnd);
+                    if self.filled_pipe {
+                        self.enter_probe_bw(ack.now);
+                    } else {
+                        self.mode = BbrMode::Startup;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn update_cwnd(&mut self, packets_acked: u32) {
+        let gain = match self.mode {
+            BbrMode::Startup => HIGH_GAIN,
+            BbrMode::Drain => 1.0,
+            BbrMode::ProbeBw { cycle_index, .. } => PROBE_BW_GAINS[cycle_index],
+            BbrMode::ProbeRtt { .. } => {
+                self.cwnd = std::cmp::min(self.cwnd, MIN_CWND);
+                return;
+            }
+        };
+
+        let target = match self.bdp() {
+            Some(bdp) => std::cmp::max((bdp * gain) as u32, MIN_CWND),
+            // we don't have a bandwidth estimate yet, so grow like slow start
+            None => {
+                self.cwnd += packets_acked;
+                return;
+            }
+        };
+
+        if !self.filled_pipe {
+            if self.cwnd < target {
+                self.cwnd = std::cmp::min(self.cwnd + packets_acked, target);
+            }
+        } else if self.cwnd > target {
+            self.cwnd = target;
+        } else {
+            self.cwnd = std::cmp::min(self.cwnd + packets_acked, target);
+        }
+    }
+}
+
+impl Default for Bbr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CongestionControl for Bbr {
+    fn algorithm(&self) -> TcpCongestionAlgorithm {
+        TcpCongestionAlgorithm::Bbr
+    }
+
+    fn cwnd(&self) -> u32 {
+        self.cwnd
+    }
+
+    fn ssthresh(&self) -> u32 {
+        // bbr doesn't use a slow start threshold
+        INFINITE_SSTHRESH
+    }
+
+    fn in_fast_recovery(&self) -> bool {
+        self.in_fast_recovery
+    }
+
+    fn on_duplicate_ack(&mut self) {
+        // bbr doesn't treat loss as a congestion signal, so the window isn't reduced
+        self.duplicate_ack_count += 1;
+        if self.duplicate_ack_count == 3 {
+            self.in_fast_recovery = true;
+        }
+    }
+
+    fn on_new_ack(&mut self, ack: &AckInfo) {
+        self.duplicate_ack_count = 0;
+        self.in_fast_recovery = false;
+
+        self.delivered += ack.packets_acked as u64;
+
+        let min_rtt_expired = self.update_min_rtt(ack);
+        let round_start = self.update_round(ack);
+        self.update_mode(ack, round_start, min_rtt_expired);
+        self.update_cwnd(ack.packets_acked);
+    }
+
+    fn on_timeout(&mut self, _now: EmulatedTime) {
+        // the window will grow back to the bandwidth-delay product as packets are acked
+        self.duplicate_ack_count = 0;
+        self.in_fast_recovery = false;
+        self.cwnd = 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulate a connection with a bottleneck of `capacity` packets per `rtt`, where a full
+    /// window is acked every rtt.
+    fn run(
+        bbr: &mut Bbr,
+        now: &mut EmulatedTime,
+        rtt: SimulationTime,
+        capacity: u32,
+        duration: SimulationTime,
+    ) {
+        let end = *now + duration;
+        while *now < end {
+            *now = *now + rtt;
+            let acked = std::cmp::min(bbr.cwnd(), capacity);
+            bbr.on_new_ack(&AckInfo {
+                packets_acked: acked,
+                packets_in_flight: acked,
+                srtt: Some(rtt),
+                now: *now,
+            });
+        }
+    }
+
+    #[test]
+    fn test_startup() {
+        let mut bbr = Bbr::new();
+        let mut now = EmulatedTime::SIMULATION_START;
+        let rtt = SimulationTime::from_millis(100);
+
+        run(&mut bbr, &mut now, rtt, 1000, rtt * 3);
+        assert_eq!(bbr.mode, BbrMode::Startup);
+        assert!(bbr.cwnd() > 40);
+    }
+
+    #[test]
+    fn test_probe_bw() {
+        let mut bbr = Bbr::new();
+        let mut now = EmulatedTime::SIMULATION_START;
+        let rtt = SimulationTime::from_millis(100);
+
+        run(&mut bbr, &mut now, rtt, 50, SimulationTime::from_secs(2));
+        assert!(matches!(bbr.mode, BbrMode::ProbeBw { .. }));
+
+        // the window cycles around the bandwidth-delay product
+        for _ in 0..PROBE_BW_GAINS.len() {
+            run(&mut bbr, &mut now, rtt, 50, rtt);
+            assert!(bbr.cwnd() >= 37 && bbr.cwnd() <= 63);
+        }
+    }
+
+    #[test]
+    fn test_probe_rtt() {
+        let mut bbr = Bbr::new();
+        let mut now = EmulatedTime::SIMULATION_START;
+        let rtt = SimulationTime::from_millis(100);
+
+        run(&mut bbr, &mut now, rtt, 50, SimulationTime::from_secs(2));
+
+        // the rtt increases, so the min rtt estimate expires
+        let rtt = SimulationTime::from_millis(150);
+        run(&mut bbr, &mut now, rtt, 50, SimulationTime::from_secs(9));
+        assert!(matches!(bbr.mode, BbrMode::ProbeBw { .. }));
+        run(&mut bbr, &mut now, rtt, 50, SimulationTime::from_secs(1));
+        assert!(matches!(bbr.mode, BbrMode::ProbeRtt { .. }));
+        assert_eq!(bbr.cwnd(), MIN_CWND);
+
+        // and it returns to probing bandwidth after measuring the rtt
+        run(
+            &mut bbr,
+            &mut now,
+            rtt,
+            50,
+            SimulationTime::from_millis(600),
+        );
+        assert!(matches!(bbr.mode, BbrMode::ProbeBw { .. }));
+        assert_eq!(bbr.min_rtt, Some(rtt));
+    }
+}
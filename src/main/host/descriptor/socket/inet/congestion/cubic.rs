@@ -0,0 +1,280 @@
+use super::{AckInfo, CongestionControl, INFINITE_SSTHRESH};
+use crate::core::support::configuration::TcpCongestionAlgorithm;
+use crate::core::support::emulated_time::EmulatedTime;
+use crate::core::support::simulation_time::SimulationTime;
+
+/// The multiplicative window decrease factor.
+const BETA: f64 = 0.7;
+/// The cubic scaling constant.
+const C: f64 = 0.4;
+/// The additive increase factor used to estimate the window of a reno flow.
+const RENO_ALPHA: f64 = 3.0 * (1.0 - BETA) / (1.0 + BETA);
+/// The initial window used by linux.
+const INITIAL_CWND: u32 = 10;
+const MIN_CWND: u32 = 2;
+
+/// TCP CUBIC congestion control (RFC 8312), which is the default algorithm in linux. This
+/// implementation does not include linux's "HyStart" slow start exit heuristic.
+#[derive(Debug)]
+pub struct Cubic {
+    cwnd: u32,
+    ssthresh: u32,
+    /// Window growth that hasn't yet added up to a full packet.
+    cwnd_fraction: f64,
+    in_fast_recovery: bool,
+    duplicate_ack_count: usize,
+    /// The window size just before the last reduction.
+    w_max: f64,
+    /// The start of the current congestion avoidance epoch.
+    epoch_start: Option<EmulatedTime>,
+    /// The time (in seconds) from the start of the epoch until the window reaches `origin`.
+    k: f64,
+    /// The window size at the plateau of the cubic function.
+    origin: f64,
+    /// An estimate of the window that a reno flow would have.
+    w_est: f64,
+    min_rtt: Option<SimulationTime>,
+}
+
+impl Cubic {
+    pub fn new() -> Self {
+        Self {
+            cwnd: INITIAL_CWND,
+            ssthresh: INFINITE_SSTHRESH,
+            cwnd_fraction: 0.0,
+            in_fast_recovery: false,
+            duplicate_ack_count: 0,
+            w_max: 0.0,
+            epoch_start: None,
+            k: 0.0,
+            origin: 0.0,
+            w_est: 0.0,
+            min_rtt: None,
+        }
+    }
+
+    /// Reduce the slow start threshold after a loss, and end the current epoch.
+    fn on_loss(&mut self) {
+        let cwnd = self.cwnd as f64;
+
+        // fast convergence: release bandwidth to new flows if the window didn't grow back to its
+        // previous maximum
+        self.w_max = if cwnd < self.w_max {
+            cwnd * (1.0 + BETA) / 2.0
+        } else {
+            cwnd
+        };
+
+        self.ssthresh = std::cmp::max((cwnd * BETA) as u32, MIN_CWND);
+        self.epoch_start = None;
+        self.cwnd_fraction = 0.0;
+    }
+
+    fn cong_avoid(&mut self, n: u32, now: EmulatedTime) {
+        let cwnd = self.cwnd as f64;
+
+        let epoch_start = match self.epoch_start {
+            Some(x) => x,
+            None => {
+                if cwnd < self.w_max {
+                    self.k = ((self.w_max - cwnd) / C).cbrt();
+                    self.origin = self.w_max;
+                } else {
+                    self.k = 0.0;
+                    self.origin = cwnd;
+                }
+                self.w_est = cwnd;
+                self.epoch_start = Some(now);
+                now
+            }
+        };
+
+        // the target is the window size one rtt in the future
+        let rtt = self.min_rtt.unwrap_or(SimulationTime::ZERO);
+        let t = (now.saturating_duration_since(&epoch_start) + rtt).as_nanos_f64() / 1e9;
+        let mut target = self.origin + C * (t - self.k).powi(3);
+
+        // never grow slower than a reno flow would
+        self.w_est += RENO_ALPHA * (n as f64) / cwnd;
+        target = target.max(self.w_est);
+
+        // never grow by more than 50% per rtt
+        target = target.min(cwnd * 1.5);
+
+        let increase_per_packet = if target > cwnd {
+            (target - cwnd) / cwnd
+        } else {
+            // grow very slowly at the plateau
+            1.0 / (100.0 * cwnd)
+        };
+
+        self.cwnd_fraction += increase_per_packet * (n as f64);
+        let whole = self.cwnd_fraction.floor();
+        self.cwnd += whole as u32;
+        self.cwnd_fraction -= whole;
+    }
+}
+
+impl Default for Cubic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CongestionControl for Cubic {
+    fn algorithm(&self) -> TcpCongestionAlgorithm {
+        TcpCongestionAlgorithm::Cubic
+    }
+
+    fn cwnd(&self) -> u32 {
+        self.cwnd
+    }
+
+    fn ssthresh(&self) -> u32 {
+        self.ssthresh
+    }
+
+    fn in_fast_recovery(&self) -> bool {
+        self.in_fast_recovery
+    }
+
+    fn on_duplicate_ack(&mut self) {
+        if self.in_fast_recovery {
+            // inflate the window for each packet that has left the network
+            self.cwnd += 1;
+            return;
+        }
+
+        self.duplicate_ack_count += 1;
+
+        if self.duplicate_ack_count == 3 {
+            log::debug!("[CONG] three duplicate acks, transition to fast recovery");
+            self.on_loss();
+            self.cwnd = self.ssthresh + 3;
+            self.in_fast_recovery = true;
+        }
+    }
+
+    fn on_new_ack(&mut self, ack: &AckInfo) {
+        self.duplicate_ack_count = 0;
+
+        if let Some(srtt) = ack.srtt {
+            self.min_rtt = Some(self.min_rtt.map_or(srtt, |x| std::cmp::min(x, srtt)));
+        }
+
+        if self.in_fast_recovery {
+            // deflate the window and continue in congestion avoidance
+            self.in_fast_recovery = false;
+            self.cwnd = self.ssthresh;
+            return;
+        }
+
+        let mut n = ack.packets_acked;
+
+        if self.cwnd < self.ssthresh {
+            // slow start
+            let increase = std::cmp::min(n, self.ssthresh - self.cwnd);
+            self.cwnd += increase;
+            n -= increase;
+        }
+
+        if n > 0 {
+            self.cong_avoid(n, ack.now);
+        }
+    }
+
+    fn on_timeout(&mut self, _now: EmulatedTime) {
+        self.on_loss();
+        self.duplicate_ack_count = 0;
+        self.in_fast_recovery = false;
+        self.cwnd = 1;
+        log::debug!("[CONG] transition to slow start");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rtt() -> SimulationTime {
+        SimulationTime::from_millis(100)
+    }
+
+    fn ack(n: u32, now: EmulatedTime) -> AckInfo {
+        AckInfo {
+            packets_acked: n,
+            packets_in_flight: 0,
+            srtt: Some(rtt()),
+            now,
+        }
+    }
+
+    #[test]
+    fn test_slow_start() {
+        let mut cubic = Cubic::new();
+        assert_eq!(cubic.cwnd(), INITIAL_CWND);
+
+        cubic.on_new_ack(&ack(10, EmulatedTime::SIMULATION_START));
+        assert_eq!(cubic.cwnd(), 20);
+    }
+
+    #[test]
+    fn test_loss_reduction() {
+        let mut cubic = Cubic::new();
+        cubic.on_new_ack(&ack(90, EmulatedTime::SIMULATION_START));
+        assert_eq!(cubic.cwnd(), 100);
+
+        for _ in 0..3 {
+            cubic.on_duplicate_ack();
+        }
+        assert!(cubic.in_fast_recovery());
+        assert_eq!(cubic.ssthresh(), 70);
+
+        cubic.on_new_ack(&ack(1, EmulatedTime::SIMULATION_START));
+        assert!(!cubic.in_fast_recovery());
+        assert_eq!(cubic.cwnd(), 70);
+    }
+
+    #[test]
+    fn test_cubic_growth() {
+        let mut now = EmulatedTime::SIMULATION_START;
+
+        let mut cubic = Cubic::new();
+        cubic.on_new_ack(&ack(90, now));
+        for _ in 0..3 {
+            cubic.on_duplicate_ack();
+        }
+        cubic.on_new_ack(&ack(1, now));
+        assert_eq!(cubic.cwnd(), 70);
+
+        // acknowledge a full window every rtt
+        let mut run_for = |cubic: &mut Cubic, duration: SimulationTime| {
+            let end = now + duration;
+            while now < end {
+                now = now + rtt();
+                cubic.on_new_ack(&ack(cubic.cwnd(), now));
+            }
+        };
+
+        // the window grows quickly at first
+        run_for(&mut cubic, SimulationTime::SECOND);
+        assert!(cubic.cwnd() > 80 && cubic.cwnd() < 100);
+
+        // and then plateaus at the window size before the loss (k ~= 4.2 seconds)
+        run_for(&mut cubic, SimulationTime::from_secs(3));
+        assert!(cubic.cwnd() >= 95 && cubic.cwnd() <= 100);
+
+        // and then probes for more bandwidth
+        run_for(&mut cubic, SimulationTime::from_secs(4));
+        assert!(cubic.cwnd() > 105);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut cubic = Cubic::new();
+        cubic.on_new_ack(&ack(90, EmulatedTime::SIMULATION_START));
+        cubic.on_timeout(EmulatedTime::SIMULATION_START);
+        assert_eq!(cubic.cwnd(), 1);
+        assert_eq!(cubic.ssthresh(), 70);
+    }
+}
@@ -0,0 +1,199 @@
+//! TCP congestion control algorithms.
+//!
+//! All window sizes are measured in packets (segments), since shadow's TCP implementation
+//! acknowledges whole packets rather than individual bytes.
+
+use crate::core::support::configuration::TcpCongestionAlgorithm;
+use crate::core::support::emulated_time::EmulatedTime;
+use crate::core::support::simulation_time::SimulationTime;
+
+pub mod bbr;
+pub mod cubic;
+pub mod reno;
+
+/// The slow start threshold to report when an algorithm doesn't use one. This is the value of
+/// `TCP_INFINITE_SSTHRESH` in linux.
+pub const INFINITE_SSTHRESH: u32 = 0x7fffffff;
+
+/// Information about an acknowledgement that acknowledged new data.
+#[derive(Debug, Copy, Clone)]
+pub struct AckInfo {
+    /// The number of packets that were newly acknowledged.
+    pub packets_acked: u32,
+    /// The number of packets that are still in flight after processing the acknowledgement.
+    pub packets_in_flight: u32,
+    /// The connection's smoothed round-trip time, if it has been measured.
+    pub srtt: Option<SimulationTime>,
+    /// The time at which the acknowledgement was received.
+    pub now: EmulatedTime,
+}
+
+/// A congestion control algorithm for a single TCP connection. The TCP implementation notifies
+/// the algorithm of congestion events, and limits the number of packets in flight to the
+/// algorithm's congestion window.
+pub trait CongestionControl: std::fmt::Debug + Send + Sync {
+    /// The algorithm that this object implements.
+    fn algorithm(&self) -> TcpCongestionAlgorithm;
+
+    /// The congestion window, in packets.
+    fn cwnd(&self) -> u32;
+
+    /// The slow start threshold, in packets.
+    fn ssthresh(&self) -> u32;
+
+    /// Is the algorithm currently recovering from a loss detected by duplicate acknowledgements?
+    fn in_fast_recovery(&self) -> bool;
+
+    /// A duplicate acknowledgement was received.
+    fn on_duplicate_ack(&mut self);
+
+    /// An acknowledgement for new data was received.
+    fn on_new_ack(&mut self, ack: &AckInfo);
+
+    /// The retransmission timer expired.
+    fn on_timeout(&mut self, now: EmulatedTime);
+}
+
+/// Create a new congestion control object for a new connection.
+pub fn new_congestion_control(algorithm: TcpCongestionAlgorithm) -> Box<dyn CongestionControl> {
+    match algorithm {
+        TcpCongestionAlgorithm::Reno => Box::new(reno::Reno::new()),
+        TcpCongestionAlgorithm::Cubic => Box::new(cubic::Cubic::new()),
+        TcpCongestionAlgorithm::Bbr => Box::new(bbr::Bbr::new()),
+    }
+}
+
+/// A congestion control object that can be owned by C code.
+#[derive(Debug)]
+pub struct TcpCongestionControl(Box<dyn CongestionControl>);
+
+mod export {
+    use super::*;
+
+    use std::ffi::CStr;
+
+    use crate::core::worker::Worker;
+    use crate::cshadow as c;
+
+    /// Create a new congestion control object using the given algorithm. The object must be
+    /// freed with `tcpcongestion_free()`.
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_new(
+        algorithm: TcpCongestionAlgorithm,
+    ) -> *mut TcpCongestionControl {
+        Box::into_raw(Box::new(TcpCongestionControl(new_congestion_control(
+            algorithm,
+        ))))
+    }
+
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_free(cong: *mut TcpCongestionControl) {
+        if cong.is_null() {
+            return;
+        }
+        unsafe { Box::from_raw(cong) };
+    }
+
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_getAlgorithm(
+        cong: *const TcpCongestionControl,
+    ) -> TcpCongestionAlgorithm {
+        assert!(!cong.is_null());
+        let cong = unsafe { &*cong };
+        cong.0.algorithm()
+    }
+
+    /// Get the linux name of the algorithm as a static nul-terminated string.
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_algorithmName(
+        algorithm: TcpCongestionAlgorithm,
+    ) -> *const libc::c_char {
+        let name: &'static [u8] = match algorithm {
+            TcpCongestionAlgorithm::Reno => b"reno\0",
+            TcpCongestionAlgorithm::Cubic => b"cubic\0",
+            TcpCongestionAlgorithm::Bbr => b"bbr\0",
+        };
+        name.as_ptr() as *const libc::c_char
+    }
+
+    /// Look up an algorithm by its linux name. Returns false if there is no algorithm with the
+    /// given name.
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_algorithmFromName(
+        name: *const libc::c_char,
+        algorithm: *mut TcpCongestionAlgorithm,
+    ) -> bool {
+        assert!(!name.is_null());
+        assert!(!algorithm.is_null());
+
+        let name = match unsafe { CStr::from_ptr(name) }.to_str() {
+            Ok(x) => x,
+            Err(_) => return false,
+        };
+
+        match TcpCongestionAlgorithm::from_name(name) {
+            Some(x) => {
+                unsafe { algorithm.write(x) };
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_getCwnd(cong: *const TcpCongestionControl) -> u32 {
+        assert!(!cong.is_null());
+        let cong = unsafe { &*cong };
+        cong.0.cwnd()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_getSSThresh(cong: *const TcpCongestionControl) -> u32 {
+        assert!(!cong.is_null());
+        let cong = unsafe { &*cong };
+        cong.0.ssthresh()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_isInFastRecovery(cong: *const TcpCongestionControl) -> bool {
+        assert!(!cong.is_null());
+        let cong = unsafe { &*cong };
+        cong.0.in_fast_recovery()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_onDuplicateAck(cong: *mut TcpCongestionControl) {
+        assert!(!cong.is_null());
+        let cong = unsafe { &mut *cong };
+        cong.0.on_duplicate_ack();
+    }
+
+    /// Notify the algorithm of an acknowledgement for new data. A `srtt` of 0 means that the
+    /// round-trip time hasn't been measured yet.
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_onNewAck(
+        cong: *mut TcpCongestionControl,
+        packets_acked: u32,
+        packets_in_flight: u32,
+        srtt: c::SimulationTime,
+    ) {
+        assert!(!cong.is_null());
+        let cong = unsafe { &mut *cong };
+
+        let srtt = SimulationTime::from_c_simtime(srtt).filter(|x| !x.is_zero());
+
+        cong.0.on_new_ack(&AckInfo {
+            packets_acked,
+            packets_in_flight,
+            srtt,
+            now: Worker::current_time().unwrap(),
+        });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn tcpcongestion_onTimeout(cong: *mut TcpCongestionControl) {
+        assert!(!cong.is_null());
+        let cong = unsafe { &mut *cong };
+        cong.0.on_timeout(Worker::current_time().unwrap());
+    }
+}
@@ -0,0 +1,203 @@
+use super::{AckInfo, CongestionControl};
+use crate::core::support::configuration::TcpCongestionAlgorithm;
+use crate::core::support::emulated_time::EmulatedTime;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum RenoState {
+    SlowStart,
+    FastRecovery,
+    CongAvoid,
+}
+
+/// TCP Reno congestion control (RFC 5681).
+#[derive(Debug)]
+pub struct Reno {
+    state: RenoState,
+    cwnd: u32,
+    ssthresh: u32,
+    duplicate_ack_count: usize,
+    /// Number of packets acked in congestion avoidance since the window was last increased.
+    cong_avoid_acked: u32,
+}
+
+impl Reno {
+    pub fn new() -> Self {
+        Self {
+            state: RenoState::SlowStart,
+            cwnd: 1,
+            ssthresh: i32::MAX as u32,
+            duplicate_ack_count: 0,
+            cong_avoid_acked: 0,
+        }
+    }
+
+    fn halve_ssthresh(&mut self) {
+        self.ssthresh = (self.cwnd / 2) + 1;
+    }
+
+    /// Transition to congestion avoidance, acking `n` packets during the transition.
+    fn transition_to_cong_avoid(&mut self, n: u32) {
+        self.cong_avoid_acked = 0;
+        self.state = RenoState::CongAvoid;
+        self.cong_avoid_new_ack(n);
+        log::debug!("[CONG] transition to congestion avoidance");
+    }
+
+    fn cong_avoid_new_ack(&mut self, n: u32) {
+        self.cong_avoid_acked += n;
+
+        // we only increase by one for each send window
+        while self.cong_avoid_acked >= self.cwnd {
+            self.cong_avoid_acked -= self.cwnd;
+            self.cwnd += 1;
+        }
+    }
+}
+
+impl Default for Reno {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CongestionControl for Reno {
+    fn algorithm(&self) -> TcpCongestionAlgorithm {
+        TcpCongestionAlgorithm::Reno
+    }
+
+    fn cwnd(&self) -> u32 {
+        self.cwnd
+    }
+
+    fn ssthresh(&self) -> u32 {
+        self.ssthresh
+    }
+
+    fn in_fast_recovery(&self) -> bool {
+        self.state == RenoState::FastRecovery
+    }
+
+    fn on_duplicate_ack(&mut self) {
+        match self.state {
+            // slow start and congestion avoidance have the same duplicate ack behaviour
+            RenoState::SlowStart | RenoState::CongAvoid => {
+                self.duplicate_ack_count += 1;
+
+                if self.duplicate_ack_count == 3 {
+                    log::debug!("[CONG] three duplicate acks, transition to fast recovery");
+                    self.halve_ssthresh();
+                    self.cwnd = self.ssthresh + 3;
+                    self.state = RenoState::FastRecovery;
+                }
+            }
+            RenoState::FastRecovery => self.cwnd += 1,
+        }
+    }
+
+    fn on_new_ack(&mut self, ack: &AckInfo) {
+        let n = ack.packets_acked;
+
+        match self.state {
+            RenoState::SlowStart => {
+                self.duplicate_ack_count = 0;
+
+                let new_cwnd = self.cwnd + n;
+
+                if new_cwnd >= self.ssthresh {
+                    // if we have acked too many packets, raise the cwnd to ssthresh and then
+                    // transition to congestion avoidance with the leftover acks
+                    let n_left = new_cwnd - self.ssthresh;
+                    self.cwnd = self.ssthresh;
+                    self.transition_to_cong_avoid(n_left);
+                } else {
+                    self.cwnd = new_cwnd;
+                }
+            }
+            RenoState::FastRecovery => {
+                self.duplicate_ack_count = 0;
+                self.cwnd = self.ssthresh;
+                self.transition_to_cong_avoid(n);
+            }
+            RenoState::CongAvoid => self.cong_avoid_new_ack(n),
+        }
+    }
+
+    fn on_timeout(&mut self, _now: EmulatedTime) {
+        // all timeouts have the same behaviour
+        self.duplicate_ack_count = 0;
+        self.halve_ssthresh();
+        self.cwnd = 10;
+
+        self.state = RenoState::SlowStart;
+        log::debug!("[CONG] transition to slow start");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ack(n: u32) -> AckInfo {
+        AckInfo {
+            packets_acked: n,
+            packets_in_flight: 0,
+            srtt: None,
+            now: EmulatedTime::SIMULATION_START,
+        }
+    }
+
+    #[test]
+    fn test_slow_start() {
+        let mut reno = Reno::new();
+        assert_eq!(reno.cwnd(), 1);
+
+        reno.on_new_ack(&ack(1));
+        assert_eq!(reno.cwnd(), 2);
+        reno.on_new_ack(&ack(2));
+        assert_eq!(reno.cwnd(), 4);
+        assert!(!reno.in_fast_recovery());
+    }
+
+    #[test]
+    fn test_fast_recovery() {
+        let mut reno = Reno::new();
+        reno.on_new_ack(&ack(19));
+        assert_eq!(reno.cwnd(), 20);
+
+        reno.on_duplicate_ack();
+        reno.on_duplicate_ack();
+        assert!(!reno.in_fast_recovery());
+        reno.on_duplicate_ack();
+        assert!(reno.in_fast_recovery());
+        assert_eq!(reno.ssthresh(), 11);
+        assert_eq!(reno.cwnd(), 14);
+
+        // each additional duplicate ack inflates the window
+        reno.on_duplicate_ack();
+        assert_eq!(reno.cwnd(), 15);
+
+        // a new ack deflates the window and moves to congestion avoidance
+        reno.on_new_ack(&ack(1));
+        assert!(!reno.in_fast_recovery());
+        assert_eq!(reno.cwnd(), 11);
+    }
+
+    #[test]
+    fn test_cong_avoid() {
+        let mut reno = Reno::new();
+        reno.on_new_ack(&ack(19));
+        reno.on_timeout(EmulatedTime::SIMULATION_START);
+        assert_eq!(reno.ssthresh(), 11);
+        assert_eq!(reno.cwnd(), 10);
+
+        // reach ssthresh
+        reno.on_new_ack(&ack(1));
+        assert_eq!(reno.cwnd(), 11);
+
+        // the window only grows by one packet per window of acked packets
+        reno.on_new_ack(&ack(10));
+        assert_eq!(reno.cwnd(), 11);
+        reno.on_new_ack(&ack(1));
+        assert_eq!(reno.cwnd(), 12);
+    }
+}
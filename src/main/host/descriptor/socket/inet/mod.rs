@@ -0,0 +1,599 @@
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use nix::errno::Errno;
+
+use crate::cshadow as c;
+use crate::host::descriptor::socket::AncillaryData;
+use crate::host::descriptor::{
+    FileMode, FileState, FileStatus, StateListenerFilter, SyscallResult,
+};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall_types::{PluginPtr, SysCallReg, SyscallError};
+use crate::network::packet::PacketRc;
+use crate::utility::event_queue::{EventQueue, Handle};
+use crate::utility::HostTreePointer;
+
+use tcp::TcpSocket;
+use udp::UdpSocket;
+
+// https://github.com/shadow/shadow/issues/2093
+#[allow(deprecated)]
+use nix::sys::socket::{InetAddr, SockAddr};
+
+pub mod congestion;
+pub mod tcp;
+pub mod udp;
+
+#[derive(Clone)]
+pub enum InetSocket {
+    Tcp(Arc<AtomicRefCell<TcpSocket>>),
+    Udp(Arc<AtomicRefCell<UdpSocket>>),
+}
+
+impl InetSocket {
+    pub fn borrow(&self) -> InetSocketRef {
+        match self {
+            Self::Tcp(ref f) => InetSocketRef::Tcp(f.borrow()),
+            Self::Udp(ref f) => InetSocketRef::Udp(f.borrow()),
+        }
+    }
+
+    pub fn try_borrow(&self) -> Result<InetSocketRef, atomic_refcell::BorrowError> {
+        Ok(match self {
+            Self::Tcp(ref f) => InetSocketRef::Tcp(f.try_borrow()?),
+            Self::Udp(ref f) => InetSocketRef::Udp(f.try_borrow()?),
+        })
+    }
+
+    pub fn borrow_mut(&self) -> InetSocketRefMut {
+        match self {
+            Self::Tcp(ref f) => InetSocketRefMut::Tcp(f.borrow_mut()),
+            Self::Udp(ref f) => InetSocketRefMut::Udp(f.borrow_mut()),
+        }
+    }
+
+    pub fn try_borrow_mut(&self) -> Result<InetSocketRefMut, atomic_refcell::BorrowMutError> {
+        Ok(match self {
+            Self::Tcp(ref f) => InetSocketRefMut::Tcp(f.try_borrow_mut()?),
+            Self::Udp(ref f) => InetSocketRefMut::Udp(f.try_borrow_mut()?),
+        })
+    }
+
+    pub fn canonical_handle(&self) -> usize {
+        match self {
+            Self::Tcp(f) => Arc::as_ptr(f) as usize,
+            Self::Udp(f) => Arc::as_ptr(f) as usize,
+        }
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn bind(&self, addr: Option<&SockAddr>, rng: impl rand::Rng) -> SyscallResult {
+        match self {
+            Self::Tcp(socket) => TcpSocket::bind(socket, addr, rng),
+            Self::Udp(socket) => UdpSocket::bind(socket, addr, rng),
+        }
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn connect(
+        &self,
+        addr: &SockAddr,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        match self {
+            Self::Tcp(socket) => TcpSocket::connect(socket, addr, event_queue),
+            Self::Udp(socket) => UdpSocket::connect(socket, addr, event_queue),
+        }
+    }
+
+    pub fn disconnect(&self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        match self {
+            Self::Tcp(socket) => TcpSocket::disconnect(socket, event_queue),
+            Self::Udp(socket) => UdpSocket::disconnect(socket, event_queue),
+        }
+    }
+}
+
+impl std::fmt::Debug for InetSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(_) => write!(f, "Tcp")?,
+            Self::Udp(_) => write!(f, "Udp")?,
+        }
+
+        if let Ok(file) = self.try_borrow() {
+            write!(
+                f,
+                "(state: {:?}, status: {:?})",
+                file.state(),
+                file.get_status()
+            )
+        } else {
+            write!(f, "(already borrowed)")
+        }
+    }
+}
+
+pub enum InetSocketRef<'a> {
+    Tcp(atomic_refcell::AtomicRef<'a, TcpSocket>),
+    Udp(atomic_refcell::AtomicRef<'a, UdpSocket>),
+}
+
+pub enum InetSocketRefMut<'a> {
+    Tcp(atomic_refcell::AtomicRefMut<'a, TcpSocket>),
+    Udp(atomic_refcell::AtomicRefMut<'a, UdpSocket>),
+}
+
+// file functions
+impl InetSocketRef<'_> {
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn state(&self) -> FileState
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn mode(&self) -> FileMode
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn get_status(&self) -> FileStatus
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn has_open_file(&self) -> bool
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn supports_sa_restart(&self) -> bool
+    );
+}
+
+// socket-specific functions
+impl InetSocketRef<'_> {
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn getpeername(&self) -> Result<Option<SocketAddrV4>, SyscallError>
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn getsockname(&self) -> Result<Option<SocketAddrV4>, SyscallError>
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn address_family(&self) -> nix::sys::socket::AddressFamily
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn socket_type(&self) -> nix::sys::socket::SockType
+    );
+
+    /// The peer address that the network interfaces use to find the socket.
+    pub fn association_peer_address(&self) -> Option<SocketAddrV4> {
+        match self {
+            Self::Tcp(socket) => socket.association_peer_address(),
+            Self::Udp(socket) => socket.getpeername().ok().flatten(),
+        }
+    }
+}
+
+// file functions
+impl InetSocketRefMut<'_> {
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn state(&self) -> FileState
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn mode(&self) -> FileMode
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn get_status(&self) -> FileStatus
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn has_open_file(&self) -> bool
+    );
+    enum_passthrough!(self, (val), Tcp, Udp;
+        pub fn set_has_open_file(&mut self, val: bool)
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn supports_sa_restart(&self) -> bool
+    );
+    enum_passthrough!(self, (event_queue), Tcp, Udp;
+        pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError>
+    );
+    enum_passthrough!(self, (status), Tcp, Udp;
+        pub fn set_status(&mut self, status: FileStatus)
+    );
+    enum_passthrough!(self, (request, arg_ptr, memory_manager), Tcp, Udp;
+        pub fn ioctl(&mut self, request: u64, arg_ptr: PluginPtr, memory_manager: &mut MemoryManager) -> SyscallResult
+    );
+    enum_passthrough!(self, (monitoring, filter, notify_fn), Tcp, Udp;
+        pub fn add_listener(
+            &mut self,
+            monitoring: FileState,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+        ) -> Handle<(FileState, FileState)>
+    );
+    enum_passthrough!(self, (monitoring, filter, notify_fn), Tcp, Udp;
+        pub fn add_exclusive_listener(
+            &mut self,
+            monitoring: FileState,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+        ) -> Handle<(FileState, FileState, u64)>
+    );
+    enum_passthrough!(self, (ptr), Tcp, Udp;
+        pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>)
+    );
+    enum_passthrough!(self, (ptr), Tcp, Udp;
+        pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener)
+    );
+
+    enum_passthrough_generic!(self, (bytes, offset, event_queue), Tcp, Udp;
+        pub fn read<W>(&mut self, bytes: W, offset: libc::off_t, event_queue: &mut EventQueue) -> SyscallResult
+        where W: std::io::Write + std::io::Seek
+    );
+
+    enum_passthrough_generic!(self, (source, offset, event_queue), Tcp, Udp;
+        pub fn write<R>(&mut self, source: R, offset: libc::off_t, event_queue: &mut EventQueue) -> SyscallResult
+        where R: std::io::Read + std::io::Seek
+    );
+}
+
+// socket-specific functions
+impl InetSocketRefMut<'_> {
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn getpeername(&self) -> Result<Option<SocketAddrV4>, SyscallError>
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn getsockname(&self) -> Result<Option<SocketAddrV4>, SyscallError>
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn address_family(&self) -> nix::sys::socket::AddressFamily
+    );
+    enum_passthrough!(self, (), Tcp, Udp;
+        pub fn socket_type(&self) -> nix::sys::socket::SockType
+    );
+
+    enum_passthrough_generic!(self, (source, flags, addr, ancillary, event_queue), Tcp, Udp;
+        // https://github.com/shadow/shadow/issues/2093
+        #[allow(deprecated)]
+        pub fn sendto<R>(&mut self, source: R, flags: libc::c_int, addr: Option<nix::sys::socket::SockAddr>, ancillary: AncillaryData, event_queue: &mut EventQueue)
+            -> SyscallResult
+        where R: std::io::Read + std::io::Seek
+    );
+
+    enum_passthrough_generic!(self, (bytes, flags, event_queue), Tcp, Udp;
+        // https://github.com/shadow/shadow/issues/2093
+        #[allow(deprecated)]
+        pub fn recvfrom<W>(&mut self, bytes: W, flags: nix::sys::socket::MsgFlags, event_queue: &mut EventQueue)
+            -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>, usize, AncillaryData), SyscallError>
+        where W: std::io::Write + std::io::Seek
+    );
+
+    enum_passthrough!(self, (level, optname, optval_ptr, optlen, memory_manager), Tcp, Udp;
+        pub fn getsockopt(&mut self, level: libc::c_int, optname: libc::c_int, optval_ptr: PluginPtr,
+                          optlen: libc::socklen_t, memory_manager: &mut MemoryManager)
+            -> Result<libc::socklen_t, SyscallError>
+    );
+
+    enum_passthrough!(self, (level, optname, optval_ptr, optlen, memory_manager, event_queue), Tcp, Udp;
+        pub fn setsockopt(&mut self, level: libc::c_int, optname: libc::c_int, optval_ptr: PluginPtr,
+                          optlen: libc::socklen_t, memory_manager: &MemoryManager,
+                          event_queue: &mut EventQueue)
+            -> Result<(), SyscallError>
+    );
+
+    enum_passthrough!(self, (backlog, event_queue), Tcp, Udp;
+        pub fn listen(&mut self, backlog: i32, event_queue: &mut EventQueue) -> Result<(), SyscallError>
+    );
+
+    enum_passthrough!(self, (how, event_queue), Tcp, Udp;
+        pub fn shutdown(&mut self, how: nix::sys::socket::Shutdown, event_queue: &mut EventQueue) -> Result<(), SyscallError>
+    );
+
+    pub fn accept(&mut self, event_queue: &mut EventQueue) -> Result<InetSocket, SyscallError> {
+        match self {
+            Self::Tcp(socket) => socket.accept(event_queue).map(InetSocket::Tcp),
+            Self::Udp(socket) => socket.accept(event_queue).map(InetSocket::Udp),
+        }
+    }
+
+    pub fn peek_next_out_packet(&self) -> Option<&PacketRc> {
+        match self {
+            Self::Tcp(socket) => socket.peek_next_out_packet(),
+            Self::Udp(socket) => socket.peek_next_out_packet(),
+        }
+    }
+
+    enum_passthrough!(self, (event_queue), Tcp, Udp;
+        pub fn pull_out_packet(&mut self, event_queue: &mut EventQueue) -> Option<PacketRc>
+    );
+
+    enum_passthrough!(self, (packet, event_queue), Tcp, Udp;
+        pub fn push_in_packet(&mut self, packet: PacketRc, event_queue: &mut EventQueue)
+    );
+}
+
+impl std::fmt::Debug for InetSocketRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(_) => write!(f, "Tcp")?,
+            Self::Udp(_) => write!(f, "Udp")?,
+        }
+
+        write!(
+            f,
+            "(state: {:?}, status: {:?})",
+            self.state(),
+            self.get_status()
+        )
+    }
+}
+
+impl std::fmt::Debug for InetSocketRefMut<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(_) => write!(f, "Tcp")?,
+            Self::Udp(_) => write!(f, "Udp")?,
+        }
+
+        write!(
+            f,
+            "(state: {:?}, status: {:?})",
+            self.state(),
+            self.get_status()
+        )
+    }
+}
+
+/// Convert a nix socket address to an IPv4 socket address, or `None` if it's not an IPv4
+/// address.
+// https://github.com/shadow/shadow/issues/2093
+#[allow(deprecated)]
+pub fn sockaddr_to_ipv4(addr: &SockAddr) -> Option<SocketAddrV4> {
+    match addr {
+        SockAddr::Inet(addr) => match addr.to_std() {
+            SocketAddr::V4(addr) => Some(addr),
+            SocketAddr::V6(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Convert an IPv4 socket address to a nix socket address.
+// https://github.com/shadow/shadow/issues/2093
+#[allow(deprecated)]
+pub fn ipv4_to_sockaddr(addr: SocketAddrV4) -> SockAddr {
+    SockAddr::Inet(InetAddr::from_std(&SocketAddr::V4(addr)))
+}
+
+/// Returns true if packets sent from `host` can be routed to `ip`.
+fn is_routable(host: *mut c::Host, ip: Ipv4Addr) -> bool {
+    if ip.is_loopback() {
+        return true;
+    }
+
+    let dst = unsafe { c::worker_resolveIPToAddress(u32::from(ip).to_be()) };
+    !dst.is_null() && unsafe { c::worker_isRoutable(c::host_getDefaultAddress(host), dst) }
+}
+
+/// Bind to `addr` on the host's network interfaces, choosing an ephemeral port if the port is 0.
+/// If choosing an ephemeral port, the port only needs to be unused for the given peer address.
+fn bind_port(
+    host: *mut c::Host,
+    protocol: c::ProtocolType,
+    addr: SocketAddrV4,
+    peer_addr: SocketAddrV4,
+) -> Result<SocketAddrV4, SyscallError> {
+    let ip_be = u32::from(*addr.ip()).to_be();
+    let peer_ip_be = u32::from(*peer_addr.ip()).to_be();
+    let peer_port_be = peer_addr.port().to_be();
+
+    // make sure the address belongs to this host
+    if unsafe { c::host_doesInterfaceExist(host, ip_be) } == 0 {
+        return Err(Errno::EADDRNOTAVAIL.into());
+    }
+
+    let port = if addr.port() == 0 {
+        let port_be =
+            unsafe { c::host_getRandomFreePort(host, protocol, ip_be, peer_ip_be, peer_port_be) };
+
+        if port_be == 0 {
+            log::warn!("Unable to find a free port for address {}", addr.ip());
+            return Err(Errno::EADDRINUSE.into());
+        }
+
+        u16::from_be(port_be)
+    } else {
+        let is_available = unsafe {
+            c::host_isInterfaceAvailable(
+                host,
+                protocol,
+                ip_be,
+                addr.port().to_be(),
+                peer_ip_be,
+                peer_port_be,
+            )
+        };
+
+        if is_available == 0 {
+            return Err(Errno::EADDRINUSE.into());
+        }
+
+        addr.port()
+    };
+
+    Ok(SocketAddrV4::new(*addr.ip(), port))
+}
+
+/// The local address used when sending packets from `host` to `dst_ip` from the unspecified
+/// address.
+fn source_ip_for(host: *mut c::Host, dst_ip: Ipv4Addr) -> Ipv4Addr {
+    if dst_ip.is_loopback() {
+        Ipv4Addr::LOCALHOST
+    } else {
+        let ip_be = unsafe { c::host_getDefaultIP(host) };
+        Ipv4Addr::from(u32::from_be(ip_be))
+    }
+}
+
+/// Associate the socket with the host's network interfaces for `ip` so that it will receive
+/// packets sent to its address. The interfaces will look up the socket's addresses, so the socket
+/// must not be mutably borrowed.
+fn associate(host: *mut c::Host, socket: &InetSocket, ip: Ipv4Addr) {
+    unsafe { c::host_associateInetSocket(host, socket, u32::from(ip).to_be()) };
+}
+
+/// Disassociate the socket from the host's network interfaces. The interfaces will look up the
+/// socket's addresses, so the socket must not be mutably borrowed.
+fn disassociate(host: *mut c::Host, socket: &InetSocket) {
+    unsafe { c::host_disassociateInetSocket(host, socket) };
+}
+
+/// Inform the network interface for `src_ip` that the socket has packets to send. The interface
+/// may pull packets from the socket, so the socket must not be borrowed.
+fn wants_send(host: *mut c::Host, socket: &InetSocket, src_ip: Ipv4Addr) {
+    unsafe { c::host_inetSocketWantsSend(host, socket, u32::from(src_ip).to_be()) };
+}
+
+mod export {
+    use super::*;
+
+    /// Decrement the ref count of the `InetSocket` object. The pointer must not be used after
+    /// calling this function.
+    #[no_mangle]
+    pub extern "C" fn inetsocket_drop(socket: *const InetSocket) {
+        assert!(!socket.is_null());
+        unsafe { Box::from_raw(socket as *mut InetSocket) };
+    }
+
+    /// Increment the ref count of the `InetSocket` object. The returned pointer will not be the
+    /// same as the given pointer (they are distinct references), and they both must be dropped
+    /// with `inetsocket_drop` separately later.
+    #[no_mangle]
+    pub extern "C" fn inetsocket_cloneRef(socket: *const InetSocket) -> *const InetSocket {
+        assert!(!socket.is_null());
+        let socket = unsafe { &*socket };
+        Box::into_raw(Box::new(socket.clone()))
+    }
+
+    /// Get a handle that is the same for all references to the same socket.
+    #[no_mangle]
+    pub extern "C" fn inetsocket_getCanonicalHandle(socket: *const InetSocket) -> libc::uintptr_t {
+        assert!(!socket.is_null());
+        let socket = unsafe { &*socket };
+        socket.canonical_handle()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn inetsocket_getProtocol(socket: *const InetSocket) -> c::ProtocolType {
+        assert!(!socket.is_null());
+        let socket = unsafe { &*socket };
+        match socket {
+            InetSocket::Tcp(_) => c::_ProtocolType_PTCP,
+            InetSocket::Udp(_) => c::_ProtocolType_PUDP,
+        }
+    }
+
+    /// Get the socket's peer address in network byte order. Returns false if the socket is not
+    /// connected. The out-pointers may be null.
+    #[no_mangle]
+    pub extern "C" fn inetsocket_getPeerName(
+        socket: *const InetSocket,
+        ip: *mut libc::in_addr_t,
+        port: *mut libc::in_port_t,
+    ) -> bool {
+        assert!(!socket.is_null());
+        let socket = unsafe { &*socket };
+        let addr = socket.borrow().association_peer_address();
+        write_addr(addr, ip, port)
+    }
+
+    /// Get the socket's bound address in network byte order. Returns false if the socket is not
+    /// bound. The out-pointers may be null.
+    #[no_mangle]
+    pub extern "C" fn inetsocket_getSocketName(
+        socket: *const InetSocket,
+        ip: *mut libc::in_addr_t,
+        port: *mut libc::in_port_t,
+    ) -> bool {
+        assert!(!socket.is_null());
+        let socket = unsafe { &*socket };
+        // an unbound socket will return port 0
+        let addr = socket
+            .borrow()
+            .getsockname()
+            .ok()
+            .flatten()
+            .filter(|addr| addr.port() != 0);
+        write_addr(addr, ip, port)
+    }
+
+    fn write_addr(
+        addr: Option<SocketAddrV4>,
+        ip: *mut libc::in_addr_t,
+        port: *mut libc::in_port_t,
+    ) -> bool {
+        let addr = match addr {
+            Some(addr) => addr,
+            None => return false,
+        };
+
+        if !ip.is_null() {
+            unsafe { *ip = u32::from(*addr.ip()).to_be() };
+        }
+
+        if !port.is_null() {
+            unsafe { *port = addr.port().to_be() };
+        }
+
+        true
+    }
+
+    /// Get a borrowed pointer to the next packet that the socket will send, or null if there are
+    /// no packets to send. The packet must not be used after the socket is next modified.
+    #[no_mangle]
+    pub extern "C" fn inetsocket_peekNextOutPacket(socket: *const InetSocket) -> *const c::Packet {
+        assert!(!socket.is_null());
+        let socket = unsafe { &*socket };
+        socket
+            .borrow_mut()
+            .peek_next_out_packet()
+            .map(|packet| packet.borrow_inner())
+            .unwrap_or(std::ptr::null())
+    }
+
+    /// Give a packet to the socket. The socket takes its own reference to the packet, so the
+    /// caller keeps its reference.
+    #[no_mangle]
+    pub extern "C" fn inetsocket_pushInPacket(
+        socket: *const InetSocket,
+        host: *mut c::Host,
+        packet: *mut c::Packet,
+    ) {
+        assert!(!socket.is_null());
+        assert!(!host.is_null());
+        assert!(!packet.is_null());
+        let socket = unsafe { &*socket };
+
+        let packet = unsafe {
+            c::packet_ref(packet);
+            PacketRc::from_raw(packet)
+        };
+
+        EventQueue::queue_and_run(|event_queue| {
+            socket.borrow_mut().push_in_packet(packet, event_queue)
+        });
+    }
+
+    /// Remove the next packet that the socket will send, or return null if there are no packets to
+    /// send. The caller owns the returned packet reference and must unref it.
+    #[no_mangle]
+    pub extern "C" fn inetsocket_pullOutPacket(
+        socket: *const InetSocket,
+        host: *mut c::Host,
+    ) -> *mut c::Packet {
+        assert!(!socket.is_null());
+        assert!(!host.is_null());
+        let socket = unsafe { &*socket };
+
+        EventQueue::queue_and_run(|event_queue| socket.borrow_mut().pull_out_packet(event_queue))
+            .map(|packet| packet.into_inner())
+            .unwrap_or(std::ptr::null_mut())
+    }
+}
@@ -0,0 +1,3091 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::sync::{Arc, Weak};
+
+use atomic_refcell::AtomicRefCell;
+use nix::errno::Errno;
+use nix::sys::socket::MsgFlags;
+
+use crate::core::support::configuration::TcpCongestionAlgorithm;
+use crate::core::support::emulated_time::EmulatedTime;
+use crate::core::support::simulation_time::SimulationTime;
+use crate::core::work::task::TaskRef;
+use crate::core::worker::Worker;
+use crate::cshadow as c;
+use crate::host::descriptor::socket::inet::congestion::{self, AckInfo, CongestionControl};
+use crate::host::descriptor::socket::inet::{self, InetSocket};
+use crate::host::descriptor::socket::{
+    read_int_sockopt, write_int_sockopt, write_sockopt, AncillaryData, Socket,
+};
+use crate::host::descriptor::{
+    File, FileMode, FileState, FileStatus, StateEventSource, StateListenerFilter, SyscallResult,
+};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall::Trigger;
+use crate::host::syscall_condition::SysCallCondition;
+use crate::host::syscall_types::{Blocked, PluginPtr, SysCallReg, SyscallError, TypedPluginPtr};
+use crate::network::packet::{PacketRc, TcpHeader};
+use crate::utility::byte_queue::write_partial;
+use crate::utility::event_queue::{EventQueue, Handle};
+use crate::utility::pod::{self, Pod};
+use crate::utility::stream_len::StreamLen;
+use crate::utility::HostTreePointer;
+
+/// The maximum payload size of a TCP segment.
+const TCP_MSS: u64 = c::CONFIG_TCP_MAX_SEGMENT_SIZE as u64;
+
+/// Windows are advertised in units of `1 << TCP_WINDOW_SCALE` bytes so that large receive buffers
+/// fit in the 16-bit window field.
+const TCP_WINDOW_SCALE: u32 = 7;
+
+/// The retransmission timeout before the round-trip time has been measured (RFC 6298).
+const TCP_RTO_INITIAL_MS: u64 = 1000;
+
+/// The bounds of the retransmission timeout (linux's `TCP_RTO_MIN` and `TCP_RTO_MAX`).
+const TCP_RTO_MIN_MS: u64 = 200;
+const TCP_RTO_MAX_MS: u64 = 120_000;
+
+/// The number of times a SYN or SYN-ACK is retransmitted before giving up (linux's
+/// `net.ipv4.tcp_syn_retries` and `net.ipv4.tcp_synack_retries`).
+const TCP_SYN_RETRIES: u32 = 6;
+const TCP_SYNACK_RETRIES: u32 = 5;
+
+/// The number of times data is retransmitted before giving up (linux's `net.ipv4.tcp_retries2`).
+const TCP_RETRIES: u32 = 15;
+
+/// How long a connection stays in the TIME-WAIT state, and how long a closed socket waits in the
+/// FIN-WAIT-2 state for the peer to close (linux's `TCP_TIMEWAIT_LEN` and
+/// `net.ipv4.tcp_fin_timeout`).
+const TCP_LINGER_MS: u64 = 60_000;
+
+/// The maximum length of a congestion control algorithm name (linux's `TCP_CA_NAME_MAX`).
+const TCP_CA_NAME_MAX: usize = 16;
+
+/// The maximum buffer size that can be set with `SO_SNDBUF` or `SO_RCVBUF`.
+const TCP_SOCKET_MAX_BUFFER_SIZE: u64 = 268_435_456;
+
+/// The minimum buffer sizes that can be set with `SO_SNDBUF` and `SO_RCVBUF` (linux's
+/// `SOCK_MIN_SNDBUF` and `SOCK_MIN_RCVBUF`).
+const TCP_SOCKET_MIN_SEND_BUFFER_SIZE: u64 = 4608;
+const TCP_SOCKET_MIN_RECV_BUFFER_SIZE: u64 = 2304;
+
+/// A TCP socket. The `TcpSocket` is the public-facing API, which forwards API calls to the inner
+/// state object.
+pub struct TcpSocket {
+    /// Data and functionality that is general for all states.
+    common: TcpSocketCommon,
+    /// State-specific data and functionality.
+    protocol_state: ProtocolState,
+}
+
+impl TcpSocket {
+    pub fn new(status: FileStatus, host: HostTreePointer<c::Host>) -> Arc<AtomicRefCell<Self>> {
+        let host_ptr = unsafe { host.ptr() };
+
+        Arc::new_cyclic(|weak| {
+            let common = TcpSocketCommon {
+                this_socket: Weak::clone(weak),
+                host,
+                out_packets: VecDeque::new(),
+                send_buffer_size: unsafe { c::host_getConfiguredSendBufSize(host_ptr) },
+                recv_buffer_size: unsafe { c::host_getConfiguredRecvBufSize(host_ptr) },
+                congestion_algorithm: unsafe { c::host_getTcpCongestionAlgorithm(host_ptr) },
+                nodelay: false,
+                timer_scheduled_for: None,
+                event_source: StateEventSource::new(),
+                state: FileState::ACTIVE,
+                status,
+                has_open_file: false,
+            };
+
+            let protocol_state = Initial { bound_addr: None }.into();
+
+            AtomicRefCell::new(Self {
+                common,
+                protocol_state,
+            })
+        })
+    }
+
+    /// Create a socket for a connection that was requested by a SYN received by the listening
+    /// socket `listener`. The child socket inherits the listener's options.
+    fn new_child(
+        listener: &TcpSocketCommon,
+        bound_addr: SocketAddrV4,
+        peer_addr: SocketAddrV4,
+        syn: &TcpHeader,
+        event_queue: &mut EventQueue,
+    ) -> Arc<AtomicRefCell<Self>> {
+        let socket = Arc::new_cyclic(|weak| {
+            let common = TcpSocketCommon {
+                this_socket: Weak::clone(weak),
+                host: listener.host,
+                out_packets: VecDeque::new(),
+                send_buffer_size: listener.send_buffer_size,
+                recv_buffer_size: listener.recv_buffer_size,
+                congestion_algorithm: listener.congestion_algorithm,
+                nodelay: listener.nodelay,
+                timer_scheduled_for: None,
+                event_source: StateEventSource::new(),
+                state: FileState::ACTIVE,
+                // like linux, accepted sockets don't inherit the listener's file status flags
+                status: FileStatus::empty(),
+                has_open_file: false,
+            };
+
+            let mut connection = Connection::new(
+                &common,
+                bound_addr,
+                peer_addr,
+                TcpState::SynReceived,
+                /* accepted= */ false,
+            );
+            connection.recv.irs = Some(syn.sequence);
+            connection.recv.nxt = 1;
+            connection.recv.ts_recent = syn.timestamp_value;
+            connection.send.window = u64::from(syn.window) << TCP_WINDOW_SCALE;
+
+            AtomicRefCell::new(Self {
+                common,
+                protocol_state: connection.into(),
+            })
+        });
+
+        {
+            let mut socket_mut = socket.borrow_mut();
+            let socket_mut = &mut *socket_mut;
+
+            // the network interfaces will look up the socket's addresses, so we must wait until
+            // the socket is no longer borrowed before associating it
+            socket_mut
+                .common
+                .associate_later(*bound_addr.ip(), event_queue);
+
+            // send the SYN-ACK
+            socket_mut
+                .protocol_state
+                .flush(&mut socket_mut.common, event_queue);
+            socket_mut.refresh_file_state(event_queue);
+        }
+
+        socket
+    }
+
+    pub fn get_status(&self) -> FileStatus {
+        self.common.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.common.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ | FileMode::WRITE
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.common.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        true
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.common.has_open_file = val;
+    }
+
+    pub fn getsockname(&self) -> Result<Option<SocketAddrV4>, SyscallError> {
+        // return the bound address if set, otherwise return an unspecified address
+        Ok(Some(
+            self.protocol_state
+                .bound_address()
+                .unwrap_or(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)),
+        ))
+    }
+
+    pub fn getpeername(&self) -> Result<Option<SocketAddrV4>, SyscallError> {
+        match self.protocol_state.peer_address() {
+            Some(addr) => Ok(Some(addr)),
+            None => Err(Errno::ENOTCONN.into()),
+        }
+    }
+
+    /// The peer address that the network interfaces use to find this socket. Unlike
+    /// [`getpeername()`](Self::getpeername), this doesn't change during the lifetime of a
+    /// connection, so the socket can always be disassociated using the same address that it was
+    /// associated with.
+    pub fn association_peer_address(&self) -> Option<SocketAddrV4> {
+        self.protocol_state.association_peer_address()
+    }
+
+    pub fn address_family(&self) -> nix::sys::socket::AddressFamily {
+        nix::sys::socket::AddressFamily::Inet
+    }
+
+    pub fn socket_type(&self) -> nix::sys::socket::SockType {
+        nix::sys::socket::SockType::Stream
+    }
+
+    pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        self.protocol_state.close(&mut self.common, event_queue)
+    }
+
+    fn refresh_file_state(&mut self, event_queue: &mut EventQueue) {
+        self.protocol_state
+            .refresh_file_state(&mut self.common, event_queue)
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn bind(
+        socket: &Arc<AtomicRefCell<Self>>,
+        addr: Option<&nix::sys::socket::SockAddr>,
+        _rng: impl rand::Rng,
+    ) -> SyscallResult {
+        Self::with_reassociation(socket, |socket| {
+            socket
+                .protocol_state
+                .bind(&mut socket.common, addr)
+                .map(|_| 0.into())
+        })
+    }
+
+    pub fn read<W>(
+        &mut self,
+        bytes: W,
+        _offset: libc::off_t,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        // the read() syscall handler should call TcpSocket::recvfrom() instead, but other syscalls
+        // (for example splice()) may read from the socket; sockets aren't seekable, so the offset
+        // is ignored
+        self.recvfrom(bytes, MsgFlags::empty(), event_queue)
+            .map(|(rv, _from_addr, _msg_len, _ancillary)| rv)
+    }
+
+    pub fn write<R>(
+        &mut self,
+        bytes: R,
+        _offset: libc::off_t,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        // the write() syscall handler should call TcpSocket::sendto() instead, but other syscalls
+        // (for example sendfile()) may write to the socket; sockets aren't seekable, so the offset
+        // is ignored
+        self.sendto(bytes, 0, None, AncillaryData::default(), event_queue)
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn sendto<R>(
+        &mut self,
+        bytes: R,
+        _flags: libc::c_int,
+        _addr: Option<nix::sys::socket::SockAddr>,
+        ancillary: AncillaryData,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        // tcp sockets can't pass file descriptors, and linux ignores credentials for inet sockets
+        if !ancillary.rights.is_empty() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        // like linux, the address is ignored for stream sockets, and shadow doesn't delay sending
+        // data so `MSG_MORE` has no effect
+        self.protocol_state
+            .sendto(&mut self.common, bytes, event_queue)
+    }
+
+    /// Returns the number of bytes copied, the source address, and the number of bytes copied
+    /// (tcp sockets don't have message boundaries, so data is never truncated).
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn recvfrom<W>(
+        &mut self,
+        bytes: W,
+        flags: MsgFlags,
+        event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        self.protocol_state
+            .recvfrom(&mut self.common, bytes, flags, event_queue)
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: u64,
+        arg_ptr: PluginPtr,
+        memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        let val = match request {
+            // the number of bytes that can be read (also known as `SIOCINQ`)
+            libc::FIONREAD => self.protocol_state.recv_buffer_len(),
+            // the number of bytes that haven't been acknowledged (also known as `SIOCOUTQ`)
+            libc::TIOCOUTQ => self.protocol_state.send_buffer_len(),
+            libc::TCGETS
+            | libc::TCSETS
+            | libc::TCSETSW
+            | libc::TCSETSF
+            | libc::TCGETA
+            | libc::TCSETA
+            | libc::TCSETAW
+            | libc::TCSETAF
+            | libc::TIOCGWINSZ
+            | libc::TIOCSWINSZ => {
+                // not a terminal
+                return Err(Errno::ENOTTY.into());
+            }
+            _ => {
+                log::warn!(
+                    "We do not yet handle ioctl request {} on tcp sockets",
+                    request
+                );
+                return Err(Errno::EINVAL.into());
+            }
+        };
+
+        let val = libc::c_int::try_from(val).unwrap();
+        let arg_ptr = TypedPluginPtr::new::<libc::c_int>(arg_ptr, 1);
+        memory_manager.copy_to_ptr(arg_ptr, &[val])?;
+
+        Ok(0.into())
+    }
+
+    /// Write the option value to `optval_ptr`, and return the option's length. This takes a
+    /// mutable reference since reading `SO_ERROR` clears the socket's error.
+    pub fn getsockopt(
+        &mut self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &mut MemoryManager,
+    ) -> Result<libc::socklen_t, SyscallError> {
+        match (level, optname) {
+            (libc::IPPROTO_TCP, libc::TCP_INFO) => {
+                let info = self.protocol_state.tcp_info();
+                return write_sockopt(
+                    pod::to_u8_slice(&[info]),
+                    optval_ptr,
+                    optlen,
+                    memory_manager,
+                );
+            }
+            (libc::IPPROTO_TCP, libc::TCP_CONGESTION) => {
+                let name = self.common.congestion_algorithm.name();
+
+                // like linux, the returned length doesn't depend on the length of the name
+                let mut optval = [0u8; TCP_CA_NAME_MAX];
+                optval[..name.len()].copy_from_slice(name.as_bytes());
+
+                return write_sockopt(&optval, optval_ptr, optlen, memory_manager);
+            }
+            _ => {}
+        }
+
+        let optval: libc::c_int = match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_TYPE) => libc::SOCK_STREAM,
+            (libc::SOL_SOCKET, libc::SO_DOMAIN) => libc::AF_INET,
+            (libc::SOL_SOCKET, libc::SO_PROTOCOL) => libc::IPPROTO_TCP,
+            // like linux, reading the error clears it
+            (libc::SOL_SOCKET, libc::SO_ERROR) => {
+                let error = self.protocol_state.take_error();
+                self.refresh_file_state_later();
+                error.map_or(0, |e| e as libc::c_int)
+            }
+            (libc::SOL_SOCKET, libc::SO_ACCEPTCONN) => {
+                matches!(self.protocol_state, ProtocolState::Listening(_)).into()
+            }
+            (libc::SOL_SOCKET, libc::SO_SNDBUF) => self.common.send_buffer_size.try_into().unwrap(),
+            (libc::SOL_SOCKET, libc::SO_RCVBUF) => self.common.recv_buffer_size.try_into().unwrap(),
+            (libc::IPPROTO_TCP, libc::TCP_NODELAY) => self.common.nodelay.into(),
+            _ => {
+                log::warn!(
+                    "getsockopt() for level {} and option {} is not supported for tcp sockets",
+                    level,
+                    optname
+                );
+                return Err(Errno::ENOPROTOOPT.into());
+            }
+        };
+
+        write_int_sockopt(optval, optval_ptr, optlen, memory_manager)
+    }
+
+    pub fn setsockopt(
+        &mut self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &MemoryManager,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_SNDBUF) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.common.send_buffer_size =
+                    sockopt_buffer_size(optval, TCP_SOCKET_MIN_SEND_BUFFER_SIZE);
+            }
+            (libc::SOL_SOCKET, libc::SO_RCVBUF) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.common.recv_buffer_size =
+                    sockopt_buffer_size(optval, TCP_SOCKET_MIN_RECV_BUFFER_SIZE);
+            }
+            // these options have no effect in shadow
+            (libc::SOL_SOCKET, libc::SO_REUSEADDR | libc::SO_REUSEPORT | libc::SO_KEEPALIVE) => {
+                read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+            }
+            // shadow doesn't implement nagle's algorithm, so segments are never delayed
+            (libc::IPPROTO_TCP, libc::TCP_NODELAY) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.common.nodelay = optval != 0;
+            }
+            (libc::IPPROTO_TCP, libc::TCP_CONGESTION) => {
+                // the name may not be nul-terminated
+                let len = std::cmp::min(usize::try_from(optlen).unwrap(), TCP_CA_NAME_MAX);
+                let mut optval = vec![0u8; len];
+                memory_manager
+                    .copy_from_ptr(&mut optval, TypedPluginPtr::new::<u8>(optval_ptr, len))?;
+
+                let name = optval.split(|x| *x == 0).next().unwrap();
+                let name = String::from_utf8_lossy(name);
+
+                let algorithm = match TcpCongestionAlgorithm::from_name(&name) {
+                    Some(x) => x,
+                    None => {
+                        log::warn!(
+                            "Shadow sockets only support 'reno', 'cubic', and 'bbr' for \
+                             TCP_CONGESTION, not '{}'",
+                            name
+                        );
+                        return Err(Errno::ENOENT.into());
+                    }
+                };
+
+                self.common.congestion_algorithm = algorithm;
+                self.protocol_state.set_congestion_algorithm(algorithm);
+            }
+            _ => {
+                log::warn!(
+                    "setsockopt() for level {} and option {} is not supported for tcp sockets",
+                    level,
+                    optname
+                );
+                return Err(Errno::ENOPROTOOPT.into());
+            }
+        }
+
+        // the buffer sizes may have changed
+        self.refresh_file_state(event_queue);
+
+        Ok(())
+    }
+
+    pub fn listen(
+        &mut self,
+        backlog: i32,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        self.protocol_state
+            .listen(&mut self.common, backlog, event_queue)
+    }
+
+    pub fn shutdown(
+        &mut self,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        self.protocol_state
+            .shutdown(&mut self.common, how, event_queue)
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn connect(
+        socket: &Arc<AtomicRefCell<Self>>,
+        addr: &nix::sys::socket::SockAddr,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let peer_addr = match inet::sockaddr_to_ipv4(addr) {
+            Some(addr) => addr,
+            None => {
+                log::warn!("Unsupported tcp peer address {:?}", addr);
+                return Err(Errno::EAFNOSUPPORT.into());
+            }
+        };
+
+        // like linux, an unspecified peer address refers to this host
+        let peer_addr = if peer_addr.ip().is_unspecified() {
+            SocketAddrV4::new(Ipv4Addr::LOCALHOST, peer_addr.port())
+        } else {
+            peer_addr
+        };
+
+        // make sure we will be able to route packets to the peer
+        let host = socket.borrow().common.host_ptr();
+        if !inet::is_routable(host, *peer_addr.ip()) {
+            log::warn!(
+                "Attempting to connect to address '{}' for which no host exists",
+                peer_addr
+            );
+            return Err(Errno::ECONNREFUSED.into());
+        }
+
+        Self::with_reassociation(socket, |socket| {
+            socket
+                .protocol_state
+                .connect(&mut socket.common, peer_addr, event_queue)
+        })
+    }
+
+    /// Disconnect the socket from its peer (`connect()` with an `AF_UNSPEC` address).
+    pub fn disconnect(
+        _socket: &Arc<AtomicRefCell<Self>>,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        log::warn!("Disconnecting tcp sockets is not supported");
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    pub fn accept(
+        &mut self,
+        event_queue: &mut EventQueue,
+    ) -> Result<Arc<AtomicRefCell<TcpSocket>>, SyscallError> {
+        self.protocol_state.accept(&mut self.common, event_queue)
+    }
+
+    /// Run `f`, which may change the socket's bound or peer address. The network interfaces find
+    /// the socket using these addresses, so the socket is disassociated from the interfaces
+    /// before running `f`, and then re-associated using the (possibly new) addresses. The
+    /// interfaces will look up the socket's addresses, so the socket must not be borrowed when
+    /// calling this function.
+    fn with_reassociation<T>(
+        socket: &Arc<AtomicRefCell<Self>>,
+        f: impl FnOnce(&mut Self) -> Result<T, SyscallError>,
+    ) -> Result<T, SyscallError> {
+        let inet_socket = InetSocket::Tcp(Arc::clone(socket));
+
+        let (host, old_bound_addr) = {
+            let socket = socket.borrow();
+            (
+                socket.common.host_ptr(),
+                socket.protocol_state.associated_address(),
+            )
+        };
+
+        if old_bound_addr.is_some() {
+            inet::disassociate(host, &inet_socket);
+        }
+
+        let rv = f(&mut socket.borrow_mut());
+
+        let new_bound_addr = socket.borrow().protocol_state.associated_address();
+        if let Some(addr) = new_bound_addr {
+            inet::associate(host, &inet_socket, *addr.ip());
+        }
+
+        rv
+    }
+
+    /// The next packet that will be returned by [`pull_out_packet()`](Self::pull_out_packet), if
+    /// any.
+    pub fn peek_next_out_packet(&self) -> Option<&PacketRc> {
+        self.common.out_packets.front()
+    }
+
+    /// Remove the next packet so that it can be sent by the network interface. The packet's
+    /// acknowledgement, window, and timestamps are set to their current values.
+    pub fn pull_out_packet(&mut self, event_queue: &mut EventQueue) -> Option<PacketRc> {
+        let mut packet = self.common.out_packets.pop_front()?;
+        self.protocol_state
+            .prepare_out_packet(&self.common, &mut packet);
+
+        // the advertised window may have changed
+        self.refresh_file_state(event_queue);
+
+        Some(packet)
+    }
+
+    /// Process a packet received by the network interface.
+    pub fn push_in_packet(&mut self, packet: PacketRc, event_queue: &mut EventQueue) {
+        self.protocol_state
+            .push_in_packet(&mut self.common, packet, event_queue)
+    }
+
+    /// Called by the timer task that was scheduled for time `scheduled_for`.
+    fn timer_fired(&mut self, scheduled_for: EmulatedTime, event_queue: &mut EventQueue) {
+        if self.common.timer_scheduled_for == Some(scheduled_for) {
+            self.common.timer_scheduled_for = None;
+        }
+
+        self.protocol_state
+            .timer_fired(&mut self.common, event_queue)
+    }
+
+    /// The state of a connection created by a listening socket, which the listening socket uses
+    /// to decide whether the connection is ready to be accepted.
+    fn child_state(&self) -> ChildState {
+        match &self.protocol_state {
+            ProtocolState::Connection(Some(x)) if x.was_established => ChildState::Ready,
+            ProtocolState::Connection(Some(x)) if x.state != TcpState::Closed => {
+                ChildState::Pending
+            }
+            _ => ChildState::Failed,
+        }
+    }
+
+    /// Whether a new connection from the same peer could replace this connection created by a
+    /// listening socket.
+    fn is_reusable_child(&self) -> bool {
+        match &self.protocol_state {
+            ProtocolState::Connection(Some(x)) => x.disassociated,
+            _ => true,
+        }
+    }
+
+    /// Mark a connection created by a listening socket as belonging to the plugin.
+    fn set_accepted(&mut self) {
+        if let ProtocolState::Connection(Some(x)) = &mut self.protocol_state {
+            x.accepted = true;
+        }
+    }
+
+    /// Reset a connection created by a listening socket that was closed before the connection
+    /// was accepted.
+    fn abort_child(&mut self, event_queue: &mut EventQueue) {
+        if let ProtocolState::Connection(Some(x)) = &mut self.protocol_state {
+            x.user_closed = true;
+            x.abort(&mut self.common, event_queue);
+            x.refresh_file_state(&mut self.common, event_queue);
+        }
+    }
+
+    /// Refresh the file state once the socket is no longer borrowed. This is used by methods
+    /// that don't have access to an event queue.
+    fn refresh_file_state_later(&self) {
+        if let Some(socket) = self.common.this_socket.upgrade() {
+            let mut task = TaskRef::new(move |_host| {
+                EventQueue::queue_and_run(|event_queue| {
+                    socket.borrow_mut().refresh_file_state(event_queue)
+                });
+            });
+            unsafe {
+                c::worker_scheduleTaskWithDelay(
+                    &mut task,
+                    self.common.host_ptr(),
+                    SimulationTime::to_c_simtime(Some(SimulationTime::ZERO)),
+                )
+            };
+        }
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState)> {
+        self.common
+            .event_source
+            .add_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        self.common
+            .event_source
+            .add_exclusive_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.common.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.common.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.common.state
+    }
+}
+
+/// See [`TcpSocket::child_state()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ChildState {
+    /// The handshake hasn't completed.
+    Pending,
+    /// The handshake completed, so the connection can be accepted.
+    Ready,
+    /// The connection closed before the handshake completed.
+    Failed,
+}
+
+/// A socket that hasn't connected or started listening.
+struct Initial {
+    bound_addr: Option<SocketAddrV4>,
+}
+struct Listening {
+    bound_addr: SocketAddrV4,
+    /// The max number of connections in the accept queue.
+    queue_limit: u32,
+    /// The connections created by this socket, indexed by the peer's address. These are used to
+    /// forward packets to the connections, since the network interfaces give all packets for the
+    /// listening port to the listening socket.
+    children: HashMap<SocketAddrV4, Weak<AtomicRefCell<TcpSocket>>>,
+    /// Connections that haven't completed the handshake.
+    pending: Vec<Arc<AtomicRefCell<TcpSocket>>>,
+    /// Connections that have completed the handshake and are waiting to be accepted.
+    accept_queue: VecDeque<Arc<AtomicRefCell<TcpSocket>>>,
+}
+struct Closed {
+    // the address is kept so that the socket can still be disassociated from the network
+    // interfaces after it's closed
+    bound_addr: Option<SocketAddrV4>,
+}
+
+/// The state of a connection, as described in RFC 793.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TcpState {
+    SynSent,
+    SynReceived,
+    Established,
+    FinWait1,
+    FinWait2,
+    Closing,
+    TimeWait,
+    CloseWait,
+    LastAck,
+    Closed,
+}
+
+impl TcpState {
+    /// The value of linux's `tcpi_state` for this state.
+    fn to_linux(self) -> u8 {
+        match self {
+            Self::Established => 1,
+            Self::SynSent => 2,
+            Self::SynReceived => 3,
+            Self::FinWait1 => 4,
+            Self::FinWait2 => 5,
+            Self::TimeWait => 6,
+            Self::Closed => 7,
+            Self::CloseWait => 8,
+            Self::LastAck => 9,
+            Self::Closing => 11,
+        }
+    }
+
+    /// Whether the connection can send data (or a FIN) in this state.
+    fn can_send(self) -> bool {
+        matches!(
+            self,
+            Self::Established | Self::CloseWait | Self::FinWait1 | Self::Closing | Self::LastAck
+        )
+    }
+
+    /// Whether the connection can receive data (or a FIN) in this state.
+    fn can_receive(self) -> bool {
+        matches!(self, Self::Established | Self::FinWait1 | Self::FinWait2)
+    }
+}
+
+/// The sending half of a connection. Sequence numbers are stored as 64-bit offsets from the
+/// initial sequence number so that they don't wrap; the SYN has offset 0, data starts at offset
+/// 1, and the FIN follows the data.
+struct SendState {
+    /// The initial sequence number.
+    isn: u32,
+    /// Data written by the plugin that hasn't been acknowledged.
+    buffer: VecDeque<u8>,
+    /// The offset of the first byte in `buffer`.
+    buffer_offset: u64,
+    /// The first offset that hasn't been acknowledged.
+    una: u64,
+    /// The next offset to send.
+    nxt: u64,
+    /// The highest offset that has been sent. This is larger than `nxt` while retransmitting.
+    max: u64,
+    /// The peer's receive window, in bytes.
+    window: u64,
+    /// The end offsets of the segments that have been sent but not acknowledged.
+    in_flight: VecDeque<u64>,
+    /// Whether a FIN will be sent after the data in `buffer`.
+    fin_queued: bool,
+    /// Whether to send a byte even though the peer's window is zero.
+    probe: bool,
+}
+
+impl SendState {
+    /// The offset following the last byte of data.
+    fn data_end(&self) -> u64 {
+        self.buffer_offset + self.buffer.len() as u64
+    }
+
+    /// Whether our FIN has been acknowledged.
+    fn fin_acked(&self) -> bool {
+        self.fin_queued && self.una > self.data_end()
+    }
+
+    fn to_wire(&self, offset: u64) -> u32 {
+        self.isn.wrapping_add(offset as u32)
+    }
+}
+
+/// The receiving half of a connection. Sequence numbers are stored as 64-bit offsets from the
+/// peer's initial sequence number, as in [`SendState`].
+struct RecvState {
+    /// The peer's initial sequence number, which is unknown until its SYN is received.
+    irs: Option<u32>,
+    /// The next offset that we expect to receive.
+    nxt: u64,
+    /// Data that has been received in order but not read by the plugin.
+    buffer: VecDeque<u8>,
+    /// Data that has been received out of order, indexed by offset.
+    out_of_order: BTreeMap<u64, Vec<u8>>,
+    /// The number of bytes in `out_of_order`.
+    out_of_order_len: u64,
+    /// The offset of the peer's FIN, if it has been received.
+    fin_offset: Option<u64>,
+    /// Whether all data up to and including the peer's FIN has been received.
+    fin_received: bool,
+    /// The window that we last advertised to the peer, in bytes.
+    advertised_window: u64,
+    /// The most recent timestamp received from the peer, which is echoed back to the peer.
+    ts_recent: c::SimulationTime,
+}
+
+impl RecvState {
+    /// The acknowledgement number to send.
+    fn ack(&self) -> u32 {
+        self.irs.map_or(0, |irs| irs.wrapping_add(self.nxt as u32))
+    }
+
+    /// Move out-of-order data that is now in order into the receive buffer.
+    fn reassemble(&mut self) {
+        while let Some(start) = self.out_of_order.keys().next().copied() {
+            if start > self.nxt {
+                break;
+            }
+
+            let data = self.out_of_order.remove(&start).unwrap();
+            self.out_of_order_len -= data.len() as u64;
+
+            let end = start + data.len() as u64;
+            if end > self.nxt {
+                self.buffer
+                    .extend(&data[usize::try_from(self.nxt - start).unwrap()..]);
+                self.nxt = end;
+            }
+        }
+    }
+}
+
+/// Round-trip time and retransmission state.
+struct Timing {
+    /// The smoothed round-trip time, if it has been measured.
+    srtt: Option<SimulationTime>,
+    /// The round-trip time variation.
+    rttvar: SimulationTime,
+    /// The retransmission timeout, including any backoff.
+    rto: SimulationTime,
+    /// The number of consecutive retransmission timeouts.
+    retries: u32,
+    /// The total number of retransmitted segments.
+    total_retransmits: u32,
+    /// When the retransmission (or persist) timer expires.
+    rto_deadline: Option<EmulatedTime>,
+    /// When the connection will be closed while in the TIME-WAIT state, or in the FIN-WAIT-2
+    /// state after the socket was closed.
+    linger_deadline: Option<EmulatedTime>,
+}
+
+impl Timing {
+    /// Update the round-trip time estimate with a new sample, as described in RFC 6298.
+    fn add_rtt_sample(&mut self, rtt: SimulationTime) {
+        match self.srtt {
+            None => {
+                self.srtt = Some(rtt);
+                self.rttvar = rtt / 2;
+            }
+            Some(srtt) => {
+                let diff = if srtt > rtt { srtt - rtt } else { rtt - srtt };
+                self.rttvar = (self.rttvar * 3) / 4 + diff / 4;
+                self.srtt = Some((srtt * 7) / 8 + rtt / 8);
+            }
+        }
+
+        let rto = self.srtt.unwrap() + self.rttvar * 4;
+        self.rto = clamp_rto(rto);
+    }
+}
+
+/// A connection that is being established, is established, or has closed.
+struct Connection {
+    bound_addr: SocketAddrV4,
+    peer_addr: SocketAddrV4,
+    state: TcpState,
+    /// Whether the connection belongs to the plugin. This is false for connections created by a
+    /// listening socket that haven't been accepted yet.
+    accepted: bool,
+    /// Whether the plugin has closed the socket.
+    user_closed: bool,
+    /// Whether the socket has been disassociated from the network interfaces.
+    disassociated: bool,
+    /// Whether the handshake completed at some point.
+    was_established: bool,
+    /// Whether a `connect()` call hasn't returned the result of the handshake yet.
+    connect_in_progress: bool,
+    /// An error that will be returned by the next syscall, or by `SO_ERROR`.
+    error: Option<Errno>,
+    /// Whether reading has been shut down.
+    read_shutdown: bool,
+    /// Whether writing has been shut down.
+    write_shutdown: bool,
+    cong: Box<dyn CongestionControl>,
+    send: SendState,
+    recv: RecvState,
+    timing: Timing,
+}
+
+/// The current protocol state of the tcp socket. An `Option` is required for each variant so that
+/// the inner state object can be removed, transformed into a new state, and then re-added as a
+/// different variant.
+enum ProtocolState {
+    Initial(Option<Initial>),
+    Listening(Option<Listening>),
+    Connection(Option<Connection>),
+    Closed(Option<Closed>),
+}
+
+/// Upcast from a type to an enum variant.
+macro_rules! state_upcast {
+    ($type:ty, $parent:ident::$variant:ident) => {
+        impl From<$type> for $parent {
+            fn from(x: $type) -> Self {
+                Self::$variant(Some(x))
+            }
+        }
+    };
+}
+
+// implement upcasting for all state types
+state_upcast!(Initial, ProtocolState::Initial);
+state_upcast!(Listening, ProtocolState::Listening);
+state_upcast!(Connection, ProtocolState::Connection);
+state_upcast!(Closed, ProtocolState::Closed);
+
+impl ProtocolState {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        match self {
+            Self::Initial(x) => x.as_ref().unwrap().peer_address(),
+            Self::Listening(x) => x.as_ref().unwrap().peer_address(),
+            Self::Connection(x) => x.as_ref().unwrap().peer_address(),
+            Self::Closed(x) => x.as_ref().unwrap().peer_address(),
+        }
+    }
+
+    fn association_peer_address(&self) -> Option<SocketAddrV4> {
+        match self {
+            Self::Connection(x) => Some(x.as_ref().unwrap().peer_addr),
+            _ => None,
+        }
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        match self {
+            Self::Initial(x) => x.as_ref().unwrap().bound_address(),
+            Self::Listening(x) => x.as_ref().unwrap().bound_address(),
+            Self::Connection(x) => x.as_ref().unwrap().bound_address(),
+            Self::Closed(x) => x.as_ref().unwrap().bound_address(),
+        }
+    }
+
+    /// The bound address if the socket is associated with the network interfaces.
+    fn associated_address(&self) -> Option<SocketAddrV4> {
+        match self {
+            Self::Initial(x) => x.as_ref().unwrap().bound_addr,
+            Self::Listening(x) => Some(x.as_ref().unwrap().bound_addr),
+            Self::Connection(x) => {
+                let x = x.as_ref().unwrap();
+                (!x.disassociated).then(|| x.bound_addr)
+            }
+            Self::Closed(_) => None,
+        }
+    }
+
+    fn recv_buffer_len(&self) -> u64 {
+        match self {
+            Self::Connection(x) => x.as_ref().unwrap().recv.buffer.len() as u64,
+            _ => 0,
+        }
+    }
+
+    fn send_buffer_len(&self) -> u64 {
+        match self {
+            Self::Connection(x) => x.as_ref().unwrap().send.buffer.len() as u64,
+            _ => 0,
+        }
+    }
+
+    fn take_error(&mut self) -> Option<Errno> {
+        match self {
+            Self::Connection(x) => x.as_mut().unwrap().error.take(),
+            _ => None,
+        }
+    }
+
+    fn set_congestion_algorithm(&mut self, algorithm: TcpCongestionAlgorithm) {
+        if let Self::Connection(x) = self {
+            let x = x.as_mut().unwrap();
+            if x.cong.algorithm() != algorithm {
+                x.cong = congestion::new_congestion_control(algorithm);
+            }
+        }
+    }
+
+    fn tcp_info(&self) -> TcpInfo {
+        match self {
+            Self::Connection(x) => x.as_ref().unwrap().tcp_info(),
+            Self::Listening(_) => TcpInfo {
+                tcpi_state: TCP_LISTEN,
+                ..TcpInfo::new()
+            },
+            Self::Initial(_) | Self::Closed(_) => TcpInfo {
+                tcpi_state: TcpState::Closed.to_linux(),
+                ..TcpInfo::new()
+            },
+        }
+    }
+
+    fn prepare_out_packet(&mut self, common: &TcpSocketCommon, packet: &mut PacketRc) {
+        if let Self::Connection(x) = self {
+            x.as_mut().unwrap().prepare_out_packet(common, packet);
+        }
+    }
+
+    fn refresh_file_state(&self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        match self {
+            Self::Initial(x) => x.as_ref().unwrap().refresh_file_state(common, event_queue),
+            Self::Listening(x) => x.as_ref().unwrap().refresh_file_state(common, event_queue),
+            Self::Connection(x) => x.as_ref().unwrap().refresh_file_state(common, event_queue),
+            Self::Closed(x) => x.as_ref().unwrap().refresh_file_state(common, event_queue),
+        }
+    }
+
+    fn close(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let (new_state, rv) = match self {
+            Self::Initial(x) => x.take().unwrap().close(common, event_queue),
+            Self::Listening(x) => x.take().unwrap().close(common, event_queue),
+            Self::Connection(x) => x.take().unwrap().close(common, event_queue),
+            Self::Closed(x) => x.take().unwrap().close(common, event_queue),
+        };
+
+        *self = new_state;
+        rv
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        match self {
+            Self::Initial(x) => x.as_mut().unwrap().bind(common, addr),
+            Self::Listening(x) => x.as_mut().unwrap().bind(common, addr),
+            Self::Connection(x) => x.as_mut().unwrap().bind(common, addr),
+            Self::Closed(x) => x.as_mut().unwrap().bind(common, addr),
+        }
+    }
+
+    fn listen(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        backlog: i32,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let (new_state, rv) = match self {
+            Self::Initial(x) => x.take().unwrap().listen(common, backlog, event_queue),
+            Self::Listening(x) => x.take().unwrap().listen(common, backlog, event_queue),
+            Self::Connection(x) => x.take().unwrap().listen(common, backlog, event_queue),
+            Self::Closed(x) => x.take().unwrap().listen(common, backlog, event_queue),
+        };
+
+        *self = new_state;
+        rv
+    }
+
+    fn connect(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        peer_addr: SocketAddrV4,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let (new_state, rv) = match self {
+            Self::Initial(x) => x.take().unwrap().connect(common, peer_addr, event_queue),
+            Self::Listening(x) => x.take().unwrap().connect(common, peer_addr, event_queue),
+            Self::Connection(x) => x.take().unwrap().connect(common, peer_addr, event_queue),
+            Self::Closed(x) => x.take().unwrap().connect(common, peer_addr, event_queue),
+        };
+
+        *self = new_state;
+        rv
+    }
+
+    fn accept(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> Result<Arc<AtomicRefCell<TcpSocket>>, SyscallError> {
+        match self {
+            Self::Initial(x) => x.as_mut().unwrap().accept(common, event_queue),
+            Self::Listening(x) => x.as_mut().unwrap().accept(common, event_queue),
+            Self::Connection(x) => x.as_mut().unwrap().accept(common, event_queue),
+            Self::Closed(x) => x.as_mut().unwrap().accept(common, event_queue),
+        }
+    }
+
+    fn sendto<R>(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        bytes: R,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        match self {
+            Self::Initial(x) => x.as_mut().unwrap().sendto(common, bytes, event_queue),
+            Self::Listening(x) => x.as_mut().unwrap().sendto(common, bytes, event_queue),
+            Self::Connection(x) => x.as_mut().unwrap().sendto(common, bytes, event_queue),
+            Self::Closed(x) => x.as_mut().unwrap().sendto(common, bytes, event_queue),
+        }
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        bytes: W,
+        flags: MsgFlags,
+        event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        match self {
+            Self::Initial(x) => x
+                .as_mut()
+                .unwrap()
+                .recvfrom(common, bytes, flags, event_queue),
+            Self::Listening(x) => x
+                .as_mut()
+                .unwrap()
+                .recvfrom(common, bytes, flags, event_queue),
+            Self::Connection(x) => x
+                .as_mut()
+                .unwrap()
+                .recvfrom(common, bytes, flags, event_queue),
+            Self::Closed(x) => x
+                .as_mut()
+                .unwrap()
+                .recvfrom(common, bytes, flags, event_queue),
+        }
+    }
+
+    fn shutdown(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        match self {
+            Self::Initial(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+            Self::Listening(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+            Self::Connection(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+            Self::Closed(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+        }
+    }
+
+    fn push_in_packet(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        packet: PacketRc,
+        event_queue: &mut EventQueue,
+    ) {
+        match self {
+            Self::Initial(x) => x
+                .as_mut()
+                .unwrap()
+                .push_in_packet(common, packet, event_queue),
+            Self::Listening(x) => x
+                .as_mut()
+                .unwrap()
+                .push_in_packet(common, packet, event_queue),
+            Self::Connection(x) => x
+                .as_mut()
+                .unwrap()
+                .push_in_packet(common, packet, event_queue),
+            Self::Closed(x) => x
+                .as_mut()
+                .unwrap()
+                .push_in_packet(common, packet, event_queue),
+        }
+    }
+
+    fn flush(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        if let Self::Connection(x) = self {
+            x.as_mut().unwrap().flush(common, event_queue);
+        }
+    }
+
+    fn timer_fired(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        if let Self::Connection(x) = self {
+            x.as_mut().unwrap().timer_fired(common, event_queue);
+        }
+    }
+}
+
+trait Protocol
+where
+    Self: Sized + Into<ProtocolState>,
+{
+    fn peer_address(&self) -> Option<SocketAddrV4>;
+    fn bound_address(&self) -> Option<SocketAddrV4>;
+    fn refresh_file_state(&self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue);
+
+    fn close(
+        self,
+        _common: &mut TcpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        log::warn!("close() while in state {}", std::any::type_name::<Self>());
+        (self.into(), Err(Errno::EOPNOTSUPP.into()))
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        log::warn!("bind() while in state {}", std::any::type_name::<Self>());
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    fn listen(
+        self,
+        _common: &mut TcpSocketCommon,
+        _backlog: i32,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        log::warn!("listen() while in state {}", std::any::type_name::<Self>());
+        (self.into(), Err(Errno::EOPNOTSUPP.into()))
+    }
+
+    fn connect(
+        self,
+        _common: &mut TcpSocketCommon,
+        _peer_addr: SocketAddrV4,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        log::warn!("connect() while in state {}", std::any::type_name::<Self>());
+        (self.into(), Err(Errno::EOPNOTSUPP.into()))
+    }
+
+    fn accept(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> Result<Arc<AtomicRefCell<TcpSocket>>, SyscallError> {
+        // linux returns EINVAL for sockets that aren't listening
+        Err(Errno::EINVAL.into())
+    }
+
+    fn sendto<R>(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _bytes: R,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        log::warn!("sendto() while in state {}", std::any::type_name::<Self>());
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _bytes: W,
+        _flags: MsgFlags,
+        _event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        log::warn!(
+            "recvfrom() while in state {}",
+            std::any::type_name::<Self>()
+        );
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    fn shutdown(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _how: nix::sys::socket::Shutdown,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        log::warn!(
+            "shutdown() while in state {}",
+            std::any::type_name::<Self>()
+        );
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    fn push_in_packet(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        mut packet: PacketRc,
+        _event_queue: &mut EventQueue,
+    ) {
+        log::trace!(
+            "Dropping packet received while in state {}",
+            std::any::type_name::<Self>()
+        );
+        packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+    }
+}
+
+impl Protocol for Initial {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        None
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        self.bound_addr
+    }
+
+    fn refresh_file_state(&self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        common.copy_state(
+            /* mask= */ FileState::all(),
+            FileState::ACTIVE,
+            event_queue,
+        );
+    }
+
+    fn close(
+        self,
+        common: &mut TcpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        let new_state = Closed {
+            bound_addr: self.bound_addr,
+        };
+        new_state.refresh_file_state(common, event_queue);
+
+        // the socket should no longer reserve its port
+        if self.bound_addr.is_some() {
+            common.disassociate_later(event_queue);
+        }
+        (new_state.into(), common.close(event_queue))
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        // if already bound
+        if self.bound_addr.is_some() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let addr = match addr.and_then(inet::sockaddr_to_ipv4) {
+            Some(addr) => addr,
+            None => {
+                log::warn!("Unsupported tcp bind address {:?}", addr);
+                return Err(Errno::EINVAL.into());
+            }
+        };
+
+        let bound_addr = common.bind(addr, SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+        self.bound_addr = Some(bound_addr);
+
+        Ok(bound_addr)
+    }
+
+    fn listen(
+        self,
+        common: &mut TcpSocketCommon,
+        backlog: i32,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        // like linux, bind to an ephemeral port if the socket isn't already bound
+        let bound_addr = match self.bound_addr {
+            Some(addr) => addr,
+            None => {
+                let unspecified = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+                let addr = match common.bind(unspecified, unspecified) {
+                    Ok(addr) => addr,
+                    Err(e) => return (self.into(), Err(e)),
+                };
+
+                // the network interfaces will look up the socket's addresses, so we must wait
+                // until the socket is no longer borrowed before associating it
+                common.associate_later(*addr.ip(), event_queue);
+
+                addr
+            }
+        };
+
+        let new_state = Listening {
+            bound_addr,
+            queue_limit: backlog_to_queue_size(backlog),
+            children: HashMap::new(),
+            pending: Vec::new(),
+            accept_queue: VecDeque::new(),
+        };
+        new_state.refresh_file_state(common, event_queue);
+
+        (new_state.into(), Ok(()))
+    }
+
+    fn connect(
+        self,
+        common: &mut TcpSocketCommon,
+        peer_addr: SocketAddrV4,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        let bound_addr = match self.bound_addr {
+            Some(addr) => addr,
+            None => {
+                // like linux, bind to an ephemeral port if the socket isn't already bound
+                match common.bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0), peer_addr) {
+                    Ok(addr) => addr,
+                    Err(e) => return (self.into(), Err(e)),
+                }
+            }
+        };
+
+        // like linux, if the socket is bound to the unspecified address, the local address is
+        // chosen based on the route to the peer
+        let bound_addr = if bound_addr.ip().is_unspecified() {
+            SocketAddrV4::new(
+                inet::source_ip_for(common.host_ptr(), *peer_addr.ip()),
+                bound_addr.port(),
+            )
+        } else {
+            bound_addr
+        };
+
+        let mut connection = Connection::new(
+            common,
+            bound_addr,
+            peer_addr,
+            TcpState::SynSent,
+            /* accepted= */ true,
+        );
+        connection.connect_in_progress = true;
+
+        // send the SYN
+        connection.flush(common, event_queue);
+        connection.refresh_file_state(common, event_queue);
+
+        let rv = if common.status.contains(FileStatus::NONBLOCK) {
+            Err(Errno::EINPROGRESS.into())
+        } else {
+            connection.connect_result(common)
+        };
+        (connection.into(), rv)
+    }
+
+    fn accept(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> Result<Arc<AtomicRefCell<TcpSocket>>, SyscallError> {
+        Err(Errno::EINVAL.into())
+    }
+
+    fn sendto<R>(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _bytes: R,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        // linux returns EPIPE for sockets that never connected
+        Err(Errno::EPIPE.into())
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _bytes: W,
+        _flags: MsgFlags,
+        _event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        Err(Errno::ENOTCONN.into())
+    }
+
+    fn shutdown(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _how: nix::sys::socket::Shutdown,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        Err(Errno::ENOTCONN.into())
+    }
+}
+
+impl Protocol for Listening {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        None
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        Some(self.bound_addr)
+    }
+
+    fn refresh_file_state(&self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        let mut new_state = FileState::ACTIVE;
+        new_state.set(FileState::READABLE, !self.accept_queue.is_empty());
+
+        common.copy_state(/* mask= */ FileState::all(), new_state, event_queue);
+    }
+
+    fn close(
+        self,
+        common: &mut TcpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        // like linux, reset any connections that haven't been accepted
+        for child in self.pending.into_iter().chain(self.accept_queue) {
+            event_queue.add(move |event_queue| child.borrow_mut().abort_child(event_queue));
+        }
+
+        let new_state = Closed {
+            bound_addr: Some(self.bound_addr),
+        };
+        new_state.refresh_file_state(common, event_queue);
+
+        // the socket should no longer receive packets
+        common.disassociate_later(event_queue);
+        (new_state.into(), common.close(event_queue))
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        // listening sockets are always bound
+        Err(Errno::EINVAL.into())
+    }
+
+    fn listen(
+        mut self,
+        common: &mut TcpSocketCommon,
+        backlog: i32,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        // like linux, listening again only changes the backlog
+        self.queue_limit = backlog_to_queue_size(backlog);
+        self.refresh_file_state(common, event_queue);
+
+        (self.into(), Ok(()))
+    }
+
+    fn connect(
+        self,
+        _common: &mut TcpSocketCommon,
+        _peer_addr: SocketAddrV4,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        (self.into(), Err(Errno::EISCONN.into()))
+    }
+
+    fn accept(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> Result<Arc<AtomicRefCell<TcpSocket>>, SyscallError> {
+        let child = match self.accept_queue.pop_front() {
+            Some(x) => x,
+            None => return Err(Errno::EWOULDBLOCK.into()),
+        };
+
+        child.borrow_mut().set_accepted();
+        self.refresh_file_state(common, event_queue);
+
+        Ok(child)
+    }
+
+    fn sendto<R>(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _bytes: R,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        Err(Errno::ENOTCONN.into())
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _bytes: W,
+        _flags: MsgFlags,
+        _event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        Err(Errno::ENOTCONN.into())
+    }
+
+    fn shutdown(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _how: nix::sys::socket::Shutdown,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        Err(Errno::ENOTCONN.into())
+    }
+
+    fn push_in_packet(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        mut packet: PacketRc,
+        event_queue: &mut EventQueue,
+    ) {
+        let header = packet.tcp_header();
+        let peer_addr = packet.src_address();
+
+        let child = self.children.get(&peer_addr).and_then(Weak::upgrade);
+
+        // a SYN from a peer whose previous connection has finished starts a new connection
+        let is_new_connection = header.flags & c::ProtocolTCPFlags_PTCP_SYN != 0
+            && header.flags & c::ProtocolTCPFlags_PTCP_ACK == 0
+            && child
+                .as_ref()
+                .map_or(true, |child| child.borrow().is_reusable_child());
+
+        if !is_new_connection {
+            match child {
+                // the network interfaces give all packets for our port to the listening socket,
+                // so forward packets for existing connections
+                Some(child) => child.borrow_mut().push_in_packet(packet, event_queue),
+                None => {
+                    packet
+                        .add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+                }
+            }
+
+            self.update_children(common, event_queue);
+            return;
+        }
+
+        packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_PROCESSED);
+
+        // like linux, drop the SYN if the accept queue is full so that the peer retries later
+        if self.accept_queue.len() + self.pending.len() >= self.queue_limit as usize {
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+            return;
+        }
+
+        // the connection's local address is the address that the SYN was sent to
+        let child = TcpSocket::new_child(
+            common,
+            packet.dst_address(),
+            peer_addr,
+            &header,
+            event_queue,
+        );
+
+        // remove any connections that no longer exist
+        self.children.retain(|_, child| child.strong_count() > 0);
+
+        self.children.insert(peer_addr, Arc::downgrade(&child));
+        self.pending.push(child);
+    }
+}
+
+impl Listening {
+    /// Move connections that have completed the handshake to the accept queue, and remove
+    /// connections that failed before completing the handshake.
+    fn update_children(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            let state = self.pending[i].borrow().child_state();
+            match state {
+                ChildState::Pending => i += 1,
+                ChildState::Ready => {
+                    let child = self.pending.remove(i);
+                    self.accept_queue.push_back(child);
+                }
+                ChildState::Failed => {
+                    self.pending.remove(i);
+                }
+            }
+        }
+
+        self.refresh_file_state(common, event_queue);
+    }
+}
+
+impl Protocol for Connection {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        // like linux, the socket isn't connected until the handshake completes
+        self.was_established.then(|| self.peer_addr)
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        Some(self.bound_addr)
+    }
+
+    fn refresh_file_state(&self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        if self.user_closed {
+            common.copy_state(
+                /* mask= */ FileState::all(),
+                FileState::CLOSED,
+                event_queue,
+            );
+            return;
+        }
+
+        let mut new_state = FileState::ACTIVE;
+
+        let has_error = self.error.is_some();
+        let is_closed = self.state == TcpState::Closed;
+
+        new_state.set(
+            FileState::READABLE,
+            !self.recv.buffer.is_empty()
+                || self.recv.fin_received
+                || self.read_shutdown
+                || has_error
+                || is_closed,
+        );
+
+        // writing returns an error if writing is no longer possible
+        let writable = if has_error || self.write_shutdown || self.send.fin_queued {
+            true
+        } else {
+            match self.state {
+                TcpState::Established | TcpState::CloseWait => {
+                    (self.send.buffer.len() as u64) < common.send_buffer_size
+                }
+                TcpState::SynSent | TcpState::SynReceived => false,
+                _ => true,
+            }
+        };
+        new_state.set(FileState::WRITABLE, writable);
+
+        common.copy_state(/* mask= */ FileState::all(), new_state, event_queue);
+    }
+
+    fn close(
+        mut self,
+        common: &mut TcpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        self.user_closed = true;
+
+        if !self.recv.buffer.is_empty() {
+            // like linux, reset the connection if there's data that the plugin never read
+            self.abort(common, event_queue);
+        } else {
+            match self.state {
+                TcpState::SynSent => self.enter_closed(common, event_queue),
+                // the connection may have already closed
+                TcpState::Closed => self.maybe_disassociate(common, event_queue),
+                _ => self.shutdown_write(),
+            }
+
+            self.flush(common, event_queue);
+        }
+
+        self.refresh_file_state(common, event_queue);
+        (self.into(), common.close(event_queue))
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        // connected sockets are always bound
+        Err(Errno::EINVAL.into())
+    }
+
+    fn listen(
+        self,
+        _common: &mut TcpSocketCommon,
+        _backlog: i32,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        (self.into(), Err(Errno::EINVAL.into()))
+    }
+
+    fn connect(
+        mut self,
+        common: &mut TcpSocketCommon,
+        _peer_addr: SocketAddrV4,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        let rv = self.connect_result(common);
+        self.refresh_file_state(common, event_queue);
+        (self.into(), rv)
+    }
+
+    fn accept(
+        &mut self,
+        _common: &mut TcpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> Result<Arc<AtomicRefCell<TcpSocket>>, SyscallError> {
+        Err(Errno::EINVAL.into())
+    }
+
+    fn sendto<R>(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        mut bytes: R,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        if let Some(error) = self.error.take() {
+            self.refresh_file_state(common, event_queue);
+            return Err(error.into());
+        }
+
+        if self.write_shutdown || self.send.fin_queued {
+            return Err(Errno::EPIPE.into());
+        }
+
+        match self.state {
+            TcpState::Established | TcpState::CloseWait => {}
+            // like linux, wait for the handshake to complete
+            TcpState::SynSent | TcpState::SynReceived => return Err(Errno::EWOULDBLOCK.into()),
+            TcpState::Closed if !self.was_established => return Err(Errno::ENOTCONN.into()),
+            _ => return Err(Errno::EPIPE.into()),
+        }
+
+        let len = bytes.stream_len_bp()? as usize;
+        if len == 0 {
+            return Ok(0.into());
+        }
+
+        let space = common
+            .send_buffer_size
+            .saturating_sub(self.send.buffer.len() as u64);
+        if space == 0 {
+            return Err(Errno::EWOULDBLOCK.into());
+        }
+
+        // like linux, write as much as fits in the send buffer
+        let len = std::cmp::min(len, usize::try_from(space).unwrap());
+        let mut data = vec![0u8; len];
+        bytes.read_exact(&mut data)?;
+        self.send.buffer.extend(data);
+
+        self.flush(common, event_queue);
+        self.refresh_file_state(common, event_queue);
+
+        Ok(len.into())
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        mut bytes: W,
+        flags: MsgFlags,
+        event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        if self.recv.buffer.is_empty() {
+            // like linux, buffered data is returned before any error
+            if let Some(error) = self.error.take() {
+                self.refresh_file_state(common, event_queue);
+                return Err(error.into());
+            }
+
+            if self.recv.fin_received || self.read_shutdown || self.state == TcpState::Closed {
+                if !self.was_established {
+                    return Err(Errno::ENOTCONN.into());
+                }
+
+                // EOF
+                return Ok((0.into(), None, 0, AncillaryData::default()));
+            }
+
+            return Err(Errno::EWOULDBLOCK.into());
+        }
+
+        let (first, second) = self.recv.buffer.as_slices();
+        let mut num_copied = write_partial(&mut bytes, first)?;
+        if num_copied == first.len() && !second.is_empty() {
+            num_copied += write_partial(&mut bytes, second)?;
+        }
+
+        if !flags.contains(MsgFlags::MSG_PEEK) {
+            self.recv.buffer.drain(..num_copied);
+
+            // let the peer know if there's now much more space in the receive buffer
+            if self.window_update_needed(common) {
+                common.send_ack(self, event_queue);
+            }
+        }
+
+        self.refresh_file_state(common, event_queue);
+
+        Ok((
+            num_copied.into(),
+            None,
+            num_copied,
+            AncillaryData::default(),
+        ))
+    }
+
+    fn shutdown(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        if !self.was_established {
+            return Err(Errno::ENOTCONN.into());
+        }
+
+        if how == nix::sys::socket::Shutdown::Read || how == nix::sys::socket::Shutdown::Both {
+            self.read_shutdown = true;
+        }
+
+        if how == nix::sys::socket::Shutdown::Write || how == nix::sys::socket::Shutdown::Both {
+            self.write_shutdown = true;
+            self.shutdown_write();
+        }
+
+        self.flush(common, event_queue);
+        self.refresh_file_state(common, event_queue);
+
+        Ok(())
+    }
+
+    fn push_in_packet(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        mut packet: PacketRc,
+        event_queue: &mut EventQueue,
+    ) {
+        packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_PROCESSED);
+
+        // the listening socket may forward packets that were sent to a different connection
+        if packet.src_address() != self.peer_addr || self.state == TcpState::Closed {
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+            return;
+        }
+
+        self.process_packet(common, &mut packet, event_queue);
+
+        self.flush(common, event_queue);
+        self.refresh_file_state(common, event_queue);
+    }
+}
+
+impl Connection {
+    fn new(
+        common: &TcpSocketCommon,
+        bound_addr: SocketAddrV4,
+        peer_addr: SocketAddrV4,
+        state: TcpState,
+        accepted: bool,
+    ) -> Self {
+        let now = Worker::current_time().unwrap();
+
+        Self {
+            bound_addr,
+            peer_addr,
+            state,
+            accepted,
+            user_closed: false,
+            disassociated: false,
+            was_established: false,
+            connect_in_progress: false,
+            error: None,
+            read_shutdown: false,
+            write_shutdown: false,
+            cong: congestion::new_congestion_control(common.congestion_algorithm),
+            send: SendState {
+                isn: initial_sequence_number(now),
+                buffer: VecDeque::new(),
+                buffer_offset: 1,
+                una: 0,
+                nxt: 0,
+                max: 0,
+                window: 0,
+                in_flight: VecDeque::new(),
+                fin_queued: false,
+                probe: false,
+            },
+            recv: RecvState {
+                irs: None,
+                nxt: 0,
+                buffer: VecDeque::new(),
+                out_of_order: BTreeMap::new(),
+                out_of_order_len: 0,
+                fin_offset: None,
+                fin_received: false,
+                advertised_window: 0,
+                ts_recent: 0,
+            },
+            timing: Timing {
+                srtt: None,
+                rttvar: SimulationTime::ZERO,
+                rto: SimulationTime::from_millis(TCP_RTO_INITIAL_MS),
+                retries: 0,
+                total_retransmits: 0,
+                rto_deadline: None,
+                linger_deadline: None,
+            },
+        }
+    }
+
+    /// The result of a `connect()` call on a socket that has already started connecting. A
+    /// blocking socket blocks until the handshake completes, and like linux, the result of the
+    /// handshake is returned once.
+    fn connect_result(&mut self, common: &TcpSocketCommon) -> Result<(), SyscallError> {
+        if self.state == TcpState::SynSent {
+            if common.status.contains(FileStatus::NONBLOCK) {
+                return Err(Errno::EALREADY.into());
+            }
+
+            // block until the handshake completes or fails
+            let socket = common.this_socket.upgrade().unwrap();
+            let trigger = Trigger::from_file(
+                File::Socket(Socket::Inet(InetSocket::Tcp(socket))),
+                FileState::WRITABLE,
+            );
+            return Err(SyscallError::Blocked(Blocked {
+                condition: SysCallCondition::new(trigger),
+                restartable: true,
+            }));
+        }
+
+        if !std::mem::replace(&mut self.connect_in_progress, false) {
+            return Err(Errno::EISCONN.into());
+        }
+
+        match self.error.take() {
+            Some(error) => Err(error.into()),
+            None if self.was_established => Ok(()),
+            None => Err(Errno::ECONNREFUSED.into()),
+        }
+    }
+
+    fn tcp_info(&self) -> TcpInfo {
+        let micros = |x: SimulationTime| u32::try_from(x.as_micros()).unwrap_or(u32::MAX);
+
+        TcpInfo {
+            tcpi_state: self.state.to_linux(),
+            tcpi_ca_state: if self.cong.in_fast_recovery() {
+                TCP_CA_RECOVERY
+            } else {
+                TCP_CA_OPEN
+            },
+            tcpi_retransmits: self.timing.retries.try_into().unwrap_or(u8::MAX),
+            tcpi_rto: micros(self.timing.rto),
+            tcpi_unacked: self.send.in_flight.len().try_into().unwrap(),
+            tcpi_retrans: self.timing.total_retransmits,
+            tcpi_rtt: micros(self.timing.srtt.unwrap_or(SimulationTime::ZERO)),
+            tcpi_rttvar: micros(self.timing.rttvar),
+            tcpi_snd_ssthresh: self.cong.ssthresh(),
+            tcpi_snd_cwnd: self.cong.cwnd(),
+            tcpi_rcv_space: self.recv.advertised_window.try_into().unwrap_or(u32::MAX),
+            tcpi_total_retrans: self.timing.total_retransmits,
+            ..TcpInfo::new()
+        }
+    }
+
+    /// Set the fields of an outgoing packet that should have their latest values when the packet
+    /// is sent.
+    fn prepare_out_packet(&mut self, common: &TcpSocketCommon, packet: &mut PacketRc) {
+        let now = Worker::current_time().unwrap();
+        let window = advertised_window(self.recv_window(common));
+        self.recv.advertised_window = u64::from(window) << TCP_WINDOW_SCALE;
+
+        packet.update_tcp(
+            self.recv.ack(),
+            window,
+            now.to_abs_simtime().into(),
+            self.recv.ts_recent,
+        );
+    }
+
+    /// The number of bytes that we can currently receive.
+    fn recv_window(&self, common: &TcpSocketCommon) -> u64 {
+        let used = self.recv.buffer.len() as u64 + self.recv.out_of_order_len;
+        common.recv_buffer_size.saturating_sub(used)
+    }
+
+    /// Whether the receive window has grown enough since it was last advertised that the peer
+    /// should be told about it.
+    fn window_update_needed(&self, common: &TcpSocketCommon) -> bool {
+        if !self.state.can_receive() || self.recv.fin_received {
+            return false;
+        }
+
+        let window = u64::from(advertised_window(self.recv_window(common))) << TCP_WINDOW_SCALE;
+        let threshold = std::cmp::min(2 * TCP_MSS, common.recv_buffer_size / 2);
+
+        window >= self.recv.advertised_window + threshold
+    }
+
+    /// Send a FIN after any buffered data, if one hasn't been sent already.
+    fn shutdown_write(&mut self) {
+        if self.send.fin_queued {
+            return;
+        }
+
+        // if the handshake hasn't completed, the FIN is queued once it completes
+        let new_state = match self.state {
+            TcpState::Established => TcpState::FinWait1,
+            TcpState::CloseWait => TcpState::LastAck,
+            _ => return,
+        };
+
+        self.send.fin_queued = true;
+        self.state = new_state;
+    }
+
+    /// Reset the connection.
+    fn abort(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        if !matches!(self.state, TcpState::Closed | TcpState::SynSent) {
+            let flags = c::ProtocolTCPFlags_PTCP_RST | c::ProtocolTCPFlags_PTCP_ACK;
+            self.queue_segment(common, flags, self.send.nxt, &[], event_queue);
+        }
+
+        self.enter_closed(common, event_queue);
+    }
+
+    fn enter_closed(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        self.state = TcpState::Closed;
+        self.timing.rto_deadline = None;
+        self.timing.linger_deadline = None;
+
+        self.maybe_disassociate(common, event_queue);
+    }
+
+    fn enter_time_wait(&mut self, now: EmulatedTime) {
+        self.state = TcpState::TimeWait;
+        self.timing.rto_deadline = None;
+        self.timing.linger_deadline = Some(now + SimulationTime::from_millis(TCP_LINGER_MS));
+    }
+
+    /// Disassociate the socket from the network interfaces if the connection has closed and no
+    /// longer belongs to the plugin.
+    fn maybe_disassociate(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        if self.state != TcpState::Closed || self.disassociated {
+            return;
+        }
+
+        if self.user_closed || !self.accepted {
+            self.disassociated = true;
+            common.disassociate_later(event_queue);
+        }
+    }
+
+    fn process_packet(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        packet: &mut PacketRc,
+        event_queue: &mut EventQueue,
+    ) {
+        let header = packet.tcp_header();
+        let has_flag = |flag: c::ProtocolTCPFlags| header.flags & flag != 0;
+        let now = Worker::current_time().unwrap();
+
+        if has_flag(c::ProtocolTCPFlags_PTCP_RST) {
+            if self.state == TcpState::SynSent {
+                self.error = Some(Errno::ECONNREFUSED);
+            } else if matches!(
+                self.state,
+                TcpState::SynReceived
+                    | TcpState::Established
+                    | TcpState::FinWait1
+                    | TcpState::FinWait2
+                    | TcpState::CloseWait
+            ) {
+                self.error = Some(Errno::ECONNRESET);
+            }
+
+            self.enter_closed(common, event_queue);
+            return;
+        }
+
+        if self.state == TcpState::SynSent {
+            // we only expect a SYN-ACK that acknowledges our SYN
+            let ack = unwrap_seq(header.acknowledgement.wrapping_sub(self.send.isn), 0);
+            if !has_flag(c::ProtocolTCPFlags_PTCP_SYN)
+                || !has_flag(c::ProtocolTCPFlags_PTCP_ACK)
+                || ack != 1
+            {
+                packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+                return;
+            }
+
+            self.recv.irs = Some(header.sequence);
+            self.recv.nxt = 1;
+            self.recv.ts_recent = header.timestamp_value;
+
+            self.process_ack(common, &header, /* payload_len= */ 0, now, event_queue);
+            self.set_established();
+            common.send_ack(self, event_queue);
+            return;
+        }
+
+        let seq = unwrap_seq(
+            header.sequence.wrapping_sub(self.recv.irs.unwrap()),
+            self.recv.nxt,
+        );
+        let payload_len = packet.payload_size() as u64;
+
+        if has_flag(c::ProtocolTCPFlags_PTCP_SYN) {
+            if self.state == TcpState::SynReceived && !has_flag(c::ProtocolTCPFlags_PTCP_ACK) {
+                // the peer retransmitted its SYN, so our SYN-ACK was probably lost
+                self.send.nxt = self.send.una;
+                self.send.in_flight.clear();
+            } else {
+                // the peer retransmitted its SYN-ACK, so our ACK was probably lost
+                common.send_ack(self, event_queue);
+            }
+            return;
+        }
+
+        if !has_flag(c::ProtocolTCPFlags_PTCP_ACK) {
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+            return;
+        }
+
+        if self.state == TcpState::SynReceived {
+            let ack = unwrap_seq(
+                header.acknowledgement.wrapping_sub(self.send.isn),
+                self.send.una,
+            );
+            if ack == 0 || ack > self.send.max {
+                packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+                return;
+            }
+
+            self.set_established();
+        }
+
+        // like linux, only update the timestamp to echo from segments that don't skip data
+        if seq <= self.recv.nxt {
+            self.recv.ts_recent = header.timestamp_value;
+        }
+
+        self.process_ack(common, &header, payload_len, now, event_queue);
+
+        let mut ack_needed = false;
+
+        if payload_len > 0 {
+            self.process_data(common, seq, packet);
+            ack_needed = true;
+        }
+
+        if has_flag(c::ProtocolTCPFlags_PTCP_FIN) && self.state.can_receive() {
+            self.recv.fin_offset = Some(seq + payload_len);
+            ack_needed = true;
+        }
+
+        if self.recv.fin_offset == Some(self.recv.nxt) && !self.recv.fin_received {
+            self.recv.fin_received = true;
+            self.recv.nxt += 1;
+
+            match self.state {
+                TcpState::Established => self.state = TcpState::CloseWait,
+                TcpState::FinWait1 => self.state = TcpState::Closing,
+                TcpState::FinWait2 => self.enter_time_wait(now),
+                _ => {}
+            }
+        } else if self.state == TcpState::TimeWait && has_flag(c::ProtocolTCPFlags_PTCP_FIN) {
+            // the peer retransmitted its FIN, so our ACK was probably lost
+            ack_needed = true;
+        }
+
+        // check if our FIN was acknowledged
+        if self.send.fin_acked() {
+            match self.state {
+                TcpState::FinWait1 => {
+                    self.state = TcpState::FinWait2;
+
+                    // like linux, don't wait forever for the peer to close if the plugin closed
+                    // the socket
+                    if self.user_closed {
+                        self.timing.linger_deadline =
+                            Some(now + SimulationTime::from_millis(TCP_LINGER_MS));
+                    }
+                }
+                TcpState::Closing => self.enter_time_wait(now),
+                TcpState::LastAck => self.enter_closed(common, event_queue),
+                _ => {}
+            }
+        }
+
+        if ack_needed && self.state != TcpState::Closed {
+            common.send_ack(self, event_queue);
+        }
+    }
+
+    fn set_established(&mut self) {
+        self.state = TcpState::Established;
+        self.was_established = true;
+
+        // send the FIN if writing was shut down during the handshake
+        if self.write_shutdown {
+            self.shutdown_write();
+        }
+    }
+
+    /// Process the acknowledgement number and window of a segment.
+    fn process_ack(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        header: &TcpHeader,
+        payload_len: u64,
+        now: EmulatedTime,
+        event_queue: &mut EventQueue,
+    ) {
+        let ack = unwrap_seq(
+            header.acknowledgement.wrapping_sub(self.send.isn),
+            self.send.una,
+        );
+
+        // ignore acknowledgements for data that we haven't sent or that were already received
+        if ack > self.send.max || ack < self.send.una {
+            return;
+        }
+
+        let old_window = self.send.window;
+        self.send.window = u64::from(header.window) << TCP_WINDOW_SCALE;
+
+        if ack == self.send.una {
+            let is_duplicate = payload_len == 0
+                && header.flags & c::ProtocolTCPFlags_PTCP_FIN == 0
+                && self.send.una < self.send.max
+                && self.send.window == old_window;
+
+            if is_duplicate {
+                let was_in_fast_recovery = self.cong.in_fast_recovery();
+                self.cong.on_duplicate_ack();
+
+                // retransmit the first unacknowledged segment when entering fast recovery
+                if !was_in_fast_recovery && self.cong.in_fast_recovery() {
+                    self.retransmit_first(common, event_queue);
+                }
+            }
+
+            return;
+        }
+
+        let old_una = self.send.una;
+        self.send.una = ack;
+        self.send.nxt = std::cmp::max(self.send.nxt, ack);
+
+        // remove acknowledged data from the send buffer
+        let data_acked =
+            std::cmp::min(ack, self.send.data_end()).saturating_sub(self.send.buffer_offset);
+        self.send
+            .buffer
+            .drain(..usize::try_from(data_acked).unwrap());
+        self.send.buffer_offset += data_acked;
+
+        let mut packets_acked = 0;
+        while self.send.in_flight.front().map_or(false, |end| *end <= ack) {
+            self.send.in_flight.pop_front();
+            packets_acked += 1;
+        }
+
+        // the echoed timestamp is the time that the acknowledged segment was sent
+        if let Some(sent) = SimulationTime::from_c_simtime(header.timestamp_echo) {
+            if !sent.is_zero() {
+                let sent = EmulatedTime::from_abs_simtime(sent);
+                if let Some(rtt) = now.checked_duration_since(&sent) {
+                    self.timing.add_rtt_sample(rtt);
+                }
+            }
+        }
+
+        self.timing.retries = 0;
+        self.timing.rto = clamp_rto(self.current_rto());
+
+        // the handshake doesn't count towards the congestion window
+        if old_una > 0 {
+            self.cong.on_new_ack(&AckInfo {
+                // data may be acknowledged after being resent by a retransmission timeout
+                packets_acked: std::cmp::max(packets_acked, 1),
+                packets_in_flight: self.send.in_flight.len().try_into().unwrap(),
+                srtt: self.timing.srtt,
+                now,
+            });
+        }
+
+        // restart the retransmission timer if there's still unacknowledged data
+        self.timing.rto_deadline = (self.send.una < self.send.max).then(|| now + self.timing.rto);
+    }
+
+    /// The retransmission timeout without any backoff.
+    fn current_rto(&self) -> SimulationTime {
+        match self.timing.srtt {
+            Some(srtt) => srtt + self.timing.rttvar * 4,
+            None => SimulationTime::from_millis(TCP_RTO_INITIAL_MS),
+        }
+    }
+
+    /// Add received data to the receive buffer.
+    fn process_data(&mut self, common: &TcpSocketCommon, seq: u64, packet: &mut PacketRc) {
+        // data received after closing the socket or the peer's FIN is discarded, but still
+        // acknowledged
+        if !self.state.can_receive() || self.user_closed || self.recv.fin_received {
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+            return;
+        }
+
+        let mut payload = vec![0u8; packet.payload_size()];
+        assert_eq!(packet.copy_payload(&mut payload), payload.len());
+
+        let end = seq + payload.len() as u64;
+        let space = self.recv_window(common);
+
+        // a retransmission of data that we already have
+        if end <= self.recv.nxt {
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+            return;
+        }
+
+        if seq <= self.recv.nxt {
+            // like linux, keep as much data as fits in the receive buffer
+            let start = usize::try_from(self.recv.nxt - seq).unwrap();
+            let len = std::cmp::min(payload.len() - start, usize::try_from(space).unwrap());
+
+            self.recv.buffer.extend(&payload[start..start + len]);
+            self.recv.nxt += len as u64;
+            self.recv.reassemble();
+
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_BUFFERED);
+        } else if payload.len() as u64 <= space && !self.recv.out_of_order.contains_key(&seq) {
+            self.recv.out_of_order_len += payload.len() as u64;
+            self.recv.out_of_order.insert(seq, payload);
+
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_TCP_ENQUEUE_UNORDERED);
+        } else {
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+        }
+    }
+
+    /// Send as many segments as the congestion window and the peer's receive window allow, and
+    /// schedule the connection's timer.
+    fn flush(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        let now = Worker::current_time().unwrap();
+
+        loop {
+            // the SYN (or SYN-ACK)
+            if self.send.nxt == 0 {
+                let flags = match self.state {
+                    TcpState::SynSent => c::ProtocolTCPFlags_PTCP_SYN,
+                    TcpState::SynReceived => {
+                        c::ProtocolTCPFlags_PTCP_SYN | c::ProtocolTCPFlags_PTCP_ACK
+                    }
+                    _ => break,
+                };
+
+                self.queue_segment(common, flags, 0, &[], event_queue);
+                self.send.nxt = 1;
+                self.send.in_flight.push_back(1);
+                self.send.max = std::cmp::max(self.send.max, 1);
+                continue;
+            }
+
+            if !self.state.can_send() {
+                break;
+            }
+
+            if self.send.in_flight.len() as u64 >= u64::from(self.cong.cwnd()) {
+                break;
+            }
+
+            let data_end = self.send.data_end();
+            let window = std::cmp::max(self.send.window, self.send.probe.into());
+            let window_end = self.send.una + window;
+            let start = self.send.nxt;
+
+            let (len, fin) = if start < data_end {
+                let len = std::cmp::min(
+                    std::cmp::min(TCP_MSS, data_end - start),
+                    window_end.saturating_sub(start),
+                );
+                if len == 0 {
+                    break;
+                }
+                (len, self.send.fin_queued && start + len == data_end)
+            } else if self.send.fin_queued && start == data_end {
+                (0, true)
+            } else {
+                break;
+            };
+
+            let mut flags = c::ProtocolTCPFlags_PTCP_ACK;
+            if fin {
+                flags |= c::ProtocolTCPFlags_PTCP_FIN;
+            }
+
+            let buffer_start = usize::try_from(start - self.send.buffer_offset).unwrap();
+            let payload: Vec<u8> = self
+                .send
+                .buffer
+                .range(buffer_start..buffer_start + len as usize)
+                .copied()
+                .collect();
+
+            self.queue_segment(common, flags, start, &payload, event_queue);
+
+            self.send.nxt = start + len + u64::from(fin);
+            self.send.in_flight.push_back(self.send.nxt);
+            self.send.max = std::cmp::max(self.send.max, self.send.nxt);
+            self.send.probe = false;
+        }
+
+        if self.timing.rto_deadline.is_none() && self.state != TcpState::Closed {
+            let outstanding = self.send.una < self.send.max;
+            let blocked_by_window = self.send.window == 0
+                && self.send.nxt < self.send.data_end()
+                && self.state.can_send();
+
+            // the persist timer uses the retransmission timer to probe a zero window
+            if outstanding || blocked_by_window {
+                self.timing.rto_deadline = Some(now + self.timing.rto);
+            }
+        }
+
+        let deadline = match (self.timing.rto_deadline, self.timing.linger_deadline) {
+            (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+            (a, b) => a.or(b),
+        };
+        if let Some(deadline) = deadline {
+            common.schedule_timer(deadline);
+        }
+    }
+
+    /// Queue a segment that starts at offset `start`.
+    fn queue_segment(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        flags: c::ProtocolTCPFlags,
+        start: u64,
+        payload: &[u8],
+        event_queue: &mut EventQueue,
+    ) {
+        let mut packet = PacketRc::new_tcp(
+            common.host_ptr(),
+            flags,
+            self.bound_addr,
+            self.peer_addr,
+            self.send.to_wire(start),
+            payload,
+        );
+
+        // segments that we've sent before are retransmissions
+        let is_retransmission =
+            start < self.send.max && (flags & c::ProtocolTCPFlags_PTCP_RST == 0);
+        if is_retransmission {
+            self.timing.total_retransmits += 1;
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_SND_TCP_RETRANSMITTED);
+        }
+
+        common.queue_packet(packet, event_queue);
+    }
+
+    /// Retransmit the first unacknowledged segment without changing what will be sent next.
+    fn retransmit_first(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        let start = self.send.una;
+        let data_end = self.send.data_end();
+
+        let (len, fin) = if start < data_end {
+            let len = std::cmp::min(TCP_MSS, data_end - start);
+            (len, self.send.fin_queued && start + len == data_end)
+        } else if self.send.fin_queued && start == data_end {
+            (0, true)
+        } else {
+            return;
+        };
+
+        let mut flags = c::ProtocolTCPFlags_PTCP_ACK;
+        if fin {
+            flags |= c::ProtocolTCPFlags_PTCP_FIN;
+        }
+
+        let buffer_start = usize::try_from(start - self.send.buffer_offset).unwrap();
+        let payload: Vec<u8> = self
+            .send
+            .buffer
+            .range(buffer_start..buffer_start + len as usize)
+            .copied()
+            .collect();
+
+        self.queue_segment(common, flags, start, &payload, event_queue);
+    }
+
+    fn timer_fired(&mut self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        let now = Worker::current_time().unwrap();
+
+        if self.timing.linger_deadline.map_or(false, |x| x <= now) {
+            self.enter_closed(common, event_queue);
+        }
+
+        if self.timing.rto_deadline.map_or(false, |x| x <= now) {
+            self.timing.rto_deadline = None;
+            self.retransmission_timeout(common, now, event_queue);
+        }
+
+        self.flush(common, event_queue);
+        self.refresh_file_state(common, event_queue);
+    }
+
+    fn retransmission_timeout(
+        &mut self,
+        common: &mut TcpSocketCommon,
+        now: EmulatedTime,
+        event_queue: &mut EventQueue,
+    ) {
+        self.timing.rto = clamp_rto(self.timing.rto * 2);
+
+        // nothing is outstanding, so this is the persist timer
+        if self.send.una >= self.send.max {
+            if self.send.window == 0 && self.send.nxt < self.send.data_end() {
+                self.send.probe = true;
+            }
+            return;
+        }
+
+        self.timing.retries += 1;
+
+        let max_retries = match self.state {
+            TcpState::SynSent => TCP_SYN_RETRIES,
+            TcpState::SynReceived => TCP_SYNACK_RETRIES,
+            _ => TCP_RETRIES,
+        };
+
+        if self.timing.retries > max_retries {
+            self.error = Some(Errno::ETIMEDOUT);
+            self.enter_closed(common, event_queue);
+            return;
+        }
+
+        // the handshake isn't subject to congestion control
+        if !matches!(self.state, TcpState::SynSent | TcpState::SynReceived) {
+            self.cong.on_timeout(now);
+        }
+
+        // resend everything that hasn't been acknowledged
+        self.send.nxt = self.send.una;
+        self.send.in_flight.clear();
+    }
+}
+
+impl Protocol for Closed {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        None
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        self.bound_addr
+    }
+
+    fn refresh_file_state(&self, common: &mut TcpSocketCommon, event_queue: &mut EventQueue) {
+        common.copy_state(
+            /* mask= */ FileState::all(),
+            FileState::CLOSED,
+            event_queue,
+        );
+    }
+
+    fn close(
+        self,
+        _common: &mut TcpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        // why are we trying to close an already closed file? we probably want a bt
+        panic!("Trying to close an already closed socket");
+    }
+}
+
+/// Common data and functionality that is useful for all states.
+struct TcpSocketCommon {
+    this_socket: Weak<AtomicRefCell<TcpSocket>>,
+    host: HostTreePointer<c::Host>,
+    /// Packets waiting to be sent by the network interface.
+    out_packets: VecDeque<PacketRc>,
+    /// The max number of bytes in the send buffer (`SO_SNDBUF`).
+    send_buffer_size: u64,
+    /// The max number of bytes in the receive buffer (`SO_RCVBUF`).
+    recv_buffer_size: u64,
+    /// The congestion control algorithm (`TCP_CONGESTION`).
+    congestion_algorithm: TcpCongestionAlgorithm,
+    /// Shadow doesn't implement nagle's algorithm, so `TCP_NODELAY` has no effect, but it's
+    /// stored so that `getsockopt()` returns the value that was set.
+    nodelay: bool,
+    /// The time that the earliest pending timer task will run.
+    timer_scheduled_for: Option<EmulatedTime>,
+    event_source: StateEventSource,
+    state: FileState,
+    status: FileStatus,
+    // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file
+    has_open_file: bool,
+}
+
+impl TcpSocketCommon {
+    fn host_ptr(&self) -> *mut c::Host {
+        unsafe { self.host.ptr() }
+    }
+
+    pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        // check that the CLOSED flag was set by the protocol state
+        if !self.state.contains(FileState::CLOSED) {
+            // set the flag here since we missed doing it before
+            // do this before the below panic, otherwise rust gives us warnings
+            self.copy_state(
+                /* mask= */ FileState::all(),
+                FileState::CLOSED,
+                event_queue,
+            );
+
+            // panic in debug builds since the backtrace will be helpful for debugging
+            debug_panic!("When closing a tcp socket, the CLOSED flag was not set");
+        }
+
+        Ok(())
+    }
+
+    /// Bind to `addr`, choosing an ephemeral port if the port is 0. If choosing an ephemeral port,
+    /// the port only needs to be unused for the given peer address.
+    fn bind(
+        &mut self,
+        addr: SocketAddrV4,
+        peer_addr: SocketAddrV4,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        inet::bind_port(self.host_ptr(), c::_ProtocolType_PTCP, addr, peer_addr)
+    }
+
+    /// Associate the socket with the network interfaces once the socket is no longer borrowed.
+    fn associate_later(&self, ip: Ipv4Addr, event_queue: &mut EventQueue) {
+        if let Some(socket) = self.this_socket.upgrade() {
+            let host = self.host_ptr();
+            event_queue.add(move |_| inet::associate(host, &InetSocket::Tcp(socket), ip));
+        }
+    }
+
+    /// Disassociate the socket from the network interfaces once the socket is no longer borrowed.
+    fn disassociate_later(&self, event_queue: &mut EventQueue) {
+        if let Some(socket) = self.this_socket.upgrade() {
+            let host = self.host_ptr();
+            event_queue.add(move |_| inet::disassociate(host, &InetSocket::Tcp(socket)));
+        }
+    }
+
+    /// Add a packet to the queue of packets waiting to be sent.
+    fn queue_packet(&mut self, mut packet: PacketRc, event_queue: &mut EventQueue) {
+        packet.add_delivery_status(
+            c::_PacketDeliveryStatusFlags_PDS_SND_CREATED
+                | c::_PacketDeliveryStatusFlags_PDS_SND_SOCKET_BUFFERED,
+        );
+
+        let src_ip = *packet.src_address().ip();
+        self.out_packets.push_back(packet);
+
+        // the network interface will pull the packet from the socket, so we must wait until the
+        // socket is no longer borrowed before notifying the interface
+        if let Some(socket) = self.this_socket.upgrade() {
+            let host = self.host_ptr();
+            event_queue.add(move |_| inet::wants_send(host, &InetSocket::Tcp(socket), src_ip));
+        }
+    }
+
+    /// Send an acknowledgement for the connection's received data. Packets get the latest
+    /// acknowledgement number when they're sent, so a new packet is only needed if there isn't
+    /// one waiting to be sent already.
+    fn send_ack(&mut self, connection: &mut Connection, event_queue: &mut EventQueue) {
+        if !self.out_packets.is_empty() {
+            return;
+        }
+
+        connection.queue_segment(
+            self,
+            c::ProtocolTCPFlags_PTCP_ACK,
+            connection.send.nxt,
+            &[],
+            event_queue,
+        );
+    }
+
+    /// Schedule a task that runs the socket's timer at `deadline`, unless one is already
+    /// scheduled to run earlier. A task that runs before the socket's deadline will schedule a
+    /// new task.
+    fn schedule_timer(&mut self, deadline: EmulatedTime) {
+        if self.timer_scheduled_for.map_or(false, |x| x <= deadline) {
+            return;
+        }
+
+        self.timer_scheduled_for = Some(deadline);
+
+        let socket = Weak::clone(&self.this_socket);
+        let mut task = TaskRef::new(move |_host| {
+            if let Some(socket) = socket.upgrade() {
+                EventQueue::queue_and_run(|event_queue| {
+                    socket.borrow_mut().timer_fired(deadline, event_queue)
+                });
+            }
+        });
+
+        unsafe {
+            c::worker_scheduleTaskAtEmulatedTime(
+                &mut task,
+                self.host_ptr(),
+                EmulatedTime::to_c_emutime(Some(deadline)),
+            )
+        };
+    }
+
+    fn copy_state(&mut self, mask: FileState, state: FileState, event_queue: &mut EventQueue) {
+        let old_state = self.state;
+
+        // remove the masked flags, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, event_queue);
+    }
+
+    fn handle_state_change(&mut self, old_state: FileState, event_queue: &mut EventQueue) {
+        let states_changed = self.state ^ old_state;
+
+        // if nothing changed
+        if states_changed.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, event_queue);
+    }
+}
+
+/// The value of linux's `tcpi_state` for a listening socket.
+const TCP_LISTEN: u8 = 10;
+
+/// The values of linux's `tcpi_ca_state`.
+const TCP_CA_OPEN: u8 = 0;
+const TCP_CA_RECOVERY: u8 = 3;
+
+/// The beginning of linux's `struct tcp_info`, which the libc crate doesn't expose. Linux copies
+/// at most the requested number of bytes, so programs built against a newer version of the struct
+/// still work.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct TcpInfo {
+    tcpi_state: u8,
+    tcpi_ca_state: u8,
+    tcpi_retransmits: u8,
+    tcpi_probes: u8,
+    tcpi_backoff: u8,
+    tcpi_options: u8,
+    tcpi_wscale: u8,
+    tcpi_flags: u8,
+    tcpi_rto: u32,
+    tcpi_ato: u32,
+    tcpi_snd_mss: u32,
+    tcpi_rcv_mss: u32,
+    tcpi_unacked: u32,
+    tcpi_sacked: u32,
+    tcpi_lost: u32,
+    tcpi_retrans: u32,
+    tcpi_fackets: u32,
+    tcpi_last_data_sent: u32,
+    tcpi_last_ack_sent: u32,
+    tcpi_last_data_recv: u32,
+    tcpi_last_ack_recv: u32,
+    tcpi_pmtu: u32,
+    tcpi_rcv_ssthresh: u32,
+    tcpi_rtt: u32,
+    tcpi_rttvar: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    tcpi_advmss: u32,
+    tcpi_reordering: u32,
+    tcpi_rcv_rtt: u32,
+    tcpi_rcv_space: u32,
+    tcpi_total_retrans: u32,
+}
+
+const _: () = assert!(std::mem::size_of::<TcpInfo>() == 104);
+
+unsafe impl Pod for TcpInfo {}
+
+impl TcpInfo {
+    /// The info with only the fields that are the same for all sockets set.
+    fn new() -> Self {
+        Self {
+            tcpi_snd_mss: c::CONFIG_TCP_MAX_SEGMENT_SIZE,
+            tcpi_rcv_mss: c::CONFIG_TCP_MAX_SEGMENT_SIZE,
+            tcpi_pmtu: c::CONFIG_MTU,
+            tcpi_advmss: c::CONFIG_TCP_MAX_SEGMENT_SIZE,
+            ..pod::zeroed()
+        }
+    }
+}
+
+/// An initial sequence number based on the current time, as suggested by RFC 793.
+fn initial_sequence_number(now: EmulatedTime) -> u32 {
+    // the clock increments every 4 microseconds
+    (now.to_abs_simtime().as_micros() / 4) as u32
+}
+
+/// Convert a 32-bit sequence number that is relative to an initial sequence number into a 64-bit
+/// offset, choosing the offset that is closest to `near`.
+fn unwrap_seq(relative: u32, near: u64) -> u64 {
+    let diff = relative.wrapping_sub(near as u32) as i32;
+    std::cmp::max(near as i64 + i64::from(diff), 0) as u64
+}
+
+/// The scaled value of the window field for a receive window of `window` bytes.
+fn advertised_window(window: u64) -> u32 {
+    std::cmp::min(window >> TCP_WINDOW_SCALE, u64::from(u16::MAX)) as u32
+}
+
+fn clamp_rto(rto: SimulationTime) -> SimulationTime {
+    rto.clamp(
+        SimulationTime::from_millis(TCP_RTO_MIN_MS),
+        SimulationTime::from_millis(TCP_RTO_MAX_MS),
+    )
+}
+
+fn backlog_to_queue_size(backlog: i32) -> u32 {
+    // linux also makes this cast, so negative backlogs wrap around to large positive backlogs
+    let backlog = backlog as u32;
+
+    // the linux '__sys_listen()' applies the somaxconn max to all protocols
+    let queue_limit = std::cmp::min(backlog, c::SHADOW_SOMAXCONN);
+
+    // linux uses a limit of one greater than the provided backlog (ex: a backlog value of 0 allows
+    // for one incoming connection at a time)
+    queue_limit.saturating_add(1)
+}
+
+/// Get the buffer size for a `SO_SNDBUF` or `SO_RCVBUF` option value. Like Linux, the value is
+/// capped to a maximum and then doubled to allow space for bookkeeping overhead.
+fn sockopt_buffer_size(optval: libc::c_int, min: u64) -> u64 {
+    // linux interprets the value as unsigned, so negative values become very large
+    let optval = u64::from(optval as u32);
+    std::cmp::max(std::cmp::min(optval, TCP_SOCKET_MAX_BUFFER_SIZE) * 2, min)
+}
@@ -0,0 +1,1699 @@
+use std::collections::VecDeque;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::sync::{Arc, Weak};
+
+use atomic_refcell::AtomicRefCell;
+use nix::errno::Errno;
+use nix::sys::socket::MsgFlags;
+
+use crate::cshadow as c;
+use crate::host::descriptor::socket::inet::{self, InetSocket};
+use crate::host::descriptor::socket::{read_int_sockopt, write_int_sockopt, AncillaryData};
+use crate::host::descriptor::{
+    FileMode, FileState, FileStatus, StateEventSource, StateListenerFilter, SyscallResult,
+};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall_types::{PluginPtr, SysCallReg, SyscallError, TypedPluginPtr};
+use crate::network::packet::PacketRc;
+use crate::utility::byte_queue::write_partial;
+use crate::utility::event_queue::{EventQueue, Handle};
+use crate::utility::stream_len::StreamLen;
+use crate::utility::HostTreePointer;
+
+/// The maximum payload size of a UDP datagram.
+const UDP_MAX_PAYLOAD_SIZE: usize = c::CONFIG_DATAGRAM_MAX_SIZE as usize;
+
+/// The maximum buffer size that can be set with `SO_SNDBUF` or `SO_RCVBUF`.
+const UDP_SOCKET_MAX_BUFFER_SIZE: u64 = 268_435_456;
+
+/// The minimum buffer sizes that can be set with `SO_SNDBUF` and `SO_RCVBUF`.
+const UDP_SOCKET_MIN_SEND_BUFFER_SIZE: u64 = 4096;
+const UDP_SOCKET_MIN_RECV_BUFFER_SIZE: u64 = 2048;
+
+/// The default time-to-live of IP packets (linux's `net.ipv4.ip_default_ttl`).
+const IP_DEFAULT_TTL: u8 = 64;
+
+/// A UDP socket. The `UdpSocket` is the public-facing API, which forwards API calls to the inner
+/// state object.
+pub struct UdpSocket {
+    /// Data and functionality that is general for all states.
+    common: UdpSocketCommon,
+    /// State-specific data and functionality.
+    protocol_state: ProtocolState,
+}
+
+impl UdpSocket {
+    pub fn new(status: FileStatus, host: HostTreePointer<c::Host>) -> Arc<AtomicRefCell<Self>> {
+        let host_ptr = unsafe { host.ptr() };
+
+        Arc::new_cyclic(|weak| {
+            let common = UdpSocketCommon {
+                this_socket: Weak::clone(weak),
+                send_buffer: VecDeque::new(),
+                send_buffer_len: 0,
+                send_buffer_size: unsafe { c::host_getConfiguredSendBufSize(host_ptr) },
+                corked: None,
+                recv_buffer: VecDeque::new(),
+                recv_buffer_len: 0,
+                recv_buffer_size: unsafe { c::host_getConfiguredRecvBufSize(host_ptr) },
+                ip_options: IpOptions::default(),
+                host,
+                event_source: StateEventSource::new(),
+                // udp sockets are immediately able to send data
+                state: FileState::ACTIVE | FileState::WRITABLE,
+                status,
+                has_open_file: false,
+            };
+
+            let protocol_state = Unconnected { bound_addr: None }.into();
+
+            AtomicRefCell::new(Self {
+                common,
+                protocol_state,
+            })
+        })
+    }
+
+    pub fn get_status(&self) -> FileStatus {
+        self.common.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.common.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ | FileMode::WRITE
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.common.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        true
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.common.has_open_file = val;
+    }
+
+    pub fn getsockname(&self) -> Result<Option<SocketAddrV4>, SyscallError> {
+        // return the bound address if set, otherwise return an unspecified address
+        Ok(Some(
+            self.protocol_state
+                .bound_address()
+                .unwrap_or(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)),
+        ))
+    }
+
+    pub fn getpeername(&self) -> Result<Option<SocketAddrV4>, SyscallError> {
+        match self.protocol_state.peer_address() {
+            Some(addr) => Ok(Some(addr)),
+            None => Err(Errno::ENOTCONN.into()),
+        }
+    }
+
+    pub fn address_family(&self) -> nix::sys::socket::AddressFamily {
+        nix::sys::socket::AddressFamily::Inet
+    }
+
+    pub fn socket_type(&self) -> nix::sys::socket::SockType {
+        nix::sys::socket::SockType::Datagram
+    }
+
+    pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        // like linux, a corked datagram is discarded
+        self.common.corked = None;
+        self.protocol_state.close(&mut self.common, event_queue)
+    }
+
+    fn refresh_file_state(&mut self, event_queue: &mut EventQueue) {
+        self.protocol_state
+            .refresh_file_state(&mut self.common, event_queue)
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn bind(
+        socket: &Arc<AtomicRefCell<Self>>,
+        addr: Option<&nix::sys::socket::SockAddr>,
+        _rng: impl rand::Rng,
+    ) -> SyscallResult {
+        Self::with_reassociation(socket, |socket| {
+            socket
+                .protocol_state
+                .bind(&mut socket.common, addr)
+                .map(|_| 0.into())
+        })
+    }
+
+    pub fn read<W>(
+        &mut self,
+        bytes: W,
+        _offset: libc::off_t,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        // the read() syscall handler should call UdpSocket::recvfrom() instead, but other syscalls
+        // (for example splice()) may read from the socket; sockets aren't seekable, so the offset
+        // is ignored
+        self.recvfrom(bytes, MsgFlags::empty(), event_queue)
+            .map(|(rv, _from_addr, _msg_len, _ancillary)| rv)
+    }
+
+    pub fn write<R>(
+        &mut self,
+        bytes: R,
+        _offset: libc::off_t,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        // the write() syscall handler should call UdpSocket::sendto() instead, but other syscalls
+        // (for example sendfile()) may write to the socket; sockets aren't seekable, so the offset
+        // is ignored
+        self.sendto(bytes, 0, None, AncillaryData::default(), event_queue)
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn sendto<R>(
+        &mut self,
+        bytes: R,
+        flags: libc::c_int,
+        addr: Option<nix::sys::socket::SockAddr>,
+        ancillary: AncillaryData,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        // udp sockets can't pass file descriptors, and linux ignores credentials for inet sockets
+        if !ancillary.rights.is_empty() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        // the datagram isn't sent until there's a send without `MSG_MORE`
+        let more = flags & libc::MSG_MORE != 0;
+
+        // like linux, data sent while a datagram is corked is appended to that datagram, and the
+        // address is ignored
+        if self.common.corked.is_some() {
+            let rv = self.common.send_corked(bytes, more, event_queue);
+            self.refresh_file_state(event_queue);
+            return rv;
+        }
+
+        let addr = match addr {
+            Some(addr) => match inet::sockaddr_to_ipv4(&addr) {
+                Some(addr) => Some(addr),
+                None => {
+                    log::warn!("Unsupported udp destination address {:?}", addr);
+                    return Err(Errno::EAFNOSUPPORT.into());
+                }
+            },
+            None => None,
+        };
+
+        self.protocol_state
+            .sendto(&mut self.common, bytes, addr, more, event_queue)
+    }
+
+    /// Returns the number of bytes copied, the source address, and the length of the datagram.
+    /// The datagram length will be larger than the number of bytes copied if the datagram was
+    /// truncated.
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn recvfrom<W>(
+        &mut self,
+        bytes: W,
+        flags: MsgFlags,
+        event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        self.protocol_state
+            .recvfrom(&mut self.common, bytes, flags, event_queue)
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: u64,
+        arg_ptr: PluginPtr,
+        memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        self.common.ioctl(request, arg_ptr, memory_manager)
+    }
+
+    /// Write the option value to `optval_ptr`, and return the option's length.
+    pub fn getsockopt(
+        &self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &mut MemoryManager,
+    ) -> Result<libc::socklen_t, SyscallError> {
+        self.common
+            .getsockopt(level, optname, optval_ptr, optlen, memory_manager)
+    }
+
+    pub fn setsockopt(
+        &mut self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &MemoryManager,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        self.common
+            .setsockopt(level, optname, optval_ptr, optlen, memory_manager)?;
+
+        // the buffer sizes may have changed
+        self.refresh_file_state(event_queue);
+
+        Ok(())
+    }
+
+    pub fn listen(
+        &mut self,
+        _backlog: i32,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        // linux doesn't support listening on udp sockets
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    pub fn shutdown(
+        &mut self,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        self.protocol_state
+            .shutdown(&mut self.common, how, event_queue)
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn connect(
+        socket: &Arc<AtomicRefCell<Self>>,
+        addr: &nix::sys::socket::SockAddr,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let peer_addr = match inet::sockaddr_to_ipv4(addr) {
+            Some(addr) => addr,
+            None => {
+                log::warn!("Unsupported udp peer address {:?}", addr);
+                return Err(Errno::EAFNOSUPPORT.into());
+            }
+        };
+
+        // like linux, an unspecified peer address refers to this host
+        let peer_addr = if peer_addr.ip().is_unspecified() {
+            SocketAddrV4::new(Ipv4Addr::LOCALHOST, peer_addr.port())
+        } else {
+            peer_addr
+        };
+
+        // make sure we will be able to route packets to the peer
+        let host = socket.borrow().common.host_ptr();
+        if !inet::is_routable(host, *peer_addr.ip()) {
+            log::warn!(
+                "Attempting to connect to address '{}' for which no host exists",
+                peer_addr
+            );
+            return Err(Errno::ECONNREFUSED.into());
+        }
+
+        Self::with_reassociation(socket, |socket| {
+            socket
+                .protocol_state
+                .connect(&mut socket.common, peer_addr, event_queue)
+        })
+    }
+
+    /// Disconnect the socket from its peer (`connect()` with an `AF_UNSPEC` address).
+    pub fn disconnect(
+        socket: &Arc<AtomicRefCell<Self>>,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        Self::with_reassociation(socket, |socket| {
+            socket
+                .protocol_state
+                .disconnect(&mut socket.common, event_queue)
+        })
+    }
+
+    pub fn accept(
+        &mut self,
+        _event_queue: &mut EventQueue,
+    ) -> Result<Arc<AtomicRefCell<UdpSocket>>, SyscallError> {
+        // linux doesn't support accepting on udp sockets
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    /// Run `f`, which may change the socket's bound or peer address. The network interfaces find
+    /// the socket using these addresses, so the socket is disassociated from the interfaces
+    /// before running `f`, and then re-associated using the (possibly new) addresses. The
+    /// interfaces will look up the socket's addresses, so the socket must not be borrowed when
+    /// calling this function.
+    fn with_reassociation<T>(
+        socket: &Arc<AtomicRefCell<Self>>,
+        f: impl FnOnce(&mut Self) -> Result<T, SyscallError>,
+    ) -> Result<T, SyscallError> {
+        let inet_socket = InetSocket::Udp(Arc::clone(socket));
+
+        let (host, old_bound_addr) = {
+            let socket = socket.borrow();
+            (
+                socket.common.host_ptr(),
+                socket.protocol_state.bound_address(),
+            )
+        };
+
+        if old_bound_addr.is_some() {
+            inet::disassociate(host, &inet_socket);
+        }
+
+        let rv = f(&mut socket.borrow_mut());
+
+        let new_bound_addr = socket.borrow().protocol_state.bound_address();
+        if let Some(addr) = new_bound_addr {
+            inet::associate(host, &inet_socket, *addr.ip());
+        }
+
+        rv
+    }
+
+    /// The next packet that will be returned by [`pull_out_packet()`](Self::pull_out_packet), if
+    /// any.
+    pub fn peek_next_out_packet(&self) -> Option<&PacketRc> {
+        self.common.send_buffer.front()
+    }
+
+    /// Remove the next packet from the send buffer so that it can be sent by the network
+    /// interface.
+    pub fn pull_out_packet(&mut self, event_queue: &mut EventQueue) -> Option<PacketRc> {
+        let packet = self.common.send_buffer.pop_front()?;
+        self.common.send_buffer_len -= packet.payload_size() as u64;
+
+        // there may be space in the send buffer now
+        self.refresh_file_state(event_queue);
+
+        Some(packet)
+    }
+
+    /// Add a packet received by the network interface to the receive buffer. The packet will be
+    /// dropped if there isn't space in the receive buffer.
+    pub fn push_in_packet(&mut self, packet: PacketRc, event_queue: &mut EventQueue) {
+        self.protocol_state
+            .push_in_packet(&mut self.common, packet, event_queue)
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState)> {
+        self.common
+            .event_source
+            .add_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        self.common
+            .event_source
+            .add_exclusive_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.common.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.common.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.common.state
+    }
+}
+
+struct Unconnected {
+    bound_addr: Option<SocketAddrV4>,
+}
+struct Connected {
+    bound_addr: SocketAddrV4,
+    peer_addr: SocketAddrV4,
+    /// The bound address before connecting, which is restored if the socket is disconnected.
+    unconnected_bound_addr: Option<SocketAddrV4>,
+    /// Whether reading has been shut down.
+    read_shutdown: bool,
+    /// Whether writing has been shut down.
+    write_shutdown: bool,
+}
+struct Closed {
+    // the addresses are kept so that the socket can still be disassociated from the network
+    // interfaces after it's closed
+    bound_addr: Option<SocketAddrV4>,
+    peer_addr: Option<SocketAddrV4>,
+}
+
+/// The current protocol state of the udp socket. An `Option` is required for each variant so that
+/// the inner state object can be removed, transformed into a new state, and then re-added as a
+/// different variant.
+enum ProtocolState {
+    Unconnected(Option<Unconnected>),
+    Connected(Option<Connected>),
+    Closed(Option<Closed>),
+}
+
+/// Upcast from a type to an enum variant.
+macro_rules! state_upcast {
+    ($type:ty, $parent:ident::$variant:ident) => {
+        impl From<$type> for $parent {
+            fn from(x: $type) -> Self {
+                Self::$variant(Some(x))
+            }
+        }
+    };
+}
+
+// implement upcasting for all state types
+state_upcast!(Unconnected, ProtocolState::Unconnected);
+state_upcast!(Connected, ProtocolState::Connected);
+state_upcast!(Closed, ProtocolState::Closed);
+
+impl ProtocolState {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        match self {
+            Self::Unconnected(x) => x.as_ref().unwrap().peer_address(),
+            Self::Connected(x) => x.as_ref().unwrap().peer_address(),
+            Self::Closed(x) => x.as_ref().unwrap().peer_address(),
+        }
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        match self {
+            Self::Unconnected(x) => x.as_ref().unwrap().bound_address(),
+            Self::Connected(x) => x.as_ref().unwrap().bound_address(),
+            Self::Closed(x) => x.as_ref().unwrap().bound_address(),
+        }
+    }
+
+    fn refresh_file_state(&self, common: &mut UdpSocketCommon, event_queue: &mut EventQueue) {
+        match self {
+            Self::Unconnected(x) => x.as_ref().unwrap().refresh_file_state(common, event_queue),
+            Self::Connected(x) => x.as_ref().unwrap().refresh_file_state(common, event_queue),
+            Self::Closed(x) => x.as_ref().unwrap().refresh_file_state(common, event_queue),
+        }
+    }
+
+    fn close(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let (new_state, rv) = match self {
+            Self::Unconnected(x) => x.take().unwrap().close(common, event_queue),
+            Self::Connected(x) => x.take().unwrap().close(common, event_queue),
+            Self::Closed(x) => x.take().unwrap().close(common, event_queue),
+        };
+
+        *self = new_state;
+        rv
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        match self {
+            Self::Unconnected(x) => x.as_mut().unwrap().bind(common, addr),
+            Self::Connected(x) => x.as_mut().unwrap().bind(common, addr),
+            Self::Closed(x) => x.as_mut().unwrap().bind(common, addr),
+        }
+    }
+
+    fn sendto<R>(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        bytes: R,
+        addr: Option<SocketAddrV4>,
+        more: bool,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        match self {
+            Self::Unconnected(x) => {
+                x.as_mut()
+                    .unwrap()
+                    .sendto(common, bytes, addr, more, event_queue)
+            }
+            Self::Connected(x) => {
+                x.as_mut()
+                    .unwrap()
+                    .sendto(common, bytes, addr, more, event_queue)
+            }
+            Self::Closed(x) => x
+                .as_mut()
+                .unwrap()
+                .sendto(common, bytes, addr, more, event_queue),
+        }
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        bytes: W,
+        flags: MsgFlags,
+        event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        match self {
+            Self::Unconnected(x) => x
+                .as_mut()
+                .unwrap()
+                .recvfrom(common, bytes, flags, event_queue),
+            Self::Connected(x) => x
+                .as_mut()
+                .unwrap()
+                .recvfrom(common, bytes, flags, event_queue),
+            Self::Closed(x) => x
+                .as_mut()
+                .unwrap()
+                .recvfrom(common, bytes, flags, event_queue),
+        }
+    }
+
+    fn shutdown(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        match self {
+            Self::Unconnected(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+            Self::Connected(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+            Self::Closed(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+        }
+    }
+
+    fn connect(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        peer_addr: SocketAddrV4,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let (new_state, rv) = match self {
+            Self::Unconnected(x) => x.take().unwrap().connect(common, peer_addr, event_queue),
+            Self::Connected(x) => x.take().unwrap().connect(common, peer_addr, event_queue),
+            Self::Closed(x) => x.take().unwrap().connect(common, peer_addr, event_queue),
+        };
+
+        *self = new_state;
+        rv
+    }
+
+    fn disconnect(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        let (new_state, rv) = match self {
+            Self::Unconnected(x) => x.take().unwrap().disconnect(common, event_queue),
+            Self::Connected(x) => x.take().unwrap().disconnect(common, event_queue),
+            Self::Closed(x) => x.take().unwrap().disconnect(common, event_queue),
+        };
+
+        *self = new_state;
+        rv
+    }
+
+    fn push_in_packet(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        packet: PacketRc,
+        event_queue: &mut EventQueue,
+    ) {
+        match self {
+            Self::Unconnected(x) => x
+                .as_mut()
+                .unwrap()
+                .push_in_packet(common, packet, event_queue),
+            Self::Connected(x) => x
+                .as_mut()
+                .unwrap()
+                .push_in_packet(common, packet, event_queue),
+            Self::Closed(x) => x
+                .as_mut()
+                .unwrap()
+                .push_in_packet(common, packet, event_queue),
+        }
+    }
+}
+
+trait Protocol
+where
+    Self: Sized + Into<ProtocolState>,
+{
+    fn peer_address(&self) -> Option<SocketAddrV4>;
+    fn bound_address(&self) -> Option<SocketAddrV4>;
+    fn refresh_file_state(&self, common: &mut UdpSocketCommon, event_queue: &mut EventQueue);
+
+    fn close(
+        self,
+        _common: &mut UdpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        log::warn!("close() while in state {}", std::any::type_name::<Self>());
+        (self.into(), Err(Errno::EOPNOTSUPP.into()))
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        _common: &mut UdpSocketCommon,
+        _addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        log::warn!("bind() while in state {}", std::any::type_name::<Self>());
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    fn sendto<R>(
+        &mut self,
+        _common: &mut UdpSocketCommon,
+        _bytes: R,
+        _addr: Option<SocketAddrV4>,
+        _more: bool,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        log::warn!("sendto() while in state {}", std::any::type_name::<Self>());
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        _common: &mut UdpSocketCommon,
+        _bytes: W,
+        _flags: MsgFlags,
+        _event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        log::warn!(
+            "recvfrom() while in state {}",
+            std::any::type_name::<Self>()
+        );
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    fn shutdown(
+        &mut self,
+        _common: &mut UdpSocketCommon,
+        _how: nix::sys::socket::Shutdown,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        log::warn!(
+            "shutdown() while in state {}",
+            std::any::type_name::<Self>()
+        );
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    fn connect(
+        self,
+        _common: &mut UdpSocketCommon,
+        _peer_addr: SocketAddrV4,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        log::warn!("connect() while in state {}", std::any::type_name::<Self>());
+        (self.into(), Err(Errno::EOPNOTSUPP.into()))
+    }
+
+    fn disconnect(
+        self,
+        _common: &mut UdpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        log::warn!(
+            "disconnect() while in state {}",
+            std::any::type_name::<Self>()
+        );
+        (self.into(), Err(Errno::EOPNOTSUPP.into()))
+    }
+
+    fn push_in_packet(
+        &mut self,
+        _common: &mut UdpSocketCommon,
+        mut packet: PacketRc,
+        _event_queue: &mut EventQueue,
+    ) {
+        log::trace!(
+            "Dropping packet received while in state {}",
+            std::any::type_name::<Self>()
+        );
+        packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+    }
+}
+
+impl Protocol for Unconnected {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        None
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        self.bound_addr
+    }
+
+    fn refresh_file_state(&self, common: &mut UdpSocketCommon, event_queue: &mut EventQueue) {
+        common.refresh_file_state(/* read_shutdown= */ false, event_queue);
+    }
+
+    fn close(
+        self,
+        common: &mut UdpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        let new_state = Closed {
+            bound_addr: self.bound_addr,
+            peer_addr: None,
+        };
+        new_state.refresh_file_state(common, event_queue);
+
+        // the socket should no longer receive packets
+        if self.bound_addr.is_some() {
+            common.disassociate_later(event_queue);
+        }
+        (new_state.into(), common.close(event_queue))
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        // if already bound
+        if self.bound_addr.is_some() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let addr = match addr.and_then(inet::sockaddr_to_ipv4) {
+            Some(addr) => addr,
+            None => {
+                log::warn!("Unsupported udp bind address {:?}", addr);
+                return Err(Errno::EINVAL.into());
+            }
+        };
+
+        let bound_addr = common.bind(addr)?;
+        self.bound_addr = Some(bound_addr);
+
+        Ok(bound_addr)
+    }
+
+    fn sendto<R>(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        bytes: R,
+        addr: Option<SocketAddrV4>,
+        more: bool,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        let dst_addr = match addr {
+            Some(addr) => addr,
+            None => return Err(Errno::EDESTADDRREQ.into()),
+        };
+
+        // like linux, bind to an ephemeral port if the socket isn't already bound
+        let bound_addr = match self.bound_addr {
+            Some(addr) => addr,
+            None => {
+                let addr = common.bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+                self.bound_addr = Some(addr);
+
+                // the network interfaces will look up the socket's addresses, so we must wait
+                // until the socket is no longer borrowed before associating it
+                common.associate_later(*addr.ip(), event_queue);
+
+                addr
+            }
+        };
+
+        let rv = common.sendto(bytes, bound_addr, dst_addr, more, event_queue);
+        self.refresh_file_state(common, event_queue);
+        rv
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        bytes: W,
+        flags: MsgFlags,
+        event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        let rv = common.recvfrom(bytes, flags);
+        self.refresh_file_state(common, event_queue);
+        rv
+    }
+
+    fn shutdown(
+        &mut self,
+        _common: &mut UdpSocketCommon,
+        _how: nix::sys::socket::Shutdown,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        Err(Errno::ENOTCONN.into())
+    }
+
+    fn connect(
+        self,
+        common: &mut UdpSocketCommon,
+        peer_addr: SocketAddrV4,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        let bound_addr = match self.bound_addr {
+            Some(addr) => addr,
+            None => {
+                // like linux, bind to an ephemeral port if the socket isn't already bound
+                match common.bind_for_peer(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0), peer_addr) {
+                    Ok(addr) => addr,
+                    Err(e) => return (self.into(), Err(e)),
+                }
+            }
+        };
+
+        let new_state = Connected {
+            bound_addr: connected_bound_addr(common, bound_addr, peer_addr),
+            peer_addr,
+            unconnected_bound_addr: self.bound_addr,
+            read_shutdown: false,
+            write_shutdown: false,
+        };
+        new_state.refresh_file_state(common, event_queue);
+
+        (new_state.into(), Ok(()))
+    }
+
+    fn disconnect(
+        self,
+        _common: &mut UdpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        // disconnecting an unconnected socket has no effect
+        (self.into(), Ok(()))
+    }
+
+    fn push_in_packet(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        packet: PacketRc,
+        event_queue: &mut EventQueue,
+    ) {
+        common.push_in_packet(packet);
+        self.refresh_file_state(common, event_queue);
+    }
+}
+
+impl Protocol for Connected {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        Some(self.peer_addr)
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        Some(self.bound_addr)
+    }
+
+    fn refresh_file_state(&self, common: &mut UdpSocketCommon, event_queue: &mut EventQueue) {
+        common.refresh_file_state(self.read_shutdown, event_queue);
+    }
+
+    fn close(
+        self,
+        common: &mut UdpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        let new_state = Closed {
+            bound_addr: Some(self.bound_addr),
+            peer_addr: Some(self.peer_addr),
+        };
+        new_state.refresh_file_state(common, event_queue);
+
+        // the socket should no longer receive packets
+        common.disassociate_later(event_queue);
+        (new_state.into(), common.close(event_queue))
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn bind(
+        &mut self,
+        _common: &mut UdpSocketCommon,
+        _addr: Option<&nix::sys::socket::SockAddr>,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        // connected sockets are always bound
+        Err(Errno::EINVAL.into())
+    }
+
+    fn sendto<R>(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        bytes: R,
+        addr: Option<SocketAddrV4>,
+        more: bool,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        if self.write_shutdown {
+            return Err(Errno::EPIPE.into());
+        }
+
+        // like linux, a connected udp socket can still send to other addresses
+        let dst_addr = addr.unwrap_or(self.peer_addr);
+
+        let rv = common.sendto(bytes, self.bound_addr, dst_addr, more, event_queue);
+        self.refresh_file_state(common, event_queue);
+        rv
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvfrom<W>(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        bytes: W,
+        flags: MsgFlags,
+        event_queue: &mut EventQueue,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        // like linux, return EOF if reading has been shut down and there are no more datagrams
+        if self.read_shutdown && common.recv_buffer.is_empty() {
+            return Ok((0.into(), None, 0, AncillaryData::default()));
+        }
+
+        let rv = common.recvfrom(bytes, flags);
+        self.refresh_file_state(common, event_queue);
+        rv
+    }
+
+    fn shutdown(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        if how == nix::sys::socket::Shutdown::Read || how == nix::sys::socket::Shutdown::Both {
+            self.read_shutdown = true;
+        }
+
+        if how == nix::sys::socket::Shutdown::Write || how == nix::sys::socket::Shutdown::Both {
+            self.write_shutdown = true;
+        }
+
+        self.refresh_file_state(common, event_queue);
+
+        Ok(())
+    }
+
+    fn connect(
+        self,
+        common: &mut UdpSocketCommon,
+        peer_addr: SocketAddrV4,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        // reconnecting chooses a new local address based on the new peer, but keeps the same port
+        let bound_addr = match self.unconnected_bound_addr {
+            Some(addr) => addr,
+            None => SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, self.bound_addr.port()),
+        };
+
+        let new_state = Connected {
+            bound_addr: connected_bound_addr(common, bound_addr, peer_addr),
+            peer_addr,
+            unconnected_bound_addr: self.unconnected_bound_addr,
+            read_shutdown: false,
+            write_shutdown: false,
+        };
+        new_state.refresh_file_state(common, event_queue);
+
+        (new_state.into(), Ok(()))
+    }
+
+    fn disconnect(
+        self,
+        common: &mut UdpSocketCommon,
+        event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        // like linux, the socket returns to the address it was bound to before connecting, or
+        // becomes unbound if it was implicitly bound when connecting
+        let new_state = Unconnected {
+            bound_addr: self.unconnected_bound_addr,
+        };
+        new_state.refresh_file_state(common, event_queue);
+
+        (new_state.into(), Ok(()))
+    }
+
+    fn push_in_packet(
+        &mut self,
+        common: &mut UdpSocketCommon,
+        mut packet: PacketRc,
+        event_queue: &mut EventQueue,
+    ) {
+        // connected sockets only receive datagrams from their peer
+        if packet.src_address() != self.peer_addr {
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+            return;
+        }
+
+        common.push_in_packet(packet);
+        self.refresh_file_state(common, event_queue);
+    }
+}
+
+impl Protocol for Closed {
+    fn peer_address(&self) -> Option<SocketAddrV4> {
+        self.peer_addr
+    }
+
+    fn bound_address(&self) -> Option<SocketAddrV4> {
+        self.bound_addr
+    }
+
+    fn refresh_file_state(&self, common: &mut UdpSocketCommon, event_queue: &mut EventQueue) {
+        common.copy_state(
+            /* mask= */ FileState::all(),
+            FileState::CLOSED,
+            event_queue,
+        );
+    }
+
+    fn close(
+        self,
+        _common: &mut UdpSocketCommon,
+        _event_queue: &mut EventQueue,
+    ) -> (ProtocolState, Result<(), SyscallError>) {
+        // why are we trying to close an already closed file? we probably want a bt
+        panic!("Trying to close an already closed socket");
+    }
+}
+
+/// The address that a socket bound to `bound_addr` will use when connected to `peer_addr`. Like
+/// linux, if the socket is bound to the unspecified address, the local address is chosen based on
+/// the route to the peer.
+fn connected_bound_addr(
+    common: &UdpSocketCommon,
+    bound_addr: SocketAddrV4,
+    peer_addr: SocketAddrV4,
+) -> SocketAddrV4 {
+    if !bound_addr.ip().is_unspecified() {
+        return bound_addr;
+    }
+
+    SocketAddrV4::new(common.source_ip_for(*peer_addr.ip()), bound_addr.port())
+}
+
+/// A datagram that is being built from multiple sends using `MSG_MORE`.
+struct CorkedDatagram {
+    src_addr: SocketAddrV4,
+    dst_addr: SocketAddrV4,
+    payload: Vec<u8>,
+}
+
+/// Common data and functionality that is useful for all states.
+struct UdpSocketCommon {
+    this_socket: Weak<AtomicRefCell<UdpSocket>>,
+    host: HostTreePointer<c::Host>,
+    /// Packets waiting to be sent by the network interface.
+    send_buffer: VecDeque<PacketRc>,
+    /// The number of payload bytes in the send buffer.
+    send_buffer_len: u64,
+    /// The max number of payload bytes in the send buffer (`SO_SNDBUF`).
+    send_buffer_size: u64,
+    /// A datagram that was sent with `MSG_MORE`, and will be sent with the data of later sends.
+    corked: Option<CorkedDatagram>,
+    /// Packets received from the network interface but not yet read by the plugin.
+    recv_buffer: VecDeque<PacketRc>,
+    /// The number of payload bytes in the receive buffer.
+    recv_buffer_len: u64,
+    /// The max number of payload bytes in the receive buffer (`SO_RCVBUF`).
+    recv_buffer_size: u64,
+    /// The IP-level socket options. Shadow doesn't model these options, but they're stored so that
+    /// `getsockopt()` returns the value that was set.
+    ip_options: IpOptions,
+    event_source: StateEventSource,
+    state: FileState,
+    status: FileStatus,
+    // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file
+    has_open_file: bool,
+}
+
+impl UdpSocketCommon {
+    fn host_ptr(&self) -> *mut c::Host {
+        unsafe { self.host.ptr() }
+    }
+
+    pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        // check that the CLOSED flag was set by the protocol state
+        if !self.state.contains(FileState::CLOSED) {
+            // set the flag here since we missed doing it before
+            // do this before the below panic, otherwise rust gives us warnings
+            self.copy_state(
+                /* mask= */ FileState::all(),
+                FileState::CLOSED,
+                event_queue,
+            );
+
+            // panic in debug builds since the backtrace will be helpful for debugging
+            debug_panic!("When closing a udp socket, the CLOSED flag was not set");
+        }
+
+        Ok(())
+    }
+
+    /// Bind to `addr`, choosing an ephemeral port if the port is 0.
+    pub fn bind(&mut self, addr: SocketAddrV4) -> Result<SocketAddrV4, SyscallError> {
+        self.bind_for_peer(addr, SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))
+    }
+
+    /// Bind to `addr`, choosing an ephemeral port if the port is 0. If choosing an ephemeral port,
+    /// the port only needs to be unused for the given peer address.
+    pub fn bind_for_peer(
+        &mut self,
+        addr: SocketAddrV4,
+        peer_addr: SocketAddrV4,
+    ) -> Result<SocketAddrV4, SyscallError> {
+        inet::bind_port(self.host_ptr(), c::_ProtocolType_PUDP, addr, peer_addr)
+    }
+
+    /// Associate the socket with the network interfaces once the socket is no longer borrowed.
+    fn associate_later(&self, ip: Ipv4Addr, event_queue: &mut EventQueue) {
+        if let Some(socket) = self.this_socket.upgrade() {
+            let host = self.host_ptr();
+            event_queue.add(move |_| inet::associate(host, &InetSocket::Udp(socket), ip));
+        }
+    }
+
+    /// Disassociate the socket from the network interfaces once the socket is no longer borrowed.
+    fn disassociate_later(&self, event_queue: &mut EventQueue) {
+        if let Some(socket) = self.this_socket.upgrade() {
+            let host = self.host_ptr();
+            event_queue.add(move |_| inet::disassociate(host, &InetSocket::Udp(socket)));
+        }
+    }
+
+    /// The local address used when sending packets to `dst_ip` from the unspecified address.
+    fn source_ip_for(&self, dst_ip: Ipv4Addr) -> Ipv4Addr {
+        inet::source_ip_for(self.host_ptr(), dst_ip)
+    }
+
+    pub fn sendto<R>(
+        &mut self,
+        bytes: R,
+        bound_addr: SocketAddrV4,
+        dst_addr: SocketAddrV4,
+        more: bool,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        assert!(self.corked.is_none());
+
+        // like linux, an unspecified destination address refers to this host
+        let dst_addr = if dst_addr.ip().is_unspecified() {
+            SocketAddrV4::new(Ipv4Addr::LOCALHOST, dst_addr.port())
+        } else {
+            dst_addr
+        };
+
+        if dst_addr.port() == 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let src_addr = if bound_addr.ip().is_unspecified() {
+            SocketAddrV4::new(self.source_ip_for(*dst_addr.ip()), bound_addr.port())
+        } else {
+            bound_addr
+        };
+
+        let payload = self.read_payload(bytes, 0)?;
+        let len = payload.len();
+
+        let datagram = CorkedDatagram {
+            src_addr,
+            dst_addr,
+            payload,
+        };
+
+        if more {
+            self.corked = Some(datagram);
+        } else {
+            self.send_datagram(datagram, event_queue);
+        }
+
+        Ok(len.into())
+    }
+
+    /// Append to the corked datagram, and send it if `more` is false.
+    pub fn send_corked<R>(
+        &mut self,
+        bytes: R,
+        more: bool,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        let corked_len = self.corked.as_ref().unwrap().payload.len();
+
+        let payload = self.read_payload(bytes, corked_len)?;
+        let len = payload.len();
+
+        let mut datagram = self.corked.take().unwrap();
+        datagram.payload.extend(payload);
+
+        if more {
+            self.corked = Some(datagram);
+        } else {
+            self.send_datagram(datagram, event_queue);
+        }
+
+        Ok(len.into())
+    }
+
+    /// Read the data for a datagram that already has `corked_len` bytes of payload, if it fits in
+    /// the datagram and the send buffer.
+    fn read_payload<R>(&self, mut bytes: R, corked_len: usize) -> Result<Vec<u8>, SyscallError>
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        let len = bytes.stream_len_bp()? as usize;
+
+        if corked_len + len > UDP_MAX_PAYLOAD_SIZE {
+            return Err(Errno::EMSGSIZE.into());
+        }
+
+        // like linux, a datagram can always be added to an empty send buffer
+        let buffered_len = self.send_buffer_len + corked_len as u64;
+        if buffered_len > 0 && buffered_len + len as u64 > self.send_buffer_size {
+            return Err(Errno::EWOULDBLOCK.into());
+        }
+
+        let mut payload = vec![0u8; len];
+        bytes.read_exact(&mut payload)?;
+
+        Ok(payload)
+    }
+
+    /// Add the datagram to the send buffer.
+    fn send_datagram(&mut self, datagram: CorkedDatagram, event_queue: &mut EventQueue) {
+        let CorkedDatagram {
+            src_addr,
+            dst_addr,
+            payload,
+        } = datagram;
+
+        // the packet would be dropped by the network, so there's no need to create it
+        if !inet::is_routable(self.host_ptr(), *dst_addr.ip()) {
+            log::warn!(
+                "Dropping udp datagram sent to address '{}' for which no host exists",
+                dst_addr
+            );
+            return;
+        }
+
+        let mut packet = PacketRc::new_udp(self.host_ptr(), src_addr, dst_addr, &payload);
+        packet.add_delivery_status(
+            c::_PacketDeliveryStatusFlags_PDS_SND_CREATED
+                | c::_PacketDeliveryStatusFlags_PDS_SND_SOCKET_BUFFERED,
+        );
+
+        self.send_buffer.push_back(packet);
+        self.send_buffer_len += payload.len() as u64;
+
+        // the network interface will pull the packet from the socket, so we must wait until the
+        // socket is no longer borrowed before notifying the interface
+        if let Some(socket) = self.this_socket.upgrade() {
+            let host = self.host_ptr();
+            event_queue
+                .add(move |_| inet::wants_send(host, &InetSocket::Udp(socket), *src_addr.ip()));
+        }
+    }
+
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    pub fn recvfrom<W>(
+        &mut self,
+        mut bytes: W,
+        flags: MsgFlags,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        let packet = match self.recv_buffer.front() {
+            Some(packet) => packet,
+            None => return Err(Errno::EWOULDBLOCK.into()),
+        };
+
+        // packet payloads are guarded by a mutex, so it's easiest to make a copy of them
+        let msg_len = packet.payload_size();
+        let mut payload = vec![0u8; msg_len];
+        assert_eq!(packet.copy_payload(&mut payload), msg_len);
+
+        // like linux, the datagram is truncated if the buffer is too small
+        let num_copied = write_partial(&mut bytes, &payload)?;
+        let from_addr = inet::ipv4_to_sockaddr(packet.src_address());
+
+        if !flags.contains(MsgFlags::MSG_PEEK) {
+            let mut packet = self.recv_buffer.pop_front().unwrap();
+            self.recv_buffer_len -= msg_len as u64;
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DELIVERED);
+        }
+
+        Ok((
+            num_copied.into(),
+            Some(from_addr),
+            msg_len,
+            AncillaryData::default(),
+        ))
+    }
+
+    pub fn push_in_packet(&mut self, mut packet: PacketRc) {
+        packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_PROCESSED);
+
+        let len = packet.payload_size() as u64;
+
+        // like linux, a datagram can always be added to an empty receive buffer
+        if !self.recv_buffer.is_empty() && self.recv_buffer_len + len > self.recv_buffer_size {
+            packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_DROPPED);
+            return;
+        }
+
+        packet.add_delivery_status(c::_PacketDeliveryStatusFlags_PDS_RCV_SOCKET_BUFFERED);
+
+        self.recv_buffer.push_back(packet);
+        self.recv_buffer_len += len;
+    }
+
+    pub fn getsockopt(
+        &self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &mut MemoryManager,
+    ) -> Result<libc::socklen_t, SyscallError> {
+        let optval: libc::c_int = match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_TYPE) => libc::SOCK_DGRAM,
+            (libc::SOL_SOCKET, libc::SO_DOMAIN) => libc::AF_INET,
+            (libc::SOL_SOCKET, libc::SO_PROTOCOL) => libc::IPPROTO_UDP,
+            // we don't yet support asynchronous errors for udp sockets
+            (libc::SOL_SOCKET, libc::SO_ERROR) => 0,
+            (libc::SOL_SOCKET, libc::SO_ACCEPTCONN) => 0,
+            (libc::SOL_SOCKET, libc::SO_SNDBUF) => self.send_buffer_size.try_into().unwrap(),
+            (libc::SOL_SOCKET, libc::SO_RCVBUF) => self.recv_buffer_size.try_into().unwrap(),
+            (libc::IPPROTO_IP, libc::IP_TOS) => self.ip_options.tos.into(),
+            (libc::IPPROTO_IP, libc::IP_TTL) => self.ip_options.ttl.into(),
+            (libc::IPPROTO_IP, libc::IP_MTU_DISCOVER) => self.ip_options.mtu_discover,
+            (libc::IPPROTO_IP, libc::IP_RECVERR) => self.ip_options.recv_err.into(),
+            _ => {
+                log::warn!(
+                    "getsockopt() for level {} and option {} is not supported for udp sockets",
+                    level,
+                    optname
+                );
+                return Err(Errno::ENOPROTOOPT.into());
+            }
+        };
+
+        write_int_sockopt(optval, optval_ptr, optlen, memory_manager)
+    }
+
+    pub fn setsockopt(
+        &mut self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &MemoryManager,
+    ) -> Result<(), SyscallError> {
+        match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_SNDBUF) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.send_buffer_size =
+                    sockopt_buffer_size(optval, UDP_SOCKET_MIN_SEND_BUFFER_SIZE);
+            }
+            (libc::SOL_SOCKET, libc::SO_RCVBUF) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.recv_buffer_size =
+                    sockopt_buffer_size(optval, UDP_SOCKET_MIN_RECV_BUFFER_SIZE);
+            }
+            // these options have no effect in shadow
+            (
+                libc::SOL_SOCKET,
+                libc::SO_REUSEADDR | libc::SO_REUSEPORT | libc::SO_KEEPALIVE | libc::SO_BROADCAST,
+            ) => {
+                read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+            }
+            (libc::IPPROTO_IP, libc::IP_TOS) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                // like linux, only the low byte is used
+                self.ip_options.tos = optval as u8;
+            }
+            (libc::IPPROTO_IP, libc::IP_TTL) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.ip_options.ttl = match optval {
+                    // restore the default ttl
+                    -1 => IP_DEFAULT_TTL,
+                    1..=255 => optval as u8,
+                    _ => return Err(Errno::EINVAL.into()),
+                };
+            }
+            (libc::IPPROTO_IP, libc::IP_MTU_DISCOVER) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                if !(libc::IP_PMTUDISC_DONT..=libc::IP_PMTUDISC_OMIT).contains(&optval) {
+                    return Err(Errno::EINVAL.into());
+                }
+                self.ip_options.mtu_discover = optval;
+            }
+            // shadow doesn't generate icmp errors, so the error queue is always empty
+            (libc::IPPROTO_IP, libc::IP_RECVERR) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.ip_options.recv_err = optval != 0;
+            }
+            _ => {
+                log::warn!(
+                    "setsockopt() for level {} and option {} is not supported for udp sockets",
+                    level,
+                    optname
+                );
+                return Err(Errno::ENOPROTOOPT.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: u64,
+        arg_ptr: PluginPtr,
+        memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        let val = match request {
+            // the payload size of the next datagram (also known as `SIOCINQ`)
+            libc::FIONREAD => self
+                .recv_buffer
+                .front()
+                .map_or(0, |packet| packet.payload_size() as u64),
+            // the number of bytes waiting to be sent (also known as `SIOCOUTQ`)
+            libc::TIOCOUTQ => self.send_buffer_len,
+            libc::TCGETS
+            | libc::TCSETS
+            | libc::TCSETSW
+            | libc::TCSETSF
+            | libc::TCGETA
+            | libc::TCSETA
+            | libc::TCSETAW
+            | libc::TCSETAF
+            | libc::TIOCGWINSZ
+            | libc::TIOCSWINSZ => {
+                // not a terminal
+                return Err(Errno::ENOTTY.into());
+            }
+            _ => {
+                log::warn!(
+                    "We do not yet handle ioctl request {} on udp sockets",
+                    request
+                );
+                return Err(Errno::EINVAL.into());
+            }
+        };
+
+        let val = libc::c_int::try_from(val).unwrap();
+        let arg_ptr = TypedPluginPtr::new::<libc::c_int>(arg_ptr, 1);
+        memory_manager.copy_to_ptr(arg_ptr, &[val])?;
+
+        Ok(0.into())
+    }
+
+    fn refresh_file_state(&mut self, read_shutdown: bool, event_queue: &mut EventQueue) {
+        let mut new_state = FileState::ACTIVE;
+
+        new_state.set(
+            FileState::READABLE,
+            !self.recv_buffer.is_empty() || read_shutdown,
+        );
+        new_state.set(
+            FileState::WRITABLE,
+            self.send_buffer_len < self.send_buffer_size,
+        );
+
+        self.copy_state(/* mask= */ FileState::all(), new_state, event_queue);
+    }
+
+    fn copy_state(&mut self, mask: FileState, state: FileState, event_queue: &mut EventQueue) {
+        let old_state = self.state;
+
+        // remove the masked flags, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, event_queue);
+    }
+
+    fn handle_state_change(&mut self, old_state: FileState, event_queue: &mut EventQueue) {
+        let states_changed = self.state ^ old_state;
+
+        // if nothing changed
+        if states_changed.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, event_queue);
+    }
+}
+
+/// The IP-level (`IPPROTO_IP`) socket options.
+struct IpOptions {
+    /// The type-of-service field (`IP_TOS`).
+    tos: u8,
+    /// The time-to-live field (`IP_TTL`).
+    ttl: u8,
+    /// The path MTU discovery setting (`IP_MTU_DISCOVER`).
+    mtu_discover: libc::c_int,
+    /// Whether extended error reporting is enabled (`IP_RECVERR`).
+    recv_err: bool,
+}
+
+impl Default for IpOptions {
+    fn default() -> Self {
+        Self {
+            tos: 0,
+            ttl: IP_DEFAULT_TTL,
+            mtu_discover: libc::IP_PMTUDISC_WANT,
+            recv_err: false,
+        }
+    }
+}
+
+/// Get the buffer size for a `SO_SNDBUF` or `SO_RCVBUF` option value. Like Linux, the value is
+/// capped to a maximum and then doubled to allow space for bookkeeping overhead.
+fn sockopt_buffer_size(optval: libc::c_int, min: u64) -> u64 {
+    // linux interprets the value as unsigned, so negative values become very large
+    let optval = u64::from(optval as u32);
+    std::cmp::max(std::cmp::min(optval, UDP_SOCKET_MAX_BUFFER_SIZE) * 2, min)
+}
@@ -1,14 +1,18 @@
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
+use nix::errno::Errno;
 
 use crate::cshadow as c;
-use crate::host::descriptor::{FileMode, FileState, FileStatus, SyscallResult};
+use crate::host::descriptor::{
+    CompatFile, FileMode, FileState, FileStatus, StateListenerFilter, SyscallResult,
+};
 use crate::host::memory_manager::MemoryManager;
-use crate::host::syscall_types::{PluginPtr, SysCallReg, SyscallError};
-use crate::utility::event_queue::EventQueue;
+use crate::host::syscall_types::{PluginPtr, SysCallReg, SyscallError, TypedPluginPtr};
+use crate::utility::event_queue::{EventQueue, Handle};
 use crate::utility::HostTreePointer;
 
+use inet::InetSocket;
 use unix::UnixSocket;
 
 // https://github.com/shadow/shadow/issues/2093
@@ -16,41 +20,49 @@ use unix::UnixSocket;
 use nix::sys::socket::SockAddr;
 
 pub mod abstract_unix_ns;
+pub mod inet;
+pub mod pathname_unix_ns;
 pub mod unix;
 
 #[derive(Clone)]
 pub enum Socket {
     Unix(Arc<AtomicRefCell<UnixSocket>>),
+    Inet(InetSocket),
 }
 
 impl Socket {
     pub fn borrow(&self) -> SocketRef {
         match self {
             Self::Unix(ref f) => SocketRef::Unix(f.borrow()),
+            Self::Inet(ref f) => SocketRef::Inet(f.borrow()),
         }
     }
 
     pub fn try_borrow(&self) -> Result<SocketRef, atomic_refcell::BorrowError> {
         Ok(match self {
             Self::Unix(ref f) => SocketRef::Unix(f.try_borrow()?),
+            Self::Inet(ref f) => SocketRef::Inet(f.try_borrow()?),
         })
     }
 
     pub fn borrow_mut(&self) -> SocketRefMut {
         match self {
             Self::Unix(ref f) => SocketRefMut::Unix(f.borrow_mut()),
+            Self::Inet(ref f) => SocketRefMut::Inet(f.borrow_mut()),
         }
     }
 
     pub fn try_borrow_mut(&self) -> Result<SocketRefMut, atomic_refcell::BorrowMutError> {
         Ok(match self {
             Self::Unix(ref f) => SocketRefMut::Unix(f.try_borrow_mut()?),
+            Self::Inet(ref f) => SocketRefMut::Inet(f.try_borrow_mut()?),
         })
     }
 
     pub fn canonical_handle(&self) -> usize {
         match self {
             Self::Unix(f) => Arc::as_ptr(f) as usize,
+            Self::Inet(f) => f.canonical_handle(),
         }
     }
 
@@ -63,6 +75,7 @@ impl Socket {
     ) -> SyscallResult {
         match self {
             Self::Unix(socket) => UnixSocket::bind(socket, addr, rng),
+            Self::Inet(socket) => socket.bind(addr, rng),
         }
     }
 
@@ -75,6 +88,19 @@ impl Socket {
     ) -> Result<(), SyscallError> {
         match self {
             Self::Unix(socket) => UnixSocket::connect(socket, addr, event_queue),
+            Self::Inet(socket) => socket.connect(addr, event_queue),
+        }
+    }
+
+    /// Dissolve the socket's association with its peer (`connect()` with an `AF_UNSPEC`
+    /// address).
+    pub fn disconnect(&self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        match self {
+            Self::Unix(_) => {
+                log::warn!("Disconnecting unix sockets is not supported");
+                Err(Errno::EINVAL.into())
+            }
+            Self::Inet(socket) => socket.disconnect(event_queue),
         }
     }
 }
@@ -83,6 +109,7 @@ impl std::fmt::Debug for Socket {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Unix(_) => write!(f, "Unix")?,
+            Self::Inet(_) => write!(f, "Inet")?,
         }
 
         if let Ok(file) = self.try_borrow() {
@@ -100,27 +127,29 @@ impl std::fmt::Debug for Socket {
 
 pub enum SocketRef<'a> {
     Unix(atomic_refcell::AtomicRef<'a, UnixSocket>),
+    Inet(inet::InetSocketRef<'a>),
 }
 
 pub enum SocketRefMut<'a> {
     Unix(atomic_refcell::AtomicRefMut<'a, UnixSocket>),
+    Inet(inet::InetSocketRefMut<'a>),
 }
 
 // file functions
 impl SocketRef<'_> {
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn get_status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn supports_sa_restart(&self) -> bool
     );
 }
@@ -132,6 +161,7 @@ impl SocketRef<'_> {
     pub fn getpeername(&self) -> Result<Option<SockAddr>, SyscallError> {
         match self {
             Self::Unix(socket) => socket.getpeername().map(|x| x.map(SockAddr::Unix)),
+            Self::Inet(socket) => socket.getpeername().map(|x| x.map(inet::ipv4_to_sockaddr)),
         }
     }
 
@@ -140,56 +170,77 @@ impl SocketRef<'_> {
     pub fn getsockname(&self) -> Result<Option<SockAddr>, SyscallError> {
         match self {
             Self::Unix(socket) => socket.getsockname().map(|x| x.map(SockAddr::Unix)),
+            Self::Inet(socket) => socket.getsockname().map(|x| x.map(inet::ipv4_to_sockaddr)),
         }
     }
 
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn address_family(&self) -> nix::sys::socket::AddressFamily
     );
+
+    enum_passthrough!(self, (), Unix, Inet;
+        pub fn socket_type(&self) -> nix::sys::socket::SockType
+    );
 }
 
 // file functions
 impl SocketRefMut<'_> {
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn get_status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (val), Unix;
+    enum_passthrough!(self, (val), Unix, Inet;
         pub fn set_has_open_file(&mut self, val: bool)
     );
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn supports_sa_restart(&self) -> bool
     );
-    enum_passthrough!(self, (event_queue), Unix;
+    enum_passthrough!(self, (event_queue), Unix, Inet;
         pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError>
     );
-    enum_passthrough!(self, (status), Unix;
+    enum_passthrough!(self, (status), Unix, Inet;
         pub fn set_status(&mut self, status: FileStatus)
     );
-    enum_passthrough!(self, (request, arg_ptr, memory_manager), Unix;
+    enum_passthrough!(self, (request, arg_ptr, memory_manager), Unix, Inet;
         pub fn ioctl(&mut self, request: u64, arg_ptr: PluginPtr, memory_manager: &mut MemoryManager) -> SyscallResult
     );
-    enum_passthrough!(self, (ptr), Unix;
+    enum_passthrough!(self, (monitoring, filter, notify_fn), Unix, Inet;
+        pub fn add_listener(
+            &mut self,
+            monitoring: FileState,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+        ) -> Handle<(FileState, FileState)>
+    );
+    enum_passthrough!(self, (monitoring, filter, notify_fn), Unix, Inet;
+        pub fn add_exclusive_listener(
+            &mut self,
+            monitoring: FileState,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+        ) -> Handle<(FileState, FileState, u64)>
+    );
+    enum_passthrough!(self, (ptr), Unix, Inet;
         pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>)
     );
-    enum_passthrough!(self, (ptr), Unix;
+    enum_passthrough!(self, (ptr), Unix, Inet;
         pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener)
     );
 
-    enum_passthrough_generic!(self, (bytes, offset, event_queue), Unix;
+    enum_passthrough_generic!(self, (bytes, offset, event_queue), Unix, Inet;
         pub fn read<W>(&mut self, bytes: W, offset: libc::off_t, event_queue: &mut EventQueue) -> SyscallResult
         where W: std::io::Write + std::io::Seek
     );
 
-    enum_passthrough_generic!(self, (source, offset, event_queue), Unix;
+    enum_passthrough_generic!(self, (source, offset, event_queue), Unix, Inet;
         pub fn write<R>(&mut self, source: R, offset: libc::off_t, event_queue: &mut EventQueue) -> SyscallResult
         where R: std::io::Read + std::io::Seek
     );
@@ -202,6 +253,7 @@ impl SocketRefMut<'_> {
     pub fn getpeername(&self) -> Result<Option<SockAddr>, SyscallError> {
         match self {
             Self::Unix(socket) => socket.getpeername().map(|x| x.map(SockAddr::Unix)),
+            Self::Inet(socket) => socket.getpeername().map(|x| x.map(inet::ipv4_to_sockaddr)),
         }
     }
 
@@ -210,36 +262,59 @@ impl SocketRefMut<'_> {
     pub fn getsockname(&self) -> Result<Option<SockAddr>, SyscallError> {
         match self {
             Self::Unix(socket) => socket.getsockname().map(|x| x.map(SockAddr::Unix)),
+            Self::Inet(socket) => socket.getsockname().map(|x| x.map(inet::ipv4_to_sockaddr)),
         }
     }
 
-    enum_passthrough!(self, (), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
         pub fn address_family(&self) -> nix::sys::socket::AddressFamily
     );
 
-    enum_passthrough_generic!(self, (source, addr, event_queue), Unix;
+    enum_passthrough!(self, (), Unix, Inet;
+        pub fn socket_type(&self) -> nix::sys::socket::SockType
+    );
+
+    enum_passthrough_generic!(self, (source, flags, addr, ancillary, event_queue), Unix, Inet;
         // https://github.com/shadow/shadow/issues/2093
         #[allow(deprecated)]
-        pub fn sendto<R>(&mut self, source: R, addr: Option<nix::sys::socket::SockAddr>, event_queue: &mut EventQueue)
+        pub fn sendto<R>(&mut self, source: R, flags: libc::c_int, addr: Option<nix::sys::socket::SockAddr>, ancillary: AncillaryData, event_queue: &mut EventQueue)
             -> SyscallResult
         where R: std::io::Read + std::io::Seek
     );
 
-    enum_passthrough_generic!(self, (bytes, event_queue), Unix;
+    enum_passthrough_generic!(self, (bytes, flags, event_queue), Unix, Inet;
         // https://github.com/shadow/shadow/issues/2093
         #[allow(deprecated)]
-        pub fn recvfrom<W>(&mut self, bytes: W, event_queue: &mut EventQueue)
-            -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>), SyscallError>
+        pub fn recvfrom<W>(&mut self, bytes: W, flags: nix::sys::socket::MsgFlags, event_queue: &mut EventQueue)
+            -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>, usize, AncillaryData), SyscallError>
         where W: std::io::Write + std::io::Seek
     );
 
-    enum_passthrough!(self, (backlog, event_queue), Unix;
+    enum_passthrough!(self, (level, optname, optval_ptr, optlen, memory_manager), Unix, Inet;
+        pub fn getsockopt(&mut self, level: libc::c_int, optname: libc::c_int, optval_ptr: PluginPtr,
+                          optlen: libc::socklen_t, memory_manager: &mut MemoryManager)
+            -> Result<libc::socklen_t, SyscallError>
+    );
+
+    enum_passthrough!(self, (level, optname, optval_ptr, optlen, memory_manager, event_queue), Unix, Inet;
+        pub fn setsockopt(&mut self, level: libc::c_int, optname: libc::c_int, optval_ptr: PluginPtr,
+                          optlen: libc::socklen_t, memory_manager: &MemoryManager,
+                          event_queue: &mut EventQueue)
+            -> Result<(), SyscallError>
+    );
+
+    enum_passthrough!(self, (backlog, event_queue), Unix, Inet;
         pub fn listen(&mut self, backlog: i32, event_queue: &mut EventQueue) -> Result<(), SyscallError>
     );
 
+    enum_passthrough!(self, (how, event_queue), Unix, Inet;
+        pub fn shutdown(&mut self, how: nix::sys::socket::Shutdown, event_queue: &mut EventQueue) -> Result<(), SyscallError>
+    );
+
     pub fn accept(&mut self, event_queue: &mut EventQueue) -> Result<Socket, SyscallError> {
         match self {
             Self::Unix(socket) => socket.accept(event_queue).map(Socket::Unix),
+            Self::Inet(socket) => socket.accept(event_queue).map(Socket::Inet),
         }
     }
 }
@@ -248,6 +323,7 @@ impl std::fmt::Debug for SocketRef<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Unix(_) => write!(f, "Unix")?,
+            Self::Inet(_) => write!(f, "Inet")?,
         }
 
         write!(
@@ -263,6 +339,7 @@ impl std::fmt::Debug for SocketRefMut<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Unix(_) => write!(f, "Unix")?,
+            Self::Inet(_) => write!(f, "Inet")?,
         }
 
         write!(
@@ -274,6 +351,15 @@ impl std::fmt::Debug for SocketRefMut<'_> {
     }
 }
 
+/// Ancillary data (control messages) that is sent or received along with socket data.
+#[derive(Clone, Default)]
+pub struct AncillaryData {
+    /// Open files that are being passed to the receiver (`SCM_RIGHTS`).
+    pub rights: Vec<CompatFile>,
+    /// Credentials of the sending process (`SCM_CREDENTIALS`).
+    pub creds: Option<libc::ucred>,
+}
+
 /// Returns a nix socket address object where only the family is set.
 // https://github.com/shadow/shadow/issues/2093
 #[allow(deprecated)]
@@ -284,3 +370,45 @@ pub fn empty_sockaddr(family: nix::sys::socket::AddressFamily) -> nix::sys::sock
     // the size of ss_family will be 2 bytes on linux
     nix::sys::socket::sockaddr_storage_to_addr(&addr, 2).unwrap()
 }
+
+/// Write an integer socket option value to the plugin, and return the length written. As in
+/// Linux, the value is truncated if `optlen` is too small.
+pub fn write_int_sockopt(
+    optval: libc::c_int,
+    optval_ptr: PluginPtr,
+    optlen: libc::socklen_t,
+    memory_manager: &mut MemoryManager,
+) -> Result<libc::socklen_t, SyscallError> {
+    write_sockopt(&optval.to_ne_bytes(), optval_ptr, optlen, memory_manager)
+}
+
+/// Write a socket option value to the plugin, and return the length written. As in Linux, the
+/// value is truncated if `optlen` is too small.
+pub fn write_sockopt(
+    optval: &[u8],
+    optval_ptr: PluginPtr,
+    optlen: libc::socklen_t,
+    memory_manager: &mut MemoryManager,
+) -> Result<libc::socklen_t, SyscallError> {
+    let len = std::cmp::min(usize::try_from(optlen).unwrap(), optval.len());
+
+    memory_manager.copy_to_ptr(TypedPluginPtr::new::<u8>(optval_ptr, len), &optval[..len])?;
+
+    Ok(len.try_into().unwrap())
+}
+
+/// Read an integer socket option value from the plugin.
+pub fn read_int_sockopt(
+    optval_ptr: PluginPtr,
+    optlen: libc::socklen_t,
+    memory_manager: &MemoryManager,
+) -> Result<libc::c_int, SyscallError> {
+    if usize::try_from(optlen).unwrap() < std::mem::size_of::<libc::c_int>() {
+        return Err(Errno::EINVAL.into());
+    }
+
+    let [optval] = memory_manager
+        .read_vals::<libc::c_int, 1>(TypedPluginPtr::new::<libc::c_int>(optval_ptr, 1))?;
+
+    Ok(optval)
+}
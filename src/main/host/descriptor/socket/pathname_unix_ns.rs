@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Weak};
+
+use atomic_refcell::AtomicRefCell;
+use nix::errno::Errno;
+
+use crate::host::descriptor::socket::abstract_unix_ns::BindError;
+use crate::host::descriptor::socket::unix::{UnixSocket, UnixSocketType};
+
+/// The name of the directory (within the host's data directory) that contains the placeholder
+/// files for bound pathname sockets.
+const PLACEHOLDER_DIR_NAME: &str = "unix-sockets";
+
+struct NamespaceEntry {
+    /// The bound socket.
+    socket: Weak<AtomicRefCell<UnixSocket>>,
+    /// The type of the bound socket.
+    socket_type: UnixSocketType,
+    /// The placeholder file representing the socket in the host's data directory, if one was
+    /// created.
+    placeholder: Option<PathBuf>,
+}
+
+impl Drop for NamespaceEntry {
+    fn drop(&mut self) {
+        if let Some(ref placeholder) = self.placeholder {
+            if let Err(e) = std::fs::remove_file(placeholder) {
+                log::debug!(
+                    "Could not remove unix socket placeholder file {:?}: {}",
+                    placeholder,
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// A namespace for unix sockets bound to filesystem paths. Unlike Linux, the sockets are not
+/// created in the real filesystem (which is shared by all hosts), so each host has its own
+/// independent namespace of socket paths.
+///
+/// As in Linux, a name remains bound after its socket has been closed, and is only released when
+/// the path is unlinked.
+pub struct PathnameUnixNamespace {
+    address_map: HashMap<PathBuf, NamespaceEntry>,
+    /// The directory that relative socket paths are resolved against.
+    working_dir: PathBuf,
+    /// The directory in which placeholder files are created, if any.
+    placeholder_dir: Option<PathBuf>,
+}
+
+impl PathnameUnixNamespace {
+    /// Create a new namespace. Relative socket paths will be resolved against `working_dir`, and
+    /// placeholder files will be created within `data_dir` (if given).
+    pub fn new(working_dir: PathBuf, data_dir: Option<&Path>) -> Self {
+        Self {
+            address_map: HashMap::new(),
+            working_dir,
+            placeholder_dir: data_dir.map(|x| x.join(PLACEHOLDER_DIR_NAME)),
+        }
+    }
+
+    /// Get the normalized absolute path that `path` refers to in this namespace.
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        normalize_path(&self.working_dir, path)
+    }
+
+    pub fn lookup(
+        &self,
+        sock_type: UnixSocketType,
+        path: &Path,
+    ) -> Result<Arc<AtomicRefCell<UnixSocket>>, Errno> {
+        let entry = self
+            .address_map
+            .get(&self.resolve(path))
+            .ok_or(Errno::ENOENT)?;
+
+        if entry.socket_type != sock_type {
+            return Err(Errno::EPROTOTYPE);
+        }
+
+        // the name is still bound, but the socket no longer exists
+        entry.socket.upgrade().ok_or(Errno::ECONNREFUSED)
+    }
+
+    pub fn bind(
+        ns_arc: &Arc<AtomicRefCell<Self>>,
+        sock_type: UnixSocketType,
+        path: &Path,
+        socket: &Arc<AtomicRefCell<UnixSocket>>,
+    ) -> Result<(), Errno> {
+        let mut ns = ns_arc.borrow_mut();
+        let path = ns.resolve(path);
+
+        if ns.address_map.contains_key(&path) {
+            return Err(Errno::EADDRINUSE);
+        }
+
+        // the socket isn't created in the real filesystem, but as in linux the parent directory
+        // must exist and the path must not already be in use by a file
+        match path.parent().map(std::fs::metadata) {
+            Some(Ok(metadata)) if metadata.is_dir() => {}
+            Some(Ok(_)) => return Err(Errno::ENOTDIR),
+            Some(Err(_)) | None => return Err(Errno::ENOENT),
+        }
+        if std::fs::symlink_metadata(&path).is_ok() {
+            return Err(Errno::EADDRINUSE);
+        }
+
+        let placeholder = ns.create_placeholder(&path);
+
+        ns.address_map.insert(
+            path,
+            NamespaceEntry {
+                socket: Arc::downgrade(socket),
+                socket_type: sock_type,
+                placeholder,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Release the name so that it can be bound again. Sockets that are already bound or
+    /// connected are not affected.
+    pub fn unlink(&mut self, path: &Path) -> Result<(), BindError> {
+        // dropping the entry removes the placeholder file
+        match self.address_map.remove(&self.resolve(path)) {
+            Some(_) => Ok(()),
+            None => Err(BindError::NameNotFound),
+        }
+    }
+
+    /// Create an empty file in the host's data directory representing the bound socket, so that
+    /// users can see which socket paths are in use. Failure to create the file is not an error.
+    fn create_placeholder(&self, path: &Path) -> Option<PathBuf> {
+        let placeholder_dir = self.placeholder_dir.as_ref()?;
+
+        // the path is absolute, so strip the root before joining it to the placeholder directory
+        let placeholder = placeholder_dir.join(path.strip_prefix("/").unwrap());
+
+        let rv = placeholder
+            .parent()
+            .map(std::fs::create_dir_all)
+            .transpose()
+            .and_then(|_| std::fs::File::create(&placeholder));
+
+        match rv {
+            Ok(_) => Some(placeholder),
+            Err(e) => {
+                log::warn!(
+                    "Could not create unix socket placeholder file {:?}: {}",
+                    placeholder,
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Lexically normalize `path` to an absolute path, resolving it relative to `working_dir` if
+/// necessary. Symbolic links are not resolved since the socket doesn't exist in the real
+/// filesystem.
+fn normalize_path(working_dir: &Path, path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::from("/");
+
+    let components = if path.is_absolute() {
+        path.components().collect::<Vec<_>>()
+    } else {
+        working_dir.components().chain(path.components()).collect()
+    };
+
+    for component in components {
+        match component {
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+            Component::ParentDir => {
+                // the parent of "/" is "/"
+                normalized.pop();
+            }
+            Component::Normal(x) => normalized.push(x),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        let cwd = Path::new("/home/user/shadow.data/hosts/server");
+
+        assert_eq!(
+            normalize_path(cwd, Path::new("/tmp/app.sock")),
+            Path::new("/tmp/app.sock")
+        );
+        assert_eq!(
+            normalize_path(cwd, Path::new("app.sock")),
+            Path::new("/home/user/shadow.data/hosts/server/app.sock")
+        );
+        assert_eq!(
+            normalize_path(cwd, Path::new("./run/../app.sock")),
+            Path::new("/home/user/shadow.data/hosts/server/app.sock")
+        );
+        assert_eq!(
+            normalize_path(cwd, Path::new("/tmp//./app.sock")),
+            Path::new("/tmp/app.sock")
+        );
+        assert_eq!(
+            normalize_path(cwd, Path::new("/../../app.sock")),
+            Path::new("/app.sock")
+        );
+    }
+}
+
+mod export {
+    use super::*;
+
+    /// Create a new pathname unix socket namespace. Relative socket paths will be resolved against
+    /// `working_dir`, and placeholder files will be created within `data_dir` (if not NULL).
+    #[no_mangle]
+    pub unsafe extern "C" fn pathnameunixnamespace_new(
+        working_dir: *const libc::c_char,
+        data_dir: *const libc::c_char,
+    ) -> *mut Arc<AtomicRefCell<PathnameUnixNamespace>> {
+        assert!(!working_dir.is_null());
+        let working_dir = unsafe { std::ffi::CStr::from_ptr(working_dir) };
+        let working_dir = Path::new(working_dir.to_str().unwrap());
+        let working_dir = std::fs::canonicalize(working_dir).unwrap_or(working_dir.into());
+
+        let data_dir = (!data_dir.is_null()).then(|| unsafe { std::ffi::CStr::from_ptr(data_dir) });
+        let data_dir = data_dir.map(|x| Path::new(x.to_str().unwrap()));
+
+        Box::into_raw(Box::new(Arc::new(AtomicRefCell::new(
+            PathnameUnixNamespace::new(working_dir, data_dir),
+        ))))
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn pathnameunixnamespace_free(
+        ns: *mut Arc<AtomicRefCell<PathnameUnixNamespace>>,
+    ) {
+        assert!(!ns.is_null());
+        unsafe { Box::from_raw(ns) };
+    }
+}
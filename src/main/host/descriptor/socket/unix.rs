@@ -3,13 +3,17 @@ use std::sync::{Arc, Weak};
 
 use atomic_refcell::AtomicRefCell;
 use nix::errno::Errno;
+use nix::sys::socket::MsgFlags;
 
 use crate::cshadow as c;
 use crate::host::descriptor::shared_buf::{
     BufferHandle, BufferState, ReaderHandle, SharedBuf, WriterHandle,
 };
 use crate::host::descriptor::socket::abstract_unix_ns::AbstractUnixNamespace;
-use crate::host::descriptor::socket::{empty_sockaddr, Socket};
+use crate::host::descriptor::socket::pathname_unix_ns::PathnameUnixNamespace;
+use crate::host::descriptor::socket::{
+    empty_sockaddr, read_int_sockopt, write_int_sockopt, write_sockopt, AncillaryData, Socket,
+};
 use crate::host::descriptor::{
     File, FileMode, FileState, FileStatus, StateEventSource, StateListenerFilter, SyscallResult,
 };
@@ -18,11 +22,20 @@ use crate::host::syscall::Trigger;
 use crate::host::syscall_condition::SysCallCondition;
 use crate::host::syscall_types::{Blocked, PluginPtr, SysCallReg, SyscallError};
 use crate::utility::event_queue::{EventQueue, Handle};
+use crate::utility::pod;
 use crate::utility::stream_len::StreamLen;
 use crate::utility::HostTreePointer;
 
 const UNIX_SOCKET_DEFAULT_BUFFER_SIZE: u64 = 212_992;
 
+/// The maximum buffer size that can be set with `SO_SNDBUF` or `SO_RCVBUF` (linux's default
+/// 'wmem_max' and 'rmem_max').
+const UNIX_SOCKET_MAX_BUFFER_SIZE: u64 = 212_992;
+
+/// The minimum buffer sizes that can be set with `SO_SNDBUF` and `SO_RCVBUF`.
+const UNIX_SOCKET_MIN_SEND_BUFFER_SIZE: u64 = 4608;
+const UNIX_SOCKET_MIN_RECV_BUFFER_SIZE: u64 = 2304;
+
 /// A unix socket. The `UnixSocket` is the public-facing API, which forwards API calls to the inner
 /// state object.
 pub struct UnixSocket {
@@ -36,23 +49,37 @@ impl UnixSocket {
     pub fn new(
         status: FileStatus,
         socket_type: UnixSocketType,
+        creds: libc::ucred,
         namespace: &Arc<AtomicRefCell<AbstractUnixNamespace>>,
+        pathname_namespace: &Arc<AtomicRefCell<PathnameUnixNamespace>>,
     ) -> Arc<AtomicRefCell<Self>> {
         Arc::new_cyclic(|weak| {
-            // each socket tracks its own send limit, and we let the receiver have an unlimited recv
-            // buffer size
-            let recv_buffer = SharedBuf::new(usize::MAX);
+            // each socket tracks its own send limit, and the receive buffer is limited by the
+            // `SO_RCVBUF` option
+            let recv_buffer = SharedBuf::new(UNIX_SOCKET_DEFAULT_BUFFER_SIZE.try_into().unwrap());
             let recv_buffer = Arc::new(AtomicRefCell::new(recv_buffer));
 
             let mut common = UnixSocketCommon {
                 recv_buffer,
                 send_limit: UNIX_SOCKET_DEFAULT_BUFFER_SIZE,
                 sent_len: 0,
+                recv_buffer_size: UNIX_SOCKET_DEFAULT_BUFFER_SIZE,
+                waiting_for_space: false,
+                space_waiters: Vec::new(),
+                this_socket: weak.clone(),
+                creds,
+                peer_creds: None,
                 event_source: StateEventSource::new(),
                 state: FileState::ACTIVE,
                 status,
                 socket_type,
                 namespace: Arc::clone(namespace),
+                pathname_namespace: Arc::clone(pathname_namespace),
+                recv_ancillary: VecDeque::new(),
+                recv_written: 0,
+                recv_read: 0,
+                recv_head_creds: None,
+                pass_cred: false,
                 has_open_file: false,
             };
 
@@ -112,6 +139,14 @@ impl UnixSocket {
         nix::sys::socket::AddressFamily::Unix
     }
 
+    pub fn socket_type(&self) -> nix::sys::socket::SockType {
+        match self.common.socket_type {
+            UnixSocketType::Stream => nix::sys::socket::SockType::Stream,
+            UnixSocketType::Dgram => nix::sys::socket::SockType::Datagram,
+            UnixSocketType::SeqPacket => nix::sys::socket::SockType::SeqPacket,
+        }
+    }
+
     fn recv_buffer(&self) -> &Arc<AtomicRefCell<SharedBuf>> {
         &self.common.recv_buffer
     }
@@ -178,28 +213,52 @@ impl UnixSocket {
     pub fn sendto<R>(
         &mut self,
         bytes: R,
+        _flags: libc::c_int,
         addr: Option<nix::sys::socket::SockAddr>,
+        ancillary: AncillaryData,
         event_queue: &mut EventQueue,
     ) -> SyscallResult
     where
         R: std::io::Read + std::io::Seek,
     {
-        self.protocol_state
-            .sendto(&mut self.common, bytes, addr, event_queue)
+        // linux ignores `MSG_MORE` for unix sockets
+        let rv = self
+            .protocol_state
+            .sendto(&mut self.common, bytes, addr, ancillary, event_queue);
+
+        // the destination's receive buffer may have been full
+        if self.common.waiting_for_space {
+            self.refresh_file_state(event_queue);
+        }
+
+        rv
     }
 
+    /// Returns the number of bytes copied, the source address, and the number of bytes removed
+    /// from the receive buffer. For message-based sockets, the number of bytes removed is the
+    /// length of the message, which will be larger than the number of bytes copied if the message
+    /// was truncated. Any ancillary data sent with the data is also returned.
     // https://github.com/shadow/shadow/issues/2093
     #[allow(deprecated)]
     pub fn recvfrom<W>(
         &mut self,
         bytes: W,
+        flags: MsgFlags,
         event_queue: &mut EventQueue,
-    ) -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>), SyscallError>
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
     where
         W: std::io::Write + std::io::Seek,
     {
         self.protocol_state
-            .recvfrom(&mut self.common, bytes, event_queue)
+            .recvfrom(&mut self.common, bytes, flags, event_queue)
     }
 
     pub fn ioctl(
@@ -212,6 +271,76 @@ impl UnixSocket {
             .ioctl(&mut self.common, request, arg_ptr, memory_manager)
     }
 
+    /// Write the option value to `optval_ptr`, and return the option's length.
+    pub fn getsockopt(
+        &self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &mut MemoryManager,
+    ) -> Result<libc::socklen_t, SyscallError> {
+        // options that depend on the protocol state
+        match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_ACCEPTCONN) => {
+                let is_listening =
+                    matches!(self.protocol_state, ProtocolState::ConnOrientedListening(_));
+                write_int_sockopt(is_listening.into(), optval_ptr, optlen, memory_manager)
+            }
+            (libc::SOL_SOCKET, libc::SO_PEERCRED) => {
+                // connection-oriented sockets get the credentials of their peer when they connect,
+                // but connecting a dgram socket doesn't set any credentials
+                let creds = match &self.protocol_state {
+                    ProtocolState::ConnOrientedConnected(Some(state)) => {
+                        Some(state.peer.borrow().common.creds)
+                    }
+                    _ => self.common.peer_creds,
+                };
+
+                // linux returns these values if the socket has no peer credentials
+                let creds = creds.unwrap_or(libc::ucred {
+                    pid: 0,
+                    uid: libc::uid_t::MAX,
+                    gid: libc::gid_t::MAX,
+                });
+
+                write_sockopt(
+                    pod::to_u8_slice(std::slice::from_ref(&creds)),
+                    optval_ptr,
+                    optlen,
+                    memory_manager,
+                )
+            }
+            _ => self
+                .common
+                .getsockopt(level, optname, optval_ptr, optlen, memory_manager),
+        }
+    }
+
+    pub fn setsockopt(
+        &mut self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &MemoryManager,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        self.common.setsockopt(
+            level,
+            optname,
+            optval_ptr,
+            optlen,
+            memory_manager,
+            event_queue,
+        )?;
+
+        // the send buffer size may have changed
+        self.refresh_file_state(event_queue);
+
+        Ok(())
+    }
+
     pub fn listen(
         &mut self,
         backlog: i32,
@@ -221,6 +350,15 @@ impl UnixSocket {
             .listen(&mut self.common, backlog, event_queue)
     }
 
+    pub fn shutdown(
+        &mut self,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        self.protocol_state
+            .shutdown(&mut self.common, how, event_queue)
+    }
+
     // https://github.com/shadow/shadow/issues/2093
     #[allow(deprecated)]
     pub fn connect(
@@ -244,11 +382,17 @@ impl UnixSocket {
     pub fn pair(
         status: FileStatus,
         socket_type: UnixSocketType,
+        creds: libc::ucred,
         namespace: &Arc<AtomicRefCell<AbstractUnixNamespace>>,
+        pathname_namespace: &Arc<AtomicRefCell<PathnameUnixNamespace>>,
         event_queue: &mut EventQueue,
     ) -> (Arc<AtomicRefCell<Self>>, Arc<AtomicRefCell<Self>>) {
-        let socket_1 = UnixSocket::new(status, socket_type, namespace);
-        let socket_2 = UnixSocket::new(status, socket_type, namespace);
+        let socket_1 = UnixSocket::new(status, socket_type, creds, namespace, pathname_namespace);
+        let socket_2 = UnixSocket::new(status, socket_type, creds, namespace, pathname_namespace);
+
+        // unlike other connectionless sockets, a connectionless socket pair has peer credentials
+        socket_1.borrow_mut().common.peer_creds = Some(creds);
+        socket_2.borrow_mut().common.peer_creds = Some(creds);
 
         {
             let socket_1_ref = &mut *socket_1.borrow_mut();
@@ -290,6 +434,17 @@ impl UnixSocket {
             .add_listener(monitoring, filter, notify_fn)
     }
 
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        self.common
+            .event_source
+            .add_exclusive_listener(monitoring, filter, notify_fn)
+    }
+
     pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
         self.common.event_source.add_legacy_listener(ptr);
     }
@@ -315,8 +470,10 @@ struct ConnOrientedConnected {
     bound_addr: Option<nix::sys::socket::UnixAddr>,
     peer_addr: Option<nix::sys::socket::UnixAddr>,
     peer: Arc<AtomicRefCell<UnixSocket>>,
-    reader_handle: ReaderHandle,
-    writer_handle: WriterHandle,
+    /// The reader handle for the receive buffer, or `None` if reading has been shut down.
+    reader_handle: Option<ReaderHandle>,
+    /// The writer handle for the peer's receive buffer, or `None` if writing has been shut down.
+    writer_handle: Option<WriterHandle>,
     // these handles are never accessed, but we store them because of their drop impls
     _recv_buffer_handle: BufferHandle,
     _send_buffer_handle: BufferHandle,
@@ -330,6 +487,8 @@ struct ConnLessInitial {
     peer: Option<Arc<AtomicRefCell<UnixSocket>>>,
     recv_data: LinkedList<ByteData>,
     reader_handle: ReaderHandle,
+    /// Whether reading has been shut down.
+    read_shutdown: bool,
     // this handle is never accessed, but we store it because of its drop impl
     _recv_buffer_handle: BufferHandle,
 }
@@ -414,12 +573,22 @@ impl ProtocolState {
                     peer: None,
                     recv_data: LinkedList::new(),
                     reader_handle,
+                    read_shutdown: false,
                     _recv_buffer_handle: recv_buffer_handle,
                 }))
             }
         }
     }
 
+    /// The socket that this socket is connected to, if any.
+    fn peer(&self) -> Option<&Arc<AtomicRefCell<UnixSocket>>> {
+        match self {
+            Self::ConnOrientedConnected(x) => x.as_ref().map(|x| &x.peer),
+            Self::ConnLessInitial(x) => x.as_ref().and_then(|x| x.peer.as_ref()),
+            _ => None,
+        }
+    }
+
     fn peer_address(&self) -> Result<Option<nix::sys::socket::UnixAddr>, SyscallError> {
         match self {
             Self::ConnOrientedInitial(x) => x.as_ref().unwrap().peer_address(),
@@ -505,6 +674,7 @@ impl ProtocolState {
         common: &mut UnixSocketCommon,
         bytes: R,
         addr: Option<nix::sys::socket::SockAddr>,
+        ancillary: AncillaryData,
         event_queue: &mut EventQueue,
     ) -> SyscallResult
     where
@@ -512,21 +682,35 @@ impl ProtocolState {
     {
         match self {
             Self::ConnOrientedInitial(x) => {
-                x.as_mut().unwrap().sendto(common, bytes, addr, event_queue)
+                x.as_mut()
+                    .unwrap()
+                    .sendto(common, bytes, addr, ancillary, event_queue)
             }
             Self::ConnOrientedListening(x) => {
-                x.as_mut().unwrap().sendto(common, bytes, addr, event_queue)
+                x.as_mut()
+                    .unwrap()
+                    .sendto(common, bytes, addr, ancillary, event_queue)
             }
             Self::ConnOrientedConnected(x) => {
-                x.as_mut().unwrap().sendto(common, bytes, addr, event_queue)
+                x.as_mut()
+                    .unwrap()
+                    .sendto(common, bytes, addr, ancillary, event_queue)
             }
             Self::ConnOrientedClosed(x) => {
-                x.as_mut().unwrap().sendto(common, bytes, addr, event_queue)
+                x.as_mut()
+                    .unwrap()
+                    .sendto(common, bytes, addr, ancillary, event_queue)
             }
             Self::ConnLessInitial(x) => {
-                x.as_mut().unwrap().sendto(common, bytes, addr, event_queue)
+                x.as_mut()
+                    .unwrap()
+                    .sendto(common, bytes, addr, ancillary, event_queue)
+            }
+            Self::ConnLessClosed(x) => {
+                x.as_mut()
+                    .unwrap()
+                    .sendto(common, bytes, addr, ancillary, event_queue)
             }
-            Self::ConnLessClosed(x) => x.as_mut().unwrap().sendto(common, bytes, addr, event_queue),
         }
     }
 
@@ -536,24 +720,51 @@ impl ProtocolState {
         &mut self,
         common: &mut UnixSocketCommon,
         bytes: W,
+        flags: MsgFlags,
         event_queue: &mut EventQueue,
-    ) -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>), SyscallError>
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
     where
         W: std::io::Write + std::io::Seek,
     {
         match self {
             Self::ConnOrientedInitial(x) => {
-                x.as_mut().unwrap().recvfrom(common, bytes, event_queue)
+                x.as_mut()
+                    .unwrap()
+                    .recvfrom(common, bytes, flags, event_queue)
             }
             Self::ConnOrientedListening(x) => {
-                x.as_mut().unwrap().recvfrom(common, bytes, event_queue)
+                x.as_mut()
+                    .unwrap()
+                    .recvfrom(common, bytes, flags, event_queue)
             }
             Self::ConnOrientedConnected(x) => {
-                x.as_mut().unwrap().recvfrom(common, bytes, event_queue)
+                x.as_mut()
+                    .unwrap()
+                    .recvfrom(common, bytes, flags, event_queue)
+            }
+            Self::ConnOrientedClosed(x) => {
+                x.as_mut()
+                    .unwrap()
+                    .recvfrom(common, bytes, flags, event_queue)
+            }
+            Self::ConnLessInitial(x) => {
+                x.as_mut()
+                    .unwrap()
+                    .recvfrom(common, bytes, flags, event_queue)
+            }
+            Self::ConnLessClosed(x) => {
+                x.as_mut()
+                    .unwrap()
+                    .recvfrom(common, bytes, flags, event_queue)
             }
-            Self::ConnOrientedClosed(x) => x.as_mut().unwrap().recvfrom(common, bytes, event_queue),
-            Self::ConnLessInitial(x) => x.as_mut().unwrap().recvfrom(common, bytes, event_queue),
-            Self::ConnLessClosed(x) => x.as_mut().unwrap().recvfrom(common, bytes, event_queue),
         }
     }
 
@@ -661,6 +872,26 @@ impl ProtocolState {
         rv
     }
 
+    fn shutdown(
+        &mut self,
+        common: &mut UnixSocketCommon,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        match self {
+            Self::ConnOrientedInitial(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+            Self::ConnOrientedListening(x) => {
+                x.as_mut().unwrap().shutdown(common, how, event_queue)
+            }
+            Self::ConnOrientedConnected(x) => {
+                x.as_mut().unwrap().shutdown(common, how, event_queue)
+            }
+            Self::ConnOrientedClosed(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+            Self::ConnLessInitial(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+            Self::ConnLessClosed(x) => x.as_mut().unwrap().shutdown(common, how, event_queue),
+        }
+    }
+
     // https://github.com/shadow/shadow/issues/2093
     #[allow(deprecated)]
     fn connect(
@@ -848,6 +1079,7 @@ where
         _common: &mut UnixSocketCommon,
         _bytes: R,
         _addr: Option<nix::sys::socket::SockAddr>,
+        _ancillary: AncillaryData,
         _event_queue: &mut EventQueue,
     ) -> SyscallResult
     where
@@ -863,8 +1095,17 @@ where
         &mut self,
         _common: &mut UnixSocketCommon,
         _bytes: W,
+        _flags: MsgFlags,
         _event_queue: &mut EventQueue,
-    ) -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>), SyscallError>
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
     where
         W: std::io::Write + std::io::Seek,
     {
@@ -908,6 +1149,19 @@ where
         (self.into(), Err(Errno::EOPNOTSUPP.into()))
     }
 
+    fn shutdown(
+        &mut self,
+        _common: &mut UnixSocketCommon,
+        _how: nix::sys::socket::Shutdown,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        log::warn!(
+            "shutdown() while in state {}",
+            std::any::type_name::<Self>()
+        );
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
     // https://github.com/shadow/shadow/issues/2093
     #[allow(deprecated)]
     fn connect(
@@ -1012,6 +1266,7 @@ impl Protocol for ConnOrientedInitial {
         common: &mut UnixSocketCommon,
         _bytes: R,
         addr: Option<nix::sys::socket::SockAddr>,
+        _ancillary: AncillaryData,
         _event_queue: &mut EventQueue,
     ) -> SyscallResult
     where
@@ -1034,8 +1289,17 @@ impl Protocol for ConnOrientedInitial {
         &mut self,
         common: &mut UnixSocketCommon,
         _bytes: W,
+        _flags: MsgFlags,
         _event_queue: &mut EventQueue,
-    ) -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>), SyscallError>
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
     where
         W: std::io::Write + std::io::Seek,
     {
@@ -1098,7 +1362,12 @@ impl Protocol for ConnOrientedInitial {
         };
 
         // look up the server socket
-        let server = match lookup_address(&common.namespace.borrow(), common.socket_type, addr) {
+        let server = match lookup_address(
+            &common.namespace.borrow(),
+            &common.pathname_namespace.borrow(),
+            common.socket_type,
+            addr,
+        ) {
             Ok(x) => x,
             Err(e) => return (self.into(), Err(e.into())),
         };
@@ -1172,8 +1441,8 @@ impl Protocol for ConnOrientedInitial {
             bound_addr: self.bound_addr,
             peer_addr: Some(addr.clone()),
             peer: Arc::clone(peer),
-            reader_handle,
-            writer_handle,
+            reader_handle: Some(reader_handle),
+            writer_handle: Some(writer_handle),
             _recv_buffer_handle: recv_buffer_handle,
             _send_buffer_handle: send_buffer_handle,
         };
@@ -1230,8 +1499,8 @@ impl Protocol for ConnOrientedInitial {
             bound_addr: None,
             peer_addr: None,
             peer,
-            reader_handle,
-            writer_handle,
+            reader_handle: Some(reader_handle),
+            writer_handle: Some(writer_handle),
             _recv_buffer_handle: recv_buffer_handle,
             _send_buffer_handle: send_buffer_handle,
         };
@@ -1249,6 +1518,17 @@ impl Protocol for ConnOrientedInitial {
         log::warn!("accept() while in state {}", std::any::type_name::<Self>());
         Err(Errno::EINVAL.into())
     }
+
+    fn shutdown(
+        &mut self,
+        _common: &mut UnixSocketCommon,
+        _how: nix::sys::socket::Shutdown,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        // linux allows shutting down an unconnected unix socket, and since there's no peer there's
+        // nothing to do
+        Ok(())
+    }
 }
 
 impl Protocol for ConnOrientedListening {
@@ -1343,7 +1623,10 @@ impl Protocol for ConnOrientedListening {
             // copy the parent's status
             common.status,
             common.socket_type,
+            // the connecting peer will see the credentials of the listening socket
+            common.creds,
             &common.namespace,
+            &common.pathname_namespace,
         );
 
         let child_recv_buffer = Arc::clone(&child_socket.borrow_mut().common.recv_buffer);
@@ -1379,8 +1662,8 @@ impl Protocol for ConnOrientedListening {
             bound_addr: Some(self.bound_addr.clone()),
             peer_addr: from_address,
             peer: Arc::clone(peer),
-            reader_handle,
-            writer_handle,
+            reader_handle: Some(reader_handle),
+            writer_handle: Some(writer_handle),
             _recv_buffer_handle: recv_buffer_handle,
             _send_buffer_handle: send_buffer_handle,
         };
@@ -1405,6 +1688,17 @@ impl Protocol for ConnOrientedListening {
         // return a reference to the enqueued child socket
         Ok(self.queue.back().unwrap())
     }
+
+    fn shutdown(
+        &mut self,
+        _common: &mut UnixSocketCommon,
+        _how: nix::sys::socket::Shutdown,
+        _event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        // linux allows shutting down an unconnected unix socket, and since there's no peer there's
+        // nothing to do
+        Ok(())
+    }
 }
 
 impl Protocol for ConnOrientedConnected {
@@ -1428,13 +1722,19 @@ impl Protocol for ConnOrientedConnected {
             let peer = self.peer.borrow();
             let send_buffer = peer.recv_buffer().borrow();
 
+            // if reading or writing has been shut down, reads will return EOF and writes will
+            // return EPIPE without blocking
             new_state.set(
                 FileState::READABLE,
-                recv_buffer.has_data() || recv_buffer.num_writers() == 0,
+                recv_buffer.has_data()
+                    || recv_buffer.num_writers() == 0
+                    || self.reader_handle.is_none(),
             );
             new_state.set(
                 FileState::WRITABLE,
-                common.sent_len < common.send_limit || send_buffer.num_readers() == 0,
+                (common.sent_len < common.send_limit && !common.waiting_for_space)
+                    || send_buffer.num_readers() == 0
+                    || self.writer_handle.is_none(),
             );
         }
 
@@ -1446,18 +1746,22 @@ impl Protocol for ConnOrientedConnected {
         common: &mut UnixSocketCommon,
         event_queue: &mut EventQueue,
     ) -> (ProtocolState, Result<(), SyscallError>) {
-        // inform the buffer that there is one fewer readers
-        common
-            .recv_buffer
-            .borrow_mut()
-            .remove_reader(self.reader_handle, event_queue);
+        // inform the buffer that there is one fewer readers (if not already shut down)
+        if let Some(handle) = self.reader_handle {
+            common
+                .recv_buffer
+                .borrow_mut()
+                .remove_reader(handle, event_queue);
+        }
 
-        // inform the buffer that there is one fewer writers
-        self.peer
-            .borrow()
-            .recv_buffer()
-            .borrow_mut()
-            .remove_writer(self.writer_handle, event_queue);
+        // inform the buffer that there is one fewer writers (if not already shut down)
+        if let Some(handle) = self.writer_handle {
+            self.peer
+                .borrow()
+                .recv_buffer()
+                .borrow_mut()
+                .remove_writer(handle, event_queue);
+        }
 
         let new_state = ConnOrientedClosed {};
         new_state.refresh_file_state(common, event_queue);
@@ -1471,13 +1775,20 @@ impl Protocol for ConnOrientedConnected {
         common: &mut UnixSocketCommon,
         bytes: R,
         addr: Option<nix::sys::socket::SockAddr>,
+        ancillary: AncillaryData,
         event_queue: &mut EventQueue,
     ) -> SyscallResult
     where
         R: std::io::Read + std::io::Seek,
     {
         let recv_socket = common.resolve_destination(Some(&self.peer), addr)?;
-        let rv = common.sendto(bytes, &recv_socket, event_queue)?;
+
+        // writing has been shut down
+        if self.writer_handle.is_none() {
+            return Err(Errno::EPIPE.into());
+        }
+
+        let rv = common.sendto(bytes, &recv_socket, ancillary, event_queue)?;
 
         self.refresh_file_state(common, event_queue);
 
@@ -1490,15 +1801,37 @@ impl Protocol for ConnOrientedConnected {
         &mut self,
         common: &mut UnixSocketCommon,
         bytes: W,
+        flags: MsgFlags,
         event_queue: &mut EventQueue,
-    ) -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>), SyscallError>
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
     where
         W: std::io::Write + std::io::Seek,
     {
-        let (num_copied, num_removed_from_buf) = common.recvfrom(bytes, event_queue)?;
+        // if reading has been shut down, return EOF once there is no remaining data
+        if self.reader_handle.is_none() && !common.recv_buffer.borrow().has_data() {
+            return Ok((
+                0.into(),
+                self.peer_addr.map(nix::sys::socket::SockAddr::Unix),
+                0,
+                AncillaryData::default(),
+            ));
+        }
+
+        let (num_copied, num_removed_from_buf, ancillary) =
+            common.recvfrom(bytes, flags, event_queue)?;
+        let msg_len = num_removed_from_buf;
         let num_removed_from_buf = u64::try_from(num_removed_from_buf).unwrap();
 
-        if num_removed_from_buf > 0 {
+        // peeked data remains in the buffer
+        if num_removed_from_buf > 0 && !flags.contains(MsgFlags::MSG_PEEK) {
             // defer informing the peer until we're done processing the current socket
             let peer = Arc::clone(&self.peer);
             event_queue.add(move |event_queue| {
@@ -1512,6 +1845,8 @@ impl Protocol for ConnOrientedConnected {
         Ok((
             num_copied.into(),
             self.peer_addr.map(nix::sys::socket::SockAddr::Unix),
+            msg_len,
+            ancillary,
         ))
     }
 
@@ -1543,6 +1878,42 @@ impl Protocol for ConnOrientedConnected {
         log::warn!("accept() while in state {}", std::any::type_name::<Self>());
         Err(Errno::EINVAL.into())
     }
+
+    fn shutdown(
+        &mut self,
+        common: &mut UnixSocketCommon,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        use nix::sys::socket::Shutdown;
+
+        if matches!(how, Shutdown::Read | Shutdown::Both) {
+            // inform the buffer that there is one fewer readers; the peer will no longer be able
+            // to send to this socket
+            if let Some(handle) = self.reader_handle.take() {
+                common
+                    .recv_buffer
+                    .borrow_mut()
+                    .remove_reader(handle, event_queue);
+            }
+        }
+
+        if matches!(how, Shutdown::Write | Shutdown::Both) {
+            // inform the buffer that there is one fewer writers; the peer will read an EOF after
+            // reading any remaining data
+            if let Some(handle) = self.writer_handle.take() {
+                self.peer
+                    .borrow()
+                    .recv_buffer()
+                    .borrow_mut()
+                    .remove_writer(handle, event_queue);
+            }
+        }
+
+        self.refresh_file_state(common, event_queue);
+
+        Ok(())
+    }
 }
 
 impl Protocol for ConnOrientedClosed {
@@ -1607,8 +1978,15 @@ impl Protocol for ConnLessInitial {
         {
             let recv_buffer = common.recv_buffer.borrow();
 
-            new_state.set(FileState::READABLE, recv_buffer.has_data());
-            new_state.set(FileState::WRITABLE, common.sent_len < common.send_limit);
+            // if reading has been shut down, reads will return EOF without blocking
+            new_state.set(
+                FileState::READABLE,
+                recv_buffer.has_data() || self.read_shutdown,
+            );
+            new_state.set(
+                FileState::WRITABLE,
+                common.sent_len < common.send_limit && !common.waiting_for_space,
+            );
         }
 
         common.copy_state(/* mask= */ FileState::all(), new_state, event_queue);
@@ -1665,13 +2043,22 @@ impl Protocol for ConnLessInitial {
         common: &mut UnixSocketCommon,
         bytes: R,
         addr: Option<nix::sys::socket::SockAddr>,
+        ancillary: AncillaryData,
         event_queue: &mut EventQueue,
     ) -> SyscallResult
     where
         R: std::io::Read + std::io::Seek,
     {
         let recv_socket = common.resolve_destination(self.peer.as_ref(), addr)?;
-        let rv = common.sendto(bytes, &recv_socket, event_queue)?;
+
+        // the destination socket has shut down reading
+        if let ProtocolState::ConnLessInitial(Some(state)) = &recv_socket.borrow().protocol_state {
+            if state.read_shutdown {
+                return Err(Errno::EPIPE.into());
+            }
+        }
+
+        let rv = common.sendto(bytes, &recv_socket, ancillary, event_queue)?;
 
         let byte_data = ByteData {
             from_socket: self.this_socket.upgrade().unwrap(),
@@ -1700,14 +2087,48 @@ impl Protocol for ConnLessInitial {
         &mut self,
         common: &mut UnixSocketCommon,
         bytes: W,
+        flags: MsgFlags,
         event_queue: &mut EventQueue,
-    ) -> Result<(SysCallReg, Option<nix::sys::socket::SockAddr>), SyscallError>
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    >
     where
         W: std::io::Write + std::io::Seek,
     {
-        let (num_copied, num_removed_from_buf) = common.recvfrom(bytes, event_queue)?;
+        // if reading has been shut down, linux returns EOF once there is no remaining data, but
+        // only for blocking reads
+        if self.read_shutdown
+            && !common.recv_buffer.borrow().has_data()
+            && !common.status.contains(FileStatus::NONBLOCK)
+            && !flags.contains(MsgFlags::MSG_DONTWAIT)
+        {
+            return Ok((0.into(), None, 0, AncillaryData::default()));
+        }
+
+        let (num_copied, num_removed_from_buf, ancillary) =
+            common.recvfrom(bytes, flags, event_queue)?;
+        let msg_len = num_removed_from_buf;
         let num_removed_from_buf = u64::try_from(num_removed_from_buf).unwrap();
 
+        // peeked messages remain in the buffer
+        if flags.contains(MsgFlags::MSG_PEEK) {
+            let byte_data = self.recv_data.front().unwrap();
+            assert!(num_removed_from_buf == byte_data.num_bytes);
+
+            return Ok((
+                num_copied.into(),
+                byte_data.from_addr.map(nix::sys::socket::SockAddr::Unix),
+                msg_len,
+                ancillary,
+            ));
+        }
+
         let byte_data = self.recv_data.pop_front().unwrap();
         assert!(num_removed_from_buf == byte_data.num_bytes);
 
@@ -1724,6 +2145,8 @@ impl Protocol for ConnLessInitial {
         Ok((
             num_copied.into(),
             byte_data.from_addr.map(nix::sys::socket::SockAddr::Unix),
+            msg_len,
+            ancillary,
         ))
     }
 
@@ -1763,7 +2186,12 @@ impl Protocol for ConnLessInitial {
         };
 
         // find the socket bound at the address
-        let peer = match lookup_address(&common.namespace.borrow(), common.socket_type, addr) {
+        let peer = match lookup_address(
+            &common.namespace.borrow(),
+            &common.pathname_namespace.borrow(),
+            common.socket_type,
+            addr,
+        ) {
             Ok(x) => x,
             Err(e) => return (self.into(), Err(e.into())),
         };
@@ -1800,6 +2228,24 @@ impl Protocol for ConnLessInitial {
 
         (new_state.into(), Ok(()))
     }
+
+    fn shutdown(
+        &mut self,
+        common: &mut UnixSocketCommon,
+        how: nix::sys::socket::Shutdown,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        use nix::sys::socket::Shutdown;
+
+        // like linux, shutting down writing has no effect for dgram sockets
+        if matches!(how, Shutdown::Read | Shutdown::Both) {
+            self.read_shutdown = true;
+        }
+
+        self.refresh_file_state(common, event_queue);
+
+        Ok(())
+    }
 }
 
 impl Protocol for ConnLessClosed {
@@ -1849,11 +2295,42 @@ struct UnixSocketCommon {
     send_limit: u64,
     /// The number of "in flight" bytes.
     sent_len: u64,
+    /// The value of the `SO_RCVBUF` option, which limits the size of the receive buffer.
+    recv_buffer_size: u64,
+    /// Whether the last send failed because the destination's receive buffer was full. The socket
+    /// isn't writable until the destination reads some data.
+    waiting_for_space: bool,
+    /// Sockets that failed to send because this socket's receive buffer was full, and that should
+    /// be informed when data is read from the buffer.
+    space_waiters: Vec<Weak<AtomicRefCell<UnixSocket>>>,
+    this_socket: Weak<AtomicRefCell<UnixSocket>>,
+    /// The credentials of the process that created the socket, which are returned to the peer
+    /// socket for `SO_PEERCRED`.
+    creds: libc::ucred,
+    /// The credentials returned for `SO_PEERCRED` if this is a connectionless socket created by
+    /// `socketpair()`. As in linux, other connectionless sockets have no peer credentials.
+    peer_creds: Option<libc::ucred>,
     event_source: StateEventSource,
     state: FileState,
     status: FileStatus,
     socket_type: UnixSocketType,
     namespace: Arc<AtomicRefCell<AbstractUnixNamespace>>,
+    pathname_namespace: Arc<AtomicRefCell<PathnameUnixNamespace>>,
+    /// Ancillary data attached to data in the receive buffer, and the position of the data that
+    /// it's attached to. For stream sockets the position is a byte offset, and for message-based
+    /// sockets it's a message index. Files sent to this socket but never received will not be
+    /// closed until this socket is dropped, and (unlike Linux) reference cycles are not collected.
+    recv_ancillary: VecDeque<(u64, AncillaryData)>,
+    /// The number of bytes (stream sockets) or messages (message-based sockets) that have ever
+    /// been written to the receive buffer.
+    recv_written: u64,
+    /// The number of bytes (stream sockets) or messages (message-based sockets) that have ever
+    /// been read from the receive buffer.
+    recv_read: u64,
+    /// The credentials attached to the data at the head of a stream socket's receive buffer.
+    recv_head_creds: Option<libc::ucred>,
+    /// Whether the `SO_PASSCRED` option is set.
+    pass_cred: bool,
     // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
     // this file
     has_open_file: bool,
@@ -1861,6 +2338,9 @@ struct UnixSocketCommon {
 
 impl UnixSocketCommon {
     pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        // any sockets waiting to send to us should try again (and fail)
+        self.inform_space_waiters(event_queue);
+
         // check that the CLOSED flag was set by the protocol state
         if !self.state.contains(FileState::CLOSED) {
             // set the flag here since we missed doing it before
@@ -1926,9 +2406,16 @@ impl UnixSocketCommon {
                 Ok(ref name) => nix::sys::socket::UnixAddr::new_abstract(name).unwrap(),
                 Err(_) => return Err(Errno::EADDRINUSE.into()),
             }
+        } else if let Some(path) = addr.path() {
+            // if given a pathname address
+            let namespace = Arc::clone(&self.pathname_namespace);
+            match PathnameUnixNamespace::bind(&namespace, self.socket_type, path, socket) {
+                Ok(()) => *addr,
+                Err(e) => return Err(e.into()),
+            }
         } else {
-            log::warn!("Only abstract names are currently supported for unix sockets");
-            return Err(Errno::ENOTSUP.into());
+            log::warn!("Unrecognized unix socket address {:?}", addr);
+            return Err(Errno::EINVAL.into());
         };
 
         Ok(bound_addr)
@@ -1974,8 +2461,12 @@ impl UnixSocketCommon {
             Some(ref x) => Arc::clone(x),
             None => {
                 // look up the socket from the address name
-                let recv_socket =
-                    lookup_address(&self.namespace.borrow(), self.socket_type, &addr.unwrap())?;
+                let recv_socket = lookup_address(
+                    &self.namespace.borrow(),
+                    &self.pathname_namespace.borrow(),
+                    self.socket_type,
+                    &addr.unwrap(),
+                )?;
                 // store an Arc of the recv buffer
                 Arc::clone(&recv_socket)
             }
@@ -1990,13 +2481,15 @@ impl UnixSocketCommon {
         &mut self,
         mut bytes: R,
         peer: &Arc<AtomicRefCell<UnixSocket>>,
+        ancillary: AncillaryData,
         event_queue: &mut EventQueue,
     ) -> Result<usize, SyscallError>
     where
         R: std::io::Read + std::io::Seek,
     {
-        let peer_ref = peer.borrow();
-        let mut send_buffer = peer_ref.recv_buffer().borrow_mut();
+        let mut peer_ref = peer.borrow_mut();
+        let send_buffer = Arc::clone(peer_ref.recv_buffer());
+        let mut send_buffer = send_buffer.borrow_mut();
 
         // if the buffer has no readers, the destination socket is closed
         if send_buffer.num_readers() == 0 {
@@ -2038,6 +2531,25 @@ impl UnixSocketCommon {
             }
         };
 
+        // the destination's receive buffer is limited by its `SO_RCVBUF` option
+        let fits_in_buffer = match self.socket_type {
+            UnixSocketType::Stream => len == 0 || send_buffer.space_available() > 0,
+            UnixSocketType::Dgram | UnixSocketType::SeqPacket => {
+                if len > send_buffer.max_len() {
+                    // the destination could never receive this message
+                    return Err(Errno::EMSGSIZE.into());
+                }
+                len <= send_buffer.space_available()
+            }
+        };
+
+        if !fits_in_buffer {
+            // we won't be writable until the destination has read some data
+            self.waiting_for_space = true;
+            peer_ref.common.space_waiters.push(self.this_socket.clone());
+            return Err(Errno::EAGAIN.into());
+        }
+
         let bytes = bytes.take(len.try_into().unwrap());
 
         let num_copied = match self.socket_type {
@@ -2057,6 +2569,8 @@ impl UnixSocketCommon {
         // if we successfully sent bytes, update the sent count
         self.sent_len += u64::try_from(num_copied).unwrap();
 
+        peer_ref.common.push_recv_ancillary(num_copied, ancillary);
+
         Ok(num_copied)
     }
 
@@ -2065,8 +2579,9 @@ impl UnixSocketCommon {
     pub fn recvfrom<W>(
         &mut self,
         mut bytes: W,
+        flags: MsgFlags,
         event_queue: &mut EventQueue,
-    ) -> Result<(usize, usize), SyscallError>
+    ) -> Result<(usize, usize, AncillaryData), SyscallError>
     where
         W: std::io::Write + std::io::Seek,
     {
@@ -2083,9 +2598,232 @@ impl UnixSocketCommon {
             return Err(Errno::EWOULDBLOCK.into());
         }
 
-        let (num_copied, num_removed_from_buf) = recv_buffer.read(&mut bytes, event_queue)?;
+        let has_data = recv_buffer.has_data();
+
+        // the credentials and whether there are files attached to the data at the head of the
+        // buffer, if the data has ancillary data that hasn't been received yet
+        let head_ancillary = match self.recv_ancillary.front() {
+            Some((position, x)) if *position == self.recv_read => {
+                Some((x.creds, !x.rights.is_empty()))
+            }
+            _ => None,
+        };
+
+        let peek = flags.contains(MsgFlags::MSG_PEEK);
+
+        let (num_copied, num_removed_from_buf) = match self.socket_type {
+            UnixSocketType::Stream => {
+                let (head_creds, head_has_rights) =
+                    head_ancillary.unwrap_or((self.recv_head_creds, false));
+
+                // as in linux, don't read past the end of data that has files attached, or past
+                // the start of data that has files attached or was sent with different credentials
+                let limit = self
+                    .recv_ancillary
+                    .iter()
+                    .skip(head_ancillary.is_some().into())
+                    .find(|(_, x)| head_has_rights || !x.rights.is_empty() || x.creds != head_creds)
+                    .map(|(position, _)| usize::try_from(position - self.recv_read).unwrap())
+                    .unwrap_or(usize::MAX);
+
+                let writer = LimitWriter::new(&mut bytes, limit);
+                if peek {
+                    recv_buffer.peek(writer)?
+                } else {
+                    recv_buffer.read(writer, event_queue)?
+                }
+            }
+            UnixSocketType::Dgram | UnixSocketType::SeqPacket => {
+                if peek {
+                    recv_buffer.peek(&mut bytes)?
+                } else {
+                    recv_buffer.read(&mut bytes, event_queue)?
+                }
+            }
+        };
+        drop(recv_buffer);
 
-        Ok((num_copied, num_removed_from_buf))
+        if !peek && num_removed_from_buf > 0 {
+            self.inform_space_waiters(event_queue);
+        }
+
+        let ancillary = match self.socket_type {
+            // peeking doesn't consume the ancillary data (any files are duplicated)
+            UnixSocketType::Stream if peek && num_copied > 0 => match head_ancillary {
+                Some(_) => self.recv_ancillary.front().unwrap().1.clone(),
+                None => AncillaryData {
+                    rights: Vec::new(),
+                    creds: self.recv_head_creds,
+                },
+            },
+            UnixSocketType::Dgram | UnixSocketType::SeqPacket if peek && has_data => {
+                self.recv_ancillary.front().unwrap().1.clone()
+            }
+            UnixSocketType::Stream if num_copied > 0 => {
+                let ancillary = match head_ancillary {
+                    Some(_) => self.recv_ancillary.pop_front().unwrap().1,
+                    None => AncillaryData {
+                        rights: Vec::new(),
+                        creds: self.recv_head_creds,
+                    },
+                };
+                self.recv_head_creds = ancillary.creds;
+                self.recv_read += u64::try_from(num_copied).unwrap();
+
+                // any remaining ancillary data for the data we read has no files and the same
+                // credentials, so can be discarded
+                while let Some((position, _)) = self.recv_ancillary.front() {
+                    if *position >= self.recv_read {
+                        break;
+                    }
+                    self.recv_ancillary.pop_front();
+                }
+
+                ancillary
+            }
+            UnixSocketType::Dgram | UnixSocketType::SeqPacket if has_data => {
+                // every message has its own ancillary data
+                let (position, ancillary) = self.recv_ancillary.pop_front().unwrap();
+                assert_eq!(position, self.recv_read);
+                self.recv_read += 1;
+                ancillary
+            }
+            _ => AncillaryData::default(),
+        };
+
+        // only return credentials if requested
+        let ancillary = AncillaryData {
+            rights: ancillary.rights,
+            creds: ancillary.creds.filter(|_| self.pass_cred),
+        };
+
+        Ok((num_copied, num_removed_from_buf, ancillary))
+    }
+
+    /// Inform any sockets that were waiting for space in the receive buffer that they can try
+    /// sending again.
+    fn inform_space_waiters(&mut self, event_queue: &mut EventQueue) {
+        for waiter in self.space_waiters.drain(..) {
+            // defer informing the sender until we're done processing the current socket
+            event_queue.add(move |event_queue| {
+                if let Some(socket) = waiter.upgrade() {
+                    let mut socket = socket.borrow_mut();
+                    socket.common.waiting_for_space = false;
+                    socket.refresh_file_state(event_queue);
+                }
+            });
+        }
+    }
+
+    /// Attach ancillary data to data that was just written to the receive buffer.
+    fn push_recv_ancillary(&mut self, num_written: usize, ancillary: AncillaryData) {
+        let position = self.recv_written;
+
+        match self.socket_type {
+            UnixSocketType::Stream => {
+                // linux drops the ancillary data if there are no bytes to attach it to
+                if num_written == 0 {
+                    return;
+                }
+
+                self.recv_written += u64::try_from(num_written).unwrap();
+
+                // consecutive data with the same credentials and without files can be merged
+                if let Some((_, last)) = self.recv_ancillary.back() {
+                    if ancillary.rights.is_empty()
+                        && last.rights.is_empty()
+                        && ancillary.creds == last.creds
+                    {
+                        return;
+                    }
+                }
+            }
+            UnixSocketType::Dgram | UnixSocketType::SeqPacket => self.recv_written += 1,
+        }
+
+        self.recv_ancillary.push_back((position, ancillary));
+    }
+
+    pub fn getsockopt(
+        &self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &mut MemoryManager,
+    ) -> Result<libc::socklen_t, SyscallError> {
+        let optval: libc::c_int = match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_TYPE) => match self.socket_type {
+                UnixSocketType::Stream => libc::SOCK_STREAM,
+                UnixSocketType::Dgram => libc::SOCK_DGRAM,
+                UnixSocketType::SeqPacket => libc::SOCK_SEQPACKET,
+            },
+            (libc::SOL_SOCKET, libc::SO_DOMAIN) => libc::AF_UNIX,
+            (libc::SOL_SOCKET, libc::SO_PROTOCOL) => 0,
+            // unix sockets never have asynchronous errors
+            (libc::SOL_SOCKET, libc::SO_ERROR) => 0,
+            (libc::SOL_SOCKET, libc::SO_SNDBUF) => self.send_limit.try_into().unwrap(),
+            (libc::SOL_SOCKET, libc::SO_RCVBUF) => self.recv_buffer_size.try_into().unwrap(),
+            (libc::SOL_SOCKET, libc::SO_PASSCRED) => self.pass_cred.into(),
+            _ => {
+                log::warn!(
+                    "getsockopt() for level {} and option {} is not supported for unix sockets",
+                    level,
+                    optname
+                );
+                return Err(Errno::ENOPROTOOPT.into());
+            }
+        };
+
+        write_int_sockopt(optval, optval_ptr, optlen, memory_manager)
+    }
+
+    pub fn setsockopt(
+        &mut self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: PluginPtr,
+        optlen: libc::socklen_t,
+        memory_manager: &MemoryManager,
+        event_queue: &mut EventQueue,
+    ) -> Result<(), SyscallError> {
+        match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_PASSCRED) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.pass_cred = optval != 0;
+            }
+            (libc::SOL_SOCKET, libc::SO_SNDBUF) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.send_limit = sockopt_buffer_size(optval, UNIX_SOCKET_MIN_SEND_BUFFER_SIZE);
+            }
+            (libc::SOL_SOCKET, libc::SO_RCVBUF) => {
+                let optval = read_int_sockopt(optval_ptr, optlen, memory_manager)?;
+                self.recv_buffer_size =
+                    sockopt_buffer_size(optval, UNIX_SOCKET_MIN_RECV_BUFFER_SIZE);
+
+                // like linux, shrinking the buffer doesn't discard data that's already buffered
+                let mut recv_buffer = self.recv_buffer.borrow_mut();
+                let max_len = std::cmp::max(
+                    usize::try_from(self.recv_buffer_size).unwrap(),
+                    recv_buffer.num_bytes(),
+                );
+                recv_buffer.set_max_len(max_len, event_queue).unwrap();
+                drop(recv_buffer);
+
+                // the buffer may have more space available
+                self.inform_space_waiters(event_queue);
+            }
+            _ => {
+                log::warn!(
+                    "setsockopt() for level {} and option {} is not supported for unix sockets",
+                    level,
+                    optname
+                );
+                return Err(Errno::ENOPROTOOPT.into());
+            }
+        }
+
+        Ok(())
     }
 
     pub fn ioctl(
@@ -2126,6 +2864,7 @@ impl UnixSocketCommon {
 
 fn lookup_address(
     namespace: &AbstractUnixNamespace,
+    pathname_namespace: &PathnameUnixNamespace,
     socket_type: UnixSocketType,
     addr: &nix::sys::socket::UnixAddr,
 ) -> Result<Arc<AtomicRefCell<UnixSocket>>, nix::errno::Errno> {
@@ -2135,9 +2874,11 @@ fn lookup_address(
         namespace
             .lookup(socket_type, name)
             .ok_or(nix::errno::Errno::ECONNREFUSED)
+    } else if let Some(path) = addr.path() {
+        // look up the socket from the filesystem path
+        pathname_namespace.lookup(socket_type, path)
     } else {
-        log::warn!("Unix sockets with pathname addresses are not yet supported");
-        Err(nix::errno::Errno::ENOENT)
+        Err(nix::errno::Errno::EINVAL)
     }
 }
 
@@ -2209,3 +2950,40 @@ struct ByteData {
     from_addr: Option<nix::sys::socket::UnixAddr>,
     num_bytes: u64,
 }
+
+/// A writer that will write at most `limit` bytes to the inner writer.
+struct LimitWriter<W> {
+    inner: W,
+    limit: usize,
+}
+
+impl<W> LimitWriter<W> {
+    fn new(inner: W, limit: usize) -> Self {
+        Self { inner, limit }
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for LimitWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = std::cmp::min(buf.len(), self.limit);
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let written = self.inner.write(&buf[..len])?;
+        self.limit -= written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Get the buffer size for a `SO_SNDBUF` or `SO_RCVBUF` option value. Like Linux, the value is
+/// capped to a maximum and then doubled to allow space for bookkeeping overhead.
+fn sockopt_buffer_size(optval: libc::c_int, min: u64) -> u64 {
+    // linux interprets the value as unsigned, so negative values become very large
+    let optval = u64::from(optval as u32);
+    std::cmp::max(std::cmp::min(optval, UNIX_SOCKET_MAX_BUFFER_SIZE) * 2, min)
+}
@@ -20,12 +20,11 @@
 #include <sys/types.h>
 
 #include "lib/logger/logger.h"
+#include "main/bindings/c/bindings.h"
 #include "main/core/support/definitions.h"
 #include "main/core/worker.h"
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/descriptor/socket.h"
-#include "main/host/descriptor/tcp_cong.h"
-#include "main/host/descriptor/tcp_cong_reno.h"
 #include "main/host/descriptor/tcp_retransmit_tally.h"
 #include "main/host/descriptor/transport.h"
 #include "main/host/host.h"
@@ -91,16 +90,13 @@ struct _TCPChild {
     enum TCPChildState state;
     guint key; /* hash(peerIP, peerPort) */
     TCP* parent;
-    /* the handle to return when the socket is accepted */
-    int handle;
     MAGIC_DECLARE;
 };
 
 typedef struct _TCPServer TCPServer;
 struct _TCPServer {
-    /* children will be registered in this process' descriptor table */
-    Process* processForChildren;
-    /* all children of this server */
+    /* all children of this server. children that haven't been accepted are also owned by the
+     * server, and are registered in the accepting process' descriptor table when accepted. */
     GHashTable* children;
     /* pending children to accept in order. */
     GQueue *pending;
@@ -203,8 +199,8 @@ struct _TCP {
         gsize space;
     } autotune;
 
-    /* congestion object for implementing different types of congestion control (aimd, reno, cubic) */
-    TCPCong cong;
+    /* congestion object for implementing different types of congestion control (reno, cubic, bbr) */
+    TcpCongestionControl* cong;
 
     struct {
       gint rttSmoothed;
@@ -265,8 +261,7 @@ static TCP* _tcp_fromLegacyFile(LegacyFile* descriptor) {
     return (TCP*)descriptor;
 }
 
-static TCPChild* _tcpchild_new(TCP* tcp, TCP* parent, int handle, in_addr_t peerIP,
-                               in_port_t peerPort) {
+static TCPChild* _tcpchild_new(TCP* tcp, TCP* parent, in_addr_t peerIP, in_port_t peerPort) {
     MAGIC_ASSERT(tcp);
     MAGIC_ASSERT(parent);
 
@@ -282,8 +277,6 @@ static TCPChild* _tcpchild_new(TCP* tcp, TCP* parent, int handle, in_addr_t peer
     child->state = TCPCS_INCOMPLETE;
     legacysocket_setPeerName(&(tcp->super), peerIP, peerPort);
 
-    child->handle = handle;
-
     /* the child is bound to the parent server's address, because all packets
      * coming from the child should appear to be coming from the server itself */
     in_addr_t parentAddress;
@@ -332,7 +325,7 @@ static void _tcpserver_updateBacklog(TCPServer* server, gint _backlog) {
     server->pendingMax = backlog;
 }
 
-static TCPServer* _tcpserver_new(gint backlog, Process* processForChildren) {
+static TCPServer* _tcpserver_new(gint backlog) {
     TCPServer* server = g_new0(TCPServer, 1);
     MAGIC_INIT(server);
 
@@ -342,9 +335,6 @@ static TCPServer* _tcpserver_new(gint backlog, Process* processForChildren) {
     server->pending = g_queue_new();
     server->pendingMax = 0;
 
-    process_ref(processForChildren);
-    server->processForChildren = processForChildren;
-
     _tcpserver_updateBacklog(server, backlog);
 
     return server;
@@ -360,9 +350,6 @@ static void _tcpserver_free(TCPServer* server) {
         g_hash_table_destroy(server->children);
     }
 
-    process_unref(server->processForChildren);
-    server->processForChildren = NULL;
-
     MAGIC_CLEAR(server);
     g_free(server);
 }
@@ -372,8 +359,21 @@ static bool _tcpserver_acceptQueueFull(TCPServer* server) {
     return server->pendingCount >= server->pendingMax;
 }
 
-struct TCPCong_ *tcp_cong(TCP *tcp) {
-    return &tcp->cong;
+TcpCongestionAlgorithm tcp_getCongestionAlgorithm(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return tcpcongestion_getAlgorithm(tcp->cong);
+}
+
+void tcp_setCongestionAlgorithm(TCP* tcp, TcpCongestionAlgorithm algorithm) {
+    MAGIC_ASSERT(tcp);
+
+    if (tcpcongestion_getAlgorithm(tcp->cong) == algorithm) {
+        return;
+    }
+
+    /* like linux, the new algorithm starts from its initial state */
+    tcpcongestion_free(tcp->cong);
+    tcp->cong = tcpcongestion_new(algorithm);
 }
 
 void tcp_clearAllChildrenIfServer(TCP* tcp) {
@@ -615,7 +615,7 @@ static void _tcp_autotuneSendBuffer(TCP* tcp, Host* host) {
      * or sample from a distribution. */
 
     gsize sndmem = 2404;
-    gsize demanded = (gsize)tcp->cong.cwnd;
+    gsize demanded = (gsize)tcpcongestion_getCwnd(tcp->cong);
 
     gsize newSize = (gsize)MIN((gsize)(sndmem * 2 * demanded), _tcp_computeMaxWMEM(tcp, host));
 
@@ -836,7 +836,7 @@ static void _tcp_updateSendWindow(TCP* tcp) {
     MAGIC_ASSERT(tcp);
 
     /* send window is minimum of congestion window and the last advertised window */
-    tcp->send.window = (guint32)MIN(tcp->cong.cwnd, (gint)tcp->receive.lastWindow);
+    tcp->send.window = (guint32)MIN(tcpcongestion_getCwnd(tcp->cong), tcp->receive.lastWindow);
 }
 
 static Packet* _tcp_createPacketWithoutPayload(TCP* tcp, Host* host, enum ProtocolTCPFlags flags,
@@ -886,15 +886,42 @@ static Packet* _tcp_createPacketWithoutPayload(TCP* tcp, Host* host, enum Protoc
     return packet;
 }
 
+/* User data that is sent or received, which is either in plugin memory or in shadow's memory. */
+typedef struct _TCPUserBuffer TCPUserBuffer;
+struct _TCPUserBuffer {
+    /* if non-NULL, the data is in shadow's memory and `pluginPtr` is unused */
+    void* shadowPtr;
+    PluginVirtualPtr pluginPtr;
+};
+
+static TCPUserBuffer _tcp_userBufferAtOffset(TCPUserBuffer buffer, gsize offset) {
+    if (buffer.shadowPtr != NULL) {
+        return (TCPUserBuffer){.shadowPtr = (char*)buffer.shadowPtr + offset};
+    }
+    return (TCPUserBuffer){.pluginPtr = (PluginVirtualPtr){.val = buffer.pluginPtr.val + offset}};
+}
+
+static gssize _tcp_copyPayloadToUser(const Packet* packet, Thread* thread, gsize payloadOffset,
+                                     TCPUserBuffer buffer, gsize bufferLength) {
+    if (buffer.shadowPtr != NULL) {
+        return packet_copyPayloadShadow(packet, payloadOffset, buffer.shadowPtr, bufferLength);
+    }
+    return packet_copyPayload(packet, thread, payloadOffset, buffer.pluginPtr, bufferLength);
+}
+
 static Packet* _tcp_createDataPacket(TCP* tcp, Thread* thread, enum ProtocolTCPFlags flags,
-                                     PluginVirtualPtr payload, gsize payloadLength) {
+                                     TCPUserBuffer payload, gsize payloadLength) {
     MAGIC_ASSERT(tcp);
 
     Host* host = thread_getHost(thread);
     bool isEmpty = payloadLength == 0;
     Packet* packet = _tcp_createPacketWithoutPayload(tcp, host, flags, isEmpty);
     if (!isEmpty) {
-        packet_setPayload(packet, thread, payload, payloadLength);
+        if (payload.shadowPtr != NULL) {
+            packet_setPayloadShadow(packet, host, payload.shadowPtr, payloadLength);
+        } else {
+            packet_setPayload(packet, thread, payload.pluginPtr, payloadLength);
+        }
     }
     return packet;
 }
@@ -1419,7 +1446,7 @@ static void _tcp_runRetransmitTimerExpiredTask(Host* host, gpointer voidTcp, gpo
     _tcp_setRetransmitTimeout(tcp, tcp->retransmit.timeout * 2);
     _tcp_setRetransmitTimer(tcp, host, now);
 
-    tcp->cong.hooks->tcp_cong_timeout_ev(tcp);
+    tcpcongestion_onTimeout(tcp->cong);
     debug("[CONG] a congestion timeout has occurred on %s", tcp->super.boundString);
     _tcp_logCongestionInfo(tcp);
 
@@ -1534,7 +1561,8 @@ void tcp_getInfo(TCP* tcp, struct tcp_info *tcpinfo) {
     memset(tcpinfo, 0, sizeof(struct tcp_info));
 
     tcpinfo->tcpi_state = (u_int8_t) _tcp_getTCPInfoState(tcp);
-//  tcpinfo->tcpi_ca_state;
+    tcpinfo->tcpi_ca_state =
+        tcpcongestion_isInFastRecovery(tcp->cong) ? TCP_CA_Recovery : TCP_CA_Open;
 //  tcpinfo->tcpi_retransmits;
 //  tcpinfo->tcpi_probes;
 //  tcpinfo->tcpi_backoff;
@@ -1569,8 +1597,8 @@ void tcp_getInfo(TCP* tcp, struct tcp_info *tcpinfo) {
 //  tcpinfo->tcpi_rcv_ssthresh;
     tcpinfo->tcpi_rtt = (u_int32_t)tcp->timing.rttSmoothed;
     tcpinfo->tcpi_rttvar = (u_int32_t)tcp->timing.rttVariance;
-    tcpinfo->tcpi_snd_ssthresh = (u_int32_t)tcp->cong.hooks->tcp_cong_ssthresh(tcp);
-    tcpinfo->tcpi_snd_cwnd = (u_int32_t)tcp->cong.cwnd;
+    tcpinfo->tcpi_snd_ssthresh = (u_int32_t)tcpcongestion_getSSThresh(tcp->cong);
+    tcpinfo->tcpi_snd_cwnd = (u_int32_t)tcpcongestion_getCwnd(tcp->cong);
     tcpinfo->tcpi_advmss = (u_int32_t)CONFIG_TCP_MAX_SEGMENT_SIZE;
     //  tcpinfo->tcpi_reordering;
 
@@ -1604,11 +1632,44 @@ static gint _tcp_connectToPeer(LegacySocket* socket, Host* host, in_addr_t ip, i
     return -EINPROGRESS;
 }
 
-void tcp_enterServerMode(TCP* tcp, Host* host, Process* process, gint backlog) {
+/* Close and release the server's reference to a child that hasn't been accepted. */
+static void _tcpserver_dropChild(TCP* child, Host* host) {
+    MAGIC_ASSERT(child);
+
+    if (!(legacyfile_getStatus((LegacyFile*)child) & STATUS_FILE_CLOSED)) {
+        legacyfile_close((LegacyFile*)child, host);
+    }
+    legacyfile_unref(child);
+}
+
+/* Close the children that were never accepted. Otherwise they and the server would keep each other
+ * alive. */
+static void _tcpserver_dropUnacceptedChildren(TCPServer* server, Host* host) {
+    MAGIC_ASSERT(server);
+
+    /* dropping a child removes it from the children table, so we can't iterate over the table */
+    GList* children = g_hash_table_get_values(server->children);
+
+    for (GList* item = children; item != NULL; item = item->next) {
+        TCP* child = item->data;
+        MAGIC_ASSERT(child);
+        MAGIC_ASSERT(child->child);
+
+        if (child->child->state != TCPCS_ACCEPTED) {
+            _tcpserver_dropChild(child, host);
+        }
+    }
+
+    g_list_free(children);
+    g_queue_clear(server->pending);
+    server->pendingCount = 0;
+}
+
+void tcp_enterServerMode(TCP* tcp, Host* host, gint backlog) {
     MAGIC_ASSERT(tcp);
 
     /* we are a server ready to listen, build our server state */
-    tcp->server = _tcpserver_new(backlog, process);
+    tcp->server = _tcpserver_new(backlog);
 
     /* we are now listening for connections */
     _tcp_setState(tcp, host, TCPS_LISTEN);
@@ -1620,8 +1681,8 @@ void tcp_updateServerBacklog(TCP* tcp, gint backlog) {
     _tcpserver_updateBacklog(tcp->server, backlog);
 }
 
-gint tcp_acceptServerPeer(TCP* tcp, Host* host, in_addr_t* ip, in_port_t* port,
-                          gint* acceptedHandle) {
+gint tcp_acceptServerPeer(TCP* tcp, Host* host, Process* process, in_addr_t* ip,
+                          in_port_t* port, gint* acceptedHandle) {
     MAGIC_ASSERT(tcp);
     utility_assert(acceptedHandle);
 
@@ -1644,18 +1705,35 @@ gint tcp_acceptServerPeer(TCP* tcp, Host* host, in_addr_t* ip, in_port_t* port,
     }
 
     /* double check the pending child before its accepted */
-    TCP* tcpChild = g_queue_pop_head(tcp->server->pending);
+    TCP* tcpChild = g_queue_peek_head(tcp->server->pending);
     if(!tcpChild) {
         return -ECONNABORTED;
     }
 
-    tcp->server->pendingCount -= 1;
-
     MAGIC_ASSERT(tcpChild);
     if(tcpChild->error == TCPE_CONNECTION_RESET) {
+        g_queue_pop_head(tcp->server->pending);
+        tcp->server->pendingCount -= 1;
+        _tcpserver_dropChild(tcpChild, host);
         return -ECONNABORTED;
     }
 
+    /* The descriptor takes a new ref to the child. Like linux, if the process has reached its fd
+     * limit, the child stays in the queue and can be accepted later. */
+    legacyfile_ref(tcpChild);
+    Descriptor* desc = descriptor_fromLegacyFile((LegacyFile*)tcpChild, /* flags= */ 0);
+    int handle = process_registerDescriptor(process, desc);
+    if (handle < 0) {
+        debug("Unable to register the accepted socket (%s)", g_strerror(-handle));
+        return handle;
+    }
+
+    g_queue_pop_head(tcp->server->pending);
+    tcp->server->pendingCount -= 1;
+
+    /* the child is now owned by the descriptor table rather than the server */
+    legacyfile_unref(tcpChild);
+
     /* better have a peer if we are established */
     utility_assert(tcpChild->super.peerIP && tcpChild->super.peerPort);
 
@@ -1674,7 +1752,7 @@ gint tcp_acceptServerPeer(TCP* tcp, Host* host, in_addr_t* ip, in_port_t* port,
         legacyfile_adjustStatus(&(tcp->super.super.super), STATUS_FILE_READABLE, FALSE);
     }
 
-    *acceptedHandle = tcpChild->child->handle;
+    *acceptedHandle = handle;
     utility_assert(ip);
     *ip = tcpChild->super.peerIP;
     utility_assert(port);
@@ -1828,7 +1906,7 @@ TCPProcessFlags _tcp_ackProcessing(TCP* tcp, Host* host, Packet* packet, PacketT
     if (is_dup) {
         debug("[CONG-AVOID] duplicate ack");
         _tcp_logCongestionInfo(tcp);
-        tcp->cong.hooks->tcp_cong_duplicate_ack_ev(tcp);
+        tcpcongestion_onDuplicateAck(tcp->cong);
     }
 
     gint nPacketsAcked = 0;
@@ -1850,7 +1928,9 @@ TCPProcessFlags _tcp_ackProcessing(TCP* tcp, Host* host, Packet* packet, PacketT
             flags |= TCP_PF_DATA_ACKED;
 
             debug("[CONG] %i packets were acked", nPacketsAcked);
-            tcp->cong.hooks->tcp_cong_new_ack_ev(tcp, nPacketsAcked);
+            tcpcongestion_onNewAck(
+                tcp->cong, nPacketsAcked, tcp->send.next - tcp->send.unacked,
+                (SimulationTime)tcp->timing.rttSmoothed * SIMTIME_ONE_MILLISECOND);
 
             /* increase send buffer size with autotuning */
             if (tcp->autotune.isEnabled && !tcp->autotune.userDisabledSend &&
@@ -1900,7 +1980,8 @@ static void _tcp_logCongestionInfo(TCP* tcp) {
           "sndbufsize=%" G_GSIZE_FORMAT " sndbuflen=%" G_GSIZE_FORMAT " rcvbufsize=%" G_GSIZE_FORMAT
           " rcbuflen=%" G_GSIZE_FORMAT " "
           "retrans=%" G_GSIZE_FORMAT " ploss=%f desc=%p",
-          tcp->cong.cwnd, tcp->cong.hooks->tcp_cong_ssthresh(tcp), tcp->timing.rttSmoothed, outSize,
+          tcpcongestion_getCwnd(tcp->cong), tcpcongestion_getSSThresh(tcp->cong),
+          tcp->timing.rttSmoothed, outSize,
           outLength, inSize, inLength, tcp->info.retransmitCount, ploss,
           &tcp->super.super.super);
 }
@@ -1986,25 +2067,17 @@ static void _tcp_processPacket(LegacySocket* socket, Host* host, Packet* packet)
                 guint64 recvBufSize = host_getConfiguredRecvBufSize(host);
                 guint64 sendBufSize = host_getConfiguredSendBufSize(host);
 
-                /* We will register the child socket with whichever process called listen() on the
-                 * parent socket. This is incorrect and we should register the child socket with
-                 * whichever process eventually calls accept() on the parent socket, but this is
-                 * difficult to fix and isn't an issue until we support fork().
-                 * See: https://github.com/shadow/shadow/issues/1780 */
-                Process* registerInProcess = tcp->server->processForChildren;
-
                 /* we need to multiplex a new child */
                 TCP* multiplexed = tcp_new(host, recvBufSize, sendBufSize);
-                Descriptor* desc =
-                    descriptor_fromLegacyFile((LegacyFile*)multiplexed, /* flags= */ 0);
-                int handle = process_registerDescriptor(registerInProcess, desc);
+                /* like linux, the child inherits the listener's congestion control */
+                tcp_setCongestionAlgorithm(multiplexed, tcpcongestion_getAlgorithm(tcp->cong));
 
                 multiplexed->child =
-                    _tcpchild_new(multiplexed, tcp, handle, header->sourceIP, header->sourcePort);
+                    _tcpchild_new(multiplexed, tcp, header->sourceIP, header->sourcePort);
                 utility_assert(g_hash_table_lookup(tcp->server->children, &(multiplexed->child->key)) == NULL);
 
-                /* multiplexed TCP was initialized with a ref of 1, which the host table consumes.
-                 * so we need another ref for the children table */
+                /* multiplexed TCP was initialized with a ref of 1, which the server owns until the
+                 * child is accepted. so we need another ref for the children table */
                 legacyfile_refWeak(multiplexed);
                 g_hash_table_replace(tcp->server->children, &(multiplexed->child->key), multiplexed);
 
@@ -2304,9 +2377,8 @@ static void _tcp_endOfFileSignalled(TCP* tcp, enum TCPFlags flags) {
     }
 }
 
-static gssize _tcp_sendUserData(Transport* transport, Thread* thread, PluginVirtualPtr buffer,
-                                gsize nBytes, in_addr_t ip, in_port_t port) {
-    TCP* tcp = _tcp_fromLegacyFile((LegacyFile*)transport);
+static gssize _tcp_sendUserDataBuffer(TCP* tcp, Thread* thread, TCPUserBuffer buffer,
+                                      gsize nBytes) {
     MAGIC_ASSERT(tcp);
 
     /* return 0 to signal close, if necessary */
@@ -2336,7 +2408,7 @@ static gssize _tcp_sendUserData(Transport* transport, Thread* thread, PluginVirt
 
         /* use helper to create the packet */
         Packet* packet = _tcp_createDataPacket(
-            tcp, thread, PTCP_ACK, (PluginVirtualPtr){.val = buffer.val + bytesCopied}, copyLength);
+            tcp, thread, PTCP_ACK, _tcp_userBufferAtOffset(buffer, bytesCopied), copyLength);
         if(copyLength > 0) {
             /* we are sending more user data */
             tcp->send.end++;
@@ -2360,6 +2432,18 @@ static gssize _tcp_sendUserData(Transport* transport, Thread* thread, PluginVirt
     return (gssize)(bytesCopied == 0 && nBytes != 0 ? -EWOULDBLOCK : bytesCopied);
 }
 
+static gssize _tcp_sendUserData(Transport* transport, Thread* thread, PluginVirtualPtr buffer,
+                                gsize nBytes, in_addr_t ip, in_port_t port) {
+    TCP* tcp = _tcp_fromLegacyFile((LegacyFile*)transport);
+    return _tcp_sendUserDataBuffer(tcp, thread, (TCPUserBuffer){.pluginPtr = buffer}, nBytes);
+}
+
+gssize tcp_sendUserDataShadow(TCP* tcp, Thread* thread, const void* buffer, gsize nBytes) {
+    // the buffer is only read from
+    return _tcp_sendUserDataBuffer(
+        tcp, thread, (TCPUserBuffer){.shadowPtr = (void*)buffer}, nBytes);
+}
+
 static void _tcp_sendWindowUpdate(Host* host, gpointer voidTcp, gpointer data) {
     TCP* tcp = voidTcp;
     MAGIC_ASSERT(tcp);
@@ -2373,11 +2457,67 @@ static void _tcp_sendWindowUpdate(Host* host, gpointer voidTcp, gpointer data) {
     tcp->receive.windowUpdatePending = FALSE;
 }
 
-static gssize _tcp_receiveUserData(Transport* transport, Thread* thread, PluginVirtualPtr buffer,
-                                   gsize nBytes, in_addr_t* ip, in_port_t* port) {
-    TCP* tcp = _tcp_fromLegacyFile((LegacyFile*)transport);
+/* Copy (or with MSG_TRUNC, only count) up to nBytes of the readable user data without removing
+ * it from the socket. */
+static gssize _tcp_peekUserData(TCP* tcp, Thread* thread, TCPUserBuffer buffer, gsize nBytes,
+                                bool discard) {
     MAGIC_ASSERT(tcp);
 
+    gsize remaining = nBytes;
+    gsize totalCopied = 0;
+
+    /* start with the unread part of the partially read packet, if any */
+    const Packet* packet = tcp->partialUserDataPacket;
+    gsize packetOffset = tcp->partialOffset;
+    guint nextIndex = 0;
+
+    if (packet == NULL) {
+        packet = legacysocket_peekNthInPacket(&tcp->super, nextIndex++);
+    }
+
+    while (remaining > 0 && packet != NULL) {
+        gsize packetBytes = packet_getPayloadSize(packet) - packetOffset;
+        gsize copyLength = MIN(packetBytes, remaining);
+
+        if (!discard) {
+            gssize bytesCopied =
+                _tcp_copyPayloadToUser(packet, thread, packetOffset,
+                                       _tcp_userBufferAtOffset(buffer, totalCopied), copyLength);
+            if (bytesCopied < 0) {
+                // Error writing to PluginVirtualPtr
+                return bytesCopied;
+            }
+            utility_assert(bytesCopied == copyLength);
+        }
+
+        totalCopied += copyLength;
+        remaining -= copyLength;
+
+        packet = legacysocket_peekNthInPacket(&tcp->super, nextIndex++);
+        packetOffset = 0;
+    }
+
+    trace("%s <-> %s: peeking %" G_GSIZE_FORMAT " user bytes", tcp->super.boundString,
+          tcp->super.peerString, totalCopied);
+
+    if (totalCopied == 0 && nBytes != 0) {
+        /* like a regular read, return EOF once all of the data has been read */
+        if ((tcp->unorderedInputLength == 0) && (tcp->error & TCPE_RECEIVE_EOF)) {
+            return 0;
+        }
+        return -EWOULDBLOCK;
+    }
+
+    return totalCopied;
+}
+
+static gssize _tcp_receiveUserDataBuffer(TCP* tcp, Thread* thread, TCPUserBuffer buffer,
+                                         gsize nBytes, int flags) {
+    MAGIC_ASSERT(tcp);
+
+    /* with MSG_TRUNC, linux discards the received data rather than copying it */
+    bool discard = (flags & MSG_TRUNC) != 0;
+
     Host* host = thread_getHost(thread);
 
     /*
@@ -2402,11 +2542,15 @@ static gssize _tcp_receiveUserData(Transport* transport, Thread* thread, PluginV
         return -EWOULDBLOCK;
     }
 
-    if (buffer.val == 0 && nBytes > 0) {
+    if (buffer.shadowPtr == NULL && buffer.pluginPtr.val == 0 && nBytes > 0 && !discard) {
         debug("Can't recv >0 bytes into NULL buffer on socket");
         return -EFAULT;
     }
 
+    if (flags & MSG_PEEK) {
+        return _tcp_peekUserData(tcp, thread, buffer, nBytes, discard);
+    }
+
     /* check if we have a partial packet waiting to get finished */
     if(remaining > 0 && tcp->partialUserDataPacket) {
         gsize partialLength = packet_getPayloadSize(tcp->partialUserDataPacket);
@@ -2414,8 +2558,10 @@ static gssize _tcp_receiveUserData(Transport* transport, Thread* thread, PluginV
         utility_assert(partialBytes > 0);
 
         copyLength = MIN(partialBytes, remaining);
-        gssize bytesCopied = packet_copyPayload(
-            tcp->partialUserDataPacket, thread, tcp->partialOffset, buffer, copyLength);
+        gssize bytesCopied =
+            discard ? copyLength
+                    : _tcp_copyPayloadToUser(tcp->partialUserDataPacket, thread,
+                                             tcp->partialOffset, buffer, copyLength);
         if (bytesCopied < 0) {
             // Error writing to PluginVirtualPtr
             return bytesCopied;
@@ -2453,8 +2599,10 @@ static gssize _tcp_receiveUserData(Transport* transport, Thread* thread, PluginV
 
         gsize packetLength = packet_getPayloadSize(nextPacket);
         copyLength = MIN(packetLength, remaining);
-        gssize bytesCopied = packet_copyPayload(
-            nextPacket, thread, 0, (PluginVirtualPtr){.val = buffer.val + offset}, copyLength);
+        gssize bytesCopied =
+            discard ? copyLength
+                    : _tcp_copyPayloadToUser(nextPacket, thread, 0,
+                                             _tcp_userBufferAtOffset(buffer, offset), copyLength);
         if (bytesCopied < 0) {
             // Error writing to PluginVirtualPtr
             if (totalCopied > 0) {
@@ -2548,6 +2696,17 @@ static gssize _tcp_receiveUserData(Transport* transport, Thread* thread, PluginV
     return totalCopied;
 }
 
+static gssize _tcp_receiveUserData(Transport* transport, Thread* thread, PluginVirtualPtr buffer,
+                                   gsize nBytes, in_addr_t* ip, in_port_t* port, int flags) {
+    TCP* tcp = _tcp_fromLegacyFile((LegacyFile*)transport);
+    return _tcp_receiveUserDataBuffer(
+        tcp, thread, (TCPUserBuffer){.pluginPtr = buffer}, nBytes, flags);
+}
+
+gssize tcp_receiveUserDataShadow(TCP* tcp, Thread* thread, void* buffer, gsize nBytes) {
+    return _tcp_receiveUserDataBuffer(tcp, thread, (TCPUserBuffer){.shadowPtr = buffer}, nBytes, 0);
+}
+
 static void _tcp_cleanup(LegacyFile* descriptor) {
     TCP* tcp = _tcp_fromLegacyFile(descriptor);
     MAGIC_ASSERT(tcp);
@@ -2583,7 +2742,7 @@ static void _tcp_free(LegacyFile* descriptor) {
         _tcpserver_free(tcp->server);
     }
 
-    tcp->cong.hooks->tcp_cong_delete(tcp);
+    tcpcongestion_free(tcp->cong);
     retransmit_tally_destroy(tcp->retransmit.tally);
 
     legacyfile_clear((LegacyFile*)tcp);
@@ -2610,6 +2769,9 @@ static void _tcp_close(LegacyFile* descriptor, Host* host) {
     switch (tcp->state) {
         case TCPS_LISTEN:
         case TCPS_SYNSENT: {
+            if (tcp->server) {
+                _tcpserver_dropUnacceptedChildren(tcp->server, host);
+            }
             _tcp_setState(tcp, host, TCPS_CLOSED);
             return;
         }
@@ -2696,9 +2858,7 @@ TCP* tcp_new(Host* host, guint receiveBufferSize, guint sendBufferSize) {
     guint32 initial_window = 10;
     gint tcpSSThresh = 0;
 
-    /* in the future we'd like to support more congestion control types
-     * and allow it to be set as a host option */
-    tcp_cong_reno_init(tcp);
+    tcp->cong = tcpcongestion_new(host_getTcpCongestionAlgorithm(host));
 
     tcp->send.window = initial_window;
     tcp->send.lastWindow = initial_window;
@@ -12,13 +12,14 @@
 #include <netinet/tcp.h>
 #include <sys/un.h>
 
+#include "main/bindings/c/bindings-opaque.h"
 #include "main/core/support/definitions.h"
+#include "main/host/thread.h"
 #include "main/routing/packet.minimal.h"
 
 #define TCP_MIN_CWND 10
 
 typedef struct _TCP TCP;
-struct TCPCong_;
 
 /* these were redefined in shd-tcp-retransmit-tally.h
  * if they change here, they must also change there!! (-RSW)
@@ -34,11 +35,6 @@ enum TCPProcessFlags {
     TCP_PF_RWND_UPDATED = 1 << 5,
 };
 
-typedef enum _TCPCongestionType TCPCongestionType;
-enum _TCPCongestionType {
-    TCP_CC_UNKNOWN, TCP_CC_AIMD, TCP_CC_RENO, TCP_CC_CUBIC,
-};
-
 TCP* tcp_new(Host* host, guint receiveBufferSize, guint sendBufferSize);
 
 // clang-format off
@@ -62,15 +58,21 @@ gint tcp_getConnectionError(TCP* tcp);
 // clang-format on
 
 void tcp_getInfo(TCP* tcp, struct tcp_info *tcpinfo);
-void tcp_enterServerMode(TCP* tcp, Host* host, Process* process, gint backlog);
+void tcp_enterServerMode(TCP* tcp, Host* host, gint backlog);
 void tcp_updateServerBacklog(TCP* tcp, gint backlog);
-gint tcp_acceptServerPeer(TCP* tcp, Host* host, in_addr_t* ip, in_port_t* port,
-                          gint* acceptedHandle);
+gint tcp_acceptServerPeer(TCP* tcp, Host* host, Process* process, in_addr_t* ip,
+                          in_port_t* port, gint* acceptedHandle);
 
-struct TCPCong_ *tcp_cong(TCP *tcp);
+TcpCongestionAlgorithm tcp_getCongestionAlgorithm(TCP* tcp);
+void tcp_setCongestionAlgorithm(TCP* tcp, TcpCongestionAlgorithm algorithm);
 
 void tcp_clearAllChildrenIfServer(TCP* tcp);
 
+/* Send or receive user data that is in shadow's memory rather than in plugin memory. These
+ * return the number of bytes copied, or a negative errno. */
+gssize tcp_sendUserDataShadow(TCP* tcp, Thread* thread, const void* buffer, gsize nBytes);
+gssize tcp_receiveUserDataShadow(TCP* tcp, Thread* thread, void* buffer, gsize nBytes);
+
 gsize tcp_getOutputBufferLength(TCP* tcp);
 gsize tcp_getInputBufferLength(TCP* tcp);
 gsize tcp_getNotSentBytes(TCP* tcp);
@@ -85,6 +87,4 @@ gint tcp_shutdown(TCP* tcp, Host* host, gint how);
 
 void tcp_networkInterfaceIsAboutToSendPacket(TCP* tcp, Host* host, Packet* packet);
 
-TCPCongestionType tcpCongestion_getType(const gchar* type);
-
 #endif /* SHD_TCP_H_ */
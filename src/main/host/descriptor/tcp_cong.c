@@ -1 +0,0 @@
-#include "main/host/descriptor/tcp_cong.h"
@@ -0,0 +1,298 @@
+use std::sync::{Arc, Weak};
+
+use atomic_refcell::AtomicRefCell;
+use nix::errno::Errno;
+
+use crate::core::support::emulated_time::EmulatedTime;
+use crate::core::support::simulation_time::SimulationTime;
+use crate::cshadow as c;
+use crate::host::descriptor::{
+    FileMode, FileState, FileStatus, StateEventSource, StateListenerFilter,
+};
+use crate::host::host::Host;
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall_types::{PluginPtr, SyscallError, SyscallResult};
+use crate::host::timer::Timer;
+use crate::utility::event_queue::{EventQueue, Handle};
+use crate::utility::stream_len::StreamLen;
+use crate::utility::HostTreePointer;
+
+pub struct TimerFd {
+    timer: Timer,
+    clock_id: libc::clockid_t,
+    /// Set if the timer was armed with `TFD_TIMER_CANCEL_ON_SET` for an absolute realtime
+    /// expiration.
+    cancel_on_set: bool,
+    /// Set if the realtime clock was changed while `cancel_on_set` was set. Cleared by the next
+    /// read or `timerfd_settime()`.
+    canceled: bool,
+    event_source: StateEventSource,
+    state: FileState,
+    status: FileStatus,
+    // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file
+    has_open_file: bool,
+}
+
+impl TimerFd {
+    pub fn new(clock_id: libc::clockid_t, status: FileStatus) -> Arc<AtomicRefCell<Self>> {
+        Arc::new_cyclic(|weak| {
+            let weak = Weak::clone(weak);
+            let timer = Timer::new(move |_host| {
+                // the timerfd may have been dropped before the timer was
+                if let Some(timerfd) = weak.upgrade() {
+                    EventQueue::queue_and_run(|event_queue| {
+                        timerfd.borrow_mut().update_state(event_queue)
+                    });
+                }
+            });
+
+            AtomicRefCell::new(Self {
+                timer,
+                clock_id,
+                cancel_on_set: false,
+                canceled: false,
+                event_source: StateEventSource::new(),
+                state: FileState::ACTIVE,
+                status,
+                has_open_file: false,
+            })
+        })
+    }
+
+    pub fn get_status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        false
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn clock_id(&self) -> libc::clockid_t {
+        self.clock_id
+    }
+
+    pub fn close(&mut self, event_queue: &mut EventQueue) -> Result<(), SyscallError> {
+        self.timer.disarm();
+
+        // set the closed flag and remove the active and readable flags
+        self.copy_state(
+            FileState::CLOSED | FileState::ACTIVE | FileState::READABLE,
+            FileState::CLOSED,
+            event_queue,
+        );
+
+        Ok(())
+    }
+
+    /// The time remaining until the next expiration (or zero if disarmed), and the interval.
+    pub fn get_time(&self) -> (SimulationTime, SimulationTime) {
+        let remaining = self.timer.remaining_time().unwrap_or(SimulationTime::ZERO);
+        (remaining, self.timer.expire_interval())
+    }
+
+    /// Arm the timer to first expire at `expire_time`, and then every `interval` if the interval
+    /// is non-zero. Any unread expirations are discarded. If `realtime` is set, `expire_time` is a
+    /// value of the host's realtime clock, and the timer follows changes to that clock.
+    pub fn arm(
+        &mut self,
+        host: &mut Host,
+        expire_time: EmulatedTime,
+        interval: SimulationTime,
+        realtime: bool,
+        cancel_on_set: bool,
+        event_queue: &mut EventQueue,
+    ) {
+        // timerfd_settime(2): "If the specified time has already passed [...] the timer expires
+        // immediately"
+        if realtime {
+            self.timer.arm_realtime(host, expire_time, interval);
+        } else {
+            self.timer.arm(host, expire_time, interval);
+        }
+
+        self.cancel_on_set = cancel_on_set;
+        self.canceled = false;
+        self.update_state(event_queue);
+    }
+
+    /// Disarm the timer. Any unread expirations are discarded.
+    pub fn disarm(&mut self, event_queue: &mut EventQueue) {
+        self.timer.disarm();
+        self.cancel_on_set = false;
+        self.canceled = false;
+        self.update_state(event_queue);
+    }
+
+    /// Should be called when the realtime clock is set (`was_set`) or its rate changes. A timer
+    /// armed with an absolute realtime expiration is moved to follow the clock, and if it was
+    /// armed with `TFD_TIMER_CANCEL_ON_SET` and the clock was set, the next read will fail with
+    /// `ECANCELED`.
+    pub fn realtime_clock_changed(
+        &mut self,
+        host: &mut Host,
+        was_set: bool,
+        event_queue: &mut EventQueue,
+    ) {
+        self.timer.realtime_clock_changed(host);
+
+        if was_set && self.cancel_on_set && self.timer.remaining_time().is_some() {
+            self.canceled = true;
+        }
+
+        self.update_state(event_queue);
+    }
+
+    pub fn read<W>(
+        &mut self,
+        mut bytes: W,
+        offset: libc::off_t,
+        event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        // timerfds don't support seeking
+        if offset != 0 {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        // timerfd_create(2): "read(2) returns an unsigned 8-byte integer"
+        const NUM_BYTES: usize = 8;
+
+        // this check doesn't guarentee that we can write all bytes since the stream length is only
+        // a hint
+        if usize::try_from(bytes.stream_len_bp()?).unwrap() < NUM_BYTES {
+            log::trace!(
+                "Reading from timerfd requires a buffer of at least {} bytes",
+                NUM_BYTES
+            );
+            return Err(Errno::EINVAL.into());
+        }
+
+        if self.canceled {
+            self.canceled = false;
+            self.update_state(event_queue);
+            return Err(Errno::ECANCELED.into());
+        }
+
+        let expiration_count = self.timer.expiration_count();
+        if expiration_count == 0 {
+            log::trace!("Timerfd has not expired and cannot be read right now");
+            return Err(Errno::EWOULDBLOCK.into());
+        }
+
+        bytes.write_all(&expiration_count.to_ne_bytes())?;
+        self.timer.consume_expiration_count();
+        self.update_state(event_queue);
+
+        Ok(NUM_BYTES.into())
+    }
+
+    pub fn write<R>(
+        &mut self,
+        _bytes: R,
+        _offset: libc::off_t,
+        _event_queue: &mut EventQueue,
+    ) -> SyscallResult
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: u64,
+        _arg_ptr: PluginPtr,
+        _memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        log::warn!("We do not yet handle ioctl request {} on timerfds", request);
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, &mut EventQueue) + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState)> {
+        self.event_source
+            .add_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring: FileState,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, bool, &mut EventQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<(FileState, FileState, u64)> {
+        self.event_source
+            .add_exclusive_listener(monitoring, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    fn update_state(&mut self, event_queue: &mut EventQueue) {
+        if self.state.contains(FileState::CLOSED) {
+            return;
+        }
+
+        // readable if there are unread expirations, or if the read would fail with ECANCELED
+        let readable = if self.canceled || self.timer.expiration_count() > 0 {
+            FileState::READABLE
+        } else {
+            FileState::empty()
+        };
+
+        self.copy_state(FileState::READABLE, readable, event_queue);
+    }
+
+    fn copy_state(&mut self, mask: FileState, state: FileState, event_queue: &mut EventQueue) {
+        let old_state = self.state;
+
+        // remove the masked flags, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, event_queue);
+    }
+
+    fn handle_state_change(&mut self, old_state: FileState, event_queue: &mut EventQueue) {
+        let states_changed = self.state ^ old_state;
+
+        // if nothing changed
+        if states_changed.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, event_queue);
+    }
+}
@@ -70,8 +70,8 @@ gssize transport_sendUserData(Transport* transport, Thread* thread, PluginVirtua
 }
 
 gssize transport_receiveUserData(Transport* transport, Thread* thread, PluginVirtualPtr buffer,
-                                 gsize nBytes, in_addr_t* ip, in_port_t* port) {
+                                 gsize nBytes, in_addr_t* ip, in_port_t* port, int flags) {
     MAGIC_ASSERT(transport);
     MAGIC_ASSERT(transport->vtable);
-    return transport->vtable->receive(transport, thread, buffer, nBytes, ip, port);
+    return transport->vtable->receive(transport, thread, buffer, nBytes, ip, port, flags);
 }
@@ -23,7 +23,7 @@ typedef gssize (*TransportSendFunc)(Transport* transport, Thread* thread, Plugin
                                     gsize nBytes, in_addr_t ip, in_port_t port);
 typedef gssize (*TransportReceiveFunc)(Transport* transport, Thread* thread,
                                        PluginVirtualPtr buffer, gsize nBytes, in_addr_t* ip,
-                                       in_port_t* port);
+                                       in_port_t* port, int flags);
 
 struct _TransportFunctionTable {
     LegacyFileCloseFunc close;
@@ -46,6 +46,6 @@ void transport_init(Transport* transport, TransportFunctionTable* vtable, Legacy
 gssize transport_sendUserData(Transport* transport, Thread* thread, PluginVirtualPtr buffer,
                               gsize nBytes, in_addr_t ip, in_port_t port);
 gssize transport_receiveUserData(Transport* transport, Thread* thread, PluginVirtualPtr buffer,
-                                 gsize nBytes, in_addr_t* ip, in_port_t* port);
+                                 gsize nBytes, in_addr_t* ip, in_port_t* port, int flags);
 
 #endif /* SHD_TRANSPORT_H_ */
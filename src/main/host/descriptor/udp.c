@@ -170,7 +170,7 @@ static gssize _udp_sendUserData(Transport* transport, Thread* thread, PluginVirt
 }
 
 static gssize _udp_receiveUserData(Transport* transport, Thread* thread, PluginVirtualPtr buffer,
-                                   gsize nBytes, in_addr_t* ip, in_port_t* port) {
+                                   gsize nBytes, in_addr_t* ip, in_port_t* port, int flags) {
     UDP* udp = _udp_fromLegacyFile((LegacyFile*)transport);
     MAGIC_ASSERT(udp);
 
@@ -196,24 +196,33 @@ static gssize _udp_receiveUserData(Transport* transport, Thread* thread, PluginV
         return bytesCopied;
     }
 
-    Packet* packet = legacysocket_removeFromInputBuffer((LegacySocket*)udp, thread_getHost(thread));
-
     utility_assert(bytesCopied == copyLength);
-    packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DELIVERED);
 
     /* fill in address info */
     if(ip) {
-        *ip = packet_getSourceIP(packet);
+        *ip = packet_getSourceIP(nextPacket);
     }
     if(port) {
-        *port = packet_getSourcePort(packet);
+        *port = packet_getSourcePort(nextPacket);
     }
 
-    /* destroy packet, throwing away any bytes not claimed by the app */
-    packet_unref(packet);
+    /* a peeked packet stays in the input buffer for the next read */
+    if (!(flags & MSG_PEEK)) {
+        Packet* packet =
+            legacysocket_removeFromInputBuffer((LegacySocket*)udp, thread_getHost(thread));
+        packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DELIVERED);
+
+        /* destroy packet, throwing away any bytes not claimed by the app */
+        packet_unref(packet);
+    }
 
     trace("user read %ld inbound UDP bytes", bytesCopied);
 
+    /* MSG_TRUNC returns the real length of the datagram, even if it was truncated */
+    if (flags & MSG_TRUNC) {
+        return (gssize)packetLength;
+    }
+
     return bytesCopied;
 }
 
@@ -30,7 +30,6 @@
 #include "main/host/descriptor/regular_file.h"
 #include "main/host/descriptor/socket.h"
 #include "main/host/descriptor/tcp.h"
-#include "main/host/descriptor/timerfd.h"
 #include "main/host/descriptor/transport.h"
 #include "main/host/descriptor/udp.h"
 #include "main/host/futex_table.h"
@@ -77,6 +76,9 @@ struct _Host {
     /* map abstract socket addresses to unix sockets */
     Arc_AtomicRefCell_AbstractUnixNamespace* abstractUnixNamespace;
 
+    /* map filesystem paths to unix sockets */
+    Arc_AtomicRefCell_PathnameUnixNamespace* pathnameUnixNamespace;
+
     /* map address to futex objects */
     FutexTable* futexTable;
 
@@ -89,6 +91,9 @@ struct _Host {
     /* Lock protecting parts of shimSharedMemBlock. */
     ShimShmemHostLock* shimShmemHostLock;
 
+    /* adjtimex state of the realtime clock that isn't kept in shimSharedMemBlock */
+    HostClockStatus clockStatus;
+
     /* random stream */
     Random* random;
 
@@ -115,6 +120,9 @@ ADD_CONFIG_HANDLER(config_getUnblockedVdsoLatency, _unblockedVdsoLatencyConfig)
 static SimulationTime _maxUnappliedCpuLatencyConfig;
 ADD_CONFIG_HANDLER(config_getMaxUnappliedCpuLatency, _maxUnappliedCpuLatencyConfig)
 
+static bool _useLegacyWorkingDir = false;
+ADD_CONFIG_HANDLER(config_getUseLegacyWorkingDir, _useLegacyWorkingDir)
+
 /* this function is called by manager before the workers exist */
 Host* host_new(const HostParameters* params) {
     utility_assert(params);
@@ -149,9 +157,13 @@ Host* host_new(const HostParameters* params) {
          g_quark_to_string(host->params.id));
 
     host->shimSharedMemBlock = shmemallocator_globalAlloc(shimshmemhost_size());
+    // the config ensures that the host's realtime clock starts at a valid emulated time
+    EmulatedTime realtimeStart = host->params.startDatetime + host->params.clockOffset;
     shimshmemhost_init(host_getSharedMem(host), host, _modelUnblockedSyscallLatencyConfig,
                        _maxUnappliedCpuLatencyConfig, _unblockedSyscallLatencyConfig,
-                       _unblockedVdsoLatencyConfig);
+                       _unblockedVdsoLatencyConfig, realtimeStart, host->params.clockDriftPpm);
+    // the nominal tick length of a kernel with USER_HZ=100
+    host->clockStatus.tick = 10000;
 
     host->processIDCounter = 1000;
     host->referenceCount = 1;
@@ -213,6 +225,16 @@ void host_setup(Host* host, DNS* dns, gulong rawCPUFreq, const gchar* hostRootPa
 
     host->abstractUnixNamespace = abstractunixnamespace_new();
 
+    /* relative socket paths are resolved against the processes' working directory */
+    if (_useLegacyWorkingDir) {
+        gchar* cwd = g_get_current_dir();
+        host->pathnameUnixNamespace = pathnameunixnamespace_new(cwd, host->dataDirPath);
+        g_free(cwd);
+    } else {
+        host->pathnameUnixNamespace =
+            pathnameunixnamespace_new(host->dataDirPath, host->dataDirPath);
+    }
+
     /* table to track futexes used by processes/threads */
     host->futexTable = futextable_new();
 
@@ -228,10 +250,12 @@ void host_setup(Host* host, DNS* dns, gulong rawCPUFreq, const gchar* hostRootPa
     /* virtual addresses and interfaces for managing network I/O */
     NetworkInterface* loopback =
         networkinterface_new(host, loopbackAddress, pcapDir, host->params.pcapCaptureSize,
-                             host->params.qdisc, host->params.interfaceBufSize);
+                             host->params.startDatetime, host->params.qdisc,
+                             host->params.interfaceBufSize);
     NetworkInterface* ethernet =
         networkinterface_new(host, ethernetAddress, pcapDir, host->params.pcapCaptureSize,
-                             host->params.qdisc, host->params.interfaceBufSize);
+                             host->params.startDatetime, host->params.qdisc,
+                             host->params.interfaceBufSize);
 
     g_free(pcapDir);
 
@@ -294,6 +318,10 @@ void host_shutdown(Host* host) {
         abstractunixnamespace_free(host->abstractUnixNamespace);
     }
 
+    if (host->pathnameUnixNamespace) {
+        pathnameunixnamespace_free(host->pathnameUnixNamespace);
+    }
+
     if (host->futexTable) {
         futextable_unref(host->futexTable);
     }
@@ -411,6 +439,11 @@ guint host_getNewProcessID(Host* host) {
     return host->processIDCounter++;
 }
 
+guint host_getLastProcessID(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->processIDCounter - 1;
+}
+
 guint64 host_getNewEventID(Host* host) {
     MAGIC_ASSERT(host);
     return host->eventIDCounter++;
@@ -455,6 +488,12 @@ void host_addApplication(Host* host, SimulationTime startTime, SimulationTime st
     g_strfreev(envv_dup);
 }
 
+void host_addChildProcess(Host* host, Process* proc) {
+    MAGIC_ASSERT(host);
+    process_ref(proc);
+    g_queue_push_tail(host->processes, proc);
+}
+
 void host_freeAllApplications(Host* host) {
     MAGIC_ASSERT(host);
     trace("start freeing applications for host '%s'", host->params.hostname);
@@ -583,6 +622,26 @@ void host_disassociateInterface(Host* host, const CompatSocket* socket) {
     }
 }
 
+void host_associateInetSocket(Host* host, const InetSocket* socket, in_addr_t bindAddress) {
+    CompatSocket compatSocket = compatsocket_fromInetSocket(socket);
+    host_associateInterface(host, &compatSocket, bindAddress);
+}
+
+void host_disassociateInetSocket(Host* host, const InetSocket* socket) {
+    CompatSocket compatSocket = compatsocket_fromInetSocket(socket);
+    host_disassociateInterface(host, &compatSocket);
+}
+
+void host_inetSocketWantsSend(Host* host, const InetSocket* socket, in_addr_t srcIP) {
+    MAGIC_ASSERT(host);
+
+    NetworkInterface* interface = host_lookupInterface(host, srcIP);
+    utility_assert(interface != NULL);
+
+    CompatSocket compatSocket = compatsocket_fromInetSocket(socket);
+    networkinterface_wantsSend(interface, host, &compatSocket);
+}
+
 guint64 host_getConfiguredRecvBufSize(Host* host) {
     MAGIC_ASSERT(host);
     return host->params.recvBufSize;
@@ -593,6 +652,115 @@ guint64 host_getConfiguredSendBufSize(Host* host) {
     return host->params.sendBufSize;
 }
 
+TcpCongestionAlgorithm host_getTcpCongestionAlgorithm(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.tcpCongestionControl;
+}
+
+gboolean host_useNewTcp(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.useNewTcp;
+}
+
+guint64 host_getFdLimit(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.fdLimit;
+}
+
+guint32 host_getCpuCount(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.cpuCount;
+}
+
+guint64 host_getRamTotal(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.ramTotal;
+}
+
+guint64 host_getRamFree(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.ramFree;
+}
+
+guint64 host_getSwapTotal(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.swapTotal;
+}
+
+guint64 host_getSwapFree(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.swapFree;
+}
+
+const gdouble* host_getLoadAverage(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->params.loadAverage;
+}
+
+EmulatedTime host_getRealtime(Host* host) {
+    MAGIC_ASSERT(host);
+    utility_assert(host->shimShmemHostLock);
+    return shimshmem_getRealtime(host->shimShmemHostLock, worker_getCurrentEmulatedTime());
+}
+
+EmulatedTime host_getEmulatedTimeForRealtime(Host* host, EmulatedTime realtime) {
+    MAGIC_ASSERT(host);
+    utility_assert(host->shimShmemHostLock);
+    return shimshmem_getEmulatedTimeForRealtime(host->shimShmemHostLock, realtime);
+}
+
+static void _host_notifyRealtimeClockSet(gpointer data, gpointer userData) {
+    process_notifyRealtimeClockChanged((Process*)data, (Host*)userData, true);
+}
+
+static void _host_notifyRealtimeClockRateChanged(gpointer data, gpointer userData) {
+    process_notifyRealtimeClockChanged((Process*)data, (Host*)userData, false);
+}
+
+void host_setRealtime(Host* host, EmulatedTime realtime) {
+    MAGIC_ASSERT(host);
+    utility_assert(host->shimShmemHostLock);
+    shimshmem_setRealtime(host->shimShmemHostLock, worker_getCurrentEmulatedTime(), realtime);
+    g_queue_foreach(host->processes, _host_notifyRealtimeClockSet, host);
+}
+
+gdouble host_getRealtimeFrequencyPpm(Host* host) {
+    MAGIC_ASSERT(host);
+    utility_assert(host->shimShmemHostLock);
+    return shimshmem_getRealtimeFrequencyPpm(host->shimShmemHostLock);
+}
+
+void host_setRealtimeFrequencyPpm(Host* host, gdouble frequencyPpm) {
+    MAGIC_ASSERT(host);
+    utility_assert(host->shimShmemHostLock);
+    shimshmem_setRealtimeFrequencyPpm(
+        host->shimShmemHostLock, worker_getCurrentEmulatedTime(), frequencyPpm);
+    g_queue_foreach(host->processes, _host_notifyRealtimeClockRateChanged, host);
+}
+
+gint64 host_getRealtimeSlew(Host* host) {
+    MAGIC_ASSERT(host);
+    utility_assert(host->shimShmemHostLock);
+    return shimshmem_getRealtimeSlew(host->shimShmemHostLock, worker_getCurrentEmulatedTime());
+}
+
+void host_setRealtimeSlew(Host* host, gint64 slew) {
+    MAGIC_ASSERT(host);
+    utility_assert(host->shimShmemHostLock);
+    shimshmem_setRealtimeSlew(host->shimShmemHostLock, worker_getCurrentEmulatedTime(), slew);
+    g_queue_foreach(host->processes, _host_notifyRealtimeClockRateChanged, host);
+}
+
+HostClockStatus host_getClockStatus(Host* host) {
+    MAGIC_ASSERT(host);
+    return host->clockStatus;
+}
+
+void host_setClockStatus(Host* host, HostClockStatus status) {
+    MAGIC_ASSERT(host);
+    host->clockStatus = status;
+}
+
 gboolean host_doesInterfaceExist(Host* host, in_addr_t interfaceIP) {
     MAGIC_ASSERT(host);
 
@@ -728,6 +896,10 @@ Arc_AtomicRefCell_AbstractUnixNamespace* host_getAbstractUnixNamespace(Host* hos
     return host->abstractUnixNamespace;
 }
 
+Arc_AtomicRefCell_PathnameUnixNamespace* host_getPathnameUnixNamespace(Host* host) {
+    return host->pathnameUnixNamespace;
+}
+
 FutexTable* host_getFutexTable(Host* host) { return host->futexTable; }
 
 Process* host_getProcess(Host* host, pid_t virtualPID) {
@@ -747,6 +919,24 @@ Process* host_getProcess(Host* host, pid_t virtualPID) {
     return NULL;
 }
 
+Process* host_getChildProcess(Host* host, pid_t parentPID, pid_t virtualPID, bool waitableOnly) {
+    MAGIC_ASSERT(host);
+
+    GList* current = g_queue_peek_head_link(host->processes);
+
+    while (current != NULL) {
+        Process* proc = current->data;
+        if (process_getParentProcessID(proc) == parentPID && !process_isReaped(proc) &&
+            (virtualPID == -1 || process_getProcessID(proc) == virtualPID) &&
+            (!waitableOnly || process_isWaitable(proc))) {
+            return proc;
+        }
+        current = current->next;
+    }
+
+    return NULL;
+}
+
 Thread* host_getThread(Host* host, pid_t virtualTID) {
     MAGIC_ASSERT(host);
 
@@ -18,6 +18,23 @@
 typedef struct _Host Host;
 typedef GQuark HostId;
 
+// The state of a host's realtime clock that is set and reported by adjtimex, but
+// that isn't otherwise modeled. Linux's kernel uses these for its phase-locked
+// loop and to report the clock's estimated error.
+typedef struct _HostClockStatus HostClockStatus;
+struct _HostClockStatus {
+    // The adjtimex `status` flags.
+    gint status;
+    // The maximum and estimated error in microseconds.
+    glong maxError;
+    glong estError;
+    // The phase-locked loop's time constant.
+    glong timeConstant;
+    // The length of a clock tick in microseconds. Changing it also changes the
+    // clock's frequency.
+    glong tick;
+};
+
 #include "lib/logger/log_level.h"
 #include "lib/shim/shim_shmem.h"
 #include "lib/tsc/tsc.h"
@@ -49,12 +66,18 @@ void host_boot(Host* host);
 void host_shutdown(Host* host);
 
 guint host_getNewProcessID(Host* host);
+/* The most recently assigned process ID. */
+guint host_getLastProcessID(Host* host);
 guint64 host_getNewEventID(Host* host);
 guint64 host_getNewPacketID(Host* host);
 void host_addApplication(Host* host, SimulationTime startTime, SimulationTime stopTime,
                          const gchar* pluginName, const gchar* pluginPath, const gchar* const* envv,
                          const gchar* const* argv, bool pause_for_debugging);
 void host_freeAllApplications(Host* host);
+// Takes a reference to a process created by another process on this host (e.g.
+// with `fork`), so that it is stopped and freed along with the host's other
+// processes.
+void host_addChildProcess(Host* host, Process* proc);
 
 gint host_compare(gconstpointer a, gconstpointer b, gpointer user_data);
 HostId host_getID(Host* host);
@@ -71,6 +94,42 @@ gboolean host_autotuneReceiveBuffer(Host* host);
 gboolean host_autotuneSendBuffer(Host* host);
 guint64 host_getConfiguredRecvBufSize(Host* host);
 guint64 host_getConfiguredSendBufSize(Host* host);
+TcpCongestionAlgorithm host_getTcpCongestionAlgorithm(Host* host);
+gboolean host_useNewTcp(Host* host);
+guint64 host_getFdLimit(Host* host);
+guint32 host_getCpuCount(Host* host);
+guint64 host_getRamTotal(Host* host);
+guint64 host_getRamFree(Host* host);
+guint64 host_getSwapTotal(Host* host);
+guint64 host_getSwapFree(Host* host);
+// Returns the 1, 5, and 15 minute load averages as an array of length 3.
+const gdouble* host_getLoadAverage(Host* host);
+
+// Returns the current value of the host's realtime clock (CLOCK_REALTIME). Unlike
+// the other clocks, the realtime clock may be offset from the emulated time, drift
+// from it, and be set by the host's processes.
+EmulatedTime host_getRealtime(Host* host);
+// Returns the emulated time at which the host's realtime clock will have value
+// `realtime`.
+EmulatedTime host_getEmulatedTimeForRealtime(Host* host, EmulatedTime realtime);
+// Steps the host's realtime clock to `realtime`. Absolute realtime timers of the
+// host's processes are moved to follow the clock, and timers that must be
+// canceled when the clock is set are canceled.
+void host_setRealtime(Host* host, EmulatedTime realtime);
+// Get and set the frequency adjustment in parts per million of the host's realtime
+// clock, which is applied in addition to the clock's configured drift. Absolute
+// realtime timers are moved to follow the new rate.
+gdouble host_getRealtimeFrequencyPpm(Host* host);
+void host_setRealtimeFrequencyPpm(Host* host, gdouble frequencyPpm);
+// Get and set the number of nanoseconds by which the host's realtime clock is
+// still to be gradually slewed, as set by adjtime. Setting the clock cancels the
+// slew. Absolute realtime timers are moved to follow the slewed clock.
+gint64 host_getRealtimeSlew(Host* host);
+void host_setRealtimeSlew(Host* host, gint64 slew);
+// Get and set the values that the host's processes set and read with adjtimex,
+// but that don't otherwise affect the host's realtime clock.
+HostClockStatus host_getClockStatus(Host* host);
+void host_setClockStatus(Host* host, HostClockStatus status);
 
 NetworkInterface* host_lookupInterface(Host* host, in_addr_t handle);
 Router* host_getUpstreamRouter(Host* host, in_addr_t handle);
@@ -91,11 +150,17 @@ gboolean host_isInterfaceAvailable(Host* host, ProtocolType type,
                                    in_addr_t peerIP, in_port_t peerPort);
 void host_associateInterface(Host* host, const CompatSocket* socket, in_addr_t bindAddress);
 void host_disassociateInterface(Host* host, const CompatSocket* socket);
+/* Wrappers around the above functions for sockets implemented in rust. */
+void host_associateInetSocket(Host* host, const InetSocket* socket, in_addr_t bindAddress);
+void host_disassociateInetSocket(Host* host, const InetSocket* socket);
+/* Tell the interface for 'srcIP' that the socket has packets to send. */
+void host_inetSocketWantsSend(Host* host, const InetSocket* socket, in_addr_t srcIP);
 in_port_t host_getRandomFreePort(Host* host, ProtocolType type,
                                  in_addr_t interfaceIP, in_addr_t peerIP,
                                  in_port_t peerPort);
 
 Arc_AtomicRefCell_AbstractUnixNamespace* host_getAbstractUnixNamespace(Host* host);
+Arc_AtomicRefCell_PathnameUnixNamespace* host_getPathnameUnixNamespace(Host* host);
 FutexTable* host_getFutexTable(Host* host);
 
 // converts a virtual (shadow) tid into the native tid
@@ -103,6 +168,10 @@ pid_t host_getNativeTID(Host* host, pid_t virtualPID, pid_t virtualTID);
 
 // Returns the specified process, or NULL if it doesn't exist.
 Process* host_getProcess(Host* host, pid_t virtualPID);
+// Get the oldest child of the process `parentPID` that hasn't been reaped, and
+// that matches `virtualPID` (any child if -1). If `waitableOnly`, only return
+// children that have exited and can be reaped.
+Process* host_getChildProcess(Host* host, pid_t parentPID, pid_t virtualPID, bool waitableOnly);
 
 // Returns the specified thread, or NULL if it doesn't exist.
 // If you already have the thread's Process*, `process_getThread` may be more
@@ -2,8 +2,10 @@ use once_cell::unsync::OnceCell;
 use std::net::IpAddr;
 use std::sync::Arc;
 
+use crate::core::support::emulated_time::EmulatedTime;
 use crate::cshadow;
 use crate::host::descriptor::socket::abstract_unix_ns::AbstractUnixNamespace;
+use crate::host::descriptor::socket::pathname_unix_ns::PathnameUnixNamespace;
 
 use atomic_refcell::AtomicRefCell;
 
@@ -102,6 +104,12 @@ impl Host {
         unsafe { &*ptr }
     }
 
+    pub fn pathname_unix_namespace(&self) -> &Arc<AtomicRefCell<PathnameUnixNamespace>> {
+        let ptr = unsafe { cshadow::host_getPathnameUnixNamespace(self.chost) };
+        assert!(!ptr.is_null());
+        unsafe { &*ptr }
+    }
+
     pub fn log_level(&self) -> Option<log::LevelFilter> {
         let level = unsafe { cshadow::host_getLogLevel(self.chost) };
         crate::core::logger::log_wrapper::c_to_rust_log_level(level).map(|l| l.to_level_filter())
@@ -113,6 +121,64 @@ impl Host {
         &mut random.0
     }
 
+    /// The current value of the host's realtime clock (`CLOCK_REALTIME`). Unlike the other clocks,
+    /// the realtime clock may be offset from the emulated time, drift from it, and be set by the
+    /// host's processes.
+    pub fn realtime(&self) -> EmulatedTime {
+        let realtime = unsafe { cshadow::host_getRealtime(self.chost) };
+        EmulatedTime::from_c_emutime(realtime).unwrap()
+    }
+
+    /// The emulated time at which the host's realtime clock will have value `realtime`.
+    pub fn emulated_time_for_realtime(&self, realtime: EmulatedTime) -> EmulatedTime {
+        let time = unsafe {
+            cshadow::host_getEmulatedTimeForRealtime(
+                self.chost,
+                EmulatedTime::to_c_emutime(Some(realtime)),
+            )
+        };
+        EmulatedTime::from_c_emutime(time).unwrap()
+    }
+
+    /// Step the host's realtime clock to `realtime`. Absolute realtime timers of the host's
+    /// processes are moved to follow the clock, and any timerfds that were armed with
+    /// `TFD_TIMER_CANCEL_ON_SET` are canceled.
+    pub fn set_realtime(&mut self, realtime: EmulatedTime) {
+        unsafe {
+            cshadow::host_setRealtime(self.chost, EmulatedTime::to_c_emutime(Some(realtime)))
+        };
+    }
+
+    /// The frequency adjustment in parts per million of the host's realtime clock, as set by
+    /// `adjtimex`. This is applied in addition to the clock's configured drift.
+    pub fn realtime_frequency_ppm(&self) -> f64 {
+        unsafe { cshadow::host_getRealtimeFrequencyPpm(self.chost) }
+    }
+
+    pub fn set_realtime_frequency_ppm(&mut self, frequency_ppm: f64) {
+        unsafe { cshadow::host_setRealtimeFrequencyPpm(self.chost, frequency_ppm) };
+    }
+
+    /// The number of nanoseconds by which the host's realtime clock is still to be gradually
+    /// slewed, as set by `adjtime`. Setting the clock cancels the slew.
+    pub fn realtime_slew(&self) -> i64 {
+        unsafe { cshadow::host_getRealtimeSlew(self.chost) }
+    }
+
+    pub fn set_realtime_slew(&mut self, slew: i64) {
+        unsafe { cshadow::host_setRealtimeSlew(self.chost, slew) };
+    }
+
+    /// The state of the host's realtime clock that `adjtimex` sets and reports, but that doesn't
+    /// otherwise affect the clock.
+    pub fn clock_status(&self) -> cshadow::HostClockStatus {
+        unsafe { cshadow::host_getClockStatus(self.chost) }
+    }
+
+    pub fn set_clock_status(&mut self, status: cshadow::HostClockStatus) {
+        unsafe { cshadow::host_setClockStatus(self.chost, status) };
+    }
+
     pub fn chost(&self) -> *mut cshadow::Host {
         self.chost
     }
@@ -38,6 +38,21 @@ struct _HostParameters {
     guint64 sendBufSize;
     gboolean autotuneSendBuf;
     guint64 interfaceBufSize;
+    TcpCongestionAlgorithm tcpCongestionControl;
+    gboolean useNewTcp;
+    guint64 fdLimit;
+    guint32 cpuCount;
+    guint64 ramTotal;
+    guint64 ramFree;
+    guint64 swapTotal;
+    guint64 swapFree;
+    // the 1, 5, and 15 minute load averages
+    gdouble loadAverage[3];
+    // the initial offset of the realtime clock from `startDatetime` in nanoseconds
+    gint64 clockOffset;
+    gdouble clockDriftPpm;
+    // the date and time at the start of the simulation
+    EmulatedTime startDatetime;
 };
 
 #endif
@@ -84,6 +84,57 @@ struct ShmFile {
 }
 
 impl ShmFile {
+    /// Create a new, empty memory file, and open it in the plugin process.
+    fn new(memory_manager: &mut MemoryManager, thread: &mut impl Thread) -> ShmFile {
+        let shm_path = format!(
+            "/dev/shm/shadow_memory_manager_{}_{}_{}",
+            process::id(),
+            u32::from(thread.host_id()),
+            u32::from(thread.process_id())
+        );
+        let shm_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&shm_path)
+            .unwrap();
+
+        // We don't need the file anymore in the file system. Unlinking it now
+        // ensures that it will be removed when there are no more open file
+        // descriptors to it.
+        match std::fs::remove_file(&shm_path) {
+            Ok(_) => (),
+            Err(e) => warn!("removing '{}': {}", shm_path, e),
+        }
+
+        // The file can no longer be accessed by its original path, but *can*
+        // be accessed via the file-descriptor link in /proc.
+        let shm_path = format!("/proc/{}/fd/{}\0", process::id(), shm_file.as_raw_fd());
+
+        let shm_plugin_fd = {
+            let path_buf_plugin_ptr = TypedPluginPtr::new::<u8>(
+                thread.malloc_plugin_ptr(shm_path.len()).unwrap(),
+                shm_path.len(),
+            );
+            memory_manager
+                .copy_to_ptr(path_buf_plugin_ptr, shm_path.as_bytes())
+                .unwrap();
+            let shm_plugin_fd = thread
+                .native_open(path_buf_plugin_ptr.ptr(), libc::O_RDWR | libc::O_CLOEXEC, 0)
+                .unwrap();
+            thread
+                .free_plugin_ptr(path_buf_plugin_ptr.ptr(), path_buf_plugin_ptr.len())
+                .unwrap();
+            shm_plugin_fd
+        };
+
+        ShmFile {
+            shm_file,
+            shm_plugin_fd,
+            len: 0,
+        }
+    }
+
     /// Allocate space in the file for the given interval.
     fn alloc(&mut self, interval: &Interval) {
         let needed_len = interval.end as libc::off_t;
@@ -156,6 +207,31 @@ impl ShmFile {
             .unwrap()
     }
 
+    /// Copy the given interval of `src` into the same interval of this file. Space for the
+    /// interval must already have been allocated in both files.
+    fn copy_from_file(&self, src: &ShmFile, interval: &Interval) {
+        let mut off_in = interval.start as libc::loff_t;
+        let mut off_out = interval.start as libc::loff_t;
+        let mut remaining = interval.len();
+        while remaining > 0 {
+            let rv = unsafe {
+                libc::copy_file_range(
+                    src.shm_file.as_raw_fd(),
+                    &mut off_in,
+                    self.shm_file.as_raw_fd(),
+                    &mut off_out,
+                    remaining,
+                    0,
+                )
+            };
+            if rv < 0 {
+                panic!("copy_file_range: {}", nix::errno::Errno::last());
+            }
+            assert!(rv > 0, "copy_file_range: unexpected end of file");
+            remaining -= rv as usize;
+        }
+    }
+
     /// Map the given range of the file into the plugin's address space.
     fn mmap_into_plugin(&self, thread: &mut dyn Thread, interval: &Interval, prot: i32) {
         thread
@@ -328,53 +404,7 @@ impl Drop for MemoryMapper {
 
 impl MemoryMapper {
     pub fn new(memory_manager: &mut MemoryManager, thread: &mut impl Thread) -> MemoryMapper {
-        let shm_path = format!(
-            "/dev/shm/shadow_memory_manager_{}_{}_{}",
-            process::id(),
-            u32::from(thread.host_id()),
-            u32::from(thread.process_id())
-        );
-        let shm_file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create_new(true)
-            .open(&shm_path)
-            .unwrap();
-
-        // We don't need the file anymore in the file system. Unlinking it now
-        // ensures that it will be removed when there are no more open file
-        // descriptors to it.
-        match std::fs::remove_file(&shm_path) {
-            Ok(_) => (),
-            Err(e) => warn!("removing '{}': {}", shm_path, e),
-        }
-
-        // The file can no longer be accessed by its original path, but *can*
-        // be accessed via the file-descriptor link in /proc.
-        let shm_path = format!("/proc/{}/fd/{}\0", process::id(), shm_file.as_raw_fd());
-
-        let shm_plugin_fd = {
-            let path_buf_plugin_ptr = TypedPluginPtr::new::<u8>(
-                thread.malloc_plugin_ptr(shm_path.len()).unwrap(),
-                shm_path.len(),
-            );
-            memory_manager
-                .copy_to_ptr(path_buf_plugin_ptr, shm_path.as_bytes())
-                .unwrap();
-            let shm_plugin_fd = thread
-                .native_open(path_buf_plugin_ptr.ptr(), libc::O_RDWR | libc::O_CLOEXEC, 0)
-                .unwrap();
-            thread
-                .free_plugin_ptr(path_buf_plugin_ptr.ptr(), path_buf_plugin_ptr.len())
-                .unwrap();
-            shm_plugin_fd
-        };
-
-        let mut shm_file = ShmFile {
-            shm_file,
-            shm_plugin_fd,
-            len: 0,
-        };
+        let mut shm_file = ShmFile::new(memory_manager, thread);
         let mut regions = get_regions(memory_manager.pid);
         let heap = get_heap(&mut shm_file, thread, memory_manager, &mut regions);
         map_stack(memory_manager, thread, &mut shm_file, &mut regions);
@@ -387,6 +417,44 @@ impl MemoryMapper {
         }
     }
 
+    /// Create a MemoryMapper for a child process that was just created by `fork`. The child
+    /// inherits our remapped regions as *shared* mappings of our memory file, so we copy their
+    /// contents into a new file and map it over the child's regions, giving the child its own
+    /// copy. `child_thread` must be running and ready to make native syscalls.
+    pub fn fork(
+        &self,
+        child_memory_manager: &mut MemoryManager,
+        child_thread: &mut impl Thread,
+    ) -> MemoryMapper {
+        let mut shm_file = ShmFile::new(child_memory_manager, child_thread);
+
+        let mut regions = IntervalMap::new();
+        for (interval, region) in self.regions.iter() {
+            let mut region = region.clone();
+            if !region.shadow_base.is_null() {
+                shm_file.alloc(&interval);
+                shm_file.copy_from_file(&self.shm_file, &interval);
+                region.shadow_base = shm_file.mmap_into_shadow(&interval, region.prot);
+                shm_file.mmap_into_plugin(child_thread, &interval, region.prot);
+            }
+            let mutations = regions.insert(interval, region);
+            // Regions shouldn't overlap.
+            assert_eq!(mutations.len(), 0);
+        }
+
+        // The child inherited our file descriptor for our memory file, which it no longer needs.
+        child_thread
+            .native_close(self.shm_file.shm_plugin_fd)
+            .unwrap_or_else(|e| warn!("close: {}", e));
+
+        MemoryMapper {
+            shm_file,
+            regions,
+            misses_by_path: RefCell::new(HashMap::new()),
+            heap: self.heap.clone(),
+        }
+    }
+
     /// Processes the mutations returned by an IntervalMap::insert or IntervalMap::clear operation.
     /// Each mutation describes a mapping that has been partly or completely overwritten (in the
     /// case of an insert) or cleared (in the case of clear).
@@ -550,6 +550,19 @@ impl MemoryManager {
         self.memory_mapper = Some(MemoryMapper::new(self, thread));
     }
 
+    /// Create a MemoryManager for a child process that was just created from this
+    /// process by `fork`. `child_thread` must be the child's only thread, and must be
+    /// running and ready to make native syscalls.
+    ///
+    /// SAFETY: See `MemoryManager::new`.
+    pub unsafe fn fork(&self, child_pid: Pid, child_thread: &mut impl Thread) -> Self {
+        let mut child = unsafe { Self::new(child_pid) };
+        if let Some(mapper) = &self.memory_mapper {
+            child.memory_mapper = Some(mapper.fork(&mut child, child_thread));
+        }
+        child
+    }
+
     /// Whether the internal MemoryMapper has been initialized.
     pub fn has_mapper(&self) -> bool {
         self.memory_mapper.is_some()
@@ -740,6 +753,22 @@ mod export {
         }))
     }
 
+    /// Create a MemoryManager for the child process `child_pid`, which was just created from
+    /// `mm`'s process by `fork`. `child_thread` must be the child's thread, and must be
+    /// running and ready to make native syscalls.
+    #[no_mangle]
+    pub unsafe extern "C" fn memorymanager_fork(
+        mm: *const MemoryManager,
+        child_pid: libc::pid_t,
+        child_thread: *mut c::Thread,
+    ) -> *mut MemoryManager {
+        let mm = unsafe { mm.as_ref() }.unwrap();
+        let mut child_thread = unsafe { CThread::new(notnull_mut_debug(child_thread)) };
+        Box::into_raw(Box::new(unsafe {
+            mm.fork(nix::unistd::Pid::from_raw(child_pid), &mut child_thread)
+        }))
+    }
+
     /// # Safety
     /// * `mm` must point to a valid object.
     #[no_mangle]
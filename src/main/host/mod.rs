@@ -1,7 +1,10 @@
 pub mod context;
+pub mod cpu_affinity;
+pub mod cpu_timer;
 pub mod descriptor;
 pub mod host;
 pub mod memory_manager;
+pub mod posix_timer;
 pub mod process;
 pub mod syscall;
 pub mod syscall_condition;
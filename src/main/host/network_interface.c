@@ -62,6 +62,8 @@ struct _NetworkInterface {
 
     /* To support capturing incoming and outgoing packets */
     PcapWriter_BufWriter_File* pcap;
+    /* The wall-clock time of captured packets at the start of the simulation */
+    EmulatedTime pcapStartTime;
 
     MAGIC_DECLARE;
 };
@@ -221,7 +223,7 @@ static void _networkinterface_capturePacket(NetworkInterface* interface, Packet*
     utility_assert(interface->pcap != NULL);
 
     /* get the current time that the packet is being sent/received */
-    SimulationTime now = worker_getCurrentSimulationTime();
+    EmulatedTime now = interface->pcapStartTime + worker_getCurrentSimulationTime();
     guint32 ts_sec = now / SIMTIME_ONE_SECOND;
     guint32 ts_usec = (now % SIMTIME_ONE_SECOND) / SIMTIME_ONE_MICROSECOND;
 
@@ -296,6 +298,10 @@ static void _networkinterface_process_packet_in(Host* host, NetworkInterface* in
         case CST_LEGACY_SOCKET:
             legacySocket = socket.object.as_legacy_socket;
             break;
+        case CST_INET_SOCKET:
+            /* the tracker only supports legacy sockets */
+            legacySocket = NULL;
+            break;
         case CST_NONE:
             legacySocket = NULL;
             break;
@@ -406,6 +412,10 @@ static Packet* _networkinterface_selectRoundRobin(NetworkInterface* interface, H
             case CST_LEGACY_SOCKET:
                 *socketOut = socket.object.as_legacy_socket;
                 break;
+            case CST_INET_SOCKET:
+                /* the tracker only supports legacy sockets */
+                *socketOut = NULL;
+                break;
             case CST_NONE:
                 *socketOut = NULL;
                 break;
@@ -449,6 +459,10 @@ static Packet* _networkinterface_selectFirstInFirstOut(NetworkInterface* interfa
             case CST_LEGACY_SOCKET:
                 *socketOut = socket.object.as_legacy_socket;
                 break;
+            case CST_INET_SOCKET:
+                /* the tracker only supports legacy sockets */
+                *socketOut = NULL;
+                break;
             case CST_NONE:
                 *socketOut = NULL;
                 break;
@@ -638,8 +652,8 @@ Router* networkinterface_getRouter(NetworkInterface* interface) {
 }
 
 NetworkInterface* networkinterface_new(Host* host, Address* address, gchar* pcapDir,
-                                       guint32 pcapCaptureSize, QDiscMode qdisc,
-                                       guint64 interfaceReceiveLength) {
+                                       guint32 pcapCaptureSize, EmulatedTime pcapStartTime,
+                                       QDiscMode qdisc, guint64 interfaceReceiveLength) {
     NetworkInterface* interface = g_new0(NetworkInterface, 1);
     MAGIC_INIT(interface);
 
@@ -670,6 +684,7 @@ NetworkInterface* networkinterface_new(Host* host, Address* address, gchar* pcap
                                address_toHostIPString(interface->address));
 
         interface->pcap = pcapwriter_new(filename->str, pcapCaptureSize);
+        interface->pcapStartTime = pcapStartTime;
         g_string_free(filename, TRUE);
     }
 
@@ -20,8 +20,8 @@
 typedef struct _NetworkInterface NetworkInterface;
 
 NetworkInterface* networkinterface_new(Host* host, Address* address, gchar* pcapDir,
-                                       guint32 pcapCaptureSize, QDiscMode qdisc,
-                                       guint64 interfaceReceiveLength);
+                                       guint32 pcapCaptureSize, EmulatedTime pcapStartTime,
+                                       QDiscMode qdisc, guint64 interfaceReceiveLength);
 void networkinterface_free(NetworkInterface* interface);
 
 gboolean networkinterface_isAssociated(NetworkInterface* interface, ProtocolType type,
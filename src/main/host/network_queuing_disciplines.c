@@ -80,10 +80,19 @@ void rrsocketqueue_push(RrSocketQueue* self, const CompatSocket* socket) {
     g_queue_push_tail(self->queue, (void*)compatsocket_toTagged(socket));
 }
 
+static gint _compareSocketHandleTagged(gconstpointer sa_tagged, gconstpointer sb_tagged) {
+    CompatSocket sa = compatsocket_fromTagged((uintptr_t)sa_tagged);
+    CompatSocket sb = compatsocket_fromTagged((uintptr_t)sb_tagged);
+
+    /* different references to the same socket may have different pointers */
+    return compatsocket_getCanonicalHandle(&sa) == compatsocket_getCanonicalHandle(&sb) ? 0 : 1;
+}
+
 bool rrsocketqueue_find(RrSocketQueue* self, const CompatSocket* socket) {
     utility_assert(self != NULL);
     utility_assert(self->queue != NULL);
-    return g_queue_find(self->queue, (void*)compatsocket_toTagged(socket));
+    return g_queue_find_custom(
+        self->queue, (void*)compatsocket_toTagged(socket), _compareSocketHandleTagged);
 }
 
 static gint _compareSocket(const CompatSocket* sa, const CompatSocket* sb) {
@@ -115,6 +124,9 @@ void fifosocketqueue_init(FifoSocketQueue* self) {
     utility_assert(self != NULL);
     utility_assert(self->queue == NULL);
     self->queue = priorityqueue_new((GCompareDataFunc)_compareSocketTagged, NULL, NULL);
+
+    utility_assert(self->handles == NULL);
+    self->handles = g_hash_table_new(g_direct_hash, g_direct_equal);
 }
 
 void fifosocketqueue_destroy(FifoSocketQueue* self, void (*fn_processItem)(const CompatSocket*)) {
@@ -137,6 +149,9 @@ void fifosocketqueue_destroy(FifoSocketQueue* self, void (*fn_processItem)(const
 
     priorityqueue_free(self->queue);
     self->queue = NULL;
+
+    g_hash_table_destroy(self->handles);
+    self->handles = NULL;
 }
 
 bool fifosocketqueue_isEmpty(FifoSocketQueue* self) {
@@ -168,6 +183,7 @@ bool fifosocketqueue_pop(FifoSocketQueue* self, CompatSocket* socket) {
     }
 
     *socket = compatsocket_fromTagged(taggedSocket);
+    g_hash_table_remove(self->handles, (void*)compatsocket_getCanonicalHandle(socket));
     return true;
 }
 
@@ -176,10 +192,11 @@ void fifosocketqueue_push(FifoSocketQueue* self, const CompatSocket* socket) {
     utility_assert(self->queue != NULL);
     utility_assert(socket->type != CST_NONE);
     priorityqueue_push(self->queue, (void*)compatsocket_toTagged(socket));
+    g_hash_table_add(self->handles, (void*)compatsocket_getCanonicalHandle(socket));
 }
 
 bool fifosocketqueue_find(FifoSocketQueue* self, const CompatSocket* socket) {
     utility_assert(self != NULL);
     utility_assert(self->queue != NULL);
-    return priorityqueue_find(self->queue, (void*)compatsocket_toTagged(socket));
+    return g_hash_table_contains(self->handles, (void*)compatsocket_getCanonicalHandle(socket));
 }
@@ -22,6 +22,9 @@ struct _RrSocketQueue {
 typedef struct _FifoSocketQueue FifoSocketQueue;
 struct _FifoSocketQueue {
     PriorityQueue* queue;
+    /* The canonical handles of the sockets in the queue. Different references to the same socket
+     * may have different pointers, so we can't search the queue for a socket's pointer. */
+    GHashTable* handles;
 };
 
 void rrsocketqueue_init(RrSocketQueue* self);
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+
+use crate::core::support::emulated_time::EmulatedTime;
+use crate::core::support::simulation_time::SimulationTime;
+use crate::cshadow as c;
+use crate::host::host::Host;
+use crate::host::process::ProcessId;
+use crate::host::timer::Timer;
+
+/// How a timer expiration is reported, as specified by the `struct sigevent` given to
+/// `timer_create()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimerNotification {
+    /// `SIGEV_NONE`: the expiration isn't reported.
+    None,
+    /// `SIGEV_SIGNAL`: send `signo` to the process.
+    Signal { signo: libc::c_int, value: usize },
+    /// `SIGEV_THREAD_ID`: send `signo` to the thread `tid`.
+    ThreadSignal {
+        signo: libc::c_int,
+        value: usize,
+        tid: libc::pid_t,
+    },
+}
+
+/// A POSIX per-process timer, as created by `timer_create()`.
+pub struct PosixTimer {
+    timer: Timer,
+    clock_id: libc::clockid_t,
+    /// The overrun count of the most recently generated signal.
+    overrun: Arc<AtomicI32>,
+}
+
+impl PosixTimer {
+    pub fn new(
+        process_id: ProcessId,
+        timer_id: libc::c_int,
+        clock_id: libc::clockid_t,
+        notification: TimerNotification,
+    ) -> Self {
+        let overrun = Arc::new(AtomicI32::new(0));
+
+        let timer = {
+            let overrun = Arc::clone(&overrun);
+            Timer::new(move |host| Self::expire(host, process_id, timer_id, notification, &overrun))
+        };
+
+        Self {
+            timer,
+            clock_id,
+            overrun,
+        }
+    }
+
+    pub fn clock_id(&self) -> libc::clockid_t {
+        self.clock_id
+    }
+
+    /// The time remaining until the next expiration (or zero if disarmed), and the interval.
+    pub fn get_time(&self) -> (SimulationTime, SimulationTime) {
+        let remaining = self.timer.remaining_time().unwrap_or(SimulationTime::ZERO);
+        (remaining, self.timer.expire_interval())
+    }
+
+    /// The number of additional expirations that occurred while the timer's most recent signal
+    /// was pending.
+    pub fn overrun(&self) -> libc::c_int {
+        self.overrun.load(Ordering::Relaxed)
+    }
+
+    /// Arm the timer to first expire at `expire_time`, and then every `interval` if the interval
+    /// is non-zero. An expiration time in the past expires immediately. If `realtime` is set,
+    /// `expire_time` is a value of the host's realtime clock, and the timer follows changes to
+    /// that clock.
+    pub fn arm(
+        &mut self,
+        host: &mut Host,
+        expire_time: EmulatedTime,
+        interval: SimulationTime,
+        realtime: bool,
+    ) {
+        if realtime {
+            self.timer.arm_realtime(host, expire_time, interval);
+        } else {
+            self.timer.arm(host, expire_time, interval);
+        }
+        self.overrun.store(0, Ordering::Relaxed);
+    }
+
+    /// Should be called when the host's realtime clock is set or its rate changes.
+    pub fn realtime_clock_changed(&mut self, host: &mut Host) {
+        self.timer.realtime_clock_changed(host);
+    }
+
+    pub fn disarm(&mut self) {
+        self.timer.disarm();
+        self.overrun.store(0, Ordering::Relaxed);
+    }
+
+    fn expire(
+        host: &mut Host,
+        process_id: ProcessId,
+        timer_id: libc::c_int,
+        notification: TimerNotification,
+        overrun: &AtomicI32,
+    ) {
+        let (signo, value, tid) = match notification {
+            TimerNotification::None => return,
+            TimerNotification::Signal { signo, value } => (signo, value, None),
+            TimerNotification::ThreadSignal { signo, value, tid } => (signo, value, Some(tid)),
+        };
+
+        let pid = u32::from(process_id).try_into().unwrap();
+        let cprocess = unsafe { c::host_getProcess(host.chost(), pid) };
+        if cprocess.is_null() {
+            log::trace!("Process {} for expired timer no longer exists", pid);
+            return;
+        }
+
+        let cthread = match tid {
+            Some(tid) => {
+                let cthread = unsafe { c::process_getThread(cprocess, tid) };
+                if cthread.is_null() {
+                    // the target thread has exited, so the signal is lost
+                    log::trace!("Thread {} for expired timer no longer exists", tid);
+                    return;
+                }
+                cthread
+            }
+            None => std::ptr::null_mut(),
+        };
+
+        let mut info: c::siginfo_t = unsafe { std::mem::zeroed() };
+        info.si_signo = signo;
+        info.si_code = libc::SI_TIMER;
+        info._sifields._timer = c::siginfo_t__bindgen_ty_1__bindgen_ty_2 {
+            si_tid: timer_id,
+            si_overrun: 0,
+            si_sigval: c::sigval {
+                sival_ptr: value as *mut libc::c_void,
+            },
+        };
+
+        let new_overrun = unsafe { c::process_signalTimer(cprocess, cthread, &info) };
+        overrun.store(new_overrun, Ordering::Relaxed);
+    }
+}
+
+/// The POSIX timers of a process.
+pub struct PosixTimerTable {
+    timers: BTreeMap<libc::c_int, PosixTimer>,
+}
+
+impl PosixTimerTable {
+    pub fn new() -> Self {
+        Self {
+            timers: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the lowest timer id that isn't in use.
+    pub fn unused_id(&self) -> libc::c_int {
+        let mut id = 0;
+        for used_id in self.timers.keys() {
+            if *used_id != id {
+                break;
+            }
+            id += 1;
+        }
+        id
+    }
+
+    /// Add a timer with the given id, and return the timer it replaced.
+    pub fn insert(&mut self, id: libc::c_int, timer: PosixTimer) -> Option<PosixTimer> {
+        self.timers.insert(id, timer)
+    }
+
+    pub fn get(&self, id: libc::c_int) -> Option<&PosixTimer> {
+        self.timers.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: libc::c_int) -> Option<&mut PosixTimer> {
+        self.timers.get_mut(&id)
+    }
+
+    pub fn remove(&mut self, id: libc::c_int) -> Option<PosixTimer> {
+        self.timers.remove(&id)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut PosixTimer> {
+        self.timers.values_mut()
+    }
+}
+
+impl Default for PosixTimerTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod export {
+    use super::*;
+
+    /// Create a new, empty POSIX timer table. Must be freed using `posixtimertable_free`.
+    #[no_mangle]
+    pub extern "C" fn posixtimertable_new() -> *mut PosixTimerTable {
+        Box::into_raw(Box::new(PosixTimerTable::new()))
+    }
+
+    /// Free the table, disarming all of its timers.
+    #[no_mangle]
+    pub extern "C" fn posixtimertable_free(table: *mut PosixTimerTable) {
+        if table.is_null() {
+            return;
+        }
+
+        unsafe { Box::from_raw(table) };
+    }
+}
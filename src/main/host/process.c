@@ -25,6 +25,7 @@
 #include <stdbool.h>
 #include <stddef.h>
 #include <sys/file.h>
+#include <sys/stat.h>
 #include <sys/types.h>
 #include <sys/uio.h>
 #include <sys/un.h>
@@ -46,10 +47,10 @@
 #include "main/host/descriptor/regular_file.h"
 #include "main/host/descriptor/socket.h"
 #include "main/host/descriptor/tcp.h"
-#include "main/host/descriptor/timerfd.h"
 #include "main/host/host.h"
 #include "main/host/process.h"
 #include "main/host/shimipc.h"
+#include "main/host/status_listener.h"
 #include "main/host/syscall_condition.h"
 #include "main/host/syscall_types.h"
 #include "main/host/thread.h"
@@ -83,6 +84,12 @@ struct _Process {
 
     /* unique id of the program that this process should run */
     guint processID;
+    /* id of the process that created this one with `fork`, or 0 if this process was started by
+     * shadow */
+    guint parentProcessID;
+    /* id of the process group; forked processes stay in their parent's group, and processes
+     * started by shadow lead their own group */
+    guint processGroupID;
     GString* processName;
 
     /* All of the descriptors opened by this process. */
@@ -125,6 +132,20 @@ struct _Process {
     gboolean didLogReturnCode;
     gboolean killedByShadow;
 
+    /* The status of the exited process, as returned by `wait`, and whether a
+     * `wait` has already consumed it. */
+    gboolean hasWaitStatus;
+    gint waitStatus;
+    gboolean isReaped;
+
+    /* Set while the process, created with `vfork`, hasn't yet called `execve`
+     * or exited. Its parent is blocked until then. */
+    bool isVforkPending;
+
+    /* Listeners waiting for a child process to change state. The key is a
+     * listener of type StatusListener*. */
+    GHashTable* childEventListeners;
+
     // int thread_id -> Thread*.
     GHashTable* threads;
 
@@ -152,6 +173,18 @@ struct _Process {
      */
     Timer* itimerReal;
 
+    /* Timers created with `timer_create`. */
+    PosixTimerTable* posixTimers;
+
+    /* ITIMER_VIRTUAL and ITIMER_PROF. These count down the process's modeled
+     * CPU time rather than the emulated time. */
+    CpuTimer* itimerVirtual;
+    CpuTimer* itimerProf;
+
+    /* The host's total modeled CPU latency when this process's CPU time was
+     * last charged to its CPU timers. */
+    SimulationTime cpuLatencyCheckpoint;
+
     /* Pause shadow after launching this process, to give the user time to attach gdb */
     bool pause_for_debugging;
 
@@ -213,6 +246,65 @@ guint process_getProcessID(Process* proc) {
     return proc->processID;
 }
 
+guint process_getParentProcessID(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->parentProcessID;
+}
+
+guint process_getProcessGroupID(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->processGroupID;
+}
+
+ShMemBlock* process_getSharedMemBlock(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return &proc->shimSharedMemBlock;
+}
+
+bool process_isVforkPending(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->isVforkPending && process_isRunning(proc);
+}
+
+void process_setVforkPending(Process* proc) {
+    MAGIC_ASSERT(proc);
+    proc->isVforkPending = true;
+}
+
+bool process_isWaitable(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->hasWaitStatus && !proc->isReaped;
+}
+
+bool process_isReaped(Process* proc) {
+    MAGIC_ASSERT(proc);
+    return proc->isReaped;
+}
+
+int process_getWaitStatus(Process* proc) {
+    MAGIC_ASSERT(proc);
+    utility_assert(proc->hasWaitStatus);
+    return proc->waitStatus;
+}
+
+void process_reap(Process* proc) {
+    MAGIC_ASSERT(proc);
+    utility_assert(proc->hasWaitStatus);
+    proc->isReaped = TRUE;
+}
+
+void process_addChildEventListener(Process* proc, StatusListener* listener) {
+    MAGIC_ASSERT(proc);
+    utility_assert(listener);
+    statuslistener_ref(listener);
+    g_hash_table_add(proc->childEventListeners, listener);
+}
+
+void process_removeChildEventListener(Process* proc, StatusListener* listener) {
+    MAGIC_ASSERT(proc);
+    g_hash_table_remove(proc->childEventListeners, listener); // Will unref the listener
+}
+
 pid_t process_getNativePid(const Process* proc) {
     MAGIC_ASSERT(proc);
     return proc->nativePid;
@@ -324,6 +416,14 @@ static void _process_handleProcessExit(Process* proc) {
         timer_drop(proc->itimerReal);
         proc->itimerReal = NULL;
     }
+
+    posixtimertable_free(proc->posixTimers);
+    proc->posixTimers = NULL;
+
+    cputimer_free(proc->itimerVirtual);
+    proc->itimerVirtual = NULL;
+    cputimer_free(proc->itimerProf);
+    proc->itimerProf = NULL;
 }
 
 static void _process_terminate(Process* proc) {
@@ -376,6 +476,8 @@ static void _process_getAndLogReturnCode(Process* proc) {
     } else if (rv != proc->nativePid) {
         warning("waitpid returned %d instead of the requested %d", rv, proc->nativePid);
     } else {
+        proc->hasWaitStatus = TRUE;
+        proc->waitStatus = wstatus;
         if (WIFEXITED(wstatus)) {
             proc->returnCode = WEXITSTATUS(wstatus);
         } else if (WIFSIGNALED(wstatus)) {
@@ -418,7 +520,8 @@ static void _process_getAndLogReturnCode(Process* proc) {
     // if there was no error or was intentionally killed
     // TODO: once we've implemented clean shutdown via SIGTERM,
     //       treat death by SIGKILL as a plugin error
-    if (proc->returnCode == 0 || proc->killedByShadow) {
+    // A forked process's exit status is for its parent to check with `wait`.
+    if (proc->returnCode == 0 || proc->killedByShadow || proc->parentProcessID) {
         info("%s", mainResultString->str);
     } else {
         warning("%s", mainResultString->str);
@@ -457,6 +560,69 @@ pid_t process_findNativeTID(Process* proc, pid_t virtualPID, pid_t virtualTID) {
     }
 }
 
+static void _process_notifyChildEventListeners(Process* proc) {
+    MAGIC_ASSERT(proc);
+
+    // We cannot use an iterator here, in case the hash table is modified
+    // in the status changed callback.
+    GList* listenerList = g_hash_table_get_keys(proc->childEventListeners);
+
+    // Notify the listeners in deterministic order.
+    listenerList = g_list_sort(listenerList, status_listener_compare);
+
+    for (GList* item = listenerList; item != NULL; item = g_list_next(item)) {
+        StatusListener* listener = item->data;
+        // Only call if the listener is still valid
+        if (g_hash_table_contains(proc->childEventListeners, listener)) {
+            statuslistener_onStatusChanged(listener, STATUS_CHILD_EVENT, STATUS_CHILD_EVENT);
+        }
+    }
+
+    g_list_free(listenerList);
+}
+
+static void _process_notifyParentOfExit(Process* proc) {
+    MAGIC_ASSERT(proc);
+
+    if (!proc->parentProcessID || proc->killedByShadow) {
+        // Nobody is going to wait for us.
+        proc->isReaped = TRUE;
+        return;
+    }
+
+    Process* parent = host_getProcess(proc->host, proc->parentProcessID);
+    if (!parent || !process_isRunning(parent)) {
+        // Orphaned processes are reaped by init.
+        proc->isReaped = TRUE;
+        return;
+    }
+
+    struct shd_kernel_sigaction action = shimshmem_getSignalAction(
+        host_getShimShmemLock(proc->host), process_getSharedMem(parent), SIGCHLD);
+    if (action.ksa_handler == SIG_IGN || (action.ksa_flags & SA_NOCLDWAIT)) {
+        // From wait(2): the children of such a parent don't become zombies,
+        // and a blocked `wait` fails with ECHILD once they've all exited.
+        proc->isReaped = TRUE;
+    }
+
+    if (action.ksa_handler != SIG_IGN) {
+        siginfo_t siginfo = {
+            .si_signo = SIGCHLD,
+            .si_code = CLD_EXITED,
+            .si_pid = proc->processID,
+            .si_uid = 0,
+            .si_status = proc->returnCode,
+        };
+        if (proc->hasWaitStatus && WIFSIGNALED(proc->waitStatus)) {
+            siginfo.si_code = WCOREDUMP(proc->waitStatus) ? CLD_DUMPED : CLD_KILLED;
+            siginfo.si_status = WTERMSIG(proc->waitStatus);
+        }
+        process_signal(parent, NULL, &siginfo);
+    }
+
+    _process_notifyChildEventListeners(parent);
+}
+
 static void _process_check(Process* proc) {
     MAGIC_ASSERT(proc);
 
@@ -473,6 +639,8 @@ static void _process_check(Process* proc) {
 
     descriptortable_shutdownHelper(proc->descTable);
     descriptortable_removeAndCloseAll(proc->descTable, proc->host);
+
+    _process_notifyParentOfExit(proc);
 }
 
 static void _process_check_thread(Process* proc, Thread* thread) {
@@ -511,7 +679,7 @@ static RegularFile* _process_openStdIOFileHelper(Process* proc, int fd, gchar* f
             "getcwd unable to allocate string buffer, error %i: %s", errno, strerror(errno));
     }
 
-    int errcode = regularfile_open(stdfile, fileName, O_WRONLY | O_CREAT | O_TRUNC,
+    int errcode = regularfile_open(stdfile, proc->host, fileName, O_WRONLY | O_CREAT | O_TRUNC,
                                    S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH, cwd);
     free(cwd);
 
@@ -524,6 +692,15 @@ static RegularFile* _process_openStdIOFileHelper(Process* proc, int fd, gchar* f
     return stdfile;
 }
 
+static gchar** _process_setEnvShMemBlock(gchar** envv, const char* name, ShMemBlock* block) {
+    ShMemBlockSerialized sharedMemBlockSerial = shmemallocator_globalBlockSerialize(block);
+
+    char sharedMemBlockBuf[SHD_SHMEM_BLOCK_SERIALIZED_MAX_STRLEN] = {0};
+    shmemblockserialized_toString(&sharedMemBlockSerial, sharedMemBlockBuf);
+
+    return g_environ_setenv(envv, name, sharedMemBlockBuf, TRUE);
+}
+
 static void _process_start(Process* proc) {
     MAGIC_ASSERT(proc);
 
@@ -572,16 +749,8 @@ static void _process_start(Process* proc) {
 #endif
 
     /* Add shared mem block of first thread to env */
-    {
-        ShMemBlockSerialized sharedMemBlockSerial =
-            shmemallocator_globalBlockSerialize(thread_getShMBlock(mainThread));
-
-        char sharedMemBlockBuf[SHD_SHMEM_BLOCK_SERIALIZED_MAX_STRLEN] = {0};
-        shmemblockserialized_toString(&sharedMemBlockSerial, sharedMemBlockBuf);
-
-        /* append to the env */
-        proc->envv = g_environ_setenv(proc->envv, "SHADOW_SHM_THREAD_BLK", sharedMemBlockBuf, TRUE);
-    }
+    proc->envv =
+        _process_setEnvShMemBlock(proc->envv, "SHADOW_SHM_THREAD_BLK", thread_getShMBlock(mainThread));
 
     proc->plugin.isExecuting = TRUE;
     _process_setSharedTime(proc);
@@ -687,10 +856,16 @@ void process_continue(Process* proc, Thread* thread) {
     _process_setSharedTime(proc);
 
     shimshmem_resetUnappliedCpuLatency(host_getShimShmemLock(proc->host));
+    // Latency modeled while other processes were running isn't ours.
+    proc->cpuLatencyCheckpoint = shimshmem_getTotalCpuLatency(host_getShimShmemLock(proc->host));
     proc->plugin.isExecuting = TRUE;
     thread_resume(thread);
     proc->plugin.isExecuting = FALSE;
 
+    // Charge any latency that the shim modeled without making a syscall to
+    // shadow.
+    process_updateCpuTime(proc, NULL);
+
 #ifdef USE_PERF_TIMERS
     gdouble elapsed = g_timer_elapsed(proc->cpuDelayTimer, NULL);
     _process_handleTimerResult(proc, elapsed);
@@ -812,6 +987,7 @@ Process* process_new(Host* host, guint processID, SimulationTime startTime, Simu
     host_ref(proc->host);
 
     proc->processID = processID;
+    proc->processGroupID = processID;
 
     /* plugin name and path are required so we know what to execute */
     utility_assert(pluginName);
@@ -852,16 +1028,8 @@ Process* process_new(Host* host, guint processID, SimulationTime startTime, Simu
 
     envv = g_strdupv(envv);
 
-    {
-        ShMemBlockSerialized sharedMemBlockSerial =
-            shmemallocator_globalBlockSerialize(&proc->shimSharedMemBlock);
-
-        char sharedMemBlockBuf[SHD_SHMEM_BLOCK_SERIALIZED_MAX_STRLEN] = {0};
-        shmemblockserialized_toString(&sharedMemBlockSerial, sharedMemBlockBuf);
-
-        /* append to the env */
-        envv = g_environ_setenv(envv, "SHADOW_SHM_PROCESS_BLK", sharedMemBlockBuf, TRUE);
-    }
+    /* append to the env */
+    envv = _process_setEnvShMemBlock(envv, "SHADOW_SHM_PROCESS_BLK", &proc->shimSharedMemBlock);
 
     /* add log file to env */
     {
@@ -879,9 +1047,12 @@ Process* process_new(Host* host, guint processID, SimulationTime startTime, Simu
     proc->envv = envv;
 
     proc->descTable = descriptortable_new();
+    descriptortable_setFdLimit(proc->descTable, host_getFdLimit(host));
 
     proc->threads =
         g_hash_table_new_full(g_direct_hash, g_direct_equal, NULL, _thread_gpointer_unref);
+    proc->childEventListeners = g_hash_table_new_full(
+        g_direct_hash, g_direct_equal, (GDestroyNotify)statuslistener_unref, NULL);
 
     proc->referenceCount = 1;
     proc->isExiting = false;
@@ -901,11 +1072,251 @@ Process* process_new(Host* host, guint processID, SimulationTime startTime, Simu
     // timer_new clones the task; we don't need our own reference anymore.
     taskref_drop(task);
 
+    proc->posixTimers = posixtimertable_new();
+    proc->itimerVirtual = cputimer_new();
+    proc->itimerProf = cputimer_new();
+
     worker_count_allocation(Process);
 
     return proc;
 }
 
+static Process* _process_newForked(Process* parent, guint processID) {
+    MAGIC_ASSERT(parent);
+
+    Process* proc = g_new0(Process, 1);
+    MAGIC_INIT(proc);
+
+    proc->host = parent->host;
+    host_ref(proc->host);
+
+    proc->processID = processID;
+    proc->parentProcessID = parent->processID;
+    proc->processGroupID = parent->processGroupID;
+
+    proc->plugin.exeName = g_string_new(parent->plugin.exeName->str);
+    proc->plugin.exePath = g_string_new(parent->plugin.exePath->str);
+
+    proc->processName = g_string_new(NULL);
+    g_string_printf(proc->processName, "%s.%s.%u", host_getName(proc->host),
+                    proc->plugin.exeName->str, proc->processID);
+
+#ifdef USE_PERF_TIMERS
+    proc->cpuDelayTimer = g_timer_new();
+#endif
+
+    /* the child runs until it exits, or until the simulation ends */
+    proc->startTime = worker_getCurrentEmulatedTime();
+    proc->stopTime = EMUTIME_INVALID;
+
+    proc->workingDir = strdup(parent->workingDir);
+
+    proc->shimSharedMemBlock = shmemallocator_globalAlloc(shimshmemprocess_size());
+    shimshmemprocess_init(proc->shimSharedMemBlock.p, proc);
+
+    /* signal dispositions are inherited; pending signals are not */
+    ShimShmemHostLock* lock = host_getShimShmemLock(proc->host);
+    for (int sig = 1; sig <= SHD_SIGRT_MAX; sig++) {
+        struct shd_kernel_sigaction action =
+            shimshmem_getSignalAction(lock, process_getSharedMem(parent), sig);
+        shimshmem_setSignalAction(lock, process_getSharedMem(proc), sig, &action);
+    }
+
+    proc->argv = g_strdupv(parent->argv);
+    proc->envv = g_strdupv(parent->envv);
+
+    /* the child's descriptors refer to the same open files as the parent's */
+    proc->descTable = descriptortable_fork(parent->descTable);
+
+    proc->threads =
+        g_hash_table_new_full(g_direct_hash, g_direct_equal, NULL, _thread_gpointer_unref);
+    proc->childEventListeners = g_hash_table_new_full(
+        g_direct_hash, g_direct_equal, (GDestroyNotify)statuslistener_unref, NULL);
+
+    proc->referenceCount = 1;
+    proc->isExiting = false;
+
+    proc->straceLoggingMode = parent->straceLoggingMode;
+    proc->straceFd = -1;
+    if (proc->straceLoggingMode != STRACE_FMT_MODE_OFF) {
+        gchar* straceFileName = _process_outputFileName(proc, "strace");
+        proc->straceFd = open(straceFileName, O_CREAT | O_TRUNC | O_WRONLY | O_CLOEXEC,
+                              S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH);
+        g_free(straceFileName);
+    }
+
+    proc->memoryMutRef = NULL;
+    proc->memoryRefs = g_array_new(FALSE, FALSE, sizeof(ProcessMemoryRef_u8*));
+
+    /* interval timers are not inherited */
+    process_ref(proc);
+    TaskRef* task = taskref_new_bound(host_getID(proc->host), _process_itimer_real_expiration,
+                                      proc, NULL, _unref_process_cb, NULL);
+    proc->itimerReal = timer_new(task);
+    // timer_new clones the task; we don't need our own reference anymore.
+    taskref_drop(task);
+
+    proc->posixTimers = posixtimertable_new();
+    proc->itimerVirtual = cputimer_new();
+    proc->itimerProf = cputimer_new();
+
+    worker_count_allocation(Process);
+
+    return proc;
+}
+
+int process_fork(Process* parent, Thread* parentThread, unsigned long flags, unsigned long newtls,
+                 Process** childp) {
+    MAGIC_ASSERT(parent);
+
+    // Make sure the child's copy of our memory includes any pending writes.
+    process_flushPtrs(parent);
+
+    Process* child = _process_newForked(parent, host_getNewProcessID(parent->host));
+
+    Thread* childThread = NULL;
+    pid_t childNativePid = thread_fork(parentThread, child, flags, newtls, &childThread);
+    if (childNativePid < 0) {
+        // Break the timer's circular reference so that the child is freed.
+        timer_drop(child->itimerReal);
+        child->itimerReal = NULL;
+        process_unref(child);
+        return childNativePid;
+    }
+    utility_assert(childThread);
+
+    child->nativePid = childNativePid;
+    child->memoryManager =
+        memorymanager_fork(parent->memoryManager, childNativePid, childThread);
+
+    /* the calling thread's signal mask and alternate signal stack are inherited */
+    ShimShmemHostLock* lock = host_getShimShmemLock(parent->host);
+    shimshmem_setBlockedSignals(
+        lock, thread_sharedMem(childThread),
+        shimshmem_getBlockedSignals(lock, thread_sharedMem(parentThread)));
+    shimshmem_setSigAltStack(lock, thread_sharedMem(childThread),
+                             shimshmem_getSigAltStack(lock, thread_sharedMem(parentThread)));
+
+    info("process '%s' forked new process '%s'", process_getName(parent), process_getName(child));
+
+    host_addChildProcess(parent->host, child);
+
+    // Adds the thread to the child process and schedules it to run.
+    process_addThread(child, childThread);
+
+    *childp = child;
+
+    // The host holds a reference to the child now.
+    process_unref(child);
+
+    return childNativePid;
+}
+
+// Returns whether `var` (a "NAME=value" string) is one of the variables that
+// shadow sets for the shim.
+static bool _process_isShimEnvVar(const char* var) {
+    return g_str_has_prefix(var, "SHADOW_") || g_str_has_prefix(var, "LD_PRELOAD=");
+}
+
+int process_execve(Process* proc, Thread* thread, const char* pathname, char** argv,
+                   char** envv) {
+    MAGIC_ASSERT(proc);
+
+    if (g_hash_table_size(proc->threads) > 1) {
+        // We'd need to kill the other threads, and make the calling thread the
+        // thread group leader.
+        warning("execve from a process with more than one thread is unsupported");
+        return -ENOTSUP;
+    }
+
+    gchar* path = g_path_is_absolute(pathname) ? g_strdup(pathname)
+                                               : g_build_filename(proc->workingDir, pathname, NULL);
+
+    // Check the errors that execve would return before we replace the native
+    // process, since there's no way back after that.
+    struct stat statbuf;
+    if (stat(path, &statbuf) < 0) {
+        int err = errno;
+        g_free(path);
+        return -err;
+    }
+    if (!S_ISREG(statbuf.st_mode)) {
+        g_free(path);
+        return -EACCES;
+    }
+    if (access(path, X_OK) < 0) {
+        int err = errno;
+        g_free(path);
+        return -err;
+    }
+
+    // The new program gets the environment it was given, but with shadow's
+    // variables for the shim, which refer to this process and thread.
+    GPtrArray* vars = g_ptr_array_new();
+    for (gchar** var = envv; var && *var; var++) {
+        if (!_process_isShimEnvVar(*var)) {
+            g_ptr_array_add(vars, g_strdup(*var));
+        }
+    }
+    for (gchar** var = proc->envv; var && *var; var++) {
+        if (_process_isShimEnvVar(*var)) {
+            g_ptr_array_add(vars, g_strdup(*var));
+        }
+    }
+    g_ptr_array_add(vars, NULL);
+    gchar** newEnvv = (gchar**)g_ptr_array_free(vars, FALSE);
+    newEnvv =
+        _process_setEnvShMemBlock(newEnvv, "SHADOW_SHM_PROCESS_BLK", &proc->shimSharedMemBlock);
+    newEnvv =
+        _process_setEnvShMemBlock(newEnvv, "SHADOW_SHM_THREAD_BLK", thread_getShMBlock(thread));
+    {
+        gchar* logFileName = _process_outputFileName(proc, "shimlog");
+        newEnvv = g_environ_setenv(newEnvv, "SHADOW_LOG_FILE", logFileName, TRUE);
+        g_free(logFileName);
+    }
+
+    info("process '%s' is executing '%s'", process_getName(proc), path);
+
+    // Pending reads and writes refer to the old program's memory.
+    process_freePtrsWithoutFlushing(proc);
+
+    proc->nativePid = thread_execve(thread, path, argv, newEnvv, proc->workingDir);
+    process_setMemoryManager(proc, memorymanager_new(proc->nativePid));
+
+    g_string_assign(proc->plugin.exePath, path);
+    g_strfreev(proc->argv);
+    proc->argv = g_strdupv(argv);
+    g_strfreev(proc->envv);
+    proc->envv = newEnvv;
+    g_free(path);
+
+    descriptortable_removeAndCloseCloexec(proc->descTable, proc->host);
+
+    // Handled signals are reset to their default action; ignored signals stay
+    // ignored. See execve(2).
+    ShimShmemHostLock* lock = host_getShimShmemLock(proc->host);
+    for (int sig = 1; sig <= SHD_SIGRT_MAX; sig++) {
+        struct shd_kernel_sigaction action =
+            shimshmem_getSignalAction(lock, process_getSharedMem(proc), sig);
+        if (action.ksa_handler != SIG_IGN && action.ksa_handler != SIG_DFL) {
+            action = (struct shd_kernel_sigaction){.ksa_handler = SIG_DFL};
+            shimshmem_setSignalAction(lock, process_getSharedMem(proc), sig, &action);
+        }
+    }
+    shimshmem_setSigAltStack(lock, thread_sharedMem(thread), (stack_t){.ss_flags = SS_DISABLE});
+
+    if (proc->isVforkPending) {
+        // The parent that created us with `vfork` can continue now.
+        proc->isVforkPending = false;
+        Process* parent = host_getProcess(proc->host, proc->parentProcessID);
+        if (parent) {
+            _process_notifyChildEventListeners(parent);
+        }
+    }
+
+    return 0;
+}
+
 static void _process_free(Process* proc) {
     MAGIC_ASSERT(proc);
 
@@ -917,6 +1328,10 @@ static void _process_free(Process* proc) {
         g_hash_table_destroy(proc->threads);
         proc->threads = NULL;
     }
+    if (proc->childEventListeners) {
+        g_hash_table_destroy(proc->childEventListeners);
+        proc->childEventListeners = NULL;
+    }
     if(proc->plugin.exePath) {
         g_string_free(proc->plugin.exePath, TRUE);
     }
@@ -970,6 +1385,14 @@ static void _process_free(Process* proc) {
         proc->itimerReal = NULL;
     }
 
+    posixtimertable_free(proc->posixTimers);
+    proc->posixTimers = NULL;
+
+    cputimer_free(proc->itimerVirtual);
+    proc->itimerVirtual = NULL;
+    cputimer_free(proc->itimerProf);
+    proc->itimerProf = NULL;
+
     shmemallocator_globalFree(&proc->shimSharedMemBlock);
 
     worker_count_deallocation(Process);
@@ -1254,6 +1677,23 @@ static void _process_interruptWithSignal(Process* process, ShimShmemHostLock* ho
     }
 }
 
+// Returns true if every thread in the process has `signo` blocked.
+static bool _process_allThreadsBlockSignal(Process* process, ShimShmemHostLock* hostLock,
+                                           int signo) {
+    GHashTableIter iter;
+    gpointer key, value;
+    g_hash_table_iter_init(&iter, process->threads);
+    while (g_hash_table_iter_next(&iter, &key, &value)) {
+        Thread* thread = value;
+        shd_kernel_sigset_t blocked_signals =
+            shimshmem_getBlockedSignals(hostLock, thread_sharedMem(thread));
+        if (!shd_sigismember(&blocked_signals, signo)) {
+            return false;
+        }
+    }
+    return true;
+}
+
 void process_signal(Process* process, Thread* currentRunningThread, const siginfo_t* siginfo) {
     MAGIC_ASSERT(process);
     utility_assert(siginfo->si_signo >= 0);
@@ -1282,10 +1722,14 @@ void process_signal(Process* process, Thread* currentRunningThread, const siginf
 
     struct shd_kernel_sigaction action = shimshmem_getSignalAction(
         host_getShimShmemLock(process->host), process_getSharedMem(process), siginfo->si_signo);
-    if (action.ksa_handler == SIG_IGN ||
-        (action.ksa_handler == SIG_DFL &&
-         shd_defaultAction(siginfo->si_signo) == SHD_DEFAULT_ACTION_IGN)) {
-        // Don't deliver an ignored signal.
+    if ((action.ksa_handler == SIG_IGN ||
+         (action.ksa_handler == SIG_DFL &&
+          shd_defaultAction(siginfo->si_signo) == SHD_DEFAULT_ACTION_IGN)) &&
+        !_process_allThreadsBlockSignal(
+            process, host_getShimShmemLock(process->host), siginfo->si_signo)) {
+        // Don't deliver an ignored signal. Blocked signals are left pending even if ignored, since
+        // the disposition may change before they're unblocked, and they may be consumed through a
+        // signalfd.
         return;
     }
 
@@ -1304,6 +1748,7 @@ void process_signal(Process* process, Thread* currentRunningThread, const siginf
         host_getShimShmemLock(process->host), process_getSharedMem(process), pending_signals);
     shimshmem_setProcessSiginfo(host_getShimShmemLock(process->host), process_getSharedMem(process),
                                 siginfo->si_signo, siginfo);
+    process_notifySignalFds(process);
 
     if (currentRunningThread != NULL && thread_getProcess(currentRunningThread) == process) {
         shd_kernel_sigset_t blocked_signals = shimshmem_getBlockedSignals(
@@ -1318,7 +1763,204 @@ void process_signal(Process* process, Thread* currentRunningThread, const siginf
     _process_interruptWithSignal(process, host_getShimShmemLock(process->host), siginfo->si_signo);
 }
 
+void process_signalThread(Process* process, Thread* currentRunningThread, Thread* thread,
+                          const siginfo_t* siginfo) {
+    MAGIC_ASSERT(process);
+    utility_assert(thread_getProcess(thread) == process);
+    utility_assert(siginfo->si_signo >= 0);
+    utility_assert(siginfo->si_signo <= SHD_SIGRT_MAX);
+    utility_assert(siginfo->si_signo <= SHD_STANDARD_SIGNAL_MAX_NO);
+
+    int sig = siginfo->si_signo;
+    if (sig == 0) {
+        return;
+    }
+
+    if (!shimipc_getUseSeccomp()) {
+        // ~legacy ptrace path. Send a real signal to the thread.
+        if (!currentRunningThread) {
+            error("Sending a signal to a thread in ptrace-mode is unimplemented. Signal %d to "
+                  "thread %d lost.",
+                  sig, thread_getID(thread));
+        } else {
+            long res = thread_nativeSyscall(currentRunningThread, SYS_tgkill, process->nativePid,
+                                            thread_getNativePid(thread), sig);
+            if (res != 0) {
+                error("Sending signal to thread: %s", strerror(-res));
+            }
+        }
+        return;
+    }
+
+    ShimShmemHostLock* hostLock = host_getShimShmemLock(process->host);
+
+    struct shd_kernel_sigaction action =
+        shimshmem_getSignalAction(hostLock, process_getSharedMem(process), sig);
+    shd_kernel_sigset_t blocked_signals =
+        shimshmem_getBlockedSignals(hostLock, thread_sharedMem(thread));
+    if ((action.ksa_handler == SIG_IGN ||
+         (action.ksa_handler == SIG_DFL && shd_defaultAction(sig) == SHD_DEFAULT_ACTION_IGN)) &&
+        !shd_sigismember(&blocked_signals, sig)) {
+        // Don't deliver an ignored signal. Blocked signals are left pending even if ignored, since
+        // the disposition may change before they're unblocked, and they may be consumed through a
+        // signalfd.
+        return;
+    }
+
+    shd_kernel_sigset_t pending_signals =
+        shimshmem_getThreadPendingSignals(hostLock, thread_sharedMem(thread));
+
+    if (shd_sigismember(&pending_signals, sig)) {
+        // Signal is already pending. From signal(7):In the case where a standard signal is already
+        // pending, the siginfo_t structure (see sigaction(2)) associated with  that  signal is not
+        // overwritten on arrival of subsequent instances of the same signal.
+        return;
+    }
+
+    shd_sigaddset(&pending_signals, sig);
+    shimshmem_setThreadPendingSignals(hostLock, thread_sharedMem(thread), pending_signals);
+    shimshmem_setThreadSiginfo(hostLock, thread_sharedMem(thread), sig, siginfo);
+    process_notifySignalFds(process);
+
+    if (thread == currentRunningThread) {
+        // Target is the current thread. It'll be handled synchronously when the
+        // current syscall returns (if it's unblocked).
+        return;
+    }
+
+    if (shd_sigismember(&blocked_signals, sig)) {
+        // Target thread has the signal blocked. We'll leave it pending, but no
+        // need to schedule an event to process the signal. It'll get processed
+        // synchronously when the thread executes a syscall that would unblock
+        // the signal.
+        return;
+    }
+
+    SysCallCondition* cond = thread_getSysCallCondition(thread);
+    if (cond == NULL) {
+        // We may be able to get here if a thread is signalled before it runs
+        // for the first time. Just return; the signal will be delivered when
+        // the thread runs.
+        return;
+    }
+    syscallcondition_wakeupForSignal(cond, hostLock, sig);
+}
+
+int process_signalTimer(Process* process, Thread* thread, const siginfo_t* siginfo) {
+    MAGIC_ASSERT(process);
+    utility_assert(siginfo->si_code == SI_TIMER);
+
+    int sig = siginfo->si_signo;
+    ShimShmemHostLock* hostLock = host_getShimShmemLock(process->host);
+
+    shd_kernel_sigset_t pending_signals =
+        thread ? shimshmem_getThreadPendingSignals(hostLock, thread_sharedMem(thread))
+               : shimshmem_getProcessPendingSignals(hostLock, process_getSharedMem(process));
+
+    if (shimipc_getUseSeccomp() && shd_sigismember(&pending_signals, sig)) {
+        siginfo_t pending_info =
+            thread ? shimshmem_getThreadSiginfo(hostLock, thread_sharedMem(thread), sig)
+                   : shimshmem_getProcessSiginfo(hostLock, process_getSharedMem(process), sig);
+
+        if (pending_info.si_code != SI_TIMER || pending_info.si_timerid != siginfo->si_timerid) {
+            // The signal is already pending from some other source, so this one is lost.
+            return 0;
+        }
+
+        // timer_getoverrun(2): "only one signal is queued to the process for a given timer at any
+        // point in time. [...] an overrun occurs" instead.
+        if (pending_info.si_overrun < DELAYTIMER_MAX) {
+            pending_info.si_overrun++;
+        }
+
+        if (thread) {
+            shimshmem_setThreadSiginfo(hostLock, thread_sharedMem(thread), sig, &pending_info);
+        } else {
+            shimshmem_setProcessSiginfo(hostLock, process_getSharedMem(process), sig, &pending_info);
+        }
+
+        return pending_info.si_overrun;
+    }
+
+    if (thread) {
+        process_signalThread(process, NULL, thread, siginfo);
+    } else {
+        process_signal(process, NULL, siginfo);
+    }
+
+    return siginfo->si_overrun;
+}
+
 Timer* process_getRealtimeTimer(Process* process) {
     MAGIC_ASSERT(process);
     return process->itimerReal;
 }
+
+PosixTimerTable* process_getPosixTimers(Process* process) {
+    MAGIC_ASSERT(process);
+    return process->posixTimers;
+}
+
+CpuTimer* process_getVirtualTimer(Process* process) {
+    MAGIC_ASSERT(process);
+    return process->itimerVirtual;
+}
+
+CpuTimer* process_getProfTimer(Process* process) {
+    MAGIC_ASSERT(process);
+    return process->itimerProf;
+}
+
+void process_updateCpuTime(Process* process, Thread* currentRunningThread) {
+    MAGIC_ASSERT(process);
+
+    SimulationTime total = shimshmem_getTotalCpuLatency(host_getShimShmemLock(process->host));
+    SimulationTime cpuTime = total - process->cpuLatencyCheckpoint;
+    process->cpuLatencyCheckpoint = total;
+
+    // The timers are freed when the process exits.
+    if (cpuTime == 0 || !process_isRunning(process)) {
+        return;
+    }
+
+    // We don't distinguish time spent in the process from time spent in the
+    // kernel on its behalf, so both timers count the same CPU time.
+    if (cputimer_consume(process->itimerVirtual, cpuTime) > 0) {
+        process_signal(process, currentRunningThread,
+                       &(siginfo_t){.si_signo = SIGVTALRM, .si_code = SI_KERNEL});
+    }
+    if (cputimer_consume(process->itimerProf, cpuTime) > 0) {
+        process_signal(process, currentRunningThread,
+                       &(siginfo_t){.si_signo = SIGPROF, .si_code = SI_KERNEL});
+    }
+}
+
+shd_kernel_sigset_t process_getPendingSignals(Process* process) {
+    MAGIC_ASSERT(process);
+    ShimShmemHostLock* hostLock = host_getShimShmemLock(process->host);
+
+    shd_kernel_sigset_t pending_signals =
+        shimshmem_getProcessPendingSignals(hostLock, process_getSharedMem(process));
+
+    GHashTableIter iter;
+    gpointer key, value;
+    g_hash_table_iter_init(&iter, process->threads);
+    while (g_hash_table_iter_next(&iter, &key, &value)) {
+        Thread* thread = value;
+        shd_kernel_sigset_t thread_pending_signals =
+            shimshmem_getThreadPendingSignals(hostLock, thread_sharedMem(thread));
+        pending_signals = shd_sigorset(&pending_signals, &thread_pending_signals);
+    }
+
+    return pending_signals;
+}
+
+int process_takePendingSignal(Process* process, Thread* thread, shd_kernel_sigset_t set,
+                              siginfo_t* info) {
+    MAGIC_ASSERT(process);
+    utility_assert(thread_getProcess(thread) == process);
+
+    return shimshmem_takePendingSignal(host_getShimShmemLock(process->host),
+                                       process_getSharedMem(process), thread_sharedMem(thread),
+                                       set, info);
+}
@@ -35,7 +35,7 @@ typedef struct _Process Process;
 #include "main/bindings/c/bindings.h"
 #include "main/core/support/definitions.h"
 #include "main/host/descriptor/descriptor_types.h"
-#include "main/host/descriptor/timerfd.h"
+#include "main/host/status_listener.h"
 #include "main/host/syscall_handler.h"
 #include "main/host/syscall_types.h"
 #include "main/host/thread.h"
@@ -46,6 +46,25 @@ Process* process_new(Host* host, guint processID, SimulationTime startTime, Simu
 void process_ref(Process* proc);
 void process_unref(Process* proc);
 
+// Create a new process on the same host as `parent`, as for `fork(2)`, by
+// natively cloning the process with the given `clone(2)` flags (which must not
+// include CLONE_VM) from `parentThread`. The new process gets a copy of the
+// parent's descriptor table, memory, and signal dispositions, and a new
+// process id. The new process is added to the host, and its thread is scheduled
+// to run. Returns the native pid of the new process and sets `child` to the
+// (borrowed) new process, or returns a negative errno.
+int process_fork(Process* parent, Thread* parentThread, unsigned long flags, unsigned long newtls,
+                 Process** child);
+
+// Replace the program that the process is running, as for a successful
+// `execve(2)` from `thread`, which must be the process's only thread. The
+// descriptors with `FD_CLOEXEC` set are closed, and handled signals are reset
+// to their default action. The new program starts running when `thread` is
+// next resumed. Returns 0, or a negative errno if the program can't be run, in
+// which case the process is unchanged.
+int process_execve(Process* proc, Thread* thread, const char* pathname, char** argv,
+                   char** envv);
+
 void process_schedule(Process* proc, gpointer nothing);
 void process_continue(Process* proc, Thread* thread);
 void process_stop(Process* proc);
@@ -87,6 +106,37 @@ const gchar* process_getPluginName(Process* proc);
 /* Returns the processID that was assigned to us in process_new */
 guint process_getProcessID(Process* proc);
 
+/* Returns the processID of the process that created this one with `fork`, or 0
+ * if this process was started by shadow. */
+guint process_getParentProcessID(Process* proc);
+
+/* Returns the ID of the process group that this process belongs to. Forked
+ * processes stay in their parent's group. */
+guint process_getProcessGroupID(Process* proc);
+
+/* Whether the process was created with `vfork` and hasn't yet called `execve`
+ * or exited. Its parent's child event listeners are notified when that
+ * changes. */
+bool process_isVforkPending(Process* proc);
+/* Mark a new process as created with `vfork`. */
+void process_setVforkPending(Process* proc);
+
+/* Whether the process has exited and can be reaped by its parent with `wait`. */
+bool process_isWaitable(Process* proc);
+/* Whether the process has exited and been reaped, either by its parent or
+ * automatically. */
+bool process_isReaped(Process* proc);
+/* The exit status of an exited process, encoded as for `wait(2)`. */
+int process_getWaitStatus(Process* proc);
+/* Mark an exited process as reaped. */
+void process_reap(Process* proc);
+
+/* Add a listener that is notified with STATUS_CHILD_EVENT whenever one of the
+ * process's children changes state. */
+void process_addChildEventListener(Process* proc, StatusListener* listener);
+/* Remove a listener added with process_addChildEventListener. */
+void process_removeChildEventListener(Process* proc, StatusListener* listener);
+
 /* Returns the native pid of the process */
 pid_t process_getNativePid(const Process* proc);
 
@@ -182,12 +232,55 @@ void process_parseArgStrFree(char** argv, char* error);
 // Process state kept in memory shared with the managed process's shim.
 ShimShmemProcess* process_getSharedMem(Process* proc);
 
+// The block backing `process_getSharedMem`.
+ShMemBlock* process_getSharedMemBlock(Process* proc);
+
 // Send the signal described in `siginfo` to `process`. `currentRunningThread`
 // should be set if there is one (e.g. if this is being called from a syscall
 // handler), and NULL otherwise (e.g. when called from a timer expiration event).
 void process_signal(Process* process, Thread* currentRunningThread, const siginfo_t* siginfo);
 
+// Send the signal described in `siginfo` to `thread` in `process`.
+// `currentRunningThread` should be set if there is one (e.g. if this is being
+// called from a syscall handler), and NULL otherwise.
+void process_signalThread(Process* process, Thread* currentRunningThread, Thread* thread,
+                          const siginfo_t* siginfo);
+
+// Send the expiration signal of a POSIX timer, described in `siginfo`, to
+// `thread`, or to `process` if `thread` is NULL. If the timer's previous
+// signal is still pending, its overrun count is incremented instead.
+//
+// Returns the overrun count of the timer's pending signal.
+int process_signalTimer(Process* process, Thread* thread, const siginfo_t* siginfo);
+
 // Access the process's realtime timer; e.g. corresponding to ITIMER_REAL.
 Timer* process_getRealtimeTimer(Process* process);
 
+// Access the process's timers created with `timer_create`.
+PosixTimerTable* process_getPosixTimers(Process* process);
+
+// Access the process's CPU-time timers; i.e. corresponding to ITIMER_VIRTUAL
+// and ITIMER_PROF.
+CpuTimer* process_getVirtualTimer(Process* process);
+CpuTimer* process_getProfTimer(Process* process);
+
+// Charge the CPU latency modeled since the last call to the process's CPU-time
+// timers, delivering SIGVTALRM and SIGPROF on expiration.
+//
+// `currentRunningThread` should be the thread that's currently running (if
+// called from a syscall handler), and NULL otherwise.
+void process_updateCpuTime(Process* process, Thread* currentRunningThread);
+
+// Returns the signals pending for `process`, including those pending for any
+// of its threads.
+shd_kernel_sigset_t process_getPendingSignals(Process* process);
+
+// Takes a pending signal in `set` that is directed at `thread` or its process,
+// and marks it no longer pending. Sets `info` if non-NULL. Blocked signals may
+// be taken. This is how signals are consumed through a signalfd.
+//
+// Returns 0 if no signal in `set` is pending.
+int process_takePendingSignal(Process* process, Thread* thread, shd_kernel_sigset_t set,
+                              siginfo_t* info);
+
 #endif /* SHD_PROCESS_H_ */
@@ -3,11 +3,15 @@ use std::os::unix::io::{FromRawFd, IntoRawFd};
 use nix::unistd::Pid;
 
 use crate::cshadow;
-use crate::host::descriptor::{CompatFile, Descriptor};
+use crate::host::descriptor::{CompatFile, Descriptor, File};
 use crate::host::syscall::format::{FmtOptions, StraceFmtMode};
+use crate::utility::event_queue::EventQueue;
 
+use super::cpu_timer::CpuTimer;
+use super::host::{Host, HostId};
+use super::memory_manager::MemoryManager;
+use super::posix_timer::PosixTimerTable;
 use super::timer::Timer;
-use super::{host::HostId, memory_manager::MemoryManager};
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct ProcessId(u32);
@@ -65,15 +69,21 @@ impl Process {
         unsafe { &*self.memory_manager_ptr() }
     }
 
-    /// Register a descriptor and return its fd handle.
-    pub fn register_descriptor(&mut self, desc: Descriptor) -> u32 {
+    /// Register a descriptor and return its fd handle. If the fd limit has been reached, the
+    /// descriptor is returned as an error.
+    pub fn register_descriptor(&mut self, desc: Descriptor) -> Result<u32, Descriptor> {
         let desc_table =
             unsafe { cshadow::process_getDescriptorTable(self.cprocess).as_mut() }.unwrap();
         desc_table.add(desc, 0)
     }
 
-    /// Register a descriptor and return its fd handle.
-    pub fn register_descriptor_with_min_fd(&mut self, desc: Descriptor, min_fd: u32) -> u32 {
+    /// Register a descriptor and return its fd handle. If the fd limit has been reached, the
+    /// descriptor is returned as an error.
+    pub fn register_descriptor_with_min_fd(
+        &mut self,
+        desc: Descriptor,
+        min_fd: u32,
+    ) -> Result<u32, Descriptor> {
         let desc_table =
             unsafe { cshadow::process_getDescriptorTable(self.cprocess).as_mut() }.unwrap();
         desc_table.add(desc, min_fd)
@@ -97,6 +107,28 @@ impl Process {
         desc_table.remove(fd)
     }
 
+    /// Returns true if the process has reached its fd limit, and no new descriptors can be
+    /// registered.
+    pub fn at_fd_limit(&self) -> bool {
+        let desc_table =
+            unsafe { cshadow::process_getDescriptorTable(self.cprocess).as_ref() }.unwrap();
+        desc_table.at_fd_limit()
+    }
+
+    /// Get the process' `RLIMIT_NOFILE` limit.
+    pub fn fd_limit(&self) -> libc::rlimit {
+        let desc_table =
+            unsafe { cshadow::process_getDescriptorTable(self.cprocess).as_ref() }.unwrap();
+        desc_table.fd_limit()
+    }
+
+    /// Set the process' `RLIMIT_NOFILE` limit.
+    pub fn set_fd_limit(&mut self, fd_limit: libc::rlimit) {
+        let desc_table =
+            unsafe { cshadow::process_getDescriptorTable(self.cprocess).as_mut() }.unwrap();
+        desc_table.set_fd_limit(fd_limit);
+    }
+
     pub fn strace_logging_options(&self) -> Option<FmtOptions> {
         StraceFmtMode::try_from(unsafe { cshadow::process_straceLoggingMode(self.cprocess) })
             .unwrap()
@@ -149,13 +181,93 @@ impl Process {
         let timer = unsafe { cshadow::process_getRealtimeTimer(self.cprocess) };
         unsafe { timer.as_mut().unwrap() }
     }
+
+    pub fn posix_timers(&self) -> &PosixTimerTable {
+        let timers = unsafe { cshadow::process_getPosixTimers(self.cprocess) };
+        unsafe { timers.as_ref().unwrap() }
+    }
+
+    pub fn posix_timers_mut(&mut self) -> &mut PosixTimerTable {
+        let timers = unsafe { cshadow::process_getPosixTimers(self.cprocess) };
+        unsafe { timers.as_mut().unwrap() }
+    }
+
+    /// The CPU-time timer for `ITIMER_VIRTUAL` or `ITIMER_PROF`, or `None` for any other `which`.
+    pub fn cpu_timer(&self, which: libc::c_int) -> Option<&CpuTimer> {
+        let timer = self.cpu_timer_ptr(which)?;
+        Some(unsafe { timer.as_ref().unwrap() })
+    }
+
+    pub fn cpu_timer_mut(&mut self, which: libc::c_int) -> Option<&mut CpuTimer> {
+        let timer = self.cpu_timer_ptr(which)?;
+        Some(unsafe { timer.as_mut().unwrap() })
+    }
+
+    fn cpu_timer_ptr(&self, which: libc::c_int) -> Option<*mut CpuTimer> {
+        match which {
+            libc::ITIMER_VIRTUAL => {
+                Some(unsafe { cshadow::process_getVirtualTimer(self.cprocess) })
+            }
+            libc::ITIMER_PROF => Some(unsafe { cshadow::process_getProfTimer(self.cprocess) }),
+            _ => None,
+        }
+    }
+
+    /// Update the readiness of the signalfds in the descriptor table. Should be called whenever
+    /// the set of signals pending for the process or any of its threads changes.
+    pub fn notify_signalfds(&self) {
+        let pending = unsafe { cshadow::process_getPendingSignals(self.cprocess) };
+        let desc_table =
+            unsafe { cshadow::process_getDescriptorTable(self.cprocess).as_ref() }.unwrap();
+
+        EventQueue::queue_and_run(|event_queue| {
+            for (_, desc) in desc_table.iter() {
+                if let CompatFile::New(file) = desc.file() {
+                    if let File::SignalFd(signalfd) = file.inner_file() {
+                        signalfd
+                            .borrow_mut()
+                            .set_pending_signals(pending, event_queue);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Notify the process's timers that the host's realtime clock was set (`was_set`) or its
+    /// rate changed. Absolute realtime timers are moved to follow the clock, and if the clock was
+    /// set, any timerfds armed with `TFD_TIMER_CANCEL_ON_SET` are canceled.
+    pub fn notify_realtime_clock_changed(&self, host: &mut Host, was_set: bool) {
+        // the posix timers are freed when the process exits
+        let posix_timers = unsafe { cshadow::process_getPosixTimers(self.cprocess).as_mut() };
+        if let Some(posix_timers) = posix_timers {
+            for timer in posix_timers.iter_mut() {
+                timer.realtime_clock_changed(host);
+            }
+        }
+
+        let desc_table =
+            unsafe { cshadow::process_getDescriptorTable(self.cprocess).as_ref() }.unwrap();
+
+        EventQueue::queue_and_run(|event_queue| {
+            for (_, desc) in desc_table.iter() {
+                if let CompatFile::New(file) = desc.file() {
+                    if let File::TimerFd(timerfd) = file.inner_file() {
+                        timerfd
+                            .borrow_mut()
+                            .realtime_clock_changed(host, was_set, event_queue);
+                    }
+                }
+            }
+        });
+    }
 }
 
 mod export {
     use super::*;
 
     /// Register a `Descriptor`. This takes ownership of the descriptor and you must not access it
-    /// after.
+    /// after. If the process' fd limit has been reached, the descriptor is dropped and `-EMFILE` is
+    /// returned.
     #[no_mangle]
     pub extern "C" fn process_registerDescriptor(
         proc: *mut cshadow::Process,
@@ -164,8 +276,13 @@ mod export {
         let mut proc = unsafe { Process::borrow_from_c(proc) };
         let desc = Descriptor::from_raw(desc).unwrap();
 
-        let fd = proc.register_descriptor(*desc);
-        fd.try_into().unwrap()
+        match proc.register_descriptor(*desc) {
+            Ok(fd) => fd.try_into().unwrap(),
+            Err(_) => {
+                log::debug!("Reached the process' fd limit");
+                -libc::EMFILE
+            }
+        }
     }
 
     /// Get a temporary reference to a descriptor.
@@ -240,4 +357,25 @@ mod export {
             None => std::ptr::null_mut(),
         }
     }
+
+    /// Update the readiness of the signalfds in the process's descriptor table. Should be called
+    /// whenever a signal becomes pending for the process or one of its threads.
+    #[no_mangle]
+    pub extern "C" fn process_notifySignalFds(proc: *mut cshadow::Process) {
+        let proc = unsafe { Process::borrow_from_c(proc) };
+        proc.notify_signalfds();
+    }
+
+    /// Notify the process's timers that the host's realtime clock was set (`was_set`) or its
+    /// rate changed.
+    #[no_mangle]
+    pub extern "C" fn process_notifyRealtimeClockChanged(
+        proc: *mut cshadow::Process,
+        host: *mut cshadow::Host,
+        was_set: bool,
+    ) {
+        let proc = unsafe { Process::borrow_from_c(proc) };
+        let mut host = unsafe { Host::borrow_from_c(host) };
+        proc.notify_realtime_clock_changed(&mut host, was_set);
+    }
 }
@@ -6,14 +6,107 @@
 #include "clone.h"
 
 #include <errno.h>
+#include <sched.h>
+#include <signal.h>
 #include <stdlib.h>
 
 #include "lib/logger/logger.h"
 #include "main/core/worker.h"
 #include "main/host/syscall/protected.h"
+#include "main/host/process.h"
+#include "main/host/syscall_condition.h"
 #include "main/host/thread.h"
 #include "main/utility/utility.h"
 
+// Blocks the calling thread until its `vfork` child has called `execve` or
+// exited, and then returns the child's pid.
+static SysCallReturn _syscallhandler_waitForVforkChild(SysCallHandler* sys, pid_t childPid) {
+    Process* child = host_getProcess(sys->host, childPid);
+
+    // Linux only lets a fatal signal interrupt the wait. We can't fail with
+    // EINTR since the child already exists, so we stop waiting instead. glibc
+    // blocks all signals around its vfork-style clones anyway.
+    if (child && process_isVforkPending(child) &&
+        !thread_unblockedSignalPending(sys->thread, host_getShimShmemLock(sys->host))) {
+        thread_setVforkChildID(sys->thread, childPid);
+        return (SysCallReturn){
+            .state = SYSCALL_BLOCK,
+            .cond = syscallcondition_new((Trigger){.type = TRIGGER_CHILD,
+                                                   .object.as_process = sys->process,
+                                                   .status = STATUS_CHILD_EVENT}),
+            .restartable = false};
+    }
+
+    thread_setVforkChildID(sys->thread, 0);
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = childPid};
+}
+
+// Any `child_stack` requested by the plugin is applied by the shim, in the
+// child, when it returns from the syscall.
+static SysCallReturn _syscallhandler_fork(SysCallHandler* sys, unsigned long flags,
+                                          PluginPtr ptid, PluginPtr ctid, unsigned long newtls) {
+    pid_t vforkChildPid = thread_getVforkChildID(sys->thread);
+    if (vforkChildPid) {
+        // We already created the child, and blocked waiting for it.
+        utility_assert(_syscallhandler_wasBlocked(sys));
+        return _syscallhandler_waitForVforkChild(sys, vforkChildPid);
+    }
+
+    // The child gets its own copy of the parent's memory rather than sharing
+    // it. With CLONE_VFORK the parent still waits for the child to exec or
+    // exit, since the child may expect the parent not to run until then.
+    unsigned long filtered_flags = flags & ~(CLONE_VM | CLONE_VFORK | CLONE_PARENT_SETTID |
+                                             CLONE_CHILD_SETTID | CLONE_CHILD_CLEARTID);
+    Process* child = NULL;
+    {
+        int rv = process_fork(sys->process, sys->thread, filtered_flags, newtls, &child);
+        if (rv < 0) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = rv};
+        }
+    }
+    utility_assert(child);
+
+    pid_t childPid = process_getProcessID(child);
+    Thread* childThread = process_getThread(child, childPid);
+    utility_assert(childThread);
+
+    unsigned long handled_flags = CLONE_VM | CLONE_VFORK;
+    if (flags & CLONE_PARENT_SETTID) {
+        handled_flags |= CLONE_PARENT_SETTID;
+        pid_t* ptidp = process_getWriteablePtr(sys->process, ptid, sizeof(*ptidp));
+        *ptidp = childPid;
+    }
+
+    if (flags & CLONE_CHILD_SETTID) {
+        handled_flags |= CLONE_CHILD_SETTID;
+        pid_t* ctidp = process_getWriteablePtr(child, ctid, sizeof(*ctidp));
+        *ctidp = childPid;
+        process_flushPtrs(child);
+    }
+
+    if (flags & CLONE_CHILD_CLEARTID) {
+        handled_flags |= CLONE_CHILD_CLEARTID;
+        thread_setTidAddress(childThread, ctid);
+    }
+
+    // Flags that are OK to have handled in the native clone call
+    unsigned long native_handled_flags = CLONE_SETTLS | CSIGNAL;
+
+    unsigned long unhandled_flags = flags & ~(handled_flags | native_handled_flags);
+    if (unhandled_flags) {
+        warning("Unhandled fork clone flags 0x%lx", unhandled_flags);
+    }
+
+    if (flags & CLONE_VFORK) {
+        // Blocking would otherwise discard the write to `ptid`.
+        process_flushPtrs(sys->process);
+        process_setVforkPending(child);
+        return _syscallhandler_waitForVforkChild(sys, childPid);
+    }
+
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = childPid};
+}
+
 SysCallReturn syscallhandler_clone(SysCallHandler* sys, const SysCallArgs* args) {
     utility_assert(sys && args);
 
@@ -25,6 +118,18 @@ SysCallReturn syscallhandler_clone(SysCallHandler* sys, const SysCallArgs* args)
     PluginPtr ctid = args->args[3].as_ptr;
     unsigned long newtls = args->args[4].as_i64;
 
+    // Without CLONE_THREAD we're creating a new process. We only support
+    // creating it with its own copy of the parent's memory; a vfork-style
+    // clone (CLONE_VM|CLONE_VFORK), as used by glibc's `posix_spawn`, gets a
+    // copy too, and blocks the parent until the child execs or exits.
+    if (!(flags & CLONE_THREAD)) {
+        if ((flags & CLONE_VM) && !(flags & CLONE_VFORK)) {
+            warning("Unsupported clone of a new process sharing memory with flags 0x%lx", flags);
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -ENOTSUP};
+        }
+        return _syscallhandler_fork(sys, flags, ptid, ctid, newtls);
+    }
+
     unsigned long required_flags =
         CLONE_VM | CLONE_FS | CLONE_FILES | CLONE_SIGHAND | CLONE_THREAD | CLONE_SYSVSEM;
     if ((flags & required_flags) != required_flags) {
@@ -79,6 +184,17 @@ SysCallReturn syscallhandler_clone(SysCallHandler* sys, const SysCallArgs* args)
     return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = thread_getID(child)};
 }
 
+SysCallReturn syscallhandler_fork(SysCallHandler* sys, const SysCallArgs* args) {
+    utility_assert(sys && args);
+    return _syscallhandler_fork(sys, SIGCHLD, (PluginPtr){0}, (PluginPtr){0}, 0);
+}
+
+SysCallReturn syscallhandler_vfork(SysCallHandler* sys, const SysCallArgs* args) {
+    utility_assert(sys && args);
+    return _syscallhandler_fork(
+        sys, CLONE_VM | CLONE_VFORK | SIGCHLD, (PluginPtr){0}, (PluginPtr){0}, 0);
+}
+
 SysCallReturn syscallhandler_gettid(SysCallHandler* sys, const SysCallArgs* args) {
     utility_assert(sys && args);
     return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = thread_getID(sys->thread)};
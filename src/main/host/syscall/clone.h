@@ -9,6 +9,8 @@
 #include "main/host/syscall/protected.h"
 
 SYSCALL_HANDLER(clone);
+SYSCALL_HANDLER(fork);
 SYSCALL_HANDLER(gettid);
+SYSCALL_HANDLER(vfork);
 
 #endif /* SRC_MAIN_HOST_SYSCALL_CLONE_H_ */
@@ -65,8 +65,8 @@ static SysCallReturn _syscallhandler_openHelper(SysCallHandler* sys,
 
     /* Create and open the file. */
     RegularFile* filed = regularfile_new();
-    errcode = regularfile_open(
-        filed, pathname, flags & ~O_CLOEXEC, mode, process_getWorkingDir(sys->process));
+    errcode = regularfile_open(filed, sys->host, pathname, flags & ~O_CLOEXEC, mode,
+                               process_getWorkingDir(sys->process));
 
     if (errcode < 0) {
         /* This will unref/free the RegularFile. */
@@ -126,8 +126,8 @@ SysCallReturn syscallhandler_openat(SysCallHandler* sys,
 
     /* Create and open the file. */
     RegularFile* file_desc = regularfile_new();
-    errcode = regularfile_openat(file_desc, dir_desc, pathname, flags & ~O_CLOEXEC, mode,
-                                 process_getWorkingDir(sys->process));
+    errcode = regularfile_openat(file_desc, sys->host, dir_desc, pathname, flags & ~O_CLOEXEC,
+                                 mode, process_getWorkingDir(sys->process));
 
     if (errcode < 0) {
         /* This will unref/free the RegularFile. */
@@ -537,3 +537,16 @@ SysCallReturn syscallhandler_statx(SysCallHandler* sys,
         .retval.as_i64 = regularfile_statx(dir_desc, pathname, flags, mask, statxbuf, plugin_cwd)};
 }
 #endif
+
+const char* syscallhandler_getDirectoryPath(SysCallHandler* sys, int dirfd) {
+    if (dirfd == AT_FDCWD) {
+        return NULL;
+    }
+
+    RegularFile* dir_desc = NULL;
+    if (_syscallhandler_validateDirHelper(sys, dirfd, &dir_desc) < 0 || dir_desc == NULL) {
+        return NULL;
+    }
+
+    return regularfile_getAbsolutePathAtOpen(dir_desc);
+}
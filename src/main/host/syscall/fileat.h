@@ -26,4 +26,8 @@ SYSCALL_HANDLER(symlinkat);
 SYSCALL_HANDLER(unlinkat);
 SYSCALL_HANDLER(utimensat);
 
+/* Returns the path that the directory `dirfd` had when it was opened, or NULL if `dirfd` is
+ * AT_FDCWD or is not a valid directory. */
+const char* syscallhandler_getDirectoryPath(SysCallHandler* sys, int dirfd);
+
 #endif /* SRC_MAIN_HOST_SYSCALL_FILEAT_H_ */
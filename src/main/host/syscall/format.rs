@@ -180,6 +180,12 @@ impl TryFromSyscallReg for nix::fcntl::OFlag {
     }
 }
 
+impl TryFromSyscallReg for nix::sys::epoll::EpollCreateFlags {
+    fn try_from_reg(reg: SysCallReg) -> Option<Self> {
+        Self::from_bits(reg.into())
+    }
+}
+
 impl TryFromSyscallReg for nix::sys::eventfd::EfdFlags {
     fn try_from_reg(reg: SysCallReg) -> Option<Self> {
         Self::from_bits(reg.into())
@@ -211,10 +217,19 @@ simple_array_impl!(i8, i16, i32, i64, isize);
 simple_array_impl!(u8, u16, u32, u64, usize);
 
 safe_pointer_impl!(libc::c_void);
+safe_pointer_impl!(libc::epoll_event);
+safe_pointer_impl!(libc::iovec);
+safe_pointer_impl!(libc::mmsghdr);
+safe_pointer_impl!(libc::msghdr);
+safe_pointer_impl!(libc::rlimit);
+safe_pointer_impl!(libc::rlimit64);
+safe_pointer_impl!(libc::sigset_t);
 safe_pointer_impl!(libc::sockaddr);
 safe_pointer_impl!(libc::sysinfo);
+safe_pointer_impl!(libc::timespec);
 
 simple_debug_impl!(nix::fcntl::OFlag);
+simple_debug_impl!(nix::sys::epoll::EpollCreateFlags);
 simple_debug_impl!(nix::sys::eventfd::EfdFlags);
 simple_debug_impl!(nix::sys::socket::AddressFamily);
 simple_debug_impl!(nix::sys::socket::MsgFlags);
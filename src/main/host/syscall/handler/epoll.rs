@@ -0,0 +1,285 @@
+use crate::core::support::simulation_time::SimulationTime;
+use crate::core::worker::Worker;
+use crate::cshadow as c;
+use crate::host::context::ThreadContext;
+use crate::host::descriptor::epoll::{Epoll, EpollTarget};
+use crate::host::descriptor::{
+    CompatFile, Descriptor, DescriptorFlags, File, FileState, FileStatus, OpenFile,
+};
+use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall::Trigger;
+use crate::host::syscall_condition::SysCallCondition;
+use crate::host::syscall_types::{Blocked, PluginPtr, SysCallArgs, TypedPluginPtr};
+use crate::host::syscall_types::{SyscallError, SyscallResult};
+use crate::utility::event_queue::EventQueue;
+
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use log::*;
+use nix::errno::Errno;
+use nix::sys::epoll::{EpollCreateFlags, EpollFlags};
+
+use syscall_logger::log_syscall;
+
+impl SyscallHandler {
+    #[log_syscall(/* rv */ libc::c_int, /* size */ libc::c_int)]
+    pub fn epoll_create(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let size: libc::c_int = args.get(0).into();
+
+        // epoll_create(2): "the size argument is ignored, but must be greater than zero"
+        if size <= 0 {
+            trace!("Invalid size argument {}", size);
+            return Err(Errno::EINVAL.into());
+        }
+
+        self.epoll_create_helper(ctx, 0)
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* flags */ nix::sys::epoll::EpollCreateFlags)]
+    pub fn epoll_create1(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let flags: libc::c_int = args.get(0).into();
+
+        self.epoll_create_helper(ctx, flags)
+    }
+
+    fn epoll_create_helper(&self, ctx: &mut ThreadContext, flags: libc::c_int) -> SyscallResult {
+        let flags = match EpollCreateFlags::from_bits(flags) {
+            Some(x) => x,
+            None => {
+                trace!("Invalid epoll_create1 flags: {}", flags);
+                return Err(Errno::EINVAL.into());
+            }
+        };
+
+        let mut descriptor_flags = DescriptorFlags::empty();
+
+        if flags.contains(EpollCreateFlags::EPOLL_CLOEXEC) {
+            descriptor_flags.insert(DescriptorFlags::CLOEXEC);
+        }
+
+        let epoll = Epoll::new(FileStatus::empty());
+        let epoll = Arc::new(AtomicRefCell::new(epoll));
+
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::Epoll(epoll))));
+        desc.set_flags(descriptor_flags);
+
+        let fd = ctx
+            .process
+            .register_descriptor(desc)
+            .map_err(|_| Errno::EMFILE)?;
+
+        trace!("epoll_create() returning fd {}", fd);
+
+        Ok(fd.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* epfd */ libc::c_int, /* op */ libc::c_int,
+                  /* fd */ libc::c_int, /* event */ *const libc::epoll_event)]
+    pub fn epoll_ctl(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let epfd: libc::c_int = args.get(0).into();
+        let op: libc::c_int = args.get(1).into();
+        let fd: libc::c_int = args.get(2).into();
+        let event_ptr: PluginPtr = args.get(3).into();
+
+        // epoll_ctl(2): "EINVAL: epfd is not an epoll file descriptor, or fd is the same as epfd"
+        if epfd == fd {
+            trace!("Epoll fd {} cannot be used to wait on itself", epfd);
+            return Err(Errno::EINVAL.into());
+        }
+
+        let epoll = Self::get_epoll(ctx, epfd)?;
+
+        let target = match Self::get_descriptor(ctx.process, fd)?.file() {
+            CompatFile::New(file) => EpollTarget::File(file.inner_file().clone()),
+            CompatFile::Legacy(file) => {
+                // We allow closed legacy files for EPOLL_CTL_DEL ops, since libevent frequently
+                // closes before issuing the EPOLL_CTL_DEL op.
+                let status = unsafe { c::legacyfile_getStatus(file.ptr()) };
+                if op != libc::EPOLL_CTL_DEL && FileState::from(status).contains(FileState::CLOSED)
+                {
+                    debug!("Child {} of epoll {} is closed", fd, epfd);
+                    return Err(Errno::EBADF.into());
+                }
+
+                EpollTarget::Legacy(file.file().clone())
+            }
+        };
+
+        // the event is ignored (and may be NULL) for EPOLL_CTL_DEL
+        let (events, data) = if op == libc::EPOLL_CTL_DEL {
+            (EpollFlags::empty(), 0)
+        } else {
+            if event_ptr.is_null() {
+                trace!("NULL event pointer passed for epoll {}", epfd);
+                return Err(Errno::EFAULT.into());
+            }
+
+            let event_ptr = TypedPluginPtr::new::<libc::epoll_event>(event_ptr, 1);
+            let event = ctx.process.memory().read_vals::<_, 1>(event_ptr)?[0];
+
+            // copy the fields out of the packed struct
+            let events = event.events;
+            let data = event.u64;
+
+            (EpollFlags::from_bits_truncate(events as libc::c_int), data)
+        };
+
+        trace!(
+            "Calling epoll_ctl on epoll {} with child {} and op {}",
+            epfd,
+            fd,
+            op
+        );
+
+        EventQueue::queue_and_run(|event_queue| {
+            Epoll::ctl(&epoll, op, fd, target, events, data, event_queue)
+        })?;
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* epfd */ libc::c_int,
+                  /* events */ *const libc::epoll_event, /* maxevents */ libc::c_int,
+                  /* timeout */ libc::c_int)]
+    pub fn epoll_wait(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let epfd: libc::c_int = args.get(0).into();
+        let events_ptr: PluginPtr = args.get(1).into();
+        let max_events: libc::c_int = args.get(2).into();
+        let timeout_ms: libc::c_int = args.get(3).into();
+
+        self.epoll_wait_helper(ctx, epfd, events_ptr, max_events, timeout_ms)
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* epfd */ libc::c_int,
+                  /* events */ *const libc::epoll_event, /* maxevents */ libc::c_int,
+                  /* timeout */ libc::c_int, /* sigmask */ *const libc::sigset_t)]
+    pub fn epoll_pwait(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let epfd: libc::c_int = args.get(0).into();
+        let events_ptr: PluginPtr = args.get(1).into();
+        let max_events: libc::c_int = args.get(2).into();
+        let timeout_ms: libc::c_int = args.get(3).into();
+        let sigmask_ptr: PluginPtr = args.get(4).into();
+
+        if !sigmask_ptr.is_null() {
+            error!(
+                "epoll_pwait called with non-null sigmask, which is not yet supported by shadow; \
+                 returning ENOSYS"
+            );
+            return Err(Errno::ENOSYS.into());
+        }
+
+        self.epoll_wait_helper(ctx, epfd, events_ptr, max_events, timeout_ms)
+    }
+
+    fn epoll_wait_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        epfd: libc::c_int,
+        events_ptr: PluginPtr,
+        max_events: libc::c_int,
+        timeout_ms: libc::c_int,
+    ) -> SyscallResult {
+        if max_events <= 0 {
+            trace!("Maxevents {} is not greater than 0", max_events);
+            return Err(Errno::EINVAL.into());
+        }
+
+        if events_ptr.is_null() {
+            trace!("NULL events pointer passed for epoll {}", epfd);
+            return Err(Errno::EFAULT.into());
+        }
+
+        // if we were previously blocked, get the active file from the last syscall handler
+        // invocation since it may no longer exist in the descriptor table
+        let prev_condition = ctx.thread.syscall_condition();
+        let prev_timeout = prev_condition.as_ref().and_then(|x| x.timeout());
+        let open_file = prev_condition.and_then(|x| x.active_file().cloned());
+
+        let open_file = match open_file {
+            Some(x) => x,
+            None => match Self::get_descriptor(ctx.process, epfd)?.file() {
+                CompatFile::New(file) => file.clone(),
+                CompatFile::Legacy(_) => return Err(Errno::EINVAL.into()),
+            },
+        };
+
+        let epoll = match open_file.inner_file() {
+            File::Epoll(epoll) => Arc::clone(epoll),
+            _ => return Err(Errno::EINVAL.into()),
+        };
+
+        let max_events = usize::try_from(max_events).unwrap();
+        let events = EventQueue::queue_and_run(|event_queue| {
+            epoll
+                .borrow_mut()
+                .collect_ready_events(max_events, event_queue)
+        });
+
+        if !events.is_empty() {
+            trace!("Found {} ready events on epoll {}", events.len(), epfd);
+
+            let events: Vec<libc::epoll_event> = events
+                .into_iter()
+                .map(|(events, data)| libc::epoll_event {
+                    events: events.bits() as u32,
+                    u64: data,
+                })
+                .collect();
+
+            let events_ptr = TypedPluginPtr::new::<libc::epoll_event>(events_ptr, events.len());
+            ctx.process.memory_mut().copy_to_ptr(events_ptr, &events)?;
+
+            return Ok(libc::c_int::try_from(events.len()).unwrap().into());
+        }
+
+        let now = Worker::current_time().unwrap();
+
+        // return immediately if the timeout is 0, or we were already blocked for a while and still
+        // have no events
+        if timeout_ms == 0 || prev_timeout.map(|x| now >= x).unwrap_or(false) {
+            trace!(
+                "No events are ready on epoll {} and we need to return now",
+                epfd
+            );
+            return Ok(0.into());
+        }
+
+        trace!("No events are ready on epoll {} and we need to block", epfd);
+
+        // an epoll is readable when it has events to report
+        let trigger = Trigger::from_file(open_file.inner_file().clone(), FileState::READABLE);
+        let mut cond = SysCallCondition::new(trigger);
+
+        if timeout_ms > 0 {
+            // if we were previously blocked, keep the original timeout
+            let timeout = prev_timeout.unwrap_or_else(|| {
+                now + SimulationTime::from_millis(u64::try_from(timeout_ms).unwrap())
+            });
+            cond.set_timeout(ctx.host.chost(), timeout);
+        }
+
+        let supports_sa_restart = epoll.borrow().supports_sa_restart();
+        cond.set_active_file(open_file);
+
+        Err(SyscallError::Blocked(Blocked {
+            condition: cond,
+            restartable: supports_sa_restart,
+        }))
+    }
+
+    /// Returns the epoll for the file descriptor, or an error if it doesn't exist or isn't an
+    /// epoll.
+    fn get_epoll(
+        ctx: &mut ThreadContext,
+        epfd: libc::c_int,
+    ) -> Result<Arc<AtomicRefCell<Epoll>>, Errno> {
+        match Self::get_descriptor(ctx.process, epfd)?.file() {
+            CompatFile::New(file) => match file.inner_file() {
+                File::Epoll(epoll) => Ok(Arc::clone(epoll)),
+                _ => Err(Errno::EINVAL),
+            },
+            CompatFile::Legacy(_) => Err(Errno::EINVAL),
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::host::context::ThreadContext;
 use crate::host::descriptor::{CompatFile, DescriptorFlags, File, FileStatus};
 use crate::host::syscall::handler::SyscallHandler;
 use crate::host::syscall_types::{SysCallArgs, SysCallReg, SyscallResult};
+use crate::utility::event_queue::EventQueue;
 use log::warn;
 use nix::errno::Errno;
 use nix::fcntl::OFlag;
@@ -138,9 +139,17 @@ impl SyscallHandler {
                 let min_fd: u32 = min_fd.try_into().map_err(|_| nix::errno::Errno::EINVAL)?;
 
                 let new_desc = desc.dup(DescriptorFlags::empty());
+
+                // from 'man 2 fcntl': "EINVAL: arg is negative or is greater than the maximum
+                // allowable value"
+                if u64::from(min_fd) >= ctx.process.fd_limit().rlim_cur {
+                    return Err(Errno::EINVAL.into());
+                }
+
                 let new_fd = ctx
                     .process
-                    .register_descriptor_with_min_fd(new_desc, min_fd);
+                    .register_descriptor_with_min_fd(new_desc, min_fd)
+                    .map_err(|_| Errno::EMFILE)?;
                 SysCallReg::from(i32::try_from(new_fd).unwrap())
             }
             libc::F_DUPFD_CLOEXEC => {
@@ -148,9 +157,17 @@ impl SyscallHandler {
                 let min_fd: u32 = min_fd.try_into().map_err(|_| nix::errno::Errno::EINVAL)?;
 
                 let new_desc = desc.dup(DescriptorFlags::CLOEXEC);
+
+                // from 'man 2 fcntl': "EINVAL: arg is negative or is greater than the maximum
+                // allowable value"
+                if u64::from(min_fd) >= ctx.process.fd_limit().rlim_cur {
+                    return Err(Errno::EINVAL.into());
+                }
+
                 let new_fd = ctx
                     .process
-                    .register_descriptor_with_min_fd(new_desc, min_fd);
+                    .register_descriptor_with_min_fd(new_desc, min_fd)
+                    .map_err(|_| Errno::EMFILE)?;
                 SysCallReg::from(i32::try_from(new_fd).unwrap())
             }
             libc::F_GETPIPE_SZ => {
@@ -167,6 +184,26 @@ impl SyscallHandler {
                     return Err(Errno::EINVAL.into());
                 }
             }
+            libc::F_SETPIPE_SZ => {
+                let file = match desc.file() {
+                    CompatFile::New(d) => d,
+                    // if it's a legacy file, use the C syscall handler instead
+                    CompatFile::Legacy(_) => return legacy_syscall_fn(ctx, args),
+                };
+
+                // the kernel interprets the argument as an unsigned int
+                let size: u32 = args.args[2].into();
+
+                #[allow(irrefutable_let_patterns)]
+                if let File::Pipe(pipe) = file.inner_file() {
+                    let new_size = EventQueue::queue_and_run(|event_queue| {
+                        pipe.borrow_mut().set_max_size(size, event_queue)
+                    })?;
+                    SysCallReg::from(i32::try_from(new_size).unwrap())
+                } else {
+                    return Err(Errno::EINVAL.into());
+                }
+            }
             _ => return Err(Errno::EINVAL.into()),
         })
     }
@@ -7,41 +7,65 @@ use crate::host::syscall_types::SyscallResult;
 
 use nix::errno::Errno;
 
+use std::cell::Cell;
+
+mod epoll;
 mod eventfd;
 mod fcntl;
 mod ioctl;
 mod random;
+mod resource;
 mod sched;
+mod signalfd;
 mod socket;
+mod splice;
 mod sysinfo;
 mod time;
+mod timerfd;
+mod uio;
 mod unistd;
+mod wait;
 
 pub struct SyscallHandler {
-    // Will eventually contain syscall handler state once migrated from the c handler
+    /// The number of bytes already received by a `MSG_WAITALL` receive that blocked before its
+    /// buffer was full. The receive continues after these bytes when the syscall is run again.
+    waitall_num_received: Cell<usize>,
 }
 
 impl SyscallHandler {
     pub fn new() -> SyscallHandler {
-        SyscallHandler {}
+        SyscallHandler {
+            waitall_num_received: Cell::new(0),
+        }
     }
 
     pub fn syscall(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
         match args.number {
             libc::SYS_accept => self.accept(ctx, args),
             libc::SYS_accept4 => self.accept4(ctx, args),
+            libc::SYS_adjtimex => self.adjtimex(ctx, args),
             libc::SYS_bind => self.bind(ctx, args),
+            libc::SYS_clock_adjtime => self.clock_adjtime(ctx, args),
+            libc::SYS_clock_settime => self.clock_settime(ctx, args),
             libc::SYS_close => self.close(ctx, args),
             libc::SYS_connect => self.connect(ctx, args),
+            libc::SYS_copy_file_range => self.copy_file_range(ctx, args),
             libc::SYS_dup => self.dup(ctx, args),
             libc::SYS_dup2 => self.dup2(ctx, args),
             libc::SYS_dup3 => self.dup3(ctx, args),
+            libc::SYS_epoll_create => self.epoll_create(ctx, args),
+            libc::SYS_epoll_create1 => self.epoll_create1(ctx, args),
+            libc::SYS_epoll_ctl => self.epoll_ctl(ctx, args),
+            libc::SYS_epoll_pwait => self.epoll_pwait(ctx, args),
+            libc::SYS_epoll_wait => self.epoll_wait(ctx, args),
             libc::SYS_eventfd => self.eventfd(ctx, args),
             libc::SYS_eventfd2 => self.eventfd2(ctx, args),
             libc::SYS_fcntl => self.fcntl(ctx, args),
+            libc::SYS_getcpu => self.getcpu(ctx, args),
             libc::SYS_getitimer => self.getitimer(ctx, args),
             libc::SYS_getpeername => self.getpeername(ctx, args),
             libc::SYS_getrandom => self.getrandom(ctx, args),
+            libc::SYS_getrlimit => self.getrlimit(ctx, args),
             libc::SYS_getsockname => self.getsockname(ctx, args),
             libc::SYS_getsockopt => self.getsockopt(ctx, args),
             libc::SYS_ioctl => self.ioctl(ctx, args),
@@ -49,19 +73,51 @@ impl SyscallHandler {
             libc::SYS_pipe => self.pipe(ctx, args),
             libc::SYS_pipe2 => self.pipe2(ctx, args),
             libc::SYS_pread64 => self.pread64(ctx, args),
+            libc::SYS_preadv => self.preadv(ctx, args),
+            libc::SYS_preadv2 => self.preadv2(ctx, args),
+            libc::SYS_prlimit64 => self.prlimit64(ctx, args),
             libc::SYS_pwrite64 => self.pwrite64(ctx, args),
+            libc::SYS_pwritev => self.pwritev(ctx, args),
+            libc::SYS_pwritev2 => self.pwritev2(ctx, args),
             libc::SYS_rseq => self.rseq(ctx, args),
             libc::SYS_read => self.read(ctx, args),
+            libc::SYS_readv => self.readv(ctx, args),
             libc::SYS_recvfrom => self.recvfrom(ctx, args),
+            libc::SYS_recvmmsg => self.recvmmsg(ctx, args),
+            libc::SYS_recvmsg => self.recvmsg(ctx, args),
+            libc::SYS_sched_getaffinity => self.sched_getaffinity(ctx, args),
+            libc::SYS_sched_setaffinity => self.sched_setaffinity(ctx, args),
             libc::SYS_sched_yield => self.sched_yield(ctx, args),
+            libc::SYS_sendfile => self.sendfile(ctx, args),
+            libc::SYS_sendmmsg => self.sendmmsg(ctx, args),
+            libc::SYS_sendmsg => self.sendmsg(ctx, args),
             libc::SYS_sendto => self.sendto(ctx, args),
             libc::SYS_setitimer => self.setitimer(ctx, args),
+            libc::SYS_setrlimit => self.setrlimit(ctx, args),
             libc::SYS_setsockopt => self.setsockopt(ctx, args),
+            libc::SYS_settimeofday => self.settimeofday(ctx, args),
             libc::SYS_shutdown => self.shutdown(ctx, args),
+            libc::SYS_signalfd => self.signalfd(ctx, args),
+            libc::SYS_signalfd4 => self.signalfd4(ctx, args),
             libc::SYS_socket => self.socket(ctx, args),
             libc::SYS_socketpair => self.socketpair(ctx, args),
+            libc::SYS_splice => self.splice(ctx, args),
             libc::SYS_sysinfo => self.sysinfo(ctx, args),
+            libc::SYS_tee => self.tee(ctx, args),
+            libc::SYS_timer_create => self.timer_create(ctx, args),
+            libc::SYS_timer_delete => self.timer_delete(ctx, args),
+            libc::SYS_timer_getoverrun => self.timer_getoverrun(ctx, args),
+            libc::SYS_timer_gettime => self.timer_gettime(ctx, args),
+            libc::SYS_timer_settime => self.timer_settime(ctx, args),
+            libc::SYS_timerfd_create => self.timerfd_create(ctx, args),
+            libc::SYS_timerfd_gettime => self.timerfd_gettime(ctx, args),
+            libc::SYS_timerfd_settime => self.timerfd_settime(ctx, args),
+            libc::SYS_unlink => self.unlink(ctx, args),
+            libc::SYS_unlinkat => self.unlinkat(ctx, args),
+            libc::SYS_wait4 => self.wait4(ctx, args),
+            libc::SYS_waitid => self.waitid(ctx, args),
             libc::SYS_write => self.write(ctx, args),
+            libc::SYS_writev => self.writev(ctx, args),
             _ => {
                 // if we added a HANDLE_RUST() macro for this syscall in
                 // 'syscallhandler_make_syscall()' but didn't add an entry here, we should get a
@@ -101,6 +157,12 @@ impl SyscallHandler {
             None => Err(nix::errno::Errno::EBADF),
         }
     }
+
+    /// Internal helper that raises SIGPIPE on the calling thread. Should be called when a write
+    /// to a pipe or stream socket fails with EPIPE.
+    fn raise_sigpipe(ctx: &mut ThreadContext) {
+        unsafe { c::syscallhandler_raiseSigpipe(ctx.thread.csyscallhandler()) };
+    }
 }
 
 mod export {
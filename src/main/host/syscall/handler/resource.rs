@@ -0,0 +1,119 @@
+use crate::host::context::ThreadContext;
+use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall_types::{SysCallArgs, TypedPluginPtr};
+use crate::host::syscall_types::{SyscallError, SyscallResult};
+
+use log::*;
+use nix::errno::Errno;
+
+use syscall_logger::log_syscall;
+
+impl SyscallHandler {
+    #[log_syscall(/* rv */ libc::c_int, /* resource */ libc::c_uint,
+                  /* rlim */ *const libc::rlimit)]
+    pub fn getrlimit(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let resource = libc::c_uint::from(args.get(0));
+        let rlim_ptr = TypedPluginPtr::new::<libc::rlimit>(args.get(1).into(), 1);
+
+        // we only simulate the fd limit; the other limits are those of the native process
+        if resource != libc::RLIMIT_NOFILE {
+            return Err(SyscallError::Native);
+        }
+
+        let fd_limit = ctx.process.fd_limit();
+        ctx.process
+            .memory_mut()
+            .copy_to_ptr(rlim_ptr, &[fd_limit])?;
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* resource */ libc::c_uint,
+                  /* rlim */ *const libc::rlimit)]
+    pub fn setrlimit(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let resource = libc::c_uint::from(args.get(0));
+        let rlim_ptr = TypedPluginPtr::new::<libc::rlimit>(args.get(1).into(), 1);
+
+        // we only simulate the fd limit; the other limits are those of the native process
+        if resource != libc::RLIMIT_NOFILE {
+            return Err(SyscallError::Native);
+        }
+
+        let new_limit = ctx.process.memory().read_vals::<_, 1>(rlim_ptr)?[0];
+        check_fd_limit(&ctx.process.fd_limit(), &new_limit)?;
+
+        ctx.process.set_fd_limit(new_limit);
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* pid */ libc::pid_t, /* resource */ libc::c_uint,
+                  /* new_rlim */ *const libc::rlimit64, /* old_rlim */ *const libc::rlimit64)]
+    pub fn prlimit64(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let pid = libc::pid_t::from(args.get(0));
+        let resource = libc::c_uint::from(args.get(1));
+        let new_rlim_ptr = TypedPluginPtr::new::<libc::rlimit64>(args.get(2).into(), 1);
+        let old_rlim_ptr = TypedPluginPtr::new::<libc::rlimit64>(args.get(3).into(), 1);
+
+        trace!("prlimit called on pid {} for resource {}", pid, resource);
+
+        // TODO: we do not currently support getting or setting the limits of other processes
+        let own_pid = libc::pid_t::try_from(u32::from(ctx.process.id())).unwrap();
+        if pid != 0 && pid != own_pid {
+            return Err(Errno::ENOSYS.into());
+        }
+
+        // we only simulate the fd limit; the other limits are those of the native process
+        if resource != libc::RLIMIT_NOFILE {
+            // the native syscall would interpret the pid as a native pid
+            if pid != 0 {
+                return Err(Errno::ENOSYS.into());
+            }
+            return Err(SyscallError::Native);
+        }
+
+        let old_limit = ctx.process.fd_limit();
+
+        let new_limit = if new_rlim_ptr.is_null() {
+            None
+        } else {
+            let new_limit = ctx.process.memory().read_vals::<_, 1>(new_rlim_ptr)?[0];
+            let new_limit = libc::rlimit {
+                rlim_cur: new_limit.rlim_cur,
+                rlim_max: new_limit.rlim_max,
+            };
+            check_fd_limit(&old_limit, &new_limit)?;
+            Some(new_limit)
+        };
+
+        if !old_rlim_ptr.is_null() {
+            let old_limit = libc::rlimit64 {
+                rlim_cur: old_limit.rlim_cur,
+                rlim_max: old_limit.rlim_max,
+            };
+            ctx.process
+                .memory_mut()
+                .copy_to_ptr(old_rlim_ptr, &[old_limit])?;
+        }
+
+        if let Some(new_limit) = new_limit {
+            ctx.process.set_fd_limit(new_limit);
+        }
+
+        Ok(0.into())
+    }
+}
+
+/// Check that the process is allowed to change its fd limit from `old` to `new`.
+fn check_fd_limit(old: &libc::rlimit, new: &libc::rlimit) -> Result<(), Errno> {
+    if new.rlim_cur > new.rlim_max {
+        return Err(Errno::EINVAL);
+    }
+
+    // processes don't have `CAP_SYS_RESOURCE`, so they can't raise their hard limit
+    if new.rlim_max > old.rlim_max {
+        return Err(Errno::EPERM);
+    }
+
+    Ok(())
+}
@@ -1,17 +1,28 @@
+use crate::cshadow as c;
 use crate::host::context::ThreadContext;
+use crate::host::cpu_affinity::CpuAffinity;
+use crate::host::memory_manager::MemoryManager;
+use crate::host::process::Process;
 use crate::host::syscall::handler::SyscallHandler;
-use crate::host::syscall_types::{SysCallArgs, SyscallResult, TypedPluginPtr};
+use crate::host::syscall_types::{
+    PluginPtr, SysCallArgs, SyscallError, SyscallResult, TypedPluginPtr,
+};
+use crate::host::thread::{CThread, Thread};
 use crate::utility::pod::Pod;
 
 use log::warn;
 use nix::errno::Errno;
 use syscall_logger::log_syscall;
 
-// We always report that the thread is running on CPU 0, Node 0
-const CURRENT_CPU: u32 = 0;
+// We always report that the thread is running on the lowest CPU in its affinity mask, and on
+// NUMA node 0
+const CURRENT_NODE: u32 = 0;
 
 const RSEQ_FLAG_UNREGISTER: i32 = 1;
 
+/// The value of `rseq.cpu_id` when the area isn't registered.
+const RSEQ_CPU_ID_UNINITIALIZED: i32 = -1;
+
 #[repr(C, align(32))]
 #[derive(Debug, Copy, Clone)]
 struct rseq {
@@ -32,6 +43,92 @@ impl SyscallHandler {
         Ok(0.into())
     }
 
+    #[log_syscall(/* rv */ libc::c_int, /* pid */ libc::pid_t, /* cpusetsize */ usize,
+                  /* mask */ *const libc::c_void)]
+    pub fn sched_getaffinity(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let tid = libc::pid_t::from(args.get(0));
+        let size = usize::from(args.get(1));
+        let mask_ptr = PluginPtr::from(args.get(2));
+
+        let cpu_count = ctx.thread.cpu_affinity().cpu_count();
+
+        // the mask must be large enough for all CPUs, and must be a multiple of the word size
+        if size * 8 < usize::try_from(cpu_count).unwrap()
+            || size % std::mem::size_of::<libc::c_ulong>() != 0
+        {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mask = thread_cpu_affinity(ctx, tid)?.to_bytes();
+
+        // we may have a smaller mask than the plugin's buffer
+        let mask = &mask[..std::cmp::min(size, mask.len())];
+        let mask_ptr = TypedPluginPtr::new::<u8>(mask_ptr, mask.len());
+        ctx.process.memory_mut().copy_to_ptr(mask_ptr, mask)?;
+
+        // the syscall returns the number of bytes written, unlike the libc wrapper
+        Ok(libc::c_int::try_from(mask.len()).unwrap().into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* pid */ libc::pid_t, /* cpusetsize */ usize,
+                  /* mask */ *const libc::c_void)]
+    pub fn sched_setaffinity(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let tid = libc::pid_t::from(args.get(0));
+        let size = usize::from(args.get(1));
+        let mask_ptr = PluginPtr::from(args.get(2));
+
+        // ignore any bytes past the end of our mask
+        let size = std::cmp::min(size, ctx.thread.cpu_affinity().mask_size());
+
+        let mut mask = vec![0u8; size];
+        let mask_ptr = TypedPluginPtr::new::<u8>(mask_ptr, size);
+        ctx.process.memory().copy_from_ptr(&mut mask, mask_ptr)?;
+
+        if tid == 0 {
+            set_cpu_affinity(ctx.thread, ctx.process, &mask)?;
+            return Ok(0.into());
+        }
+
+        let cthread = unsafe { c::host_getThread(ctx.host.chost(), tid) };
+        if cthread.is_null() {
+            return Err(Errno::ESRCH.into());
+        }
+
+        // the thread is owned by the host, which outlives the syscall
+        let mut thread = unsafe { CThread::new(cthread) };
+        let cprocess = unsafe { c::thread_getProcess(cthread) };
+        if cprocess == ctx.process.cprocess() {
+            set_cpu_affinity(&mut thread, ctx.process, &mask)?;
+        } else {
+            let mut process = unsafe { Process::borrow_from_c(cprocess) };
+            set_cpu_affinity(&mut thread, &mut process, &mask)?;
+        }
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* cpu */ *const libc::c_uint,
+                  /* node */ *const libc::c_uint, /* tcache */ *const libc::c_void)]
+    pub fn getcpu(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let cpu_ptr = TypedPluginPtr::new::<libc::c_uint>(args.get(0).into(), 1);
+        let node_ptr = TypedPluginPtr::new::<libc::c_uint>(args.get(1).into(), 1);
+        // the third argument is unused since linux 2.6.24
+
+        let cpu = ctx.thread.cpu_affinity().current_cpu();
+
+        if !cpu_ptr.is_null() {
+            ctx.process.memory_mut().copy_to_ptr(cpu_ptr, &[cpu])?;
+        }
+
+        if !node_ptr.is_null() {
+            ctx.process
+                .memory_mut()
+                .copy_to_ptr(node_ptr, &[CURRENT_NODE])?;
+        }
+
+        Ok(0.into())
+    }
+
     #[log_syscall(/* rv */ i32, /* rseq  */*const libc::c_void, /* rseq_len */u32, /* flags */i32, /* sig */u32)]
     pub fn rseq(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
         let rseq_ptr = TypedPluginPtr::new::<rseq>(unsafe { args.get(0).as_ptr }.into(), 1);
@@ -63,15 +160,19 @@ impl SyscallHandler {
             return Err(Errno::EINVAL.into());
         }
         if flags & RSEQ_FLAG_UNREGISTER != 0 {
-            // TODO:
-            // * Validate that an rseq was previously registered
-            // * Validate that `sig` matches registration
-            // * Set the cpu_id of the previously registerd rseq to the uninitialized
-            //   state.
+            // TODO: Validate that `sig` matches registration
+            if ctx.thread.rseq_ptr().map(usize::from) != Some(usize::from(rseq_ptr.ptr())) {
+                return Err(Errno::EINVAL.into());
+            }
+            write_rseq_cpu(
+                ctx.process.memory_mut(),
+                rseq_ptr,
+                RSEQ_CPU_ID_UNINITIALIZED,
+            )?;
+            ctx.thread.set_rseq_ptr(None);
             return Ok(0.into());
         }
-        let mem = ctx.process.memory_mut();
-        let mut rseq = mem.memory_ref_mut(rseq_ptr)?;
+        let current_cpu = i32::try_from(ctx.thread.cpu_affinity().current_cpu()).unwrap();
 
         // rseq is mostly unimplemented, but also mostly unneeded in Shadow.
         // We'd only need to implement the "real" functionality if we ever implement
@@ -87,11 +188,69 @@ impl SyscallHandler {
         // in a handler.
         // https://github.com/shadow/shadow/issues/2139
 
-        // For now we just update to reflect that the thread is running on CPU 0.
-        rseq[0].cpu_id = CURRENT_CPU as i32;
-        rseq[0].cpu_id_start = CURRENT_CPU as i32;
-        rseq.flush()?;
+        // For now we just update to reflect the CPU that the thread is running on, and again
+        // whenever `sched_setaffinity` moves the thread to a different CPU.
+        write_rseq_cpu(ctx.process.memory_mut(), rseq_ptr, current_cpu)?;
+        ctx.thread.set_rseq_ptr(Some(rseq_ptr.ptr()));
 
         Ok(0.into())
     }
 }
+
+/// Get the CPU affinity of the thread `tid` on the current host, where a `tid` of 0 is the calling
+/// thread.
+fn thread_cpu_affinity<'a>(
+    ctx: &'a mut ThreadContext,
+    tid: libc::pid_t,
+) -> Result<&'a mut CpuAffinity, Errno> {
+    if tid == 0 {
+        return Ok(ctx.thread.cpu_affinity_mut());
+    }
+
+    let thread = unsafe { c::host_getThread(ctx.host.chost(), tid) };
+    if thread.is_null() {
+        return Err(Errno::ESRCH);
+    }
+
+    // the thread is owned by the host, which outlives the syscall
+    Ok(unsafe { c::thread_getCpuAffinity(thread).as_mut() }.unwrap())
+}
+
+/// Set the CPU affinity of `thread`, which belongs to `process`. If this changes the CPU that the
+/// thread runs on, the thread's registered rseq area is updated with the new CPU.
+fn set_cpu_affinity(
+    thread: &mut dyn Thread,
+    process: &mut Process,
+    mask: &[u8],
+) -> Result<(), SyscallError> {
+    let old_cpu = thread.cpu_affinity().current_cpu();
+    thread.cpu_affinity_mut().set_from_bytes(mask)?;
+    let new_cpu = thread.cpu_affinity().current_cpu();
+
+    if new_cpu != old_cpu {
+        if let Some(rseq_ptr) = thread.rseq_ptr() {
+            let rseq_ptr = TypedPluginPtr::new::<rseq>(rseq_ptr, 1);
+            write_rseq_cpu(
+                process.memory_mut(),
+                rseq_ptr,
+                i32::try_from(new_cpu).unwrap(),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Set the `cpu_id_start` and `cpu_id` fields of an rseq area.
+fn write_rseq_cpu(
+    mem: &mut MemoryManager,
+    rseq_ptr: TypedPluginPtr<rseq>,
+    cpu: i32,
+) -> Result<(), SyscallError> {
+    let mut rseq = mem.memory_ref_mut(rseq_ptr)?;
+    // `cpu_id_start` is always a valid CPU, even when unregistered
+    rseq[0].cpu_id_start = std::cmp::max(cpu, 0);
+    rseq[0].cpu_id = cpu;
+    rseq.flush()?;
+    Ok(())
+}
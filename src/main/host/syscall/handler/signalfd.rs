@@ -0,0 +1,166 @@
+use crate::cshadow as c;
+use crate::host::context::ThreadContext;
+use crate::host::descriptor::signalfd::SignalFd;
+use crate::host::descriptor::{
+    CompatFile, Descriptor, DescriptorFlags, File, FileStatus, OpenFile,
+};
+use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall::io::{IoVec, IoVecWriter};
+use crate::host::syscall_types::SyscallResult;
+use crate::host::syscall_types::{PluginPtr, SysCallArgs, TypedPluginPtr};
+use crate::utility::event_queue::EventQueue;
+
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use log::*;
+use nix::errno::Errno;
+
+use syscall_logger::log_syscall;
+
+/// Returns the bit representing `signal` in a kernel signal set.
+fn sigset_bit(signal: libc::c_int) -> u64 {
+    1 << (signal - 1)
+}
+
+impl SyscallHandler {
+    #[log_syscall(/* rv */ libc::c_int, /* fd */ libc::c_int, /* mask */ *const libc::sigset_t,
+                  /* sizemask */ libc::size_t)]
+    pub fn signalfd(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd: libc::c_int = args.get(0).into();
+        let mask_ptr: PluginPtr = args.get(1).into();
+        let mask_size: libc::size_t = args.get(2).into();
+
+        self.signalfd_helper(ctx, fd, mask_ptr, mask_size, 0)
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* fd */ libc::c_int, /* mask */ *const libc::sigset_t,
+                  /* sizemask */ libc::size_t, /* flags */ libc::c_int)]
+    pub fn signalfd4(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd: libc::c_int = args.get(0).into();
+        let mask_ptr: PluginPtr = args.get(1).into();
+        let mask_size: libc::size_t = args.get(2).into();
+        let flags: libc::c_int = args.get(3).into();
+
+        self.signalfd_helper(ctx, fd, mask_ptr, mask_size, flags)
+    }
+
+    fn signalfd_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        fd: libc::c_int,
+        mask_ptr: PluginPtr,
+        mask_size: libc::size_t,
+        flags: libc::c_int,
+    ) -> SyscallResult {
+        // the raw syscall takes a kernel signal set, not a libc `sigset_t`
+        if mask_size != std::mem::size_of::<c::shd_kernel_sigset_t>() {
+            debug!("Invalid signalfd mask size {}", mask_size);
+            return Err(Errno::EINVAL.into());
+        }
+
+        if flags & !(libc::SFD_NONBLOCK | libc::SFD_CLOEXEC) != 0 {
+            debug!("Invalid signalfd flags: {}", flags);
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mask_ptr = TypedPluginPtr::new::<u64>(mask_ptr, 1);
+        let mask = ctx.process.memory().read_vals::<_, 1>(mask_ptr)?[0];
+
+        // signalfd(2): "It is not possible to receive SIGKILL or SIGSTOP signals via a signalfd
+        // file descriptor; these signals are silently ignored if specified in mask"
+        let mask = mask & !(sigset_bit(libc::SIGKILL) | sigset_bit(libc::SIGSTOP));
+        let mask = c::shd_kernel_sigset_t { val: mask };
+
+        // signalfd(2): "If the fd argument is not -1, then it must specify a valid existing signalfd
+        // file descriptor, and mask is used to replace the signal set associated with that file
+        // descriptor"
+        if fd != -1 {
+            let signalfd = Self::get_signalfd(ctx, fd)?;
+            EventQueue::queue_and_run(|event_queue| {
+                signalfd.borrow_mut().set_mask(mask, event_queue)
+            });
+            return Ok(fd.into());
+        }
+
+        let mut file_flags = FileStatus::empty();
+        let mut descriptor_flags = DescriptorFlags::empty();
+
+        if flags & libc::SFD_NONBLOCK != 0 {
+            file_flags.insert(FileStatus::NONBLOCK);
+        }
+
+        if flags & libc::SFD_CLOEXEC != 0 {
+            descriptor_flags.insert(DescriptorFlags::CLOEXEC);
+        }
+
+        let file = SignalFd::new(mask, file_flags);
+        let file = Arc::new(AtomicRefCell::new(file));
+
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::SignalFd(file))));
+        desc.set_flags(descriptor_flags);
+
+        let fd = ctx
+            .process
+            .register_descriptor(desc)
+            .map_err(|_| Errno::EMFILE)?;
+
+        // signals may already be pending
+        ctx.process.notify_signalfds();
+
+        trace!("signalfd() returning fd {}", fd);
+
+        Ok(fd.into())
+    }
+
+    /// Read pending signals from a signalfd. Unlike other files, reading a signalfd consumes
+    /// signals pending for the reading thread and its process.
+    pub fn signalfd_read_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        signalfd: &Arc<AtomicRefCell<SignalFd>>,
+        iovs: &[IoVec],
+        offset: libc::off_t,
+    ) -> SyscallResult {
+        let cprocess = ctx.process.cprocess();
+        let cthread = unsafe { ctx.thread.csyscallhandler().as_ref() }
+            .unwrap()
+            .thread;
+
+        let result = signalfd.borrow_mut().read_signals(
+            IoVecWriter::new(iovs, ctx.process.memory_mut()),
+            offset,
+            |mask| {
+                let mut info: c::siginfo_t = unsafe { std::mem::zeroed() };
+                let signo =
+                    unsafe { c::process_takePendingSignal(cprocess, cthread, mask, &mut info) };
+                if signo != 0 {
+                    Some(info)
+                } else {
+                    None
+                }
+            },
+        );
+
+        // the pending signals may have changed (or may have been consumed by the shim since the
+        // signalfds were last updated)
+        ctx.process.notify_signalfds();
+
+        result
+    }
+
+    /// Returns the signalfd for the file descriptor, or an error if it doesn't exist or isn't a
+    /// signalfd.
+    fn get_signalfd(
+        ctx: &mut ThreadContext,
+        fd: libc::c_int,
+    ) -> Result<Arc<AtomicRefCell<SignalFd>>, Errno> {
+        match Self::get_descriptor(ctx.process, fd)?.file() {
+            CompatFile::New(file) => match file.inner_file() {
+                File::SignalFd(signalfd) => Ok(Arc::clone(signalfd)),
+                _ => Err(Errno::EINVAL),
+            },
+            CompatFile::Legacy(_) => Err(Errno::EINVAL),
+        }
+    }
+}
@@ -1,21 +1,33 @@
+use crate::core::support::simulation_time::SimulationTime;
+use crate::core::worker::Worker;
 use crate::cshadow as c;
 use crate::host::context::ThreadContext;
+use crate::host::descriptor::socket::inet::tcp::TcpSocket;
+use crate::host::descriptor::socket::inet::udp::UdpSocket;
+use crate::host::descriptor::socket::inet::InetSocket;
 use crate::host::descriptor::socket::unix::{UnixSocket, UnixSocketType};
-use crate::host::descriptor::socket::Socket;
+use crate::host::descriptor::socket::{AncillaryData, Socket};
 use crate::host::descriptor::{
-    CompatFile, Descriptor, DescriptorFlags, File, FileState, FileStatus, OpenFile,
+    CompatFile, Descriptor, DescriptorFlags, File, FileState, FileStatus, LegacyFileCounter,
+    OpenFile,
 };
-use crate::host::memory_manager::MemoryManager;
+use crate::host::memory_manager::{AllocdMem, MemoryManager};
 use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall::io::{
+    iovecs_len, iovecs_skip, read_iovecs, IoVec, IoVecReader, IoVecWriter, IOV_MAX,
+};
 use crate::host::syscall::Trigger;
 use crate::host::syscall_condition::SysCallCondition;
-use crate::host::syscall_types::{Blocked, PluginPtr, SysCallArgs, TypedPluginPtr};
+use crate::host::syscall_types::{Blocked, PluginPtr, SysCallArgs, SysCallReg, TypedPluginPtr};
 use crate::host::syscall_types::{SyscallError, SyscallResult};
 use crate::utility::event_queue::EventQueue;
+use crate::utility::{pod, HostTreePointer};
+
+use std::io::{Read, Write};
 
 use log::*;
 use nix::errno::Errno;
-use nix::sys::socket::{MsgFlags, SockFlag};
+use nix::sys::socket::{MsgFlags, SockFlag, SockType};
 
 use syscall_logger::log_syscall;
 
@@ -31,8 +43,18 @@ impl SyscallHandler {
         let flags = socket_type & (libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC);
         let socket_type = socket_type & !flags;
 
-        // if it's not a unix socket, use the C syscall handler instead
-        if domain != libc::AF_UNIX {
+        let is_udp = domain == libc::AF_INET
+            && socket_type == libc::SOCK_DGRAM
+            && (protocol == 0 || protocol == libc::IPPROTO_UDP);
+
+        // the rust tcp socket is experimental, so it's only used if enabled for this host
+        let is_tcp = domain == libc::AF_INET
+            && socket_type == libc::SOCK_STREAM
+            && (protocol == 0 || protocol == libc::IPPROTO_TCP)
+            && unsafe { c::host_useNewTcp(ctx.host.chost()) } != 0;
+
+        // if it's not a unix, udp, or rust tcp socket, use the C syscall handler instead
+        if domain != libc::AF_UNIX && !is_udp && !is_tcp {
             return unsafe {
                 c::syscallhandler_socket(
                     ctx.thread.csyscallhandler(),
@@ -75,16 +97,29 @@ impl SyscallHandler {
                 Socket::Unix(UnixSocket::new(
                     file_flags,
                     socket_type,
+                    process_creds(ctx),
                     ctx.host.abstract_unix_namespace(),
+                    ctx.host.pathname_unix_namespace(),
                 ))
             }
+            libc::AF_INET if is_tcp => Socket::Inet(InetSocket::Tcp(TcpSocket::new(
+                file_flags,
+                HostTreePointer::new(ctx.host.chost()),
+            ))),
+            libc::AF_INET => Socket::Inet(InetSocket::Udp(UdpSocket::new(
+                file_flags,
+                HostTreePointer::new(ctx.host.chost()),
+            ))),
             _ => return Err(Errno::EAFNOSUPPORT.into()),
         };
 
         let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::Socket(socket))));
         desc.set_flags(descriptor_flags);
 
-        let fd = ctx.process.register_descriptor(desc);
+        let fd = ctx
+            .process
+            .register_descriptor(desc)
+            .map_err(|_| Errno::EMFILE)?;
 
         debug!("Created socket fd {}", fd);
 
@@ -171,6 +206,21 @@ impl SyscallHandler {
         self.sendto_helper(ctx, file, buf_ptr, buf_len, flags, addr_ptr, addr_len)
     }
 
+    /// Send the data in the io vectors on a connected socket, as `write()` and `writev()` do.
+    pub fn send_iovecs_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        open_file: OpenFile,
+        iovs: &[IoVec],
+        flags: libc::c_int,
+    ) -> SyscallResult {
+        let ancillary = AncillaryData {
+            rights: Vec::new(),
+            creds: Some(process_creds(ctx)),
+        };
+        self.sendmsg_helper(ctx, open_file, iovs, flags, PluginPtr::null(), 0, ancillary)
+    }
+
     pub fn sendto_helper(
         &self,
         ctx: &mut ThreadContext,
@@ -180,14 +230,40 @@ impl SyscallHandler {
         flags: libc::c_int,
         addr_ptr: PluginPtr,
         addr_len: libc::socklen_t,
+    ) -> SyscallResult {
+        let iov = IoVec {
+            base: buf_ptr,
+            len: buf_len,
+        };
+        let ancillary = AncillaryData {
+            rights: Vec::new(),
+            creds: Some(process_creds(ctx)),
+        };
+        self.sendmsg_helper(ctx, open_file, &[iov], flags, addr_ptr, addr_len, ancillary)
+    }
+
+    /// Send the data in the io vectors as a single message (for message-based sockets) or as a
+    /// contiguous sequence of bytes (for stream sockets), along with any ancillary data.
+    fn sendmsg_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        open_file: OpenFile,
+        iovs: &[IoVec],
+        flags: libc::c_int,
+        addr_ptr: PluginPtr,
+        addr_len: libc::socklen_t,
+        ancillary: AncillaryData,
     ) -> SyscallResult {
         let socket = match open_file.inner_file() {
             File::Socket(ref x) => x,
             _ => return Err(Errno::ENOTSOCK.into()),
         };
 
+        // nix doesn't support the `MSG_MORE` flag, so the socket is given the raw flags
+        let raw_flags = flags;
+
         // get the send flags
-        let flags = match MsgFlags::from_bits(flags) {
+        let flags = match MsgFlags::from_bits(flags & !libc::MSG_MORE) {
             Some(x) => x,
             None => {
                 // linux doesn't return an error if there are unexpected flags
@@ -196,11 +272,6 @@ impl SyscallHandler {
             }
         };
 
-        // MSG_NOSIGNAL is currently a no-op, since we haven't implemented the behavior
-        // it's meant to disable.
-        // TODO: Once we've implemented generating a SIGPIPE when the peer on a
-        // stream-oriented socket has closed the connection, MSG_NOSIGNAL should
-        // disable it.
         let supported_flags = MsgFlags::MSG_DONTWAIT | MsgFlags::MSG_NOSIGNAL;
         if flags.intersects(!supported_flags) {
             warn!("Unsupported sendto flags: {:?}", flags);
@@ -209,21 +280,33 @@ impl SyscallHandler {
 
         let addr = read_sockaddr(ctx.process.memory(), addr_ptr, addr_len)?;
 
-        debug!("Attempting to send {} bytes to {:?}", buf_len, addr);
+        debug!(
+            "Attempting to send {} bytes to {:?}",
+            iovecs_len(iovs),
+            addr
+        );
 
         let file_status = socket.borrow().get_status();
 
         // call the socket's sendto(), and run any resulting events
         let result = EventQueue::queue_and_run(|event_queue| {
             socket.borrow_mut().sendto(
-                ctx.process
-                    .memory()
-                    .reader(TypedPluginPtr::new::<u8>(buf_ptr, buf_len)),
+                IoVecReader::new(iovs, ctx.process.memory()),
+                raw_flags,
                 addr,
+                ancillary,
                 event_queue,
             )
         });
 
+        // linux raises SIGPIPE for stream sockets, unless the `MSG_NOSIGNAL` flag is set
+        if result == Err(Errno::EPIPE.into())
+            && socket.borrow().socket_type() == SockType::Stream
+            && !flags.contains(MsgFlags::MSG_NOSIGNAL)
+        {
+            Self::raise_sigpipe(ctx);
+        }
+
         // if the syscall would block, it's a blocking descriptor, and the `MSG_DONTWAIT` flag is not set
         if result == Err(Errno::EWOULDBLOCK.into())
             && !file_status.contains(FileStatus::NONBLOCK)
@@ -269,6 +352,18 @@ impl SyscallHandler {
             // get the file from the descriptor table, or return early if it doesn't exist
             None => match Self::get_descriptor(ctx.process, fd)?.file() {
                 CompatFile::New(file) => file.clone(),
+                // linux keeps receiving on a tcp socket until the buffer is full
+                CompatFile::Legacy(file) if legacy_waitall_applies(file, flags) => {
+                    let buf = IoVec {
+                        base: buf_ptr,
+                        len: buf_len,
+                    };
+                    let num_received = self.recv_waitall(ctx, &[buf], |ctx, iovs| {
+                        let rv = legacy_recvfrom(ctx, fd, iovs[0], flags, addr_ptr, addr_len_ptr)?;
+                        Ok((rv.into(), false))
+                    })?;
+                    return Ok(num_received.into());
+                }
                 // if it's a legacy file, use the C syscall handler instead
                 CompatFile::Legacy(_) => {
                     return unsafe {
@@ -295,6 +390,60 @@ impl SyscallHandler {
         addr_ptr: PluginPtr,
         addr_len_ptr: PluginPtr,
     ) -> SyscallResult {
+        let iov = IoVec {
+            base: buf_ptr,
+            len: buf_len,
+        };
+        // any ancillary data (for example passed file descriptors) is discarded
+        let (result, from_addr, _msg_len, _ancillary) =
+            self.recvmsg_helper(ctx, open_file, &[iov], flags)?;
+
+        if !addr_ptr.is_null() {
+            write_sockaddr(
+                ctx.process.memory_mut(),
+                from_addr,
+                addr_ptr,
+                TypedPluginPtr::new::<libc::socklen_t>(addr_len_ptr, 1),
+            )?;
+        }
+
+        Ok(result)
+    }
+
+    /// Receive data into the io vectors on a connected socket, as `read()` and `readv()` do.
+    pub fn recv_iovecs_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        open_file: OpenFile,
+        iovs: &[IoVec],
+        flags: libc::c_int,
+    ) -> SyscallResult {
+        // any ancillary data (for example passed file descriptors) is discarded
+        let (result, _from_addr, _msg_len, _ancillary) =
+            self.recvmsg_helper(ctx, open_file, iovs, flags)?;
+        Ok(result)
+    }
+
+    /// Receive data into the io vectors. Returns the number of bytes copied, the source address,
+    /// the length of the received message (which may be larger than the number of bytes copied
+    /// if the message was truncated), and any ancillary data received with the message.
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvmsg_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        open_file: OpenFile,
+        iovs: &[IoVec],
+        flags: libc::c_int,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    > {
         let socket = match open_file.inner_file() {
             File::Socket(ref x) => x,
             _ => return Err(Errno::ENOTSOCK.into()),
@@ -310,26 +459,96 @@ impl SyscallHandler {
             }
         };
 
-        let supported_flags = MsgFlags::MSG_DONTWAIT;
+        let supported_flags = MsgFlags::MSG_DONTWAIT
+            | MsgFlags::MSG_PEEK
+            | MsgFlags::MSG_TRUNC
+            | MsgFlags::MSG_WAITALL;
         if flags.intersects(!supported_flags) {
             warn!("Unsupported recvfrom flags: {:?}", flags);
             return Err(Errno::EOPNOTSUPP.into());
         }
 
-        debug!("Attempting to recv {} bytes", buf_len);
+        let file_status = socket.borrow().get_status();
+
+        // linux keeps receiving on a stream socket until the buffer is full
+        let waitall = waitall_applies(flags, file_status.contains(FileStatus::NONBLOCK))
+            && socket.borrow().socket_type() == SockType::Stream;
+
+        if !waitall {
+            return Self::recvmsg_once(ctx, &open_file, iovs, flags);
+        }
+
+        let mut from_addr = None;
+        let mut ancillary = AncillaryData::default();
+
+        let num_received = self.recv_waitall(ctx, iovs, |ctx, iovs| {
+            let (rv, addr, _msg_len, received_ancillary) =
+                Self::recvmsg_once(ctx, &open_file, iovs, flags)?;
+
+            if from_addr.is_none() {
+                from_addr = addr;
+            }
+            ancillary.creds = ancillary.creds.or(received_ancillary.creds);
+
+            // like linux, stop receiving once any files have been received
+            let received_files = !received_ancillary.rights.is_empty();
+            ancillary.rights = received_ancillary.rights;
+
+            Ok((rv.into(), received_files))
+        })?;
+
+        Ok((num_received.into(), from_addr, num_received, ancillary))
+    }
+
+    /// Receive data into the io vectors with a single call to the socket's `recvfrom()`. Returns
+    /// the same values as [`recvmsg_helper()`](Self::recvmsg_helper).
+    // https://github.com/shadow/shadow/issues/2093
+    #[allow(deprecated)]
+    fn recvmsg_once(
+        ctx: &mut ThreadContext,
+        open_file: &OpenFile,
+        iovs: &[IoVec],
+        flags: MsgFlags,
+    ) -> Result<
+        (
+            SysCallReg,
+            Option<nix::sys::socket::SockAddr>,
+            usize,
+            AncillaryData,
+        ),
+        SyscallError,
+    > {
+        let socket = match open_file.inner_file() {
+            File::Socket(ref x) => x,
+            _ => return Err(Errno::ENOTSOCK.into()),
+        };
+
+        debug!("Attempting to recv {} bytes", iovecs_len(iovs));
 
         let file_status = socket.borrow().get_status();
 
         // call the socket's recvfrom(), and run any resulting events
         let result = EventQueue::queue_and_run(|event_queue| {
             socket.borrow_mut().recvfrom(
-                ctx.process
-                    .memory_mut()
-                    .writer(TypedPluginPtr::new::<u8>(buf_ptr, buf_len)),
+                IoVecWriter::new(iovs, ctx.process.memory_mut()),
+                flags,
                 event_queue,
             )
         });
 
+        // for message-based sockets, the `MSG_TRUNC` flag returns the full length of the message
+        // even if it was longer than the buffer (linux ignores the flag for unix stream sockets)
+        let result = result.map(|(num_copied, from_addr, msg_len, ancillary)| {
+            let rv = if flags.contains(MsgFlags::MSG_TRUNC)
+                && socket.borrow().socket_type() != SockType::Stream
+            {
+                msg_len.into()
+            } else {
+                num_copied
+            };
+            (rv, from_addr, msg_len, ancillary)
+        });
+
         // if the syscall would block, it's a blocking descriptor, and the `MSG_DONTWAIT` flag is not set
         if result == Err(Errno::EWOULDBLOCK.into())
             && !file_status.contains(FileStatus::NONBLOCK)
@@ -338,7 +557,7 @@ impl SyscallHandler {
             let trigger = Trigger::from_file(open_file.inner_file().clone(), FileState::READABLE);
             let mut cond = SysCallCondition::new(trigger);
             let supports_sa_restart = socket.borrow().supports_sa_restart();
-            cond.set_active_file(open_file);
+            cond.set_active_file(open_file.clone());
 
             return Err(SyscallError::Blocked(Blocked {
                 condition: cond,
@@ -346,15 +565,431 @@ impl SyscallHandler {
             }));
         };
 
-        let (result, from_addr) = result?;
+        result
+    }
 
-        if !addr_ptr.is_null() {
-            write_sockaddr(
-                ctx.process.memory_mut(),
-                from_addr,
-                addr_ptr,
-                TypedPluginPtr::new::<libc::socklen_t>(addr_len_ptr, 1),
-            )?;
+    /// Receive with `MSG_WAITALL` on a stream socket, which keeps receiving until the io vectors
+    /// are full rather than returning once any data is available. `recv_fn` receives into the
+    /// given io vectors and returns the number of bytes received, and whether the receive must
+    /// return early. Returns the total number of bytes received.
+    ///
+    /// A blocked syscall is run again from the start, so if the receive blocks after receiving
+    /// some data, the number of bytes received is stored and the next run of the syscall receives
+    /// into the remainder of the io vectors.
+    fn recv_waitall(
+        &self,
+        ctx: &mut ThreadContext,
+        iovs: &[IoVec],
+        mut recv_fn: impl FnMut(&mut ThreadContext, &[IoVec]) -> Result<(usize, bool), SyscallError>,
+    ) -> Result<usize, SyscallError> {
+        let total_len = iovecs_len(iovs);
+
+        // continue from where we left off if this syscall was previously blocked
+        let mut num_received = self.waitall_num_received.take();
+        if ctx.thread.syscall_condition().is_none() {
+            num_received = 0;
+        }
+
+        loop {
+            match recv_fn(ctx, &iovecs_skip(iovs, num_received)) {
+                Ok((n, return_early)) => {
+                    num_received += n;
+
+                    // stop at EOF or once the buffer is full
+                    if n == 0 || return_early || num_received >= total_len {
+                        return Ok(num_received);
+                    }
+                }
+                Err(e) if num_received == 0 => return Err(e),
+                Err(SyscallError::Blocked(blocked)) => {
+                    // a signal would interrupt the syscall with EINTR, but linux returns the data
+                    // that was already received
+                    if ctx.thread.unblocked_signal_pending(ctx.host) {
+                        return Ok(num_received);
+                    }
+
+                    self.waitall_num_received.set(num_received);
+                    return Err(SyscallError::Blocked(blocked));
+                }
+                // return the data that was already received; the socket will return the error
+                // again on the next receive
+                Err(_) => return Ok(num_received),
+            }
+        }
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* sockfd */ libc::c_int, /* msg */ *const libc::msghdr,
+                  /* flags */ nix::sys::socket::MsgFlags)]
+    pub fn sendmsg(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd: libc::c_int = args.get(0).into();
+        let msg_ptr: PluginPtr = args.get(1).into();
+        let flags: libc::c_int = args.get(2).into();
+
+        let file = Self::socket_file(ctx, fd)?;
+
+        self.sendmsg_one(ctx, fd, &file, msg_ptr, flags)
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* sockfd */ libc::c_int, /* msg */ *const libc::msghdr,
+                  /* flags */ nix::sys::socket::MsgFlags)]
+    pub fn recvmsg(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd: libc::c_int = args.get(0).into();
+        let msg_ptr: PluginPtr = args.get(1).into();
+        let flags: libc::c_int = args.get(2).into();
+
+        let file = Self::socket_file(ctx, fd)?;
+
+        self.recvmsg_one(ctx, fd, &file, msg_ptr, flags)
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* sockfd */ libc::c_int, /* msgvec */ *const libc::mmsghdr,
+                  /* vlen */ libc::c_uint, /* flags */ nix::sys::socket::MsgFlags)]
+    pub fn sendmmsg(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd: libc::c_int = args.get(0).into();
+        let msgvec_ptr: PluginPtr = args.get(1).into();
+        let vlen: libc::c_uint = args.get(2).into();
+        let flags: libc::c_int = args.get(3).into();
+
+        let file = Self::socket_file(ctx, fd)?;
+
+        // linux silently caps the number of messages
+        let vlen = std::cmp::min(vlen as usize, IOV_MAX);
+        let msgvec_ptr = TypedPluginPtr::new::<libc::mmsghdr>(msgvec_ptr, vlen);
+
+        let mut num_sent: libc::c_int = 0;
+        for i in 0..vlen {
+            let mmsg_ptr = msgvec_ptr.slice(i..i + 1);
+
+            // we can't block once a message has been sent since we can't restart the syscall
+            // from where it left off
+            let flags = if i == 0 {
+                flags
+            } else {
+                flags | libc::MSG_DONTWAIT
+            };
+
+            // the `msg_hdr` field is at the start of the `mmsghdr`
+            let rv = self
+                .sendmsg_one(ctx, fd, &file, mmsg_ptr.ptr(), flags)
+                .and_then(|len| set_mmsghdr_len(ctx.process.memory_mut(), mmsg_ptr, len));
+
+            match rv {
+                Ok(()) => num_sent += 1,
+                // only return an error if no messages were sent
+                Err(e) if num_sent == 0 => return Err(e),
+                Err(_) => break,
+            }
+        }
+
+        Ok(num_sent.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* sockfd */ libc::c_int, /* msgvec */ *const libc::mmsghdr,
+                  /* vlen */ libc::c_uint, /* flags */ nix::sys::socket::MsgFlags,
+                  /* timeout */ *const libc::timespec)]
+    pub fn recvmmsg(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd: libc::c_int = args.get(0).into();
+        let msgvec_ptr: PluginPtr = args.get(1).into();
+        let vlen: libc::c_uint = args.get(2).into();
+        let flags: libc::c_int = args.get(3).into();
+        let timeout_ptr = TypedPluginPtr::new::<libc::timespec>(args.get(4).into(), 1);
+
+        // like linux, the timeout is only checked after each message is received, so the syscall
+        // will still block indefinitely if no messages arrive
+        let timeout = if timeout_ptr.is_null() {
+            None
+        } else {
+            let timeout = ctx.process.memory().read_vals::<_, 1>(timeout_ptr)?[0];
+            Some(SimulationTime::try_from(timeout).map_err(|_| Errno::EINVAL)?)
+        };
+
+        let file = Self::socket_file(ctx, fd)?;
+
+        // if we were previously blocked, keep the original deadline
+        let now = Worker::current_time().unwrap();
+        let prev_deadline = ctx.thread.syscall_condition().and_then(|x| x.timeout());
+        let deadline = timeout.map(|x| prev_deadline.unwrap_or_else(|| now.saturating_add(x)));
+
+        // linux silently caps the number of messages
+        let vlen = std::cmp::min(vlen as usize, IOV_MAX);
+        let msgvec_ptr = TypedPluginPtr::new::<libc::mmsghdr>(msgvec_ptr, vlen);
+
+        // only the first message can block; linux would otherwise wait for all `vlen` messages
+        // (unless MSG_WAITFORONE is set), but we can't restart the syscall from where it left off
+        let flags = flags & !libc::MSG_WAITFORONE;
+
+        let mut num_recv: libc::c_int = 0;
+        for i in 0..vlen {
+            // the timeout expired after receiving the previous message
+            if i > 0 && deadline.map(|x| now >= x).unwrap_or(false) {
+                break;
+            }
+
+            let mmsg_ptr = msgvec_ptr.slice(i..i + 1);
+
+            let flags = if i == 0 {
+                flags
+            } else {
+                flags | libc::MSG_DONTWAIT
+            };
+
+            // the `msg_hdr` field is at the start of the `mmsghdr`
+            let rv = self
+                .recvmsg_one(ctx, fd, &file, mmsg_ptr.ptr(), flags)
+                .and_then(|len| set_mmsghdr_len(ctx.process.memory_mut(), mmsg_ptr, len));
+
+            match rv {
+                Ok(()) => num_recv += 1,
+                // wake up at the deadline so that we know if it expired while blocked, but only
+                // once since the syscall keeps blocking after it expires
+                Err(SyscallError::Blocked(mut blocked)) if num_recv == 0 => {
+                    if let Some(deadline) = deadline {
+                        if now < deadline {
+                            blocked.condition.set_timeout(ctx.host.chost(), deadline);
+                        }
+                    }
+                    return Err(SyscallError::Blocked(blocked));
+                }
+                // only return an error if no messages were received
+                Err(e) if num_recv == 0 => return Err(e),
+                Err(_) => break,
+            }
+        }
+
+        // linux updates the timeout with the time remaining
+        if let Some(deadline) = deadline {
+            let remaining =
+                libc::timespec::try_from(deadline.saturating_duration_since(&now)).unwrap();
+            ctx.process
+                .memory_mut()
+                .copy_to_ptr(timeout_ptr, &[remaining])?;
+        }
+
+        Ok(num_recv.into())
+    }
+
+    /// Get the file for the socket syscall. If we were previously blocked, the active file from
+    /// the last syscall handler invocation is used since it may no longer exist in the descriptor
+    /// table.
+    fn socket_file(ctx: &mut ThreadContext, fd: libc::c_int) -> Result<CompatFile, SyscallError> {
+        let file = ctx
+            .thread
+            .syscall_condition()
+            // if this was for a C descriptor, then there won't be an active file object
+            .map(|x| x.active_file().cloned())
+            .flatten();
+
+        Ok(match file {
+            Some(x) => CompatFile::New(x),
+            None => Self::get_descriptor(ctx.process, fd)?.file().clone(),
+        })
+    }
+
+    /// Send a single `msghdr`.
+    fn sendmsg_one(
+        &self,
+        ctx: &mut ThreadContext,
+        fd: libc::c_int,
+        file: &CompatFile,
+        msg_ptr: PluginPtr,
+        flags: libc::c_int,
+    ) -> SyscallResult {
+        let (msg, iovs) = read_msghdr(ctx.process.memory(), msg_ptr)?;
+
+        let has_control = !msg.msg_control.is_null() && msg.msg_controllen > 0;
+
+        let addr_ptr = PluginPtr::from(msg.msg_name as usize);
+        let addr_len = msg.msg_namelen;
+
+        match file {
+            CompatFile::New(file) => {
+                let mut ancillary = AncillaryData {
+                    rights: Vec::new(),
+                    creds: Some(process_creds(ctx)),
+                };
+
+                if has_control {
+                    // linux limits the control buffer size to 'optmem_max'
+                    if msg.msg_controllen > MAX_CONTROL_LEN {
+                        return Err(Errno::ENOBUFS.into());
+                    }
+
+                    let mut control = vec![0u8; msg.msg_controllen];
+                    ctx.process.memory().copy_from_ptr(
+                        &mut control,
+                        TypedPluginPtr::new::<u8>(
+                            PluginPtr::from(msg.msg_control as usize),
+                            msg.msg_controllen,
+                        ),
+                    )?;
+
+                    let (fds, creds) = parse_control_msgs(&control)?;
+
+                    for fd in fds {
+                        let file = Self::get_descriptor(ctx.process, fd)?.file().clone();
+                        ancillary.rights.push(file);
+                    }
+
+                    if let Some(creds) = creds {
+                        // a process can only send credentials that match its own ids
+                        if !creds_are_permitted(&creds, ancillary.creds.as_ref().unwrap()) {
+                            return Err(Errno::EPERM.into());
+                        }
+                        ancillary.creds = Some(creds);
+                    }
+                }
+
+                self.sendmsg_helper(
+                    ctx,
+                    file.clone(),
+                    &iovs,
+                    flags,
+                    addr_ptr,
+                    addr_len,
+                    ancillary,
+                )
+            }
+            CompatFile::Legacy(file) => {
+                if has_control {
+                    warn!("Ancillary data for sendmsg is not supported for this socket");
+                    return Err(Errno::EOPNOTSUPP.into());
+                }
+
+                let file_type = unsafe { c::legacyfile_getType(file.ptr()) };
+                legacy_sendmsg(ctx, fd, file_type, &iovs, flags, addr_ptr, addr_len)
+            }
+        }
+    }
+
+    /// Receive into a single `msghdr`, and update the `msghdr` in plugin memory.
+    fn recvmsg_one(
+        &self,
+        ctx: &mut ThreadContext,
+        fd: libc::c_int,
+        file: &CompatFile,
+        msg_ptr: PluginPtr,
+        flags: libc::c_int,
+    ) -> SyscallResult {
+        let (mut msg, iovs) = read_msghdr(ctx.process.memory(), msg_ptr)?;
+        let addr_ptr = PluginPtr::from(msg.msg_name as usize);
+
+        // only affects the file descriptors received in the ancillary data
+        let cloexec = flags & libc::MSG_CMSG_CLOEXEC != 0;
+        let flags = flags & !libc::MSG_CMSG_CLOEXEC;
+
+        let (result, from_addr, msg_len, ancillary) = match file {
+            CompatFile::New(file) => {
+                let (result, from_addr, msg_len, ancillary) =
+                    self.recvmsg_helper(ctx, file.clone(), &iovs, flags)?;
+                (result, Some(from_addr), msg_len, ancillary)
+            }
+            CompatFile::Legacy(file) => {
+                let file_type = unsafe { c::legacyfile_getType(file.ptr()) };
+                let (result, msg_len) = if legacy_waitall_applies(file, flags) {
+                    // linux keeps receiving on a tcp socket until the buffer is full
+                    let num_received = self.recv_waitall(ctx, &iovs, |ctx, iovs| {
+                        let (rv, _msg_len) =
+                            legacy_recvmsg(ctx, fd, file_type, iovs, flags, addr_ptr, msg_ptr)?;
+                        Ok((rv.into(), false))
+                    })?;
+                    (num_received.into(), num_received)
+                } else {
+                    legacy_recvmsg(ctx, fd, file_type, &iovs, flags, addr_ptr, msg_ptr)?
+                };
+
+                // the C syscall handler may have updated the `msg_namelen`
+                msg = read_msghdr(ctx.process.memory(), msg_ptr)?.0;
+                if file_type != c::_LegacyFileType_DT_UDPSOCKET && !addr_ptr.is_null() {
+                    // only UDP sockets return an address
+                    msg.msg_namelen = 0;
+                }
+
+                (result, None, msg_len, AncillaryData::default())
+            }
+        };
+
+        let control_len = if msg.msg_control.is_null() {
+            0
+        } else {
+            msg.msg_controllen
+        };
+
+        // add the received files to the descriptor table, but only as many as will fit in the
+        // control buffer; the remaining files are dropped (closed)
+        let mut rights = ancillary.rights;
+        let num_fds = control_fd_capacity(ancillary.creds.is_some(), control_len);
+        let num_fds = std::cmp::min(num_fds, rights.len());
+        let _dropped = rights.split_off(num_fds);
+
+        // like linux, any files that don't fit within the fd limit are also dropped, and the
+        // control data is marked as truncated
+        let mut fds_dropped = false;
+        let fds: Vec<libc::c_int> = rights
+            .into_iter()
+            .filter_map(|file| {
+                let mut desc = Descriptor::new(file);
+                if cloexec {
+                    desc.set_flags(DescriptorFlags::CLOEXEC);
+                }
+                match ctx.process.register_descriptor(desc) {
+                    Ok(fd) => Some(fd as libc::c_int),
+                    Err(_) => {
+                        fds_dropped = true;
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let (control, control_truncated) = build_control_msgs(ancillary.creds, &fds, control_len);
+
+        msg.msg_controllen = control.len();
+        msg.msg_flags = 0;
+        if msg_len > iovecs_len(&iovs) {
+            msg.msg_flags |= libc::MSG_TRUNC;
+        }
+        if control_truncated || fds_dropped {
+            msg.msg_flags |= libc::MSG_CTRUNC;
+        }
+
+        let write_res = (|| {
+            let mem = ctx.process.memory_mut();
+
+            if !control.is_empty() {
+                mem.copy_to_ptr(
+                    TypedPluginPtr::new::<u8>(
+                        PluginPtr::from(msg.msg_control as usize),
+                        control.len(),
+                    ),
+                    &control,
+                )?;
+            }
+
+            mem.copy_to_ptr(TypedPluginPtr::new::<libc::msghdr>(msg_ptr, 1), &[msg])?;
+
+            // for rust sockets we need to write the address ourselves
+            if let Some(from_addr) = from_addr {
+                if !addr_ptr.is_null() {
+                    write_sockaddr(mem, from_addr, addr_ptr, msg_namelen_ptr(msg_ptr))?;
+                }
+            }
+
+            Ok::<_, SyscallError>(())
+        })();
+
+        // if the message header couldn't be written, the caller will never learn about the
+        // received file descriptors, so remove them from the descriptor table
+        if let Err(e) = write_res {
+            EventQueue::queue_and_run(|event_queue| {
+                for fd in fds {
+                    // ignore any errors when closing
+                    ctx.process
+                        .deregister_descriptor(fd.try_into().unwrap())
+                        .unwrap()
+                        .close(ctx.host.chost(), event_queue);
+                }
+            });
+            return Err(e);
         }
 
         Ok(result)
@@ -588,6 +1223,12 @@ impl SyscallHandler {
             }
         };
 
+        // like linux, check for a free descriptor before accepting so that the connection stays
+        // queued if there isn't one
+        if ctx.process.at_fd_limit() {
+            return Err(Errno::EMFILE.into());
+        }
+
         let result =
             EventQueue::queue_and_run(|event_queue| socket.borrow_mut().accept(event_queue));
 
@@ -637,7 +1278,8 @@ impl SyscallHandler {
             new_desc.set_flags(DescriptorFlags::CLOEXEC);
         }
 
-        let new_fd = ctx.process.register_descriptor(new_desc);
+        // we checked the fd limit above
+        let new_fd = ctx.process.register_descriptor(new_desc).unwrap();
 
         Ok(new_fd.into())
     }
@@ -682,6 +1324,14 @@ impl SyscallHandler {
             _ => return Err(Errno::ENOTSOCK.into()),
         };
 
+        // connecting to an `AF_UNSPEC` address dissolves the socket's association with its peer
+        if read_sockaddr_family(ctx.process.memory(), addr_ptr, addr_len)?
+            == Some(libc::AF_UNSPEC as libc::sa_family_t)
+        {
+            EventQueue::queue_and_run(|event_queue| socket.disconnect(event_queue))?;
+            return Ok(0.into());
+        }
+
         let addr = read_sockaddr(ctx.process.memory(), addr_ptr, addr_len)?.ok_or(Errno::EINVAL)?;
 
         let mut rv =
@@ -701,6 +1351,7 @@ impl SyscallHandler {
     #[log_syscall(/* rv */ libc::c_int, /* sockfd */ libc::c_int, /* how */ libc::c_int)]
     pub fn shutdown(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
         let fd: libc::c_int = args.get(0).into();
+        let how: libc::c_int = args.get(1).into();
 
         // get the descriptor, or return early if it doesn't exist
         let desc = Self::get_descriptor(ctx.process, fd)?;
@@ -724,13 +1375,16 @@ impl SyscallHandler {
             _ => return Err(Errno::ENOTSOCK.into()),
         };
 
-        // TODO: support rust sockets
-        log::warn!(
-            "shutdown() syscall not yet supported for fd {} of type {:?}; Returning ENOSYS",
-            fd,
-            socket,
-        );
-        Err(Errno::ENOSYS.into())
+        let how = match how {
+            libc::SHUT_RD => nix::sys::socket::Shutdown::Read,
+            libc::SHUT_WR => nix::sys::socket::Shutdown::Write,
+            libc::SHUT_RDWR => nix::sys::socket::Shutdown::Both,
+            _ => return Err(Errno::EINVAL.into()),
+        };
+
+        EventQueue::queue_and_run(|event_queue| socket.borrow_mut().shutdown(how, event_queue))?;
+
+        Ok(0.into())
     }
 
     #[log_syscall(/* rv */ libc::c_int, /* domain */ nix::sys::socket::AddressFamily,
@@ -783,7 +1437,9 @@ impl SyscallHandler {
             UnixSocket::pair(
                 file_flags,
                 socket_type,
+                process_creds(ctx),
                 ctx.host.abstract_unix_namespace(),
+                ctx.host.pathname_unix_namespace(),
                 event_queue,
             )
         });
@@ -801,8 +1457,23 @@ impl SyscallHandler {
         desc_2.set_flags(descriptor_flags);
 
         // register the file descriptors
-        let fd_1 = ctx.process.register_descriptor(desc_1);
-        let fd_2 = ctx.process.register_descriptor(desc_2);
+        let fd_1 = ctx
+            .process
+            .register_descriptor(desc_1)
+            .map_err(|_| Errno::EMFILE)?;
+        let fd_2 = match ctx.process.register_descriptor(desc_2) {
+            Ok(fd) => fd,
+            Err(_) => {
+                // ignore any errors when closing
+                EventQueue::queue_and_run(|event_queue| {
+                    ctx.process
+                        .deregister_descriptor(fd_1)
+                        .unwrap()
+                        .close(ctx.host.chost(), event_queue)
+                });
+                return Err(Errno::EMFILE.into());
+            }
+        };
 
         // try to write them to the caller
         let fds = [i32::try_from(fd_1).unwrap(), i32::try_from(fd_2).unwrap()];
@@ -859,13 +1530,28 @@ impl SyscallHandler {
             _ => return Err(Errno::ENOTSOCK.into()),
         };
 
-        // TODO: support rust sockets
-        log::warn!(
-            "getsockopt() syscall not yet supported for fd {} of type {:?}; Returning ENOSYS",
-            fd,
-            socket,
-        );
-        Err(Errno::ENOSYS.into())
+        let level: libc::c_int = args.get(1).into();
+        let optname: libc::c_int = args.get(2).into();
+        let optval_ptr: PluginPtr = args.get(3).into();
+        let optlen_ptr = TypedPluginPtr::new::<libc::socklen_t>(args.get(4).into(), 1);
+
+        let socket = socket.clone();
+
+        let [optlen] = ctx.process.memory().read_vals::<_, 1>(optlen_ptr)?;
+
+        let optlen = socket.borrow_mut().getsockopt(
+            level,
+            optname,
+            optval_ptr,
+            optlen,
+            ctx.process.memory_mut(),
+        )?;
+
+        ctx.process
+            .memory_mut()
+            .copy_to_ptr(optlen_ptr, &[optlen])?;
+
+        Ok(0.into())
     }
 
     #[log_syscall(/* rv */ libc::c_int, /* sockfd */ libc::c_int, /* level */ libc::c_int,
@@ -896,13 +1582,25 @@ impl SyscallHandler {
             _ => return Err(Errno::ENOTSOCK.into()),
         };
 
-        // TODO: support rust sockets
-        log::warn!(
-            "setsockopt() syscall not yet supported for fd {} of type {:?}; Returning ENOSYS",
-            fd,
-            socket,
-        );
-        Err(Errno::ENOSYS.into())
+        let level: libc::c_int = args.get(1).into();
+        let optname: libc::c_int = args.get(2).into();
+        let optval_ptr: PluginPtr = args.get(3).into();
+        let optlen: libc::socklen_t = args.get(4).into();
+
+        let socket = socket.clone();
+
+        EventQueue::queue_and_run(|event_queue| {
+            socket.borrow_mut().setsockopt(
+                level,
+                optname,
+                optval_ptr,
+                optlen,
+                ctx.process.memory(),
+                event_queue,
+            )
+        })?;
+
+        Ok(0.into())
     }
 }
 
@@ -1031,6 +1729,446 @@ fn read_sockaddr(
     )?))
 }
 
+/// Read the address family of a socket address from the plugin. Returns `None` if the address is
+/// null or too short to contain the family.
+fn read_sockaddr_family(
+    mem: &MemoryManager,
+    addr_ptr: PluginPtr,
+    addr_len: libc::socklen_t,
+) -> Result<Option<libc::sa_family_t>, SyscallError> {
+    if addr_ptr.is_null()
+        || usize::try_from(addr_len).unwrap() < std::mem::size_of::<libc::sa_family_t>()
+    {
+        return Ok(None);
+    }
+
+    let [family] = mem
+        .read_vals::<libc::sa_family_t, 1>(TypedPluginPtr::new::<libc::sa_family_t>(addr_ptr, 1))?;
+
+    Ok(Some(family))
+}
+
+/// Read a `msghdr` and its io vectors from the plugin.
+fn read_msghdr(
+    mem: &MemoryManager,
+    msg_ptr: PluginPtr,
+) -> Result<(libc::msghdr, Vec<IoVec>), SyscallError> {
+    let [msg] = mem.read_vals::<_, 1>(TypedPluginPtr::new::<libc::msghdr>(msg_ptr, 1))?;
+
+    // linux returns EMSGSIZE rather than EINVAL for a `msghdr` with too many io vectors
+    if msg.msg_iovlen > IOV_MAX {
+        return Err(Errno::EMSGSIZE.into());
+    }
+
+    let iovs = read_iovecs(mem, PluginPtr::from(msg.msg_iov as usize), msg.msg_iovlen)?;
+
+    Ok((msg, iovs))
+}
+
+/// Get a pointer to the `msg_namelen` field of the plugin's `msghdr`.
+fn msg_namelen_ptr(msg_ptr: PluginPtr) -> TypedPluginPtr<libc::socklen_t> {
+    let msg = pod::zeroed::<libc::msghdr>();
+    let offset = std::ptr::addr_of!(msg.msg_namelen) as usize - std::ptr::addr_of!(msg) as usize;
+    TypedPluginPtr::new::<libc::socklen_t>(PluginPtr::from(usize::from(msg_ptr) + offset), 1)
+}
+
+/// Set the `msg_len` field of the plugin's `mmsghdr` to the value returned by
+/// `sendmsg()`/`recvmsg()`.
+fn set_mmsghdr_len(
+    mem: &mut MemoryManager,
+    mmsg_ptr: TypedPluginPtr<libc::mmsghdr>,
+    len: SysCallReg,
+) -> Result<(), SyscallError> {
+    let mut mmsg = mem.memory_ref_mut(mmsg_ptr)?;
+    mmsg.get_mut(0).unwrap().msg_len = u32::from(len);
+    mmsg.flush()?;
+    Ok(())
+}
+
+/// The maximum size of a `sendmsg()` control buffer (linux's default 'optmem_max').
+const MAX_CONTROL_LEN: usize = 20480;
+
+/// The maximum number of file descriptors that can be sent in a single `SCM_RIGHTS` message.
+const SCM_MAX_FD: usize = 253;
+
+/// The credentials of the process, as passed to the receiver in an `SCM_CREDENTIALS` message.
+fn process_creds(ctx: &ThreadContext) -> libc::ucred {
+    libc::ucred {
+        pid: u32::from(ctx.process.id()) as libc::pid_t,
+        uid: nix::unistd::getuid().as_raw(),
+        gid: nix::unistd::getgid().as_raw(),
+    }
+}
+
+/// Returns true if a process with credentials `actual` is allowed to send the credentials
+/// `requested`. Unlike linux we don't consider privileged processes.
+fn creds_are_permitted(requested: &libc::ucred, actual: &libc::ucred) -> bool {
+    let uid_ok = requested.uid == actual.uid || requested.uid == nix::unistd::geteuid().as_raw();
+    let gid_ok = requested.gid == actual.gid || requested.gid == nix::unistd::getegid().as_raw();
+    requested.pid == actual.pid && uid_ok && gid_ok
+}
+
+/// Equivalent to linux's `CMSG_ALIGN()`.
+fn cmsg_align(len: usize) -> usize {
+    let align = std::mem::size_of::<usize>();
+    (len + align - 1) & !(align - 1)
+}
+
+/// Equivalent to linux's `CMSG_LEN()`.
+fn cmsg_len(data_len: usize) -> usize {
+    cmsg_align(std::mem::size_of::<libc::cmsghdr>()) + data_len
+}
+
+/// Equivalent to linux's `CMSG_SPACE()`.
+fn cmsg_space(data_len: usize) -> usize {
+    cmsg_align(std::mem::size_of::<libc::cmsghdr>()) + cmsg_align(data_len)
+}
+
+/// Parse the control messages from a `sendmsg()` control buffer. Returns the file descriptors
+/// from any `SCM_RIGHTS` messages and the credentials from any `SCM_CREDENTIALS` message.
+fn parse_control_msgs(control: &[u8]) -> Result<(Vec<libc::c_int>, Option<libc::ucred>), Errno> {
+    let hdr_len = std::mem::size_of::<libc::cmsghdr>();
+
+    let mut fds = Vec::new();
+    let mut creds = None;
+    let mut offset = 0;
+
+    while control.len() - offset >= hdr_len {
+        let mut hdr: libc::cmsghdr = pod::zeroed();
+        pod::to_u8_slice_mut(std::slice::from_mut(&mut hdr))
+            .copy_from_slice(&control[offset..][..hdr_len]);
+
+        let len = hdr.cmsg_len as usize;
+        if len < cmsg_len(0) || len > control.len() - offset {
+            return Err(Errno::EINVAL);
+        }
+
+        let data = &control[offset + cmsg_len(0)..offset + len];
+
+        match (hdr.cmsg_level, hdr.cmsg_type) {
+            (libc::SOL_SOCKET, libc::SCM_RIGHTS) => {
+                let fd_size = std::mem::size_of::<libc::c_int>();
+                fds.extend(
+                    data.chunks_exact(fd_size)
+                        .map(|x| libc::c_int::from_ne_bytes(x.try_into().unwrap())),
+                );
+                if fds.len() > SCM_MAX_FD {
+                    return Err(Errno::EINVAL);
+                }
+            }
+            (libc::SOL_SOCKET, libc::SCM_CREDENTIALS) => {
+                if data.len() != std::mem::size_of::<libc::ucred>() {
+                    return Err(Errno::EINVAL);
+                }
+                let mut value: libc::ucred = pod::zeroed();
+                pod::to_u8_slice_mut(std::slice::from_mut(&mut value)).copy_from_slice(data);
+                creds = Some(value);
+            }
+            (libc::SOL_SOCKET, _) => return Err(Errno::EINVAL),
+            (level, type_) => {
+                warn!(
+                    "Ignoring control message with level {} and type {}",
+                    level, type_
+                );
+            }
+        }
+
+        // the next header begins at the next aligned offset
+        offset = std::cmp::min(offset + cmsg_align(len), control.len());
+    }
+
+    Ok((fds, creds))
+}
+
+/// The number of file descriptors that will fit in a `recvmsg()` control buffer of length
+/// `control_len`, after any credentials have been added.
+fn control_fd_capacity(has_creds: bool, control_len: usize) -> usize {
+    let mut remaining = control_len;
+
+    if has_creds && remaining >= cmsg_len(std::mem::size_of::<libc::ucred>()) {
+        remaining -= std::cmp::min(remaining, cmsg_space(std::mem::size_of::<libc::ucred>()));
+    }
+
+    remaining.saturating_sub(cmsg_len(0)) / std::mem::size_of::<libc::c_int>()
+}
+
+/// Build the control messages for a `recvmsg()` control buffer of length `control_len`.
+/// Returns the control buffer and whether any of the control data did not fit in the buffer
+/// (`MSG_CTRUNC`).
+fn build_control_msgs(
+    creds: Option<libc::ucred>,
+    fds: &[libc::c_int],
+    control_len: usize,
+) -> (Vec<u8>, bool) {
+    let mut control = Vec::new();
+    let mut truncated = false;
+
+    if let Some(creds) = creds {
+        if control_len >= cmsg_len(std::mem::size_of::<libc::ucred>()) {
+            push_control_msg(
+                &mut control,
+                libc::SCM_CREDENTIALS,
+                pod::to_u8_slice(std::slice::from_ref(&creds)),
+                control_len,
+            );
+        } else {
+            truncated = true;
+        }
+    }
+
+    if !fds.is_empty() {
+        let num_fds = std::cmp::min(control_fd_capacity(creds.is_some(), control_len), fds.len());
+        if num_fds < fds.len() {
+            truncated = true;
+        }
+
+        if num_fds > 0 {
+            let data: Vec<u8> = fds[..num_fds]
+                .iter()
+                .flat_map(|fd| fd.to_ne_bytes())
+                .collect();
+            push_control_msg(&mut control, libc::SCM_RIGHTS, &data, control_len);
+        }
+    }
+
+    (control, truncated)
+}
+
+/// Append a `SOL_SOCKET` control message to the control buffer. The padding after the message is
+/// limited so that the buffer never exceeds `control_len` bytes.
+fn push_control_msg(control: &mut Vec<u8>, type_: libc::c_int, data: &[u8], control_len: usize) {
+    let mut hdr: libc::cmsghdr = pod::zeroed();
+    hdr.cmsg_len = cmsg_len(data.len()) as _;
+    hdr.cmsg_level = libc::SOL_SOCKET;
+    hdr.cmsg_type = type_;
+
+    let start = control.len();
+    control.extend_from_slice(pod::to_u8_slice(std::slice::from_ref(&hdr)));
+    control.resize(start + cmsg_len(0), 0);
+    control.extend_from_slice(data);
+
+    let end = std::cmp::min(start + cmsg_space(data.len()), control_len);
+    if end > control.len() {
+        control.resize(end, 0);
+    }
+}
+
+/// Call the C syscall handler's `sendto()` for a legacy socket.
+fn legacy_sendto(
+    ctx: &mut ThreadContext,
+    fd: libc::c_int,
+    buf: IoVec,
+    flags: libc::c_int,
+    addr_ptr: PluginPtr,
+    addr_len: libc::socklen_t,
+) -> SyscallResult {
+    let args = SysCallArgs {
+        number: libc::SYS_sendto,
+        args: [
+            fd.into(),
+            buf.base.into(),
+            buf.len.into(),
+            flags.into(),
+            addr_ptr.into(),
+            addr_len.into(),
+        ],
+    };
+    unsafe { c::syscallhandler_sendto(ctx.thread.csyscallhandler(), &args as *const SysCallArgs) }
+        .into()
+}
+
+/// Returns true if a receive with the given flags should keep receiving until the buffer is full.
+/// Linux ignores `MSG_WAITALL` for peeks and non-blocking receives.
+fn waitall_applies(flags: MsgFlags, nonblocking: bool) -> bool {
+    flags.contains(MsgFlags::MSG_WAITALL)
+        && !flags.intersects(MsgFlags::MSG_PEEK | MsgFlags::MSG_DONTWAIT)
+        && !nonblocking
+}
+
+/// Returns true if a receive on the legacy socket should keep receiving until the buffer is full.
+/// Only tcp sockets wait for the buffer to be full.
+fn legacy_waitall_applies(file: &LegacyFileCounter, flags: libc::c_int) -> bool {
+    let file_type = unsafe { c::legacyfile_getType(file.ptr()) };
+    let file_flags = unsafe { c::legacyfile_getFlags(file.ptr()) };
+
+    file_type == c::_LegacyFileType_DT_TCPSOCKET
+        && waitall_applies(
+            MsgFlags::from_bits_truncate(flags),
+            file_flags & libc::O_NONBLOCK != 0,
+        )
+}
+
+/// Call the C syscall handler's `recvfrom()` for a legacy socket.
+fn legacy_recvfrom(
+    ctx: &mut ThreadContext,
+    fd: libc::c_int,
+    buf: IoVec,
+    flags: libc::c_int,
+    addr_ptr: PluginPtr,
+    addr_len_ptr: PluginPtr,
+) -> SyscallResult {
+    let args = SysCallArgs {
+        number: libc::SYS_recvfrom,
+        args: [
+            fd.into(),
+            buf.base.into(),
+            buf.len.into(),
+            flags.into(),
+            addr_ptr.into(),
+            addr_len_ptr.into(),
+        ],
+    };
+    unsafe { c::syscallhandler_recvfrom(ctx.thread.csyscallhandler(), &args as *const SysCallArgs) }
+        .into()
+}
+
+/// Send the io vectors on a legacy TCP or UDP socket. The C syscall handler only supports a
+/// single buffer, so multiple io vectors are gathered into a temporary buffer.
+fn legacy_sendmsg(
+    ctx: &mut ThreadContext,
+    fd: libc::c_int,
+    file_type: c::LegacyFileType,
+    iovs: &[IoVec],
+    flags: libc::c_int,
+    addr_ptr: PluginPtr,
+    addr_len: libc::socklen_t,
+) -> SyscallResult {
+    let non_empty: Vec<IoVec> = iovs.iter().filter(|x| x.len > 0).copied().collect();
+
+    match non_empty.as_slice() {
+        // the C syscall handler requires a non-NULL buffer, even for an empty message
+        [] => {
+            let buf = iovs.first().copied().unwrap_or(IoVec {
+                base: PluginPtr::null(),
+                len: 0,
+            });
+            legacy_sendto(ctx, fd, buf, flags, addr_ptr, addr_len)
+        }
+        [buf] => legacy_sendto(ctx, fd, *buf, flags, addr_ptr, addr_len),
+        // gather the io vectors into a temporary buffer so that they're sent with a single call,
+        // which keeps udp datagrams and tcp writes atomic
+        _ => {
+            let len = iovecs_len(&non_empty);
+            // a partial write is fine for a tcp socket, but a datagram can't be split
+            let len = if file_type == c::_LegacyFileType_DT_UDPSOCKET {
+                len
+            } else {
+                std::cmp::min(len, c::SYSCALL_IO_BUFSIZE as usize)
+            };
+
+            let mut bytes = vec![0u8; len];
+            IoVecReader::new(&non_empty, ctx.process.memory()).read_exact(&mut bytes)?;
+
+            let buf = AllocdMem::<u8>::new(ctx, len);
+            let iov = IoVec {
+                base: buf.ptr().ptr(),
+                len,
+            };
+
+            let rv = match ctx.process.memory_mut().copy_to_ptr(buf.ptr(), &bytes) {
+                Ok(()) => legacy_sendto(ctx, fd, iov, flags, addr_ptr, addr_len),
+                Err(e) => Err(e.into()),
+            };
+
+            buf.free(ctx);
+            rv
+        }
+    }
+}
+
+/// Receive into the io vectors from a legacy TCP or UDP socket. Returns the number of bytes
+/// copied and the length of the received message. The source address (UDP only) is written by
+/// the C syscall handler to the `msg_name` and `msg_namelen` fields of the plugin's `msghdr`.
+fn legacy_recvmsg(
+    ctx: &mut ThreadContext,
+    fd: libc::c_int,
+    file_type: c::LegacyFileType,
+    iovs: &[IoVec],
+    flags: libc::c_int,
+    addr_ptr: PluginPtr,
+    msg_ptr: PluginPtr,
+) -> Result<(SysCallReg, usize), SyscallError> {
+    let non_empty: Vec<IoVec> = iovs.iter().filter(|x| x.len > 0).copied().collect();
+    let addr_len_ptr = msg_namelen_ptr(msg_ptr).ptr();
+
+    if file_type == c::_LegacyFileType_DT_UDPSOCKET {
+        let max_size = c::CONFIG_DATAGRAM_MAX_SIZE as usize;
+
+        // receive directly into the plugin's buffer if it can hold any datagram
+        if let [buf] = non_empty.as_slice() {
+            if buf.len >= max_size {
+                let rv = legacy_recvfrom(ctx, fd, *buf, flags, addr_ptr, addr_len_ptr)?;
+                return Ok((rv, rv.into()));
+            }
+        }
+
+        // otherwise receive the full datagram into a temporary buffer so that we know its length
+        let buf = AllocdMem::<u8>::new(ctx, max_size);
+        let iov = IoVec {
+            base: buf.ptr().ptr(),
+            len: max_size,
+        };
+
+        let rv = legacy_recvfrom(ctx, fd, iov, flags, addr_ptr, addr_len_ptr).and_then(|rv| {
+            let msg_len: usize = rv.into();
+            let mut bytes = vec![0u8; msg_len];
+            let mem = ctx.process.memory_mut();
+            mem.copy_from_ptr(&mut bytes, buf.ptr().slice(..msg_len))?;
+            let num_copied = std::cmp::min(msg_len, iovecs_len(&non_empty));
+            IoVecWriter::new(&non_empty, mem).write_all(&bytes[..num_copied])?;
+
+            // the `MSG_TRUNC` flag returns the full length of the datagram
+            let rv = if flags & libc::MSG_TRUNC != 0 {
+                msg_len
+            } else {
+                num_copied
+            };
+            Ok((rv.into(), msg_len))
+        });
+
+        buf.free(ctx);
+        return rv;
+    }
+
+    match non_empty.as_slice() {
+        [] => {
+            let buf = iovs.first().copied().unwrap_or(IoVec {
+                base: PluginPtr::null(),
+                len: 0,
+            });
+            let rv = legacy_recvfrom(ctx, fd, buf, flags, addr_ptr, addr_len_ptr)?;
+            Ok((rv, rv.into()))
+        }
+        [buf] => {
+            let rv = legacy_recvfrom(ctx, fd, *buf, flags, addr_ptr, addr_len_ptr)?;
+            Ok((rv, rv.into()))
+        }
+        // receive into a temporary buffer with a single call and scatter the data into the io
+        // vectors, since receiving one io vector at a time would peek the same data repeatedly and
+        // could return data interleaved with other readers
+        _ => {
+            let len = std::cmp::min(iovecs_len(&non_empty), c::SYSCALL_IO_BUFSIZE as usize);
+            let buf = AllocdMem::<u8>::new(ctx, len);
+            let iov = IoVec {
+                base: buf.ptr().ptr(),
+                len,
+            };
+
+            let rv = legacy_recvfrom(ctx, fd, iov, flags, addr_ptr, addr_len_ptr).and_then(|rv| {
+                let num_recv: usize = rv.into();
+                let mut bytes = vec![0u8; num_recv];
+                let mem = ctx.process.memory_mut();
+                mem.copy_from_ptr(&mut bytes, buf.ptr().slice(..num_recv))?;
+                IoVecWriter::new(&non_empty, mem).write_all(&bytes)?;
+                Ok((rv, num_recv))
+            });
+
+            buf.free(ctx);
+            rv
+        }
+    }
+}
+
 /// Workaround for a nix bug. It's unclear if the bug is that the documentation for
 /// `sockaddr_storage_to_addr()` is incorrect, or if the implemention of
 /// `sockaddr_storage_to_addr()` is incorrect.
@@ -1245,4 +2383,72 @@ mod tests {
         #[allow(deprecated)]
         nix::sys::socket::sockaddr_storage_to_addr(&addr, corrected_addr_len).unwrap();
     }
+
+    /// Build a control message and make sure we can parse it back.
+    #[test]
+    fn test_control_msgs_round_trip() {
+        let creds = libc::ucred {
+            pid: 1000,
+            uid: 10,
+            gid: 20,
+        };
+        let fds = [3, 4, 5];
+
+        let (control, truncated) = build_control_msgs(Some(creds), &fds, 1024);
+        assert!(!truncated);
+        assert_eq!(
+            control.len(),
+            cmsg_space(std::mem::size_of::<libc::ucred>()) + cmsg_len(3 * 4)
+        );
+
+        let (parsed_fds, parsed_creds) = parse_control_msgs(&control).unwrap();
+        assert_eq!(parsed_fds, fds);
+        let parsed_creds = parsed_creds.unwrap();
+        assert_eq!(
+            (parsed_creds.pid, parsed_creds.uid, parsed_creds.gid),
+            (creds.pid, creds.uid, creds.gid)
+        );
+    }
+
+    /// Make sure that file descriptors that don't fit in the control buffer are truncated.
+    #[test]
+    fn test_control_msgs_truncated() {
+        let fds = [3, 4, 5];
+        let control_len = cmsg_len(2 * 4);
+
+        assert_eq!(control_fd_capacity(false, control_len), 2);
+
+        let (control, truncated) = build_control_msgs(None, &fds, control_len);
+        assert!(truncated);
+        assert_eq!(control.len(), control_len);
+
+        let (parsed_fds, parsed_creds) = parse_control_msgs(&control).unwrap();
+        assert_eq!(parsed_fds, [3, 4]);
+        assert!(parsed_creds.is_none());
+
+        // not enough room for any control messages
+        let (control, truncated) = build_control_msgs(None, &fds, cmsg_len(0));
+        assert!(truncated);
+        assert!(control.is_empty());
+    }
+
+    /// Make sure that invalid control messages are rejected.
+    #[test]
+    fn test_control_msgs_invalid() {
+        let (mut control, _) = build_control_msgs(None, &[3], 1024);
+
+        // a length larger than the buffer
+        let mut hdr: libc::cmsghdr = pod::zeroed();
+        pod::to_u8_slice_mut(std::slice::from_mut(&mut hdr))
+            .copy_from_slice(&control[..std::mem::size_of::<libc::cmsghdr>()]);
+        hdr.cmsg_len = (control.len() + 1) as _;
+        control[..std::mem::size_of::<libc::cmsghdr>()]
+            .copy_from_slice(pod::to_u8_slice(std::slice::from_ref(&hdr)));
+        assert!(matches!(parse_control_msgs(&control), Err(Errno::EINVAL)));
+
+        // too many file descriptors
+        let fds: Vec<libc::c_int> = (0..(SCM_MAX_FD as libc::c_int + 1)).collect();
+        let (control, _) = build_control_msgs(None, &fds, 4096);
+        assert!(matches!(parse_control_msgs(&control), Err(Errno::EINVAL)));
+    }
 }
@@ -0,0 +1,561 @@
+use crate::cshadow as c;
+use crate::host::context::ThreadContext;
+use crate::host::descriptor::pipe::Pipe;
+use crate::host::descriptor::socket::{AncillaryData, Socket};
+use crate::host::descriptor::{
+    CompatFile, CountedLegacyFileRef, Descriptor, File, FileState, FileStatus,
+};
+use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall::Trigger;
+use crate::host::syscall_condition::SysCallCondition;
+use crate::host::syscall_types::{Blocked, SysCallArgs, TypedPluginPtr};
+use crate::host::syscall_types::{SyscallError, SyscallResult};
+use crate::utility::event_queue::EventQueue;
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use log::*;
+use nix::errno::Errno;
+use nix::sys::socket::MsgFlags;
+
+use syscall_logger::log_syscall;
+
+/// The maximum number of bytes that are copied through shadow's memory at a time.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+impl SyscallHandler {
+    #[log_syscall(/* rv */ libc::ssize_t, /* out_fd */ libc::c_int, /* in_fd */ libc::c_int,
+                  /* offset */ *const libc::off_t, /* count */ libc::size_t)]
+    pub fn sendfile(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let out_fd = libc::c_int::from(args.get(0));
+        let in_fd = libc::c_int::from(args.get(1));
+        let offset_ptr = TypedPluginPtr::new::<libc::off_t>(args.get(2).into(), 1);
+        let count = libc::size_t::from(args.get(3));
+
+        let src = Endpoint::from_descriptor(Self::get_descriptor(ctx.process, in_fd)?)?;
+        let dst = Endpoint::from_descriptor(Self::get_descriptor(ctx.process, out_fd)?)?;
+
+        // sendfile(2): "in_fd must correspond to a file which supports mmap(2)-like operations
+        // (i.e., it cannot be a socket)"; linux also supports pipes
+        if !src.is_regular_file() && !src.is_pipe() {
+            debug!("Unsupported sendfile input file");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut offset = if offset_ptr.is_null() {
+            None
+        } else {
+            Some(ctx.process.memory().read_vals::<_, 1>(offset_ptr)?[0])
+        };
+
+        if offset.is_some() && !src.is_regular_file() {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        let result = Self::copy_helper(ctx, &src, &mut offset, &dst, &mut None, count, true, false);
+
+        // sendfile(2): "When sendfile() returns, this variable will be set to the offset of the
+        // byte following the last byte that was read"
+        if let (Ok(_), Some(offset)) = (&result, offset) {
+            ctx.process
+                .memory_mut()
+                .copy_to_ptr(offset_ptr, &[offset])?;
+        }
+
+        result
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd_in */ libc::c_int, /* off_in */ *const libc::loff_t,
+                  /* fd_out */ libc::c_int, /* off_out */ *const libc::loff_t,
+                  /* len */ libc::size_t, /* flags */ libc::c_uint)]
+    pub fn splice(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd_in = libc::c_int::from(args.get(0));
+        let off_in_ptr = TypedPluginPtr::new::<libc::loff_t>(args.get(1).into(), 1);
+        let fd_out = libc::c_int::from(args.get(2));
+        let off_out_ptr = TypedPluginPtr::new::<libc::loff_t>(args.get(3).into(), 1);
+        let len = libc::size_t::from(args.get(4));
+        let flags = u32::from(args.get(5));
+
+        let nonblock = Self::splice_nonblock(flags)?;
+
+        let src = Endpoint::from_descriptor(Self::get_descriptor(ctx.process, fd_in)?)?;
+        let dst = Endpoint::from_descriptor(Self::get_descriptor(ctx.process, fd_out)?)?;
+
+        // splice(2): "Neither of the file descriptors refers to a pipe."
+        if !src.is_pipe() && !dst.is_pipe() {
+            debug!("Neither splice file is a pipe");
+            return Err(Errno::EINVAL.into());
+        }
+
+        // splice(2): "fd_in and fd_out refer to the same pipe"
+        if src.is_same_pipe(&dst) {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut off_in = Self::read_splice_offset(ctx, &src, off_in_ptr)?;
+        let mut off_out = Self::read_splice_offset(ctx, &dst, off_out_ptr)?;
+
+        let result = Self::copy_helper(
+            ctx,
+            &src,
+            &mut off_in,
+            &dst,
+            &mut off_out,
+            len,
+            true,
+            nonblock,
+        );
+
+        if result.is_ok() {
+            if let Some(off_in) = off_in {
+                ctx.process
+                    .memory_mut()
+                    .copy_to_ptr(off_in_ptr, &[off_in])?;
+            }
+            if let Some(off_out) = off_out {
+                ctx.process
+                    .memory_mut()
+                    .copy_to_ptr(off_out_ptr, &[off_out])?;
+            }
+        }
+
+        result
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd_in */ libc::c_int, /* fd_out */ libc::c_int,
+                  /* len */ libc::size_t, /* flags */ libc::c_uint)]
+    pub fn tee(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd_in = libc::c_int::from(args.get(0));
+        let fd_out = libc::c_int::from(args.get(1));
+        let len = libc::size_t::from(args.get(2));
+        let flags = u32::from(args.get(3));
+
+        let nonblock = Self::splice_nonblock(flags)?;
+
+        let src = Endpoint::from_descriptor(Self::get_descriptor(ctx.process, fd_in)?)?;
+        let dst = Endpoint::from_descriptor(Self::get_descriptor(ctx.process, fd_out)?)?;
+
+        // tee(2): "fd_in or fd_out does not refer to a pipe; or fd_in and fd_out refer to the
+        // same pipe"
+        if !src.is_pipe() || !dst.is_pipe() || src.is_same_pipe(&dst) {
+            return Err(Errno::EINVAL.into());
+        }
+
+        // the data is copied but not consumed from the input pipe
+        Self::copy_helper(ctx, &src, &mut None, &dst, &mut None, len, false, nonblock)
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd_in */ libc::c_int, /* off_in */ *const libc::loff_t,
+                  /* fd_out */ libc::c_int, /* off_out */ *const libc::loff_t,
+                  /* len */ libc::size_t, /* flags */ libc::c_uint)]
+    pub fn copy_file_range(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd_in = libc::c_int::from(args.get(0));
+        let off_in_ptr = TypedPluginPtr::new::<libc::loff_t>(args.get(1).into(), 1);
+        let fd_out = libc::c_int::from(args.get(2));
+        let off_out_ptr = TypedPluginPtr::new::<libc::loff_t>(args.get(3).into(), 1);
+        let len = libc::size_t::from(args.get(4));
+        let flags = u32::from(args.get(5));
+
+        // copy_file_range(2): "The flags argument is provided to allow for future extensions and
+        // currently must be set to 0."
+        if flags != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let src = Endpoint::from_descriptor(Self::get_descriptor(ctx.process, fd_in)?)?;
+        let dst = Endpoint::from_descriptor(Self::get_descriptor(ctx.process, fd_out)?)?;
+
+        // copy_file_range(2): "Either fd_in or fd_out is not a regular file."
+        if !src.is_regular_file() || !dst.is_regular_file() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut off_in = Self::read_splice_offset(ctx, &src, off_in_ptr)?;
+        let mut off_out = Self::read_splice_offset(ctx, &dst, off_out_ptr)?;
+
+        let result =
+            Self::copy_helper(ctx, &src, &mut off_in, &dst, &mut off_out, len, true, false);
+
+        if result.is_ok() {
+            if let Some(off_in) = off_in {
+                ctx.process
+                    .memory_mut()
+                    .copy_to_ptr(off_in_ptr, &[off_in])?;
+            }
+            if let Some(off_out) = off_out {
+                ctx.process
+                    .memory_mut()
+                    .copy_to_ptr(off_out_ptr, &[off_out])?;
+            }
+        }
+
+        result
+    }
+
+    /// Validate the flags of `splice()` or `tee()`, and return whether `SPLICE_F_NONBLOCK` is set.
+    fn splice_nonblock(flags: libc::c_uint) -> Result<bool, Errno> {
+        let supported_flags = libc::SPLICE_F_MOVE
+            | libc::SPLICE_F_NONBLOCK
+            | libc::SPLICE_F_MORE
+            | libc::SPLICE_F_GIFT;
+
+        if flags & !supported_flags != 0 {
+            debug!("Invalid splice flags: {}", flags);
+            return Err(Errno::EINVAL);
+        }
+
+        Ok(flags & libc::SPLICE_F_NONBLOCK != 0)
+    }
+
+    /// Read the offset for one end of a `splice()` or `copy_file_range()`. Only regular files
+    /// support offsets.
+    fn read_splice_offset(
+        ctx: &mut ThreadContext,
+        endpoint: &Endpoint,
+        offset_ptr: TypedPluginPtr<libc::loff_t>,
+    ) -> Result<Option<libc::off_t>, SyscallError> {
+        if offset_ptr.is_null() {
+            return Ok(None);
+        }
+
+        if !endpoint.is_regular_file() {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        let offset = ctx.process.memory().read_vals::<_, 1>(offset_ptr)?[0];
+        if offset < 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        Ok(Some(offset))
+    }
+
+    /// Copy up to `len` bytes from `src` to `dst` through a buffer in shadow's memory. If an
+    /// offset is given, the regular file is accessed at that offset and the offset is advanced by
+    /// the number of bytes copied. Otherwise the file position is used. If `consume` is false, the
+    /// data is not removed from a `src` pipe.
+    ///
+    /// Returns the number of bytes copied. The syscall only blocks if no bytes could be copied.
+    #[allow(clippy::too_many_arguments)]
+    fn copy_helper(
+        ctx: &mut ThreadContext,
+        src: &Endpoint,
+        src_offset: &mut Option<libc::off_t>,
+        dst: &Endpoint,
+        dst_offset: &mut Option<libc::off_t>,
+        len: libc::size_t,
+        consume: bool,
+        nonblock: bool,
+    ) -> SyscallResult {
+        let len = usize::try_from(len).unwrap();
+
+        // read a regular file at an explicit offset, and then update the file position using the
+        // number of bytes that were written, so that bytes that couldn't be written aren't skipped
+        let update_position = src_offset.is_none() && src.is_regular_file();
+        if update_position {
+            *src_offset = Some(src.seek(0, libc::SEEK_CUR)?);
+        }
+
+        let mut buf = vec![0u8; std::cmp::min(len, CHUNK_SIZE)];
+        let mut total = 0;
+
+        let result = loop {
+            if total == len {
+                break Ok(total);
+            }
+
+            // don't read more than the destination pipe can hold, since data that has been read
+            // from a socket can't be returned
+            let mut chunk_len = std::cmp::min(len - total, CHUNK_SIZE);
+            if let Some(space) = dst.space_available() {
+                chunk_len = std::cmp::min(chunk_len, space);
+            }
+
+            if chunk_len == 0 {
+                if total > 0 {
+                    break Ok(total);
+                }
+                break Err(Errno::EWOULDBLOCK.into());
+            }
+
+            let chunk = &mut buf[..chunk_len];
+
+            // peek at the pipe data so that it remains in the pipe if it can't be written
+            let peek = !consume || src.is_pipe();
+
+            let num_read = match src.read(ctx, chunk, *src_offset, peek) {
+                // end of file
+                Ok(0) => break Ok(total),
+                Ok(x) => x,
+                Err(_) if total > 0 => break Ok(total),
+                Err(e) => {
+                    if e == Errno::EWOULDBLOCK.into() {
+                        return Self::copy_would_block(src, FileState::READABLE, nonblock);
+                    }
+                    break Err(e);
+                }
+            };
+
+            let num_written = match dst.write(ctx, &chunk[..num_read], *dst_offset) {
+                Ok(x) => x,
+                Err(_) if total > 0 => break Ok(total),
+                Err(e) => {
+                    // writing to a pipe or stream socket with no readers raises SIGPIPE
+                    if e == Errno::EPIPE.into() {
+                        Self::raise_sigpipe(ctx);
+                    }
+                    break Err(e);
+                }
+            };
+
+            // remove the data that was written from the pipe
+            if consume && src.is_pipe() && num_written > 0 {
+                src.read(ctx, &mut chunk[..num_written], None, false)?;
+            }
+
+            let num_written_off = libc::off_t::try_from(num_written).unwrap();
+            if let Some(offset) = src_offset {
+                *offset += num_written_off;
+            }
+            if let Some(offset) = dst_offset {
+                *offset += num_written_off;
+            }
+
+            total += num_written;
+
+            if num_written < num_read {
+                break Ok(total);
+            }
+        };
+
+        if update_position {
+            src.seek(src_offset.take().unwrap(), libc::SEEK_SET)?;
+        }
+
+        match result {
+            Ok(total) => Ok(total.into()),
+            Err(e) if e == Errno::EWOULDBLOCK.into() => {
+                Self::copy_would_block(dst, FileState::WRITABLE, nonblock)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns EWOULDBLOCK if the endpoint is non-blocking, otherwise blocks until the endpoint
+    /// reaches the given state.
+    fn copy_would_block(endpoint: &Endpoint, state: FileState, nonblock: bool) -> SyscallResult {
+        // `SPLICE_F_NONBLOCK` only applies to pipes
+        if (nonblock && endpoint.is_pipe()) || endpoint.is_nonblocking() {
+            return Err(Errno::EWOULDBLOCK.into());
+        }
+
+        Err(SyscallError::Blocked(Blocked {
+            condition: SysCallCondition::new(endpoint.trigger(state)),
+            restartable: endpoint.supports_sa_restart(),
+        }))
+    }
+}
+
+/// One end of a `sendfile()`, `splice()`, `tee()`, or `copy_file_range()`.
+enum Endpoint {
+    Pipe(Arc<AtomicRefCell<Pipe>>),
+    Socket(Socket),
+    /// A legacy regular file or TCP socket.
+    Legacy(CountedLegacyFileRef),
+}
+
+impl Endpoint {
+    fn from_descriptor(desc: &Descriptor) -> Result<Self, Errno> {
+        match desc.file() {
+            CompatFile::New(file) => match file.inner_file() {
+                File::Pipe(pipe) => Ok(Self::Pipe(Arc::clone(pipe))),
+                File::Socket(socket) => Ok(Self::Socket(socket.clone())),
+                _ => Err(Errno::EINVAL),
+            },
+            CompatFile::Legacy(file) => {
+                let file_type = unsafe { c::legacyfile_getType(file.ptr()) };
+                match file_type {
+                    c::_LegacyFileType_DT_FILE | c::_LegacyFileType_DT_TCPSOCKET => {
+                        Ok(Self::Legacy(file.file().clone()))
+                    }
+                    _ => Err(Errno::EINVAL),
+                }
+            }
+        }
+    }
+
+    fn is_pipe(&self) -> bool {
+        matches!(self, Self::Pipe(_))
+    }
+
+    fn is_regular_file(&self) -> bool {
+        match self {
+            Self::Legacy(file) => {
+                let file_type = unsafe { c::legacyfile_getType(file.ptr()) };
+                file_type == c::_LegacyFileType_DT_FILE
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if both endpoints are pipes that use the same buffer.
+    fn is_same_pipe(&self, other: &Endpoint) -> bool {
+        match (self, other) {
+            (Self::Pipe(a), Self::Pipe(b)) => a.borrow().shares_buffer_with(&b.borrow()),
+            _ => false,
+        }
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        match self {
+            Self::Pipe(pipe) => pipe.borrow().get_status().contains(FileStatus::NONBLOCK),
+            Self::Socket(socket) => socket.borrow().get_status().contains(FileStatus::NONBLOCK),
+            Self::Legacy(file) => {
+                let flags = unsafe { c::legacyfile_getFlags(file.ptr()) };
+                flags & libc::O_NONBLOCK != 0
+            }
+        }
+    }
+
+    fn supports_sa_restart(&self) -> bool {
+        match self {
+            Self::Pipe(pipe) => pipe.borrow().supports_sa_restart(),
+            Self::Socket(socket) => socket.borrow().supports_sa_restart(),
+            Self::Legacy(file) => unsafe { c::legacyfile_supportsSaRestart(file.ptr()) },
+        }
+    }
+
+    fn trigger(&self, state: FileState) -> Trigger {
+        match self {
+            Self::Pipe(pipe) => Trigger::from_file(File::Pipe(Arc::clone(pipe)), state),
+            Self::Socket(socket) => Trigger::from_file(File::Socket(socket.clone()), state),
+            Self::Legacy(file) => Trigger::from_legacy_file(unsafe { file.ptr() }, state),
+        }
+    }
+
+    /// The number of bytes that can be written without blocking, or `None` if unknown.
+    fn space_available(&self) -> Option<usize> {
+        match self {
+            Self::Pipe(pipe) => Some(pipe.borrow().space_available()),
+            _ => None,
+        }
+    }
+
+    /// Change the file position of a regular file.
+    fn seek(&self, offset: libc::off_t, whence: libc::c_int) -> Result<libc::off_t, Errno> {
+        match self {
+            Self::Legacy(file) => {
+                let rv = unsafe { c::syscallhandler_seekLegacyFile(file.ptr(), offset, whence) };
+                if rv < 0 {
+                    return Err(Errno::from_i32(-rv as i32));
+                }
+                Ok(rv)
+            }
+            _ => Err(Errno::ESPIPE),
+        }
+    }
+
+    /// Read into `buf`, at `offset` if given. If `peek` is true, the data is not removed from a
+    /// pipe.
+    fn read(
+        &self,
+        ctx: &mut ThreadContext,
+        buf: &mut [u8],
+        offset: Option<libc::off_t>,
+        peek: bool,
+    ) -> Result<usize, SyscallError> {
+        match self {
+            Self::Pipe(pipe) => {
+                let rv = if peek {
+                    pipe.borrow().peek(Cursor::new(buf))
+                } else {
+                    EventQueue::queue_and_run(|event_queue| {
+                        pipe.borrow_mut().read(Cursor::new(buf), 0, event_queue)
+                    })
+                };
+                Ok(rv?.into())
+            }
+            Self::Socket(socket) => {
+                let (rv, _addr, _len, _ancillary) = EventQueue::queue_and_run(|event_queue| {
+                    socket
+                        .borrow_mut()
+                        .recvfrom(Cursor::new(buf), MsgFlags::empty(), event_queue)
+                })?;
+                Ok(rv.into())
+            }
+            Self::Legacy(file) => {
+                let (offset, use_offset) = match offset {
+                    Some(x) => (x, true),
+                    None => (0, false),
+                };
+                let rv = unsafe {
+                    c::syscallhandler_readLegacyFileShadow(
+                        ctx.thread.csyscallhandler(),
+                        file.ptr(),
+                        buf.as_mut_ptr() as *mut libc::c_void,
+                        buf.len().try_into().unwrap(),
+                        offset,
+                        use_offset,
+                    )
+                };
+                legacy_result(rv)
+            }
+        }
+    }
+
+    /// Write `buf`, at `offset` if given.
+    fn write(
+        &self,
+        ctx: &mut ThreadContext,
+        buf: &[u8],
+        offset: Option<libc::off_t>,
+    ) -> Result<usize, SyscallError> {
+        match self {
+            Self::Pipe(pipe) => {
+                let rv = EventQueue::queue_and_run(|event_queue| {
+                    pipe.borrow_mut().write(Cursor::new(buf), 0, event_queue)
+                });
+                Ok(rv?.into())
+            }
+            Self::Socket(socket) => {
+                let rv = EventQueue::queue_and_run(|event_queue| {
+                    socket.borrow_mut().sendto(
+                        Cursor::new(buf),
+                        0,
+                        None,
+                        AncillaryData::default(),
+                        event_queue,
+                    )
+                });
+                Ok(rv?.into())
+            }
+            Self::Legacy(file) => {
+                let (offset, use_offset) = match offset {
+                    Some(x) => (x, true),
+                    None => (0, false),
+                };
+                let rv = unsafe {
+                    c::syscallhandler_writeLegacyFileShadow(
+                        ctx.thread.csyscallhandler(),
+                        file.ptr(),
+                        buf.as_ptr() as *const libc::c_void,
+                        buf.len().try_into().unwrap(),
+                        offset,
+                        use_offset,
+                    )
+                };
+                legacy_result(rv)
+            }
+        }
+    }
+}
+
+/// Convert the return value of a C function that returns a number of bytes or a negative errno.
+fn legacy_result(rv: libc::ssize_t) -> Result<usize, SyscallError> {
+    if rv < 0 {
+        return Err(Errno::from_i32(-rv as i32).into());
+    }
+    Ok(rv.try_into().unwrap())
+}
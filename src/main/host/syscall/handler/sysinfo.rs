@@ -1,5 +1,6 @@
 use crate::core::support::emulated_time::EmulatedTime;
 use crate::core::worker::Worker;
+use crate::cshadow as c;
 use crate::host::context::ThreadContext;
 use crate::host::syscall::handler::SyscallHandler;
 use crate::host::syscall_types::{PluginPtr, SysCallArgs, SyscallResult, TypedPluginPtr};
@@ -7,6 +8,9 @@ use crate::utility::pod;
 
 use syscall_logger::log_syscall;
 
+/// The fixed-point shift of the load averages, from linux's 'include/uapi/linux/sysinfo.h'.
+const SI_LOAD_SHIFT: u32 = 16;
+
 impl SyscallHandler {
     #[log_syscall(/* rv */ libc::c_int, /* info */ *const libc::sysinfo)]
     pub fn sysinfo(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
@@ -22,24 +26,28 @@ impl SyscallHandler {
         // Get a zeroed struct to make sure we init all fields.
         let mut info = pod::zeroed::<libc::sysinfo>();
 
-        // These values are chosen arbitrarily; we don't think it matters too
-        // much, except to maintain determinism. For example, Tor make decisions
-        // about how many circuits to allow to be open (and other OOM settings)
+        let chost = ctx.host.chost();
+        let load_average = unsafe { std::slice::from_raw_parts(c::host_getLoadAverage(chost), 3) };
+
+        // The memory and load values are configured for each host, and must match the emulated
+        // '/proc/meminfo' and '/proc/loadavg' files. Applications may depend on them; for example,
+        // Tor makes decisions about how many circuits to allow to be open (and other OOM settings)
         // based on available memory.
         info.uptime = i64::try_from(seconds).unwrap_or(i64::MAX);
-        info.loads[0] = 1;
-        info.loads[1] = 1;
-        info.loads[2] = 1;
-        info.totalram = 32;
-        info.freeram = 24;
-        info.sharedram = 4;
-        info.bufferram = 4;
-        info.totalswap = 0;
-        info.freeswap = 0;
-        info.procs = 100;
-        info.totalhigh = 4;
-        info.freehigh = 3;
-        info.mem_unit = 1024 * 1024 * 1024; // GiB
+        for (load, configured) in info.loads.iter_mut().zip(load_average) {
+            *load = (configured * f64::from(1 << SI_LOAD_SHIFT)) as libc::c_ulong;
+        }
+        info.totalram = unsafe { c::host_getRamTotal(chost) };
+        info.freeram = unsafe { c::host_getRamFree(chost) };
+        info.sharedram = 0;
+        info.bufferram = 0;
+        info.totalswap = unsafe { c::host_getSwapTotal(chost) };
+        info.freeswap = unsafe { c::host_getSwapFree(chost) };
+        info.procs = c::SHADOW_SIMULATED_PROCS.try_into().unwrap();
+        // there is no high memory on 64-bit systems
+        info.totalhigh = 0;
+        info.freehigh = 0;
+        info.mem_unit = 1;
 
         // Write the result to plugin memory.
         ctx.process.memory_mut().copy_to_ptr(info_ptr, &[info])?;
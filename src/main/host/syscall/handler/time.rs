@@ -1,10 +1,15 @@
+use crate::core::support::emulated_time::EmulatedTime;
 use crate::core::support::simulation_time::SimulationTime;
 use crate::core::worker::Worker;
+use crate::cshadow as c;
 use crate::host::context::ThreadContext;
+use crate::host::cpu_timer::CpuTimer;
+use crate::host::posix_timer::{PosixTimer, TimerNotification};
 use crate::host::syscall::handler::SyscallHandler;
 use crate::host::syscall_types::SyscallResult;
-use crate::host::syscall_types::{SysCallArgs, TypedPluginPtr};
+use crate::host::syscall_types::{PluginPtr, SysCallArgs, TypedPluginPtr};
 use crate::host::timer::Timer;
+use crate::utility::pod;
 
 use log::*;
 use nix::errno::Errno;
@@ -22,18 +27,311 @@ fn itimerval_from_timer(timer: &Timer) -> libc::itimerval {
     }
 }
 
+fn itimerval_from_cpu_timer(timer: &CpuTimer) -> libc::itimerval {
+    libc::itimerval {
+        it_interval: timer.expire_interval().try_into().unwrap(),
+        it_value: timer
+            .remaining_time()
+            .unwrap_or(SimulationTime::ZERO)
+            .try_into()
+            .unwrap(),
+    }
+}
+
+/// The largest signal number. Signals above `STANDARD_SIGNAL_MAX_NO` are realtime signals, which
+/// we don't support.
+const SIGRT_MAX: libc::c_int = 64;
+const STANDARD_SIGNAL_MAX_NO: libc::c_int = 31;
+
+// from linux's 'include/uapi/linux/timex.h'
+const ADJ_OFFSET: libc::c_uint = 0x0001;
+const ADJ_FREQUENCY: libc::c_uint = 0x0002;
+const ADJ_MAXERROR: libc::c_uint = 0x0004;
+const ADJ_ESTERROR: libc::c_uint = 0x0008;
+const ADJ_STATUS: libc::c_uint = 0x0010;
+const ADJ_TIMECONST: libc::c_uint = 0x0020;
+const ADJ_SETOFFSET: libc::c_uint = 0x0100;
+const ADJ_MICRO: libc::c_uint = 0x1000;
+const ADJ_NANO: libc::c_uint = 0x2000;
+const ADJ_TICK: libc::c_uint = 0x4000;
+const ADJ_ADJTIME: libc::c_uint = 0x8000;
+const ADJ_OFFSET_READONLY: libc::c_uint = 0x2000;
+const STA_PLL: libc::c_int = 0x0001;
+const STA_UNSYNC: libc::c_int = 0x0040;
+const STA_CLOCKERR: libc::c_int = 0x1000;
+const STA_NANO: libc::c_int = 0x2000;
+const STA_RONLY: libc::c_int = 0xff00;
+const TIME_OK: libc::c_int = 0;
+const TIME_ERROR: libc::c_int = 5;
+
+/// The largest offset in nanoseconds that `adjtimex` accepts with `ADJ_OFFSET`. Larger offsets
+/// are clamped to this value, like linux's `MAXPHASE`.
+const MAX_PHASE_NANOS: i64 = 500_000_000;
+
+/// The largest phase-locked loop time constant, like linux's `MAXTC`.
+const MAX_TIME_CONSTANT: libc::c_long = 10;
+
+/// The largest frequency adjustment in ppm that `adjtimex` accepts. Larger adjustments are clamped
+/// to this value, like linux's `MAXFREQ`.
+const MAX_FREQUENCY_PPM: f64 = 500.0;
+
+/// The `struct timex` frequency is in ppm with a 16-bit fractional part.
+const FREQUENCY_SCALE: f64 = 65536.0;
+
+/// The value of `struct timex` `tick` for a kernel with `HZ=100`. `adjtimex` accepts ticks
+/// within 10% of this value.
+const TICK_USECS: libc::c_long = 10_000;
+
+/// The frequency adjustment in ppm caused by a `struct timex` `tick` of `tick` microseconds.
+fn tick_frequency_ppm(tick: libc::c_long) -> f64 {
+    (tick - TICK_USECS) as f64 * 1_000_000.0 / TICK_USECS as f64
+}
+
+fn itimerspec_from_time(value: SimulationTime, interval: SimulationTime) -> libc::itimerspec {
+    libc::itimerspec {
+        it_interval: interval.try_into().unwrap(),
+        it_value: value.try_into().unwrap(),
+    }
+}
+
 impl SyscallHandler {
+    #[log_syscall(/* rv */ libc::c_int, /* clockid */ libc::clockid_t, /* tp */ *const libc::timespec)]
+    pub fn clock_settime(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let clock_id: libc::clockid_t = args.get(0).into();
+        let tp_ptr = TypedPluginPtr::new::<libc::timespec>(args.get(1).into(), 1);
+
+        // only the realtime clock can be set
+        if clock_id != libc::CLOCK_REALTIME {
+            debug!("Unable to set clockid {}", clock_id);
+            return Err(Errno::EINVAL.into());
+        }
+
+        let tp = ctx.process.memory().read_vals::<_, 1>(tp_ptr)?[0];
+        let since_epoch = SimulationTime::try_from(tp).map_err(|_| Errno::EINVAL)?;
+        let realtime = EmulatedTime::UNIX_EPOCH
+            .checked_add(since_epoch)
+            .ok_or(Errno::EINVAL)?;
+
+        ctx.host.set_realtime(realtime);
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* tv */ *const libc::timeval, /* tz */ *const libc::c_void)]
+    pub fn settimeofday(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let tv_ptr = TypedPluginPtr::new::<libc::timeval>(args.get(0).into(), 1);
+        let tz_ptr: PluginPtr = args.get(1).into();
+
+        // all hosts use UTC
+        if !tz_ptr.is_null() {
+            debug!("Ignoring the timezone given to settimeofday");
+        }
+
+        if tv_ptr.is_null() {
+            return Ok(0.into());
+        }
+
+        let tv = ctx.process.memory().read_vals::<_, 1>(tv_ptr)?[0];
+        let since_epoch = SimulationTime::try_from(tv).map_err(|_| Errno::EINVAL)?;
+        let realtime = EmulatedTime::UNIX_EPOCH
+            .checked_add(since_epoch)
+            .ok_or(Errno::EINVAL)?;
+
+        ctx.host.set_realtime(realtime);
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* buf */ *const libc::c_void)]
+    pub fn adjtimex(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let buf_ptr = TypedPluginPtr::new::<libc::timex>(args.get(0).into(), 1);
+        Self::adjtime_helper(ctx, buf_ptr)
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* clockid */ libc::clockid_t, /* buf */ *const libc::c_void)]
+    pub fn clock_adjtime(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let clock_id: libc::clockid_t = args.get(0).into();
+        let buf_ptr = TypedPluginPtr::new::<libc::timex>(args.get(1).into(), 1);
+
+        // only the realtime clock can be adjusted
+        if clock_id != libc::CLOCK_REALTIME {
+            debug!("Unable to adjust clockid {}", clock_id);
+            return Err(Errno::EINVAL.into());
+        }
+
+        Self::adjtime_helper(ctx, buf_ptr)
+    }
+
+    /// Apply the adjustments in the `struct timex` to the host's realtime clock, and write the
+    /// state of the clock back to the `struct timex`. We don't model the kernel's phase-locked
+    /// loop; offsets set with `ADJ_OFFSET` are slewed like `adjtime` offsets, and the status,
+    /// error, and time constant values are only stored and reported back. The clock is reported
+    /// as synchronized unless a process sets `STA_UNSYNC`.
+    fn adjtime_helper(
+        ctx: &mut ThreadContext,
+        buf_ptr: TypedPluginPtr<libc::timex>,
+    ) -> SyscallResult {
+        let buf = ctx.process.memory().read_vals::<_, 1>(buf_ptr)?[0];
+        let modes = buf.modes;
+
+        let mut status = ctx.host.clock_status();
+        let mut frequency = ctx.host.realtime_frequency_ppm() - tick_frequency_ppm(status.tick);
+
+        let offset = if modes & ADJ_ADJTIME != 0 {
+            // an `adjtime` call, which only uses the offset, in microseconds; the previous offset
+            // that was still to be applied is returned
+            let remaining = ctx.host.realtime_slew() / 1_000;
+            if modes & ADJ_OFFSET_READONLY == 0 {
+                ctx.host.set_realtime_slew(buf.offset.saturating_mul(1_000));
+            }
+            remaining
+        } else {
+            let supported_modes = ADJ_OFFSET
+                | ADJ_FREQUENCY
+                | ADJ_MAXERROR
+                | ADJ_ESTERROR
+                | ADJ_STATUS
+                | ADJ_TIMECONST
+                | ADJ_SETOFFSET
+                | ADJ_MICRO
+                | ADJ_NANO
+                | ADJ_TICK;
+            if modes & !supported_modes != 0 {
+                warn!("Unsupported adjtimex modes {:#x}", modes & !supported_modes);
+                return Err(Errno::EINVAL.into());
+            }
+
+            if modes & ADJ_TICK != 0
+                && !(TICK_USECS * 9 / 10..=TICK_USECS * 11 / 10).contains(&buf.tick)
+            {
+                return Err(Errno::EINVAL.into());
+            }
+
+            if modes & ADJ_SETOFFSET != 0 {
+                // the offset is in `time`, where `tv_usec` is in nanoseconds if ADJ_NANO is set
+                let (frac_per_sec, nanos_per_frac) = if modes & ADJ_NANO != 0 {
+                    (1_000_000_000, 1)
+                } else {
+                    (1_000_000, 1_000)
+                };
+                if !(0..frac_per_sec).contains(&buf.time.tv_usec) {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let offset = i128::from(buf.time.tv_sec) * 1_000_000_000
+                    + i128::from(buf.time.tv_usec) * nanos_per_frac;
+                let now = ctx
+                    .host
+                    .realtime()
+                    .duration_since(&EmulatedTime::UNIX_EPOCH)
+                    .as_nanos();
+                let realtime = i128::try_from(now).unwrap() + offset;
+                let realtime = u64::try_from(realtime)
+                    .ok()
+                    .and_then(|x| {
+                        EmulatedTime::UNIX_EPOCH.checked_add(SimulationTime::from_nanos(x))
+                    })
+                    .ok_or(Errno::EINVAL)?;
+
+                ctx.host.set_realtime(realtime);
+            }
+
+            // the remaining modes are applied in the same order as linux
+            if modes & ADJ_STATUS != 0 {
+                status.status = (status.status & STA_RONLY) | (buf.status & !STA_RONLY);
+            }
+            if modes & ADJ_NANO != 0 {
+                status.status |= STA_NANO;
+            }
+            if modes & ADJ_MICRO != 0 {
+                status.status &= !STA_NANO;
+            }
+            if modes & ADJ_FREQUENCY != 0 {
+                frequency = (buf.freq as f64 / FREQUENCY_SCALE)
+                    .clamp(-MAX_FREQUENCY_PPM, MAX_FREQUENCY_PPM);
+            }
+            if modes & ADJ_MAXERROR != 0 {
+                status.maxError = buf.maxerror;
+            }
+            if modes & ADJ_ESTERROR != 0 {
+                status.estError = buf.esterror;
+            }
+            if modes & ADJ_TIMECONST != 0 {
+                // the time constant is 4 larger in microsecond mode
+                let constant = if status.status & STA_NANO != 0 {
+                    buf.constant
+                } else {
+                    buf.constant.saturating_add(4)
+                };
+                status.timeConstant = constant.clamp(0, MAX_TIME_CONSTANT);
+            }
+            // like linux, offsets are only used when the phase-locked loop is enabled
+            if modes & ADJ_OFFSET != 0 && status.status & STA_PLL != 0 {
+                let offset = if status.status & STA_NANO != 0 {
+                    buf.offset
+                } else {
+                    buf.offset.saturating_mul(1_000)
+                };
+                ctx.host
+                    .set_realtime_slew(offset.clamp(-MAX_PHASE_NANOS, MAX_PHASE_NANOS));
+            }
+            if modes & ADJ_TICK != 0 {
+                status.tick = buf.tick;
+            }
+            if modes & (ADJ_FREQUENCY | ADJ_TICK) != 0 {
+                ctx.host
+                    .set_realtime_frequency_ppm(frequency + tick_frequency_ppm(status.tick));
+            }
+
+            ctx.host.set_clock_status(status);
+
+            if status.status & STA_NANO != 0 {
+                ctx.host.realtime_slew()
+            } else {
+                ctx.host.realtime_slew() / 1_000
+            }
+        };
+
+        let nano = status.status & STA_NANO != 0;
+        let now = ctx
+            .host
+            .realtime()
+            .duration_since(&EmulatedTime::UNIX_EPOCH);
+        let now = libc::timespec::try_from(now).unwrap();
+
+        let mut result = pod::zeroed::<libc::timex>();
+        result.modes = modes;
+        result.offset = offset;
+        result.freq = (frequency * FREQUENCY_SCALE).round() as libc::c_long;
+        result.maxerror = status.maxError;
+        result.esterror = status.estError;
+        result.status = status.status;
+        result.constant = status.timeConstant;
+        result.precision = 1;
+        result.tolerance = (MAX_FREQUENCY_PPM * FREQUENCY_SCALE) as libc::c_long;
+        result.tick = status.tick;
+        result.time.tv_sec = now.tv_sec;
+        result.time.tv_usec = if nano {
+            now.tv_nsec
+        } else {
+            now.tv_nsec / 1_000
+        };
+
+        ctx.process.memory_mut().copy_to_ptr(buf_ptr, &[result])?;
+
+        if status.status & (STA_UNSYNC | STA_CLOCKERR) != 0 {
+            Ok(TIME_ERROR.into())
+        } else {
+            Ok(TIME_OK.into())
+        }
+    }
+
     #[log_syscall(/* rv */ libc::c_int, /* which */ libc::c_int, /*curr_value*/ *const libc::c_void)]
     pub fn getitimer(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
         let which = libc::c_int::from(args.get(0));
         let curr_value_ptr = TypedPluginPtr::new::<libc::itimerval>(args.get(1).into(), 1);
 
-        if which != libc::ITIMER_REAL {
-            error!("Timer type {} unsupported", which);
-            return Err(Errno::ENOSYS.into());
-        }
-
-        let itimerval = itimerval_from_timer(ctx.process.realtime_timer());
+        let itimerval = Self::get_itimer(ctx, which)?;
         ctx.process
             .memory_mut()
             .copy_to_ptr(curr_value_ptr, &[itimerval])?;
@@ -47,16 +345,13 @@ impl SyscallHandler {
         let new_value_ptr = TypedPluginPtr::new::<libc::itimerval>(args.get(1).into(), 1);
         let old_value_ptr = TypedPluginPtr::new::<libc::itimerval>(args.get(2).into(), 1);
 
-        if which != libc::ITIMER_REAL {
-            error!("Timer type {} unsupported", which);
-            return Err(Errno::ENOSYS.into());
-        }
+        // also validates `which`
+        let old_itimerval = Self::get_itimer(ctx, which)?;
 
         if !old_value_ptr.is_null() {
-            let itimerval = itimerval_from_timer(ctx.process.realtime_timer());
             ctx.process
                 .memory_mut()
-                .copy_to_ptr(old_value_ptr, &[itimerval])?;
+                .copy_to_ptr(old_value_ptr, &[old_itimerval])?;
         }
 
         let new_value = ctx.process.memory().read_vals::<_, 1>(new_value_ptr)?[0];
@@ -65,7 +360,12 @@ impl SyscallHandler {
         let new_value_interval =
             SimulationTime::try_from(new_value.it_interval).map_err(|_| Errno::EINVAL)?;
 
-        if new_value_value == SimulationTime::ZERO {
+        if which != libc::ITIMER_REAL {
+            // virtual and profiling timers count down the CPU time that we model for the
+            // process, so they only advance if `model_unblocked_syscall_latency` is enabled
+            let timer = ctx.process.cpu_timer_mut(which).unwrap();
+            timer.arm(new_value_value, new_value_interval);
+        } else if new_value_value == SimulationTime::ZERO {
             ctx.process.realtime_timer_mut().disarm();
         } else {
             ctx.process.realtime_timer_mut().arm(
@@ -77,4 +377,223 @@ impl SyscallHandler {
 
         Ok(0.into())
     }
+
+    /// The current value of the interval timer `which`.
+    fn get_itimer(ctx: &ThreadContext, which: libc::c_int) -> Result<libc::itimerval, Errno> {
+        if which == libc::ITIMER_REAL {
+            return Ok(itimerval_from_timer(ctx.process.realtime_timer()));
+        }
+
+        match ctx.process.cpu_timer(which) {
+            Some(timer) => Ok(itimerval_from_cpu_timer(timer)),
+            None => {
+                debug!("Unknown timer type {}", which);
+                Err(Errno::EINVAL)
+            }
+        }
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* clockid */ libc::clockid_t, /* sevp */ *const libc::c_void,
+                  /* timerid */ *const libc::c_int)]
+    pub fn timer_create(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let clock_id: libc::clockid_t = args.get(0).into();
+        let sevp_ptr = TypedPluginPtr::new::<libc::sigevent>(args.get(1).into(), 1);
+        let timer_id_ptr = TypedPluginPtr::new::<libc::c_int>(args.get(2).into(), 1);
+
+        match clock_id {
+            libc::CLOCK_REALTIME | libc::CLOCK_MONOTONIC | libc::CLOCK_BOOTTIME => {}
+            libc::CLOCK_PROCESS_CPUTIME_ID
+            | libc::CLOCK_THREAD_CPUTIME_ID
+            | libc::CLOCK_REALTIME_ALARM
+            | libc::CLOCK_BOOTTIME_ALARM => {
+                warn!("Unsupported clockid {} for timer_create", clock_id);
+                return Err(Errno::ENOTSUP.into());
+            }
+            _ => {
+                debug!("Unknown clockid {}", clock_id);
+                return Err(Errno::EINVAL.into());
+            }
+        }
+
+        let timer_id = ctx.process.posix_timers().unused_id();
+
+        // timer_create(2): "Specifying sevp as NULL is equivalent to specifying a pointer to a
+        // sigevent structure in which sigev_notify is SIGEV_SIGNAL, sigev_signo is SIGALRM, and
+        // sigev_value.sival_int is the timer ID"
+        let notification = if sevp_ptr.is_null() {
+            TimerNotification::Signal {
+                signo: libc::SIGALRM,
+                value: timer_id as usize,
+            }
+        } else {
+            let sevp = ctx.process.memory().read_vals::<_, 1>(sevp_ptr)?[0];
+            Self::timer_notification_from_sigevent(ctx, &sevp)?
+        };
+
+        ctx.process
+            .memory_mut()
+            .copy_to_ptr(timer_id_ptr, &[timer_id])?;
+
+        let timer = PosixTimer::new(ctx.process.id(), timer_id, clock_id, notification);
+        let replaced = ctx.process.posix_timers_mut().insert(timer_id, timer);
+        debug_assert!(replaced.is_none());
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* timerid */ libc::c_int, /* flags */ libc::c_int,
+                  /* new_value */ *const libc::c_void, /* old_value */ *const libc::c_void)]
+    pub fn timer_settime(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let timer_id: libc::c_int = args.get(0).into();
+        let flags: libc::c_int = args.get(1).into();
+        let new_value_ptr = TypedPluginPtr::new::<libc::itimerspec>(args.get(2).into(), 1);
+        let old_value_ptr = TypedPluginPtr::new::<libc::itimerspec>(args.get(3).into(), 1);
+
+        if ctx.process.posix_timers().get(timer_id).is_none() {
+            debug!("Invalid timer id {}", timer_id);
+            return Err(Errno::EINVAL.into());
+        }
+
+        if new_value_ptr.is_null() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let new_value = ctx.process.memory().read_vals::<_, 1>(new_value_ptr)?[0];
+        let value = SimulationTime::try_from(new_value.it_value).map_err(|_| Errno::EINVAL)?;
+        let interval =
+            SimulationTime::try_from(new_value.it_interval).map_err(|_| Errno::EINVAL)?;
+
+        // the old value is always relative, even if TIMER_ABSTIME is set
+        if !old_value_ptr.is_null() {
+            let (old_value, old_interval) =
+                ctx.process.posix_timers().get(timer_id).unwrap().get_time();
+            ctx.process.memory_mut().copy_to_ptr(
+                old_value_ptr,
+                &[itimerspec_from_time(old_value, old_interval)],
+            )?;
+        }
+
+        let timer = ctx.process.posix_timers_mut().get_mut(timer_id).unwrap();
+
+        // timer_settime(2): "If new_value->it_value specifies a zero value (i.e., both subfields
+        // are zero), then the timer is disarmed"
+        if value == SimulationTime::ZERO {
+            timer.disarm();
+            return Ok(0.into());
+        }
+
+        // all of shadow's clocks share the same epoch
+        let is_abs = flags & libc::TIMER_ABSTIME != 0;
+        let base = if is_abs {
+            EmulatedTime::UNIX_EPOCH
+        } else {
+            Worker::current_time().unwrap()
+        };
+        let expire_time = base.checked_add(value).unwrap_or(EmulatedTime::MAX);
+
+        // the host's realtime clock may be offset from the emulated time, drift from it, and be
+        // set, so absolute realtime timers must follow the realtime clock
+        let realtime = is_abs && timer.clock_id() == libc::CLOCK_REALTIME;
+
+        timer.arm(ctx.host, expire_time, interval, realtime);
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* timerid */ libc::c_int,
+                  /* curr_value */ *const libc::c_void)]
+    pub fn timer_gettime(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let timer_id: libc::c_int = args.get(0).into();
+        let curr_value_ptr = TypedPluginPtr::new::<libc::itimerspec>(args.get(1).into(), 1);
+
+        let timer = match ctx.process.posix_timers().get(timer_id) {
+            Some(x) => x,
+            None => {
+                debug!("Invalid timer id {}", timer_id);
+                return Err(Errno::EINVAL.into());
+            }
+        };
+
+        let (value, interval) = timer.get_time();
+        ctx.process
+            .memory_mut()
+            .copy_to_ptr(curr_value_ptr, &[itimerspec_from_time(value, interval)])?;
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* timerid */ libc::c_int)]
+    pub fn timer_getoverrun(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let timer_id: libc::c_int = args.get(0).into();
+
+        match ctx.process.posix_timers().get(timer_id) {
+            Some(timer) => Ok(timer.overrun().into()),
+            None => {
+                debug!("Invalid timer id {}", timer_id);
+                Err(Errno::EINVAL.into())
+            }
+        }
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* timerid */ libc::c_int)]
+    pub fn timer_delete(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let timer_id: libc::c_int = args.get(0).into();
+
+        // dropping the timer disarms it
+        match ctx.process.posix_timers_mut().remove(timer_id) {
+            Some(_) => Ok(0.into()),
+            None => {
+                debug!("Invalid timer id {}", timer_id);
+                Err(Errno::EINVAL.into())
+            }
+        }
+    }
+
+    /// Validate the `struct sigevent` given to `timer_create()`.
+    fn timer_notification_from_sigevent(
+        ctx: &mut ThreadContext,
+        sevp: &libc::sigevent,
+    ) -> Result<TimerNotification, Errno> {
+        // `sigev_value` is a union of an int and a pointer; we keep all of its bits
+        let value = unsafe { sevp.sigev_value.sival_ptr } as usize;
+        let signo = sevp.sigev_signo;
+
+        let check_signo = || {
+            if !(1..=SIGRT_MAX).contains(&signo) {
+                debug!("Invalid timer signal {}", signo);
+                return Err(Errno::EINVAL);
+            }
+            if signo > STANDARD_SIGNAL_MAX_NO {
+                warn!("Unimplemented timer signal {}", signo);
+                return Err(Errno::ENOSYS);
+            }
+            Ok(())
+        };
+
+        match sevp.sigev_notify {
+            libc::SIGEV_NONE => Ok(TimerNotification::None),
+            libc::SIGEV_SIGNAL => {
+                check_signo()?;
+                Ok(TimerNotification::Signal { signo, value })
+            }
+            libc::SIGEV_THREAD_ID => {
+                check_signo()?;
+
+                // the thread must be in the calling process
+                let tid = sevp.sigev_notify_thread_id;
+                let thread = unsafe { c::process_getThread(ctx.process.cprocess(), tid) };
+                if thread.is_null() {
+                    debug!("Invalid timer thread id {}", tid);
+                    return Err(Errno::EINVAL);
+                }
+
+                Ok(TimerNotification::ThreadSignal { signo, value, tid })
+            }
+            // SIGEV_THREAD is implemented by libc, not the kernel
+            notify => {
+                debug!("Invalid timer notification type {}", notify);
+                Err(Errno::EINVAL)
+            }
+        }
+    }
 }
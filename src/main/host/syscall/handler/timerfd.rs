@@ -0,0 +1,182 @@
+use crate::core::support::emulated_time::EmulatedTime;
+use crate::core::support::simulation_time::SimulationTime;
+use crate::core::worker::Worker;
+use crate::host::context::ThreadContext;
+use crate::host::descriptor::timerfd::TimerFd;
+use crate::host::descriptor::{
+    CompatFile, Descriptor, DescriptorFlags, File, FileStatus, OpenFile,
+};
+use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall_types::SyscallResult;
+use crate::host::syscall_types::{SysCallArgs, TypedPluginPtr};
+use crate::utility::event_queue::EventQueue;
+
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use log::*;
+use nix::errno::Errno;
+
+use syscall_logger::log_syscall;
+
+/// From the linux headers. Not exposed by libc.
+const TFD_TIMER_CANCEL_ON_SET: libc::c_int = 1 << 1;
+
+fn itimerspec_from_time(value: SimulationTime, interval: SimulationTime) -> libc::itimerspec {
+    libc::itimerspec {
+        it_interval: interval.try_into().unwrap(),
+        it_value: value.try_into().unwrap(),
+    }
+}
+
+impl SyscallHandler {
+    #[log_syscall(/* rv */ libc::c_int, /* clockid */ libc::clockid_t, /* flags */ libc::c_int)]
+    pub fn timerfd_create(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let clock_id: libc::clockid_t = args.get(0).into();
+        let flags: libc::c_int = args.get(1).into();
+
+        match clock_id {
+            libc::CLOCK_REALTIME | libc::CLOCK_MONOTONIC | libc::CLOCK_BOOTTIME => {}
+            libc::CLOCK_REALTIME_ALARM | libc::CLOCK_BOOTTIME_ALARM => {
+                debug!("Unsupported clockid {}", clock_id);
+                return Err(Errno::ENOSYS.into());
+            }
+            _ => {
+                debug!("Unknown clockid {}", clock_id);
+                return Err(Errno::EINVAL.into());
+            }
+        }
+
+        if flags & !(libc::TFD_NONBLOCK | libc::TFD_CLOEXEC) != 0 {
+            debug!("Invalid timerfd_create flags: {}", flags);
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut file_flags = FileStatus::empty();
+        let mut descriptor_flags = DescriptorFlags::empty();
+
+        if flags & libc::TFD_NONBLOCK != 0 {
+            file_flags.insert(FileStatus::NONBLOCK);
+        }
+
+        if flags & libc::TFD_CLOEXEC != 0 {
+            descriptor_flags.insert(DescriptorFlags::CLOEXEC);
+        }
+
+        let file = TimerFd::new(clock_id, file_flags);
+
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::TimerFd(file))));
+        desc.set_flags(descriptor_flags);
+
+        let fd = ctx
+            .process
+            .register_descriptor(desc)
+            .map_err(|_| Errno::EMFILE)?;
+
+        trace!("timerfd_create() returning fd {}", fd);
+
+        Ok(fd.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* fd */ libc::c_int, /* flags */ libc::c_int,
+                  /* new_value */ *const libc::c_void, /* old_value */ *const libc::c_void)]
+    pub fn timerfd_settime(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd: libc::c_int = args.get(0).into();
+        let flags: libc::c_int = args.get(1).into();
+        let new_value_ptr = TypedPluginPtr::new::<libc::itimerspec>(args.get(2).into(), 1);
+        let old_value_ptr = TypedPluginPtr::new::<libc::itimerspec>(args.get(3).into(), 1);
+
+        if flags & !(libc::TFD_TIMER_ABSTIME | TFD_TIMER_CANCEL_ON_SET) != 0 {
+            debug!("Invalid timerfd_settime flags: {}", flags);
+            return Err(Errno::EINVAL.into());
+        }
+
+        let timerfd = Self::get_timerfd(ctx, fd)?;
+
+        let new_value = ctx.process.memory().read_vals::<_, 1>(new_value_ptr)?[0];
+        let value = SimulationTime::try_from(new_value.it_value).map_err(|_| Errno::EINVAL)?;
+        let interval =
+            SimulationTime::try_from(new_value.it_interval).map_err(|_| Errno::EINVAL)?;
+
+        // the old value is always relative, even if TFD_TIMER_ABSTIME is set
+        if !old_value_ptr.is_null() {
+            let (old_value, old_interval) = timerfd.borrow().get_time();
+            ctx.process.memory_mut().copy_to_ptr(
+                old_value_ptr,
+                &[itimerspec_from_time(old_value, old_interval)],
+            )?;
+        }
+
+        let is_abs = flags & libc::TFD_TIMER_ABSTIME != 0;
+
+        // timerfd_create(2): TFD_TIMER_CANCEL_ON_SET only applies to absolute timers using the
+        // realtime clock
+        let cancel_on_set = is_abs
+            && flags & TFD_TIMER_CANCEL_ON_SET != 0
+            && timerfd.borrow().clock_id() == libc::CLOCK_REALTIME;
+
+        EventQueue::queue_and_run(|event_queue| {
+            let mut timerfd = timerfd.borrow_mut();
+
+            // timerfd_settime(2): "Setting both fields of new_value.it_value to zero disarms the
+            // timer"
+            if value == SimulationTime::ZERO {
+                timerfd.disarm(event_queue);
+                return;
+            }
+
+            // all of shadow's clocks share the same epoch
+            let base = if is_abs {
+                EmulatedTime::UNIX_EPOCH
+            } else {
+                Worker::current_time().unwrap()
+            };
+            let expire_time = base.checked_add(value).unwrap_or(EmulatedTime::MAX);
+
+            // the host's realtime clock may be offset from the emulated time, drift from it, and
+            // be set, so absolute realtime timers must follow the realtime clock
+            let realtime = is_abs && timerfd.clock_id() == libc::CLOCK_REALTIME;
+
+            timerfd.arm(
+                ctx.host,
+                expire_time,
+                interval,
+                realtime,
+                cancel_on_set,
+                event_queue,
+            );
+        });
+
+        Ok(0.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* fd */ libc::c_int, /* curr_value */ *const libc::c_void)]
+    pub fn timerfd_gettime(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd: libc::c_int = args.get(0).into();
+        let curr_value_ptr = TypedPluginPtr::new::<libc::itimerspec>(args.get(1).into(), 1);
+
+        let timerfd = Self::get_timerfd(ctx, fd)?;
+
+        let (value, interval) = timerfd.borrow().get_time();
+        ctx.process
+            .memory_mut()
+            .copy_to_ptr(curr_value_ptr, &[itimerspec_from_time(value, interval)])?;
+
+        Ok(0.into())
+    }
+
+    /// Returns the timerfd for the file descriptor, or an error if it doesn't exist or isn't a
+    /// timerfd.
+    fn get_timerfd(
+        ctx: &mut ThreadContext,
+        fd: libc::c_int,
+    ) -> Result<Arc<AtomicRefCell<TimerFd>>, Errno> {
+        match Self::get_descriptor(ctx.process, fd)?.file() {
+            CompatFile::New(file) => match file.inner_file() {
+                File::TimerFd(timerfd) => Ok(Arc::clone(timerfd)),
+                _ => Err(Errno::EINVAL),
+            },
+            CompatFile::Legacy(_) => Err(Errno::EINVAL),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::cshadow as c;
+use crate::host::context::ThreadContext;
+use crate::host::descriptor::{CompatFile, FileMode, OpenFile};
+use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall::io::{iovecs_len, read_iovecs};
+use crate::host::syscall_types::{PluginPtr, SysCallArgs};
+use crate::host::syscall_types::{SyscallError, SyscallResult};
+
+use log::*;
+use nix::errno::Errno;
+
+use syscall_logger::log_syscall;
+
+// the `preadv2()` and `pwritev2()` flags from linux's 'include/uapi/linux/fs.h'
+const RWF_HIPRI: libc::c_int = 0x00000001;
+const RWF_DSYNC: libc::c_int = 0x00000002;
+const RWF_SYNC: libc::c_int = 0x00000004;
+const RWF_NOWAIT: libc::c_int = 0x00000008;
+const RWF_APPEND: libc::c_int = 0x00000010;
+
+/// A C syscall handler, which is used for legacy files.
+type LegacySyscallFn =
+    unsafe extern "C" fn(*mut c::SysCallHandler, *const SysCallArgs) -> c::SysCallReturn;
+
+impl SyscallHandler {
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd */ libc::c_int, /* iov */ *const libc::iovec,
+                  /* iovcnt */ libc::c_int)]
+    pub fn readv(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd = libc::c_int::from(args.get(0));
+        let iov_ptr = PluginPtr::from(args.get(1));
+        let iov_count = usize::from(args.get(2));
+
+        self.readv_helper(
+            ctx,
+            args,
+            c::syscallhandler_readv,
+            fd,
+            iov_ptr,
+            iov_count,
+            None,
+            0,
+        )
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd */ libc::c_int, /* iov */ *const libc::iovec,
+                  /* iovcnt */ libc::c_int, /* pos_l */ libc::c_ulong, /* pos_h */ libc::c_ulong)]
+    pub fn preadv(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd = libc::c_int::from(args.get(0));
+        let iov_ptr = PluginPtr::from(args.get(1));
+        let iov_count = usize::from(args.get(2));
+        // on 64-bit platforms the low word holds the entire offset and linux ignores `pos_h`
+        let offset = libc::off_t::from(args.get(3));
+
+        if offset < 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        self.readv_helper(
+            ctx,
+            args,
+            c::syscallhandler_preadv,
+            fd,
+            iov_ptr,
+            iov_count,
+            Some(offset),
+            0,
+        )
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd */ libc::c_int, /* iov */ *const libc::iovec,
+                  /* iovcnt */ libc::c_int, /* pos_l */ libc::c_ulong, /* pos_h */ libc::c_ulong,
+                  /* flags */ libc::c_int)]
+    pub fn preadv2(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd = libc::c_int::from(args.get(0));
+        let iov_ptr = PluginPtr::from(args.get(1));
+        let iov_count = usize::from(args.get(2));
+        let offset = libc::off_t::from(args.get(3));
+        let flags = libc::c_int::from(args.get(5));
+
+        // an offset of -1 uses and updates the current file position, like `readv()`
+        let offset = match offset {
+            -1 => None,
+            x if x < 0 => return Err(Errno::EINVAL.into()),
+            x => Some(x),
+        };
+
+        self.readv_helper(
+            ctx,
+            args,
+            c::syscallhandler_preadv2,
+            fd,
+            iov_ptr,
+            iov_count,
+            offset,
+            flags,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn readv_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        args: &SysCallArgs,
+        legacy_syscall_fn: LegacySyscallFn,
+        fd: libc::c_int,
+        iov_ptr: PluginPtr,
+        iov_count: usize,
+        offset: Option<libc::off_t>,
+        flags: libc::c_int,
+    ) -> SyscallResult {
+        let file = match Self::uio_file(ctx, fd)? {
+            Some(x) => x,
+            // if it's a legacy file, use the C syscall handler instead
+            None => {
+                return unsafe { legacy_syscall_fn(ctx.thread.csyscallhandler(), args) }.into();
+            }
+        };
+
+        // none of the rust file types support seeking
+        if offset.is_some() {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        if !file.inner_file().borrow().mode().contains(FileMode::READ) {
+            return Err(Errno::EBADF.into());
+        }
+
+        let iovs = read_iovecs(ctx.process.memory(), iov_ptr, iov_count)?;
+        let nowait = rw_flags_nowait(flags)?;
+
+        // linux doesn't call into the file if there's nothing to read
+        if iovecs_len(&iovs) == 0 {
+            return Ok(0.into());
+        }
+
+        self.read_helper(ctx, fd, file, &iovs, 0, nowait)
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd */ libc::c_int, /* iov */ *const libc::iovec,
+                  /* iovcnt */ libc::c_int)]
+    pub fn writev(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd = libc::c_int::from(args.get(0));
+        let iov_ptr = PluginPtr::from(args.get(1));
+        let iov_count = usize::from(args.get(2));
+
+        self.writev_helper(
+            ctx,
+            args,
+            c::syscallhandler_writev,
+            fd,
+            iov_ptr,
+            iov_count,
+            None,
+            0,
+        )
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd */ libc::c_int, /* iov */ *const libc::iovec,
+                  /* iovcnt */ libc::c_int, /* pos_l */ libc::c_ulong, /* pos_h */ libc::c_ulong)]
+    pub fn pwritev(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd = libc::c_int::from(args.get(0));
+        let iov_ptr = PluginPtr::from(args.get(1));
+        let iov_count = usize::from(args.get(2));
+        // on 64-bit platforms the low word holds the entire offset and linux ignores `pos_h`
+        let offset = libc::off_t::from(args.get(3));
+
+        if offset < 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        self.writev_helper(
+            ctx,
+            args,
+            c::syscallhandler_pwritev,
+            fd,
+            iov_ptr,
+            iov_count,
+            Some(offset),
+            0,
+        )
+    }
+
+    #[log_syscall(/* rv */ libc::ssize_t, /* fd */ libc::c_int, /* iov */ *const libc::iovec,
+                  /* iovcnt */ libc::c_int, /* pos_l */ libc::c_ulong, /* pos_h */ libc::c_ulong,
+                  /* flags */ libc::c_int)]
+    pub fn pwritev2(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let fd = libc::c_int::from(args.get(0));
+        let iov_ptr = PluginPtr::from(args.get(1));
+        let iov_count = usize::from(args.get(2));
+        let offset = libc::off_t::from(args.get(3));
+        let flags = libc::c_int::from(args.get(5));
+
+        // an offset of -1 uses and updates the current file position, like `writev()`
+        let offset = match offset {
+            -1 => None,
+            x if x < 0 => return Err(Errno::EINVAL.into()),
+            x => Some(x),
+        };
+
+        self.writev_helper(
+            ctx,
+            args,
+            c::syscallhandler_pwritev2,
+            fd,
+            iov_ptr,
+            iov_count,
+            offset,
+            flags,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn writev_helper(
+        &self,
+        ctx: &mut ThreadContext,
+        args: &SysCallArgs,
+        legacy_syscall_fn: LegacySyscallFn,
+        fd: libc::c_int,
+        iov_ptr: PluginPtr,
+        iov_count: usize,
+        offset: Option<libc::off_t>,
+        flags: libc::c_int,
+    ) -> SyscallResult {
+        let file = match Self::uio_file(ctx, fd)? {
+            Some(x) => x,
+            // if it's a legacy file, use the C syscall handler instead
+            None => {
+                return unsafe { legacy_syscall_fn(ctx.thread.csyscallhandler(), args) }.into();
+            }
+        };
+
+        // none of the rust file types support seeking
+        if offset.is_some() {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        if !file.inner_file().borrow().mode().contains(FileMode::WRITE) {
+            return Err(Errno::EBADF.into());
+        }
+
+        let iovs = read_iovecs(ctx.process.memory(), iov_ptr, iov_count)?;
+        let nowait = rw_flags_nowait(flags)?;
+
+        // linux doesn't call into the file if there's nothing to write
+        if iovecs_len(&iovs) == 0 {
+            return Ok(0.into());
+        }
+
+        self.write_helper(ctx, fd, file, &iovs, 0, nowait)
+    }
+
+    /// Get the file for a vectored io syscall. Returns `None` if it's a legacy file, which must be
+    /// handled by the C syscall handler.
+    fn uio_file(
+        ctx: &mut ThreadContext,
+        fd: libc::c_int,
+    ) -> Result<Option<OpenFile>, SyscallError> {
+        // if we were previously blocked, get the active file from the last syscall handler
+        // invocation since it may no longer exist in the descriptor table
+        let file = ctx
+            .thread
+            .syscall_condition()
+            // if this was for a C descriptor, then there won't be an active file object
+            .map(|x| x.active_file().cloned())
+            .flatten();
+
+        if let Some(file) = file {
+            // we were previously blocked, so re-use the file from the previous syscall invocation
+            return Ok(Some(file));
+        }
+
+        // get the file from the descriptor table, or return early if it doesn't exist
+        match Self::get_descriptor(ctx.process, fd)?.file() {
+            CompatFile::New(file) => Ok(Some(file.clone())),
+            CompatFile::Legacy(_) => Ok(None),
+        }
+    }
+}
+
+/// Validate the `preadv2()`/`pwritev2()` flags. Returns true if `RWF_NOWAIT` was set.
+fn rw_flags_nowait(flags: libc::c_int) -> Result<bool, Errno> {
+    let supported = RWF_HIPRI | RWF_DSYNC | RWF_SYNC | RWF_NOWAIT | RWF_APPEND;
+    if flags & !supported != 0 {
+        debug!("Unsupported read/write flags: {:#x}", flags);
+        return Err(Errno::EOPNOTSUPP);
+    }
+
+    // the other flags have no effect on the rust file types
+    Ok(flags & RWF_NOWAIT != 0)
+}
@@ -6,12 +6,16 @@ use crate::host::descriptor::{
     CompatFile, Descriptor, DescriptorFlags, File, FileMode, FileState, FileStatus, OpenFile,
 };
 use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall::io::{IoVec, IoVecReader, IoVecWriter};
 use crate::host::syscall::Trigger;
 use crate::host::syscall_condition::SysCallCondition;
 use crate::host::syscall_types::{Blocked, PluginPtr, SysCallArgs, TypedPluginPtr};
 use crate::host::syscall_types::{SyscallError, SyscallResult};
 use crate::utility::event_queue::EventQueue;
 
+use std::ffi::{CStr, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
@@ -53,7 +57,10 @@ impl SyscallHandler {
 
         // duplicate the descriptor
         let new_desc = desc.dup(DescriptorFlags::empty());
-        let new_fd = ctx.process.register_descriptor(new_desc);
+        let new_fd = ctx
+            .process
+            .register_descriptor(new_desc)
+            .map_err(|_| nix::errno::Errno::EMFILE)?;
 
         // return the new fd
         Ok(libc::c_int::try_from(new_fd).unwrap().into())
@@ -75,6 +82,11 @@ impl SyscallHandler {
 
         let new_fd: u32 = new_fd.try_into().map_err(|_| nix::errno::Errno::EBADF)?;
 
+        // linux doesn't allow a descriptor at or above the fd limit
+        if u64::from(new_fd) >= ctx.process.fd_limit().rlim_cur {
+            return Err(nix::errno::Errno::EBADF.into());
+        }
+
         // duplicate the descriptor
         let new_desc = desc.dup(DescriptorFlags::empty());
         let replaced_desc = ctx.process.register_descriptor_with_fd(new_desc, new_fd);
@@ -109,6 +121,11 @@ impl SyscallHandler {
 
         let new_fd: u32 = new_fd.try_into().map_err(|_| nix::errno::Errno::EBADF)?;
 
+        // linux doesn't allow a descriptor at or above the fd limit
+        if u64::from(new_fd) >= ctx.process.fd_limit().rlim_cur {
+            return Err(nix::errno::Errno::EBADF.into());
+        }
+
         // dup3 only supports the O_CLOEXEC flag
         let flags = match flags {
             libc::O_CLOEXEC => DescriptorFlags::CLOEXEC,
@@ -169,7 +186,11 @@ impl SyscallHandler {
             },
         };
 
-        self.read_helper(ctx, fd, file, buf_ptr, buf_size, offset)
+        let iov = IoVec {
+            base: buf_ptr,
+            len: buf_size,
+        };
+        self.read_helper(ctx, fd, file, &[iov], offset, false)
     }
 
     #[log_syscall(/* rv */ libc::ssize_t, /* fd */ libc::c_int, /* buf */ *const libc::c_void,
@@ -208,51 +229,57 @@ impl SyscallHandler {
             },
         };
 
-        self.read_helper(ctx, fd, file, buf_ptr, buf_size, offset)
+        let iov = IoVec {
+            base: buf_ptr,
+            len: buf_size,
+        };
+        self.read_helper(ctx, fd, file, &[iov], offset, false)
     }
 
-    fn read_helper(
+    /// Read into the io vectors from the file. If `nowait` is true, the syscall will not block
+    /// even if the file is a blocking file.
+    pub fn read_helper(
         &self,
         ctx: &mut ThreadContext,
         _fd: libc::c_int,
         open_file: OpenFile,
-        buf_ptr: PluginPtr,
-        buf_size: libc::size_t,
+        iovs: &[IoVec],
         offset: libc::off_t,
+        nowait: bool,
     ) -> SyscallResult {
         let generic_file = open_file.inner_file();
 
-        // if it's a socket, call recvfrom() instead
+        // if it's a socket, call recvmsg() instead
         if let File::Socket(..) = generic_file {
             if offset != 0 {
                 // sockets don't support offsets
                 return Err(Errno::ESPIPE.into());
             }
-            return self.recvfrom_helper(
-                ctx,
-                open_file,
-                buf_ptr,
-                buf_size,
-                0,
-                PluginPtr::null(),
-                PluginPtr::null(),
-            );
+            let flags = if nowait { libc::MSG_DONTWAIT } else { 0 };
+            return self.recv_iovecs_helper(ctx, open_file, iovs, flags);
         }
 
         let file_status = generic_file.borrow().get_status();
 
-        let result =
+        let result = if let File::SignalFd(signalfd) = generic_file {
+            // reading a signalfd needs access to the thread's pending signals
+            self.signalfd_read_helper(ctx, signalfd, iovs, offset)
+        } else {
             // call the file's read(), and run any resulting events
             EventQueue::queue_and_run(|event_queue| {
                 generic_file.borrow_mut().read(
-                    ctx.process.memory_mut().writer(TypedPluginPtr::new::<u8>(buf_ptr, buf_size)),
+                    IoVecWriter::new(iovs, ctx.process.memory_mut()),
                     offset,
                     event_queue,
                 )
-            });
+            })
+        };
 
         // if the syscall would block and it's a blocking descriptor
-        if result == Err(Errno::EWOULDBLOCK.into()) && !file_status.contains(FileStatus::NONBLOCK) {
+        if result == Err(Errno::EWOULDBLOCK.into())
+            && !file_status.contains(FileStatus::NONBLOCK)
+            && !nowait
+        {
             let trigger = Trigger::from_file(open_file.inner_file().clone(), FileState::READABLE);
             let mut cond = SysCallCondition::new(trigger);
             let supports_sa_restart = generic_file.borrow().supports_sa_restart();
@@ -303,7 +330,11 @@ impl SyscallHandler {
             },
         };
 
-        self.write_helper(ctx, fd, file, buf_ptr, buf_size, offset)
+        let iov = IoVec {
+            base: buf_ptr,
+            len: buf_size,
+        };
+        self.write_helper(ctx, fd, file, &[iov], offset, false)
     }
 
     #[log_syscall(/* rv */ libc::ssize_t, /* fd */ libc::c_int, /* buf */ *const libc::c_char,
@@ -342,27 +373,34 @@ impl SyscallHandler {
             },
         };
 
-        self.write_helper(ctx, fd, file, buf_ptr, buf_size, offset)
+        let iov = IoVec {
+            base: buf_ptr,
+            len: buf_size,
+        };
+        self.write_helper(ctx, fd, file, &[iov], offset, false)
     }
 
-    fn write_helper(
+    /// Write the data in the io vectors to the file. If `nowait` is true, the syscall will not
+    /// block even if the file is a blocking file.
+    pub fn write_helper(
         &self,
         ctx: &mut ThreadContext,
         _fd: libc::c_int,
         open_file: OpenFile,
-        buf_ptr: PluginPtr,
-        buf_size: libc::size_t,
+        iovs: &[IoVec],
         offset: libc::off_t,
+        nowait: bool,
     ) -> SyscallResult {
         let generic_file = open_file.inner_file();
 
-        // if it's a socket, call recvfrom() instead
+        // if it's a socket, call sendmsg() instead
         if let File::Socket(..) = generic_file {
             if offset != 0 {
                 // sockets don't support offsets
                 return Err(Errno::ESPIPE.into());
             }
-            return self.sendto_helper(ctx, open_file, buf_ptr, buf_size, 0, PluginPtr::null(), 0);
+            let flags = if nowait { libc::MSG_DONTWAIT } else { 0 };
+            return self.send_iovecs_helper(ctx, open_file, iovs, flags);
         }
 
         let file_status = generic_file.borrow().get_status();
@@ -371,14 +409,22 @@ impl SyscallHandler {
             // call the file's write(), and run any resulting events
             EventQueue::queue_and_run(|event_queue| {
                 generic_file.borrow_mut().write(
-                    ctx.process.memory().reader(TypedPluginPtr::new::<u8>(buf_ptr, buf_size)),
+                    IoVecReader::new(iovs, ctx.process.memory()),
                     offset,
                     event_queue,
                 )
             });
 
+        // writing to a pipe with no readers raises SIGPIPE
+        if result == Err(Errno::EPIPE.into()) {
+            Self::raise_sigpipe(ctx);
+        }
+
         // if the syscall would block and it's a blocking descriptor
-        if result == Err(Errno::EWOULDBLOCK.into()) && !file_status.contains(FileStatus::NONBLOCK) {
+        if result == Err(Errno::EWOULDBLOCK.into())
+            && !file_status.contains(FileStatus::NONBLOCK)
+            && !nowait
+        {
             let trigger = Trigger::from_file(open_file.inner_file().clone(), FileState::WRITABLE);
             let mut cond = SysCallCondition::new(trigger);
             let supports_sa_restart = generic_file.borrow().supports_sa_restart();
@@ -468,8 +514,23 @@ impl SyscallHandler {
         writer_desc.set_flags(descriptor_flags);
 
         // register the file descriptors
-        let read_fd = ctx.process.register_descriptor(reader_desc);
-        let write_fd = ctx.process.register_descriptor(writer_desc);
+        let read_fd = ctx
+            .process
+            .register_descriptor(reader_desc)
+            .map_err(|_| nix::errno::Errno::EMFILE)?;
+        let write_fd = match ctx.process.register_descriptor(writer_desc) {
+            Ok(fd) => fd,
+            Err(_) => {
+                // ignore any errors when closing
+                EventQueue::queue_and_run(|event_queue| {
+                    ctx.process
+                        .deregister_descriptor(read_fd)
+                        .unwrap()
+                        .close(ctx.host.chost(), event_queue)
+                });
+                return Err(nix::errno::Errno::EMFILE.into());
+            }
+        };
 
         // try to write them to the caller
         let fds = [
@@ -500,4 +561,97 @@ impl SyscallHandler {
             }
         }
     }
+
+    #[log_syscall(/* rv */ libc::c_int, /* pathname */ *const libc::c_char)]
+    pub fn unlink(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let path_ptr = PluginPtr::from(args.get(0));
+
+        let mut path_buf = [0u8; libc::PATH_MAX as usize];
+        let path = Self::read_path(ctx, path_ptr, &mut path_buf)?;
+
+        // if the path is bound to a unix socket, release the name
+        if Self::unlink_unix_socket_helper(ctx, path) {
+            return Ok(0.into());
+        }
+
+        // otherwise it's a regular file, so let linux handle it
+        Err(SyscallError::Native)
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* dirfd */ libc::c_int,
+                  /* pathname */ *const libc::c_char, /* flags */ libc::c_int)]
+    pub fn unlinkat(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let dir_fd = libc::c_int::from(args.get(0));
+        let path_ptr = PluginPtr::from(args.get(1));
+        let flags = libc::c_int::from(args.get(2));
+
+        if flags & libc::AT_REMOVEDIR == 0 {
+            let mut path_buf = [0u8; libc::PATH_MAX as usize];
+            let path = Self::read_path(ctx, path_ptr, &mut path_buf)?;
+
+            // absolute paths ignore the dirfd
+            let path = if dir_fd == libc::AT_FDCWD || path.is_absolute() {
+                Some(path.to_path_buf())
+            } else {
+                // if the dirfd isn't a valid directory, the C handler will return the error
+                Self::directory_path(ctx, dir_fd).map(|dir| dir.join(path))
+            };
+
+            if let Some(path) = path {
+                if Self::unlink_unix_socket_helper(ctx, &path) {
+                    return Ok(0.into());
+                }
+            }
+        }
+
+        unsafe {
+            c::syscallhandler_unlinkat(ctx.thread.csyscallhandler(), args as *const SysCallArgs)
+        }
+        .into()
+    }
+
+    /// Copy a path string from the plugin.
+    fn read_path<'a>(
+        ctx: &mut ThreadContext,
+        path_ptr: PluginPtr,
+        path_buf: &'a mut [u8],
+    ) -> Result<&'a Path, SyscallError> {
+        let len = path_buf.len();
+        let path = ctx
+            .process
+            .memory()
+            .copy_str_from_ptr(path_buf, TypedPluginPtr::new::<u8>(path_ptr, len))?;
+        Ok(Path::new(OsStr::from_bytes(path.to_bytes())))
+    }
+
+    /// The path that the directory `dir_fd` had when it was opened, or `None` if `dir_fd` is not
+    /// an open directory.
+    fn directory_path(ctx: &mut ThreadContext, dir_fd: libc::c_int) -> Option<PathBuf> {
+        let path =
+            unsafe { c::syscallhandler_getDirectoryPath(ctx.thread.csyscallhandler(), dir_fd) };
+        if path.is_null() {
+            return None;
+        }
+
+        let path = unsafe { CStr::from_ptr(path) };
+        // the socket namespace uses canonical paths
+        let path = std::fs::canonicalize(OsStr::from_bytes(path.to_bytes())).ok()?;
+
+        // linux returns ENOTDIR for non-directories, which the C handler will take care of
+        if path.is_dir() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Releases the name of a unix socket bound to the path. Returns `false` if no unix socket is
+    /// bound to the path.
+    fn unlink_unix_socket_helper(ctx: &mut ThreadContext, path: &Path) -> bool {
+        ctx.host
+            .pathname_unix_namespace()
+            .borrow_mut()
+            .unlink(path)
+            .is_ok()
+    }
 }
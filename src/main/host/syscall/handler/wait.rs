@@ -0,0 +1,260 @@
+use crate::cshadow as c;
+use crate::host::context::ThreadContext;
+use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall::Trigger;
+use crate::host::syscall_condition::SysCallCondition;
+use crate::host::syscall_types::{Blocked, SysCallArgs, TypedPluginPtr};
+use crate::host::syscall_types::{SyscallError, SyscallResult};
+use crate::utility::pod::Pod;
+
+use log::*;
+use nix::errno::Errno;
+
+use syscall_logger::log_syscall;
+
+/// The layout of a `siginfo_t` for `SIGCHLD`, whose fields aren't exposed by
+/// `libc::siginfo_t`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default)]
+struct SigchldInfo {
+    si_signo: libc::c_int,
+    si_errno: libc::c_int,
+    si_code: libc::c_int,
+    _pad0: libc::c_int,
+    si_pid: libc::pid_t,
+    si_uid: libc::uid_t,
+    si_status: libc::c_int,
+    _pad1: libc::c_int,
+    si_utime: libc::clock_t,
+    si_stime: libc::clock_t,
+    _pad2: [u64; 10],
+}
+
+const _: () = assert!(std::mem::size_of::<SigchldInfo>() == std::mem::size_of::<libc::siginfo_t>());
+
+unsafe impl Pod for SigchldInfo {}
+
+impl SigchldInfo {
+    /// The info for a child that exited with the given `wait` status.
+    fn for_exited_child(pid: libc::pid_t, wstatus: libc::c_int) -> Self {
+        let (si_code, si_status) = if libc::WIFSIGNALED(wstatus) {
+            let code = if libc::WCOREDUMP(wstatus) {
+                libc::CLD_DUMPED
+            } else {
+                libc::CLD_KILLED
+            };
+            (code, libc::WTERMSIG(wstatus))
+        } else {
+            (libc::CLD_EXITED, libc::WEXITSTATUS(wstatus))
+        };
+
+        Self {
+            si_signo: libc::SIGCHLD,
+            si_code,
+            si_pid: pid,
+            si_uid: 0,
+            si_status,
+            ..Default::default()
+        }
+    }
+}
+
+/// The children that a `wait` call is waiting for.
+enum WaitTarget {
+    Any,
+    Pid(libc::pid_t),
+}
+
+impl WaitTarget {
+    fn as_raw(&self) -> libc::pid_t {
+        match self {
+            Self::Any => -1,
+            Self::Pid(pid) => *pid,
+        }
+    }
+}
+
+impl SyscallHandler {
+    #[log_syscall(/* rv */ libc::pid_t, /* pid */ libc::pid_t, /* wstatus */ *const libc::c_void,
+                  /* options */ libc::c_int, /* rusage */ *const libc::c_void)]
+    pub fn wait4(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let pid = libc::pid_t::from(args.get(0));
+        let wstatus_ptr = TypedPluginPtr::new::<libc::c_int>(args.get(1).into(), 1);
+        let options = libc::c_int::from(args.get(2));
+        let rusage_ptr = TypedPluginPtr::new::<libc::rusage>(args.get(3).into(), 1);
+
+        let supported_options = libc::WNOHANG
+            | libc::WUNTRACED
+            | libc::WCONTINUED
+            | libc::__WALL
+            | libc::__WCLONE
+            | libc::__WNOTHREAD;
+        if options & !supported_options != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        // From wait(2): "wait4() [...] interprets pid the same as waitpid".
+        let target = match pid {
+            libc::INT_MIN => return Err(Errno::ESRCH.into()),
+            -1 | 0 => WaitTarget::Any,
+            pid if pid < -1 => Self::process_group_target(ctx, -pid)?,
+            pid => WaitTarget::Pid(pid),
+        };
+
+        let child = match Self::get_waitable_child(ctx, &target, options & libc::WNOHANG != 0)? {
+            Some(child) => child,
+            None => return Ok(0.into()),
+        };
+
+        let child_pid = unsafe { c::process_getProcessID(child) } as libc::pid_t;
+        let wstatus = unsafe { c::process_getWaitStatus(child) };
+
+        if !wstatus_ptr.is_null() {
+            ctx.process
+                .memory_mut()
+                .copy_to_ptr(wstatus_ptr, &[wstatus])?;
+        }
+
+        // We don't track the resource usage of children.
+        if !rusage_ptr.is_null() {
+            let rusage: libc::rusage = unsafe { std::mem::zeroed() };
+            ctx.process
+                .memory_mut()
+                .copy_to_ptr(rusage_ptr, &[rusage])?;
+        }
+
+        unsafe { c::process_reap(child) };
+
+        Ok(child_pid.into())
+    }
+
+    #[log_syscall(/* rv */ libc::c_int, /* idtype */ libc::c_int, /* id */ libc::pid_t,
+                  /* infop */ *const libc::c_void, /* options */ libc::c_int,
+                  /* rusage */ *const libc::c_void)]
+    pub fn waitid(&self, ctx: &mut ThreadContext, args: &SysCallArgs) -> SyscallResult {
+        let idtype = libc::c_int::from(args.get(0)) as libc::idtype_t;
+        let id = libc::pid_t::from(args.get(1));
+        let infop_ptr = TypedPluginPtr::new::<SigchldInfo>(args.get(2).into(), 1);
+        let options = libc::c_int::from(args.get(3));
+        let rusage_ptr = TypedPluginPtr::new::<libc::rusage>(args.get(4).into(), 1);
+
+        let supported_options = libc::WNOHANG
+            | libc::WEXITED
+            | libc::WSTOPPED
+            | libc::WCONTINUED
+            | libc::WNOWAIT
+            | libc::__WALL
+            | libc::__WCLONE
+            | libc::__WNOTHREAD;
+        if options & !supported_options != 0
+            || options & (libc::WEXITED | libc::WSTOPPED | libc::WCONTINUED) == 0
+        {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let target = match idtype {
+            libc::P_ALL => WaitTarget::Any,
+            libc::P_PID if id > 0 => WaitTarget::Pid(id),
+            libc::P_PGID if id == 0 => WaitTarget::Any,
+            libc::P_PGID if id > 0 => Self::process_group_target(ctx, id)?,
+            libc::P_PID | libc::P_PGID => return Err(Errno::EINVAL.into()),
+            _ => {
+                warn!("Unsupported waitid idtype {}", idtype);
+                return Err(Errno::EINVAL.into());
+            }
+        };
+
+        // We only track children exiting; not stopping or continuing.
+        if options & libc::WEXITED == 0 {
+            warn!("waitid without WEXITED is unsupported");
+            return Err(Errno::ENOTSUP.into());
+        }
+
+        let child = Self::get_waitable_child(ctx, &target, options & libc::WNOHANG != 0)?;
+
+        let info = match child {
+            Some(child) => {
+                let child_pid = unsafe { c::process_getProcessID(child) } as libc::pid_t;
+                let wstatus = unsafe { c::process_getWaitStatus(child) };
+                SigchldInfo::for_exited_child(child_pid, wstatus)
+            }
+            // From waitid(2): "If WNOHANG was specified in options and there were no children
+            // in a waitable state, then waitid() returns 0 immediately and the state of the
+            // siginfo_t structure pointed to by infop depends on the implementation." Linux
+            // zeroes it.
+            None => SigchldInfo::default(),
+        };
+
+        if !infop_ptr.is_null() {
+            ctx.process.memory_mut().copy_to_ptr(infop_ptr, &[info])?;
+        }
+
+        // We don't track the resource usage of children.
+        if !rusage_ptr.is_null() {
+            let rusage: libc::rusage = unsafe { std::mem::zeroed() };
+            ctx.process
+                .memory_mut()
+                .copy_to_ptr(rusage_ptr, &[rusage])?;
+        }
+
+        if let Some(child) = child {
+            if options & libc::WNOWAIT == 0 {
+                unsafe { c::process_reap(child) };
+            }
+        }
+
+        Ok(0.into())
+    }
+
+    /// The target for waiting on the children in process group `pgid`. In Shadow, a forked
+    /// child is always in its parent's process group, and only processes started by Shadow lead
+    /// their own groups, so either all of the caller's children are in the group or none are.
+    fn process_group_target(
+        ctx: &mut ThreadContext,
+        pgid: libc::pid_t,
+    ) -> Result<WaitTarget, SyscallError> {
+        let caller_pgid =
+            unsafe { c::process_getProcessGroupID(ctx.process.raw_mut()) } as libc::pid_t;
+        if pgid != caller_pgid {
+            return Err(Errno::ECHILD.into());
+        }
+        Ok(WaitTarget::Any)
+    }
+
+    /// Get a child of the calling process matching `target` that has exited and hasn't been
+    /// reaped yet. If there are matching children but none have exited, returns `None` if
+    /// `nohang` is set, or blocks until one of them exits. Returns ECHILD if there are no
+    /// matching children.
+    fn get_waitable_child(
+        ctx: &mut ThreadContext,
+        target: &WaitTarget,
+        nohang: bool,
+    ) -> Result<Option<*mut c::Process>, SyscallError> {
+        let parent_pid = u32::from(ctx.process.id()) as libc::pid_t;
+
+        let child =
+            unsafe { c::host_getChildProcess(ctx.host.chost(), parent_pid, target.as_raw(), true) };
+        if !child.is_null() {
+            return Ok(Some(child));
+        }
+
+        let any_child = unsafe {
+            c::host_getChildProcess(ctx.host.chost(), parent_pid, target.as_raw(), false)
+        };
+        if any_child.is_null() {
+            return Err(Errno::ECHILD.into());
+        }
+
+        if nohang {
+            return Ok(None);
+        }
+
+        // Wait for a child to change state. From signal(7), `wait` is restarted after a
+        // signal handler if the handler was installed with SA_RESTART.
+        let trigger = Trigger::from_child_event(ctx.process.raw_mut());
+        Err(SyscallError::Blocked(Blocked {
+            condition: SysCallCondition::new(trigger),
+            restartable: true,
+        }))
+    }
+}
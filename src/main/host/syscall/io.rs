@@ -0,0 +1,258 @@
+//! Helpers for syscalls that operate on io vectors (`struct iovec`) in plugin memory.
+
+use nix::errno::Errno;
+
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall_types::{PluginPtr, TypedPluginPtr};
+use crate::utility::pod;
+
+/// The maximum number of io vectors that can be passed to a syscall. Linux defines this as
+/// `UIO_MAXIOV`.
+pub const IOV_MAX: usize = 1024;
+
+/// A buffer in plugin memory. Equivalent to a `libc::iovec` that points to plugin memory.
+#[derive(Copy, Clone, Debug)]
+pub struct IoVec {
+    pub base: PluginPtr,
+    pub len: usize,
+}
+
+impl From<IoVec> for TypedPluginPtr<u8> {
+    fn from(iov: IoVec) -> Self {
+        TypedPluginPtr::new::<u8>(iov.base, iov.len)
+    }
+}
+
+impl From<libc::iovec> for IoVec {
+    fn from(iov: libc::iovec) -> Self {
+        IoVec {
+            base: PluginPtr::from(iov.iov_base as usize),
+            len: iov.iov_len,
+        }
+    }
+}
+
+/// Read an array of `libc::iovec` from plugin memory. Returns EINVAL if there are more than
+/// [`IOV_MAX`] io vectors or if the total length would overflow an `ssize_t`.
+pub fn read_iovecs(
+    mem: &MemoryManager,
+    iov_ptr: PluginPtr,
+    count: usize,
+) -> Result<Vec<IoVec>, Errno> {
+    if count > IOV_MAX {
+        return Err(Errno::EINVAL);
+    }
+
+    let mut iovs = vec![pod::zeroed::<libc::iovec>(); count];
+
+    mem.copy_from_ptr(
+        &mut iovs,
+        TypedPluginPtr::new::<libc::iovec>(iov_ptr, count),
+    )?;
+
+    let iovs: Vec<IoVec> = iovs.into_iter().map(IoVec::from).collect();
+
+    // linux returns EINVAL if the total length would overflow an ssize_t
+    let mut total_len: usize = 0;
+    for iov in &iovs {
+        total_len = total_len.checked_add(iov.len).ok_or(Errno::EINVAL)?;
+        if isize::try_from(total_len).is_err() {
+            return Err(Errno::EINVAL);
+        }
+    }
+
+    Ok(iovs)
+}
+
+/// The total number of bytes referenced by the io vectors.
+pub fn iovecs_len(iovs: &[IoVec]) -> usize {
+    iovs.iter().map(|x| x.len).sum()
+}
+
+/// The io vectors that remain after skipping the first `offset` bytes.
+pub fn iovecs_skip(iovs: &[IoVec], mut offset: usize) -> Vec<IoVec> {
+    let mut remaining = Vec::with_capacity(iovs.len());
+
+    for iov in iovs {
+        if offset == 0 {
+            remaining.push(*iov);
+        } else if offset >= iov.len {
+            offset -= iov.len;
+        } else {
+            remaining.push(IoVec {
+                base: TypedPluginPtr::<u8>::from(*iov).slice(offset..).ptr(),
+                len: iov.len - offset,
+            });
+            offset = 0;
+        }
+    }
+
+    remaining
+}
+
+/// Find the io vector containing the byte at `offset`, and the offset within that io vector.
+fn iovec_at_offset(iovs: &[IoVec], mut offset: usize) -> Option<(IoVec, usize)> {
+    for iov in iovs {
+        if offset < iov.len {
+            return Some((*iov, offset));
+        }
+        offset -= iov.len;
+    }
+
+    None
+}
+
+/// Shared implementation of seek for both IoVecReader and IoVecWriter.
+fn seek_helper(offset: &mut usize, len: usize, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+    use std::io::SeekFrom;
+    let new_offset = match pos {
+        SeekFrom::Current(x) => *offset as i64 + x,
+        SeekFrom::End(x) => len as i64 + x,
+        SeekFrom::Start(x) => x as i64,
+    };
+    // Seeking before the beginning is an error (but seeking to or past the
+    // end isn't).
+    if new_offset < 0 {
+        return Err(std::io::Error::from_raw_os_error(Errno::EFAULT as i32));
+    }
+    *offset = new_offset as usize;
+    Ok(new_offset as u64)
+}
+
+/// An object implementing `std::io::Read` and `std::io::Seek` for a list of io vectors in plugin
+/// memory. The io vectors are read in order as if they were a single contiguous buffer.
+pub struct IoVecReader<'a> {
+    iovs: &'a [IoVec],
+    mem: &'a MemoryManager,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> IoVecReader<'a> {
+    pub fn new(iovs: &'a [IoVec], mem: &'a MemoryManager) -> Self {
+        Self {
+            iovs,
+            mem,
+            offset: 0,
+            len: iovecs_len(iovs),
+        }
+    }
+}
+
+impl<'a> std::io::Read for IoVecReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let (iov, iov_offset) = match iovec_at_offset(self.iovs, self.offset) {
+            Some(x) => x,
+            // at or past the end
+            None => return Ok(0),
+        };
+
+        let ptr = TypedPluginPtr::from(iov).slice(iov_offset..);
+        let toread = std::cmp::min(buf.len(), ptr.len());
+        if toread == 0 {
+            return Ok(0);
+        }
+
+        self.mem
+            .copy_from_ptr(&mut buf[..toread], ptr.slice(..toread))
+            .map_err(|e| std::io::Error::from_raw_os_error(e as i32))?;
+        self.offset += toread;
+        Ok(toread)
+    }
+}
+
+impl<'a> std::io::Seek for IoVecReader<'a> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        seek_helper(&mut self.offset, self.len, pos)
+    }
+}
+
+/// An object implementing `std::io::Write` and `std::io::Seek` for a list of io vectors in plugin
+/// memory. The io vectors are written in order as if they were a single contiguous buffer.
+pub struct IoVecWriter<'a> {
+    iovs: &'a [IoVec],
+    mem: &'a mut MemoryManager,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> IoVecWriter<'a> {
+    pub fn new(iovs: &'a [IoVec], mem: &'a mut MemoryManager) -> Self {
+        Self {
+            iovs,
+            mem,
+            offset: 0,
+            len: iovecs_len(iovs),
+        }
+    }
+}
+
+impl<'a> std::io::Write for IoVecWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let (iov, iov_offset) = match iovec_at_offset(self.iovs, self.offset) {
+            Some(x) => x,
+            // at or past the end
+            None => return Ok(0),
+        };
+
+        let ptr = TypedPluginPtr::from(iov).slice(iov_offset..);
+        let towrite = std::cmp::min(buf.len(), ptr.len());
+        if towrite == 0 {
+            return Ok(0);
+        }
+
+        self.mem
+            .copy_to_ptr(ptr.slice(..towrite), &buf[..towrite])
+            .map_err(|e| std::io::Error::from_raw_os_error(e as i32))?;
+        self.offset += towrite;
+        Ok(towrite)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> std::io::Seek for IoVecWriter<'a> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        seek_helper(&mut self.offset, self.len, pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iov(base: usize, len: usize) -> IoVec {
+        IoVec {
+            base: PluginPtr::from(base),
+            len,
+        }
+    }
+
+    #[test]
+    fn test_iovec_at_offset() {
+        let iovs = [iov(100, 10), iov(200, 0), iov(300, 5)];
+
+        let check = |offset, expected: Option<(usize, usize)>| {
+            let rv = iovec_at_offset(&iovs, offset).map(|(iov, off)| (usize::from(iov.base), off));
+            assert_eq!(rv, expected);
+        };
+
+        check(0, Some((100, 0)));
+        check(9, Some((100, 9)));
+        // the empty io vector should be skipped
+        check(10, Some((300, 0)));
+        check(14, Some((300, 4)));
+        check(15, None);
+        check(100, None);
+
+        assert_eq!(iovec_at_offset(&[], 0).map(|(_, x)| x), None);
+    }
+
+    #[test]
+    fn test_iovecs_len() {
+        assert_eq!(iovecs_len(&[]), 0);
+        assert_eq!(iovecs_len(&[iov(100, 10), iov(200, 0), iov(300, 5)]), 15);
+    }
+}
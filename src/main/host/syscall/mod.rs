@@ -3,6 +3,7 @@ use crate::host::descriptor::{File, FileState};
 
 pub mod format;
 pub mod handler;
+pub mod io;
 
 // The helpers defined here are syscall-related but not handler-specific.
 
@@ -30,6 +31,28 @@ impl Trigger {
             status: status.into(),
         })
     }
+
+    /// A trigger that fires when the legacy C file reaches the given state.
+    pub fn from_legacy_file(file: *mut c::LegacyFile, status: FileState) -> Self {
+        Self(c::Trigger {
+            type_: c::_TriggerType_TRIGGER_DESCRIPTOR,
+            object: c::TriggerObject {
+                as_legacy_file: file,
+            },
+            status: status.into(),
+        })
+    }
+
+    /// A trigger that fires whenever a child of `process` changes state.
+    pub fn from_child_event(process: *mut c::Process) -> Self {
+        Self(c::Trigger {
+            type_: c::_TriggerType_TRIGGER_CHILD,
+            object: c::TriggerObject {
+                as_process: process,
+            },
+            status: FileState::CHILD_EVENT.into(),
+        })
+    }
 }
 
 impl c::SysCallReturn {
@@ -6,9 +6,12 @@
 #include "main/host/syscall/process.h"
 
 #include <errno.h>
+#include <glib.h>
+#include <limits.h>
 #include <sys/prctl.h>
 
 #include "lib/logger/logger.h"
+#include "main/host/process.h"
 #include "main/host/syscall/protected.h"
 #include "main/host/thread.h"
 
@@ -31,6 +34,45 @@ static SysCallReturn _syscallhandler_prlimitHelper(SysCallHandler* sys, pid_t pi
     }
 }
 
+// The maximum length of each argument and environment string, as in linux.
+#define EXECVE_MAX_ARG_STRLEN (32 * 4096)
+
+// Reads the NULL-terminated array of strings at `ptr` into `strv`, which the
+// caller must free with `g_strfreev`. Returns 0 or a negative errno.
+static int _syscallhandler_readStrv(SysCallHandler* sys, PluginPtr ptr, gchar*** strv) {
+    GPtrArray* strs = g_ptr_array_new_with_free_func(g_free);
+    char* buf = g_malloc(EXECVE_MAX_ARG_STRLEN);
+    int rv = 0;
+
+    // A NULL array is treated as an empty one.
+    for (uint64_t i = 0; ptr.val; i++) {
+        PluginPtr strPtr;
+        rv = process_readPtr(
+            sys->process, &strPtr, (PluginPtr){.val = ptr.val + i * sizeof(strPtr)}, sizeof(strPtr));
+        if (rv < 0 || !strPtr.val) {
+            break;
+        }
+
+        ssize_t len = process_readString(sys->process, buf, strPtr, EXECVE_MAX_ARG_STRLEN);
+        if (len < 0) {
+            rv = (len == -ENAMETOOLONG) ? -E2BIG : len;
+            break;
+        }
+        g_ptr_array_add(strs, g_strdup(buf));
+    }
+    g_free(buf);
+
+    if (rv < 0) {
+        g_ptr_array_free(strs, TRUE);
+        return rv;
+    }
+
+    g_ptr_array_set_free_func(strs, NULL);
+    g_ptr_array_add(strs, NULL);
+    *strv = (gchar**)g_ptr_array_free(strs, FALSE);
+    return 0;
+}
+
 ///////////////////////////////////////////////////////////
 // System Calls
 ///////////////////////////////////////////////////////////
@@ -72,21 +114,39 @@ SysCallReturn syscallhandler_prlimit(SysCallHandler* sys, const SysCallArgs* arg
     return _syscallhandler_prlimitHelper(sys, pid, resource, newlim, oldlim);
 }
 
-SysCallReturn syscallhandler_prlimit64(SysCallHandler* sys, const SysCallArgs* args) {
+SysCallReturn syscallhandler_execve(SysCallHandler* sys, const SysCallArgs* args) {
     utility_assert(sys && args);
-    pid_t pid = args->args[0].as_i64;
-    int resource = args->args[1].as_i64;
-    PluginPtr newlim = args->args[2].as_ptr; // const struct rlimit*
-    PluginPtr oldlim = args->args[3].as_ptr; // const struct rlimit*
-    trace("prlimit called on pid %i for resource %i", pid, resource);
-    return _syscallhandler_prlimitHelper(sys, pid, resource, newlim, oldlim);
-}
+    PluginPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    PluginPtr argvPtr = args->args[1].as_ptr;     // char* const[]
+    PluginPtr envpPtr = args->args[2].as_ptr;     // char* const[]
+
+    char pathname[PATH_MAX];
+    ssize_t len = process_readString(sys->process, pathname, pathnamePtr, sizeof(pathname));
+    if (len < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = len};
+    }
 
-SysCallReturn syscallhandler_execve(SysCallHandler* sys, const SysCallArgs* args) {
-    // The MemoryManager's state is no longer valid after an exec.
-    // Destroy it, to be recreated on the next syscall.
-    process_setMemoryManager(sys->process, NULL);
+    gchar** argv = NULL;
+    int rv = _syscallhandler_readStrv(sys, argvPtr, &argv);
+    if (rv < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = rv};
+    }
+
+    gchar** envv = NULL;
+    rv = _syscallhandler_readStrv(sys, envpPtr, &envv);
+    if (rv < 0) {
+        g_strfreev(argv);
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = rv};
+    }
+
+    trace("execve called with pathname %s", pathname);
+
+    // We run the new program in a new native process rather than letting the
+    // plugin exec natively; see `thread_execve`.
+    rv = process_execve(sys->process, sys->thread, pathname, argv, envv);
+
+    g_strfreev(argv);
+    g_strfreev(envv);
 
-    // Have the plugin execute it natively.
-    return (SysCallReturn){.state = SYSCALL_NATIVE};
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = rv};
 }
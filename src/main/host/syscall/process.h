@@ -11,6 +11,5 @@
 SYSCALL_HANDLER(execve);
 SYSCALL_HANDLER(prctl);
 SYSCALL_HANDLER(prlimit);
-SYSCALL_HANDLER(prlimit64);
 
 #endif
@@ -7,7 +7,6 @@
 
 #include <errno.h>
 #include <sys/time.h>
-#include <sys/timerfd.h>
 #include <sys/types.h>
 #include <time.h>
 #include <unistd.h>
@@ -15,7 +14,6 @@
 #include "lib/logger/logger.h"
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/descriptor/tcp.h"
-#include "main/host/descriptor/timerfd.h"
 #include "main/host/syscall_condition.h"
 #include "main/host/thread.h"
 
@@ -8,6 +8,7 @@
 #include <errno.h>
 #include <signal.h>
 #include <stdbool.h>
+#include <string.h>
 #include <sys/syscall.h>
 
 #include "lib/logger/logger.h"
@@ -57,7 +58,8 @@ static SysCallReturn _syscallhandler_signalProcess(SysCallHandler* sys, Process*
     return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
 }
 
-static SysCallReturn _syscallhandler_signalThread(SysCallHandler* sys, Thread* thread, int sig) {
+static SysCallReturn _syscallhandler_signalThread(SysCallHandler* sys, Thread* thread, int sig,
+                                                   int si_code) {
     if (sig < 0 || sig > SHD_SIGRT_MAX) {
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EINVAL};
     }
@@ -80,63 +82,26 @@ static SysCallReturn _syscallhandler_signalThread(SysCallHandler* sys, Thread* t
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = res};
     }
 
-    Process* process = thread_getProcess(thread);
-    struct shd_kernel_sigaction action = shimshmem_getSignalAction(
-        host_getShimShmemLock(sys->host), process_getSharedMem(process), sig);
-    if (action.ksa_handler == SIG_IGN ||
-        (action.ksa_handler == SIG_DFL && shd_defaultAction(sig) == SHD_DEFAULT_ACTION_IGN)) {
-        // Don't deliver ignored an signal.
-        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
-    }
+    process_signalThread(thread_getProcess(thread), sys->thread, thread,
+                         &(siginfo_t){
+                             .si_signo = sig,
+                             .si_errno = 0,
+                             .si_code = si_code,
+                             .si_pid = process_getProcessID(sys->process),
+                             .si_uid = 0,
+                         });
 
-    shd_kernel_sigset_t pending_signals = shimshmem_getThreadPendingSignals(
-        host_getShimShmemLock(sys->host), thread_sharedMem(thread));
-
-    if (shd_sigismember(&pending_signals, sig)) {
-        // Signal is already pending. From signal(7):In the case where a standard signal is already
-        // pending, the siginfo_t structure (see sigaction(2)) associated with  that  signal is not
-        // overwritten on arrival of subsequent instances of the same signal.
-        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
-    }
-
-    shd_sigaddset(&pending_signals, sig);
-    shimshmem_setThreadPendingSignals(
-        host_getShimShmemLock(sys->host), thread_sharedMem(thread), pending_signals);
-    shimshmem_setThreadSiginfo(host_getShimShmemLock(sys->host), thread_sharedMem(thread), sig,
-                               &(siginfo_t){
-                                   .si_signo = sig,
-                                   .si_errno = 0,
-                                   .si_code = SI_TKILL,
-                                   .si_pid = process_getProcessID(sys->process),
-                                   .si_uid = 0,
-                               });
-
-    if (thread == sys->thread) {
-        // Target is the current thread. It'll be handled synchronously when the
-        // current syscall returns (if it's unblocked).
-        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
-    }
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
+}
 
-    shd_kernel_sigset_t blocked_signals =
-        shimshmem_getBlockedSignals(host_getShimShmemLock(sys->host), thread_sharedMem(thread));
-    if (shd_sigismember(&blocked_signals, sig)) {
-        // Target thread has the signal blocked. We'll leave it pending, but no
-        // need to schedule an event to process the signal. It'll get processed
-        // synchronously when the thread executes a syscall that would unblock
-        // the signal.
-        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
-    }
+void syscallhandler_raiseSigpipe(SysCallHandler* sys) {
+    utility_assert(sys);
 
-    SysCallCondition* cond = thread_getSysCallCondition(thread);
-    if (cond == NULL) {
-        // We may be able to get here if a thread is signalled before it runs
-        // for the first time. Just return; the signal will be delivered when
-        // the thread runs.
-        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
+    // Like Linux, the signal is directed at the thread that attempted the write.
+    SysCallReturn ret = _syscallhandler_signalThread(sys, sys->thread, SIGPIPE, SI_USER);
+    if (ret.retval.as_i64 != 0) {
+        warning("Unable to raise SIGPIPE: %s", strerror(-ret.retval.as_i64));
     }
-    syscallcondition_wakeupForSignal(cond, host_getShimShmemLock(sys->host), sig);
-
-    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 0};
 }
 
 ///////////////////////////////////////////////////////////
@@ -203,7 +168,7 @@ SysCallReturn syscallhandler_tgkill(SysCallHandler* sys, const SysCallArgs* args
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -ESRCH};
     }
 
-    return _syscallhandler_signalThread(sys, thread, sig);
+    return _syscallhandler_signalThread(sys, thread, sig, SI_TKILL);
 }
 
 SysCallReturn syscallhandler_tkill(SysCallHandler* sys, const SysCallArgs* args) {
@@ -218,7 +183,7 @@ SysCallReturn syscallhandler_tkill(SysCallHandler* sys, const SysCallArgs* args)
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -ESRCH};
     }
 
-    SysCallReturn ret = _syscallhandler_signalThread(sys, thread, sig);
+    SysCallReturn ret = _syscallhandler_signalThread(sys, thread, sig, SI_TKILL);
     return ret;
 }
 
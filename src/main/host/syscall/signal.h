@@ -15,4 +15,8 @@ SYSCALL_HANDLER(rt_sigaction);
 SYSCALL_HANDLER(rt_sigprocmask);
 SYSCALL_HANDLER(sigaltstack);
 
+/* Raise SIGPIPE on the calling thread, as done by Linux when writing to a pipe or socket that
+ * can no longer be written to (the write also fails with EPIPE). */
+void syscallhandler_raiseSigpipe(SysCallHandler* sys);
+
 #endif
@@ -13,17 +13,17 @@
 #include <sys/types.h>
 
 #include "lib/logger/logger.h"
+#include "main/bindings/c/bindings.h"
 #include "main/core/worker.h"
 #include "main/host/descriptor/compat_socket.h"
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/descriptor/socket.h"
 #include "main/host/descriptor/tcp.h"
-#include "main/host/descriptor/tcp_cong.h"
-#include "main/host/descriptor/tcp_cong_reno.h"
 #include "main/host/descriptor/udp.h"
 #include "main/host/host.h"
 #include "main/host/process.h"
 #include "main/host/syscall/protected.h"
+#include "main/host/syscall/signal.h"
 #include "main/host/syscall_condition.h"
 #include "main/host/syscall_handler.h"
 #include "main/host/thread.h"
@@ -193,8 +193,8 @@ static SysCallReturn _syscallhandler_acceptHelper(SysCallHandler* sys,
     /* OK, now we can check if we have anything to accept. */
     struct sockaddr_in inet_addr = {.sin_family = AF_INET};
     int accepted_fd = 0;
-    errcode = tcp_acceptServerPeer(
-        tcp_desc, sys->host, &inet_addr.sin_addr.s_addr, &inet_addr.sin_port, &accepted_fd);
+    errcode = tcp_acceptServerPeer(tcp_desc, sys->host, sys->process, &inet_addr.sin_addr.s_addr,
+                                   &inet_addr.sin_port, &accepted_fd);
 
     LegacyFile* legacyDesc = (LegacyFile*)tcp_desc;
     if (errcode == -EWOULDBLOCK && !(legacyfile_getFlags(legacyDesc) & O_NONBLOCK)) {
@@ -326,11 +326,7 @@ static int _syscallhandler_getTCPOptHelper(SysCallHandler* sys, TCP* tcp, int op
             }
 
             char* dest_str = optval;
-            const char* src_str = tcp_cong(tcp)->hooks->tcp_cong_name_str();
-
-            if (src_str == NULL) {
-                panic("Shadow's congestion type has no name!");
-            }
+            const char* src_str = tcpcongestion_algorithmName(tcp_getCongestionAlgorithm(tcp));
 
             // the len value returned by linux seems to be independent from the actual string length
             *optlen = MIN(*optlen, CONG_NAME_MAX);
@@ -422,7 +418,9 @@ static int _syscallhandler_setTCPOptHelper(SysCallHandler* sys, TCP* tcp, int op
             // the value of TCP_CA_NAME_MAX in linux
             const int CONG_NAME_MAX = 16;
 
-            char name[CONG_NAME_MAX];
+            // leave room for a nul byte since the name may not be nul-terminated
+            char name[CONG_NAME_MAX + 1];
+            memset(name, 0, sizeof(name));
             optlen = MIN(optlen, CONG_NAME_MAX);
 
             int errcode = process_readPtr(sys->process, name, optvalPtr, optlen);
@@ -430,15 +428,15 @@ static int _syscallhandler_setTCPOptHelper(SysCallHandler* sys, TCP* tcp, int op
                 return errcode;
             }
 
-            if (optlen < strlen(TCP_CONG_RENO_NAME) ||
-                strncmp(name, TCP_CONG_RENO_NAME, optlen) != 0) {
-                warning("Shadow sockets only support '%s' for TCP_CONGESTION", TCP_CONG_RENO_NAME);
+            TcpCongestionAlgorithm algorithm;
+            if (!tcpcongestion_algorithmFromName(name, &algorithm)) {
+                warning("Shadow sockets only support 'reno', 'cubic', and 'bbr' for "
+                        "TCP_CONGESTION, not '%s'",
+                        name);
                 return -ENOENT;
             }
 
-            // shadow doesn't support other congestion types, so do nothing
-            const char* current_name = tcp_cong(tcp)->hooks->tcp_cong_name_str();
-            utility_assert(current_name != NULL && strcmp(current_name, TCP_CONG_RENO_NAME) == 0);
+            tcp_setCongestionAlgorithm(tcp, algorithm);
             return 0;
         }
         default: {
@@ -560,7 +558,9 @@ SysCallReturn _syscallhandler_recvfromHelper(SysCallHandler* sys, int sockfd,
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EFAULT};
     }
 
-    if (flags & ~MSG_DONTWAIT) {
+    /* MSG_WAITALL is handled by the rust syscall handler, which calls this helper until the
+     * buffer is full */
+    if (flags & ~(MSG_DONTWAIT | MSG_PEEK | MSG_TRUNC | MSG_WAITALL)) {
         warning("Unsupported recv flag(s): %d", flags);
     }
 
@@ -594,7 +594,7 @@ SysCallReturn _syscallhandler_recvfromHelper(SysCallHandler* sys, int sockfd,
         }
 
         retval = transport_receiveUserData((Transport*)socket_desc, sys->thread, bufPtr, sizeNeeded,
-                                           &inet_addr.sin_addr.s_addr, &inet_addr.sin_port);
+                                           &inet_addr.sin_addr.s_addr, &inet_addr.sin_port, flags);
 
         trace("recv returned %zd", retval);
     }
@@ -658,7 +658,7 @@ SysCallReturn _syscallhandler_sendtoHelper(SysCallHandler* sys, int sockfd,
         return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EINVAL};
     }
 
-    if (flags & ~MSG_DONTWAIT) {
+    if (flags & ~(MSG_DONTWAIT | MSG_NOSIGNAL | MSG_MORE)) {
         warning("Unsupported send flag(s): %d", flags);
     }
 
@@ -732,8 +732,7 @@ SysCallReturn _syscallhandler_sendtoHelper(SysCallHandler* sys, int sockfd,
             /* connect() was not called yet.
              * TODO: Can they can piggy back a connect() on sendto() if they
              * provide an address for the connection? */
-            return (SysCallReturn){
-                .state = SYSCALL_DONE, .retval.as_i64 = -EPIPE};
+            errcode = -EPIPE;
         } else if (errcode == 0) {
             /* They connected, but never read the success code with a second
              * call to connect(). That's OK, proceed to send as usual. */
@@ -768,6 +767,11 @@ SysCallReturn _syscallhandler_sendtoHelper(SysCallHandler* sys, int sockfd,
         trace("send returned %zd", retval);
     }
 
+    /* Linux raises SIGPIPE for stream sockets, unless MSG_NOSIGNAL is set. */
+    if (retval == -EPIPE && legacyfile_getType(desc) == DT_TCPSOCKET && !(flags & MSG_NOSIGNAL)) {
+        syscallhandler_raiseSigpipe(sys);
+    }
+
     bool nonblocking_mode = legacyfile_getFlags(desc) & O_NONBLOCK || flags & MSG_DONTWAIT;
     if (retval == -EWOULDBLOCK && !nonblocking_mode) {
         if (bufSize > 0) {
@@ -1191,7 +1195,7 @@ SysCallReturn syscallhandler_listen(SysCallHandler* sys,
         }
     }
 
-    tcp_enterServerMode(tcp_desc, sys->host, sys->process, backlog);
+    tcp_enterServerMode(tcp_desc, sys->host, backlog);
     return (SysCallReturn){.state = SYSCALL_DONE};
 }
 
@@ -1356,6 +1360,10 @@ SysCallReturn syscallhandler_socket(SysCallHandler* sys,
     /* Now make sure it will be valid when we operate on it. */
     Descriptor* desc = descriptor_fromLegacyFile((LegacyFile*)sock_desc, descFlags);
     int sockfd = process_registerDescriptor(sys->process, desc);
+    if (sockfd < 0) {
+        /* The descriptor (and the socket) was dropped. */
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = sockfd};
+    }
 
     int errcode = _syscallhandler_validateSocketHelper(sys, sockfd, NULL);
     if (errcode != 0) {
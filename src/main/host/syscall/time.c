@@ -6,10 +6,13 @@
 #include "main/host/syscall/time.h"
 
 #include <errno.h>
+#include <stdbool.h>
 #include <stddef.h>
+#include <time.h>
 
 #include "lib/logger/logger.h"
 #include "main/core/worker.h"
+#include "main/host/host.h"
 #include "main/host/process.h"
 #include "main/host/syscall/protected.h"
 #include "main/host/syscall_condition.h"
@@ -23,6 +26,21 @@
  */
 static EmulatedTime _syscallhandler_getEmulatedTime() { return worker_getCurrentEmulatedTime(); }
 
+/* the realtime clocks may be offset from the emulated time and drift from it */
+static EmulatedTime _syscallhandler_getRealtime(SysCallHandler* sys) {
+    return host_getRealtime(sys->host);
+}
+
+static bool _syscallhandler_isRealtimeClock(clockid_t clk_id) {
+    switch (clk_id) {
+        case CLOCK_REALTIME:
+        case CLOCK_REALTIME_ALARM:
+        case CLOCK_REALTIME_COARSE:
+        case CLOCK_TAI: return true;
+        default: return false;
+    }
+}
+
 static SysCallReturn _syscallhandler_nanosleep_helper(SysCallHandler* sys, clockid_t clock_id,
                                                       int flags, PluginPtr request,
                                                       PluginPtr remainder) {
@@ -129,7 +147,8 @@ SysCallReturn syscallhandler_clock_gettime(SysCallHandler* sys,
     struct timespec* res_timespec =
         process_getWriteablePtr(sys->process, args->args[1].as_ptr, sizeof(*res_timespec));
 
-    EmulatedTime now = _syscallhandler_getEmulatedTime();
+    EmulatedTime now = _syscallhandler_isRealtimeClock(clk_id) ? _syscallhandler_getRealtime(sys)
+                                                               : _syscallhandler_getEmulatedTime();
     res_timespec->tv_sec = now / SIMTIME_ONE_SECOND;
     res_timespec->tv_nsec = now % SIMTIME_ONE_SECOND;
 
@@ -139,7 +158,7 @@ SysCallReturn syscallhandler_clock_gettime(SysCallHandler* sys,
 SysCallReturn syscallhandler_time(SysCallHandler* sys, const SysCallArgs* args) {
     PluginPtr tlocPtr = args->args[0].as_ptr; // time_t*
 
-    time_t seconds = _syscallhandler_getEmulatedTime() / SIMTIME_ONE_SECOND;
+    time_t seconds = _syscallhandler_getRealtime(sys) / SIMTIME_ONE_SECOND;
 
     if (tlocPtr.val) {
         time_t* tloc = process_getWriteablePtr(sys->process, tlocPtr, sizeof(*tloc));
@@ -153,7 +172,7 @@ SysCallReturn syscallhandler_gettimeofday(SysCallHandler* sys, const SysCallArgs
     PluginPtr tvPtr = args->args[0].as_ptr; // struct timeval*
 
     if (tvPtr.val) {
-        EmulatedTime now = _syscallhandler_getEmulatedTime();
+        EmulatedTime now = _syscallhandler_getRealtime(sys);
         struct timeval* tv = process_getWriteablePtr(sys->process, tvPtr, sizeof(*tv));
         tv->tv_sec = now / SIMTIME_ONE_SECOND;
         tv->tv_usec = (now % SIMTIME_ONE_SECOND) / SIMTIME_ONE_MICROSECOND;
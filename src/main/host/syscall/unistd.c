@@ -13,7 +13,7 @@
 #include "main/core/worker.h"
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/descriptor/regular_file.h"
-#include "main/host/descriptor/timerfd.h"
+#include "main/host/descriptor/tcp.h"
 #include "main/host/host.h"
 #include "main/host/process.h"
 #include "main/host/syscall/protected.h"
@@ -81,16 +81,6 @@ static SysCallReturn _syscallhandler_readHelper(SysCallHandler* sys, int fd, Plu
                     process_getWriteablePtr(sys->process, bufPtr, sizeNeeded), sizeNeeded, offset);
             }
             break;
-        case DT_TIMER:
-            if (doPread) {
-                result = -ESPIPE;
-            } else {
-                utility_assert(offset == 0);
-                result = timerfd_read((TimerFd*)desc,
-                                      process_getWriteablePtr(sys->process, bufPtr, sizeNeeded),
-                                      sizeNeeded);
-            }
-            break;
         case DT_TCPSOCKET:
         case DT_UDPSOCKET:
             // We already diverted these to the socket handler above.
@@ -175,7 +165,6 @@ static SysCallReturn _syscallhandler_writeHelper(SysCallHandler* sys, int fd, Pl
                     sizeNeeded, offset);
             }
             break;
-        case DT_TIMER: result = -EINVAL; break;
         case DT_TCPSOCKET:
         case DT_UDPSOCKET:
             // We already diverted these to the socket handler above.
@@ -209,6 +198,96 @@ static SysCallReturn _syscallhandler_writeHelper(SysCallHandler* sys, int fd, Pl
         .state = SYSCALL_DONE, .retval.as_i64 = (int64_t)result};
 }
 
+ssize_t syscallhandler_readLegacyFileShadow(SysCallHandler* sys, LegacyFile* desc, void* buf,
+                                            size_t bufSize, off_t offset, bool useOffset) {
+    LegacyFileType dType = legacyfile_getType(desc);
+
+    if (dType != DT_FILE && useOffset) {
+        return -ESPIPE;
+    }
+
+    size_t sizeNeeded = MIN(bufSize, SYSCALL_IO_BUFSIZE);
+
+    switch (dType) {
+        case DT_FILE: {
+            int errorCode = _syscallhandler_validateLegacyFile(desc, DT_NONE);
+            if (errorCode != 0) {
+                return errorCode;
+            }
+
+            if (useOffset) {
+                return regularfile_pread((RegularFile*)desc, sys->host, buf, sizeNeeded, offset);
+            }
+            return regularfile_read((RegularFile*)desc, sys->host, buf, sizeNeeded);
+        }
+        case DT_TCPSOCKET: {
+            int errorCode = tcp_getConnectionError((TCP*)desc);
+            if (errorCode > 0) {
+                /* connect() was not called yet. */
+                return -ENOTCONN;
+            } else if (errorCode == -EALREADY) {
+                /* Connection in progress. */
+                return -EWOULDBLOCK;
+            }
+
+            return tcp_receiveUserDataShadow((TCP*)desc, sys->thread, buf, sizeNeeded);
+        }
+        default: return -EINVAL;
+    }
+}
+
+ssize_t syscallhandler_writeLegacyFileShadow(SysCallHandler* sys, LegacyFile* desc,
+                                             const void* buf, size_t bufSize, off_t offset,
+                                             bool useOffset) {
+    LegacyFileType dType = legacyfile_getType(desc);
+
+    if (dType != DT_FILE && useOffset) {
+        return -ESPIPE;
+    }
+
+    size_t sizeNeeded = MIN(bufSize, SYSCALL_IO_BUFSIZE);
+
+    switch (dType) {
+        case DT_FILE: {
+            int errorCode = _syscallhandler_validateLegacyFile(desc, DT_NONE);
+            if (errorCode != 0) {
+                return errorCode;
+            }
+
+            if (useOffset) {
+                return regularfile_pwrite((RegularFile*)desc, buf, sizeNeeded, offset);
+            }
+            return regularfile_write((RegularFile*)desc, buf, sizeNeeded);
+        }
+        case DT_TCPSOCKET: {
+            int errorCode = tcp_getConnectionError((TCP*)desc);
+            if (errorCode > 0) {
+                /* connect() was not called yet. */
+                return -EPIPE;
+            } else if (errorCode == -EALREADY) {
+                /* Connection in progress. */
+                return -EWOULDBLOCK;
+            }
+
+            return tcp_sendUserDataShadow((TCP*)desc, sys->thread, buf, sizeNeeded);
+        }
+        default: return -EINVAL;
+    }
+}
+
+off_t syscallhandler_seekLegacyFile(LegacyFile* desc, off_t offset, int whence) {
+    if (legacyfile_getType(desc) != DT_FILE) {
+        return -ESPIPE;
+    }
+
+    int errorCode = _syscallhandler_validateLegacyFile(desc, DT_NONE);
+    if (errorCode != 0) {
+        return errorCode;
+    }
+
+    return regularfile_lseek((RegularFile*)desc, offset, whence);
+}
+
 ///////////////////////////////////////////////////////////
 // System Calls
 ///////////////////////////////////////////////////////////
@@ -252,9 +331,34 @@ SysCallReturn syscallhandler_getpid(SysCallHandler* sys,
 }
 
 SysCallReturn syscallhandler_getppid(SysCallHandler* sys, const SysCallArgs* args) {
-    // We can't handle this natively in the plugin if we want determinism
-    // Just return a constant
-    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = 1};
+    // We can't handle this natively in the plugin if we want determinism.
+    // Processes started by shadow appear to have been started by init.
+    guint ppid = process_getParentProcessID(sys->process);
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = ppid ? ppid : 1};
+}
+
+SysCallReturn syscallhandler_getpgid(SysCallHandler* sys, const SysCallArgs* args) {
+    pid_t pid = args->args[0].as_i64;
+
+    if (pid < 0) {
+        return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -EINVAL};
+    }
+
+    Process* process = sys->process;
+    if (pid != 0) {
+        process = host_getProcess(sys->host, pid);
+        if (process == NULL) {
+            return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = -ESRCH};
+        }
+    }
+
+    guint pgid = process_getProcessGroupID(process);
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = pgid};
+}
+
+SysCallReturn syscallhandler_getpgrp(SysCallHandler* sys, const SysCallArgs* args) {
+    guint pgid = process_getProcessGroupID(sys->process);
+    return (SysCallReturn){.state = SYSCALL_DONE, .retval.as_i64 = pgid};
 }
 
 SysCallReturn syscallhandler_set_tid_address(SysCallHandler* sys, const SysCallArgs* args) {
@@ -6,11 +6,17 @@
 #ifndef SRC_MAIN_HOST_SYSCALL_UNISTD_H_
 #define SRC_MAIN_HOST_SYSCALL_UNISTD_H_
 
+#include <stdbool.h>
+#include <sys/types.h>
+
+#include "main/host/descriptor/descriptor.h"
 #include "main/host/syscall/protected.h"
 
 SYSCALL_HANDLER(exit_group);
 SYSCALL_HANDLER(getpid);
 SYSCALL_HANDLER(getppid);
+SYSCALL_HANDLER(getpgid);
+SYSCALL_HANDLER(getpgrp);
 SYSCALL_HANDLER(pread64);
 SYSCALL_HANDLER(pwrite64);
 SYSCALL_HANDLER(read);
@@ -18,4 +24,18 @@ SYSCALL_HANDLER(set_tid_address);
 SYSCALL_HANDLER(uname);
 SYSCALL_HANDLER(write);
 
+/* Read from or write to a legacy regular file or TCP socket using a buffer in shadow's memory
+ * rather than in plugin memory. If `useOffset` is true, the regular file is accessed at `offset`
+ * without changing its file position. These never block, and instead return -EWOULDBLOCK. Returns
+ * the number of bytes copied, or a negative errno. Other file types return -EINVAL. */
+ssize_t syscallhandler_readLegacyFileShadow(SysCallHandler* sys, LegacyFile* desc, void* buf,
+                                            size_t bufSize, off_t offset, bool useOffset);
+ssize_t syscallhandler_writeLegacyFileShadow(SysCallHandler* sys, LegacyFile* desc,
+                                             const void* buf, size_t bufSize, off_t offset,
+                                             bool useOffset);
+
+/* Change the file position of a legacy regular file, as in lseek(). Returns the new position, or a
+ * negative errno. Other file types return -ESPIPE. */
+off_t syscallhandler_seekLegacyFile(LegacyFile* desc, off_t offset, int whence);
+
 #endif /* SRC_MAIN_HOST_SYSCALL_UNISTD_H_ */
@@ -72,6 +72,10 @@ SysCallCondition* syscallcondition_new(Trigger trigger) {
                 futex_ref(cond->trigger.object.as_futex);
                 return cond;
             }
+            case TRIGGER_CHILD: {
+                process_ref(cond->trigger.object.as_process);
+                return cond;
+            }
             case TRIGGER_NONE: {
                 return cond;
             }
@@ -125,6 +129,11 @@ static void _syscallcondition_cleanupListeners(SysCallCondition* cond) {
                 futex_removeListener(cond->trigger.object.as_futex, cond->triggerListener);
                 break;
             }
+            case TRIGGER_CHILD: {
+                process_removeChildEventListener(
+                    cond->trigger.object.as_process, cond->triggerListener);
+                break;
+            }
             case TRIGGER_NONE: {
                 break;
             }
@@ -188,6 +197,10 @@ static void _syscallcondition_free(SysCallCondition* cond) {
                 futex_unref(cond->trigger.object.as_futex);
                 break;
             }
+            case TRIGGER_CHILD: {
+                process_unref(cond->trigger.object.as_process);
+                break;
+            }
             case TRIGGER_NONE: {
                 break;
             }
@@ -250,6 +263,12 @@ static void _syscallcondition_logListeningState(SysCallCondition* cond,
                                        cond->timeoutExpiration != EMUTIME_INVALID ? " and " : "");
                 break;
             }
+            case TRIGGER_CHILD: {
+                g_string_append_printf(string, "children of process %u%s",
+                                       process_getProcessID(cond->trigger.object.as_process),
+                                       cond->timeoutExpiration != EMUTIME_INVALID ? " and " : "");
+                break;
+            }
             case TRIGGER_NONE: {
                 break;
             }
@@ -294,6 +313,10 @@ static bool _syscallcondition_statusIsValid(SysCallCondition* cond) {
             // Futex status doesn't change
             return true;
         }
+        case TRIGGER_CHILD: {
+            // We're only notified when a child changes state
+            return true;
+        }
         case TRIGGER_NONE: {
             break;
         }
@@ -475,6 +498,16 @@ void syscallcondition_waitNonblock(SysCallCondition* cond, Host* host, Process*
                 futex_addListener(cond->trigger.object.as_futex, cond->triggerListener);
                 break;
             }
+            case TRIGGER_CHILD: {
+                /* Monitor the requested status an every status change. */
+                statuslistener_setMonitorStatus(
+                    cond->triggerListener, cond->trigger.status, SLF_ALWAYS);
+
+                /* Attach the listener to the process. */
+                process_addChildEventListener(
+                    cond->trigger.object.as_process, cond->triggerListener);
+                break;
+            }
             case TRIGGER_NONE: {
                 break;
             }
@@ -20,6 +20,7 @@ enum _TriggerType {
     TRIGGER_DESCRIPTOR,
     TRIGGER_FILE,
     TRIGGER_FUTEX,
+    TRIGGER_CHILD,
 };
 
 /* Pointer to the object whose status we monitor for changes */
@@ -29,6 +30,7 @@ union _TriggerObject {
     LegacyFile* as_legacy_file;
     const File* as_file;
     Futex* as_futex;
+    Process* as_process;
 };
 
 /* The spec of the condition that will cause us to unblock a process/thread waiting for the object
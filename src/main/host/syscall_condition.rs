@@ -1,3 +1,4 @@
+use crate::core::support::emulated_time::EmulatedTime;
 use crate::cshadow;
 use crate::host::descriptor::OpenFile;
 use crate::host::syscall::Trigger;
@@ -33,6 +34,11 @@ impl<'a> SysCallConditionRef<'a> {
 
         Some(unsafe { file_ptr.as_ref() }.unwrap())
     }
+
+    /// The absolute time at which the condition will time out, if any.
+    pub fn timeout(&self) -> Option<EmulatedTime> {
+        EmulatedTime::from_c_emutime(unsafe { cshadow::syscallcondition_getTimeout(self.c_ptr) })
+    }
 }
 
 /// A mutable reference to a syscall condition.
@@ -58,6 +64,17 @@ impl<'a> SysCallConditionRefMut<'a> {
         let file_ptr = Box::into_raw(Box::new(file));
         unsafe { cshadow::syscallcondition_setActiveFile(self.condition.c_ptr, file_ptr) };
     }
+
+    /// Set the absolute time at which the condition will time out.
+    pub fn set_timeout(&mut self, host: *mut cshadow::Host, timeout: EmulatedTime) {
+        unsafe {
+            cshadow::syscallcondition_setTimeout(
+                self.condition.c_ptr,
+                host,
+                EmulatedTime::to_c_emutime(Some(timeout)),
+            )
+        };
+    }
 }
 
 impl<'a> std::ops::Deref for SysCallConditionRefMut<'a> {
@@ -19,11 +19,9 @@
 #include "main/core/support/config_handlers.h"
 #include "main/core/worker.h"
 #include "main/host/descriptor/descriptor.h"
-#include "main/host/descriptor/timerfd.h"
 #include "main/host/host.h"
 #include "main/host/process.h"
 #include "main/host/syscall/clone.h"
-#include "main/host/syscall/epoll.h"
 #include "main/host/syscall/fcntl.h"
 #include "main/host/syscall/file.h"
 #include "main/host/syscall/fileat.h"
@@ -37,7 +35,6 @@
 #include "main/host/syscall/signal.h"
 #include "main/host/syscall/socket.h"
 #include "main/host/syscall/time.h"
-#include "main/host/syscall/timerfd.h"
 #include "main/host/syscall/uio.h"
 #include "main/host/syscall/unistd.h"
 #include "main/host/syscall_condition.h"
@@ -279,22 +276,26 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
         switch (args->number) {
             HANDLE_RUST(accept);
             HANDLE_RUST(accept4);
+            HANDLE_RUST(adjtimex);
             HANDLE_RUST(bind);
             HANDLE_C(brk);
+            HANDLE_RUST(clock_adjtime);
             HANDLE_C(clock_gettime);
             HANDLE_C(clock_nanosleep);
+            HANDLE_RUST(clock_settime);
             HANDLE_C(clone);
             HANDLE_RUST(close);
             HANDLE_RUST(connect);
+            HANDLE_RUST(copy_file_range);
             HANDLE_C(creat);
             HANDLE_RUST(dup);
             HANDLE_RUST(dup2);
             HANDLE_RUST(dup3);
-            HANDLE_C(epoll_create);
-            HANDLE_C(epoll_create1);
-            HANDLE_C(epoll_ctl);
-            HANDLE_C(epoll_pwait);
-            HANDLE_C(epoll_wait);
+            HANDLE_RUST(epoll_create);
+            HANDLE_RUST(epoll_create1);
+            HANDLE_RUST(epoll_ctl);
+            HANDLE_RUST(epoll_pwait);
+            HANDLE_RUST(epoll_wait);
             HANDLE_RUST(eventfd);
             HANDLE_RUST(eventfd2);
             HANDLE_C(execve);
@@ -325,6 +326,7 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             HANDLE_C(fgetxattr);
             HANDLE_C(flistxattr);
             HANDLE_C(flock);
+            HANDLE_C(fork);
             HANDLE_C(fremovexattr);
             HANDLE_C(fsetxattr);
             HANDLE_C(fstat);
@@ -335,12 +337,16 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             HANDLE_C(futimesat);
             HANDLE_C(getdents);
             HANDLE_C(getdents64);
+            HANDLE_RUST(getcpu);
             HANDLE_RUST(getitimer);
             HANDLE_RUST(getpeername);
+            HANDLE_C(getpgid);
+            HANDLE_C(getpgrp);
             HANDLE_C(getpid);
             HANDLE_C(getppid);
             HANDLE_C(gettid);
             HANDLE_RUST(getrandom);
+            HANDLE_RUST(getrlimit);
             HANDLE_C(get_robust_list);
             HANDLE_RUST(getsockname);
             HANDLE_RUST(getsockopt);
@@ -369,30 +375,34 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             HANDLE_C(ppoll);
             HANDLE_C(prctl);
             HANDLE_RUST(pread64);
-            HANDLE_C(preadv);
+            HANDLE_RUST(preadv);
 #ifdef SYS_preadv2
-            HANDLE_C(preadv2);
+            HANDLE_RUST(preadv2);
 #endif
 #ifdef SYS_prlimit
             HANDLE_C(prlimit);
 #endif
 #ifdef SYS_prlimit64
-            HANDLE_C(prlimit64);
+            HANDLE_RUST(prlimit64);
 #endif
             HANDLE_C(pselect6);
             HANDLE_RUST(pwrite64);
-            HANDLE_C(pwritev);
+            HANDLE_RUST(pwritev);
 #ifdef SYS_pwritev2
-            HANDLE_C(pwritev2);
+            HANDLE_RUST(pwritev2);
 #endif
             HANDLE_RUST(read);
             HANDLE_C(readahead);
             HANDLE_C(readlinkat);
-            HANDLE_C(readv);
+            HANDLE_RUST(readv);
             HANDLE_RUST(recvfrom);
+            HANDLE_RUST(recvmmsg);
+            HANDLE_RUST(recvmsg);
             HANDLE_C(renameat);
             HANDLE_C(renameat2);
             HANDLE_RUST(rseq);
+            HANDLE_RUST(sched_getaffinity);
+            HANDLE_RUST(sched_setaffinity);
             HANDLE_RUST(sched_yield);
             HANDLE_C(shadow_get_ipc_blk);
             HANDLE_C(shadow_get_shm_blk);
@@ -400,14 +410,20 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             HANDLE_C(shadow_init_memory_manager);
             HANDLE_C(shadow_yield);
             HANDLE_C(select);
+            HANDLE_RUST(sendfile);
+            HANDLE_RUST(sendmmsg);
+            HANDLE_RUST(sendmsg);
             HANDLE_RUST(sendto);
             HANDLE_RUST(setsockopt);
+            HANDLE_RUST(settimeofday);
 #ifdef SYS_sigaction
             // Superseded by rt_sigaction in Linux 2.2
             UNSUPPORTED(sigaction);
 #endif
             HANDLE_C(rt_sigaction);
             HANDLE_C(sigaltstack);
+            HANDLE_RUST(signalfd);
+            HANDLE_RUST(signalfd4);
 #ifdef SYS_signal
             // Superseded by sigaction in glibc 2.0
             UNSUPPORTED(signal);
@@ -419,10 +435,12 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             HANDLE_C(rt_sigprocmask);
             HANDLE_C(set_robust_list);
             HANDLE_RUST(setitimer);
+            HANDLE_RUST(setrlimit);
             HANDLE_C(set_tid_address);
             HANDLE_RUST(shutdown);
             HANDLE_RUST(socket);
             HANDLE_RUST(socketpair);
+            HANDLE_RUST(splice);
 #ifdef SYS_statx
             HANDLE_C(statx);
 #endif
@@ -430,17 +448,27 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             HANDLE_C(sync_file_range);
             HANDLE_C(syncfs);
             HANDLE_RUST(sysinfo);
+            HANDLE_RUST(tee);
             HANDLE_C(tgkill);
             HANDLE_C(time);
-            HANDLE_C(timerfd_create);
-            HANDLE_C(timerfd_gettime);
-            HANDLE_C(timerfd_settime);
+            HANDLE_RUST(timer_create);
+            HANDLE_RUST(timer_delete);
+            HANDLE_RUST(timer_getoverrun);
+            HANDLE_RUST(timer_gettime);
+            HANDLE_RUST(timer_settime);
+            HANDLE_RUST(timerfd_create);
+            HANDLE_RUST(timerfd_gettime);
+            HANDLE_RUST(timerfd_settime);
             HANDLE_C(tkill);
             HANDLE_C(uname);
-            HANDLE_C(unlinkat);
+            HANDLE_RUST(unlink);
+            HANDLE_RUST(unlinkat);
             HANDLE_C(utimensat);
+            HANDLE_C(vfork);
+            HANDLE_RUST(wait4);
+            HANDLE_RUST(waitid);
             HANDLE_RUST(write);
-            HANDLE_C(writev);
+            HANDLE_RUST(writev);
 
             // **************************************
             // Not handled (yet):
@@ -448,25 +476,10 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             // NATIVE(chdir);
             // NATIVE(fchdir);
             // NATIVE(io_getevents);
-            // NATIVE(waitid);
             // NATIVE(msync);
 
-            //// operations on pids (shadow overrides pids)
-            // NATIVE(sched_getaffinity);
-            // NATIVE(sched_setaffinity);
-
             //// copying data between various types of fds
-            // NATIVE(copy_file_range);
-            // NATIVE(sendfile);
-            // NATIVE(splice);
             // NATIVE(vmsplice);
-            // NATIVE(tee);
-
-            //// additional socket io
-            // NATIVE(recvmsg);
-            // NATIVE(sendmsg);
-            // NATIVE(recvmmsg);
-            // NATIVE(sendmmsg);
 
             // ***************************************
             // We think we don't need to handle these
@@ -483,7 +496,6 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             NATIVE(getgid);
             NATIVE(getresgid);
             NATIVE(getresuid);
-            NATIVE(getrlimit);
             NATIVE(getuid);
             NATIVE(getxattr);
             NATIVE(lchown);
@@ -509,7 +521,6 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             NATIVE(setresgid);
             NATIVE(setresuid);
             NATIVE(setreuid);
-            NATIVE(setrlimit);
             NATIVE(setuid);
             NATIVE(setxattr);
             NATIVE(stat);
@@ -519,7 +530,6 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
             NATIVE(statfs);
             NATIVE(symlink);
             NATIVE(truncate);
-            NATIVE(unlink);
             NATIVE(utime);
             NATIVE(utimes);
 
@@ -593,6 +603,10 @@ SysCallReturn syscallhandler_make_syscall(SysCallHandler* sys,
                 host_getShimShmemLock(sys->host),
                 shimshmem_unblockedSyscallLatency(host_getSharedMem(sys->host)));
         }
+        // Charge the process for the latency modeled so far, including any
+        // modeled by the shim, possibly expiring its virtual and profiling
+        // timers.
+        process_updateCpuTime(sys->process, sys->thread);
         const SimulationTime unappliedCpuLatency =
             shimshmem_getUnappliedCpuLatency(host_getShimShmemLock(sys->host));
         trace("Unapplied CPU latency amt=%ld max=%ld", unappliedCpuLatency, maxUnappliedCpuLatency);
@@ -33,6 +33,7 @@ Thread thread_create(Host* host, Process* process, int threadID, int type_id,
                      .process = process,
                      .tid = threadID,
                      .affinity = AFFINITY_UNINIT,
+                     .cpuAffinity = cpuaffinity_new(host_getCpuCount(host)),
                      .shimSharedMemBlock = shmemallocator_globalAlloc(shimshmemthread_size()),
                      MAGIC_INITIALIZER};
     host_ref(host);
@@ -63,6 +64,16 @@ static void _thread_syncAffinityWithWorker(Thread *thread) {
         affinity_setProcessAffinity(thread->nativeTid, worker_getAffinity(), thread->affinity);
 }
 
+/*
+ * Helper function. A new thread or process inherits the CPU affinity of the thread that created it.
+ */
+static void _thread_inheritCpuAffinity(Thread* child, Thread* parent) {
+    MAGIC_ASSERT(child);
+    MAGIC_ASSERT(parent);
+    cpuaffinity_free(child->cpuAffinity);
+    child->cpuAffinity = cpuaffinity_clone(parent->cpuAffinity);
+}
+
 void thread_ref(Thread* thread) {
     MAGIC_ASSERT(thread);
     (thread->referenceCount)++;
@@ -82,6 +93,7 @@ void thread_unref(Thread* thread) {
             host_unref(thread->host);
         }
         shmemallocator_globalFree(&thread->shimSharedMemBlock);
+        cpuaffinity_free(thread->cpuAffinity);
         MAGIC_CLEAR(thread);
         g_free(thread);
     }
@@ -99,6 +111,26 @@ void thread_run(Thread* thread, char* pluginPath, char** argv, char** envv,
     thread->nativeTid = thread->nativePid;
 }
 
+pid_t thread_execve(Thread* thread, const char* pluginPath, char** argv, char** envv,
+                    const char* workingDir) {
+    MAGIC_ASSERT(thread);
+    utility_assert(thread->methods.execve);
+
+    thread->nativePid = thread->methods.execve(thread, pluginPath, argv, envv, workingDir);
+    thread->nativeTid = thread->nativePid;
+
+    // The new native process hasn't been pinned to a CPU yet.
+    thread->affinity = AFFINITY_UNINIT;
+    _thread_syncAffinityWithWorker(thread);
+
+    // Like linux, the new program starts without a `clear_child_tid` address
+    // or an rseq registration.
+    thread->tidAddress = (PluginPtr){0};
+    thread->rseqAddress = (PluginPtr){0};
+
+    return thread->nativePid;
+}
+
 void thread_resume(Thread* thread) {
     MAGIC_ASSERT(thread);
     utility_assert(thread->methods.resume);
@@ -186,7 +218,25 @@ int thread_clone(Thread* thread, unsigned long flags, PluginPtr child_stack, Plu
                  PluginPtr ctid, unsigned long newtls, Thread** child) {
     MAGIC_ASSERT(thread);
     utility_assert(thread->methods.clone);
-    return thread->methods.clone(thread, flags, child_stack, ptid, ctid, newtls, child);
+    int rv = thread->methods.clone(thread, flags, child_stack, ptid, ctid, newtls, child);
+    if (rv >= 0) {
+        _thread_inheritCpuAffinity(*child, thread);
+    }
+    return rv;
+}
+
+int thread_fork(Thread* thread, Process* childProcess, unsigned long flags, unsigned long newtls,
+                Thread** child) {
+    MAGIC_ASSERT(thread);
+    utility_assert(thread->methods.fork);
+    int rv = thread->methods.fork(thread, childProcess, flags, newtls, child);
+    if (rv >= 0) {
+        _thread_inheritCpuAffinity(*child, thread);
+        // like linux, a forked process keeps its rseq registration, since its
+        // copy of the memory has the same layout; new threads start without one
+        (*child)->rseqAddress = thread->rseqAddress;
+    }
+    return rv;
 }
 
 uint32_t thread_getProcessId(Thread* thread) {
@@ -219,11 +269,36 @@ PluginVirtualPtr thread_getTidAddress(Thread* thread) {
     return thread->tidAddress;
 }
 
+CpuAffinity* thread_getCpuAffinity(Thread* thread) {
+    MAGIC_ASSERT(thread);
+    return thread->cpuAffinity;
+}
+
 void thread_setTidAddress(Thread* thread, PluginPtr addr) {
     MAGIC_ASSERT(thread);
     thread->tidAddress = addr;
 }
 
+PluginVirtualPtr thread_getRseqAddress(Thread* thread) {
+    MAGIC_ASSERT(thread);
+    return thread->rseqAddress;
+}
+
+void thread_setRseqAddress(Thread* thread, PluginPtr addr) {
+    MAGIC_ASSERT(thread);
+    thread->rseqAddress = addr;
+}
+
+void thread_setVforkChildID(Thread* thread, pid_t childID) {
+    MAGIC_ASSERT(thread);
+    thread->vforkChildID = childID;
+}
+
+pid_t thread_getVforkChildID(Thread* thread) {
+    MAGIC_ASSERT(thread);
+    return thread->vforkChildID;
+}
+
 bool thread_isLeader(Thread* thread) {
     MAGIC_ASSERT(thread);
     return thread->tid == process_getProcessID(thread->process);
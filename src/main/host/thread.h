@@ -23,6 +23,11 @@ void thread_ref(Thread* thread);
 void thread_unref(Thread* thread);
 
 void thread_run(Thread* thread, char* pluginPath, char** argv, char** envv, const char* workingDir);
+// Replace the thread's native process with a new one running `pluginPath`, as
+// for a successful `execve(2)`. The new process starts running when the thread
+// is next resumed. Returns the native pid of the new process.
+pid_t thread_execve(Thread* thread, const char* pluginPath, char** argv, char** envv,
+                    const char* workingDir);
 void thread_resume(Thread* thread);
 void thread_handleProcessExit(Thread* thread);
 int thread_getReturnCode(Thread* thread);
@@ -51,12 +56,24 @@ int thread_getID(Thread* thread);
 
 // Create a new child thread as for `clone(2)`. Returns the child pid, or a
 // negative errno.  If the returned pid is >= 0, then `child` will be set to a
-// newly allocated and initialized child Thread. Caller is responsible for
-// adding the Thread to the process and arranging for it to run (typically by
-// calling process_addThread).
+// newly allocated and initialized child Thread, which inherits `thread`'s CPU
+// affinity. Caller is responsible for adding the Thread to the process and
+// arranging for it to run (typically by calling process_addThread).
 int thread_clone(Thread* thread, unsigned long flags, PluginPtr child_stack, PluginPtr ptid,
                  PluginPtr ctid, unsigned long newtls, Thread** child);
 
+// Create a new process as for `fork(2)`, by natively cloning `thread`'s process
+// with the given `clone(2)` flags (which must not include CLONE_VM). Returns
+// the native pid of the new process, or a negative errno. If the returned pid
+// is >= 0, then `child` will be set to a newly allocated and initialized
+// Thread belonging to `childProcess`, with the same id as the process and with
+// `thread`'s CPU affinity. The new
+// process's memory initially shares any memory that Shadow has remapped with
+// the parent process; see `memorymanager_fork`. Caller is responsible for
+// adding the Thread to `childProcess` and arranging for it to run.
+int thread_fork(Thread* thread, Process* childProcess, unsigned long flags, unsigned long newtls,
+                Thread** child);
+
 // Sets the `clear_child_tid` attribute as for `set_tid_address(2)`. The thread
 // will perform a futex-wake operation on the given address on termination.
 void thread_setTidAddress(Thread* thread, PluginVirtualPtr addr);
@@ -64,6 +81,24 @@ void thread_setTidAddress(Thread* thread, PluginVirtualPtr addr);
 // Gets the `clear_child_tid` attribute, as set by `thread_setTidAddress`.
 PluginVirtualPtr thread_getTidAddress(Thread* thread);
 
+// Gets the set of simulated CPUs that the thread may run on, as for
+// `sched_getaffinity(2)`.
+CpuAffinity* thread_getCpuAffinity(Thread* thread);
+
+// Sets the restartable sequences area registered with `rseq(2)`, or NULL if
+// none is registered.
+void thread_setRseqAddress(Thread* thread, PluginVirtualPtr addr);
+
+// Gets the restartable sequences area, as set by `thread_setRseqAddress`.
+PluginVirtualPtr thread_getRseqAddress(Thread* thread);
+
+// Sets the id of the child process that the thread is waiting on after a
+// `vfork`-style clone, or 0 if it isn't waiting.
+void thread_setVforkChildID(Thread* thread, pid_t childID);
+
+// Gets the id set by `thread_setVforkChildID`.
+pid_t thread_getVforkChildID(Thread* thread);
+
 // Returns whether the given thread is its thread group (aka process) leader.
 // Typically this is true for the first thread created in a process.
 bool thread_isLeader(Thread* thread);
@@ -1,4 +1,5 @@
-use super::host::HostId;
+use super::cpu_affinity::CpuAffinity;
+use super::host::{Host, HostId};
 use super::process::ProcessId;
 use super::syscall_types::{PluginPtr, SysCallReg};
 use crate::cshadow as c;
@@ -19,6 +20,17 @@ pub trait Thread {
     fn syscall_condition(&self) -> Option<SysCallConditionRef>;
     fn syscall_condition_mut(&mut self) -> Option<SysCallConditionRefMut>;
 
+    /// The set of simulated CPUs that the thread may run on.
+    fn cpu_affinity(&self) -> &CpuAffinity;
+    fn cpu_affinity_mut(&mut self) -> &mut CpuAffinity;
+
+    /// The restartable sequences area registered with `rseq`, if any.
+    fn rseq_ptr(&self) -> Option<PluginPtr>;
+    fn set_rseq_ptr(&mut self, ptr: Option<PluginPtr>);
+
+    /// Returns true if the thread has a pending signal that isn't blocked.
+    fn unblocked_signal_pending(&self, host: &Host) -> bool;
+
     /// Natively execute munmap(2) on the given thread.
     fn native_munmap(&mut self, ptr: PluginPtr, size: usize) -> nix::Result<()> {
         self.native_syscall(libc::SYS_munmap, &[ptr.into(), size.into()])?;
@@ -208,6 +220,39 @@ impl Thread for CThread {
         Some(unsafe { SysCallConditionRefMut::borrow_from_c(syscall_condition_ptr) })
     }
 
+    fn cpu_affinity(&self) -> &CpuAffinity {
+        // Safety: self.cthread initialized in CThread::new.
+        unsafe { c::thread_getCpuAffinity(self.cthread).as_ref() }.unwrap()
+    }
+
+    fn cpu_affinity_mut(&mut self) -> &mut CpuAffinity {
+        // Safety: self.cthread initialized in CThread::new.
+        unsafe { c::thread_getCpuAffinity(self.cthread).as_mut() }.unwrap()
+    }
+
+    fn rseq_ptr(&self) -> Option<PluginPtr> {
+        // Safety: self.cthread initialized in CThread::new.
+        let ptr = PluginPtr::from(unsafe { c::thread_getRseqAddress(self.cthread) });
+        if ptr.is_null() {
+            None
+        } else {
+            Some(ptr)
+        }
+    }
+
+    fn set_rseq_ptr(&mut self, ptr: Option<PluginPtr>) {
+        let ptr = ptr.unwrap_or_else(|| PluginPtr::from(0usize));
+        // Safety: self.cthread initialized in CThread::new.
+        unsafe { c::thread_setRseqAddress(self.cthread, ptr.into()) };
+    }
+
+    fn unblocked_signal_pending(&self, host: &Host) -> bool {
+        // Safety: self.cthread initialized in CThread::new.
+        unsafe {
+            c::thread_unblockedSignalPending(self.cthread, c::host_getShimShmemLock(host.chost()))
+        }
+    }
+
     fn process_id(&self) -> ProcessId {
         // Safety: self.cthread initialized in CThread::new.
         ProcessId::from(unsafe { c::thread_getProcessId(self.cthread) })
@@ -6,6 +6,7 @@
 #include <glib.h>
 #include <sched.h>
 #include <search.h>
+#include <signal.h>
 #include <sys/prctl.h>
 #include <sys/syscall.h>
 #include <sys/types.h>
@@ -38,6 +39,10 @@ struct _ThreadPreload {
     struct IPCData* ipc_data;
 
     uint64_t notificationHandle;
+
+    /* set when the current syscall replaced the native process, which is
+     * waiting to be started rather than for the syscall's result */
+    bool didExec;
 };
 
 typedef struct _ShMemWriteBlock {
@@ -179,10 +184,9 @@ static void _markPluginExited(pid_t pid, void* voidIPC) {
     ipcData_markPluginExited(ipc);
 }
 
-pid_t threadpreload_run(Thread* base, char* pluginPath, char** argv, char** envv,
-                        const char* workingDir) {
-    ThreadPreload* thread = _threadToThreadPreload(base);
-
+// Starts a new native process running `pluginPath`, with a new IPC block.
+static pid_t _threadpreload_spawn(ThreadPreload* thread, const char* pluginPath, char** argv,
+                                  char** envv, const char* workingDir) {
     /* set the env for the child */
     gchar** myenvv = g_strdupv(envv);
 
@@ -227,6 +231,15 @@ pid_t threadpreload_run(Thread* base, char* pluginPath, char** argv, char** envv
         g_strfreev(myenvv);
     }
 
+    return child_pid;
+}
+
+pid_t threadpreload_run(Thread* base, char* pluginPath, char** argv, char** envv,
+                        const char* workingDir) {
+    ThreadPreload* thread = _threadToThreadPreload(base);
+
+    pid_t child_pid = _threadpreload_spawn(thread, pluginPath, argv, envv, workingDir);
+
     // TODO get to the point where the plugin blocks before calling main()
     thread->currentEvent.event_id = SHD_SHIM_EVENT_START;
 
@@ -236,6 +249,33 @@ pid_t threadpreload_run(Thread* base, char* pluginPath, char** argv, char** envv
     return child_pid;
 }
 
+static pid_t _threadpreload_execve(Thread* base, const char* pluginPath, char** argv, char** envv,
+                                   const char* workingDir) {
+    ThreadPreload* thread = _threadToThreadPreload(base);
+
+    // We replace the native process instead of letting it exec natively, since
+    // the new program would inherit the shim's seccomp filter before the shim
+    // is loaded again. The old process is waiting for the result of `execve`.
+    pid_t oldPid = base->nativePid;
+    childpidwatcher_unregisterPid(worker_getChildPidWatcher(), oldPid);
+    if (kill(oldPid, SIGKILL)) {
+        warning("kill(pid=%d) error %d: %s", oldPid, errno, g_strerror(errno));
+    }
+    if (waitpid(oldPid, NULL, __WALL) < 0) {
+        warning("waitpid(pid=%d) error %d: %s", oldPid, errno, g_strerror(errno));
+    }
+
+    // The old IPC block is left allocated, since the watch on the old process
+    // still marks it as exited.
+    pid_t child_pid = _threadpreload_spawn(thread, pluginPath, argv, envv, workingDir);
+
+    // The new process is started instead of completing the syscall; see
+    // `threadpreload_resume`.
+    thread->didExec = true;
+
+    return child_pid;
+}
+
 static inline void _threadpreload_waitForNextEvent(ThreadPreload* thread, ShimEvent *e) {
     MAGIC_ASSERT(_threadPreloadToThread(thread));
     utility_assert(thread->ipc_data);
@@ -327,6 +367,18 @@ SysCallCondition* threadpreload_resume(Thread* base) {
                 // Flush any writes the syscallhandler made.
                 process_flushPtrs(thread->base.process);
 
+                if (thread->didExec) {
+                    if (result.state == SYSCALL_BLOCK) {
+                        // The handler yielded, and will return its pending
+                        // result when we're resumed. The new process isn't
+                        // listening for a block message.
+                        return result.cond;
+                    }
+                    thread->didExec = false;
+                    thread->currentEvent.event_id = SHD_SHIM_EVENT_START;
+                    continue;
+                }
+
                 if (result.state == SYSCALL_BLOCK) {
                     if (shimipc_sendExplicitBlockMessageEnabled()) {
                         trace("Sending block message to plugin");
@@ -456,6 +508,74 @@ static int _threadpreload_clone(Thread* base, unsigned long flags, PluginPtr chi
     return childNativeTid;
 }
 
+static int _threadpreload_fork(Thread* base, Process* childProcess, unsigned long flags,
+                               unsigned long newtls, Thread** childp) {
+    ThreadPreload* thread = _threadToThreadPreload(base);
+    utility_assert(!(flags & CLONE_VM));
+
+    // The first thread of the new process has the same id as the process.
+    *childp = threadpreload_new(base->host, childProcess, process_getProcessID(childProcess));
+    ThreadPreload* child = _threadToThreadPreload(*childp);
+    child->ipc_blk = shmemallocator_globalAlloc(ipcData_nbytes());
+    utility_assert(child->ipc_blk.p);
+    child->ipc_data = child->ipc_blk.p;
+    ipcData_init(child->ipc_data, shimipc_spinMax());
+
+    // Send the shared memory blocks for the new process to use in place of
+    // the ones it inherits from the parent.
+    _threadpreload_continuePlugin(
+        thread, &(ShimEvent){
+                    .event_id = SHD_SHIM_EVENT_ADD_PROCESS_REQ,
+                    .event_data.add_process_req =
+                        {
+                            .ipc_block = shmemallocator_globalBlockSerialize(&child->ipc_blk),
+                            .process_shm_block = shmemallocator_globalBlockSerialize(
+                                process_getSharedMemBlock(childProcess)),
+                            .thread_shm_block =
+                                shmemallocator_globalBlockSerialize(thread_getShMBlock(*childp)),
+                        },
+                });
+    ShimEvent response = {0};
+    _threadpreload_waitForNextEvent(thread, &response);
+    utility_assert(response.event_id == SHD_SHIM_EVENT_ADD_THREAD_PARENT_RES);
+
+    // Create the new managed process. CLONE_PARENT makes it a child of Shadow
+    // rather than of the managed process, so that we can wait for it to exit.
+    // The child must keep running on the shim's current stack until it returns
+    // to the plugin; the shim switches it to any stack the plugin requested.
+    pid_t childNativePid =
+        thread_nativeSyscall(base, SYS_clone, flags | CLONE_PARENT, 0, 0, 0, newtls);
+    if (childNativePid < 0) {
+        trace("native clone failed %d(%s)", childNativePid, strerror(-childNativePid));
+        thread_unref(*childp);
+        *childp = NULL;
+        return childNativePid;
+    }
+    trace("native clone created process %d", childNativePid);
+    child->base.nativePid = childNativePid;
+    child->base.nativeTid = childNativePid;
+
+    // We don't own a pipe that closes when the child exits, as we do for
+    // processes that we start ourselves, so use a pidfd instead.
+    int pidfd = syscall(SYS_pidfd_open, childNativePid, 0);
+    if (pidfd < 0) {
+        utility_panic("pidfd_open: %s", g_strerror(errno));
+    }
+    childpidwatcher_registerPid(worker_getChildPidWatcher(), childNativePid, pidfd);
+    childpidwatcher_watch(
+        worker_getChildPidWatcher(), childNativePid, _markPluginExited, child->ipc_data);
+
+    // The child is waiting for the result of the syscall that created it, and
+    // will handle native syscalls for us in the meantime.
+    child->currentEvent = (ShimEvent){
+        .event_id = SHD_SHIM_EVENT_SYSCALL_COMPLETE,
+        .event_data.syscall_complete.retval.as_i64 = 0,
+    };
+    child->isRunning = 1;
+
+    return childNativePid;
+}
+
 long threadpreload_nativeSyscall(Thread* base, long n, va_list args) {
     ThreadPreload* thread = _threadToThreadPreload(base);
     ShimEvent req = {
@@ -490,6 +610,7 @@ Thread* threadpreload_new(Host* host, Process* process, gint threadID) {
         .base = thread_create(host, process, threadID, THREADPRELOAD_TYPE_ID,
                               (ThreadMethods){
                                   .run = threadpreload_run,
+                                  .execve = _threadpreload_execve,
                                   .resume = threadpreload_resume,
                                   .handleProcessExit = threadpreload_handleProcessExit,
                                   .getReturnCode = threadpreload_getReturnCode,
@@ -497,6 +618,7 @@ Thread* threadpreload_new(Host* host, Process* process, gint threadID) {
                                   .free = threadpreload_free,
                                   .nativeSyscall = threadpreload_nativeSyscall,
                                   .clone = _threadpreload_clone,
+                                  .fork = _threadpreload_fork,
                                   .getIPCBlock = _threadpreload_getIPCBlock,
                               }),
     };
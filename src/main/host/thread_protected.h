@@ -19,6 +19,8 @@
 typedef struct _ThreadMethods {
     pid_t (*run)(Thread* thread, char* pluginPath, char** argv, char** envv,
                  const char* workingDir);
+    pid_t (*execve)(Thread* thread, const char* pluginPath, char** argv, char** envv,
+                    const char* workingDir);
     SysCallCondition* (*resume)(Thread* thread);
     void (*handleProcessExit)(Thread* thread);
     int (*getReturnCode)(Thread* thread);
@@ -27,6 +29,8 @@ typedef struct _ThreadMethods {
     long (*nativeSyscall)(Thread* thread, long n, va_list args);
     int (*clone)(Thread* thread, unsigned long flags, PluginPtr child_stack, PluginPtr ptid,
                  PluginPtr ctid, unsigned long newtls, Thread** child);
+    int (*fork)(Thread* thread, Process* childProcess, unsigned long flags, unsigned long newtls,
+                Thread** child);
     ShMemBlock* (*getIPCBlock)(Thread* thread);
 } ThreadMethods;
 
@@ -59,6 +63,18 @@ struct _Thread {
     // not yet been pinned to a CPU.
     int affinity;
 
+    // The set of simulated CPUs that the thread may run on, as seen by the plugin through
+    // sched_getaffinity(2). Unrelated to `affinity` above.
+    CpuAffinity* cpuAffinity;
+
+    // The restartable sequences area registered with rseq(2), or NULL. Its
+    // `cpu_id` fields are updated when `cpuAffinity` changes the thread's CPU.
+    PluginPtr rseqAddress;
+
+    // The child process created by a `vfork`-style clone that the thread is
+    // waiting on to exec or exit, or 0.
+    pid_t vforkChildID;
+
     MAGIC_DECLARE;
 };
 
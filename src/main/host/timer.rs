@@ -28,6 +28,8 @@ struct TimerInternal {
     expiration_count: u64,
     next_expire_id: u64,
     min_valid_expire_id: u64,
+    /// Set if the timer was armed with `arm_realtime`.
+    realtime_anchor: Option<RealtimeAnchor>,
     on_expire: Box<dyn Fn(&mut Host) + Send + Sync>,
 }
 
@@ -37,9 +39,19 @@ impl TimerInternal {
         self.expiration_count = 0;
         self.next_expire_time = next_expire_time;
         self.expire_interval = expire_interval;
+        self.realtime_anchor = None;
     }
 }
 
+/// One expiration of a timer that follows the host's realtime clock: the value of the realtime
+/// clock at the expiration, and the emulated time that the expiration was scheduled for. All other
+/// expirations are a multiple of the interval before or after it.
+#[derive(Copy, Clone, Debug)]
+struct RealtimeAnchor {
+    realtime: EmulatedTime,
+    emulated: EmulatedTime,
+}
+
 impl Timer {
     /// Create a new Timer that directly executes `on_expire` on
     /// expiration. `on_expire` will cause a panic if it calls mutable methods
@@ -55,6 +67,7 @@ impl Timer {
                 expiration_count: 0,
                 next_expire_id: 0,
                 min_valid_expire_id: 0,
+                realtime_anchor: None,
                 on_expire: Box::new(on_expire),
             })),
         }
@@ -151,7 +164,10 @@ impl Timer {
     ) {
         let now = Worker::current_time().unwrap();
         let delay = std::cmp::min(
-            internal_ref.next_expire_time.unwrap() - now,
+            internal_ref
+                .next_expire_time
+                .unwrap()
+                .saturating_duration_since(&now),
             SimulationTime::SECOND,
         );
         let expire_id = internal_ref.next_expire_id;
@@ -160,6 +176,16 @@ impl Timer {
         Worker::schedule_task_with_delay(task, host, delay);
     }
 
+    /// Returns the time of the next expiration if the timer is armed, or None otherwise. This
+    /// may be in the past if the timer was not periodic and has already expired.
+    pub fn next_expire_time(&self) -> Option<EmulatedTime> {
+        self.magic.debug_check();
+        self.internal.borrow().next_expire_time
+    }
+
+    /// Arm the timer to first expire at `expire_time`, and then every `expire_interval` if the
+    /// interval is non-zero. If `expire_time` has already passed, the timer expires immediately,
+    /// and a periodic timer also counts the expirations that it missed.
     pub fn arm(
         &mut self,
         host: &mut Host,
@@ -167,12 +193,104 @@ impl Timer {
         expire_interval: SimulationTime,
     ) {
         self.magic.debug_check();
-        debug_assert!(expire_time >= Worker::current_time().unwrap());
 
         let mut internal = self.internal.borrow_mut();
         internal.reset(Some(expire_time), expire_interval);
+        Self::skip_missed_expirations(&mut *internal);
+        Self::schedule_new_expire_event(&mut *internal, Arc::downgrade(&self.internal), host);
+    }
+
+    /// Like `arm`, but `expire_time` is a value of the host's realtime clock. The timer will
+    /// keep expiring at the given realtime clock values if the clock is later set or its rate
+    /// changes, as long as `realtime_clock_changed` is called when that happens.
+    pub fn arm_realtime(
+        &mut self,
+        host: &mut Host,
+        expire_time: EmulatedTime,
+        expire_interval: SimulationTime,
+    ) {
+        let emulated_expire_time = host.emulated_time_for_realtime(expire_time);
+        self.arm(host, emulated_expire_time, expire_interval);
+
+        self.internal.borrow_mut().realtime_anchor = Some(RealtimeAnchor {
+            realtime: expire_time,
+            emulated: emulated_expire_time,
+        });
+    }
+
+    /// Should be called when the host's realtime clock is set or its rate changes. If the timer
+    /// was armed with `arm_realtime` and has expirations remaining, its next expiration is moved
+    /// to the emulated time at which the realtime clock will reach the expiration's value.
+    pub fn realtime_clock_changed(&mut self, host: &mut Host) {
+        self.magic.debug_check();
+
+        let mut internal = self.internal.borrow_mut();
+
+        let anchor = match internal.realtime_anchor {
+            Some(x) => x,
+            None => return,
+        };
+
+        let next_expire_time = internal.next_expire_time.unwrap();
+        let interval = internal.expire_interval;
+
+        // a one-shot timer that has already expired
+        if interval == SimulationTime::ZERO && next_expire_time <= Worker::current_time().unwrap() {
+            return;
+        }
+
+        // the realtime clock value of the next expiration
+        let num_intervals = if interval == SimulationTime::ZERO {
+            0
+        } else {
+            (next_expire_time - anchor.emulated).as_nanos() / interval.as_nanos()
+        };
+        let realtime = anchor
+            .realtime
+            .saturating_add(interval.saturating_mul(u64::try_from(num_intervals).unwrap()));
+        let emulated = host.emulated_time_for_realtime(realtime);
+
+        internal.realtime_anchor = Some(RealtimeAnchor { realtime, emulated });
+        drop(internal);
+
+        self.reschedule(host, emulated);
+    }
+
+    /// Move the next expiration of an armed timer to `expire_time`, keeping its interval and any
+    /// expirations that haven't been consumed yet. As with `arm`, an expiration time that has
+    /// already passed expires immediately.
+    pub fn reschedule(&mut self, host: &mut Host, expire_time: EmulatedTime) {
+        self.magic.debug_check();
+
+        let mut internal = self.internal.borrow_mut();
+        assert!(internal.next_expire_time.is_some());
+
+        // invalidate the previously scheduled expiration
+        internal.min_valid_expire_id = internal.next_expire_id;
+        internal.next_expire_time = Some(expire_time);
+        Self::skip_missed_expirations(&mut *internal);
         Self::schedule_new_expire_event(&mut *internal, Arc::downgrade(&self.internal), host);
     }
+
+    /// If a periodic timer's next expiration is more than one interval in the past, count the
+    /// expirations that were missed and move the next expiration to the latest one that isn't in
+    /// the future. This way the timer expires once immediately rather than once per missed
+    /// interval, and later expirations keep their original phase.
+    fn skip_missed_expirations(internal: &mut TimerInternal) {
+        let now = Worker::current_time().unwrap();
+        let next_expire_time = internal.next_expire_time.unwrap();
+
+        if next_expire_time >= now || internal.expire_interval == SimulationTime::ZERO {
+            return;
+        }
+
+        let missed = (now - next_expire_time).as_nanos() / internal.expire_interval.as_nanos();
+        let missed = u64::try_from(missed).unwrap();
+
+        internal.expiration_count += missed;
+        internal.next_expire_time =
+            Some(next_expire_time.saturating_add(internal.expire_interval.saturating_mul(missed)));
+    }
 }
 
 pub mod export {
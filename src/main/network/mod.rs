@@ -1,4 +1,4 @@
 pub mod network_graph;
-mod packet;
+pub mod packet;
 mod petgraph_wrapper;
 mod router;
@@ -298,6 +298,8 @@ impl NetworkGraph {
 pub struct PathProperties {
     /// Latency in nanoseconds.
     pub latency_ns: u64,
+    /// Maximum additional per-packet delay (jitter) in nanoseconds.
+    pub jitter_ns: u64,
     /// Packet loss as fraction.
     pub packet_loss: f32,
 }
@@ -325,6 +327,7 @@ impl core::ops::Add for PathProperties {
     fn add(self, other: Self) -> Self::Output {
         Self {
             latency_ns: self.latency_ns + other.latency_ns,
+            jitter_ns: self.jitter_ns + other.jitter_ns,
             packet_loss: 1f32 - (1f32 - self.packet_loss) * (1f32 - other.packet_loss),
         }
     }
@@ -334,6 +337,7 @@ impl std::convert::From<&ShadowEdge> for PathProperties {
     fn from(e: &ShadowEdge) -> Self {
         Self {
             latency_ns: e.latency.convert(units::TimePrefix::Nano).unwrap().value(),
+            jitter_ns: e.jitter.convert(units::TimePrefix::Nano).unwrap().value(),
             packet_loss: e.packet_loss,
         }
     }
@@ -454,10 +458,11 @@ impl<T: Eq + Hash + std::fmt::Display + Clone + Copy> RoutingInfo<T> {
         for ((start, end), count) in self.packet_counters.read().unwrap().iter() {
             let path = self.paths.get(&(*start, *end)).unwrap();
             log::debug!(
-                "Found path {}->{}: latency={}ns, packet_loss={}, packet_count={}",
+                "Found path {}->{}: latency={}ns, jitter={}ns, packet_loss={}, packet_count={}",
                 start,
                 end,
                 path.latency_ns,
+                path.jitter_ns,
                 path.packet_loss,
                 count,
             );
@@ -509,15 +514,18 @@ mod tests {
     fn test_path_add() {
         let p1 = PathProperties {
             latency_ns: 23,
+            jitter_ns: 2,
             packet_loss: 0.35,
         };
         let p2 = PathProperties {
             latency_ns: 11,
+            jitter_ns: 5,
             packet_loss: 0.85,
         };
 
         let p3 = p1 + p2;
         assert_eq!(p3.latency_ns, 34);
+        assert_eq!(p3.jitter_ns, 7);
         assert!((p3.packet_loss - 0.9025).abs() < 0.01);
     }
 
@@ -636,6 +644,72 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_shortest_path_jitter() {
+        let graph = r#"graph [
+          directed 1
+          node [
+            id 0
+          ]
+          node [
+            id 1
+          ]
+          node [
+            id 2
+          ]
+          edge [
+            source 0
+            target 0
+            latency "1 ns"
+          ]
+          edge [
+            source 1
+            target 1
+            latency "1 ns"
+            jitter "4 ns"
+          ]
+          edge [
+            source 2
+            target 2
+            latency "1 ns"
+          ]
+          edge [
+            source 0
+            target 1
+            latency "3 ns"
+            jitter "2 ns"
+          ]
+          edge [
+            source 1
+            target 2
+            latency "5 ns"
+            jitter "7 ns"
+          ]
+          edge [
+            source 2
+            target 0
+            latency "100 ns"
+          ]
+        ]"#;
+        let graph = NetworkGraph::parse(graph).unwrap();
+        let node_0 = *graph.node_id_to_index(0).unwrap();
+        let node_1 = *graph.node_id_to_index(1).unwrap();
+        let node_2 = *graph.node_id_to_index(2).unwrap();
+
+        let shortest_paths = graph
+            .compute_shortest_paths(&[node_0, node_1, node_2])
+            .unwrap();
+
+        let lookup_jitter = |a, b| shortest_paths.get(&(a, b)).unwrap().jitter_ns;
+
+        assert_eq!(lookup_jitter(node_0, node_0), 0);
+        assert_eq!(lookup_jitter(node_1, node_1), 4);
+        assert_eq!(lookup_jitter(node_0, node_1), 2);
+        assert_eq!(lookup_jitter(node_1, node_2), 7);
+        assert_eq!(lookup_jitter(node_0, node_2), 9);
+        assert_eq!(lookup_jitter(node_2, node_1), 2);
+    }
 }
 
 mod export {
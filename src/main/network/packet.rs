@@ -1,8 +1,216 @@
 use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddrV4};
 
 use crate::cshadow as c;
 use crate::utility::pcap_writer::PacketDisplay;
 
+/// An owned reference to a C `Packet`. The packet's reference count is incremented when cloned,
+/// and decremented when dropped.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PacketRc {
+    c_ptr: *mut c::Packet,
+}
+
+// SAFETY: C packets are only ever accessed by a single host at a time (packets that are sent to
+// other hosts are copied), and the host's lock is held while accessing them.
+unsafe impl Send for PacketRc {}
+unsafe impl Sync for PacketRc {}
+
+impl PacketRc {
+    /// Create a new packet with no payload or protocol header.
+    pub fn new(host: *mut c::Host) -> Self {
+        assert!(!host.is_null());
+        Self {
+            c_ptr: unsafe { c::packet_new(host) },
+        }
+    }
+
+    /// Create a new UDP packet with a copy of `payload` as its payload.
+    pub fn new_udp(
+        host: *mut c::Host,
+        src: SocketAddrV4,
+        dst: SocketAddrV4,
+        payload: &[u8],
+    ) -> Self {
+        let packet = Self::new(host);
+
+        unsafe {
+            c::packet_setPayloadShadow(
+                packet.c_ptr,
+                host,
+                payload.as_ptr() as *const libc::c_void,
+                payload.len().try_into().unwrap(),
+            );
+            c::packet_setUDP(
+                packet.c_ptr,
+                c::ProtocolUDPFlags_PUDP_NONE,
+                u32::from(*src.ip()).to_be(),
+                src.port().to_be(),
+                u32::from(*dst.ip()).to_be(),
+                dst.port().to_be(),
+            );
+        }
+
+        packet
+    }
+
+    /// Create a new TCP packet with a copy of `payload` as its payload. The acknowledgement,
+    /// window, and timestamps can be set later using [`update_tcp()`](Self::update_tcp).
+    pub fn new_tcp(
+        host: *mut c::Host,
+        flags: c::ProtocolTCPFlags,
+        src: SocketAddrV4,
+        dst: SocketAddrV4,
+        sequence: u32,
+        payload: &[u8],
+    ) -> Self {
+        let packet = Self::new(host);
+
+        unsafe {
+            // control packets have no payload
+            if !payload.is_empty() {
+                c::packet_setPayloadShadow(
+                    packet.c_ptr,
+                    host,
+                    payload.as_ptr() as *const libc::c_void,
+                    payload.len().try_into().unwrap(),
+                );
+            }
+            c::packet_setTCP(
+                packet.c_ptr,
+                flags,
+                u32::from(*src.ip()).to_be(),
+                src.port().to_be(),
+                u32::from(*dst.ip()).to_be(),
+                dst.port().to_be(),
+                sequence,
+            );
+        }
+
+        packet
+    }
+
+    /// Set the acknowledgement, window, and timestamp fields of a TCP packet.
+    pub fn update_tcp(
+        &mut self,
+        acknowledgement: u32,
+        window: u32,
+        timestamp_value: c::SimulationTime,
+        timestamp_echo: c::SimulationTime,
+    ) {
+        unsafe {
+            c::packet_updateTCP(
+                self.c_ptr,
+                acknowledgement,
+                std::ptr::null_mut(),
+                window,
+                timestamp_value,
+                timestamp_echo,
+            )
+        };
+    }
+
+    /// Get a copy of the packet's TCP header. Panics if this isn't a TCP packet.
+    pub fn tcp_header(&self) -> TcpHeader {
+        assert_eq!(
+            unsafe { c::packet_getProtocol(self.c_ptr) },
+            c::_ProtocolType_PTCP
+        );
+
+        let header = unsafe { c::packet_getTCPHeader(self.c_ptr).as_ref() }.unwrap();
+
+        TcpHeader {
+            flags: header.flags,
+            sequence: header.sequence,
+            acknowledgement: header.acknowledgment,
+            window: header.window,
+            timestamp_value: header.timestampValue,
+            timestamp_echo: header.timestampEcho,
+        }
+    }
+
+    /// Take ownership of an existing packet reference. The reference count is not incremented, so
+    /// the caller must not use its reference after calling this function.
+    ///
+    /// SAFETY: `ptr` must be a valid packet pointer with a reference owned by the caller.
+    pub unsafe fn from_raw(ptr: *mut c::Packet) -> Self {
+        assert!(!ptr.is_null());
+        Self { c_ptr: ptr }
+    }
+
+    /// Transfer ownership of the packet reference to the caller. The reference count is not
+    /// decremented, so the caller must later call `packet_unref()`.
+    pub fn into_inner(self) -> *mut c::Packet {
+        let ptr = self.c_ptr;
+        std::mem::forget(self);
+        ptr
+    }
+
+    /// Get a pointer to the packet without transferring ownership of the reference.
+    pub fn borrow_inner(&self) -> *const c::Packet {
+        self.c_ptr
+    }
+
+    pub fn payload_size(&self) -> usize {
+        unsafe { c::packet_getPayloadSize(self.c_ptr) }
+            .try_into()
+            .unwrap()
+    }
+
+    pub fn src_address(&self) -> SocketAddrV4 {
+        let ip = unsafe { c::packet_getSourceIP(self.c_ptr) };
+        let port = unsafe { c::packet_getSourcePort(self.c_ptr) };
+        SocketAddrV4::new(Ipv4Addr::from(u32::from_be(ip)), u16::from_be(port))
+    }
+
+    pub fn dst_address(&self) -> SocketAddrV4 {
+        let ip = unsafe { c::packet_getDestinationIP(self.c_ptr) };
+        let port = unsafe { c::packet_getDestinationPort(self.c_ptr) };
+        SocketAddrV4::new(Ipv4Addr::from(u32::from_be(ip)), u16::from_be(port))
+    }
+
+    /// Copy the packet's payload to `buf`, and return the number of bytes copied.
+    pub fn copy_payload(&self, buf: &mut [u8]) -> usize {
+        let count = unsafe {
+            c::packet_copyPayloadShadow(
+                self.c_ptr,
+                0,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len().try_into().unwrap(),
+            )
+        };
+        count.try_into().unwrap()
+    }
+
+    pub fn add_delivery_status(&mut self, status: c::PacketDeliveryStatusFlags) {
+        unsafe { c::packet_addDeliveryStatus(self.c_ptr, status) };
+    }
+}
+
+/// The TCP header fields of a packet that are used by the rust TCP implementation.
+#[derive(Debug, Copy, Clone)]
+pub struct TcpHeader {
+    pub flags: c::ProtocolTCPFlags,
+    pub sequence: u32,
+    pub acknowledgement: u32,
+    pub window: u32,
+    pub timestamp_value: c::SimulationTime,
+    pub timestamp_echo: c::SimulationTime,
+}
+
+impl Clone for PacketRc {
+    fn clone(&self) -> Self {
+        unsafe { c::packet_ref(self.c_ptr) };
+        Self { c_ptr: self.c_ptr }
+    }
+}
+
+impl Drop for PacketRc {
+    fn drop(&mut self) {
+        unsafe { c::packet_unref(self.c_ptr) };
+    }
+}
+
 impl PacketDisplay for *const c::Packet {
     fn display_bytes(&self, mut writer: impl Write) -> std::io::Result<()> {
         assert!(!self.is_null());
@@ -114,6 +114,18 @@ void packet_setPayload(Packet* packet, Thread* thread, PluginVirtualPtr payload,
     packet->priority = host_getNextPacketPriority(thread_getHost(thread));
 }
 
+void packet_setPayloadShadow(Packet* packet, Host* host, const void* payload,
+                             gsize payloadLength) {
+    MAGIC_ASSERT(packet);
+    utility_assert(host);
+    utility_assert(!packet->payload);
+
+    /* the payload starts with 1 ref, which we hold */
+    packet->payload = payload_newShadow(payload, payloadLength);
+    /* application data needs a priority ordering for FIFO onto the wire */
+    packet->priority = host_getNextPacketPriority(host);
+}
+
 /* copy everything except the payload.
  * the payload will point to the same payload as the original packet.
  * the payload is protected so it is safe to send the copied packet to a different host. */
@@ -37,6 +37,9 @@ const gchar* protocol_toString(ProtocolType type);
 Packet* packet_new(Host* host);
 void packet_setPayload(Packet* packet, Thread* thread, PluginVirtualPtr payload,
                        gsize payloadLength);
+/* Set the payload to a copy of the data, which is in shadow's memory. */
+void packet_setPayloadShadow(Packet* packet, Host* host, const void* payload,
+                             gsize payloadLength);
 Packet* packet_copy(Packet* packet);
 
 void packet_ref(Packet* packet);
@@ -282,6 +282,49 @@ impl ByteQueue {
         Ok((total_copied, packet_len))
     }
 
+    /// Copy data from the front of the queue without removing it. Like [`pop()`](Self::pop), only
+    /// a single type of data will be copied per invocation. Returns a tuple containing the number
+    /// of bytes copied, the number of bytes that `pop()` would have removed from the queue
+    /// (including bytes of a packet that didn't fit in `dst`), and the chunk type.
+    pub fn peek<W: Write>(&self, mut dst: W) -> std::io::Result<(usize, usize, Option<ChunkType>)> {
+        let chunk_type = match self.bytes.front() {
+            Some(x) => x.chunk_type,
+            None => return Ok((0, 0, None)),
+        };
+
+        match chunk_type {
+            ChunkType::Stream => {
+                let mut total_copied = 0;
+
+                for chunk in self.bytes.iter() {
+                    if chunk.chunk_type != ChunkType::Stream {
+                        break;
+                    }
+
+                    let copied = match write_partial(&mut dst, chunk.data.as_ref()) {
+                        Ok(x) => x,
+                        // only return an error if no bytes have been copied yet
+                        Err(e) if e.kind() == ErrorKind::WouldBlock && total_copied > 0 => 0,
+                        Err(e) => return Err(e),
+                    };
+
+                    total_copied += copied;
+
+                    if copied < chunk.data.len() {
+                        break;
+                    }
+                }
+
+                Ok((total_copied, total_copied, Some(ChunkType::Stream)))
+            }
+            ChunkType::Packet => {
+                let bytes = self.bytes.front().unwrap().data.as_ref();
+                let copied = write_partial(&mut dst, bytes)?;
+                Ok((copied, bytes.len(), Some(ChunkType::Packet)))
+            }
+        }
+    }
+
     /// Pop a single chunk of data from the queue. The `size_hint` argument is used to limit the
     /// number of bytes in the returned chunk iff the next chunk has stream data. If the returned
     /// chunk has packet data, the `size_hint` is ignored and the entire packet is returned.
@@ -308,6 +351,30 @@ impl ByteQueue {
     }
 }
 
+/// Write as many bytes as possible to `dst`, stopping early if `dst` has no more space. A
+/// `WouldBlock` error is only returned if no bytes were written.
+pub fn write_partial<W: Write>(mut dst: W, bytes: &[u8]) -> std::io::Result<usize> {
+    let mut total_copied = 0;
+
+    while total_copied < bytes.len() {
+        let copied = match dst.write(&bytes[total_copied..]) {
+            Ok(x) => x,
+            // may have been interrupted due to a signal, so try again
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) if e.kind() == ErrorKind::WouldBlock && total_copied > 0 => break,
+            Err(e) => return Err(e),
+        };
+
+        if copied == 0 {
+            break;
+        }
+
+        total_copied += copied;
+    }
+
+    Ok(total_copied)
+}
+
 // a sanity check only when using debug mode
 #[cfg(debug_assertions)]
 impl std::ops::Drop for ByteQueue {
@@ -527,6 +594,73 @@ mod tests {
         assert_eq!(bq.num_bytes(), 0);
     }
 
+    #[test]
+    fn test_bytequeue_peek_stream() {
+        let mut bq = ByteQueue::new(5);
+
+        bq.push_stream(&[1, 2, 3, 4, 5, 6, 7][..]).unwrap();
+        bq.push_packet(&[8, 9][..], 2).unwrap();
+
+        let mut buf = [0; 20];
+
+        // a peek spans multiple stream chunks, but stops at the packet
+        assert_eq!(
+            bq.peek(&mut buf[..]).unwrap(),
+            (7, 7, Some(ChunkType::Stream))
+        );
+        assert_eq!(buf[..7], [1, 2, 3, 4, 5, 6, 7]);
+
+        buf = [0; 20];
+        assert_eq!(
+            bq.peek(&mut buf[..3]).unwrap(),
+            (3, 3, Some(ChunkType::Stream))
+        );
+        assert_eq!(buf[..4], [1, 2, 3, 0]);
+
+        // nothing was removed
+        assert_eq!(bq.num_bytes(), 9);
+        assert_eq!(
+            bq.pop(&mut buf[..]).unwrap(),
+            (7, 7, Some(ChunkType::Stream))
+        );
+        assert_eq!(buf[..7], [1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_bytequeue_peek_packet() {
+        let mut bq = ByteQueue::new(5);
+
+        assert_eq!(bq.peek(&mut [0; 20][..]).unwrap(), (0, 0, None));
+
+        bq.push_packet(&[1, 2, 3, 4, 5, 6, 7][..], 7).unwrap();
+        bq.push_packet(&[][..], 0).unwrap();
+
+        let mut buf = [0; 20];
+
+        assert_eq!(
+            bq.peek(&mut buf[..4]).unwrap(),
+            (4, 7, Some(ChunkType::Packet))
+        );
+        assert_eq!(buf[..5], [1, 2, 3, 4, 0]);
+
+        assert_eq!(
+            bq.peek(&mut buf[..]).unwrap(),
+            (7, 7, Some(ChunkType::Packet))
+        );
+        assert_eq!(buf[..7], [1, 2, 3, 4, 5, 6, 7]);
+
+        // nothing was removed
+        assert_eq!(bq.num_bytes(), 7);
+        assert_eq!(
+            bq.pop(&mut buf[..]).unwrap(),
+            (7, 7, Some(ChunkType::Packet))
+        );
+        assert_eq!(
+            bq.peek(&mut buf[..]).unwrap(),
+            (0, 0, Some(ChunkType::Packet))
+        );
+    }
+
     #[test]
     fn test_bytequeue_combined_1() {
         let mut bq = ByteQueue::new(10);
@@ -242,6 +242,9 @@ impl ChildPidWatcher {
     ///
     /// Be sure to close the parent's write-end of the pipe.
     ///
+    /// Alternatively `read_fd` may be a pidfd for `pid` (see `pidfd_open(2)`), which
+    /// becomes readable when `pid` exits.
+    ///
     /// Takes ownership of `read_fd`, and will close it when appropriate.
     pub fn register_pid(&self, pid: Pid, read_fd: File) {
         let mut inner = self.inner.lock().unwrap();
@@ -255,7 +258,9 @@ impl ChildPidWatcher {
             },
         );
         assert!(prev.is_none());
-        let mut event = EpollEvent::new(EpollFlags::empty(), pid.as_raw().try_into().unwrap());
+        // The read end of a pipe reports EPOLLHUP (which doesn't need to be requested) when
+        // the write end is closed, whereas a pidfd reports EPOLLIN when the process exits.
+        let mut event = EpollEvent::new(EpollFlags::EPOLLIN, pid.as_raw().try_into().unwrap());
         epoll_ctl(
             self.epoll,
             EpollOp::EpollCtlAdd,
@@ -450,6 +455,58 @@ mod tests {
         assert_eq!(waitpid(child, None).unwrap(), WaitStatus::Exited(child, 42));
     }
 
+    #[test]
+    fn register_pidfd() {
+        let notifier = nix::sys::eventfd::eventfd(0, nix::sys::eventfd::EfdFlags::empty()).unwrap();
+
+        let child = match unsafe { nix::unistd::fork() }.unwrap() {
+            nix::unistd::ForkResult::Parent { child } => child,
+            nix::unistd::ForkResult::Child => {
+                let mut buf = [0; 8];
+                // Wait for parent to register its callback.
+                nix::unistd::read(notifier, &mut buf).unwrap();
+                unsafe { libc::_exit(42) };
+            }
+        };
+
+        let pidfd = unsafe { libc::syscall(libc::SYS_pidfd_open, child.as_raw(), 0) };
+        assert!(pidfd >= 0);
+
+        let watcher = ChildPidWatcher::new();
+        watcher.register_pid(child, unsafe { File::from_raw_fd(pidfd as i32) });
+
+        let callback_ran = Arc::new((Mutex::new(false), Condvar::new()));
+        {
+            let callback_ran = callback_ran.clone();
+            watcher.register_callback(
+                child,
+                Box::new(move |pid| {
+                    assert_eq!(pid, child);
+                    *callback_ran.0.lock().unwrap() = true;
+                    callback_ran.1.notify_all();
+                }),
+            );
+        }
+
+        // Callback shouldn't have run yet.
+        assert!(!*callback_ran.0.lock().unwrap());
+
+        // Let the child exit.
+        nix::unistd::write(notifier, &1u64.to_ne_bytes()).unwrap();
+
+        // Wait for our callback to run.
+        let mut callback_ran_lock = callback_ran.0.lock().unwrap();
+        while !*callback_ran_lock {
+            callback_ran_lock = callback_ran.1.wait(callback_ran_lock).unwrap();
+        }
+
+        // A pidfd only becomes readable once the child is reapable.
+        assert_eq!(
+            waitpid(child, Some(WaitPidFlag::WNOHANG)).unwrap(),
+            WaitStatus::Exited(child, 42)
+        );
+    }
+
     #[test]
     fn register_multiple() {
         let cb1_ran = Arc::new((Mutex::new(false), Condvar::new()));
@@ -588,6 +645,9 @@ mod export {
     ///
     /// Be sure to close the parent's write-end of the pipe.
     ///
+    /// Alternatively `read_fd` may be a pidfd for `pid` (see `pidfd_open(2)`), which
+    /// becomes readable when `pid` exits.
+    ///
     /// Takes ownership of `read_fd`, and will close it when appropriate.
     #[no_mangle]
     pub unsafe extern "C" fn childpidwatcher_registerPid(
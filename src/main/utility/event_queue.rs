@@ -123,6 +123,11 @@ impl<T: Clone + Copy + 'static> EventSource<T> {
         self.inner.borrow_mut().add_listener(inner_ref, notify_fn)
     }
 
+    /// Returns true if there is at least one listener.
+    pub fn has_listeners(&self) -> bool {
+        !self.inner.borrow().listeners.is_empty()
+    }
+
     /// Notify all listeners.
     pub fn notify_listeners(&mut self, message: T, event_queue: &mut EventQueue) {
         for (_, l) in &self.inner.borrow().listeners {
@@ -0,0 +1,317 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::sync::mpsc;
+
+use nix::errno::Errno;
+use nix::fcntl::{self, OFlag};
+use nix::sched::{sched_getaffinity, sched_setaffinity, CpuSet};
+use nix::sys::stat::Mode;
+use nix::unistd::{self, Pid};
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+// the cpu count set in 'affinity.yaml'
+const CONFIGURED_CPU_COUNT: usize = 4;
+
+/// From the linux headers. Not exposed by libc.
+const RSEQ_FLAG_UNREGISTER: libc::c_int = 1;
+const RSEQ_CPU_ID_UNINITIALIZED: i32 = -1;
+const RSEQ_SIG: u32 = 0x53053053;
+
+/// linux's `struct rseq`.
+#[repr(C, align(32))]
+#[derive(Debug, Default)]
+struct Rseq {
+    cpu_id_start: u32,
+    cpu_id: i32,
+    rseq_cs: u64,
+    flags: u32,
+}
+
+/// The CPUs in the affinity mask of thread `pid`.
+fn affinity_cpus(pid: Pid) -> nix::Result<Vec<usize>> {
+    let mask = sched_getaffinity(pid)?;
+    Ok((0..CpuSet::count())
+        .filter(|x| mask.is_set(*x).unwrap())
+        .collect())
+}
+
+fn cpu_set(cpus: &[usize]) -> CpuSet {
+    let mut mask = CpuSet::new();
+    for cpu in cpus {
+        mask.set(*cpu).unwrap();
+    }
+    mask
+}
+
+fn sched_getcpu() -> nix::Result<usize> {
+    let rv = unsafe { libc::sched_getcpu() };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+// use the syscall directly since the libc wrapper returns 0 rather than the mask size
+fn sched_getaffinity_raw(pid: libc::pid_t, buf: &mut [u8]) -> nix::Result<usize> {
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_sched_getaffinity,
+            pid,
+            buf.len(),
+            buf.as_mut_ptr(),
+        )
+    };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn rseq(area: &mut Rseq, flags: libc::c_int) -> nix::Result<()> {
+    Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_rseq,
+            area as *mut Rseq,
+            std::mem::size_of::<Rseq>() as u32,
+            flags,
+            RSEQ_SIG,
+        )
+    })
+    .map(|_| ())
+}
+
+/// Read the cpu fields of an rseq area, which are written by the kernel (or shadow).
+fn rseq_cpus(area: &Rseq) -> (u32, i32) {
+    unsafe {
+        (
+            std::ptr::read_volatile(&area.cpu_id_start),
+            std::ptr::read_volatile(&area.cpu_id),
+        )
+    }
+}
+
+fn test_configured_cpus() -> anyhow::Result<()> {
+    let all_cpus: Vec<usize> = (0..CONFIGURED_CPU_COUNT).collect();
+    ensure_ord!(affinity_cpus(Pid::from_raw(0)), ==, Ok(all_cpus));
+
+    ensure_ord!(
+        unsafe { libc::get_nprocs() },
+        ==,
+        CONFIGURED_CPU_COUNT as libc::c_int
+    );
+    ensure_ord!(
+        unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) },
+        ==,
+        CONFIGURED_CPU_COUNT as libc::c_long
+    );
+
+    // we always run on the lowest cpu in the mask
+    ensure_ord!(sched_getcpu(), ==, Ok(0));
+
+    Ok(())
+}
+
+fn test_sys_cpu_files() -> anyhow::Result<()> {
+    for name in ["online", "possible", "present"] {
+        let path = format!("/sys/devices/system/cpu/{}", name);
+        let contents = std::fs::read_to_string(&path)?;
+        ensure_ord!(contents, ==, format!("0-{}\n", CONFIGURED_CPU_COUNT - 1));
+
+        // the files are read-only
+        ensure_ord!(
+            fcntl::open(path.as_str(), OFlag::O_WRONLY, Mode::empty()),
+            ==,
+            Err(Errno::EACCES)
+        );
+    }
+
+    Ok(())
+}
+
+fn test_set_affinity() -> anyhow::Result<()> {
+    let original = sched_getaffinity(Pid::from_raw(0))?;
+    let cpu = *affinity_cpus(Pid::from_raw(0))?.last().unwrap();
+
+    sched_setaffinity(Pid::from_raw(0), &cpu_set(&[cpu]))?;
+    ensure_ord!(affinity_cpus(Pid::from_raw(0)), ==, Ok(vec![cpu]));
+    ensure_ord!(sched_getcpu(), ==, Ok(cpu));
+
+    // our own tid is the same as 0
+    let tid = unistd::gettid();
+    sched_setaffinity(tid, &original)?;
+    ensure_ord!(sched_getaffinity(tid), ==, Ok(original));
+
+    Ok(())
+}
+
+fn test_invalid_mask() -> anyhow::Result<()> {
+    let original = sched_getaffinity(Pid::from_raw(0))?;
+
+    // a mask with no cpus, or with only cpus that don't exist
+    ensure_ord!(
+        sched_setaffinity(Pid::from_raw(0), &CpuSet::new()),
+        ==,
+        Err(Errno::EINVAL)
+    );
+    ensure_ord!(
+        sched_setaffinity(Pid::from_raw(0), &cpu_set(&[CpuSet::count() - 1])),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    // the mask didn't change
+    ensure_ord!(sched_getaffinity(Pid::from_raw(0)), ==, Ok(original));
+
+    Ok(())
+}
+
+fn test_mask_size() -> anyhow::Result<()> {
+    // the buffer must be large enough for all cpus and a multiple of the word size
+    ensure_ord!(sched_getaffinity_raw(0, &mut []), ==, Err(Errno::EINVAL));
+    ensure_ord!(
+        sched_getaffinity_raw(0, &mut [0u8; 127]),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    // the syscall returns the size of the kernel's mask
+    let mut buf = [0u8; 128];
+    let size = sched_getaffinity_raw(0, &mut buf)?;
+    ensure_ord!(size, >, 0);
+    ensure_ord!(size % std::mem::size_of::<libc::c_ulong>(), ==, 0);
+
+    Ok(())
+}
+
+fn test_missing_thread() -> anyhow::Result<()> {
+    let pid = Pid::from_raw(libc::pid_t::MAX);
+    ensure_ord!(sched_getaffinity(pid), ==, Err(Errno::ESRCH));
+    ensure_ord!(
+        sched_setaffinity(pid, &cpu_set(&[0])),
+        ==,
+        Err(Errno::ESRCH)
+    );
+    Ok(())
+}
+
+fn test_thread_inherits() -> anyhow::Result<()> {
+    let original = sched_getaffinity(Pid::from_raw(0))?;
+    let cpu = *affinity_cpus(Pid::from_raw(0))?.last().unwrap();
+
+    sched_setaffinity(Pid::from_raw(0), &cpu_set(&[cpu]))?;
+    let handle = std::thread::spawn(|| affinity_cpus(Pid::from_raw(0)));
+    sched_setaffinity(Pid::from_raw(0), &original)?;
+
+    // the new thread has the mask of the thread that created it
+    ensure_ord!(handle.join().unwrap(), ==, Ok(vec![cpu]));
+
+    Ok(())
+}
+
+fn test_other_thread() -> anyhow::Result<()> {
+    let cpu = *affinity_cpus(Pid::from_raw(0))?.last().unwrap();
+
+    let (tid_send, tid_recv) = mpsc::channel();
+    let (done_send, done_recv) = mpsc::channel::<()>();
+
+    let handle = std::thread::spawn(move || {
+        tid_send.send(unistd::gettid()).unwrap();
+        done_recv.recv().unwrap();
+        affinity_cpus(Pid::from_raw(0))
+    });
+
+    // change the other thread's mask
+    let tid = tid_recv.recv()?;
+    sched_setaffinity(tid, &cpu_set(&[cpu]))?;
+    ensure_ord!(affinity_cpus(tid), ==, Ok(vec![cpu]));
+    done_send.send(())?;
+
+    ensure_ord!(handle.join().unwrap(), ==, Ok(vec![cpu]));
+
+    Ok(())
+}
+
+fn test_rseq_cpu() -> anyhow::Result<()> {
+    // use a new thread, since libc may have registered its own rseq area for the main thread
+    std::thread::spawn(|| -> anyhow::Result<()> {
+        let cpu = *affinity_cpus(Pid::from_raw(0))?.last().unwrap();
+
+        let mut area = Box::new(Rseq::default());
+        rseq(&mut area, 0)?;
+        ensure_ord!(rseq_cpus(&area), ==, (0, 0));
+
+        // the area is updated when the thread moves to a different cpu
+        sched_setaffinity(Pid::from_raw(0), &cpu_set(&[cpu]))?;
+        ensure_ord!(rseq_cpus(&area), ==, (cpu as u32, cpu as i32));
+
+        sched_setaffinity(Pid::from_raw(0), &cpu_set(&[0, cpu]))?;
+        ensure_ord!(rseq_cpus(&area), ==, (0, 0));
+
+        // only the registered area can be unregistered
+        let mut other = Box::new(Rseq::default());
+        ensure_ord!(
+            rseq(&mut other, RSEQ_FLAG_UNREGISTER),
+            ==,
+            Err(Errno::EINVAL)
+        );
+
+        rseq(&mut area, RSEQ_FLAG_UNREGISTER)?;
+        ensure_ord!(rseq_cpus(&area), ==, (0, RSEQ_CPU_ID_UNINITIALIZED));
+
+        // the area is no longer updated
+        sched_setaffinity(Pid::from_raw(0), &cpu_set(&[cpu]))?;
+        ensure_ord!(rseq_cpus(&area), ==, (0, RSEQ_CPU_ID_UNINITIALIZED));
+
+        Ok(())
+    })
+    .join()
+    .unwrap()
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        // the native cpus depend on the system
+        ShadowTest::new(
+            "configured-cpus",
+            test_configured_cpus,
+            set![TestEnvironment::Shadow],
+        ),
+        ShadowTest::new(
+            "sys-cpu-files",
+            test_sys_cpu_files,
+            set![TestEnvironment::Shadow],
+        ),
+        ShadowTest::new("set-affinity", test_set_affinity, all_envs.clone()),
+        ShadowTest::new("invalid-mask", test_invalid_mask, all_envs.clone()),
+        ShadowTest::new("mask-size", test_mask_size, all_envs.clone()),
+        ShadowTest::new("missing-thread", test_missing_thread, all_envs.clone()),
+        ShadowTest::new("thread-inherits", test_thread_inherits, all_envs.clone()),
+        ShadowTest::new("other-thread", test_other_thread, all_envs.clone()),
+        // libc may have already registered an rseq area for each thread, in which case linux
+        // doesn't allow registering another
+        ShadowTest::new("rseq-cpu", test_rseq_cpu, set![TestEnvironment::Shadow]),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
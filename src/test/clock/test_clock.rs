@@ -0,0 +1,642 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::unistd;
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+// the clock options set in 'clock.yaml'
+/// 2030-06-15T10:00:00Z
+const CONFIGURED_START: Duration = Duration::from_secs(1907748000);
+const CONFIGURED_OFFSET: Duration = Duration::from_secs(60 * 60);
+const CONFIGURED_DRIFT_PPM: i128 = 1000;
+
+/// From the linux headers. Not exposed by libc.
+const TFD_TIMER_CANCEL_ON_SET: libc::c_int = 1 << 1;
+const ADJ_OFFSET: libc::c_uint = 0x0001;
+const ADJ_FREQUENCY: libc::c_uint = 0x0002;
+const ADJ_MAXERROR: libc::c_uint = 0x0004;
+const ADJ_ESTERROR: libc::c_uint = 0x0008;
+const ADJ_STATUS: libc::c_uint = 0x0010;
+const ADJ_TIMECONST: libc::c_uint = 0x0020;
+const ADJ_SETOFFSET: libc::c_uint = 0x0100;
+const ADJ_MICRO: libc::c_uint = 0x1000;
+const ADJ_NANO: libc::c_uint = 0x2000;
+const ADJ_TICK: libc::c_uint = 0x4000;
+const ADJ_OFFSET_SINGLESHOT: libc::c_uint = 0x8001;
+const ADJ_OFFSET_SS_READ: libc::c_uint = 0xa001;
+const STA_PLL: libc::c_int = 0x0001;
+const STA_UNSYNC: libc::c_int = 0x0040;
+const TIME_OK: libc::c_int = 0;
+const TIME_ERROR: libc::c_int = 5;
+
+/// In shadow the monotonic clock starts at the same time as the simulation's internal clock,
+/// 2000-01-01T00:00:00Z, regardless of the configured start date and time.
+const SHADOW_MONOTONIC_START: Duration = Duration::from_secs(946684800);
+
+/// The largest frequency adjustment accepted by `adjtimex`, in ppm with a 16-bit fractional part.
+const MAX_FREQUENCY_SCALED: libc::c_long = 500 << 16;
+
+fn clock_gettime(clock_id: libc::clockid_t) -> Duration {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    Errno::result(unsafe { libc::clock_gettime(clock_id, &mut ts) }).unwrap();
+    timespec_to_duration(&ts)
+}
+
+fn clock_settime(clock_id: libc::clockid_t, ts: libc::timespec) -> nix::Result<()> {
+    Errno::result(unsafe { libc::clock_settime(clock_id, &ts) }).map(|_| ())
+}
+
+fn settimeofday(tv: libc::timeval) -> nix::Result<()> {
+    Errno::result(unsafe { libc::settimeofday(&tv, std::ptr::null()) }).map(|_| ())
+}
+
+fn adjtimex(buf: &mut libc::timex) -> nix::Result<libc::c_int> {
+    Errno::result(unsafe { libc::adjtimex(buf) })
+}
+
+fn empty_timex() -> libc::timex {
+    unsafe { std::mem::zeroed() }
+}
+
+fn duration_to_timespec(d: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: d.as_secs().try_into().unwrap(),
+        tv_nsec: d.subsec_nanos().into(),
+    }
+}
+
+fn timespec_to_duration(ts: &libc::timespec) -> Duration {
+    Duration::new(
+        ts.tv_sec.try_into().unwrap(),
+        ts.tv_nsec.try_into().unwrap(),
+    )
+}
+
+fn timerfd_create(clock_id: libc::clockid_t, flags: libc::c_int) -> nix::Result<libc::c_int> {
+    Errno::result(unsafe { libc::timerfd_create(clock_id, flags) })
+}
+
+fn timerfd_settime_abs(fd: libc::c_int, flags: libc::c_int, value: Duration) -> nix::Result<()> {
+    let new_value = libc::itimerspec {
+        it_value: duration_to_timespec(value),
+        it_interval: duration_to_timespec(Duration::ZERO),
+    };
+    let flags = flags | libc::TFD_TIMER_ABSTIME;
+    Errno::result(unsafe { libc::timerfd_settime(fd, flags, &new_value, std::ptr::null_mut()) })
+        .map(|_| ())
+}
+
+// We use the raw timer syscalls rather than the libc wrappers, since libc's `timer_t` isn't the
+// kernel's timer id.
+
+fn timer_create_realtime() -> nix::Result<libc::c_int> {
+    let mut sev: libc::sigevent = unsafe { std::mem::zeroed() };
+    sev.sigev_notify = libc::SIGEV_NONE;
+    let mut timer_id: libc::c_int = -1;
+    Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_timer_create,
+            libc::CLOCK_REALTIME,
+            &sev as *const libc::sigevent,
+            &mut timer_id as *mut libc::c_int,
+        )
+    })?;
+    Ok(timer_id)
+}
+
+fn timer_settime_abs(timer_id: libc::c_int, value: Duration) -> nix::Result<()> {
+    let new_value = libc::itimerspec {
+        it_value: duration_to_timespec(value),
+        it_interval: duration_to_timespec(Duration::ZERO),
+    };
+    Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_timer_settime,
+            timer_id,
+            libc::TIMER_ABSTIME,
+            &new_value as *const libc::itimerspec,
+            std::ptr::null_mut::<libc::itimerspec>(),
+        )
+    })
+    .map(|_| ())
+}
+
+/// Returns the time until the timer expires.
+fn timer_gettime(timer_id: libc::c_int) -> nix::Result<Duration> {
+    let mut value: libc::itimerspec = unsafe { std::mem::zeroed() };
+    Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_timer_gettime,
+            timer_id,
+            &mut value as *mut libc::itimerspec,
+        )
+    })?;
+    Ok(timespec_to_duration(&value.it_value))
+}
+
+fn timer_delete(timer_id: libc::c_int) -> nix::Result<()> {
+    Errno::result(unsafe { libc::syscall(libc::SYS_timer_delete, timer_id) })?;
+    Ok(())
+}
+
+/// Slew the clock by `offset` microseconds like `adjtime`, or only get the remaining offset if
+/// `offset` is `None`. Returns the offset that was still to be applied.
+fn adjtime(offset: Option<libc::c_long>) -> nix::Result<libc::c_long> {
+    let mut buf = empty_timex();
+    match offset {
+        Some(offset) => {
+            buf.modes = ADJ_OFFSET_SINGLESHOT;
+            buf.offset = offset;
+        }
+        None => buf.modes = ADJ_OFFSET_SS_READ,
+    }
+    adjtimex(&mut buf)?;
+    Ok(buf.offset)
+}
+
+/// Returns the expiration count.
+fn timerfd_read(fd: libc::c_int) -> nix::Result<u64> {
+    let mut buf = [0u8; 8];
+    let num_bytes = unistd::read(fd, &mut buf)?;
+    assert_eq!(num_bytes, buf.len());
+    Ok(u64::from_ne_bytes(buf))
+}
+
+/// The difference in nanoseconds between how much the realtime and monotonic clocks advance
+/// during `sleep`.
+fn measure_drift_nanos(sleep: Duration) -> (i128, i128) {
+    let realtime_start = clock_gettime(libc::CLOCK_REALTIME);
+    let monotonic_start = clock_gettime(libc::CLOCK_MONOTONIC);
+    std::thread::sleep(sleep);
+    let realtime_end = clock_gettime(libc::CLOCK_REALTIME);
+    let monotonic_end = clock_gettime(libc::CLOCK_MONOTONIC);
+
+    let realtime_elapsed = (realtime_end - realtime_start).as_nanos() as i128;
+    let monotonic_elapsed = (monotonic_end - monotonic_start).as_nanos() as i128;
+    (realtime_elapsed - monotonic_elapsed, monotonic_elapsed)
+}
+
+fn test_offset() -> anyhow::Result<()> {
+    let realtime = clock_gettime(libc::CLOCK_REALTIME);
+    let monotonic = clock_gettime(libc::CLOCK_MONOTONIC);
+
+    let realtime_since_start = realtime - CONFIGURED_START;
+    let monotonic_since_start = monotonic - SHADOW_MONOTONIC_START;
+
+    // the difference also includes the drift since the start of the simulation
+    ensure_ord!(
+        realtime_since_start - monotonic_since_start,
+        >=,
+        CONFIGURED_OFFSET
+    );
+    ensure_ord!(
+        realtime_since_start - monotonic_since_start,
+        <,
+        CONFIGURED_OFFSET + Duration::from_secs(1)
+    );
+
+    Ok(())
+}
+
+fn test_start_datetime() -> anyhow::Result<()> {
+    let time = unsafe { libc::time(std::ptr::null_mut()) };
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    assert!(!unsafe { libc::gmtime_r(&time, &mut tm) }.is_null());
+
+    // the process starts one second after the configured start time and offset
+    ensure_ord!(
+        (tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday),
+        ==,
+        (2030, 6, 15)
+    );
+    ensure_ord!((tm.tm_hour, tm.tm_min, tm.tm_sec), ==, (11, 0, 1));
+
+    Ok(())
+}
+
+fn test_drift() -> anyhow::Result<()> {
+    let (drift, monotonic_elapsed) = measure_drift_nanos(Duration::from_secs(1));
+    let expected = monotonic_elapsed * CONFIGURED_DRIFT_PPM / 1_000_000;
+    ensure_ord!((drift - expected).abs(), <=, 1_000);
+    Ok(())
+}
+
+fn test_realtime_clocks() -> anyhow::Result<()> {
+    let realtime = clock_gettime(libc::CLOCK_REALTIME);
+
+    let coarse = clock_gettime(libc::CLOCK_REALTIME_COARSE);
+    ensure_ord!(coarse.as_secs().abs_diff(realtime.as_secs()), <=, 1);
+
+    let time = unsafe { libc::time(std::ptr::null_mut()) };
+    ensure_ord!((time as u64).abs_diff(realtime.as_secs()), <=, 1);
+
+    let mut tv = libc::timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
+    Errno::result(unsafe { libc::gettimeofday(&mut tv, std::ptr::null_mut()) })?;
+    ensure_ord!((tv.tv_sec as u64).abs_diff(realtime.as_secs()), <=, 1);
+
+    Ok(())
+}
+
+fn test_settime_errors() -> anyhow::Result<()> {
+    let now = duration_to_timespec(clock_gettime(libc::CLOCK_REALTIME));
+
+    // only the realtime clock can be set
+    ensure_ord!(
+        clock_settime(libc::CLOCK_MONOTONIC, now),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    let invalid = libc::timespec {
+        tv_sec: now.tv_sec,
+        tv_nsec: 1_000_000_000,
+    };
+    ensure_ord!(
+        clock_settime(libc::CLOCK_REALTIME, invalid),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    Ok(())
+}
+
+fn test_settime() -> anyhow::Result<()> {
+    let monotonic_before = clock_gettime(libc::CLOCK_MONOTONIC);
+
+    let target = clock_gettime(libc::CLOCK_REALTIME) + Duration::from_secs(24 * 60 * 60);
+    clock_settime(libc::CLOCK_REALTIME, duration_to_timespec(target))?;
+    let realtime = clock_gettime(libc::CLOCK_REALTIME);
+    ensure_ord!(realtime, >=, target);
+    ensure_ord!(realtime, <, target + Duration::from_secs(1));
+
+    let target = target - Duration::from_secs(2 * 24 * 60 * 60);
+    settimeofday(libc::timeval {
+        tv_sec: target.as_secs().try_into().unwrap(),
+        tv_usec: target.subsec_micros().into(),
+    })?;
+    let realtime = clock_gettime(libc::CLOCK_REALTIME);
+    ensure_ord!(realtime, >=, target);
+    ensure_ord!(realtime, <, target + Duration::from_secs(1));
+
+    // the monotonic clock isn't affected
+    let monotonic_after = clock_gettime(libc::CLOCK_MONOTONIC);
+    ensure_ord!(monotonic_after, >=, monotonic_before);
+    ensure_ord!(
+        monotonic_after,
+        <,
+        monotonic_before + Duration::from_secs(1)
+    );
+
+    Ok(())
+}
+
+fn test_adjtimex_frequency() -> anyhow::Result<()> {
+    let mut buf = empty_timex();
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.freq, ==, 0);
+
+    // the adjustment is clamped
+    let mut buf = empty_timex();
+    buf.modes = ADJ_FREQUENCY;
+    buf.freq = -4 * MAX_FREQUENCY_SCALED;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.freq, ==, -MAX_FREQUENCY_SCALED);
+
+    // the adjustment is applied in addition to the configured drift
+    let (drift, monotonic_elapsed) = measure_drift_nanos(Duration::from_secs(1));
+    let expected = monotonic_elapsed * (CONFIGURED_DRIFT_PPM - 500) / 1_000_000;
+    ensure_ord!((drift - expected).abs(), <=, 1_000);
+
+    let mut buf = empty_timex();
+    buf.modes = ADJ_FREQUENCY;
+    buf.freq = 0;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.freq, ==, 0);
+
+    Ok(())
+}
+
+fn test_adjtimex_setoffset() -> anyhow::Result<()> {
+    let before = clock_gettime(libc::CLOCK_REALTIME);
+
+    let mut buf = empty_timex();
+    buf.modes = ADJ_SETOFFSET | ADJ_NANO;
+    buf.time.tv_sec = 10;
+    buf.time.tv_usec = 500_000_000;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+
+    let after = clock_gettime(libc::CLOCK_REALTIME);
+    ensure_ord!(after - before, >=, Duration::from_millis(10_500));
+    ensure_ord!(after - before, <, Duration::from_millis(11_500));
+
+    // the fractional part must be normalized
+    let mut buf = empty_timex();
+    buf.modes = ADJ_SETOFFSET;
+    buf.time.tv_usec = -1;
+    ensure_ord!(adjtimex(&mut buf), ==, Err(Errno::EINVAL));
+
+    Ok(())
+}
+
+fn test_cancel_on_set() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_REALTIME, libc::TFD_NONBLOCK)?;
+
+    let now = clock_gettime(libc::CLOCK_REALTIME);
+    timerfd_settime_abs(fd, TFD_TIMER_CANCEL_ON_SET, now + Duration::from_secs(10))?;
+
+    clock_settime(
+        libc::CLOCK_REALTIME,
+        duration_to_timespec(now + Duration::from_secs(1)),
+    )?;
+
+    // the timer was canceled once
+    ensure_ord!(timerfd_read(fd), ==, Err(Errno::ECANCELED));
+    ensure_ord!(timerfd_read(fd), ==, Err(Errno::EAGAIN));
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_abs_realtime_timer() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_REALTIME, 0)?;
+
+    let monotonic_start = clock_gettime(libc::CLOCK_MONOTONIC);
+    let target = clock_gettime(libc::CLOCK_REALTIME) + Duration::from_secs(1);
+    timerfd_settime_abs(fd, 0, target)?;
+    ensure_ord!(timerfd_read(fd), ==, Ok(1));
+
+    // the timer expires when the host's realtime clock reaches the target, which is sooner than
+    // one second of simulated time since the clock runs fast
+    ensure_ord!(clock_gettime(libc::CLOCK_REALTIME), >=, target);
+    let monotonic_elapsed = clock_gettime(libc::CLOCK_MONOTONIC) - monotonic_start;
+    ensure_ord!(monotonic_elapsed, <, Duration::from_secs(1));
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_adjtimex_status() -> anyhow::Result<()> {
+    let mut buf = empty_timex();
+    buf.modes = ADJ_STATUS | ADJ_MAXERROR | ADJ_ESTERROR | ADJ_TIMECONST | ADJ_MICRO;
+    buf.status = STA_UNSYNC;
+    buf.maxerror = 2_000;
+    buf.esterror = 1_000;
+    buf.constant = 2;
+    // an unsynchronized clock is reported as an error
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_ERROR));
+    ensure_ord!(buf.status, ==, STA_UNSYNC);
+    ensure_ord!(buf.maxerror, ==, 2_000);
+    ensure_ord!(buf.esterror, ==, 1_000);
+    // the time constant is 4 larger in microsecond mode
+    ensure_ord!(buf.constant, ==, 6);
+
+    // the values are kept
+    let mut buf = empty_timex();
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_ERROR));
+    ensure_ord!(buf.status, ==, STA_UNSYNC);
+    ensure_ord!(buf.maxerror, ==, 2_000);
+    ensure_ord!(buf.esterror, ==, 1_000);
+    ensure_ord!(buf.constant, ==, 6);
+
+    let mut buf = empty_timex();
+    buf.modes = ADJ_STATUS;
+    buf.status = 0;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.status, ==, 0);
+
+    Ok(())
+}
+
+fn test_adjtimex_tick() -> anyhow::Result<()> {
+    let mut buf = empty_timex();
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.tick, ==, 10_000);
+
+    // the tick must be within 10% of its nominal value
+    for tick in [8_999, 11_001] {
+        let mut buf = empty_timex();
+        buf.modes = ADJ_TICK;
+        buf.tick = tick;
+        ensure_ord!(adjtimex(&mut buf), ==, Err(Errno::EINVAL));
+    }
+
+    // a 1% longer tick makes the clock run 1% faster, in addition to the configured drift
+    let mut buf = empty_timex();
+    buf.modes = ADJ_TICK;
+    buf.tick = 10_100;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.tick, ==, 10_100);
+    ensure_ord!(buf.freq, ==, 0);
+
+    let (drift, monotonic_elapsed) = measure_drift_nanos(Duration::from_millis(100));
+    let expected = monotonic_elapsed * (CONFIGURED_DRIFT_PPM + 10_000) / 1_000_000;
+    ensure_ord!((drift - expected).abs(), <=, 1_000);
+
+    let mut buf = empty_timex();
+    buf.modes = ADJ_TICK;
+    buf.tick = 10_000;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+
+    Ok(())
+}
+
+fn test_adjtimex_offset() -> anyhow::Result<()> {
+    // the offset is ignored unless the phase-locked loop is enabled
+    let mut buf = empty_timex();
+    buf.modes = ADJ_OFFSET | ADJ_MICRO;
+    buf.offset = 1_000;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.offset, ==, 0);
+
+    let mut buf = empty_timex();
+    buf.modes = ADJ_STATUS | ADJ_OFFSET;
+    buf.status = STA_PLL;
+    buf.offset = 1_000;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.offset, ==, 1_000);
+
+    // offsets are clamped to 0.5 seconds
+    let mut buf = empty_timex();
+    buf.modes = ADJ_OFFSET;
+    buf.offset = -1_000_000;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.offset, ==, -500_000);
+
+    let mut buf = empty_timex();
+    buf.modes = ADJ_OFFSET;
+    buf.offset = 0;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+    ensure_ord!(buf.offset, ==, 0);
+
+    let mut buf = empty_timex();
+    buf.modes = ADJ_STATUS;
+    buf.status = 0;
+    ensure_ord!(adjtimex(&mut buf), ==, Ok(TIME_OK));
+
+    Ok(())
+}
+
+fn test_adjtime() -> anyhow::Result<()> {
+    ensure_ord!(adjtime(Some(100_000)), ==, Ok(0));
+
+    // the clock is slewed by an extra 500 ppm until the offset has been applied
+    let (drift, monotonic_elapsed) = measure_drift_nanos(Duration::from_millis(200));
+    let expected = monotonic_elapsed * (CONFIGURED_DRIFT_PPM + 500) / 1_000_000;
+    ensure_ord!((drift - expected).abs(), <=, 1_000);
+
+    let remaining = adjtime(None)?;
+    ensure_ord!(remaining, >, 99_800);
+    ensure_ord!(remaining, <=, 99_900);
+
+    // setting the clock cancels the slew
+    let now = clock_gettime(libc::CLOCK_REALTIME);
+    clock_settime(libc::CLOCK_REALTIME, duration_to_timespec(now))?;
+    ensure_ord!(adjtime(None), ==, Ok(0));
+
+    Ok(())
+}
+
+fn test_abs_realtime_timer_settime() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_REALTIME, 0)?;
+
+    // stepping the clock past the target expires the timer immediately
+    let monotonic_start = clock_gettime(libc::CLOCK_MONOTONIC);
+    let now = clock_gettime(libc::CLOCK_REALTIME);
+    timerfd_settime_abs(fd, 0, now + Duration::from_secs(5))?;
+    clock_settime(
+        libc::CLOCK_REALTIME,
+        duration_to_timespec(now + Duration::from_secs(10)),
+    )?;
+    ensure_ord!(timerfd_read(fd), ==, Ok(1));
+    let monotonic_elapsed = clock_gettime(libc::CLOCK_MONOTONIC) - monotonic_start;
+    ensure_ord!(monotonic_elapsed, <, Duration::from_millis(10));
+
+    // stepping the clock back delays the timer until the clock reaches the target again
+    let monotonic_start = clock_gettime(libc::CLOCK_MONOTONIC);
+    let now = clock_gettime(libc::CLOCK_REALTIME);
+    let target = now + Duration::from_millis(200);
+    timerfd_settime_abs(fd, 0, target)?;
+    clock_settime(
+        libc::CLOCK_REALTIME,
+        duration_to_timespec(now - Duration::from_millis(300)),
+    )?;
+    ensure_ord!(timerfd_read(fd), ==, Ok(1));
+    ensure_ord!(clock_gettime(libc::CLOCK_REALTIME), >=, target);
+    let monotonic_elapsed = clock_gettime(libc::CLOCK_MONOTONIC) - monotonic_start;
+    ensure_ord!(monotonic_elapsed, >=, Duration::from_millis(490));
+    ensure_ord!(monotonic_elapsed, <, Duration::from_millis(510));
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_abs_realtime_posix_timer_settime() -> anyhow::Result<()> {
+    let timer_id = timer_create_realtime()?;
+
+    // stepping the clock past the target expires the timer immediately
+    let now = clock_gettime(libc::CLOCK_REALTIME);
+    timer_settime_abs(timer_id, now + Duration::from_secs(5))?;
+    clock_settime(
+        libc::CLOCK_REALTIME,
+        duration_to_timespec(now + Duration::from_secs(10)),
+    )?;
+    ensure_ord!(timer_gettime(timer_id)?, ==, Duration::ZERO);
+
+    // stepping the clock back delays the timer until the clock reaches the target again
+    let now = clock_gettime(libc::CLOCK_REALTIME);
+    timer_settime_abs(timer_id, now + Duration::from_millis(200))?;
+    clock_settime(
+        libc::CLOCK_REALTIME,
+        duration_to_timespec(now - Duration::from_millis(300)),
+    )?;
+    let remaining = timer_gettime(timer_id)?;
+    ensure_ord!(remaining, >, Duration::from_millis(490));
+    ensure_ord!(remaining, <=, Duration::from_millis(500));
+
+    timer_delete(timer_id)?;
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    // the tests that depend on the configured clock or that set the clock only run in shadow
+    let shadow_only = set![TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("offset", test_offset, shadow_only.clone()),
+        ShadowTest::new("start-datetime", test_start_datetime, shadow_only.clone()),
+        ShadowTest::new("drift", test_drift, shadow_only.clone()),
+        ShadowTest::new("realtime-clocks", test_realtime_clocks, all_envs.clone()),
+        ShadowTest::new("settime-errors", test_settime_errors, all_envs.clone()),
+        ShadowTest::new("settime", test_settime, shadow_only.clone()),
+        ShadowTest::new(
+            "adjtimex-frequency",
+            test_adjtimex_frequency,
+            shadow_only.clone(),
+        ),
+        ShadowTest::new(
+            "adjtimex-setoffset",
+            test_adjtimex_setoffset,
+            shadow_only.clone(),
+        ),
+        ShadowTest::new("adjtimex-status", test_adjtimex_status, shadow_only.clone()),
+        ShadowTest::new("adjtimex-tick", test_adjtimex_tick, shadow_only.clone()),
+        ShadowTest::new("adjtimex-offset", test_adjtimex_offset, shadow_only.clone()),
+        ShadowTest::new("adjtime", test_adjtime, shadow_only.clone()),
+        ShadowTest::new("cancel-on-set", test_cancel_on_set, shadow_only.clone()),
+        ShadowTest::new(
+            "abs-realtime-timer",
+            test_abs_realtime_timer,
+            shadow_only.clone(),
+        ),
+        ShadowTest::new(
+            "abs-realtime-timer-settime",
+            test_abs_realtime_timer_settime,
+            shadow_only.clone(),
+        ),
+        ShadowTest::new(
+            "abs-realtime-posix-timer-settime",
+            test_abs_realtime_posix_timer_settime,
+            shadow_only.clone(),
+        ),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
@@ -116,6 +116,241 @@ fn test_threads_level() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn test_edge_rearm() -> anyhow::Result<()> {
+    let (readfd, writefd) = unistd::pipe()?;
+    let epollfd = epoll::epoll_create()?;
+
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLET | EpollFlags::EPOLLIN, 0);
+    epoll::epoll_ctl(
+        epollfd,
+        epoll::EpollOp::EpollCtlAdd,
+        readfd,
+        Some(&mut event),
+    )?;
+
+    unistd::write(writefd, &[0])?;
+
+    // The edge should be reported once.
+    let res = do_epoll_wait(epollfd, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(1));
+    let res = do_epoll_wait(epollfd, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(0));
+
+    // Drain the pipe and make it readable again to generate a new edge.
+    unistd::read(readfd, &mut [0])?;
+    unistd::write(writefd, &[0])?;
+
+    let res = do_epoll_wait(epollfd, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(1));
+    ensure_ord!(res.events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0));
+
+    Ok(())
+}
+
+fn test_oneshot() -> anyhow::Result<()> {
+    let (readfd, writefd) = unistd::pipe()?;
+    let epollfd = epoll::epoll_create()?;
+
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLONESHOT | EpollFlags::EPOLLIN, 5);
+    epoll::epoll_ctl(
+        epollfd,
+        epoll::EpollOp::EpollCtlAdd,
+        readfd,
+        Some(&mut event),
+    )?;
+
+    unistd::write(writefd, &[0])?;
+
+    let res = do_epoll_wait(epollfd, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(1));
+    ensure_ord!(res.events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 5));
+
+    // The entry is disabled after reporting an event, even though the pipe is still readable.
+    let res = do_epoll_wait(epollfd, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(0));
+
+    // EPOLL_CTL_MOD re-arms the entry.
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLONESHOT | EpollFlags::EPOLLIN, 6);
+    epoll::epoll_ctl(
+        epollfd,
+        epoll::EpollOp::EpollCtlMod,
+        readfd,
+        Some(&mut event),
+    )?;
+
+    let res = do_epoll_wait(epollfd, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(1));
+    ensure_ord!(res.events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 6));
+
+    Ok(())
+}
+
+fn test_ctl_errors() -> anyhow::Result<()> {
+    let (readfd, _writefd) = unistd::pipe()?;
+    let epollfd = epoll::epoll_create()?;
+
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0);
+
+    // Not yet registered.
+    ensure_ord!(
+        epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlMod, readfd, Some(&mut event)),
+        ==,
+        Err(nix::errno::Errno::ENOENT)
+    );
+    ensure_ord!(
+        epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlDel, readfd, None),
+        ==,
+        Err(nix::errno::Errno::ENOENT)
+    );
+
+    epoll::epoll_ctl(
+        epollfd,
+        epoll::EpollOp::EpollCtlAdd,
+        readfd,
+        Some(&mut event),
+    )?;
+
+    // Already registered.
+    ensure_ord!(
+        epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlAdd, readfd, Some(&mut event)),
+        ==,
+        Err(nix::errno::Errno::EEXIST)
+    );
+
+    // An epoll can't watch itself.
+    ensure_ord!(
+        epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlAdd, epollfd, Some(&mut event)),
+        ==,
+        Err(nix::errno::Errno::EINVAL)
+    );
+
+    epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlDel, readfd, None)?;
+
+    Ok(())
+}
+
+fn test_exclusive_errors() -> anyhow::Result<()> {
+    let (readfd, _writefd) = unistd::pipe()?;
+    let epollfd = epoll::epoll_create()?;
+    let other_epollfd = epoll::epoll_create()?;
+
+    // EPOLLEXCLUSIVE can't be combined with EPOLLONESHOT.
+    let mut event = epoll::EpollEvent::new(
+        EpollFlags::EPOLLEXCLUSIVE | EpollFlags::EPOLLONESHOT | EpollFlags::EPOLLIN,
+        0,
+    );
+    ensure_ord!(
+        epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlAdd, readfd, Some(&mut event)),
+        ==,
+        Err(nix::errno::Errno::EINVAL)
+    );
+
+    // EPOLLEXCLUSIVE can't be used with an epoll target.
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLEXCLUSIVE | EpollFlags::EPOLLIN, 0);
+    ensure_ord!(
+        epoll::epoll_ctl(
+            epollfd,
+            epoll::EpollOp::EpollCtlAdd,
+            other_epollfd,
+            Some(&mut event)
+        ),
+        ==,
+        Err(nix::errno::Errno::EINVAL)
+    );
+
+    epoll::epoll_ctl(
+        epollfd,
+        epoll::EpollOp::EpollCtlAdd,
+        readfd,
+        Some(&mut event),
+    )?;
+
+    // An exclusive entry can't be modified.
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0);
+    ensure_ord!(
+        epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlMod, readfd, Some(&mut event)),
+        ==,
+        Err(nix::errno::Errno::EINVAL)
+    );
+
+    Ok(())
+}
+
+fn test_exclusive_wakeup() -> anyhow::Result<()> {
+    let (readfd, writefd) = unistd::pipe()?;
+
+    let epollfds = [epoll::epoll_create()?, epoll::epoll_create()?];
+
+    for epollfd in epollfds {
+        let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLEXCLUSIVE | EpollFlags::EPOLLIN, 0);
+        epoll::epoll_ctl(
+            epollfd,
+            epoll::EpollOp::EpollCtlAdd,
+            readfd,
+            Some(&mut event),
+        )?;
+    }
+
+    let timeout = Duration::from_millis(100);
+
+    let threads =
+        epollfds.map(|epollfd| std::thread::spawn(move || do_epoll_wait(epollfd, timeout)));
+
+    // Wait for readers to block.
+    std::thread::sleep(timeout / 2);
+
+    // Make the read-end readable.
+    unistd::write(writefd, &[0])?;
+
+    let mut results = threads.map(|t| t.join().unwrap());
+    results.sort_by(|lhs, rhs| lhs.events.len().cmp(&rhs.events.len()));
+
+    // Only one of the waiters should have been woken.
+    ensure_ord!(results[0].epoll_res, ==, Ok(0));
+    ensure_ord!(results[0].duration, >=, timeout);
+
+    ensure_ord!(results[1].epoll_res, ==, Ok(1));
+    ensure_ord!(results[1].duration, <, timeout);
+    ensure_ord!(results[1].events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0));
+
+    Ok(())
+}
+
+fn test_nested() -> anyhow::Result<()> {
+    let (readfd, writefd) = unistd::pipe()?;
+    let inner = epoll::epoll_create()?;
+    let outer = epoll::epoll_create()?;
+
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 1);
+    epoll::epoll_ctl(inner, epoll::EpollOp::EpollCtlAdd, readfd, Some(&mut event))?;
+
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 2);
+    epoll::epoll_ctl(outer, epoll::EpollOp::EpollCtlAdd, inner, Some(&mut event))?;
+
+    // Adding the outer epoll to the inner epoll would create a cycle.
+    ensure_ord!(
+        epoll::epoll_ctl(inner, epoll::EpollOp::EpollCtlAdd, outer, Some(&mut event)),
+        ==,
+        Err(nix::errno::Errno::ELOOP)
+    );
+
+    let res = do_epoll_wait(outer, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(0));
+
+    unistd::write(writefd, &[0])?;
+
+    // The inner epoll is readable since it has an event to report.
+    let res = do_epoll_wait(outer, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(1));
+    ensure_ord!(res.events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 2));
+
+    let res = do_epoll_wait(inner, Duration::ZERO);
+    ensure_ord!(res.epoll_res, ==, Ok(1));
+    ensure_ord!(res.events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 1));
+
+    Ok(())
+}
+
 fn main() -> anyhow::Result<()> {
     // should we restrict the tests we run?
     let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
@@ -127,6 +362,12 @@ fn main() -> anyhow::Result<()> {
     let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
         ShadowTest::new("threads-edge", test_threads_edge, all_envs.clone()),
         ShadowTest::new("threads-level", test_threads_level, all_envs.clone()),
+        ShadowTest::new("edge-rearm", test_edge_rearm, all_envs.clone()),
+        ShadowTest::new("oneshot", test_oneshot, all_envs.clone()),
+        ShadowTest::new("ctl-errors", test_ctl_errors, all_envs.clone()),
+        ShadowTest::new("exclusive-errors", test_exclusive_errors, all_envs.clone()),
+        ShadowTest::new("exclusive-wakeup", test_exclusive_wakeup, all_envs.clone()),
+        ShadowTest::new("nested", test_nested, all_envs.clone()),
     ];
 
     if filter_shadow_passing {
@@ -0,0 +1,542 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_fork_ids",
+            test_fork_ids,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_fork_memory_is_copied",
+            test_fork_memory_is_copied,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_fork_shares_open_files",
+            test_fork_shares_open_files,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_execve_closes_cloexec_fds",
+            test_execve_closes_cloexec_fds,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_execve_nonexistent",
+            test_execve_nonexistent,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_vfork_waits_for_child",
+            test_vfork_waits_for_child,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_posix_spawn",
+            test_posix_spawn,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_clone_new_process",
+            test_clone_new_process,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_wait_no_children",
+            test_wait_no_children,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_waitpid_nohang",
+            test_waitpid_nohang,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_waitpid_exit_status",
+            test_waitpid_exit_status,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_waitid_nowait",
+            test_waitid_nowait,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_wait_process_group",
+            test_wait_process_group,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_sigchld",
+            test_sigchld,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
+
+    tests
+}
+
+/// Forks, runs `f` in the child, and exits the child with status 0 if `f`
+/// succeeded or 1 otherwise. Returns the child's pid in the parent.
+fn fork_and_run(f: impl FnOnce() -> Result<(), String>) -> Result<libc::pid_t, String> {
+    let pid = unsafe { libc::fork() };
+    match pid {
+        -1 => Err(format!(
+            "fork: {}",
+            test_utils::get_errno_message(test_utils::get_errno())
+        )),
+        0 => {
+            let rv = match f() {
+                Ok(()) => 0,
+                Err(e) => {
+                    eprintln!("child failed: {}", e);
+                    1
+                }
+            };
+            // Don't run any of the parent's destructors or atexit handlers.
+            unsafe { libc::_exit(rv) };
+        }
+        pid => Ok(pid),
+    }
+}
+
+/// Waits for the child to exit, and checks that it succeeded.
+fn reap(pid: libc::pid_t) -> Result<(), String> {
+    let mut status = 0;
+    let rv = unsafe { libc::waitpid(pid, &mut status, 0) };
+    test_utils::result_assert_eq(rv, pid, "Unexpected waitpid result")?;
+    test_utils::result_assert(libc::WIFEXITED(status), "Child didn't exit normally")?;
+    test_utils::result_assert_eq(libc::WEXITSTATUS(status), 0, "Child failed")
+}
+
+fn pipe() -> Result<(libc::c_int, libc::c_int), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(|| unsafe { libc::pipe(fds.as_mut_ptr()) }, &[])?;
+    Ok((fds[0], fds[1]))
+}
+
+/// Calls `execve` with an empty environment. Only returns if it fails, with the
+/// errno.
+fn execve(path: &str, args: &[&str]) -> i32 {
+    let path = std::ffi::CString::new(path).unwrap();
+    let args: Vec<std::ffi::CString> = args
+        .iter()
+        .map(|x| std::ffi::CString::new(*x).unwrap())
+        .collect();
+    let mut argv: Vec<*const libc::c_char> = args.iter().map(|x| x.as_ptr()).collect();
+    argv.push(std::ptr::null());
+    let envp = [std::ptr::null()];
+    unsafe { libc::execve(path.as_ptr(), argv.as_ptr(), envp.as_ptr()) };
+    test_utils::get_errno()
+}
+
+fn write_i32(fd: libc::c_int, val: i32) -> Result<(), String> {
+    let buf = val.to_ne_bytes();
+    let rv = test_utils::check_system_call!(
+        || unsafe { libc::write(fd, buf.as_ptr() as *const libc::c_void, buf.len()) },
+        &[]
+    )?;
+    test_utils::result_assert_eq(rv, buf.len() as isize, "Short write")
+}
+
+fn read_i32(fd: libc::c_int) -> Result<i32, String> {
+    let mut buf = [0u8; 4];
+    let rv = test_utils::check_system_call!(
+        || unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) },
+        &[]
+    )?;
+    test_utils::result_assert_eq(rv, buf.len() as isize, "Short read")?;
+    Ok(i32::from_ne_bytes(buf))
+}
+
+fn test_fork_ids() -> Result<(), String> {
+    let (read_fd, write_fd) = pipe()?;
+    let parent_pid = unsafe { libc::getpid() };
+
+    let child_pid = fork_and_run(|| {
+        write_i32(write_fd, unsafe { libc::getpid() })?;
+        write_i32(write_fd, unsafe { libc::getppid() })
+    })?;
+
+    test_utils::run_and_close_fds(&[read_fd, write_fd], || {
+        test_utils::result_assert(child_pid > 0, "Unexpected child pid")?;
+        test_utils::result_assert_ne(child_pid, parent_pid, "Child has the parent's pid")?;
+        test_utils::result_assert_eq(read_i32(read_fd)?, child_pid, "Unexpected getpid")?;
+        test_utils::result_assert_eq(read_i32(read_fd)?, parent_pid, "Unexpected getppid")?;
+        reap(child_pid)
+    })
+}
+
+fn test_fork_memory_is_copied() -> Result<(), String> {
+    let (read_fd, write_fd) = pipe()?;
+    // Use heap memory, which Shadow may have remapped.
+    let mut val = Box::new(1i32);
+
+    let child_pid = fork_and_run(|| {
+        write_i32(write_fd, *val)?;
+        *val = 2;
+        write_i32(write_fd, *val)
+    })?;
+
+    test_utils::run_and_close_fds(&[read_fd, write_fd], || {
+        test_utils::result_assert_eq(read_i32(read_fd)?, 1, "Child didn't inherit memory")?;
+        test_utils::result_assert_eq(read_i32(read_fd)?, 2, "Unexpected child value")?;
+        test_utils::result_assert_eq(*val, 1, "Child's write is visible in the parent")?;
+        reap(child_pid)
+    })
+}
+
+fn test_fork_shares_open_files() -> Result<(), String> {
+    let (read_fd, write_fd) = pipe()?;
+
+    // The child closing its copy of the write end shouldn't affect ours.
+    let child_pid = fork_and_run(|| {
+        test_utils::check_system_call!(|| unsafe { libc::close(write_fd) }, &[])?;
+        Ok(())
+    })?;
+
+    test_utils::run_and_close_fds(&[read_fd, write_fd], || {
+        reap(child_pid)?;
+        write_i32(write_fd, 42)?;
+        test_utils::result_assert_eq(read_i32(read_fd)?, 42, "Unexpected value")
+    })
+}
+
+fn test_execve_closes_cloexec_fds() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(
+        || unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) },
+        &[]
+    )?;
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    // The child's copy of the write end should be closed by the exec, while
+    // the new program is still running.
+    let child_pid = fork_and_run(|| {
+        let errno = execve("/bin/sleep", &["sleep", "1"]);
+        Err(format!("execve: {}", test_utils::get_errno_message(errno)))
+    })?;
+
+    test_utils::run_and_close_fds(&[read_fd], || {
+        test_utils::check_system_call!(|| unsafe { libc::close(write_fd) }, &[])?;
+
+        let mut buf = [0u8; 1];
+        let rv = test_utils::check_system_call!(
+            || unsafe { libc::read(read_fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 0, "Expected EOF")?;
+
+        let mut status = 0;
+        let rv = test_utils::check_system_call!(
+            || unsafe { libc::waitpid(child_pid, &mut status, libc::WNOHANG) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 0, "Child exited before the exec")?;
+
+        reap(child_pid)
+    })
+}
+
+fn test_execve_nonexistent() -> Result<(), String> {
+    // A failed exec returns to the calling program.
+    let child_pid = fork_and_run(|| {
+        let errno = execve("/nonexistent", &["nonexistent"]);
+        test_utils::result_assert_eq(errno, libc::ENOENT, "Unexpected errno")
+    })?;
+
+    reap(child_pid)
+}
+
+fn test_vfork_waits_for_child() -> Result<(), String> {
+    // Memory that the child shares with us, whether or not it has its own
+    // copy of the rest of our memory.
+    let len = std::mem::size_of::<i32>();
+    let flag = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    test_utils::result_assert(flag != libc::MAP_FAILED, "mmap failed")?;
+    let flag = flag as *mut i32;
+
+    // The child may only write to memory and exit; see vfork(2).
+    let child_pid = unsafe { libc::vfork() };
+    if child_pid == 0 {
+        unsafe {
+            std::ptr::write_volatile(flag, 1);
+            libc::_exit(0);
+        }
+    }
+
+    let rv = (|| {
+        test_utils::result_assert(child_pid > 0, "vfork failed")?;
+        test_utils::result_assert_eq(
+            unsafe { std::ptr::read_volatile(flag) },
+            1,
+            "Parent ran before the child exited",
+        )?;
+        reap(child_pid)
+    })();
+
+    unsafe { libc::munmap(flag as *mut libc::c_void, len) };
+    rv
+}
+
+fn test_posix_spawn() -> Result<(), String> {
+    // glibc creates the child with a CLONE_VM|CLONE_VFORK clone.
+    let path = std::ffi::CString::new("/bin/true").unwrap();
+    let argv = [path.as_ptr() as *mut libc::c_char, std::ptr::null_mut()];
+    let envp = [std::ptr::null_mut()];
+    let mut child_pid = 0;
+    let rv = unsafe {
+        libc::posix_spawn(
+            &mut child_pid,
+            path.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            argv.as_ptr(),
+            envp.as_ptr(),
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "posix_spawn failed")?;
+    reap(child_pid)
+}
+
+fn test_clone_new_process() -> Result<(), String> {
+    let (read_fd, write_fd) = pipe()?;
+    let mut child_tid: libc::pid_t = 0;
+
+    // Like glibc's `fork`: the kernel (or Shadow) writes the child's tid in the
+    // child's memory.
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_clone,
+            (libc::CLONE_CHILD_SETTID | libc::SIGCHLD) as libc::c_ulong,
+            std::ptr::null_mut::<libc::c_void>(),
+            std::ptr::null_mut::<libc::pid_t>(),
+            &mut child_tid as *mut libc::pid_t,
+            0 as libc::c_ulong,
+        )
+    };
+    if rv == 0 {
+        let res = write_i32(write_fd, child_tid).and_then(|_| {
+            test_utils::result_assert_eq(child_tid, unsafe { libc::getpid() }, "Unexpected tid")
+        });
+        unsafe { libc::_exit(if res.is_ok() { 0 } else { 1 }) };
+    }
+
+    test_utils::run_and_close_fds(&[read_fd, write_fd], || {
+        test_utils::result_assert(rv > 0, "clone failed")?;
+        let child_pid = rv as libc::pid_t;
+        test_utils::result_assert_eq(child_tid, 0, "Parent's memory was modified")?;
+        test_utils::result_assert_eq(read_i32(read_fd)?, child_pid, "Unexpected child tid")?;
+        reap(child_pid)
+    })
+}
+
+fn test_wait_no_children() -> Result<(), String> {
+    let mut status = 0;
+    test_utils::check_system_call!(
+        || unsafe { libc::waitpid(-1, &mut status, 0) },
+        &[libc::ECHILD]
+    )?;
+    test_utils::check_system_call!(
+        || unsafe { libc::waitpid(-1, &mut status, libc::WNOHANG) },
+        &[libc::ECHILD]
+    )?;
+    Ok(())
+}
+
+fn test_waitpid_nohang() -> Result<(), String> {
+    let (read_fd, write_fd) = pipe()?;
+
+    // The child exits once we write to the pipe.
+    let child_pid = fork_and_run(|| {
+        read_i32(read_fd)?;
+        Ok(())
+    })?;
+
+    test_utils::run_and_close_fds(&[read_fd, write_fd], || {
+        let mut status = 0;
+        let rv = test_utils::check_system_call!(
+            || unsafe { libc::waitpid(child_pid, &mut status, libc::WNOHANG) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 0, "Child exited early")?;
+
+        write_i32(write_fd, 0)?;
+        reap(child_pid)?;
+
+        // The child has already been reaped.
+        test_utils::check_system_call!(
+            || unsafe { libc::waitpid(child_pid, &mut status, libc::WNOHANG) },
+            &[libc::ECHILD]
+        )?;
+        Ok(())
+    })
+}
+
+fn test_waitpid_exit_status() -> Result<(), String> {
+    let exited_pid = fork_and_run(|| Err("exiting with 1".to_string()))?;
+    let killed_pid = fork_and_run(|| {
+        unsafe { libc::raise(libc::SIGKILL) };
+        Ok(())
+    })?;
+
+    let mut status = 0;
+    let rv = test_utils::check_system_call!(
+        || unsafe { libc::waitpid(exited_pid, &mut status, 0) },
+        &[]
+    )?;
+    test_utils::result_assert_eq(rv, exited_pid, "Unexpected waitpid result")?;
+    test_utils::result_assert(libc::WIFEXITED(status), "Child didn't exit normally")?;
+    test_utils::result_assert_eq(libc::WEXITSTATUS(status), 1, "Unexpected exit status")?;
+
+    let rv = test_utils::check_system_call!(
+        || unsafe { libc::waitpid(killed_pid, &mut status, 0) },
+        &[]
+    )?;
+    test_utils::result_assert_eq(rv, killed_pid, "Unexpected waitpid result")?;
+    test_utils::result_assert(libc::WIFSIGNALED(status), "Child wasn't killed")?;
+    test_utils::result_assert_eq(libc::WTERMSIG(status), libc::SIGKILL, "Unexpected signal")
+}
+
+fn test_waitid_nowait() -> Result<(), String> {
+    let child_pid = fork_and_run(|| Ok(()))?;
+
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    test_utils::check_system_call!(
+        || unsafe {
+            libc::waitid(
+                libc::P_PID,
+                child_pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        },
+        &[]
+    )?;
+    test_utils::result_assert_eq(info.si_signo, libc::SIGCHLD, "Unexpected si_signo")?;
+    test_utils::result_assert_eq(info.si_code, libc::CLD_EXITED, "Unexpected si_code")?;
+    test_utils::result_assert_eq(unsafe { info.si_pid() }, child_pid, "Unexpected si_pid")?;
+    test_utils::result_assert_eq(unsafe { info.si_status() }, 0, "Unexpected si_status")?;
+
+    // With WNOWAIT the child is still waitable.
+    reap(child_pid)
+}
+
+fn test_wait_process_group() -> Result<(), String> {
+    let pgid = unsafe { libc::getpgrp() };
+    // A group that none of our children are in.
+    let other_pgid = pgid + 1000;
+
+    // Forked children stay in their parent's process group.
+    let child_pid = fork_and_run(move || {
+        test_utils::result_assert_eq(unsafe { libc::getpgrp() }, pgid, "Unexpected child pgid")
+    })?;
+
+    let mut status = 0;
+    test_utils::check_system_call!(
+        || unsafe { libc::waitpid(-other_pgid, &mut status, 0) },
+        &[libc::ECHILD]
+    )?;
+
+    let rv =
+        test_utils::check_system_call!(|| unsafe { libc::waitpid(-pgid, &mut status, 0) }, &[])?;
+    test_utils::result_assert_eq(rv, child_pid, "Unexpected waitpid result")?;
+    test_utils::result_assert(libc::WIFEXITED(status), "Child didn't exit normally")?;
+    test_utils::result_assert_eq(libc::WEXITSTATUS(status), 0, "Child failed")?;
+
+    let child_pid = fork_and_run(|| Ok(()))?;
+
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    test_utils::check_system_call!(
+        || unsafe {
+            libc::waitid(
+                libc::P_PGID,
+                other_pgid as libc::id_t,
+                &mut info,
+                libc::WEXITED,
+            )
+        },
+        &[libc::ECHILD]
+    )?;
+    test_utils::check_system_call!(
+        || unsafe { libc::waitid(libc::P_PGID, pgid as libc::id_t, &mut info, libc::WEXITED) },
+        &[]
+    )?;
+    test_utils::result_assert_eq(unsafe { info.si_pid() }, child_pid, "Unexpected si_pid")?;
+    test_utils::result_assert_eq(unsafe { info.si_status() }, 0, "Child failed")
+}
+
+static SIGCHLD_COUNT: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+
+extern "C" fn sigchld_handler(_signo: libc::c_int) {
+    SIGCHLD_COUNT.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn test_sigchld() -> Result<(), String> {
+    let handler = nix::sys::signal::SigHandler::Handler(sigchld_handler);
+    let action = nix::sys::signal::SigAction::new(
+        handler,
+        nix::sys::signal::SaFlags::SA_RESTART,
+        nix::sys::signal::SigSet::empty(),
+    );
+    let old_action =
+        unsafe { nix::sys::signal::sigaction(nix::sys::signal::Signal::SIGCHLD, &action) }
+            .map_err(|e| e.to_string())?;
+    SIGCHLD_COUNT.store(0, std::sync::atomic::Ordering::SeqCst);
+
+    let child_pid = fork_and_run(|| Ok(()))?;
+    let rv = reap(child_pid);
+
+    unsafe { nix::sys::signal::sigaction(nix::sys::signal::Signal::SIGCHLD, &old_action) }
+        .map_err(|e| e.to_string())?;
+    rv?;
+
+    test_utils::result_assert_eq(
+        SIGCHLD_COUNT.load(std::sync::atomic::Ordering::SeqCst),
+        1,
+        "Unexpected number of SIGCHLD signals",
+    )
+}
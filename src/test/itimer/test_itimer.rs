@@ -52,6 +52,26 @@ extern "C" fn sigalrm_handler(sig: i32) {
     SIGNAL_CTR.fetch_add(1, Ordering::Relaxed);
 }
 
+// Counts how many times the SIGVTALRM and SIGPROF handler ran for each signal.
+static VTALRM_CTR: AtomicU64 = AtomicU64::new(0);
+static PROF_CTR: AtomicU64 = AtomicU64::new(0);
+
+// SIGVTALRM and SIGPROF handler.
+extern "C" fn cpu_timer_handler(sig: i32) {
+    match sig {
+        libc::SIGVTALRM => VTALRM_CTR.fetch_add(1, Ordering::Relaxed),
+        libc::SIGPROF => PROF_CTR.fetch_add(1, Ordering::Relaxed),
+        _ => panic!("Unexpected signal {}", sig),
+    };
+}
+
+fn timeval_from_micros(micros: i64) -> libc::timeval {
+    libc::timeval {
+        tv_sec: micros / 1_000_000,
+        tv_usec: micros % 1_000_000,
+    }
+}
+
 // Reset timer and signal count.
 fn reset() -> anyhow::Result<()> {
     setitimer(
@@ -330,6 +350,89 @@ fn test_leave_running() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn test_invalid_which() -> anyhow::Result<()> {
+    let zero = libc::itimerval {
+        it_value: timeval_from_micros(0),
+        it_interval: timeval_from_micros(0),
+    };
+    ensure_ord!(getitimer(3), ==, Err(nix::errno::Errno::EINVAL));
+    ensure_ord!(setitimer(3, &zero), ==, Err(nix::errno::Errno::EINVAL));
+    ensure_ord!(getitimer(-1), ==, Err(nix::errno::Errno::EINVAL));
+    Ok(())
+}
+
+fn test_cpu_set_then_get(which: i32) -> anyhow::Result<()> {
+    let it_value = timeval_from_micros(1_000_002);
+    let it_interval = timeval_from_micros(3_000_004);
+    let val = setitimer(
+        which,
+        &libc::itimerval {
+            it_value,
+            it_interval,
+        },
+    )?;
+    ensure_ord!(val, ==, ITimer{value: TimeVal::zero(), interval: TimeVal::zero()});
+
+    let val = getitimer(which)?;
+    // Interval should be exactly as was set.
+    ensure_ord!(val.interval, ==, TimeVal::from(it_interval));
+    // We've used very little CPU time since setting the timer.
+    let diff = TimeVal::from(it_value).sub(val.value);
+    ensure_ord!(diff, >=, TimeVal::zero());
+    ensure_ord!(diff, <, TimeVal::milliseconds(10));
+
+    // Disarm, which returns the previous value.
+    let val = setitimer(
+        which,
+        &libc::itimerval {
+            it_value: timeval_from_micros(0),
+            it_interval: timeval_from_micros(0),
+        },
+    )?;
+    ensure_ord!(val.interval, ==, TimeVal::from(it_interval));
+    ensure_ord!(getitimer(which)?, ==, ITimer{value: TimeVal::zero(), interval: TimeVal::zero()});
+
+    Ok(())
+}
+
+fn test_cpu_expiration(which: i32, counter: &AtomicU64) -> anyhow::Result<()> {
+    counter.store(0, Ordering::Relaxed);
+
+    // Expire after every 1 ms of CPU time.
+    let it_value = timeval_from_micros(1_000);
+    setitimer(
+        which,
+        &libc::itimerval {
+            it_value,
+            it_interval: it_value,
+        },
+    )?;
+
+    // Sleeping doesn't use CPU time.
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    ensure_ord!(counter.load(Ordering::Relaxed), ==, 0);
+
+    // Busy-loop making syscalls until the timer has expired a few times. Under shadow the CPU
+    // time is the modeled latency of the syscalls.
+    let start = std::time::Instant::now();
+    while counter.load(Ordering::Relaxed) < 3 && start.elapsed() < std::time::Duration::from_secs(1)
+    {
+        unsafe { libc::syscall(libc::SYS_getppid) };
+    }
+
+    setitimer(
+        which,
+        &libc::itimerval {
+            it_value: timeval_from_micros(0),
+            it_interval: timeval_from_micros(0),
+        },
+    )?;
+
+    ensure_ord!(counter.load(Ordering::Relaxed), >=, 3);
+
+    Ok(())
+}
+
 fn main() -> anyhow::Result<()> {
     // Install a SIGALRM handler that counts how many times it's been received.
     unsafe {
@@ -343,8 +446,24 @@ fn main() -> anyhow::Result<()> {
         )
         .unwrap()
     };
+    // Likewise for SIGVTALRM and SIGPROF.
+    for signal in [Signal::SIGVTALRM, Signal::SIGPROF] {
+        unsafe {
+            nix::sys::signal::sigaction(
+                signal,
+                &SigAction::new(
+                    SigHandler::Handler(cpu_timer_handler),
+                    SaFlags::SA_RESTART,
+                    SigSet::empty(),
+                ),
+            )
+            .unwrap()
+        };
+    }
     let mut sigset = SigSet::empty();
     sigset.add(Signal::SIGALRM);
+    sigset.add(Signal::SIGVTALRM);
+    sigset.add(Signal::SIGPROF);
     nix::sys::signal::sigprocmask(
         nix::sys::signal::SigmaskHow::SIG_UNBLOCK,
         Some(&sigset),
@@ -357,25 +476,54 @@ fn main() -> anyhow::Result<()> {
     let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
     // should we summarize the results rather than exit on a failed test
     let summarize = std::env::args().any(|x| x == "--summarize");
+    // should we run the ITIMER_VIRTUAL and ITIMER_PROF tests? under shadow these need
+    // 'model_unblocked_syscall_latency' to be enabled, so they're run with a separate config
+    let cpu_timers = std::env::args().any(|x| x == "--cpu-timers");
 
     let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
-    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
-        ShadowTest::new("initially_unset", test_initially_unset, all_envs.clone()),
-        ShadowTest::new("set_then_get", test_set_then_get, all_envs.clone()),
-        ShadowTest::new(
-            "itimers_are_process_wide",
-            test_itimers_are_process_wide,
-            all_envs.clone(),
-        ),
-        ShadowTest::new("set_then_set", test_set_then_set, all_envs.clone()),
-        ShadowTest::new("set_oneshot", test_oneshot, all_envs.clone()),
-        ShadowTest::new("set_interval", test_interval, all_envs.clone()),
-        ShadowTest::new("set_interval_zero", test_interval_zero, all_envs.clone()),
-        // Must be last.
-        // Validate proper cleanup for a timer that's still running when the
-        // process exits.
-        ShadowTest::new("leave_running", test_leave_running, all_envs.clone()),
-    ];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = if cpu_timers {
+        vec![
+            ShadowTest::new(
+                "virtual_set_then_get",
+                || test_cpu_set_then_get(libc::ITIMER_VIRTUAL),
+                all_envs.clone(),
+            ),
+            ShadowTest::new(
+                "prof_set_then_get",
+                || test_cpu_set_then_get(libc::ITIMER_PROF),
+                all_envs.clone(),
+            ),
+            ShadowTest::new(
+                "virtual_expiration",
+                || test_cpu_expiration(libc::ITIMER_VIRTUAL, &VTALRM_CTR),
+                all_envs.clone(),
+            ),
+            ShadowTest::new(
+                "prof_expiration",
+                || test_cpu_expiration(libc::ITIMER_PROF, &PROF_CTR),
+                all_envs.clone(),
+            ),
+        ]
+    } else {
+        vec![
+            ShadowTest::new("initially_unset", test_initially_unset, all_envs.clone()),
+            ShadowTest::new("set_then_get", test_set_then_get, all_envs.clone()),
+            ShadowTest::new(
+                "itimers_are_process_wide",
+                test_itimers_are_process_wide,
+                all_envs.clone(),
+            ),
+            ShadowTest::new("set_then_set", test_set_then_set, all_envs.clone()),
+            ShadowTest::new("set_oneshot", test_oneshot, all_envs.clone()),
+            ShadowTest::new("set_interval", test_interval, all_envs.clone()),
+            ShadowTest::new("set_interval_zero", test_interval_zero, all_envs.clone()),
+            ShadowTest::new("invalid_which", test_invalid_which, all_envs.clone()),
+            // Must be last.
+            // Validate proper cleanup for a timer that's still running when the
+            // process exits.
+            ShadowTest::new("leave_running", test_leave_running, all_envs.clone()),
+        ]
+    };
 
     if filter_shadow_passing {
         tests = tests
@@ -0,0 +1,103 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client sends timestamped datagrams to a server over a network graph edge that has jitter. The
+//! server checks that each datagram was delayed by the edge's latency plus some amount of its
+//! jitter, and writes the delays to stdout so that they can be compared between simulations.
+//!
+//! Usage:
+//!   test_jitter server <port> <count> <latency_ms> <jitter_ms>
+//!   test_jitter client <server_host> <port> <count>
+
+use std::net::UdpSocket;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+
+    match args.get(1).map(|x| x.as_str()) {
+        Some("server") if args.len() == 6 => {
+            let port: u16 = args[2].parse()?;
+            let count: u32 = args[3].parse()?;
+            let latency = Duration::from_millis(args[4].parse()?);
+            let jitter = Duration::from_millis(args[5].parse()?);
+            server(port, count, latency, jitter)
+        }
+        Some("client") if args.len() == 5 => {
+            let port: u16 = args[3].parse()?;
+            let count: u32 = args[4].parse()?;
+            client(&args[2], port, count)
+        }
+        _ => anyhow::bail!("Unexpected arguments: {:?}", args),
+    }
+}
+
+fn now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
+}
+
+fn client(server_host: &str, port: u16, count: u32) -> anyhow::Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket
+        .connect((server_host, port))
+        .context("Could not connect to the server")?;
+
+    for seq in 0..count {
+        let mut buf = [0u8; 12];
+        buf[..4].copy_from_slice(&seq.to_be_bytes());
+        buf[4..].copy_from_slice(&(now().as_nanos() as u64).to_be_bytes());
+        socket.send(&buf)?;
+
+        // space out the datagrams so that they're never delayed by the network interface
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    Ok(())
+}
+
+fn server(port: u16, count: u32, latency: Duration, jitter: Duration) -> anyhow::Result<()> {
+    let socket = UdpSocket::bind(("0.0.0.0", port))?;
+
+    let mut delays = Vec::new();
+
+    for _ in 0..count {
+        let mut buf = [0u8; 12];
+        let len = socket.recv(&mut buf)?;
+        let recv_time = now();
+
+        anyhow::ensure!(len == buf.len(), "Unexpected datagram length {}", len);
+
+        let seq = u32::from_be_bytes(buf[..4].try_into().unwrap());
+        let send_time = Duration::from_nanos(u64::from_be_bytes(buf[4..].try_into().unwrap()));
+        let delay = recv_time - send_time;
+
+        println!("{} {}", seq, delay.as_nanos());
+
+        anyhow::ensure!(
+            delay >= latency && delay <= latency + jitter,
+            "Datagram {} was delayed by {:?}, which is not within [{:?}, {:?}]",
+            seq,
+            delay,
+            latency,
+            latency + jitter,
+        );
+
+        delays.push(delay);
+    }
+
+    // with this many datagrams, the delays should cover most of the jitter range
+    let min = delays.iter().min().unwrap();
+    let max = delays.iter().max().unwrap();
+    anyhow::ensure!(
+        *max - *min > jitter / 2,
+        "Delays were in the range [{:?}, {:?}], so jitter doesn't seem to have been applied",
+        min,
+        max,
+    );
+
+    Ok(())
+}
@@ -71,6 +71,32 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_get_size,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_set_size",
+            test_set_size,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_set_size_busy",
+            test_set_size_busy,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        // a privileged process can exceed the limit on linux
+        test_utils::ShadowTest::new(
+            "test_set_size_above_limit",
+            test_set_size_above_limit,
+            set![TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_set_size_write",
+            test_set_size_write,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_fionread",
+            test_fionread,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         test_utils::ShadowTest::new(
             "test_read_after_write_close_with_empty_buffer",
             test_read_after_write_close_with_empty_buffer,
@@ -410,6 +436,270 @@ fn test_get_size() -> Result<(), String> {
     })
 }
 
+fn test_set_size() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(|| { unsafe { libc::pipe(fds.as_mut_ptr()) } }, &[])?;
+
+    test_utils::result_assert(fds[0] > 0, "fds[0] not set")?;
+    test_utils::result_assert(fds[1] > 0, "fds[1] not set")?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        // (requested size, expected size)
+        let sizes = [
+            (0, 4096),
+            (1, 4096),
+            (4096, 4096),
+            (5000, 8192),
+            (65536, 65536),
+            (100_000, 131_072),
+            (1_048_576, 1_048_576),
+        ];
+
+        for (requested, expected) in sizes {
+            // either end of the pipe can be used
+            for fd in [read_fd, write_fd] {
+                let rv = test_utils::check_system_call!(
+                    || unsafe { libc::fcntl(fd, libc::F_SETPIPE_SZ, requested) },
+                    &[]
+                )?;
+                test_utils::result_assert_eq(rv, expected, "Unexpected new size")?;
+
+                let rv = test_utils::check_system_call!(
+                    || unsafe { libc::fcntl(read_fd, libc::F_GETPIPE_SZ) },
+                    &[]
+                )?;
+                test_utils::result_assert_eq(rv, expected, "Unexpected size")?;
+            }
+        }
+
+        test_utils::check_system_call!(
+            || unsafe { libc::fcntl(read_fd, libc::F_SETPIPE_SZ, -1) },
+            &[libc::EINVAL]
+        )?;
+
+        Ok(())
+    })
+}
+
+fn test_set_size_busy() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(|| { unsafe { libc::pipe(fds.as_mut_ptr()) } }, &[])?;
+
+    test_utils::result_assert(fds[0] > 0, "fds[0] not set")?;
+    test_utils::result_assert(fds[1] > 0, "fds[1] not set")?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        let write_buf = vec![0u8; 10_000];
+
+        let rv = test_utils::check_system_call!(
+            || {
+                unsafe {
+                    libc::write(
+                        write_fd,
+                        write_buf.as_ptr() as *const libc::c_void,
+                        write_buf.len(),
+                    )
+                }
+            },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 10_000, "Expected to write 10000 bytes")?;
+
+        // the data doesn't fit in a smaller pipe
+        test_utils::check_system_call!(
+            || unsafe { libc::fcntl(write_fd, libc::F_SETPIPE_SZ, 4096) },
+            &[libc::EBUSY]
+        )?;
+
+        // but it does fit in a larger pipe
+        let rv = test_utils::check_system_call!(
+            || unsafe { libc::fcntl(write_fd, libc::F_SETPIPE_SZ, 16384) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 16384, "Unexpected new size")?;
+
+        Ok(())
+    })
+}
+
+fn test_set_size_above_limit() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(|| { unsafe { libc::pipe(fds.as_mut_ptr()) } }, &[])?;
+
+    test_utils::result_assert(fds[0] > 0, "fds[0] not set")?;
+    test_utils::result_assert(fds[1] > 0, "fds[1] not set")?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        // larger than /proc/sys/fs/pipe-max-size
+        test_utils::check_system_call!(
+            || unsafe { libc::fcntl(read_fd, libc::F_SETPIPE_SZ, 1_048_577) },
+            &[libc::EPERM]
+        )?;
+
+        let rv = test_utils::check_system_call!(
+            || unsafe { libc::fcntl(read_fd, libc::F_GETPIPE_SZ) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 65536, "Size should not have changed")?;
+
+        Ok(())
+    })
+}
+
+fn test_set_size_write() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(
+        || { unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK) } },
+        &[]
+    )?;
+
+    test_utils::result_assert(fds[0] > 0, "fds[0] not set")?;
+    test_utils::result_assert(fds[1] > 0, "fds[1] not set")?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        test_utils::check_system_call!(
+            || unsafe { libc::fcntl(write_fd, libc::F_SETPIPE_SZ, 4096) },
+            &[]
+        )?;
+
+        let write_buf = vec![0u8; 10_000];
+
+        // only a single page should fit
+        let rv = test_utils::check_system_call!(
+            || {
+                unsafe {
+                    libc::write(
+                        write_fd,
+                        write_buf.as_ptr() as *const libc::c_void,
+                        write_buf.len(),
+                    )
+                }
+            },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 4096, "Expected to write 4096 bytes")?;
+
+        // the pipe is full
+        test_utils::result_assert(
+            !test_utils::is_writable(write_fd, 0).unwrap(),
+            "Pipe should not be writable",
+        )?;
+
+        test_utils::check_system_call!(
+            || {
+                unsafe {
+                    libc::write(
+                        write_fd,
+                        write_buf.as_ptr() as *const libc::c_void,
+                        write_buf.len(),
+                    )
+                }
+            },
+            &[libc::EAGAIN]
+        )?;
+
+        // growing the pipe should make it writable again
+        test_utils::check_system_call!(
+            || unsafe { libc::fcntl(write_fd, libc::F_SETPIPE_SZ, 16384) },
+            &[]
+        )?;
+
+        test_utils::result_assert(
+            test_utils::is_writable(write_fd, 0).unwrap(),
+            "Pipe should be writable",
+        )?;
+
+        let rv = test_utils::check_system_call!(
+            || {
+                unsafe {
+                    libc::write(
+                        write_fd,
+                        write_buf.as_ptr() as *const libc::c_void,
+                        write_buf.len(),
+                    )
+                }
+            },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 10_000, "Expected to write 10000 bytes")?;
+
+        Ok(())
+    })
+}
+
+fn test_fionread() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(|| { unsafe { libc::pipe(fds.as_mut_ptr()) } }, &[])?;
+
+    test_utils::result_assert(fds[0] > 0, "fds[0] not set")?;
+    test_utils::result_assert(fds[1] > 0, "fds[1] not set")?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        let mut available: libc::c_int = -1;
+        test_utils::check_system_call!(
+            || unsafe { libc::ioctl(read_fd, libc::FIONREAD, &mut available) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(available, 0, "Expected an empty pipe")?;
+
+        let write_buf = [1u8, 2, 3, 4, 5];
+        test_utils::check_system_call!(
+            || {
+                unsafe {
+                    libc::write(
+                        write_fd,
+                        write_buf.as_ptr() as *const libc::c_void,
+                        write_buf.len(),
+                    )
+                }
+            },
+            &[]
+        )?;
+
+        // either end of the pipe can be used
+        for fd in [read_fd, write_fd] {
+            let mut available: libc::c_int = -1;
+            test_utils::check_system_call!(
+                || unsafe { libc::ioctl(fd, libc::FIONREAD, &mut available) },
+                &[]
+            )?;
+            test_utils::result_assert_eq(available, 5, "Expected 5 bytes")?;
+        }
+
+        let mut read_buf = [0u8; 2];
+        test_utils::check_system_call!(
+            || {
+                unsafe {
+                    libc::read(
+                        read_fd,
+                        read_buf.as_mut_ptr() as *mut libc::c_void,
+                        read_buf.len(),
+                    )
+                }
+            },
+            &[]
+        )?;
+
+        test_utils::check_system_call!(
+            || unsafe { libc::ioctl(read_fd, libc::FIONREAD, &mut available) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(available, 3, "Expected 3 bytes")?;
+
+        Ok(())
+    })
+}
+
 fn test_read_after_write_close_with_empty_buffer() -> Result<(), String> {
     let mut fds = [0 as libc::c_int; 2];
     test_utils::check_system_call!(
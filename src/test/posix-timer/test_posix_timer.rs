@@ -0,0 +1,387 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::sys::signal::{self, SigSet, SigmaskHow, Signal};
+use nix::unistd;
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+// We use the raw syscalls rather than the libc wrappers, since libc's `timer_t` isn't the
+// kernel's timer id.
+
+fn timer_create(
+    clock_id: libc::clockid_t,
+    sev: Option<&libc::sigevent>,
+) -> nix::Result<libc::c_int> {
+    let sev = match sev {
+        Some(x) => x as *const libc::sigevent,
+        None => std::ptr::null(),
+    };
+    let mut timer_id: libc::c_int = -1;
+    Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_timer_create,
+            clock_id,
+            sev,
+            &mut timer_id as *mut libc::c_int,
+        )
+    })?;
+    Ok(timer_id)
+}
+
+fn timer_settime(
+    timer_id: libc::c_int,
+    flags: libc::c_int,
+    value: Duration,
+    interval: Duration,
+) -> nix::Result<libc::itimerspec> {
+    let new_value = duration_to_itimerspec(value, interval);
+    let mut old_value = duration_to_itimerspec(Duration::ZERO, Duration::ZERO);
+    Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_timer_settime,
+            timer_id,
+            flags,
+            &new_value as *const libc::itimerspec,
+            &mut old_value as *mut libc::itimerspec,
+        )
+    })?;
+    Ok(old_value)
+}
+
+/// Returns the time remaining until the next expiration, and the interval.
+fn timer_gettime(timer_id: libc::c_int) -> nix::Result<(Duration, Duration)> {
+    let mut curr_value = duration_to_itimerspec(Duration::ZERO, Duration::ZERO);
+    Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_timer_gettime,
+            timer_id,
+            &mut curr_value as *mut libc::itimerspec,
+        )
+    })?;
+    Ok(itimerspec_to_durations(&curr_value))
+}
+
+fn timer_getoverrun(timer_id: libc::c_int) -> nix::Result<libc::c_int> {
+    let rv = Errno::result(unsafe { libc::syscall(libc::SYS_timer_getoverrun, timer_id) })?;
+    Ok(rv.try_into().unwrap())
+}
+
+fn timer_delete(timer_id: libc::c_int) -> nix::Result<()> {
+    Errno::result(unsafe { libc::syscall(libc::SYS_timer_delete, timer_id) })?;
+    Ok(())
+}
+
+fn sigevent(
+    notify: libc::c_int,
+    signal: Signal,
+    value: libc::c_int,
+    tid: libc::pid_t,
+) -> libc::sigevent {
+    let mut sev: libc::sigevent = unsafe { std::mem::zeroed() };
+    sev.sigev_notify = notify;
+    sev.sigev_signo = signal as libc::c_int;
+    sev.sigev_value = libc::sigval {
+        sival_ptr: value as usize as *mut libc::c_void,
+    };
+    sev.sigev_notify_thread_id = tid;
+    sev
+}
+
+fn duration_to_timespec(d: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: d.as_secs().try_into().unwrap(),
+        tv_nsec: d.subsec_nanos().into(),
+    }
+}
+
+fn duration_to_itimerspec(value: Duration, interval: Duration) -> libc::itimerspec {
+    libc::itimerspec {
+        it_value: duration_to_timespec(value),
+        it_interval: duration_to_timespec(interval),
+    }
+}
+
+fn timespec_to_duration(ts: &libc::timespec) -> Duration {
+    Duration::new(
+        ts.tv_sec.try_into().unwrap(),
+        ts.tv_nsec.try_into().unwrap(),
+    )
+}
+
+fn itimerspec_to_durations(its: &libc::itimerspec) -> (Duration, Duration) {
+    (
+        timespec_to_duration(&its.it_value),
+        timespec_to_duration(&its.it_interval),
+    )
+}
+
+/// Blocks `signal` and returns a signalfd for it, so that timer signals can be inspected.
+fn block_with_signalfd(signal: Signal) -> nix::Result<libc::c_int> {
+    let mut set = SigSet::empty();
+    set.add(signal);
+    signal::sigprocmask(SigmaskHow::SIG_BLOCK, Some(&set), None)?;
+    Errno::result(unsafe { libc::signalfd(-1, set.as_ref(), 0) })
+}
+
+fn unblock_and_close(signal: Signal, fd: libc::c_int) -> nix::Result<()> {
+    unistd::close(fd)?;
+    let mut set = SigSet::empty();
+    set.add(signal);
+    signal::sigprocmask(SigmaskHow::SIG_UNBLOCK, Some(&set), None)
+}
+
+/// Read a single record from a blocking signalfd.
+fn read_signalfd(fd: libc::c_int) -> nix::Result<libc::signalfd_siginfo> {
+    const RECORD_SIZE: usize = std::mem::size_of::<libc::signalfd_siginfo>();
+    let mut buf = [0u8; RECORD_SIZE];
+    let num_bytes = unistd::read(fd, &mut buf)?;
+    assert_eq!(num_bytes, RECORD_SIZE);
+    Ok(unsafe { std::ptr::read_unaligned(buf.as_ptr() as *const libc::signalfd_siginfo) })
+}
+
+fn test_create_errors() -> anyhow::Result<()> {
+    let sev = sigevent(libc::SIGEV_SIGNAL, Signal::SIGUSR1, 0, 0);
+
+    // invalid clock
+    ensure_ord!(timer_create(1234, Some(&sev)), ==, Err(Errno::EINVAL));
+
+    // invalid notification type
+    let bad_notify = sigevent(1234, Signal::SIGUSR1, 0, 0);
+    ensure_ord!(
+        timer_create(libc::CLOCK_MONOTONIC, Some(&bad_notify)),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    // invalid signal
+    let mut bad_signal = sigevent(libc::SIGEV_SIGNAL, Signal::SIGUSR1, 0, 0);
+    bad_signal.sigev_signo = 0;
+    ensure_ord!(
+        timer_create(libc::CLOCK_MONOTONIC, Some(&bad_signal)),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    // the thread must be in this process
+    let bad_thread = sigevent(libc::SIGEV_THREAD_ID, Signal::SIGUSR1, 0, 1_000_000);
+    ensure_ord!(
+        timer_create(libc::CLOCK_MONOTONIC, Some(&bad_thread)),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    // invalid timer id
+    ensure_ord!(
+        timer_settime(1000, 0, Duration::from_secs(1), Duration::ZERO).map(|_| ()),
+        ==,
+        Err(Errno::EINVAL)
+    );
+    ensure_ord!(timer_gettime(1000), ==, Err(Errno::EINVAL));
+    ensure_ord!(timer_getoverrun(1000), ==, Err(Errno::EINVAL));
+    ensure_ord!(timer_delete(1000), ==, Err(Errno::EINVAL));
+
+    Ok(())
+}
+
+fn test_multiple_timers() -> anyhow::Result<()> {
+    let sev = sigevent(libc::SIGEV_NONE, Signal::SIGUSR1, 0, 0);
+
+    let timer_1 = timer_create(libc::CLOCK_MONOTONIC, Some(&sev))?;
+    let timer_2 = timer_create(libc::CLOCK_REALTIME, None)?;
+    let timer_3 = timer_create(libc::CLOCK_BOOTTIME, Some(&sev))?;
+
+    ensure_ord!(timer_1, !=, timer_2);
+    ensure_ord!(timer_1, !=, timer_3);
+    ensure_ord!(timer_2, !=, timer_3);
+
+    // deleting one timer doesn't affect the others
+    timer_settime(timer_3, 0, Duration::from_secs(10), Duration::ZERO)?;
+    timer_delete(timer_1)?;
+    ensure_ord!(timer_gettime(timer_1), ==, Err(Errno::EINVAL));
+    ensure_ord!(timer_gettime(timer_3)?.0, >, Duration::ZERO);
+
+    // can't delete a timer twice
+    ensure_ord!(timer_delete(timer_1), ==, Err(Errno::EINVAL));
+
+    timer_delete(timer_2)?;
+    timer_delete(timer_3)?;
+
+    Ok(())
+}
+
+fn test_gettime() -> anyhow::Result<()> {
+    // no signal is sent on expiration
+    let sev = sigevent(libc::SIGEV_NONE, Signal::SIGUSR1, 0, 0);
+    let timer_id = timer_create(libc::CLOCK_MONOTONIC, Some(&sev))?;
+
+    ensure_ord!(
+        timer_gettime(timer_id)?,
+        ==,
+        (Duration::ZERO, Duration::ZERO)
+    );
+
+    let value = Duration::from_secs(10);
+    let interval = Duration::from_secs(2);
+    timer_settime(timer_id, 0, value, interval)?;
+
+    let (remaining, curr_interval) = timer_gettime(timer_id)?;
+    ensure_ord!(remaining, >, Duration::ZERO);
+    ensure_ord!(remaining, <=, value);
+    ensure_ord!(curr_interval, ==, interval);
+
+    // setting a zero value disarms the timer and returns the old value
+    let old = timer_settime(timer_id, 0, Duration::ZERO, Duration::ZERO)?;
+    let (old_remaining, old_interval) = itimerspec_to_durations(&old);
+    ensure_ord!(old_remaining, >, Duration::ZERO);
+    ensure_ord!(old_remaining, <=, value);
+    ensure_ord!(old_interval, ==, interval);
+
+    ensure_ord!(
+        timer_gettime(timer_id)?,
+        ==,
+        (Duration::ZERO, Duration::ZERO)
+    );
+
+    timer_delete(timer_id)?;
+
+    Ok(())
+}
+
+fn test_signal_process() -> anyhow::Result<()> {
+    let signalfd = block_with_signalfd(Signal::SIGUSR1)?;
+
+    let sev = sigevent(libc::SIGEV_SIGNAL, Signal::SIGUSR1, 42, 0);
+    let timer_id = timer_create(libc::CLOCK_MONOTONIC, Some(&sev))?;
+    timer_settime(timer_id, 0, Duration::from_millis(10), Duration::ZERO)?;
+
+    let info = read_signalfd(signalfd)?;
+    ensure_ord!(info.ssi_signo, ==, libc::SIGUSR1 as u32);
+    ensure_ord!(info.ssi_code, ==, libc::SI_TIMER);
+    ensure_ord!(info.ssi_tid, ==, timer_id as u32);
+    ensure_ord!(info.ssi_int, ==, 42);
+    ensure_ord!(info.ssi_overrun, ==, 0);
+
+    // a one-shot timer is disarmed after expiring
+    ensure_ord!(
+        timer_gettime(timer_id)?,
+        ==,
+        (Duration::ZERO, Duration::ZERO)
+    );
+    ensure_ord!(timer_getoverrun(timer_id), ==, Ok(0));
+
+    timer_delete(timer_id)?;
+    unblock_and_close(Signal::SIGUSR1, signalfd)?;
+
+    Ok(())
+}
+
+fn test_signal_thread() -> anyhow::Result<()> {
+    let signalfd = block_with_signalfd(Signal::SIGUSR2)?;
+
+    let tid = unistd::gettid().as_raw();
+    let sev = sigevent(libc::SIGEV_THREAD_ID, Signal::SIGUSR2, 7, tid);
+    let timer_id = timer_create(libc::CLOCK_REALTIME, Some(&sev))?;
+    timer_settime(timer_id, 0, Duration::from_millis(10), Duration::ZERO)?;
+
+    let info = read_signalfd(signalfd)?;
+    ensure_ord!(info.ssi_signo, ==, libc::SIGUSR2 as u32);
+    ensure_ord!(info.ssi_code, ==, libc::SI_TIMER);
+    ensure_ord!(info.ssi_tid, ==, timer_id as u32);
+    ensure_ord!(info.ssi_int, ==, 7);
+
+    timer_delete(timer_id)?;
+    unblock_and_close(Signal::SIGUSR2, signalfd)?;
+
+    Ok(())
+}
+
+fn test_abstime_past() -> anyhow::Result<()> {
+    let signalfd = block_with_signalfd(Signal::SIGUSR1)?;
+
+    let sev = sigevent(libc::SIGEV_SIGNAL, Signal::SIGUSR1, 0, 0);
+    let timer_id = timer_create(libc::CLOCK_REALTIME, Some(&sev))?;
+
+    // an absolute time in the past expires immediately
+    timer_settime(
+        timer_id,
+        libc::TIMER_ABSTIME,
+        Duration::from_secs(1),
+        Duration::ZERO,
+    )?;
+
+    let info = read_signalfd(signalfd)?;
+    ensure_ord!(info.ssi_code, ==, libc::SI_TIMER);
+    ensure_ord!(info.ssi_tid, ==, timer_id as u32);
+
+    timer_delete(timer_id)?;
+    unblock_and_close(Signal::SIGUSR1, signalfd)?;
+
+    Ok(())
+}
+
+fn test_overrun() -> anyhow::Result<()> {
+    let signalfd = block_with_signalfd(Signal::SIGUSR1)?;
+
+    let sev = sigevent(libc::SIGEV_SIGNAL, Signal::SIGUSR1, 0, 0);
+    let timer_id = timer_create(libc::CLOCK_MONOTONIC, Some(&sev))?;
+
+    let interval = Duration::from_millis(10);
+    timer_settime(timer_id, 0, interval, interval)?;
+
+    // the timer expires about 5 times while its signal is blocked, but only one signal is queued
+    std::thread::sleep(Duration::from_millis(55));
+
+    let info = read_signalfd(signalfd)?;
+    ensure_ord!(info.ssi_code, ==, libc::SI_TIMER);
+    ensure_ord!(info.ssi_tid, ==, timer_id as u32);
+    ensure_ord!(info.ssi_overrun, >=, 3);
+
+    // deleting the timer discards any future expirations
+    timer_delete(timer_id)?;
+    unblock_and_close(Signal::SIGUSR1, signalfd)?;
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("create-errors", test_create_errors, all_envs.clone()),
+        ShadowTest::new("multiple-timers", test_multiple_timers, all_envs.clone()),
+        ShadowTest::new("gettime", test_gettime, all_envs.clone()),
+        ShadowTest::new("signal-process", test_signal_process, all_envs.clone()),
+        ShadowTest::new("signal-thread", test_signal_thread, all_envs.clone()),
+        ShadowTest::new("abstime-past", test_abstime_past, all_envs.clone()),
+        ShadowTest::new("overrun", test_overrun, all_envs.clone()),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
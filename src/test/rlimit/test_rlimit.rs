@@ -0,0 +1,336 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use nix::errno::Errno;
+use nix::fcntl::{self, FcntlArg, OFlag};
+use nix::sys::epoll::{epoll_create1, EpollCreateFlags};
+use nix::sys::eventfd::{eventfd, EfdFlags};
+use nix::sys::resource::{getrlimit, setrlimit, Resource};
+use nix::sys::socket::{self, AddressFamily, SockFlag, SockType};
+use nix::sys::stat::Mode;
+use nix::unistd;
+use test_utils::socket_utils;
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+// the fd limit set in 'rlimit.yaml'
+const CONFIGURED_FD_LIMIT: u64 = 2048;
+
+fn prlimit(
+    pid: libc::pid_t,
+    resource: libc::__rlimit_resource_t,
+    new_limit: Option<&libc::rlimit64>,
+    old_limit: Option<&mut libc::rlimit64>,
+) -> nix::Result<()> {
+    let new_limit = new_limit.map_or(std::ptr::null(), |x| x as *const _);
+    let old_limit = old_limit.map_or(std::ptr::null_mut(), |x| x as *mut _);
+    let rv = unsafe { libc::prlimit64(pid, resource, new_limit, old_limit) };
+    Errno::result(rv).map(drop)
+}
+
+/// Returns the lowest fd that isn't in use.
+fn lowest_free_fd() -> nix::Result<libc::c_int> {
+    let fd = unistd::dup(0)?;
+    unistd::close(fd)?;
+    Ok(fd)
+}
+
+fn test_configured_limit() -> anyhow::Result<()> {
+    ensure_ord!(
+        getrlimit(Resource::RLIMIT_NOFILE),
+        ==,
+        Ok((CONFIGURED_FD_LIMIT, CONFIGURED_FD_LIMIT))
+    );
+    Ok(())
+}
+
+fn test_set_limit() -> anyhow::Result<()> {
+    let (soft, hard) = getrlimit(Resource::RLIMIT_NOFILE)?;
+
+    // the soft limit can't be larger than the hard limit
+    ensure_ord!(
+        setrlimit(Resource::RLIMIT_NOFILE, hard, hard - 1),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    // lower the soft limit, and then raise it again
+    ensure_ord!(setrlimit(Resource::RLIMIT_NOFILE, 100, hard), ==, Ok(()));
+    ensure_ord!(getrlimit(Resource::RLIMIT_NOFILE), ==, Ok((100, hard)));
+    ensure_ord!(setrlimit(Resource::RLIMIT_NOFILE, soft, hard), ==, Ok(()));
+    ensure_ord!(getrlimit(Resource::RLIMIT_NOFILE), ==, Ok((soft, hard)));
+
+    Ok(())
+}
+
+fn test_raise_hard_limit() -> anyhow::Result<()> {
+    let (soft, hard) = getrlimit(Resource::RLIMIT_NOFILE)?;
+
+    // an unprivileged process can't raise its hard limit
+    ensure_ord!(
+        setrlimit(Resource::RLIMIT_NOFILE, soft, hard + 1),
+        ==,
+        Err(Errno::EPERM)
+    );
+    ensure_ord!(getrlimit(Resource::RLIMIT_NOFILE), ==, Ok((soft, hard)));
+
+    Ok(())
+}
+
+fn test_prlimit() -> anyhow::Result<()> {
+    let (soft, hard) = getrlimit(Resource::RLIMIT_NOFILE)?;
+    let pid = unistd::getpid().as_raw();
+
+    let mut old_limit = libc::rlimit64 {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    ensure_ord!(
+        prlimit(0, libc::RLIMIT_NOFILE, None, Some(&mut old_limit)),
+        ==,
+        Ok(())
+    );
+    ensure_ord!((old_limit.rlim_cur, old_limit.rlim_max), ==, (soft, hard));
+
+    // change the limit using our own pid, and get the previous limit at the same time
+    let new_limit = libc::rlimit64 {
+        rlim_cur: 100,
+        rlim_max: hard,
+    };
+    let mut old_limit = libc::rlimit64 {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    ensure_ord!(
+        prlimit(
+            pid,
+            libc::RLIMIT_NOFILE,
+            Some(&new_limit),
+            Some(&mut old_limit)
+        ),
+        ==,
+        Ok(())
+    );
+    ensure_ord!((old_limit.rlim_cur, old_limit.rlim_max), ==, (soft, hard));
+    ensure_ord!(getrlimit(Resource::RLIMIT_NOFILE), ==, Ok((100, hard)));
+
+    // an invalid limit doesn't change anything
+    let new_limit = libc::rlimit64 {
+        rlim_cur: 200,
+        rlim_max: 100,
+    };
+    ensure_ord!(
+        prlimit(0, libc::RLIMIT_NOFILE, Some(&new_limit), None),
+        ==,
+        Err(Errno::EINVAL)
+    );
+    ensure_ord!(getrlimit(Resource::RLIMIT_NOFILE), ==, Ok((100, hard)));
+
+    setrlimit(Resource::RLIMIT_NOFILE, soft, hard)?;
+
+    Ok(())
+}
+
+fn test_emfile() -> anyhow::Result<()> {
+    let (soft, hard) = getrlimit(Resource::RLIMIT_NOFILE)?;
+
+    // only allow one more descriptor to be created
+    let fd = lowest_free_fd()?;
+    let limit = u64::try_from(fd).unwrap() + 1;
+    setrlimit(Resource::RLIMIT_NOFILE, limit, hard)?;
+
+    ensure_ord!(eventfd(0, EfdFlags::empty()), ==, Ok(fd));
+
+    ensure_ord!(eventfd(0, EfdFlags::empty()), ==, Err(Errno::EMFILE));
+    ensure_ord!(unistd::pipe(), ==, Err(Errno::EMFILE));
+    ensure_ord!(unistd::dup(0), ==, Err(Errno::EMFILE));
+    ensure_ord!(
+        epoll_create1(EpollCreateFlags::empty()),
+        ==,
+        Err(Errno::EMFILE)
+    );
+    ensure_ord!(
+        fcntl::open("/dev/null", OFlag::O_RDONLY, Mode::empty()),
+        ==,
+        Err(Errno::EMFILE)
+    );
+    for (domain, sock_type) in [
+        (AddressFamily::Unix, SockType::Stream),
+        (AddressFamily::Inet, SockType::Stream),
+        (AddressFamily::Inet, SockType::Datagram),
+    ] {
+        ensure_ord!(
+            socket::socket(domain, sock_type, SockFlag::empty(), None),
+            ==,
+            Err(Errno::EMFILE)
+        );
+    }
+
+    // a pipe needs two descriptors, so it shouldn't leave one behind when it fails
+    unistd::close(fd)?;
+    ensure_ord!(unistd::pipe(), ==, Err(Errno::EMFILE));
+    ensure_ord!(
+        socket::socketpair(
+            AddressFamily::Unix,
+            SockType::Stream,
+            None,
+            SockFlag::empty()
+        ),
+        ==,
+        Err(Errno::EMFILE)
+    );
+    ensure_ord!(eventfd(0, EfdFlags::empty()), ==, Ok(fd));
+
+    unistd::close(fd)?;
+    setrlimit(Resource::RLIMIT_NOFILE, soft, hard)?;
+
+    Ok(())
+}
+
+fn test_dup_above_limit() -> anyhow::Result<()> {
+    let (soft, hard) = getrlimit(Resource::RLIMIT_NOFILE)?;
+
+    let fd = lowest_free_fd()?;
+    let limit = u64::try_from(fd).unwrap() + 1;
+    setrlimit(Resource::RLIMIT_NOFILE, limit, hard)?;
+
+    // descriptors can't be placed at or above the limit
+    ensure_ord!(unistd::dup2(0, fd + 1), ==, Err(Errno::EBADF));
+    ensure_ord!(
+        unistd::dup3(0, fd + 1, OFlag::O_CLOEXEC),
+        ==,
+        Err(Errno::EBADF)
+    );
+    ensure_ord!(
+        fcntl::fcntl(0, FcntlArg::F_DUPFD(fd + 1)),
+        ==,
+        Err(Errno::EINVAL)
+    );
+    ensure_ord!(
+        fcntl::fcntl(0, FcntlArg::F_DUPFD_CLOEXEC(fd + 1)),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    // but they can be placed below the limit
+    ensure_ord!(unistd::dup2(0, fd), ==, Ok(fd));
+    ensure_ord!(fcntl::fcntl(0, FcntlArg::F_DUPFD(0)), ==, Err(Errno::EMFILE));
+
+    unistd::close(fd)?;
+    setrlimit(Resource::RLIMIT_NOFILE, soft, hard)?;
+
+    Ok(())
+}
+
+fn test_accept_emfile(domain: libc::c_int) -> anyhow::Result<()> {
+    let (soft, hard) = getrlimit(Resource::RLIMIT_NOFILE)?;
+
+    let listener = Errno::result(unsafe { libc::socket(domain, libc::SOCK_STREAM, 0) })?;
+    let client = Errno::result(unsafe { libc::socket(domain, libc::SOCK_STREAM, 0) })?;
+
+    socket_utils::autobind_helper(listener, domain);
+    Errno::result(unsafe { libc::listen(listener, 10) })?;
+    Errno::result(socket_utils::connect_to_peername(client, listener))?;
+
+    let fd = lowest_free_fd()?;
+    setrlimit(Resource::RLIMIT_NOFILE, u64::try_from(fd).unwrap(), hard)?;
+
+    let accept = || {
+        Errno::result(unsafe { libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut()) })
+    };
+
+    // the connection stays queued when the process has no free descriptors
+    ensure_ord!(accept(), ==, Err(Errno::EMFILE));
+    ensure_ord!(test_utils::is_readable(listener, 0), ==, Ok(true));
+
+    setrlimit(Resource::RLIMIT_NOFILE, soft, hard)?;
+    ensure_ord!(accept(), ==, Ok(fd));
+
+    for fd in [fd, client, listener] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_lowered_limit_keeps_fds() -> anyhow::Result<()> {
+    let (soft, hard) = getrlimit(Resource::RLIMIT_NOFILE)?;
+
+    let fd = eventfd(0, EfdFlags::empty())?;
+
+    // lowering the limit below an open descriptor doesn't affect the descriptor
+    setrlimit(Resource::RLIMIT_NOFILE, u64::try_from(fd).unwrap(), hard)?;
+    ensure_ord!(unistd::write(fd, &1u64.to_ne_bytes()), ==, Ok(8));
+
+    let mut buf = [0u8; 8];
+    ensure_ord!(unistd::read(fd, &mut buf), ==, Ok(8));
+    ensure_ord!(u64::from_ne_bytes(buf), ==, 1);
+
+    setrlimit(Resource::RLIMIT_NOFILE, soft, hard)?;
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        // the native limit depends on the system
+        ShadowTest::new(
+            "configured-limit",
+            test_configured_limit,
+            set![TestEnvironment::Shadow],
+        ),
+        ShadowTest::new("set-limit", test_set_limit, all_envs.clone()),
+        // a privileged native process would be able to raise its hard limit
+        ShadowTest::new(
+            "raise-hard-limit",
+            test_raise_hard_limit,
+            set![TestEnvironment::Shadow],
+        ),
+        ShadowTest::new("prlimit", test_prlimit, all_envs.clone()),
+        ShadowTest::new("emfile", test_emfile, all_envs.clone()),
+        ShadowTest::new("dup-above-limit", test_dup_above_limit, all_envs.clone()),
+        ShadowTest::new(
+            "accept-emfile-inet",
+            || test_accept_emfile(libc::AF_INET),
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "accept-emfile-unix",
+            || test_accept_emfile(libc::AF_UNIX),
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "lowered-limit-keeps-fds",
+            test_lowered_limit_keeps_fds,
+            all_envs.clone(),
+        ),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
@@ -1069,6 +1069,104 @@ fn test_hardware_error_signals() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+// Write with `write_fn` to a pipe or socket that can no longer be written to, and check that
+// SIGPIPE was (or wasn't) raised.
+fn check_sigpipe(
+    write_fn: &dyn Fn() -> libc::ssize_t,
+    expected_errno: Errno,
+    expect_signal: bool,
+) -> Result<(), Box<dyn Error>> {
+    let rv = write_fn();
+    assert_eq!(rv, -1);
+    assert_eq!(Errno::last(), expected_errno);
+
+    if !expect_signal {
+        assert_eq!(signal_channel().recv(), None);
+        return Ok(());
+    }
+
+    // Exactly one signal should have been delivered, synchronously, to this thread.
+    let record = signal_channel().recv().unwrap();
+    assert_eq!(signal_channel().recv(), None);
+
+    assert_eq!(Signal::try_from(record.signal).unwrap(), Signal::SIGPIPE);
+    assert_eq!(record.pid, unistd::getpid());
+    assert_eq!(record.tid, unistd::gettid());
+
+    let info = record.info.unwrap();
+    assert_eq!(info.si_code, SignalCode::SI_USER as i32);
+    assert_eq!(
+        unistd::Pid::from_raw(unsafe { info.si_pid() }),
+        unistd::getpid()
+    );
+
+    Ok(())
+}
+
+// Tests that writing to a pipe or stream socket with no reader raises SIGPIPE.
+fn test_sigpipe() -> Result<(), Box<dyn Error>> {
+    let old_action = unsafe {
+        signal::sigaction(
+            Signal::SIGPIPE,
+            &signal::SigAction::new(
+                signal::SigHandler::SigAction(signal_action),
+                signal::SaFlags::empty(),
+                signal::SigSet::empty(),
+            ),
+        )
+        .unwrap()
+    };
+
+    let buf = [1u8; 4];
+    let write = |fd: RawFd| unsafe { libc::write(fd, buf.as_ptr() as *const _, buf.len()) };
+    let send =
+        |fd: RawFd, flags| unsafe { libc::send(fd, buf.as_ptr() as *const _, buf.len(), flags) };
+
+    // A pipe with its read end closed.
+    let (read_fd, write_fd) = unistd::pipe().unwrap();
+    unistd::close(read_fd).unwrap();
+    check_sigpipe(&|| write(write_fd), Errno::EPIPE, true)?;
+    unistd::close(write_fd).unwrap();
+
+    // A unix stream socket with its peer closed.
+    let mut fds = [-1; 2];
+    assert_eq!(
+        unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) },
+        0
+    );
+    unistd::close(fds[1]).unwrap();
+    check_sigpipe(&|| write(fds[0]), Errno::EPIPE, true)?;
+    check_sigpipe(&|| send(fds[0], 0), Errno::EPIPE, true)?;
+    check_sigpipe(&|| send(fds[0], libc::MSG_NOSIGNAL), Errno::EPIPE, false)?;
+    unistd::close(fds[0]).unwrap();
+
+    // A unix seqpacket socket with its peer closed doesn't raise SIGPIPE.
+    let mut fds = [-1; 2];
+    assert_eq!(
+        unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_SEQPACKET, 0, fds.as_mut_ptr()) },
+        0
+    );
+    unistd::close(fds[1]).unwrap();
+    check_sigpipe(&|| send(fds[0], 0), Errno::EPIPE, false)?;
+    unistd::close(fds[0]).unwrap();
+
+    // A tcp socket that was never connected.
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd >= 0);
+    check_sigpipe(&|| send(fd, 0), Errno::EPIPE, true)?;
+    check_sigpipe(&|| send(fd, libc::MSG_NOSIGNAL), Errno::EPIPE, false)?;
+    unistd::close(fd).unwrap();
+
+    // An ignored SIGPIPE isn't delivered, but the write still fails.
+    unsafe { signal::sigaction(Signal::SIGPIPE, &old_action).unwrap() };
+    let (read_fd, write_fd) = unistd::pipe().unwrap();
+    unistd::close(read_fd).unwrap();
+    check_sigpipe(&|| write(write_fd), Errno::EPIPE, false)?;
+    unistd::close(write_fd).unwrap();
+
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     // should we restrict the tests we run?
     let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
@@ -1194,6 +1292,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             test_hardware_error_signals,
             all_envs.clone(),
         ),
+        ShadowTest::new("sigpipe", test_sigpipe, all_envs.clone()),
     ];
 
     if filter_shadow_passing {
@@ -0,0 +1,283 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::sys::epoll::{self, EpollFlags};
+use nix::sys::signal::{self, SigSet, SigmaskHow, Signal};
+use nix::unistd;
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+const RECORD_SIZE: usize = std::mem::size_of::<libc::signalfd_siginfo>();
+
+fn signalfd(fd: libc::c_int, signals: &[Signal], flags: libc::c_int) -> nix::Result<libc::c_int> {
+    let set = sigset(signals);
+    Errno::result(unsafe { libc::signalfd(fd, set.as_ref(), flags) })
+}
+
+fn sigset(signals: &[Signal]) -> SigSet {
+    let mut set = SigSet::empty();
+    for signal in signals {
+        set.add(*signal);
+    }
+    set
+}
+
+fn block(signals: &[Signal]) -> nix::Result<()> {
+    signal::sigprocmask(SigmaskHow::SIG_BLOCK, Some(&sigset(signals)), None)
+}
+
+fn unblock(signals: &[Signal]) -> nix::Result<()> {
+    signal::sigprocmask(SigmaskHow::SIG_UNBLOCK, Some(&sigset(signals)), None)
+}
+
+/// Read up to `max_records` records from the signalfd.
+fn read_records(fd: libc::c_int, max_records: usize) -> nix::Result<Vec<libc::signalfd_siginfo>> {
+    let mut buf = vec![0u8; max_records * RECORD_SIZE];
+    let num_bytes = unistd::read(fd, &mut buf)?;
+    assert_eq!(num_bytes % RECORD_SIZE, 0);
+
+    Ok(buf[..num_bytes]
+        .chunks_exact(RECORD_SIZE)
+        .map(|x| unsafe { std::ptr::read_unaligned(x.as_ptr() as *const libc::signalfd_siginfo) })
+        .collect())
+}
+
+fn raise_in_process(signal: Signal) -> nix::Result<()> {
+    signal::kill(unistd::getpid(), signal)
+}
+
+fn test_create_errors() -> anyhow::Result<()> {
+    let (readfd, writefd) = unistd::pipe()?;
+
+    // invalid flags
+    ensure_ord!(signalfd(-1, &[Signal::SIGUSR1], 0xff), ==, Err(Errno::EINVAL));
+
+    // invalid mask size
+    let mask: u64 = 0;
+    let rv = unsafe { libc::syscall(libc::SYS_signalfd4, -1, &mask, 4, 0) };
+    ensure_ord!(Errno::result(rv), ==, Err(Errno::EINVAL));
+
+    // not a signalfd
+    ensure_ord!(signalfd(readfd, &[Signal::SIGUSR1], 0), ==, Err(Errno::EINVAL));
+
+    // not a valid fd
+    ensure_ord!(signalfd(1000, &[Signal::SIGUSR1], 0), ==, Err(Errno::EBADF));
+
+    let fd = signalfd(
+        -1,
+        &[Signal::SIGUSR1],
+        libc::SFD_NONBLOCK | libc::SFD_CLOEXEC,
+    )?;
+
+    for fd in [fd, readfd, writefd] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_nonblocking_read() -> anyhow::Result<()> {
+    block(&[Signal::SIGUSR1])?;
+    let fd = signalfd(-1, &[Signal::SIGUSR1], libc::SFD_NONBLOCK)?;
+
+    // nothing pending
+    ensure_ord!(read_records(fd, 1).map(|x| x.len()), ==, Err(Errno::EAGAIN));
+
+    raise_in_process(Signal::SIGUSR1)?;
+
+    // buffer too small
+    let mut buf = [0u8; RECORD_SIZE - 1];
+    ensure_ord!(unistd::read(fd, &mut buf), ==, Err(Errno::EINVAL));
+
+    let records = read_records(fd, 1)?;
+    ensure_ord!(records.len(), ==, 1);
+    ensure_ord!(records[0].ssi_signo, ==, libc::SIGUSR1 as u32);
+    ensure_ord!(records[0].ssi_code, ==, libc::SI_USER);
+    ensure_ord!(records[0].ssi_pid, ==, unistd::getpid().as_raw() as u32);
+
+    // the signal was consumed
+    ensure_ord!(read_records(fd, 1).map(|x| x.len()), ==, Err(Errno::EAGAIN));
+
+    unistd::close(fd)?;
+    unblock(&[Signal::SIGUSR1])?;
+
+    Ok(())
+}
+
+fn test_multiple_records() -> anyhow::Result<()> {
+    let signals = [Signal::SIGUSR1, Signal::SIGUSR2];
+    block(&signals)?;
+    let fd = signalfd(-1, &signals, libc::SFD_NONBLOCK)?;
+
+    raise_in_process(Signal::SIGUSR2)?;
+
+    // a thread-directed signal
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_tgkill,
+            unistd::getpid().as_raw(),
+            unistd::gettid().as_raw(),
+            libc::SIGUSR1,
+        )
+    };
+    Errno::result(rv)?;
+
+    // lower signal numbers are dequeued first
+    let records = read_records(fd, 4)?;
+    ensure_ord!(records.len(), ==, 2);
+    ensure_ord!(records[0].ssi_signo, ==, libc::SIGUSR1 as u32);
+    ensure_ord!(records[0].ssi_code, ==, libc::SI_TKILL);
+    ensure_ord!(records[1].ssi_signo, ==, libc::SIGUSR2 as u32);
+    ensure_ord!(records[1].ssi_code, ==, libc::SI_USER);
+
+    unistd::close(fd)?;
+    unblock(&signals)?;
+
+    Ok(())
+}
+
+fn test_update_mask() -> anyhow::Result<()> {
+    let signals = [Signal::SIGUSR1, Signal::SIGUSR2];
+    block(&signals)?;
+    let fd = signalfd(-1, &[Signal::SIGUSR1], libc::SFD_NONBLOCK)?;
+
+    raise_in_process(Signal::SIGUSR1)?;
+
+    // replace the mask, which returns the same fd
+    ensure_ord!(signalfd(fd, &[Signal::SIGUSR2], 0), ==, Ok(fd));
+
+    // SIGUSR1 is pending but no longer in the mask
+    ensure_ord!(read_records(fd, 1).map(|x| x.len()), ==, Err(Errno::EAGAIN));
+
+    // restore the mask to consume SIGUSR1
+    ensure_ord!(signalfd(fd, &[Signal::SIGUSR1], 0), ==, Ok(fd));
+    let records = read_records(fd, 1)?;
+    ensure_ord!(records.len(), ==, 1);
+    ensure_ord!(records[0].ssi_signo, ==, libc::SIGUSR1 as u32);
+
+    unistd::close(fd)?;
+    unblock(&signals)?;
+
+    Ok(())
+}
+
+fn test_blocking_read() -> anyhow::Result<()> {
+    block(&[Signal::SIGUSR1])?;
+    let fd = signalfd(-1, &[Signal::SIGUSR1], 0)?;
+
+    // the new thread inherits our signal mask
+    let thread = std::thread::spawn(|| {
+        std::thread::sleep(Duration::from_millis(10));
+        raise_in_process(Signal::SIGUSR1).unwrap();
+    });
+
+    // should block until the other thread sends the signal
+    let records = read_records(fd, 1)?;
+    ensure_ord!(records.len(), ==, 1);
+    ensure_ord!(records[0].ssi_signo, ==, libc::SIGUSR1 as u32);
+
+    thread.join().unwrap();
+
+    unistd::close(fd)?;
+    unblock(&[Signal::SIGUSR1])?;
+
+    Ok(())
+}
+
+fn test_sigchld() -> anyhow::Result<()> {
+    // SIGCHLD is ignored by default, but blocked signals remain pending
+    block(&[Signal::SIGCHLD])?;
+    let fd = signalfd(-1, &[Signal::SIGCHLD], 0)?;
+
+    let pid = unsafe { libc::fork() };
+    if pid == 0 {
+        unsafe { libc::_exit(3) };
+    }
+    Errno::result(pid)?;
+
+    let records = read_records(fd, 1)?;
+    ensure_ord!(records.len(), ==, 1);
+    ensure_ord!(records[0].ssi_signo, ==, libc::SIGCHLD as u32);
+    ensure_ord!(records[0].ssi_code, ==, libc::CLD_EXITED);
+    ensure_ord!(records[0].ssi_pid, ==, pid as u32);
+    ensure_ord!(records[0].ssi_status, ==, 3);
+
+    let mut status = 0;
+    ensure_ord!(unsafe { libc::waitpid(pid, &mut status, 0) }, ==, pid);
+
+    unistd::close(fd)?;
+    unblock(&[Signal::SIGCHLD])?;
+
+    Ok(())
+}
+
+fn test_epoll() -> anyhow::Result<()> {
+    block(&[Signal::SIGUSR1])?;
+    let fd = signalfd(-1, &[Signal::SIGUSR1], libc::SFD_NONBLOCK)?;
+    let epollfd = epoll::epoll_create()?;
+
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0);
+    epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlAdd, fd, Some(&mut event))?;
+
+    let mut events = [epoll::EpollEvent::empty(); 10];
+
+    // not readable until a signal is pending
+    ensure_ord!(epoll::epoll_wait(epollfd, &mut events, 0), ==, Ok(0));
+
+    raise_in_process(Signal::SIGUSR1)?;
+
+    ensure_ord!(epoll::epoll_wait(epollfd, &mut events, 100), ==, Ok(1));
+    ensure_ord!(events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0));
+
+    // no longer readable after reading
+    ensure_ord!(read_records(fd, 1)?.len(), ==, 1);
+    ensure_ord!(epoll::epoll_wait(epollfd, &mut events, 0), ==, Ok(0));
+
+    unistd::close(epollfd)?;
+    unistd::close(fd)?;
+    unblock(&[Signal::SIGUSR1])?;
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("create-errors", test_create_errors, all_envs.clone()),
+        ShadowTest::new("nonblocking-read", test_nonblocking_read, all_envs.clone()),
+        ShadowTest::new("multiple-records", test_multiple_records, all_envs.clone()),
+        ShadowTest::new("update-mask", test_update_mask, all_envs.clone()),
+        ShadowTest::new("blocking-read", test_blocking_read, all_envs.clone()),
+        ShadowTest::new("sigchld", test_sigchld, all_envs.clone()),
+        ShadowTest::new("epoll", test_epoll, all_envs.clone()),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
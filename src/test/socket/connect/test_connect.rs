@@ -172,8 +172,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
                 tests.extend(vec![test_utils::ShadowTest::new(
                     &append_args("test_af_unspec"),
                     move || test_af_unspec(domain, sock_type, flag),
-                    // TODO: shadow doesn't support AF_UNSPEC for inet or unix sockets
-                    set![TestEnv::Libc],
+                    match (domain, sock_type) {
+                        (libc::AF_INET, libc::SOCK_DGRAM) => set![TestEnv::Libc, TestEnv::Shadow],
+                        // TODO: shadow doesn't support AF_UNSPEC for tcp or unix sockets
+                        _ => set![TestEnv::Libc],
+                    },
                 )]);
             }
         }
@@ -240,8 +243,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
                 tests.extend(vec![test_utils::ShadowTest::new(
                     &append_args("test_af_unspec_after_connect"),
                     move || test_af_unspec_after_connect(method, sock_type, flag),
-                    // TODO: shadow doesn't support AF_UNSPEC for inet or unix sockets
-                    set![TestEnv::Libc],
+                    match (method.domain(), sock_type) {
+                        (libc::AF_INET, libc::SOCK_DGRAM) => set![TestEnv::Libc, TestEnv::Shadow],
+                        // TODO: shadow doesn't support AF_UNSPEC for tcp or unix sockets
+                        _ => set![TestEnv::Libc],
+                    },
                 )]);
             }
         }
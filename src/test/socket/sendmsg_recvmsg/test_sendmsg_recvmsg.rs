@@ -0,0 +1,420 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use nix::errno::Errno;
+
+use test_utils::socket_utils::{socket_init_helper, SocketInitMethod};
+use test_utils::TestEnvironment as TestEnv;
+use test_utils::{ensure_ord, set};
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), anyhow::Error>> {
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![];
+
+    let init_methods = [
+        SocketInitMethod::Inet,
+        SocketInitMethod::Unix,
+        SocketInitMethod::UnixSocketpair,
+    ];
+
+    for &init_method in init_methods.iter() {
+        let sock_types = match init_method.domain() {
+            libc::AF_INET => &[libc::SOCK_STREAM, libc::SOCK_DGRAM][..],
+            libc::AF_UNIX => &[libc::SOCK_STREAM, libc::SOCK_DGRAM, libc::SOCK_SEQPACKET][..],
+            _ => unimplemented!(),
+        };
+
+        for &sock_type in sock_types.iter() {
+            // add details to the test names to avoid duplicates
+            let append_args = |s| {
+                format!(
+                    "{} <init_method={:?}, sock_type={}>",
+                    s, init_method, sock_type
+                )
+            };
+
+            tests.extend(vec![
+                test_utils::ShadowTest::new(
+                    &append_args("test_sendmsg_gather"),
+                    move || test_sendmsg_gather(init_method, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_recvmsg_scatter"),
+                    move || test_recvmsg_scatter(init_method, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_recvmsg_scatter_peek"),
+                    move || test_recvmsg_scatter_peek(init_method, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_sendmmsg"),
+                    move || test_sendmmsg(init_method, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+            ]);
+
+            // message boundaries are only preserved for message-based sockets
+            if sock_type != libc::SOCK_STREAM {
+                tests.extend(vec![
+                    test_utils::ShadowTest::new(
+                        &append_args("test_recvmmsg"),
+                        move || test_recvmmsg(init_method, sock_type),
+                        set![TestEnv::Libc, TestEnv::Shadow],
+                    ),
+                    test_utils::ShadowTest::new(
+                        &append_args("test_recvmmsg_timeout"),
+                        move || test_recvmmsg_timeout(init_method, sock_type),
+                        set![TestEnv::Libc, TestEnv::Shadow],
+                    ),
+                    test_utils::ShadowTest::new(
+                        &append_args("test_recvmmsg_zero_timeout"),
+                        move || test_recvmmsg_zero_timeout(init_method, sock_type),
+                        set![TestEnv::Libc, TestEnv::Shadow],
+                    ),
+                    test_utils::ShadowTest::new(
+                        &append_args("test_recvmmsg_invalid_timeout"),
+                        move || test_recvmmsg_invalid_timeout(init_method, sock_type),
+                        set![TestEnv::Libc, TestEnv::Shadow],
+                    ),
+                ]);
+            }
+        }
+    }
+
+    tests
+}
+
+fn iovecs(bufs: &[&[u8]]) -> Vec<libc::iovec> {
+    bufs.iter()
+        .map(|x| libc::iovec {
+            iov_base: x.as_ptr() as *mut libc::c_void,
+            iov_len: x.len(),
+        })
+        .collect()
+}
+
+fn iovecs_mut(bufs: &mut [&mut [u8]]) -> Vec<libc::iovec> {
+    bufs.iter_mut()
+        .map(|x| libc::iovec {
+            iov_base: x.as_mut_ptr() as *mut libc::c_void,
+            iov_len: x.len(),
+        })
+        .collect()
+}
+
+fn msghdr(iovs: &mut [libc::iovec]) -> libc::msghdr {
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = iovs.as_mut_ptr();
+    msg.msg_iovlen = iovs.len();
+    msg
+}
+
+fn sendmsg(fd: libc::c_int, bufs: &[&[u8]], flags: libc::c_int) -> Result<usize, Errno> {
+    let mut iovs = iovecs(bufs);
+    let msg = msghdr(&mut iovs);
+    let rv = Errno::result(unsafe { libc::sendmsg(fd, &msg, flags) })?;
+    Ok(rv.try_into().unwrap())
+}
+
+fn recvmsg(fd: libc::c_int, bufs: &mut [&mut [u8]], flags: libc::c_int) -> Result<usize, Errno> {
+    let mut iovs = iovecs_mut(bufs);
+    let mut msg = msghdr(&mut iovs);
+    let rv = Errno::result(unsafe { libc::recvmsg(fd, &mut msg, flags) })?;
+    Ok(rv.try_into().unwrap())
+}
+
+/// Wait for data sent over the network to arrive at the peer.
+fn wait_for_delivery() {
+    std::thread::sleep(std::time::Duration::from_millis(10));
+}
+
+/// Read exactly `len` bytes from a stream socket.
+fn recv_all(fd: libc::c_int, len: usize) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    let mut offset = 0;
+    while offset < len {
+        let rv = recvmsg(fd, &mut [&mut bytes[offset..]], 0)?;
+        ensure_ord!(rv, >, 0);
+        offset += rv;
+    }
+    Ok(bytes)
+}
+
+fn test_sendmsg_gather(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+) -> anyhow::Result<()> {
+    let (fd_client, fd_peer) = socket_init_helper(init_method, sock_type, 0, false);
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        ensure_ord!(
+            sendmsg(fd_client, &[b"ab", b"", b"cde", b"fghi"], 0),
+            ==,
+            Ok(9)
+        );
+
+        // the io vectors are sent as a single message
+        let mut buf = [0u8; 20];
+        ensure_ord!(recvmsg(fd_peer, &mut [&mut buf], 0), ==, Ok(9));
+        ensure_ord!(&buf[..9], ==, b"abcdefghi");
+
+        Ok(())
+    })
+}
+
+fn test_recvmsg_scatter(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+) -> anyhow::Result<()> {
+    let (fd_client, fd_peer) = socket_init_helper(init_method, sock_type, 0, false);
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        ensure_ord!(sendmsg(fd_client, &[b"abcdefghi"], 0), ==, Ok(9));
+        wait_for_delivery();
+
+        let mut buf_1 = [0u8; 4];
+        let mut buf_2 = [0u8; 0];
+        let mut buf_3 = [0u8; 10];
+        ensure_ord!(
+            recvmsg(fd_peer, &mut [&mut buf_1, &mut buf_2, &mut buf_3], 0),
+            ==,
+            Ok(9)
+        );
+        ensure_ord!(&buf_1, ==, b"abcd");
+        ensure_ord!(&buf_3[..5], ==, b"efghi");
+
+        Ok(())
+    })
+}
+
+fn test_recvmsg_scatter_peek(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+) -> anyhow::Result<()> {
+    let (fd_client, fd_peer) = socket_init_helper(init_method, sock_type, 0, false);
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        ensure_ord!(sendmsg(fd_client, &[b"abcdefghi"], 0), ==, Ok(9));
+        wait_for_delivery();
+
+        // peeking must not consume the data, even when it's spread over several io vectors
+        let mut buf_1 = [0u8; 3];
+        let mut buf_2 = [0u8; 3];
+        ensure_ord!(
+            recvmsg(fd_peer, &mut [&mut buf_1, &mut buf_2], libc::MSG_PEEK),
+            ==,
+            Ok(6)
+        );
+        ensure_ord!(&buf_1, ==, b"abc");
+        ensure_ord!(&buf_2, ==, b"def");
+
+        let mut buf = [0u8; 20];
+        ensure_ord!(recvmsg(fd_peer, &mut [&mut buf], 0), ==, Ok(9));
+        ensure_ord!(&buf[..9], ==, b"abcdefghi");
+
+        Ok(())
+    })
+}
+
+fn test_sendmmsg(init_method: SocketInitMethod, sock_type: libc::c_int) -> anyhow::Result<()> {
+    let (fd_client, fd_peer) = socket_init_helper(init_method, sock_type, 0, false);
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        let mut iovs = [
+            iovecs(&[b"ab", b"cd"]),
+            iovecs(&[b"efg"]),
+            iovecs(&[b"h", b"", b"ij"]),
+        ];
+        let mut msgs: Vec<libc::mmsghdr> = iovs
+            .iter_mut()
+            .map(|x| libc::mmsghdr {
+                msg_hdr: msghdr(x),
+                msg_len: 0,
+            })
+            .collect();
+
+        let rv = Errno::result(unsafe {
+            libc::sendmmsg(fd_client, msgs.as_mut_ptr(), msgs.len() as libc::c_uint, 0)
+        });
+        ensure_ord!(rv, ==, Ok(3));
+        let lens: Vec<_> = msgs.iter().map(|x| x.msg_len).collect();
+        ensure_ord!(lens, ==, vec![4, 3, 3]);
+
+        if sock_type == libc::SOCK_STREAM {
+            ensure_ord!(recv_all(fd_peer, 10)?, ==, b"abcdefghij");
+        } else {
+            for expected in [&b"abcd"[..], b"efg", b"hij"] {
+                let mut buf = [0u8; 20];
+                ensure_ord!(recvmsg(fd_peer, &mut [&mut buf], 0), ==, Ok(expected.len()));
+                ensure_ord!(&buf[..expected.len()], ==, expected);
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Send three messages and receive them with `recvmmsg`, where each message is received into two
+/// io vectors. Returns the `recvmmsg` return value, the received messages and their flags, and
+/// the updated timeout.
+fn recvmmsg_helper(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+    timeout: Option<libc::timespec>,
+) -> anyhow::Result<(
+    Result<libc::c_int, Errno>,
+    Vec<(Vec<u8>, libc::c_int)>,
+    Option<libc::timespec>,
+)> {
+    let (fd_client, fd_peer) = socket_init_helper(init_method, sock_type, 0, false);
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        for buf in [&b"abcd"[..], b"efg", b"hijkl"] {
+            ensure_ord!(sendmsg(fd_client, &[buf], 0), ==, Ok(buf.len()));
+        }
+        wait_for_delivery();
+
+        let mut bufs = [[0u8; 2]; 6];
+        let mut iovs: Vec<Vec<libc::iovec>> = bufs
+            .chunks_mut(2)
+            .map(|x| {
+                let (a, b) = x.split_at_mut(1);
+                iovecs_mut(&mut [&mut a[0][..], &mut b[0][..]])
+            })
+            .collect();
+        let mut msgs: Vec<libc::mmsghdr> = iovs
+            .iter_mut()
+            .map(|x| libc::mmsghdr {
+                msg_hdr: msghdr(x),
+                msg_len: 0,
+            })
+            .collect();
+
+        let mut timeout = timeout;
+        let timeout_ptr = timeout
+            .as_mut()
+            .map(|x| x as *mut libc::timespec)
+            .unwrap_or(std::ptr::null_mut());
+
+        let rv = Errno::result(unsafe {
+            libc::recvmmsg(
+                fd_peer,
+                msgs.as_mut_ptr(),
+                msgs.len() as libc::c_uint,
+                0,
+                timeout_ptr,
+            )
+        });
+
+        let num_msgs = rv.map(|x| x as usize).unwrap_or(0);
+        let received = msgs[..num_msgs]
+            .iter()
+            .zip(bufs.chunks(2))
+            .map(|(msg, bufs)| {
+                let len = msg.msg_len as usize;
+                (bufs.concat()[..len].to_vec(), msg.msg_hdr.msg_flags)
+            })
+            .collect();
+
+        Ok((rv, received, timeout))
+    })
+}
+
+fn test_recvmmsg(init_method: SocketInitMethod, sock_type: libc::c_int) -> anyhow::Result<()> {
+    let (rv, received, _) = recvmmsg_helper(init_method, sock_type, None)?;
+    ensure_ord!(rv, ==, Ok(3));
+    // the last message was truncated
+    ensure_ord!(
+        received,
+        ==,
+        vec![
+            (b"abcd".to_vec(), 0),
+            (b"efg".to_vec(), 0),
+            (b"hijk".to_vec(), libc::MSG_TRUNC)
+        ]
+    );
+    Ok(())
+}
+
+fn test_recvmmsg_timeout(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+) -> anyhow::Result<()> {
+    let timeout = libc::timespec {
+        tv_sec: 1,
+        tv_nsec: 0,
+    };
+    let (rv, received, timeout) = recvmmsg_helper(init_method, sock_type, Some(timeout))?;
+    ensure_ord!(rv, ==, Ok(3));
+    ensure_ord!(received.len(), ==, 3);
+
+    // the timeout is updated with the remaining time
+    let timeout = timeout.unwrap();
+    ensure_ord!(timeout.tv_sec, <=, 1);
+    ensure_ord!(timeout.tv_sec, >=, 0);
+    Ok(())
+}
+
+fn test_recvmmsg_zero_timeout(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+) -> anyhow::Result<()> {
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // the timeout has expired after the first message is received
+    let (rv, received, timeout) = recvmmsg_helper(init_method, sock_type, Some(timeout))?;
+    ensure_ord!(rv, ==, Ok(1));
+    ensure_ord!(received, ==, vec![(b"abcd".to_vec(), 0)]);
+
+    let timeout = timeout.unwrap();
+    ensure_ord!((timeout.tv_sec, timeout.tv_nsec), ==, (0, 0));
+    Ok(())
+}
+
+fn test_recvmmsg_invalid_timeout(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+) -> anyhow::Result<()> {
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 1_000_000_000,
+    };
+    let (rv, received, _) = recvmmsg_helper(init_method, sock_type, Some(timeout))?;
+    ensure_ord!(rv, ==, Err(Errno::EINVAL));
+    ensure_ord!(received.len(), ==, 0);
+    Ok(())
+}
@@ -189,14 +189,31 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
                     move || test_flag_dontwait(method, sock_type),
                     set![TestEnv::Libc, TestEnv::Shadow],
                 ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_flag_peek"),
+                    move || test_flag_peek(method, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_flag_trunc"),
+                    move || test_flag_trunc(method, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
             ]);
         }
 
-        tests.extend(vec![test_utils::ShadowTest::new(
-            &append_args("test_nonblocking_stream"),
-            move || test_nonblocking_stream(method),
-            set![TestEnv::Libc, TestEnv::Shadow],
-        )]);
+        tests.extend(vec![
+            test_utils::ShadowTest::new(
+                &append_args("test_nonblocking_stream"),
+                move || test_nonblocking_stream(method),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_flag_waitall"),
+                move || test_flag_waitall(method),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+        ]);
     }
 
     for &method in &[SocketInitMethod::Unix, SocketInitMethod::UnixSocketpair] {
@@ -550,6 +567,170 @@ fn test_flag_dontwait(init_method: SocketInitMethod, sock_type: libc::c_int) ->
     })
 }
 
+/// Test recvfrom() using the `MSG_PEEK` flag.
+fn test_flag_peek(init_method: SocketInitMethod, sock_type: libc::c_int) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        init_method,
+        sock_type,
+        libc::SOCK_NONBLOCK,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        simple_sendto_helper(fd_client, &[1, 2, 3, 4, 5], &[], true)?;
+
+        // shadow needs to run events
+        assert_eq!(unsafe { libc::usleep(10000) }, 0);
+
+        // peek at the first 3 bytes
+        let mut buf = vec![0u8; 3];
+        let mut recvfrom_args = RecvfromArguments {
+            fd: fd_server,
+            len: buf.len(),
+            buf: Some(&mut buf),
+            flags: libc::MSG_PEEK,
+            ..Default::default()
+        };
+        check_recvfrom_call(&mut recvfrom_args, &[], true)?;
+        test_utils::result_assert_eq(&buf[..], &[1, 2, 3][..], "Unexpected peeked bytes")?;
+
+        // peeking again should return the same bytes
+        let mut buf = vec![0u8; 3];
+        let mut recvfrom_args = RecvfromArguments {
+            fd: fd_server,
+            len: buf.len(),
+            buf: Some(&mut buf),
+            flags: libc::MSG_PEEK,
+            ..Default::default()
+        };
+        check_recvfrom_call(&mut recvfrom_args, &[], true)?;
+        test_utils::result_assert_eq(&buf[..], &[1, 2, 3][..], "Unexpected peeked bytes")?;
+
+        // the peeked data should still be available to a regular read
+        let mut buf = vec![0u8; 5];
+        simple_recvfrom_helper(fd_server, &mut buf, &[], true)?;
+        test_utils::result_assert_eq(&buf[..], &[1, 2, 3, 4, 5][..], "Unexpected read bytes")?;
+
+        // nothing should remain
+        simple_recvfrom_helper(fd_server, &mut [0u8; 5], &[libc::EAGAIN], false)?;
+
+        Ok(())
+    })
+}
+
+/// Test recvfrom() using the `MSG_WAITALL` flag on a stream socket, where the message is split
+/// across several sends.
+fn test_flag_waitall(init_method: SocketInitMethod) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        init_method,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        // send the message in three parts, with a delay before each
+        let sender = std::thread::spawn(move || -> Result<(), String> {
+            for i in 0..3 {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                simple_sendto_helper(fd_client, &[i; 10], &[], true)?;
+            }
+            Ok(())
+        });
+
+        // a single recvfrom() should block until the full message has been received
+        let mut buf = vec![0u8; 30];
+        let mut recvfrom_args = RecvfromArguments {
+            fd: fd_server,
+            len: buf.len(),
+            buf: Some(&mut buf),
+            flags: libc::MSG_WAITALL,
+            ..Default::default()
+        };
+        check_recvfrom_call(&mut recvfrom_args, &[], true)?;
+
+        sender.join().unwrap()?;
+
+        let expected = [[0u8; 10], [1; 10], [2; 10]].concat();
+        test_utils::result_assert_eq(&buf, &expected, "Unexpected received bytes")?;
+
+        // if the peer stops sending, the part of the message that was received is returned
+        simple_sendto_helper(fd_client, &[3; 10], &[], true)?;
+        assert_eq!(unsafe { libc::shutdown(fd_client, libc::SHUT_WR) }, 0);
+
+        let mut buf = vec![0u8; 30];
+        let mut recvfrom_args = RecvfromArguments {
+            fd: fd_server,
+            len: buf.len(),
+            buf: Some(&mut buf),
+            flags: libc::MSG_WAITALL,
+            ..Default::default()
+        };
+        let rv = check_recvfrom_call(&mut recvfrom_args, &[], false)?;
+        test_utils::result_assert_eq(rv, 10, "Unexpected number of bytes received")?;
+        test_utils::result_assert_eq(&buf[..10], &[3; 10][..], "Unexpected received bytes")?;
+
+        Ok(())
+    })
+}
+
+/// Test recvfrom() using the `MSG_TRUNC` flag.
+fn test_flag_trunc(init_method: SocketInitMethod, sock_type: libc::c_int) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        init_method,
+        sock_type,
+        libc::SOCK_NONBLOCK,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        simple_sendto_helper(fd_client, &[1, 2, 3, 4, 5], &[], true)?;
+
+        // shadow needs to run events
+        assert_eq!(unsafe { libc::usleep(10000) }, 0);
+
+        let mut buf = vec![0u8; 3];
+        let mut recvfrom_args = RecvfromArguments {
+            fd: fd_server,
+            len: buf.len(),
+            buf: Some(&mut buf),
+            flags: libc::MSG_TRUNC,
+            ..Default::default()
+        };
+        let rv = check_recvfrom_call(&mut recvfrom_args, &[], false)?;
+
+        match (init_method.domain(), sock_type) {
+            // tcp discards the data rather than copying it
+            (libc::AF_INET, libc::SOCK_STREAM) => {
+                test_utils::result_assert_eq(rv, 3, "Unexpected return value")?;
+                test_utils::result_assert_eq(&buf[..], &[0, 0, 0][..], "Unexpected bytes")?;
+            }
+            // unix stream sockets ignore the flag
+            (libc::AF_UNIX, libc::SOCK_STREAM) => {
+                test_utils::result_assert_eq(rv, 3, "Unexpected return value")?;
+                test_utils::result_assert_eq(&buf[..], &[1, 2, 3][..], "Unexpected bytes")?;
+            }
+            // message-based sockets return the full length of the message
+            _ => {
+                test_utils::result_assert_eq(rv, 5, "Unexpected return value")?;
+                test_utils::result_assert_eq(&buf[..], &[1, 2, 3][..], "Unexpected bytes")?;
+            }
+        }
+
+        if sock_type == libc::SOCK_STREAM {
+            // the remaining bytes of the stream are still available
+            let mut buf = vec![0u8; 2];
+            simple_recvfrom_helper(fd_server, &mut buf, &[], true)?;
+            test_utils::result_assert_eq(&buf[..], &[4, 5][..], "Unexpected read bytes")?;
+        } else {
+            // the rest of the message was discarded
+            simple_recvfrom_helper(fd_server, &mut [0u8; 5], &[libc::EAGAIN], false)?;
+        }
+
+        Ok(())
+    })
+}
+
 /// Test sendto() and recvfrom() using a non-blocking stream socket.
 fn test_nonblocking_stream(init_method: SocketInitMethod) -> Result<(), String> {
     let (fd_client, fd_peer) = socket_init_helper(
@@ -186,6 +186,39 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
         }
     }
 
+    let unix_sock_types = [libc::SOCK_STREAM, libc::SOCK_DGRAM, libc::SOCK_SEQPACKET];
+
+    for &sock_type in unix_sock_types.iter() {
+        for &flag in flags.iter() {
+            // add details to the test names to avoid duplicates
+            let append_args = |s| format!("{} <domain=unix,type={},flag={}>", s, sock_type, flag);
+
+            let mut more_tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+                test_utils::ShadowTest::new(
+                    &append_args("test_unix_not_connected"),
+                    move || test_unix_not_connected(sock_type, flag),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_unix_shut_rd"),
+                    move || test_unix_shut_rd(sock_type, flag),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+            ];
+
+            // shutting down writing has no effect for unix dgram sockets
+            if sock_type != libc::SOCK_DGRAM {
+                more_tests.push(test_utils::ShadowTest::new(
+                    &append_args("test_unix_shut_wr"),
+                    move || test_unix_shut_wr(sock_type, flag),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ));
+            }
+
+            tests.extend(more_tests);
+        }
+    }
+
     tests
 }
 
@@ -931,6 +964,137 @@ fn test_tcp_fin_correctness(domain: libc::c_int, flag: libc::c_int) -> Result<()
     })
 }
 
+/// Test shutdown() using a non-connected unix socket. Unlike TCP, linux doesn't return an error.
+fn test_unix_not_connected(sock_type: libc::c_int, flag: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_UNIX, sock_type | flag, 0) };
+    assert!(fd >= 0);
+
+    let args = ShutdownArguments {
+        fd,
+        how: libc::SHUT_RDWR,
+    };
+
+    test_utils::run_and_close_fds(&[fd], || check_shutdown_call(&args, &[]))
+}
+
+/// Generate a pair of connected unix sockets.
+fn setup_unix_sockets(sock_type: libc::c_int, flag: libc::c_int) -> (libc::c_int, libc::c_int) {
+    let mut fds = [-1; 2];
+    let rv = unsafe { libc::socketpair(libc::AF_UNIX, sock_type | flag, 0, fds.as_mut_ptr()) };
+    assert_eq!(rv, 0);
+    (fds[0], fds[1])
+}
+
+/// A wrapper for libc::send() with `MSG_NOSIGNAL` so that we get EPIPE rather than SIGPIPE.
+fn send_nosignal(fd: libc::c_int, buf: &[u8]) -> libc::ssize_t {
+    unsafe {
+        libc::send(
+            fd,
+            buf.as_ptr() as *const libc::c_void,
+            buf.len(),
+            libc::MSG_NOSIGNAL,
+        )
+    }
+}
+
+/// Returns true if the fd is readable without blocking.
+fn is_readable(fd: libc::c_int) -> bool {
+    let mut pfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let rv = unsafe { libc::poll(&mut pfd, 1, 0) };
+    assert!(rv >= 0);
+    pfd.revents & libc::POLLIN != 0
+}
+
+/// Test reading and writing on unix sockets after shutting down reading.
+fn test_unix_shut_rd(sock_type: libc::c_int, flag: libc::c_int) -> Result<(), String> {
+    let (fd_1, fd_2) = setup_unix_sockets(sock_type, flag);
+
+    test_utils::run_and_close_fds(&[fd_1, fd_2], || {
+        let message = [1u8, 2, 3, 4, 5];
+
+        write_all(fd_2, &message);
+
+        check_shutdown_call(
+            &ShutdownArguments {
+                fd: fd_1,
+                how: libc::SHUT_RD,
+            },
+            &[],
+        )?;
+
+        // data sent before the shutdown can still be read
+        let mut buf = [0u8; 10];
+        let rv = test_utils::check_system_call!(|| read_once(fd_1, &mut buf), &[])?;
+        test_utils::result_assert_eq(rv, message.len() as isize, "Unexpected read length")?;
+
+        test_utils::result_assert(is_readable(fd_1), "Socket should be readable")?;
+
+        // linux returns EAGAIN for non-blocking dgram sockets, otherwise EOF
+        let expected_errnos = if sock_type == libc::SOCK_DGRAM && flag == libc::SOCK_NONBLOCK {
+            vec![libc::EAGAIN]
+        } else {
+            vec![]
+        };
+        let rv = test_utils::check_system_call!(|| read_once(fd_1, &mut buf), &expected_errnos)?;
+        if expected_errnos.is_empty() {
+            test_utils::result_assert_eq(rv, 0, "Expected an EOF")?;
+        }
+
+        // the peer can no longer send to this socket
+        test_utils::check_system_call!(|| send_nosignal(fd_2, &message), &[libc::EPIPE])?;
+
+        // but this socket can still send to the peer
+        write_all(fd_1, &message);
+        let rv = test_utils::check_system_call!(|| read_once(fd_2, &mut buf), &[])?;
+        test_utils::result_assert_eq(rv, message.len() as isize, "Unexpected read length")?;
+
+        Ok(())
+    })
+}
+
+/// Test reading and writing on unix sockets after shutting down writing.
+fn test_unix_shut_wr(sock_type: libc::c_int, flag: libc::c_int) -> Result<(), String> {
+    let (fd_1, fd_2) = setup_unix_sockets(sock_type, flag);
+
+    test_utils::run_and_close_fds(&[fd_1, fd_2], || {
+        let message = [1u8, 2, 3, 4, 5];
+
+        write_all(fd_1, &message);
+
+        check_shutdown_call(
+            &ShutdownArguments {
+                fd: fd_1,
+                how: libc::SHUT_WR,
+            },
+            &[],
+        )?;
+
+        // the socket can no longer send
+        test_utils::check_system_call!(|| send_nosignal(fd_1, &message), &[libc::EPIPE])?;
+
+        // the peer reads the data sent before the shutdown, and then an EOF
+        let mut buf = [0u8; 10];
+        let rv = test_utils::check_system_call!(|| read_once(fd_2, &mut buf), &[])?;
+        test_utils::result_assert_eq(rv, message.len() as isize, "Unexpected read length")?;
+
+        test_utils::result_assert(is_readable(fd_2), "Peer should be readable")?;
+
+        let rv = test_utils::check_system_call!(|| read_once(fd_2, &mut buf), &[])?;
+        test_utils::result_assert_eq(rv, 0, "Expected an EOF")?;
+
+        // the peer can still send to this socket
+        write_all(fd_2, &message);
+        let rv = test_utils::check_system_call!(|| read_once(fd_1, &mut buf), &[])?;
+        test_utils::result_assert_eq(rv, message.len() as isize, "Unexpected read length")?;
+
+        Ok(())
+    })
+}
+
 fn check_shutdown_call(
     args: &ShutdownArguments,
     expected_errnos: &[libc::c_int],
@@ -192,6 +192,56 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
         }
     }
 
+    let sock_types = [libc::SOCK_STREAM, libc::SOCK_DGRAM, libc::SOCK_SEQPACKET];
+
+    for &sock_type in sock_types.iter() {
+        let domain = libc::AF_UNIX;
+
+        // add details to the test names to avoid duplicates
+        let append_args = |s| format!("{} <domain={},sock_type={}>", s, domain, sock_type);
+
+        let more_tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+            test_utils::ShadowTest::new(
+                &append_args("test_so_sndbuf"),
+                move || test_so_sndbuf(domain, sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_so_rcvbuf"),
+                move || test_so_rcvbuf(domain, sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_so_error"),
+                move || test_so_error(domain, sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_so_type_and_domain"),
+                move || test_so_type_and_domain(domain, sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_so_acceptconn"),
+                move || test_so_acceptconn(domain, sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_so_peercred"),
+                move || test_so_peercred(domain, sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            // linux doesn't limit unix sockets by the receiver's SO_RCVBUF
+            test_utils::ShadowTest::new(
+                &append_args("test_so_rcvbuf_limit"),
+                move || test_so_rcvbuf_limit(domain, sock_type),
+                set![TestEnv::Shadow],
+            ),
+        ];
+
+        tests.extend(more_tests);
+    }
+
     tests
 }
 
@@ -551,6 +601,58 @@ fn test_so_rcvbuf(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), Str
     })
 }
 
+/// Test that the SO_RCVBUF option limits the amount of data that can be sent to a unix socket.
+fn test_so_rcvbuf_limit(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let mut fds = [-1; 2];
+    assert_eq!(
+        unsafe { libc::socketpair(domain, sock_type | libc::SOCK_NONBLOCK, 0, fds.as_mut_ptr()) },
+        0
+    );
+
+    let level = libc::SOL_SOCKET;
+    let optname = libc::SO_RCVBUF;
+
+    test_utils::run_and_close_fds(&fds, || {
+        let mut set_args =
+            SetsockoptArguments::new(fds[1], level, optname, Some(4096i32.to_ne_bytes().into()));
+        check_setsockopt_call(&mut set_args, &[])?;
+
+        let mut get_args = GetsockoptArguments::new(fds[1], level, optname, Some(vec![0u8; 4]));
+        check_getsockopt_call(&mut get_args, &[])?;
+        let rcvbuf = i32::from_ne_bytes(get_args.optval.unwrap()[..].try_into().unwrap());
+        let rcvbuf = usize::try_from(rcvbuf).unwrap();
+
+        let buf = [0u8; 1000];
+
+        // fill the receive buffer
+        let mut total = 0;
+        loop {
+            let rv = unsafe { libc::send(fds[0], buf.as_ptr() as *const _, buf.len(), 0) };
+            if rv < 0 {
+                test_utils::result_assert_eq(test_utils::get_errno(), libc::EAGAIN, "")?;
+                break;
+            }
+            total += rv as usize;
+            test_utils::result_assert(total <= rcvbuf, "Sent more than the receive buffer size")?;
+        }
+
+        test_utils::result_assert(
+            total + buf.len() > rcvbuf,
+            "Could not fill the receive buffer",
+        )?;
+
+        // reading from the receiver should make space for the sender
+        let mut recv_buf = [0u8; 1000];
+        let rv = unsafe { libc::recv(fds[1], recv_buf.as_mut_ptr() as *mut _, recv_buf.len(), 0) };
+        test_utils::result_assert_eq(rv, 1000, "Unexpected recv() return value")?;
+
+        let rv = unsafe { libc::send(fds[0], buf.as_ptr() as *const _, buf.len(), 0) };
+        test_utils::result_assert(rv > 0, "Could not send after reading from the buffer")?;
+
+        Ok(())
+    })
+}
+
 fn bufsize_test_helper(
     fd: libc::c_int,
     level: libc::c_int,
@@ -634,6 +736,147 @@ fn test_so_error(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), Stri
     })
 }
 
+/// Test getsockopt() and setsockopt() using the SO_TYPE and SO_DOMAIN options.
+fn test_so_type_and_domain(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(domain, sock_type | libc::SOCK_NONBLOCK, 0) };
+    assert!(fd >= 0);
+
+    let level = libc::SOL_SOCKET;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        for (optname, expected) in [(libc::SO_TYPE, sock_type), (libc::SO_DOMAIN, domain)] {
+            let mut get_args = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 4]));
+            let mut set_args =
+                SetsockoptArguments::new(fd, level, optname, Some(expected.to_ne_bytes().into()));
+
+            check_getsockopt_call(&mut get_args, &[])?;
+            check_setsockopt_call(&mut set_args, &[libc::ENOPROTOOPT])?;
+
+            let returned_optval =
+                i32::from_ne_bytes(get_args.optval.as_ref().unwrap()[..].try_into().unwrap());
+
+            test_utils::result_assert_eq(returned_optval, expected, "Unexpected option value")?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Test getsockopt() using the SO_ACCEPTCONN option before and after calling listen().
+fn test_so_acceptconn(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(domain, sock_type | libc::SOCK_NONBLOCK, 0) };
+    assert!(fd >= 0);
+
+    let level = libc::SOL_SOCKET;
+    let optname = libc::SO_ACCEPTCONN;
+
+    let get_acceptconn = || -> Result<i32, String> {
+        let mut get_args = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 4]));
+        check_getsockopt_call(&mut get_args, &[])?;
+        Ok(i32::from_ne_bytes(
+            get_args.optval.as_ref().unwrap()[..].try_into().unwrap(),
+        ))
+    };
+
+    test_utils::run_and_close_fds(&[fd], || {
+        test_utils::result_assert_eq(get_acceptconn()?, 0, "Socket should not be listening")?;
+
+        if sock_type == libc::SOCK_DGRAM {
+            return Ok(());
+        }
+
+        // bind to an autobind address and listen
+        let addr = libc::sockaddr_un {
+            sun_family: libc::AF_UNIX as u16,
+            sun_path: [0; 108],
+        };
+        let addr_len = std::mem::size_of_val(&addr.sun_family) as libc::socklen_t;
+        let rv = unsafe { libc::bind(fd, &addr as *const _ as *const libc::sockaddr, addr_len) };
+        assert_eq!(rv, 0);
+        assert_eq!(unsafe { libc::listen(fd, 10) }, 0);
+
+        test_utils::result_assert_eq(get_acceptconn()?, 1, "Socket should be listening")?;
+
+        let mut set_args =
+            SetsockoptArguments::new(fd, level, optname, Some(0i32.to_ne_bytes().into()));
+        check_setsockopt_call(&mut set_args, &[libc::ENOPROTOOPT])?;
+
+        Ok(())
+    })
+}
+
+/// Test getsockopt() using the SO_PEERCRED option on connected and unconnected sockets.
+fn test_so_peercred(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let mut fds = [-1; 2];
+    assert_eq!(
+        unsafe { libc::socketpair(domain, sock_type, 0, fds.as_mut_ptr()) },
+        0
+    );
+    let unconnected_fd = unsafe { libc::socket(domain, sock_type, 0) };
+    assert!(unconnected_fd >= 0);
+
+    let level = libc::SOL_SOCKET;
+    let optname = libc::SO_PEERCRED;
+    let creds_len = std::mem::size_of::<libc::ucred>();
+
+    let get_peercred = |fd| -> Result<libc::ucred, String> {
+        let mut get_args = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; creds_len]));
+        check_getsockopt_call(&mut get_args, &[])?;
+        test_utils::result_assert_eq(
+            get_args.optlen.unwrap() as usize,
+            creds_len,
+            "Unexpected optlen",
+        )?;
+        let optval = get_args.optval.unwrap();
+        Ok(unsafe { std::ptr::read_unaligned(optval.as_ptr() as *const libc::ucred) })
+    };
+
+    test_utils::run_and_close_fds(&[fds[0], fds[1], unconnected_fd], || {
+        for fd in fds {
+            let creds = get_peercred(fd)?;
+            test_utils::result_assert_eq(creds.pid, unsafe { libc::getpid() }, "Unexpected pid")?;
+            test_utils::result_assert_eq(creds.uid, unsafe { libc::getuid() }, "Unexpected uid")?;
+            test_utils::result_assert_eq(creds.gid, unsafe { libc::getgid() }, "Unexpected gid")?;
+        }
+
+        let creds = get_peercred(unconnected_fd)?;
+        test_utils::result_assert_eq(creds.pid, 0, "Unexpected pid")?;
+        test_utils::result_assert_eq(creds.uid, libc::uid_t::MAX, "Unexpected uid")?;
+        test_utils::result_assert_eq(creds.gid, libc::gid_t::MAX, "Unexpected gid")?;
+
+        let mut set_args =
+            SetsockoptArguments::new(fds[0], level, optname, Some(vec![0u8; creds_len]));
+        check_setsockopt_call(&mut set_args, &[libc::ENOPROTOOPT])?;
+
+        Ok(())
+    })?;
+
+    if sock_type != libc::SOCK_DGRAM {
+        return Ok(());
+    }
+
+    // a dgram socket connected using connect() has no peer credentials
+    let fd_server = unsafe { libc::socket(domain, sock_type, 0) };
+    let fd_client = unsafe { libc::socket(domain, sock_type, 0) };
+    assert!(fd_server >= 0);
+    assert!(fd_client >= 0);
+
+    test_utils::run_and_close_fds(&[fd_server, fd_client], || {
+        let (server_addr, server_addr_len) =
+            test_utils::socket_utils::autobind_helper(fd_server, domain);
+        test_utils::socket_utils::dgram_connect_helper(fd_client, server_addr, server_addr_len);
+
+        for fd in [fd_server, fd_client] {
+            let creds = get_peercred(fd)?;
+            test_utils::result_assert_eq(creds.pid, 0, "Unexpected pid")?;
+            test_utils::result_assert_eq(creds.uid, libc::uid_t::MAX, "Unexpected uid")?;
+            test_utils::result_assert_eq(creds.gid, libc::gid_t::MAX, "Unexpected gid")?;
+        }
+
+        Ok(())
+    })
+}
+
 /// Test getsockopt() and setsockopt() using the TCP_INFO option.
 fn test_tcp_info(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
     let fd = unsafe { libc::socket(domain, sock_type, 0) };
@@ -650,7 +893,8 @@ fn test_tcp_info(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), Stri
         let expected_errnos = if sock_type == libc::SOCK_STREAM {
             vec![]
         } else {
-            vec![libc::EOPNOTSUPP]
+            // linux returns EOPNOTSUPP but Shadow returns ENOPROTOOPT
+            vec![libc::EOPNOTSUPP, libc::ENOPROTOOPT]
         };
         check_getsockopt_call(&mut get_args, &expected_errnos)?;
         check_setsockopt_call(&mut set_args, &[libc::ENOPROTOOPT])?;
@@ -717,6 +961,8 @@ fn test_tcp_congestion(domain: libc::c_int, sock_type: libc::c_int) -> Result<()
     let get_args_2 = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 3]));
     let mut set_args_1 = SetsockoptArguments::new(fd, level, optname, Some("reno".into()));
     let mut set_args_2 = SetsockoptArguments::new(fd, level, optname, Some("ren".into()));
+    let mut set_args_3 = SetsockoptArguments::new(fd, level, optname, Some("cubic".into()));
+    let mut get_args_3 = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 20]));
 
     test_utils::run_and_close_fds(&[fd], || {
         for mut get_args in [get_args_1, get_args_2] {
@@ -779,6 +1025,18 @@ fn test_tcp_congestion(domain: libc::c_int, sock_type: libc::c_int) -> Result<()
         };
         check_setsockopt_call(&mut set_args_2, &expected_errnos)?;
 
+        if sock_type != libc::SOCK_STREAM {
+            return Ok(());
+        }
+
+        // the new algorithm should be returned after changing it
+        check_setsockopt_call(&mut set_args_3, &[])?;
+        check_getsockopt_call(&mut get_args_3, &[])?;
+
+        let returned_str = &get_args_3.optval.as_ref().unwrap()[..];
+        let returned_str = &returned_str[..returned_str.iter().position(|&c| c == b'\0').unwrap()];
+        test_utils::result_assert_eq(returned_str, &b"cubic"[..], "Unexpected algorithm")?;
+
         Ok(())
     })
 }
@@ -0,0 +1,649 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use nix::errno::Errno;
+use nix::sys::socket::{self, MsgFlags, SockFlag, SockType, SockaddrIn, SockaddrLike};
+
+use test_utils::TestEnvironment as TestEnv;
+use test_utils::{ensure_ord, set};
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnv::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), anyhow::Error>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_bind_ephemeral_port",
+            test_bind_ephemeral_port,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_autobind_on_send",
+            test_autobind_on_send,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_connect_disconnect",
+            test_connect_disconnect,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_sendto_recvfrom",
+            test_sendto_recvfrom,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_msg_trunc",
+            test_msg_trunc,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_msg_peek",
+            test_msg_peek,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_msg_more",
+            test_msg_more,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_recv_buffer_full",
+            test_recv_buffer_full,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_fionread",
+            test_fionread,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_siocoutq",
+            test_siocoutq,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_ip_ttl",
+            test_ip_ttl,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_ip_tos",
+            test_ip_tos,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_ip_mtu_discover",
+            test_ip_mtu_discover,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ]
+}
+
+fn udp_socket(flags: SockFlag) -> libc::c_int {
+    socket::socket(socket::AddressFamily::Inet, SockType::Datagram, flags, None).unwrap()
+}
+
+/// Create a non-blocking udp socket bound to an ephemeral port on the loopback address.
+fn bound_udp_socket() -> (libc::c_int, SockaddrIn) {
+    let fd = udp_socket(SockFlag::SOCK_NONBLOCK);
+    socket::bind(fd, &SockaddrIn::new(127, 0, 0, 1, 0)).unwrap();
+    let addr = socket::getsockname::<SockaddrIn>(fd).unwrap();
+    (fd, addr)
+}
+
+/// Wait for data sent over the network to arrive at the peer.
+fn wait_for_delivery() {
+    std::thread::sleep(std::time::Duration::from_millis(10));
+}
+
+fn getsockopt_int(fd: libc::c_int, level: libc::c_int, optname: libc::c_int) -> Result<i32, Errno> {
+    let mut optval: libc::c_int = 0;
+    let mut optlen = std::mem::size_of_val(&optval) as libc::socklen_t;
+    Errno::result(unsafe {
+        libc::getsockopt(
+            fd,
+            level,
+            optname,
+            &mut optval as *mut _ as *mut libc::c_void,
+            &mut optlen,
+        )
+    })?;
+    Ok(optval)
+}
+
+fn setsockopt_int(
+    fd: libc::c_int,
+    level: libc::c_int,
+    optname: libc::c_int,
+    optval: libc::c_int,
+) -> Result<(), Errno> {
+    Errno::result(unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            optname,
+            &optval as *const _ as *const libc::c_void,
+            std::mem::size_of_val(&optval) as libc::socklen_t,
+        )
+    })?;
+    Ok(())
+}
+
+fn ioctl_int(fd: libc::c_int, request: libc::c_ulong) -> Result<i32, Errno> {
+    let mut val: libc::c_int = -1;
+    Errno::result(unsafe { libc::ioctl(fd, request, &mut val) })?;
+    Ok(val)
+}
+
+/// Test that binding to port 0 assigns an ephemeral port.
+fn test_bind_ephemeral_port() -> anyhow::Result<()> {
+    let fd = udp_socket(SockFlag::empty());
+
+    test_utils::run_and_close_fds(&[fd], || {
+        // an unbound socket has an unspecified address
+        let addr = socket::getsockname::<SockaddrIn>(fd)?;
+        ensure_ord!(addr, ==, SockaddrIn::new(0, 0, 0, 0, 0));
+
+        socket::bind(fd, &SockaddrIn::new(127, 0, 0, 1, 0))?;
+
+        let addr = socket::getsockname::<SockaddrIn>(fd)?;
+        ensure_ord!(addr.ip(), ==, u32::from(std::net::Ipv4Addr::LOCALHOST));
+        ensure_ord!(addr.port(), !=, 0);
+
+        // the socket can't be bound twice
+        ensure_ord!(
+            socket::bind(fd, &SockaddrIn::new(127, 0, 0, 1, 0)),
+            ==,
+            Err(Errno::EINVAL)
+        );
+
+        // another socket can't bind to the same address
+        let fd_other = udp_socket(SockFlag::empty());
+        test_utils::run_and_close_fds(&[fd_other], || {
+            ensure_ord!(socket::bind(fd_other, &addr), ==, Err(Errno::EADDRINUSE));
+            Ok(())
+        })
+    })
+}
+
+/// Test that sending from an unbound socket binds it to an ephemeral port.
+fn test_autobind_on_send() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let fd_client = udp_socket(SockFlag::empty());
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        ensure_ord!(
+            socket::sendto(fd_client, b"hello", &server_addr, MsgFlags::empty()),
+            ==,
+            Ok(5)
+        );
+
+        let client_addr = socket::getsockname::<SockaddrIn>(fd_client)?;
+        ensure_ord!(client_addr.port(), !=, 0);
+
+        wait_for_delivery();
+
+        // the datagram should come from the client's new address
+        let mut buf = [0u8; 10];
+        let (len, from) = socket::recvfrom::<SockaddrIn>(fd_server, &mut buf)?;
+        ensure_ord!(len, ==, 5);
+        ensure_ord!(from.map(|x| x.port()), ==, Some(client_addr.port()));
+
+        Ok(())
+    })
+}
+
+/// Test connecting to and disconnecting from a peer.
+fn test_connect_disconnect() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let (fd_other, other_addr) = bound_udp_socket();
+    let fd_client = udp_socket(SockFlag::empty());
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server, fd_other], || {
+        // an unconnected socket has no peer and needs a destination address
+        ensure_ord!(
+            socket::getpeername::<SockaddrIn>(fd_client),
+            ==,
+            Err(Errno::ENOTCONN)
+        );
+        ensure_ord!(
+            socket::send(fd_client, b"hello", MsgFlags::empty()),
+            ==,
+            Err(Errno::EDESTADDRREQ)
+        );
+
+        socket::connect(fd_client, &server_addr)?;
+        ensure_ord!(socket::getpeername::<SockaddrIn>(fd_client), ==, Ok(server_addr));
+
+        // the socket was bound by connect()
+        let client_addr = socket::getsockname::<SockaddrIn>(fd_client)?;
+        ensure_ord!(client_addr.port(), !=, 0);
+
+        // a connected socket can send without a destination address, and can still send to other
+        // addresses
+        ensure_ord!(socket::send(fd_client, b"hello", MsgFlags::empty()), ==, Ok(5));
+        ensure_ord!(
+            socket::sendto(fd_client, b"world", &other_addr, MsgFlags::empty()),
+            ==,
+            Ok(5)
+        );
+
+        wait_for_delivery();
+
+        let mut buf = [0u8; 10];
+        ensure_ord!(socket::recv(fd_server, &mut buf, MsgFlags::empty()), ==, Ok(5));
+        ensure_ord!(&buf[..5], ==, b"hello");
+        ensure_ord!(socket::recv(fd_other, &mut buf, MsgFlags::empty()), ==, Ok(5));
+        ensure_ord!(&buf[..5], ==, b"world");
+
+        // a connected socket only receives datagrams from its peer
+        ensure_ord!(
+            socket::sendto(fd_other, b"ignored", &client_addr, MsgFlags::empty()),
+            ==,
+            Ok(7)
+        );
+        ensure_ord!(
+            socket::sendto(fd_server, b"reply", &client_addr, MsgFlags::empty()),
+            ==,
+            Ok(5)
+        );
+
+        wait_for_delivery();
+
+        ensure_ord!(socket::recv(fd_client, &mut buf, MsgFlags::empty()), ==, Ok(5));
+        ensure_ord!(&buf[..5], ==, b"reply");
+        ensure_ord!(
+            socket::recv(fd_client, &mut buf, MsgFlags::MSG_DONTWAIT),
+            ==,
+            Err(Errno::EAGAIN)
+        );
+
+        // disconnect by connecting to an AF_UNSPEC address
+        let unspec = libc::sockaddr {
+            sa_family: libc::AF_UNSPEC as libc::sa_family_t,
+            sa_data: [0; 14],
+        };
+        let addr_len = std::mem::size_of_val(&unspec) as libc::socklen_t;
+        Errno::result(unsafe { libc::connect(fd_client, &unspec, addr_len) })?;
+
+        ensure_ord!(
+            socket::getpeername::<SockaddrIn>(fd_client),
+            ==,
+            Err(Errno::ENOTCONN)
+        );
+        ensure_ord!(
+            socket::send(fd_client, b"hello", MsgFlags::empty()),
+            ==,
+            Err(Errno::EDESTADDRREQ)
+        );
+
+        // the socket keeps its bound port
+        let addr = socket::getsockname::<SockaddrIn>(fd_client)?;
+        ensure_ord!(addr.port(), ==, client_addr.port());
+
+        Ok(())
+    })
+}
+
+/// Test sending and receiving datagrams, and that message boundaries are preserved.
+fn test_sendto_recvfrom() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let (fd_client, client_addr) = bound_udp_socket();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        for msg in [&b"one"[..], b"two2", b"", b"three"] {
+            ensure_ord!(
+                socket::sendto(fd_client, msg, &server_addr, MsgFlags::empty()),
+                ==,
+                Ok(msg.len())
+            );
+        }
+
+        wait_for_delivery();
+
+        // each datagram is received separately, including the empty datagram
+        for msg in [&b"one"[..], b"two2", b"", b"three"] {
+            let mut buf = [0u8; 10];
+            let (len, from) = socket::recvfrom::<SockaddrIn>(fd_server, &mut buf)?;
+            ensure_ord!(&buf[..len], ==, msg);
+            ensure_ord!(from, ==, Some(client_addr));
+        }
+
+        ensure_ord!(
+            socket::recvfrom::<SockaddrIn>(fd_server, &mut [0u8; 10]),
+            ==,
+            Err(Errno::EAGAIN)
+        );
+
+        Ok(())
+    })
+}
+
+/// Test that datagrams are truncated to fit the buffer, and that `MSG_TRUNC` returns the full
+/// length of the datagram.
+fn test_msg_trunc() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let (fd_client, _client_addr) = bound_udp_socket();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        for _ in 0..2 {
+            ensure_ord!(
+                socket::sendto(fd_client, b"0123456789", &server_addr, MsgFlags::empty()),
+                ==,
+                Ok(10)
+            );
+        }
+
+        wait_for_delivery();
+
+        // the rest of the datagram is discarded
+        let mut buf = [0u8; 4];
+        ensure_ord!(socket::recv(fd_server, &mut buf, MsgFlags::empty()), ==, Ok(4));
+        ensure_ord!(&buf, ==, b"0123");
+
+        let mut buf = [0u8; 4];
+        ensure_ord!(
+            socket::recv(fd_server, &mut buf, MsgFlags::MSG_TRUNC),
+            ==,
+            Ok(10)
+        );
+        ensure_ord!(&buf, ==, b"0123");
+
+        ensure_ord!(
+            socket::recv(fd_server, &mut buf, MsgFlags::empty()),
+            ==,
+            Err(Errno::EAGAIN)
+        );
+
+        Ok(())
+    })
+}
+
+/// Test that `MSG_PEEK` doesn't remove the datagram from the receive buffer.
+fn test_msg_peek() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let (fd_client, _client_addr) = bound_udp_socket();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        ensure_ord!(
+            socket::sendto(fd_client, b"hello", &server_addr, MsgFlags::empty()),
+            ==,
+            Ok(5)
+        );
+        ensure_ord!(
+            socket::sendto(fd_client, b"world", &server_addr, MsgFlags::empty()),
+            ==,
+            Ok(5)
+        );
+
+        wait_for_delivery();
+
+        let mut buf = [0u8; 3];
+        ensure_ord!(socket::recv(fd_server, &mut buf, MsgFlags::MSG_PEEK), ==, Ok(3));
+        ensure_ord!(&buf, ==, b"hel");
+
+        let mut buf = [0u8; 10];
+        ensure_ord!(socket::recv(fd_server, &mut buf, MsgFlags::MSG_PEEK), ==, Ok(5));
+        ensure_ord!(&buf[..5], ==, b"hello");
+
+        ensure_ord!(socket::recv(fd_server, &mut buf, MsgFlags::empty()), ==, Ok(5));
+        ensure_ord!(&buf[..5], ==, b"hello");
+
+        ensure_ord!(socket::recv(fd_server, &mut buf, MsgFlags::empty()), ==, Ok(5));
+        ensure_ord!(&buf[..5], ==, b"world");
+
+        Ok(())
+    })
+}
+
+/// Send `buf` to `addr` with the raw send flags `flags`, since nix doesn't support `MSG_MORE`.
+fn sendto_raw(
+    fd: libc::c_int,
+    buf: &[u8],
+    addr: &SockaddrIn,
+    flags: libc::c_int,
+) -> Result<usize, Errno> {
+    let rv = unsafe {
+        libc::sendto(
+            fd,
+            buf.as_ptr() as *const libc::c_void,
+            buf.len(),
+            flags,
+            addr.as_ptr() as *const libc::sockaddr,
+            addr.len(),
+        )
+    };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+/// Test that data sent with `MSG_MORE` is corked, and sent as a single datagram by the next send
+/// without `MSG_MORE`.
+fn test_msg_more() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let (fd_client, client_addr) = bound_udp_socket();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        ensure_ord!(
+            sendto_raw(fd_client, b"one", &server_addr, libc::MSG_MORE),
+            ==,
+            Ok(3)
+        );
+        ensure_ord!(
+            sendto_raw(fd_client, b"two", &server_addr, libc::MSG_MORE),
+            ==,
+            Ok(3)
+        );
+
+        wait_for_delivery();
+
+        // nothing has been sent yet
+        ensure_ord!(
+            socket::recv(fd_server, &mut [0u8; 20], MsgFlags::empty()),
+            ==,
+            Err(Errno::EAGAIN)
+        );
+
+        ensure_ord!(sendto_raw(fd_client, b"three", &server_addr, 0), ==, Ok(5));
+
+        wait_for_delivery();
+
+        let mut buf = [0u8; 20];
+        let (len, from) = socket::recvfrom::<SockaddrIn>(fd_server, &mut buf)?;
+        ensure_ord!(&buf[..len], ==, b"onetwothree");
+        ensure_ord!(from, ==, Some(client_addr));
+
+        ensure_ord!(
+            socket::recv(fd_server, &mut buf, MsgFlags::empty()),
+            ==,
+            Err(Errno::EAGAIN)
+        );
+
+        Ok(())
+    })
+}
+
+/// Test that datagrams are dropped when the receive buffer is full.
+fn test_recv_buffer_full() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let (fd_client, _client_addr) = bound_udp_socket();
+
+    let num_sent = 20;
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        // use the smallest receive buffer
+        setsockopt_int(fd_server, libc::SOL_SOCKET, libc::SO_RCVBUF, 1)?;
+
+        for _ in 0..num_sent {
+            ensure_ord!(
+                socket::sendto(fd_client, &[1u8; 1000], &server_addr, MsgFlags::empty()),
+                ==,
+                Ok(1000)
+            );
+            // the sender's own buffer shouldn't fill up
+            wait_for_delivery();
+        }
+
+        // the receive buffer can only hold some of the datagrams
+        let mut num_received = 0;
+        loop {
+            match socket::recv(fd_server, &mut [0u8; 1000], MsgFlags::empty()) {
+                Ok(len) => {
+                    ensure_ord!(len, ==, 1000);
+                    num_received += 1;
+                }
+                Err(e) => {
+                    ensure_ord!(e, ==, Errno::EAGAIN);
+                    break;
+                }
+            }
+        }
+
+        ensure_ord!(num_received, >, 0);
+        ensure_ord!(num_received, <, num_sent);
+
+        Ok(())
+    })
+}
+
+/// Test that `FIONREAD` (`SIOCINQ`) returns the size of the next datagram.
+fn test_fionread() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let (fd_client, _client_addr) = bound_udp_socket();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        ensure_ord!(ioctl_int(fd_server, libc::FIONREAD), ==, Ok(0));
+
+        ensure_ord!(
+            socket::sendto(fd_client, &[1u8; 10], &server_addr, MsgFlags::empty()),
+            ==,
+            Ok(10)
+        );
+        ensure_ord!(
+            socket::sendto(fd_client, &[1u8; 20], &server_addr, MsgFlags::empty()),
+            ==,
+            Ok(20)
+        );
+
+        wait_for_delivery();
+
+        ensure_ord!(ioctl_int(fd_server, libc::FIONREAD), ==, Ok(10));
+        ensure_ord!(socket::recv(fd_server, &mut [0u8; 50], MsgFlags::empty()), ==, Ok(10));
+        ensure_ord!(ioctl_int(fd_server, libc::FIONREAD), ==, Ok(20));
+        ensure_ord!(socket::recv(fd_server, &mut [0u8; 50], MsgFlags::empty()), ==, Ok(20));
+        ensure_ord!(ioctl_int(fd_server, libc::FIONREAD), ==, Ok(0));
+
+        Ok(())
+    })
+}
+
+/// Test that `SIOCOUTQ` returns an empty send queue once datagrams have been sent.
+fn test_siocoutq() -> anyhow::Result<()> {
+    let (fd_server, server_addr) = bound_udp_socket();
+    let (fd_client, _client_addr) = bound_udp_socket();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        ensure_ord!(ioctl_int(fd_client, libc::TIOCOUTQ), ==, Ok(0));
+
+        ensure_ord!(
+            socket::sendto(fd_client, &[1u8; 10], &server_addr, MsgFlags::empty()),
+            ==,
+            Ok(10)
+        );
+
+        wait_for_delivery();
+
+        ensure_ord!(ioctl_int(fd_client, libc::TIOCOUTQ), ==, Ok(0));
+
+        Ok(())
+    })
+}
+
+/// Test getting and setting the `IP_TTL` option.
+fn test_ip_ttl() -> anyhow::Result<()> {
+    let fd = udp_socket(SockFlag::empty());
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let get_ttl = || getsockopt_int(fd, libc::IPPROTO_IP, libc::IP_TTL);
+        let set_ttl = |val| setsockopt_int(fd, libc::IPPROTO_IP, libc::IP_TTL, val);
+
+        ensure_ord!(get_ttl(), ==, Ok(64));
+
+        ensure_ord!(set_ttl(10), ==, Ok(()));
+        ensure_ord!(get_ttl(), ==, Ok(10));
+
+        ensure_ord!(set_ttl(0), ==, Err(Errno::EINVAL));
+        ensure_ord!(set_ttl(256), ==, Err(Errno::EINVAL));
+        ensure_ord!(get_ttl(), ==, Ok(10));
+
+        // restore the default
+        ensure_ord!(set_ttl(-1), ==, Ok(()));
+        ensure_ord!(get_ttl(), ==, Ok(64));
+
+        Ok(())
+    })
+}
+
+/// Test getting and setting the `IP_TOS` option.
+fn test_ip_tos() -> anyhow::Result<()> {
+    let fd = udp_socket(SockFlag::empty());
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let get_tos = || getsockopt_int(fd, libc::IPPROTO_IP, libc::IP_TOS);
+        let set_tos = |val| setsockopt_int(fd, libc::IPPROTO_IP, libc::IP_TOS, val);
+
+        ensure_ord!(get_tos(), ==, Ok(0));
+
+        ensure_ord!(set_tos(libc::IPTOS_LOWDELAY as i32), ==, Ok(()));
+        ensure_ord!(get_tos(), ==, Ok(libc::IPTOS_LOWDELAY as i32));
+
+        Ok(())
+    })
+}
+
+/// Test getting and setting the `IP_MTU_DISCOVER` option.
+fn test_ip_mtu_discover() -> anyhow::Result<()> {
+    let fd = udp_socket(SockFlag::empty());
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let get_pmtudisc = || getsockopt_int(fd, libc::IPPROTO_IP, libc::IP_MTU_DISCOVER);
+        let set_pmtudisc = |val| setsockopt_int(fd, libc::IPPROTO_IP, libc::IP_MTU_DISCOVER, val);
+
+        ensure_ord!(get_pmtudisc(), ==, Ok(libc::IP_PMTUDISC_WANT));
+
+        ensure_ord!(set_pmtudisc(libc::IP_PMTUDISC_DO), ==, Ok(()));
+        ensure_ord!(get_pmtudisc(), ==, Ok(libc::IP_PMTUDISC_DO));
+
+        ensure_ord!(set_pmtudisc(100), ==, Err(Errno::EINVAL));
+        ensure_ord!(get_pmtudisc(), ==, Ok(libc::IP_PMTUDISC_DO));
+
+        Ok(())
+    })
+}
@@ -0,0 +1,411 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::sys::socket::{self, AddressFamily, SockFlag, SockType};
+use nix::unistd;
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+fn splice(
+    fd_in: libc::c_int,
+    off_in: Option<&mut libc::loff_t>,
+    fd_out: libc::c_int,
+    off_out: Option<&mut libc::loff_t>,
+    len: usize,
+    flags: libc::c_uint,
+) -> nix::Result<usize> {
+    let off_in = off_in.map_or(std::ptr::null_mut(), |x| x as *mut _);
+    let off_out = off_out.map_or(std::ptr::null_mut(), |x| x as *mut _);
+    let rv = unsafe { libc::splice(fd_in, off_in, fd_out, off_out, len, flags) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn tee(
+    fd_in: libc::c_int,
+    fd_out: libc::c_int,
+    len: usize,
+    flags: libc::c_uint,
+) -> nix::Result<usize> {
+    let rv = unsafe { libc::tee(fd_in, fd_out, len, flags) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn sendfile(
+    out_fd: libc::c_int,
+    in_fd: libc::c_int,
+    offset: Option<&mut libc::off_t>,
+    count: usize,
+) -> nix::Result<usize> {
+    let offset = offset.map_or(std::ptr::null_mut(), |x| x as *mut _);
+    let rv = unsafe { libc::sendfile(out_fd, in_fd, offset, count) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn copy_file_range(
+    fd_in: libc::c_int,
+    off_in: Option<&mut libc::loff_t>,
+    fd_out: libc::c_int,
+    off_out: Option<&mut libc::loff_t>,
+    len: usize,
+) -> nix::Result<usize> {
+    let off_in = off_in.map_or(std::ptr::null_mut(), |x| x as *mut _);
+    let off_out = off_out.map_or(std::ptr::null_mut(), |x| x as *mut _);
+    let rv = unsafe { libc::copy_file_range(fd_in, off_in, fd_out, off_out, len, 0) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+/// Create a temporary file containing `contents`, with the file position at the start.
+fn temp_file(contents: &[u8]) -> anyhow::Result<libc::c_int> {
+    let (fd, path) = unistd::mkstemp("splice_test_XXXXXX")?;
+    unistd::unlink(&path)?;
+
+    ensure_ord!(unistd::write(fd, contents)?, ==, contents.len());
+    unistd::lseek(fd, 0, unistd::Whence::SeekSet)?;
+
+    Ok(fd)
+}
+
+fn read_all(fd: libc::c_int, len: usize) -> anyhow::Result<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    let mut total = 0;
+    while total < len {
+        let n = unistd::read(fd, &mut buf[total..])?;
+        if n == 0 {
+            break;
+        }
+        total += n;
+    }
+    buf.truncate(total);
+    Ok(buf)
+}
+
+fn test_splice_pipe_to_pipe() -> anyhow::Result<()> {
+    let (r1, w1) = unistd::pipe()?;
+    let (r2, w2) = unistd::pipe()?;
+
+    unistd::write(w1, b"hello world")?;
+
+    ensure_ord!(splice(r1, None, w2, None, 5, 0), ==, Ok(5));
+    ensure_ord!(read_all(r2, 5)?, ==, b"hello".to_vec());
+
+    // the spliced data was removed from the first pipe
+    ensure_ord!(read_all(r1, 6)?, ==, b" world".to_vec());
+
+    for fd in [r1, w1, r2, w2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_tee() -> anyhow::Result<()> {
+    let (r1, w1) = unistd::pipe()?;
+    let (r2, w2) = unistd::pipe()?;
+
+    unistd::write(w1, b"hello")?;
+
+    ensure_ord!(tee(r1, w2, 100, 0), ==, Ok(5));
+    ensure_ord!(read_all(r2, 5)?, ==, b"hello".to_vec());
+
+    // the data remains in the first pipe
+    ensure_ord!(read_all(r1, 5)?, ==, b"hello".to_vec());
+
+    // not a pipe
+    let fd = temp_file(b"")?;
+    ensure_ord!(tee(fd, w2, 5, 0), ==, Err(Errno::EINVAL));
+
+    // the same pipe
+    ensure_ord!(tee(r1, w1, 5, 0), ==, Err(Errno::EINVAL));
+
+    for fd in [fd, r1, w1, r2, w2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_splice_file_to_pipe() -> anyhow::Result<()> {
+    let fd = temp_file(b"0123456789")?;
+    let (r, w) = unistd::pipe()?;
+
+    // with an offset, the file position doesn't change
+    let mut offset = 2;
+    ensure_ord!(splice(fd, Some(&mut offset), w, None, 3, 0), ==, Ok(3));
+    ensure_ord!(offset, ==, 5);
+    ensure_ord!(read_all(r, 3)?, ==, b"234".to_vec());
+    ensure_ord!(unistd::lseek(fd, 0, unistd::Whence::SeekCur)?, ==, 0);
+
+    // without an offset, the file position is used and updated
+    ensure_ord!(splice(fd, None, w, None, 4, 0), ==, Ok(4));
+    ensure_ord!(read_all(r, 4)?, ==, b"0123".to_vec());
+    ensure_ord!(unistd::lseek(fd, 0, unistd::Whence::SeekCur)?, ==, 4);
+
+    // end of file
+    ensure_ord!(splice(fd, None, w, None, 100, 0), ==, Ok(6));
+    ensure_ord!(splice(fd, None, w, None, 100, 0), ==, Ok(0));
+    ensure_ord!(read_all(r, 6)?, ==, b"456789".to_vec());
+
+    for fd in [fd, r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_splice_pipe_to_file() -> anyhow::Result<()> {
+    let fd = temp_file(b"")?;
+    let (r, w) = unistd::pipe()?;
+
+    unistd::write(w, b"abcdef")?;
+
+    ensure_ord!(splice(r, None, fd, None, 4, 0), ==, Ok(4));
+
+    let mut offset = 10;
+    ensure_ord!(splice(r, None, fd, Some(&mut offset), 100, 0), ==, Ok(2));
+    ensure_ord!(offset, ==, 12);
+
+    let mut buf = [0u8; 12];
+    ensure_ord!(nix::sys::uio::pread(fd, &mut buf, 0), ==, Ok(12));
+    ensure_ord!(&buf, ==, b"abcd\0\0\0\0\0\0ef");
+
+    for fd in [fd, r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_splice_errors() -> anyhow::Result<()> {
+    let fd1 = temp_file(b"abc")?;
+    let fd2 = temp_file(b"")?;
+    let (r, w) = unistd::pipe2(OFlag::O_NONBLOCK)?;
+
+    // neither file is a pipe
+    ensure_ord!(splice(fd1, None, fd2, None, 3, 0), ==, Err(Errno::EINVAL));
+
+    // the same pipe
+    ensure_ord!(splice(r, None, w, None, 3, 0), ==, Err(Errno::EINVAL));
+
+    // pipes don't have offsets
+    let mut offset = 0;
+    ensure_ord!(splice(fd1, None, w, Some(&mut offset), 3, 0), ==, Err(Errno::ESPIPE));
+
+    // invalid flags
+    ensure_ord!(splice(fd1, None, w, None, 3, 0x100), ==, Err(Errno::EINVAL));
+
+    // bad fd
+    ensure_ord!(splice(1000, None, w, None, 3, 0), ==, Err(Errno::EBADF));
+
+    // empty non-blocking pipe
+    ensure_ord!(splice(r, None, fd2, None, 3, libc::SPLICE_F_NONBLOCK), ==, Err(Errno::EAGAIN));
+
+    for fd in [fd1, fd2, r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_splice_full_pipe() -> anyhow::Result<()> {
+    let (r, w) = unistd::pipe()?;
+    let pipe_size = unsafe { libc::fcntl(w, libc::F_GETPIPE_SZ) } as usize;
+
+    let fd = temp_file(&vec![1u8; pipe_size + 100])?;
+
+    // only the amount of data that fits in the pipe is copied
+    ensure_ord!(splice(fd, None, w, None, pipe_size + 100, 0), ==, Ok(pipe_size));
+    ensure_ord!(unistd::lseek(fd, 0, unistd::Whence::SeekCur)?, ==, pipe_size as i64);
+
+    // the pipe is full
+    ensure_ord!(
+        splice(fd, None, w, None, 100, libc::SPLICE_F_NONBLOCK),
+        ==,
+        Err(Errno::EAGAIN)
+    );
+
+    for fd in [fd, r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_sendfile() -> anyhow::Result<()> {
+    let fd = temp_file(b"0123456789")?;
+    let (sock1, sock2) = socket::socketpair(
+        AddressFamily::Unix,
+        SockType::Stream,
+        None,
+        SockFlag::empty(),
+    )?;
+
+    // with an offset, the file position doesn't change
+    let mut offset = 3;
+    ensure_ord!(sendfile(sock1, fd, Some(&mut offset), 4), ==, Ok(4));
+    ensure_ord!(offset, ==, 7);
+    ensure_ord!(read_all(sock2, 4)?, ==, b"3456".to_vec());
+    ensure_ord!(unistd::lseek(fd, 0, unistd::Whence::SeekCur)?, ==, 0);
+
+    // without an offset, the file position is updated
+    ensure_ord!(sendfile(sock1, fd, None, 100), ==, Ok(10));
+    ensure_ord!(read_all(sock2, 10)?, ==, b"0123456789".to_vec());
+    ensure_ord!(unistd::lseek(fd, 0, unistd::Whence::SeekCur)?, ==, 10);
+
+    // end of file
+    ensure_ord!(sendfile(sock1, fd, None, 100), ==, Ok(0));
+
+    // the input can't be a socket
+    ensure_ord!(sendfile(fd, sock2, None, 100), ==, Err(Errno::EINVAL));
+
+    for fd in [fd, sock1, sock2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_tcp() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let client = TcpStream::connect(listener.local_addr()?)?;
+    let (server, _) = listener.accept()?;
+
+    let fd = temp_file(b"hello world")?;
+    let (r, w) = unistd::pipe()?;
+
+    // file to tcp socket
+    ensure_ord!(sendfile(client.as_raw_fd(), fd, None, 100), ==, Ok(11));
+    ensure_ord!(read_all(server.as_raw_fd(), 11)?, ==, b"hello world".to_vec());
+
+    // tcp socket to pipe
+    unistd::write(client.as_raw_fd(), b"abc")?;
+    ensure_ord!(splice(server.as_raw_fd(), None, w, None, 100, 0), ==, Ok(3));
+    ensure_ord!(read_all(r, 3)?, ==, b"abc".to_vec());
+
+    // pipe to tcp socket
+    unistd::write(w, b"xyz")?;
+    ensure_ord!(splice(r, None, server.as_raw_fd(), None, 100, 0), ==, Ok(3));
+    ensure_ord!(read_all(client.as_raw_fd(), 3)?, ==, b"xyz".to_vec());
+
+    for fd in [fd, r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_blocking_splice() -> anyhow::Result<()> {
+    let (r1, w1) = unistd::pipe()?;
+    let (r2, w2) = unistd::pipe()?;
+
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        unistd::write(w1, b"hello").unwrap();
+    });
+
+    // should block until the other thread writes
+    ensure_ord!(splice(r1, None, w2, None, 100, 0), ==, Ok(5));
+    ensure_ord!(read_all(r2, 5)?, ==, b"hello".to_vec());
+
+    thread.join().unwrap();
+
+    for fd in [r1, w1, r2, w2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_copy_file_range() -> anyhow::Result<()> {
+    let fd1 = temp_file(b"0123456789")?;
+    let fd2 = temp_file(b"")?;
+
+    let mut off_in = 5;
+    ensure_ord!(copy_file_range(fd1, Some(&mut off_in), fd2, None, 100), ==, Ok(5));
+    ensure_ord!(off_in, ==, 10);
+    ensure_ord!(unistd::lseek(fd1, 0, unistd::Whence::SeekCur)?, ==, 0);
+    ensure_ord!(unistd::lseek(fd2, 0, unistd::Whence::SeekCur)?, ==, 5);
+
+    let mut off_out = 5;
+    ensure_ord!(copy_file_range(fd1, None, fd2, Some(&mut off_out), 3), ==, Ok(3));
+    ensure_ord!(off_out, ==, 8);
+    ensure_ord!(unistd::lseek(fd1, 0, unistd::Whence::SeekCur)?, ==, 3);
+
+    let mut buf = [0u8; 8];
+    ensure_ord!(nix::sys::uio::pread(fd2, &mut buf, 0), ==, Ok(8));
+    ensure_ord!(&buf, ==, b"56789012");
+
+    // not a regular file
+    let (r, w) = unistd::pipe()?;
+    ensure_ord!(copy_file_range(fd1, None, w, None, 3), ==, Err(Errno::EINVAL));
+
+    // invalid flags
+    let rv = unsafe {
+        libc::copy_file_range(fd1, std::ptr::null_mut(), fd2, std::ptr::null_mut(), 3, 1)
+    };
+    ensure_ord!(Errno::result(rv), ==, Err(Errno::EINVAL));
+
+    for fd in [fd1, fd2, r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new(
+            "splice-pipe-to-pipe",
+            test_splice_pipe_to_pipe,
+            all_envs.clone(),
+        ),
+        ShadowTest::new("tee", test_tee, all_envs.clone()),
+        ShadowTest::new(
+            "splice-file-to-pipe",
+            test_splice_file_to_pipe,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "splice-pipe-to-file",
+            test_splice_pipe_to_file,
+            all_envs.clone(),
+        ),
+        ShadowTest::new("splice-errors", test_splice_errors, all_envs.clone()),
+        ShadowTest::new("splice-full-pipe", test_splice_full_pipe, all_envs.clone()),
+        ShadowTest::new("sendfile", test_sendfile, all_envs.clone()),
+        ShadowTest::new("tcp", test_tcp, all_envs.clone()),
+        ShadowTest::new("blocking-splice", test_blocking_splice, all_envs.clone()),
+        ShadowTest::new("copy-file-range", test_copy_file_range, all_envs.clone()),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
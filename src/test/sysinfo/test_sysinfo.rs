@@ -3,14 +3,136 @@
  * See LICENSE for licensing information
  */
 
-fn main() {
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+// the values set in 'sysinfo.yaml'
+const GIB: u64 = 1024 * 1024 * 1024;
+const CONFIGURED_RAM_TOTAL: u64 = 8 * GIB;
+const CONFIGURED_RAM_FREE: u64 = 6 * GIB;
+const CONFIGURED_SWAP_TOTAL: u64 = 2 * GIB;
+const CONFIGURED_SWAP_FREE: u64 = GIB;
+const CONFIGURED_LOAD_AVERAGE: [f64; 3] = [0.5, 0.25, 1.75];
+
+fn sysinfo() -> nix::Result<libc::sysinfo> {
     let mut info = unsafe { std::mem::zeroed::<libc::sysinfo>() };
-    let info_ptr = &mut info as *mut libc::sysinfo;
-    let rv = unsafe { libc::sysinfo(info_ptr) };
+    let rv = unsafe { libc::sysinfo(&mut info) };
+    nix::errno::Errno::result(rv).map(|_| info)
+}
+
+/// The value in kB of the field `name` in '/proc/meminfo'.
+fn meminfo_kb(meminfo: &str, name: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find(|x| x.split(':').next() == Some(name))?;
+    let value = line.split(':').nth(1)?.trim().strip_suffix(" kB")?;
+    value.trim().parse().ok()
+}
+
+fn test_uptime() -> anyhow::Result<()> {
+    let info = sysinfo()?;
+    ensure_ord!(info.uptime, >, 0);
+    ensure_ord!(info.mem_unit, >, 0);
+    ensure_ord!(info.freeram, <=, info.totalram);
+    ensure_ord!(info.freeswap, <=, info.totalswap);
+    Ok(())
+}
+
+fn test_configured_sysinfo() -> anyhow::Result<()> {
+    let info = sysinfo()?;
+    let unit = u64::from(info.mem_unit);
+
+    ensure_ord!(info.totalram * unit, ==, CONFIGURED_RAM_TOTAL);
+    ensure_ord!(info.freeram * unit, ==, CONFIGURED_RAM_FREE);
+    ensure_ord!(info.totalswap * unit, ==, CONFIGURED_SWAP_TOTAL);
+    ensure_ord!(info.freeswap * unit, ==, CONFIGURED_SWAP_FREE);
+
+    // the loads are fixed-point values with a 16-bit fraction
+    let loads = CONFIGURED_LOAD_AVERAGE.map(|x| (x * 65536.0) as libc::c_ulong);
+    ensure_ord!(info.loads, ==, loads);
+
+    Ok(())
+}
+
+fn test_meminfo() -> anyhow::Result<()> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo")?;
+    let info = sysinfo()?;
+    let unit = u64::from(info.mem_unit);
+
+    let expected = [
+        ("MemTotal", CONFIGURED_RAM_TOTAL),
+        ("MemFree", CONFIGURED_RAM_FREE),
+        ("MemAvailable", CONFIGURED_RAM_FREE),
+        ("SwapTotal", CONFIGURED_SWAP_TOTAL),
+        ("SwapFree", CONFIGURED_SWAP_FREE),
+    ];
+    for (name, bytes) in expected {
+        ensure_ord!(meminfo_kb(&meminfo, name), ==, Some(bytes / 1024));
+    }
+
+    // the file is consistent with sysinfo
+    ensure_ord!(
+        meminfo_kb(&meminfo, "MemTotal"),
+        ==,
+        Some(info.totalram * unit / 1024)
+    );
+
+    Ok(())
+}
+
+fn test_loadavg() -> anyhow::Result<()> {
+    let loadavg = std::fs::read_to_string("/proc/loadavg")?;
+    let fields: Vec<&str> = loadavg.split_whitespace().collect();
+    ensure_ord!(fields.len(), ==, 5);
+
+    let loads: Vec<f64> = fields[..3]
+        .iter()
+        .map(|x| x.parse())
+        .collect::<Result<_, _>>()?;
+    ensure_ord!(loads, ==, CONFIGURED_LOAD_AVERAGE.to_vec());
+
+    // the number of processes is consistent with sysinfo
+    let info = sysinfo()?;
+    ensure_ord!(fields[3], ==, format!("1/{}", info.procs));
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("uptime", test_uptime, all_envs.clone()),
+        // the native values depend on the system
+        ShadowTest::new(
+            "configured-sysinfo",
+            test_configured_sysinfo,
+            set![TestEnvironment::Shadow],
+        ),
+        ShadowTest::new("meminfo", test_meminfo, set![TestEnvironment::Shadow]),
+        ShadowTest::new("loadavg", test_loadavg, set![TestEnvironment::Shadow]),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
 
-    println!("Found return value {:?}.", rv);
-    assert_eq!(rv, 0);
-    println!("Found uptime {:?}.", info.uptime);
-    assert!(info.uptime > 0);
     println!("Success.");
+
+    Ok(())
 }
@@ -0,0 +1,269 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Transfers the same data with each TCP congestion control algorithm over a lossy network, and
+//! checks that the algorithms behave differently.
+//!
+//! Usage: `test_tcp_congestion server <port>` or `test_tcp_congestion client <host> <port>
+//! <default-algorithm>`, where `<default-algorithm>` is the algorithm that the client's host is
+//! configured to use by default.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::os::unix::io::FromRawFd;
+use std::time::{Duration, Instant};
+
+use nix::errno::Errno;
+use test_utils::ensure_ord;
+
+const ALGORITHMS: [&str; 3] = ["reno", "cubic", "bbr"];
+
+/// The number of bytes sent over each connection.
+const TRANSFER_SIZE: usize = 2 * 1024 * 1024;
+
+/// The value of `TCP_INFINITE_SSTHRESH` in linux.
+const INFINITE_SSTHRESH: u32 = 0x7fffffff;
+
+/// The beginning of linux's `struct tcp_info`. The libc crate doesn't expose this struct.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+#[allow(dead_code)]
+struct TcpInfo {
+    tcpi_state: u8,
+    tcpi_ca_state: u8,
+    tcpi_retransmits: u8,
+    tcpi_probes: u8,
+    tcpi_backoff: u8,
+    tcpi_options: u8,
+    tcpi_wscale: u8,
+    tcpi_flags: u8,
+    tcpi_rto: u32,
+    tcpi_ato: u32,
+    tcpi_snd_mss: u32,
+    tcpi_rcv_mss: u32,
+    tcpi_unacked: u32,
+    tcpi_sacked: u32,
+    tcpi_lost: u32,
+    tcpi_retrans: u32,
+    tcpi_fackets: u32,
+    tcpi_last_data_sent: u32,
+    tcpi_last_ack_sent: u32,
+    tcpi_last_data_recv: u32,
+    tcpi_last_ack_recv: u32,
+    tcpi_pmtu: u32,
+    tcpi_rcv_ssthresh: u32,
+    tcpi_rtt: u32,
+    tcpi_rttvar: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    tcpi_advmss: u32,
+    tcpi_reordering: u32,
+    tcpi_rcv_rtt: u32,
+    tcpi_rcv_space: u32,
+    tcpi_total_retrans: u32,
+}
+
+/// What was observed while transferring data with a single algorithm.
+#[derive(Debug, Default)]
+struct TransferStats {
+    /// The time from the first write until the server acknowledged receiving all of the data.
+    elapsed: Duration,
+    /// The congestion window after each write.
+    cwnd_samples: Vec<u32>,
+    /// The smallest slow start threshold observed.
+    min_ssthresh: u32,
+    /// The total number of retransmitted packets.
+    retransmits: u32,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+
+    match args.get(1).map(String::as_str) {
+        Some("server") => server(args[2].parse()?),
+        Some("client") => client(&args[2], args[3].parse()?, &args[4]),
+        _ => anyhow::bail!(
+            "Usage: {} server <port> | client <host> <port> <algorithm>",
+            args[0]
+        ),
+    }
+}
+
+/// Accept one connection per algorithm, and send a single byte back after all of the data has
+/// been received.
+fn server(port: u16) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+
+    for _ in ALGORITHMS {
+        let (mut stream, _) = listener.accept()?;
+
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut num_received = 0;
+        while num_received < TRANSFER_SIZE {
+            let n = stream.read(&mut buf)?;
+            anyhow::ensure!(n != 0, "Connection closed after {} bytes", num_received);
+            num_received += n;
+        }
+
+        ensure_ord!(num_received, ==, TRANSFER_SIZE);
+        stream.write_all(&[0])?;
+    }
+
+    Ok(())
+}
+
+fn client(host: &str, port: u16, default_algorithm: &str) -> anyhow::Result<()> {
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .find(SocketAddr::is_ipv4)
+        .ok_or_else(|| anyhow::anyhow!("No address for {}", host))?;
+
+    // new sockets should use the host's configured algorithm
+    let fd = tcp_socket()?;
+    let algorithm = get_congestion(fd);
+    nix::unistd::close(fd)?;
+    ensure_ord!(algorithm?.as_str(), ==, default_algorithm);
+
+    let mut results = Vec::new();
+    for algorithm in ALGORITHMS {
+        let stats = transfer(addr, algorithm)?;
+        println!("{}: {:?}", algorithm, stats);
+        results.push(stats);
+    }
+
+    let [reno, cubic, bbr] = <[TransferStats; 3]>::try_from(results).unwrap();
+
+    // the network is lossy, so every connection should have retransmitted packets
+    for stats in [&reno, &cubic, &bbr] {
+        ensure_ord!(stats.retransmits, >, 0);
+    }
+
+    // reno and cubic reduce the slow start threshold when packets are lost, but bbr doesn't use
+    // one
+    ensure_ord!(reno.min_ssthresh, <, INFINITE_SSTHRESH);
+    ensure_ord!(cubic.min_ssthresh, <, INFINITE_SSTHRESH);
+    ensure_ord!(bbr.min_ssthresh, ==, INFINITE_SSTHRESH);
+
+    // each algorithm should grow and shrink its window differently, which should change how long
+    // the transfer takes
+    for (a, b) in [(&reno, &cubic), (&reno, &bbr), (&cubic, &bbr)] {
+        ensure_ord!(a.cwnd_samples, !=, b.cwnd_samples);
+        ensure_ord!(a.elapsed, !=, b.elapsed);
+    }
+
+    Ok(())
+}
+
+/// Send `TRANSFER_SIZE` bytes to `addr` using the given congestion control algorithm.
+fn transfer(addr: SocketAddr, algorithm: &str) -> anyhow::Result<TransferStats> {
+    let fd = tcp_socket()?;
+
+    // take ownership so that the socket is closed when we return
+    let mut stream = unsafe { TcpStream::from_raw_fd(fd) };
+
+    set_congestion(fd, algorithm)?;
+    ensure_ord!(get_congestion(fd)?.as_str(), ==, algorithm);
+
+    let addr = match addr {
+        SocketAddr::V4(addr) => libc::sockaddr_in {
+            sin_family: libc::AF_INET as u16,
+            sin_port: addr.port().to_be(),
+            sin_addr: libc::in_addr {
+                s_addr: u32::from(*addr.ip()).to_be(),
+            },
+            sin_zero: [0; 8],
+        },
+        SocketAddr::V6(_) => unreachable!(),
+    };
+    Errno::result(unsafe {
+        libc::connect(
+            fd,
+            &addr as *const _ as *const libc::sockaddr,
+            std::mem::size_of_val(&addr) as libc::socklen_t,
+        )
+    })?;
+
+    // the algorithm shouldn't change when connecting
+    ensure_ord!(get_congestion(fd)?.as_str(), ==, algorithm);
+
+    let mut stats = TransferStats {
+        min_ssthresh: INFINITE_SSTHRESH,
+        ..Default::default()
+    };
+
+    let buf = vec![1u8; 16 * 1024];
+    let start = Instant::now();
+
+    let mut num_sent = 0;
+    while num_sent < TRANSFER_SIZE {
+        let len = std::cmp::min(buf.len(), TRANSFER_SIZE - num_sent);
+        num_sent += stream.write(&buf[..len])?;
+
+        let info = get_tcp_info(fd)?;
+        stats.cwnd_samples.push(info.tcpi_snd_cwnd);
+        stats.min_ssthresh = std::cmp::min(stats.min_ssthresh, info.tcpi_snd_ssthresh);
+    }
+
+    // wait for the server to receive everything
+    let mut reply = [0u8; 1];
+    stream.read_exact(&mut reply)?;
+    stats.elapsed = start.elapsed();
+
+    let info = get_tcp_info(fd)?;
+    stats.min_ssthresh = std::cmp::min(stats.min_ssthresh, info.tcpi_snd_ssthresh);
+    stats.retransmits = info.tcpi_total_retrans;
+
+    Ok(stats)
+}
+
+fn tcp_socket() -> nix::Result<libc::c_int> {
+    Errno::result(unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) })
+}
+
+fn get_congestion(fd: libc::c_int) -> anyhow::Result<String> {
+    let mut buf = [0u8; 16];
+    let mut len = buf.len() as libc::socklen_t;
+    Errno::result(unsafe {
+        libc::getsockopt(
+            fd,
+            libc::IPPROTO_TCP,
+            libc::TCP_CONGESTION,
+            buf.as_mut_ptr() as *mut libc::c_void,
+            &mut len,
+        )
+    })?;
+
+    let buf = &buf[..len as usize];
+    let name = buf.split(|x| *x == 0).next().unwrap();
+    Ok(String::from_utf8(name.to_vec())?)
+}
+
+fn set_congestion(fd: libc::c_int, algorithm: &str) -> nix::Result<()> {
+    Errno::result(unsafe {
+        libc::setsockopt(
+            fd,
+            libc::IPPROTO_TCP,
+            libc::TCP_CONGESTION,
+            algorithm.as_ptr() as *const libc::c_void,
+            algorithm.len() as libc::socklen_t,
+        )
+    })?;
+    Ok(())
+}
+
+fn get_tcp_info(fd: libc::c_int) -> nix::Result<TcpInfo> {
+    let mut info = TcpInfo::default();
+    let mut len = std::mem::size_of_val(&info) as libc::socklen_t;
+    Errno::result(unsafe {
+        libc::getsockopt(
+            fd,
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            &mut info as *mut _ as *mut libc::c_void,
+            &mut len,
+        )
+    })?;
+    Ok(info)
+}
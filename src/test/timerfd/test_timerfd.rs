@@ -0,0 +1,335 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::sys::epoll::{self, EpollFlags};
+use nix::unistd;
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+/// From the linux headers. Not exposed by libc.
+const TFD_TIMER_CANCEL_ON_SET: libc::c_int = 1 << 1;
+
+fn timerfd_create(clock_id: libc::clockid_t, flags: libc::c_int) -> nix::Result<libc::c_int> {
+    Errno::result(unsafe { libc::timerfd_create(clock_id, flags) })
+}
+
+fn timerfd_settime(
+    fd: libc::c_int,
+    flags: libc::c_int,
+    value: Duration,
+    interval: Duration,
+) -> nix::Result<libc::itimerspec> {
+    let new_value = libc::itimerspec {
+        it_value: duration_to_timespec(value),
+        it_interval: duration_to_timespec(interval),
+    };
+    let mut old_value = duration_to_itimerspec(Duration::ZERO, Duration::ZERO);
+
+    Errno::result(unsafe { libc::timerfd_settime(fd, flags, &new_value, &mut old_value) })?;
+
+    Ok(old_value)
+}
+
+/// Returns the time remaining until the next expiration, and the interval.
+fn timerfd_gettime(fd: libc::c_int) -> nix::Result<(Duration, Duration)> {
+    let mut curr_value = duration_to_itimerspec(Duration::ZERO, Duration::ZERO);
+    Errno::result(unsafe { libc::timerfd_gettime(fd, &mut curr_value) })?;
+    Ok(itimerspec_to_durations(&curr_value))
+}
+
+/// Returns the expiration count.
+fn timerfd_read(fd: libc::c_int) -> nix::Result<u64> {
+    let mut buf = [0u8; 8];
+    let num_bytes = unistd::read(fd, &mut buf)?;
+    assert_eq!(num_bytes, buf.len());
+    Ok(u64::from_ne_bytes(buf))
+}
+
+fn duration_to_timespec(d: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: d.as_secs().try_into().unwrap(),
+        tv_nsec: d.subsec_nanos().into(),
+    }
+}
+
+fn duration_to_itimerspec(value: Duration, interval: Duration) -> libc::itimerspec {
+    libc::itimerspec {
+        it_value: duration_to_timespec(value),
+        it_interval: duration_to_timespec(interval),
+    }
+}
+
+fn timespec_to_duration(ts: &libc::timespec) -> Duration {
+    Duration::new(
+        ts.tv_sec.try_into().unwrap(),
+        ts.tv_nsec.try_into().unwrap(),
+    )
+}
+
+fn itimerspec_to_durations(its: &libc::itimerspec) -> (Duration, Duration) {
+    (
+        timespec_to_duration(&its.it_value),
+        timespec_to_duration(&its.it_interval),
+    )
+}
+
+fn realtime_now() -> Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+}
+
+fn test_create_errors() -> anyhow::Result<()> {
+    ensure_ord!(timerfd_create(1234, 0), ==, Err(Errno::EINVAL));
+    ensure_ord!(
+        timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_TIMER_ABSTIME),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    for clock_id in [
+        libc::CLOCK_REALTIME,
+        libc::CLOCK_MONOTONIC,
+        libc::CLOCK_BOOTTIME,
+    ] {
+        let fd = timerfd_create(clock_id, libc::TFD_NONBLOCK | libc::TFD_CLOEXEC)?;
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_settime_errors() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK)?;
+    let (readfd, writefd) = unistd::pipe()?;
+
+    let value = Duration::from_secs(1);
+
+    // invalid flags
+    ensure_ord!(
+        timerfd_settime(fd, 0xff, value, Duration::ZERO).map(|_| ()),
+        ==,
+        Err(Errno::EINVAL)
+    );
+
+    // invalid timespec
+    let new_value = libc::itimerspec {
+        it_value: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 1_000_000_000,
+        },
+        it_interval: duration_to_timespec(Duration::ZERO),
+    };
+    let rv = unsafe { libc::timerfd_settime(fd, 0, &new_value, std::ptr::null_mut()) };
+    ensure_ord!(Errno::result(rv), ==, Err(Errno::EINVAL));
+
+    // not a timerfd
+    ensure_ord!(
+        timerfd_settime(readfd, 0, value, Duration::ZERO).map(|_| ()),
+        ==,
+        Err(Errno::EINVAL)
+    );
+    ensure_ord!(timerfd_gettime(readfd), ==, Err(Errno::EINVAL));
+
+    // not a valid fd
+    ensure_ord!(
+        timerfd_settime(1000, 0, value, Duration::ZERO).map(|_| ()),
+        ==,
+        Err(Errno::EBADF)
+    );
+
+    // buffer too small
+    timerfd_settime(fd, 0, Duration::from_millis(1), Duration::ZERO)?;
+    std::thread::sleep(Duration::from_millis(5));
+    let mut buf = [0u8; 4];
+    ensure_ord!(unistd::read(fd, &mut buf), ==, Err(Errno::EINVAL));
+
+    for fd in [fd, readfd, writefd] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_nonblocking_read() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK)?;
+
+    // disarmed
+    ensure_ord!(timerfd_read(fd), ==, Err(Errno::EAGAIN));
+
+    // armed but not yet expired
+    timerfd_settime(fd, 0, Duration::from_secs(10), Duration::ZERO)?;
+    ensure_ord!(timerfd_read(fd), ==, Err(Errno::EAGAIN));
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_periodic() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_BOOTTIME, 0)?;
+
+    let interval = Duration::from_millis(10);
+    timerfd_settime(fd, 0, interval, interval)?;
+
+    std::thread::sleep(Duration::from_millis(55));
+
+    // the timer should have expired at least 5 times, and a blocking read shouldn't block
+    ensure_ord!(timerfd_read(fd)?, >=, 5);
+
+    // the next read should block until the next expiration
+    let t0 = std::time::Instant::now();
+    ensure_ord!(timerfd_read(fd)?, >=, 1);
+    ensure_ord!(t0.elapsed(), <=, 2 * interval);
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_gettime() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_REALTIME, libc::TFD_NONBLOCK)?;
+
+    ensure_ord!(timerfd_gettime(fd)?, ==, (Duration::ZERO, Duration::ZERO));
+
+    // the current value is relative, even for absolute timers
+    let value = Duration::from_secs(10);
+    let interval = Duration::from_secs(2);
+    timerfd_settime(
+        fd,
+        libc::TFD_TIMER_ABSTIME,
+        realtime_now() + value,
+        interval,
+    )?;
+
+    let (remaining, curr_interval) = timerfd_gettime(fd)?;
+    ensure_ord!(remaining, >, Duration::ZERO);
+    ensure_ord!(remaining, <=, value);
+    ensure_ord!(curr_interval, ==, interval);
+
+    // the old value is also relative
+    let old = timerfd_settime(fd, 0, Duration::ZERO, Duration::ZERO)?;
+    let (old_remaining, old_interval) = itimerspec_to_durations(&old);
+    ensure_ord!(old_remaining, >, Duration::ZERO);
+    ensure_ord!(old_remaining, <=, value);
+    ensure_ord!(old_interval, ==, interval);
+
+    // setting a zero value disarmed the timer
+    ensure_ord!(timerfd_gettime(fd)?, ==, (Duration::ZERO, Duration::ZERO));
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_abstime_past() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_REALTIME, 0)?;
+
+    // an absolute time in the past expires immediately
+    timerfd_settime(
+        fd,
+        libc::TFD_TIMER_ABSTIME,
+        Duration::from_secs(1),
+        Duration::ZERO,
+    )?;
+    ensure_ord!(timerfd_read(fd), ==, Ok(1));
+    ensure_ord!(timerfd_gettime(fd)?, ==, (Duration::ZERO, Duration::ZERO));
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_cancel_on_set_flag() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_REALTIME, libc::TFD_NONBLOCK)?;
+
+    let flags = libc::TFD_TIMER_ABSTIME | TFD_TIMER_CANCEL_ON_SET;
+    timerfd_settime(
+        fd,
+        flags,
+        realtime_now() + Duration::from_millis(10),
+        Duration::ZERO,
+    )?;
+
+    // the clock wasn't changed, so the timer expires normally
+    std::thread::sleep(Duration::from_millis(20));
+    ensure_ord!(timerfd_read(fd), ==, Ok(1));
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_epoll() -> anyhow::Result<()> {
+    let fd = timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK)?;
+    let epollfd = epoll::epoll_create()?;
+
+    let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0);
+    epoll::epoll_ctl(epollfd, epoll::EpollOp::EpollCtlAdd, fd, Some(&mut event))?;
+
+    let mut events = [epoll::EpollEvent::empty(); 10];
+
+    // not readable until it expires
+    ensure_ord!(epoll::epoll_wait(epollfd, &mut events, 0), ==, Ok(0));
+
+    timerfd_settime(fd, 0, Duration::from_millis(10), Duration::ZERO)?;
+
+    ensure_ord!(epoll::epoll_wait(epollfd, &mut events, 100), ==, Ok(1));
+    ensure_ord!(events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0));
+
+    // no longer readable after reading
+    ensure_ord!(timerfd_read(fd), ==, Ok(1));
+    ensure_ord!(epoll::epoll_wait(epollfd, &mut events, 0), ==, Ok(0));
+
+    unistd::close(epollfd)?;
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("create-errors", test_create_errors, all_envs.clone()),
+        ShadowTest::new("settime-errors", test_settime_errors, all_envs.clone()),
+        ShadowTest::new("nonblocking-read", test_nonblocking_read, all_envs.clone()),
+        ShadowTest::new("periodic", test_periodic, all_envs.clone()),
+        ShadowTest::new("gettime", test_gettime, all_envs.clone()),
+        ShadowTest::new("abstime-past", test_abstime_past, all_envs.clone()),
+        ShadowTest::new(
+            "cancel-on-set-flag",
+            test_cancel_on_set_flag,
+            all_envs.clone(),
+        ),
+        ShadowTest::new("epoll", test_epoll, all_envs.clone()),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
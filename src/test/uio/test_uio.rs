@@ -0,0 +1,325 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::sys::eventfd::{eventfd, EfdFlags};
+use nix::sys::socket::{self, AddressFamily, SockFlag, SockType};
+use nix::unistd;
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+// from linux's 'include/uapi/linux/fs.h'
+const RWF_NOWAIT: libc::c_int = 0x00000008;
+
+fn to_iovecs(bufs: &[&[u8]]) -> Vec<libc::iovec> {
+    bufs.iter()
+        .map(|x| libc::iovec {
+            iov_base: x.as_ptr() as *mut libc::c_void,
+            iov_len: x.len(),
+        })
+        .collect()
+}
+
+fn to_iovecs_mut(bufs: &mut [&mut [u8]]) -> Vec<libc::iovec> {
+    bufs.iter_mut()
+        .map(|x| libc::iovec {
+            iov_base: x.as_mut_ptr() as *mut libc::c_void,
+            iov_len: x.len(),
+        })
+        .collect()
+}
+
+fn readv(fd: libc::c_int, bufs: &mut [&mut [u8]]) -> nix::Result<usize> {
+    let iovs = to_iovecs_mut(bufs);
+    let rv = unsafe { libc::readv(fd, iovs.as_ptr(), iovs.len() as libc::c_int) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn writev(fd: libc::c_int, bufs: &[&[u8]]) -> nix::Result<usize> {
+    let iovs = to_iovecs(bufs);
+    let rv = unsafe { libc::writev(fd, iovs.as_ptr(), iovs.len() as libc::c_int) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn preadv(fd: libc::c_int, bufs: &mut [&mut [u8]], offset: libc::off_t) -> nix::Result<usize> {
+    let iovs = to_iovecs_mut(bufs);
+    let rv = unsafe { libc::preadv(fd, iovs.as_ptr(), iovs.len() as libc::c_int, offset) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn pwritev(fd: libc::c_int, bufs: &[&[u8]], offset: libc::off_t) -> nix::Result<usize> {
+    let iovs = to_iovecs(bufs);
+    let rv = unsafe { libc::pwritev(fd, iovs.as_ptr(), iovs.len() as libc::c_int, offset) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+// use the syscall directly since older versions of glibc don't have a wrapper
+fn preadv2(
+    fd: libc::c_int,
+    bufs: &mut [&mut [u8]],
+    offset: libc::off_t,
+    flags: libc::c_int,
+) -> nix::Result<usize> {
+    let iovs = to_iovecs_mut(bufs);
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_preadv2,
+            fd,
+            iovs.as_ptr(),
+            iovs.len(),
+            offset,
+            0,
+            flags,
+        )
+    };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn pwritev2(
+    fd: libc::c_int,
+    bufs: &[&[u8]],
+    offset: libc::off_t,
+    flags: libc::c_int,
+) -> nix::Result<usize> {
+    let iovs = to_iovecs(bufs);
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_pwritev2,
+            fd,
+            iovs.as_ptr(),
+            iovs.len(),
+            offset,
+            0,
+            flags,
+        )
+    };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn test_pipe() -> anyhow::Result<()> {
+    let (r, w) = unistd::pipe()?;
+
+    // empty io vectors are allowed
+    ensure_ord!(writev(w, &[b"hel", b"", b"lo world"]), ==, Ok(11));
+
+    let mut buf_1 = [0u8; 5];
+    let mut buf_2 = [0u8; 10];
+    ensure_ord!(readv(r, &mut [&mut buf_1, &mut [], &mut buf_2]), ==, Ok(11));
+    ensure_ord!(&buf_1, ==, b"hello");
+    ensure_ord!(&buf_2[..6], ==, b" world");
+
+    for fd in [r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_errors() -> anyhow::Result<()> {
+    let (r, w) = unistd::pipe()?;
+
+    // no io vectors doesn't block, even though the pipe is empty
+    ensure_ord!(readv(r, &mut []), ==, Ok(0));
+    ensure_ord!(writev(w, &[]), ==, Ok(0));
+
+    // too many io vectors
+    let bufs = vec![&b"a"[..]; 1025];
+    ensure_ord!(writev(w, &bufs), ==, Err(Errno::EINVAL));
+
+    // the wrong end of the pipe
+    let mut buf = [0u8; 5];
+    ensure_ord!(readv(w, &mut [&mut buf]), ==, Err(Errno::EBADF));
+    ensure_ord!(writev(r, &[b"hello"]), ==, Err(Errno::EBADF));
+
+    // not a valid descriptor
+    ensure_ord!(readv(1000, &mut [&mut buf]), ==, Err(Errno::EBADF));
+
+    // pipes can't be seeked
+    ensure_ord!(preadv(r, &mut [&mut buf], 0), ==, Err(Errno::ESPIPE));
+    ensure_ord!(pwritev(w, &[b"hello"], 0), ==, Err(Errno::ESPIPE));
+    ensure_ord!(preadv(r, &mut [&mut buf], -2), ==, Err(Errno::EINVAL));
+
+    // unknown flags
+    ensure_ord!(
+        pwritev2(w, &[b"hello"], -1, 0x1000),
+        ==,
+        Err(Errno::EOPNOTSUPP)
+    );
+
+    for fd in [r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_current_position() -> anyhow::Result<()> {
+    let (r, w) = unistd::pipe()?;
+
+    // an offset of -1 uses the current position
+    ensure_ord!(pwritev2(w, &[b"ab", b"cd"], -1, 0), ==, Ok(4));
+
+    let mut buf = [0u8; 4];
+    ensure_ord!(preadv2(r, &mut [&mut buf], -1, 0), ==, Ok(4));
+    ensure_ord!(&buf, ==, b"abcd");
+
+    for fd in [r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_nowait() -> anyhow::Result<()> {
+    let (fd_1, fd_2) = socket::socketpair(
+        AddressFamily::Unix,
+        SockType::Stream,
+        None,
+        SockFlag::empty(),
+    )?;
+
+    // the socket is blocking, but shouldn't block
+    let mut buf = [0u8; 5];
+    ensure_ord!(
+        preadv2(fd_1, &mut [&mut buf], -1, RWF_NOWAIT),
+        ==,
+        Err(Errno::EAGAIN)
+    );
+
+    ensure_ord!(writev(fd_2, &[b"hel", b"lo"]), ==, Ok(5));
+    ensure_ord!(preadv2(fd_1, &mut [&mut buf], -1, RWF_NOWAIT), ==, Ok(5));
+    ensure_ord!(&buf, ==, b"hello");
+
+    for fd in [fd_1, fd_2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_eventfd() -> anyhow::Result<()> {
+    let fd = eventfd(0, EfdFlags::empty())?;
+
+    // the 8-byte value can be split across io vectors
+    let val = 5u64.to_ne_bytes();
+    ensure_ord!(writev(fd, &[&val[..3], &val[3..]]), ==, Ok(8));
+
+    let mut buf_1 = [0u8; 4];
+    let mut buf_2 = [0u8; 4];
+    ensure_ord!(readv(fd, &mut [&mut buf_1, &mut buf_2]), ==, Ok(8));
+    ensure_ord!([buf_1, buf_2].concat(), ==, val.to_vec());
+
+    unistd::close(fd)?;
+
+    Ok(())
+}
+
+fn test_message_boundaries(sock_type: SockType) -> anyhow::Result<()> {
+    let (fd_1, fd_2) = socket::socketpair(AddressFamily::Unix, sock_type, None, SockFlag::empty())?;
+
+    // each writev() sends a single message
+    ensure_ord!(writev(fd_1, &[b"abc", b"defg"]), ==, Ok(7));
+    ensure_ord!(writev(fd_1, &[b"hi"]), ==, Ok(2));
+    ensure_ord!(writev(fd_1, &[b"123", b"456"]), ==, Ok(6));
+    ensure_ord!(writev(fd_1, &[b"xy"]), ==, Ok(2));
+
+    let mut buf_1 = [0u8; 4];
+    let mut buf_2 = [0u8; 10];
+
+    // each readv() receives a single message
+    ensure_ord!(readv(fd_2, &mut [&mut buf_1, &mut buf_2]), ==, Ok(7));
+    ensure_ord!(&buf_1, ==, b"abcd");
+    ensure_ord!(&buf_2[..3], ==, b"efg");
+
+    ensure_ord!(readv(fd_2, &mut [&mut buf_1, &mut buf_2]), ==, Ok(2));
+    ensure_ord!(&buf_1[..2], ==, b"hi");
+
+    // the rest of a truncated message is discarded
+    let mut buf_3 = [0u8; 2];
+    let mut buf_4 = [0u8; 2];
+    ensure_ord!(readv(fd_2, &mut [&mut buf_3, &mut buf_4]), ==, Ok(4));
+    ensure_ord!([buf_3, buf_4].concat(), ==, b"1234".to_vec());
+
+    ensure_ord!(readv(fd_2, &mut [&mut buf_3, &mut buf_4]), ==, Ok(2));
+    ensure_ord!(&buf_3, ==, b"xy");
+
+    for fd in [fd_1, fd_2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_blocking_readv() -> anyhow::Result<()> {
+    let (r, w) = unistd::pipe()?;
+
+    let handle = std::thread::spawn(move || -> anyhow::Result<()> {
+        std::thread::sleep(Duration::from_millis(100));
+        ensure_ord!(writev(w, &[b"hello"]), ==, Ok(5));
+        Ok(())
+    });
+
+    // blocks until the other thread writes
+    let mut buf_1 = [0u8; 2];
+    let mut buf_2 = [0u8; 3];
+    ensure_ord!(readv(r, &mut [&mut buf_1, &mut buf_2]), ==, Ok(5));
+    ensure_ord!([&buf_1[..], &buf_2[..]].concat(), ==, b"hello".to_vec());
+
+    handle.join().unwrap()?;
+
+    for fd in [r, w] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("pipe", test_pipe, all_envs.clone()),
+        ShadowTest::new("errors", test_errors, all_envs.clone()),
+        ShadowTest::new("current-position", test_current_position, all_envs.clone()),
+        ShadowTest::new("nowait", test_nowait, all_envs.clone()),
+        ShadowTest::new("eventfd", test_eventfd, all_envs.clone()),
+        ShadowTest::new(
+            "dgram-boundaries",
+            || test_message_boundaries(SockType::Datagram),
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "seqpacket-boundaries",
+            || test_message_boundaries(SockType::SeqPacket),
+            all_envs.clone(),
+        ),
+        ShadowTest::new("blocking-readv", test_blocking_readv, all_envs.clone()),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
@@ -0,0 +1,246 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::io::IoSlice;
+use std::io::IoSliceMut;
+use std::os::unix::io::RawFd;
+
+use nix::cmsg_space;
+use nix::sys::eventfd::{eventfd, EfdFlags};
+use nix::sys::socket::{
+    self, sockopt, AddressFamily, ControlMessage, ControlMessageOwned, MsgFlags, SockFlag,
+    SockType, UnixAddr, UnixCredentials,
+};
+use nix::unistd;
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+fn socketpair(sock_type: SockType) -> nix::Result<(RawFd, RawFd)> {
+    socket::socketpair(AddressFamily::Unix, sock_type, None, SockFlag::empty())
+}
+
+/// Send `fd` over the socket along with a single byte of data.
+fn send_fd(sock: RawFd, fd: RawFd) -> nix::Result<usize> {
+    let fds = [fd];
+    let cmsgs = [ControlMessage::ScmRights(&fds)];
+    socket::sendmsg::<UnixAddr>(sock, &[IoSlice::new(b"x")], &cmsgs, MsgFlags::empty(), None)
+}
+
+/// Receive a single byte of data and any file descriptors sent with it. Returns the received file
+/// descriptors and the message flags.
+fn recv_fds(sock: RawFd) -> anyhow::Result<(Vec<RawFd>, MsgFlags)> {
+    let mut buf = [0u8; 1];
+    let mut iov = [IoSliceMut::new(&mut buf)];
+    let mut cmsg_buf = cmsg_space!([RawFd; 1]);
+
+    let msg = socket::recvmsg::<UnixAddr>(sock, &mut iov, Some(&mut cmsg_buf), MsgFlags::empty())?;
+    ensure_ord!(msg.bytes, ==, 1);
+
+    let mut fds = Vec::new();
+    for cmsg in msg.cmsgs() {
+        match cmsg {
+            ControlMessageOwned::ScmRights(x) => fds.extend(x),
+            x => anyhow::bail!("Unexpected control message: {:?}", x),
+        }
+    }
+
+    Ok((fds, msg.flags))
+}
+
+fn test_pass_pipe(sock_type: SockType) -> anyhow::Result<()> {
+    let (sock_1, sock_2) = socketpair(sock_type)?;
+    let (read_fd, write_fd) = unistd::pipe()?;
+
+    ensure_ord!(send_fd(sock_1, write_fd), ==, Ok(1));
+
+    // the file remains open in the message even though the original descriptor is closed
+    unistd::close(write_fd)?;
+
+    let (fds, flags) = recv_fds(sock_2)?;
+    ensure_ord!(fds.len(), ==, 1);
+    ensure_ord!(flags, ==, MsgFlags::empty());
+
+    // the received descriptor refers to the same pipe
+    ensure_ord!(unistd::write(fds[0], b"hello"), ==, Ok(5));
+    let mut buf = [0u8; 10];
+    ensure_ord!(unistd::read(read_fd, &mut buf), ==, Ok(5));
+    ensure_ord!(&buf[..5], ==, b"hello");
+
+    for fd in [fds[0], read_fd, sock_1, sock_2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_pass_eventfd(sock_type: SockType) -> anyhow::Result<()> {
+    let (sock_1, sock_2) = socketpair(sock_type)?;
+    let efd = eventfd(0, EfdFlags::empty())?;
+
+    ensure_ord!(send_fd(sock_1, efd), ==, Ok(1));
+
+    let (fds, _flags) = recv_fds(sock_2)?;
+    ensure_ord!(fds.len(), ==, 1);
+    ensure_ord!(fds[0], !=, efd);
+
+    // the received descriptor refers to the same eventfd
+    ensure_ord!(unistd::write(fds[0], &5u64.to_ne_bytes()), ==, Ok(8));
+    let mut buf = [0u8; 8];
+    ensure_ord!(unistd::read(efd, &mut buf), ==, Ok(8));
+    ensure_ord!(u64::from_ne_bytes(buf), ==, 5);
+
+    for fd in [fds[0], efd, sock_1, sock_2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_pass_fd_ctrunc() -> anyhow::Result<()> {
+    let (sock_1, sock_2) = socketpair(SockType::Stream)?;
+    let efd = eventfd(0, EfdFlags::empty())?;
+
+    ensure_ord!(send_fd(sock_1, efd), ==, Ok(1));
+
+    // no control buffer, so the file is discarded
+    let mut buf = [0u8; 1];
+    let mut iov = [IoSliceMut::new(&mut buf)];
+    let msg = socket::recvmsg::<UnixAddr>(sock_2, &mut iov, None, MsgFlags::empty())?;
+    ensure_ord!(msg.bytes, ==, 1);
+    ensure_ord!(msg.flags, ==, MsgFlags::MSG_CTRUNC);
+    ensure_ord!(msg.cmsgs().count(), ==, 0);
+
+    for fd in [efd, sock_1, sock_2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+/// Receive a single byte of data and return the credentials sent with it.
+fn recv_creds(sock: RawFd) -> anyhow::Result<Option<UnixCredentials>> {
+    let mut buf = [0u8; 1];
+    let mut iov = [IoSliceMut::new(&mut buf)];
+    let mut cmsg_buf = cmsg_space!(libc::ucred);
+
+    let msg = socket::recvmsg::<UnixAddr>(sock, &mut iov, Some(&mut cmsg_buf), MsgFlags::empty())?;
+    ensure_ord!(msg.bytes, ==, 1);
+
+    let mut creds = None;
+    for cmsg in msg.cmsgs() {
+        match cmsg {
+            ControlMessageOwned::ScmCredentials(x) => creds = Some(x),
+            x => anyhow::bail!("Unexpected control message: {:?}", x),
+        }
+    }
+
+    Ok(creds)
+}
+
+fn test_passcred(sock_type: SockType) -> anyhow::Result<()> {
+    let (sock_1, sock_2) = socketpair(sock_type)?;
+
+    // credentials are only received if SO_PASSCRED is enabled
+    ensure_ord!(socket::send(sock_1, b"a", MsgFlags::empty()), ==, Ok(1));
+    ensure_ord!(recv_creds(sock_2)?, ==, None);
+
+    socket::setsockopt(sock_2, sockopt::PassCred, &true)?;
+    ensure_ord!(socket::getsockopt(sock_2, sockopt::PassCred), ==, Ok(true));
+
+    // the kernel attaches the sender's credentials even if none were sent
+    ensure_ord!(socket::send(sock_1, b"b", MsgFlags::empty()), ==, Ok(1));
+    let creds = recv_creds(sock_2)?.ok_or_else(|| anyhow::anyhow!("No credentials"))?;
+    ensure_ord!(creds.pid(), ==, unistd::getpid().as_raw());
+    ensure_ord!(creds.uid(), ==, unistd::getuid().as_raw());
+    ensure_ord!(creds.gid(), ==, unistd::getgid().as_raw());
+
+    for fd in [sock_1, sock_2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn test_send_creds(sock_type: SockType) -> anyhow::Result<()> {
+    let (sock_1, sock_2) = socketpair(sock_type)?;
+    socket::setsockopt(sock_2, sockopt::PassCred, &true)?;
+
+    // a process can send its own credentials explicitly
+    let creds = UnixCredentials::new();
+    let cmsgs = [ControlMessage::ScmCredentials(&creds)];
+    ensure_ord!(
+        socket::sendmsg::<UnixAddr>(sock_1, &[IoSlice::new(b"x")], &cmsgs, MsgFlags::empty(), None),
+        ==,
+        Ok(1)
+    );
+    ensure_ord!(recv_creds(sock_2)?, ==, Some(creds));
+
+    for fd in [sock_1, sock_2] {
+        unistd::close(fd)?;
+    }
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![ShadowTest::new(
+        "pass-fd-ctrunc",
+        test_pass_fd_ctrunc,
+        all_envs.clone(),
+    )];
+
+    for sock_type in [SockType::Stream, SockType::Datagram, SockType::SeqPacket] {
+        // add details to the test names to avoid duplicates
+        let append_args = |s| format!("{} <sock_type={:?}>", s, sock_type);
+
+        tests.extend(vec![
+            ShadowTest::new(
+                &append_args("pass-pipe"),
+                move || test_pass_pipe(sock_type),
+                all_envs.clone(),
+            ),
+            ShadowTest::new(
+                &append_args("pass-eventfd"),
+                move || test_pass_eventfd(sock_type),
+                all_envs.clone(),
+            ),
+            ShadowTest::new(
+                &append_args("passcred"),
+                move || test_passcred(sock_type),
+                all_envs.clone(),
+            ),
+            ShadowTest::new(
+                &append_args("send-creds"),
+                move || test_send_creds(sock_type),
+                all_envs.clone(),
+            ),
+        ]);
+    }
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use nix::errno::Errno;
+use nix::fcntl::{self, OFlag};
+use nix::sys::socket::{self, AddressFamily, MsgFlags, SockFlag, SockType, UnixAddr};
+use nix::sys::stat::Mode;
+use nix::unistd::{self, UnlinkatFlags};
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+fn unix_socket(sock_type: SockType) -> nix::Result<libc::c_int> {
+    socket::socket(AddressFamily::Unix, sock_type, SockFlag::empty(), None)
+}
+
+/// Bind a new socket to `path`.
+fn bind_path(sock_type: SockType, path: &str) -> nix::Result<libc::c_int> {
+    let fd = unix_socket(sock_type)?;
+    match socket::bind(fd, &UnixAddr::new(path)?) {
+        Ok(()) => Ok(fd),
+        Err(e) => {
+            unistd::close(fd)?;
+            Err(e)
+        }
+    }
+}
+
+fn test_stream_connect() -> anyhow::Result<()> {
+    let path = "unix-pathname-stream.sock";
+    let addr = UnixAddr::new(path)?;
+
+    let server = bind_path(SockType::Stream, path)?;
+    socket::listen(server, 10)?;
+
+    let client = unix_socket(SockType::Stream)?;
+    socket::connect(client, &addr)?;
+    let peer = socket::accept(server)?;
+
+    ensure_ord!(socket::send(client, b"hello", MsgFlags::empty()), ==, Ok(5));
+    let mut buf = [0u8; 10];
+    ensure_ord!(socket::recv(peer, &mut buf, MsgFlags::empty()), ==, Ok(5));
+    ensure_ord!(&buf[..5], ==, b"hello");
+
+    // the server's address is the path
+    let local: UnixAddr = socket::getsockname(server)?;
+    ensure_ord!(local.path(), ==, addr.path());
+    let remote: UnixAddr = socket::getpeername(client)?;
+    ensure_ord!(remote.path(), ==, addr.path());
+
+    for fd in [client, peer, server] {
+        unistd::close(fd)?;
+    }
+    unistd::unlink(path)?;
+
+    Ok(())
+}
+
+fn test_dgram_sendto() -> anyhow::Result<()> {
+    let path = "unix-pathname-dgram.sock";
+    let addr = UnixAddr::new(path)?;
+
+    let receiver = bind_path(SockType::Datagram, path)?;
+    let sender = unix_socket(SockType::Datagram)?;
+
+    ensure_ord!(
+        socket::sendto(sender, b"hello", &addr, MsgFlags::empty()),
+        ==,
+        Ok(5)
+    );
+    let mut buf = [0u8; 10];
+    ensure_ord!(socket::recv(receiver, &mut buf, MsgFlags::empty()), ==, Ok(5));
+    ensure_ord!(&buf[..5], ==, b"hello");
+
+    unistd::close(sender)?;
+    unistd::close(receiver)?;
+    unistd::unlink(path)?;
+
+    Ok(())
+}
+
+fn test_connect_missing() -> anyhow::Result<()> {
+    let client = unix_socket(SockType::Stream)?;
+    let addr = UnixAddr::new("unix-pathname-missing.sock")?;
+    ensure_ord!(socket::connect(client, &addr), ==, Err(Errno::ENOENT));
+    unistd::close(client)?;
+    Ok(())
+}
+
+fn test_bind_missing_dir() -> anyhow::Result<()> {
+    ensure_ord!(
+        bind_path(SockType::Stream, "unix-pathname-missing-dir/a.sock"),
+        ==,
+        Err(Errno::ENOENT)
+    );
+    Ok(())
+}
+
+fn test_bind_existing_file() -> anyhow::Result<()> {
+    let path = "unix-pathname-existing-file";
+    let fd = fcntl::open(
+        path,
+        OFlag::O_CREAT | OFlag::O_WRONLY,
+        Mode::from_bits_truncate(0o600),
+    )?;
+    unistd::close(fd)?;
+
+    let rv = bind_path(SockType::Stream, path);
+    unistd::unlink(path)?;
+    ensure_ord!(rv, ==, Err(Errno::EADDRINUSE));
+
+    Ok(())
+}
+
+fn test_unlink_and_rebind() -> anyhow::Result<()> {
+    let path = "unix-pathname-rebind.sock";
+    let addr = UnixAddr::new(path)?;
+
+    let server = bind_path(SockType::Stream, path)?;
+    socket::listen(server, 10)?;
+
+    // the name is in use
+    ensure_ord!(bind_path(SockType::Stream, path), ==, Err(Errno::EADDRINUSE));
+
+    // the name stays bound after the socket is closed, but nothing is listening
+    unistd::close(server)?;
+    ensure_ord!(bind_path(SockType::Stream, path), ==, Err(Errno::EADDRINUSE));
+    let client = unix_socket(SockType::Stream)?;
+    ensure_ord!(socket::connect(client, &addr), ==, Err(Errno::ECONNREFUSED));
+    unistd::close(client)?;
+
+    // after unlinking the name can be bound again
+    unistd::unlink(path)?;
+    ensure_ord!(unistd::unlink(path), ==, Err(Errno::ENOENT));
+    let server = bind_path(SockType::Stream, path)?;
+    socket::listen(server, 10)?;
+
+    let client = unix_socket(SockType::Stream)?;
+    ensure_ord!(socket::connect(client, &addr), ==, Ok(()));
+
+    unistd::close(client)?;
+    unistd::close(server)?;
+    unistd::unlink(path)?;
+
+    Ok(())
+}
+
+fn test_unlinkat_dirfd() -> anyhow::Result<()> {
+    let path = "unix-pathname-unlinkat.sock";
+
+    let dir = fcntl::open(".", OFlag::O_DIRECTORY | OFlag::O_RDONLY, Mode::empty())?;
+
+    let server = bind_path(SockType::Stream, path)?;
+    unistd::close(server)?;
+
+    // unlink the name relative to the directory
+    ensure_ord!(
+        unistd::unlinkat(Some(dir), path, UnlinkatFlags::NoRemoveDir),
+        ==,
+        Ok(())
+    );
+
+    let server = bind_path(SockType::Stream, path)?;
+    unistd::close(server)?;
+    unistd::unlink(path)?;
+
+    unistd::close(dir)?;
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("stream-connect", test_stream_connect, all_envs.clone()),
+        ShadowTest::new("dgram-sendto", test_dgram_sendto, all_envs.clone()),
+        ShadowTest::new("connect-missing", test_connect_missing, all_envs.clone()),
+        ShadowTest::new("bind-missing-dir", test_bind_missing_dir, all_envs.clone()),
+        ShadowTest::new(
+            "bind-existing-file",
+            test_bind_existing_file,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "unlink-and-rebind",
+            test_unlink_and_rebind,
+            all_envs.clone(),
+        ),
+        ShadowTest::new("unlinkat-dirfd", test_unlinkat_dirfd, all_envs.clone()),
+    ];
+
+    if filter_shadow_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Shadow))
+            .collect()
+    }
+    if filter_libc_passing {
+        tests = tests
+            .into_iter()
+            .filter(|x| x.passing(TestEnvironment::Libc))
+            .collect()
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+
+    Ok(())
+}